@@ -29,12 +29,18 @@ impl CompileTarget {
             unreachable!("")
         };
 
-        let defines = if profile == "cs_6_6" {
-            [("COMPUTE".to_string(), "".to_string())]
+        let stage = if profile == "cs_6_6" {
+            "COMPUTE"
         } else {
-            [("GRAPHICS".to_string(), "".to_string())]
+            "GRAPHICS"
         };
 
+        // ENTRY_<entry> lets a file keep each entry's bindings apart
+        let defines = [
+            (stage.to_string(), "".to_string()),
+            (format!("ENTRY_{entry}"), "".to_string()),
+        ];
+
         Self {
             file: file.to_string(),
             entry: entry.to_string(),
@@ -65,16 +71,83 @@ fn main() -> Result<()> {
 
     Compiler::new()?
         .compile(&CompileTarget::new("colorcloud.hlsl", "ColorCloudCs"))?
+        .compile(&CompileTarget::new("colorcloud.hlsl", "ColorCloudDecayCs"))?
+        .compile(&CompileTarget::new(
+            "colorcloud.hlsl",
+            "ColorCloudCompactCs",
+        ))?
         .compile(&CompileTarget::new("colorcloud.hlsl", "ColorCloudAs"))?
         .compile(&CompileTarget::new("colorcloud.hlsl", "ColorCloudMs"))?
+        .compile(&CompileTarget::new("colorcloud.hlsl", "ColorCloudVs"))?
         .compile(&CompileTarget::new("colorcloud.hlsl", "ColorCloudPs"))?
+        .compile(&CompileTarget::new("colorcloud.hlsl", "ColorCloudSplatPs"))?
+        .compile(&CompileTarget::new("colorcloud.hlsl", "ColorCloudOitPs"))?
+        .compile(&CompileTarget::new("colorcloud.hlsl", "ColorCloudDitherPs"))?
+        .compile(&CompileTarget::new(
+            "isosurface.hlsl",
+            "IsosurfaceDensityCs",
+        ))?
+        .compile(&CompileTarget::new("isosurface.hlsl", "IsosurfaceMeshCs"))?
+        .compile(&CompileTarget::new("isosurface.hlsl", "IsosurfaceArgsCs"))?
+        .compile(&CompileTarget::new("isosurface.hlsl", "IsosurfaceVs"))?
+        .compile(&CompileTarget::new("isosurface.hlsl", "IsosurfacePs"))?
+        .compile(&CompileTarget::new("volume.hlsl", "VolumeVs"))?
+        .compile(&CompileTarget::new("volume.hlsl", "VolumePs"))?
+        .compile(&CompileTarget::new("heatmap.hlsl", "HeatmapCs"))?
+        .compile(&CompileTarget::new("heatmap.hlsl", "HeatmapVs"))?
+        .compile(&CompileTarget::new("heatmap.hlsl", "HeatmapPs"))?
+        .compile(&CompileTarget::new("background.hlsl", "BackgroundVs"))?
+        .compile(&CompileTarget::new("background.hlsl", "BackgroundPs"))?
         .compile(&CompileTarget::new("filter.hlsl", "FilterVs"))?
         .compile(&CompileTarget::new("filter.hlsl", "FilterPs"))?
+        .compile(&CompileTarget::new("difference.hlsl", "DifferenceVs"))?
+        .compile(&CompileTarget::new(
+            "difference.hlsl",
+            "DifferenceCapturePs",
+        ))?
+        .compile(&CompileTarget::new("difference.hlsl", "DifferencePs"))?
+        .compile(&CompileTarget::new("cursor.hlsl", "CursorVs"))?
+        .compile(&CompileTarget::new("cursor.hlsl", "CursorPs"))?
+        .compile(&CompileTarget::new("still.hlsl", "StillVs"))?
+        .compile(&CompileTarget::new("still.hlsl", "StillPs"))?
         .compile(&CompileTarget::new("histogram.hlsl", "HistogramCs"))?
+        .compile(&CompileTarget::new("histogram.hlsl", "HistogramCullCs"))?
+        .compile(&CompileTarget::new("histogram.hlsl", "HistogramDeltaCs"))?
         .compile(&CompileTarget::new("histogram.hlsl", "HistogramVs"))?
         .compile(&CompileTarget::new("histogram.hlsl", "HistogramPs"))?
+        .compile(&CompileTarget::new("histogram.hlsl", "HistogramDeltaVs"))?
+        .compile(&CompileTarget::new("histogram.hlsl", "HistogramScrollCs"))?
+        .compile(&CompileTarget::new("histogram.hlsl", "HistogramHistoryVs"))?
+        .compile(&CompileTarget::new("histogram.hlsl", "HistogramHistoryPs"))?
         .compile(&CompileTarget::new("primitive.hlsl", "PrimitiveVs"))?
-        .compile(&CompileTarget::new("primitive.hlsl", "PrimitivePs"))?;
+        .compile(&CompileTarget::new("primitive.hlsl", "PrimitivePs"))?
+        .compile(&CompileTarget::new("vectorscope.hlsl", "VectorscopeCs"))?
+        .compile(&CompileTarget::new("vectorscope.hlsl", "VectorscopeVs"))?
+        .compile(&CompileTarget::new("vectorscope.hlsl", "VectorscopePs"))?
+        .compile(&CompileTarget::new("hueluma.hlsl", "HueLumaCs"))?
+        .compile(&CompileTarget::new("hueluma.hlsl", "HueLumaVs"))?
+        .compile(&CompileTarget::new("hueluma.hlsl", "HueLumaPs"))?
+        .compile(&CompileTarget::new("waveform.hlsl", "WaveformCs"))?
+        .compile(&CompileTarget::new("waveform.hlsl", "WaveformVs"))?
+        .compile(&CompileTarget::new("waveform.hlsl", "WaveformPs"))?
+        .compile(&CompileTarget::new("stats.hlsl", "StatsCs"))?
+        .compile(&CompileTarget::new("stats.hlsl", "StatsPercentileCs"))?
+        .compile(&CompileTarget::new("uniformity.hlsl", "UniformityCs"))?
+        .compile(&CompileTarget::new("uniformity.hlsl", "UniformityVs"))?
+        .compile(&CompileTarget::new("uniformity.hlsl", "UniformityPs"))?
+        .compile(&CompileTarget::new("contrast.hlsl", "ContrastCs"))?
+        .compile(&CompileTarget::new("contrast.hlsl", "ContrastVs"))?
+        .compile(&CompileTarget::new("contrast.hlsl", "ContrastPs"))?
+        .compile(&CompileTarget::new("text.hlsl", "TextVs"))?
+        .compile(&CompileTarget::new("text.hlsl", "TextPs"))?
+        .compile(&CompileTarget::new("oit.hlsl", "OitCompositeVs"))?
+        .compile(&CompileTarget::new("oit.hlsl", "OitCompositePs"))?
+        .compile(&CompileTarget::new("msaa.hlsl", "MsaaCompositeVs"))?
+        .compile(&CompileTarget::new("msaa.hlsl", "MsaaCompositePs"))?
+        .compile(&CompileTarget::new("msaa.hlsl", "MsaaDepthPs"))?
+        .compile(&CompileTarget::new("record.hlsl", "RecordVs"))?
+        .compile(&CompileTarget::new("record.hlsl", "RecordPs"))?
+        .compile(&CompileTarget::new("mips.hlsl", "GenerateMipsCs"))?;
 
     Ok(())
 }
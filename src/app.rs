@@ -1,229 +1,1713 @@
-use core::f32;
-use std::sync::{Arc, Mutex};
-
-use anyhow::Result;
-use windows::Win32::Foundation::{HWND, RECT};
-
-use crate::{
-    check, col,
-    config::*,
-    graphics::math::Matrix,
-    gui::{control::Builder, hwnd::Hwnd, utils::Rect as _},
-    radio, row, slider, space, text,
-    visualize::Visualizer,
-};
-
-const ID_ENABLE_FILTER: u32 = 0x0100;
-const ID_FILTER_RGB: u32 = 0x0101;
-const ID_FILTER_CH_R: u32 = 0x0111;
-const ID_FILTER_CH_G: u32 = 0x0112;
-const ID_FILTER_CH_B: u32 = 0x0113;
-const ID_FILTER_HUE: u32 = 0x0102;
-const ID_FILTER_SAT: u32 = 0x0103;
-const ID_FILTER_LUMA: u32 = 0x0104;
-const ID_ENABLE_HISTOGRAM: u32 = 0x0200;
-const ID_HISTOGRAM_RGB: u32 = 0x0201;
-const ID_HISTOGRAM_RGBL: u32 = 0x0202;
-const ID_HISTOGRAM_LUMA: u32 = 0x0203;
-const ID_HISTOGRAM_HUE: u32 = 0x0204;
-const ID_HISTOGRAM_SCALE: u32 = 0x0211;
-const ID_ENABLE_COLORCLOUD: u32 = 0x0300;
-const ID_COLORCLOUD_RGB: u32 = 0x0301;
-const ID_COLORCLOUD_HSL: u32 = 0x0302;
-const ID_COLORCLOUD_BG: u32 = 0x0311;
-const ID_COLORCLOUD_GRID: u32 = 0x0312;
-
-const CONFIG_PATH: &str = "colormel.ini";
-
-pub struct App {
-    hwnd: HWND,
-
-    config: Arc<Mutex<Config>>,
-
-    transparency: bool,
-
-    #[allow(unused)]
-    visualizer: Visualizer,
-}
-
-impl crate::gui::app::App for App {
-    fn new(hwnd: HWND) -> Result<Self> {
-        let config = Arc::new(Mutex::new(Config::load(CONFIG_PATH)));
-        let transparency = config
-            .lock()
-            .map_or(true, |config| !config.enable_color_cloud);
-
-        let visualizer = Visualizer::new(hwnd, Arc::clone(&config))?;
-
-        Ok(Self {
-            hwnd,
-            config,
-            transparency,
-            visualizer,
-        })
-    }
-
-    fn on_destroy(&mut self) -> Result<()> {
-        if let Ok(config) = self.config.lock() {
-            config.save(CONFIG_PATH);
-        }
-        Ok(())
-    }
-
-    fn on_pos_changed(&mut self, x: i32, y: i32, width: i32, height: i32) -> Result<()> {
-        if let Ok(mut config) = self.config.lock() {
-            config.window_rect = RECT::new(x, y, width, height);
-        }
-        Ok(())
-    }
-
-    fn on_button(&mut self, id: u32, checked: bool) {
-        let mut config = match self.config.lock() {
-            Ok(config) => config,
-            _ => return,
-        };
-
-        match id {
-            ID_ENABLE_FILTER => {
-                config.enable_filter = checked;
-            }
-            ID_FILTER_RGB => {
-                config.filter_mode = FILTER_MODE_RGB;
-            }
-            ID_FILTER_HUE => {
-                config.filter_mode = FILTER_MODE_HUE;
-            }
-            ID_FILTER_SAT => {
-                config.filter_mode = FILTER_MODE_SAT;
-            }
-            ID_FILTER_LUMA => {
-                config.filter_mode = FILTER_MODE_LUMA;
-            }
-            ID_FILTER_CH_R => {
-                config.filter_channels[0] = checked;
-            }
-            ID_FILTER_CH_G => {
-                config.filter_channels[1] = checked;
-            }
-            ID_FILTER_CH_B => {
-                config.filter_channels[2] = checked;
-            }
-            ID_ENABLE_HISTOGRAM => {
-                config.enable_histogram = checked;
-            }
-            ID_HISTOGRAM_RGB => {
-                config.histogram_mode = HISTOGRAM_MODE_RGB;
-            }
-            ID_HISTOGRAM_RGBL => {
-                config.histogram_mode = HISTOGRAM_MODE_RGBL;
-            }
-            ID_HISTOGRAM_LUMA => {
-                config.histogram_mode = HISTOGRAM_MODE_LUMA;
-            }
-            ID_HISTOGRAM_HUE => {
-                config.histogram_mode = HISTOGRAM_MODE_HUE;
-            }
-            ID_ENABLE_COLORCLOUD => {
-                config.enable_color_cloud = checked;
-                self.transparency = !config.enable_color_cloud;
-            }
-            ID_COLORCLOUD_RGB => {
-                config.color_cloud_mode = COLORCLOUD_MODE_RGB;
-            }
-            ID_COLORCLOUD_HSL => {
-                config.color_cloud_mode = COLORCLOUD_MODE_HSL;
-            }
-            ID_COLORCLOUD_GRID => {
-                config.show_grid = checked;
-            }
-            _ => {}
-        }
-    }
-
-    fn on_slider(&mut self, id: u32, val: i32) {
-        let mut config = match self.config.lock() {
-            Ok(config) => config,
-            _ => return,
-        };
-
-        match id {
-            ID_HISTOGRAM_SCALE => {
-                config.histogram_scale = val as f32 / 100.0;
-            }
-            ID_COLORCLOUD_BG => {
-                config.bg_opacity = val as f32 / 100.0;
-            }
-            _ => {}
-        }
-    }
-
-    fn on_drag(&mut self, dx: i32, dy: i32) {
-        let rect = self.hwnd.rect();
-        let div = rect.width().min(rect.height()) as f32;
-        let dx = dx as f32 / div;
-        let dy = dy as f32 / div;
-
-        if let Ok(mut config) = self.config.lock() {
-            let rot = Matrix::mul(
-                &Matrix::rot_y(f32::consts::PI * -dx),
-                &Matrix::rot_x(f32::consts::PI * -dy),
-            );
-            config.rotation = Matrix::mul(&config.rotation, &rot);
-        }
-    }
-
-    fn window_rect(&mut self) -> RECT {
-        if let Ok(config) = self.config.lock() {
-            config.window_rect
-        } else {
-            RECT::new(100, 100, 1280, 720)
-        }
-    }
-
-    fn transparency(&mut self) -> bool {
-        self.transparency
-    }
-
-    fn build_menu(&mut self, builder: &mut Builder) -> Result<()> {
-        let config = self.config.lock().unwrap().to_owned();
-
-        builder.build(col!(
-            check!(ID_ENABLE_FILTER, "Filter", config.enable_filter),
-            col!(
-                indent: 16,
-                radio!(ID_FILTER_RGB, "RGB", config.filter_mode == FILTER_MODE_RGB, ID_FILTER_RGB),
-                row!(
-                    indent: 12,
-                    check!(width: 36, ID_FILTER_CH_R, "R", true),
-                    check!(width: 36, ID_FILTER_CH_G, "G", true),
-                    check!(width: 36, ID_FILTER_CH_B, "B", true)
-                ),
-                radio!(ID_FILTER_HUE, "Hue", config.filter_mode == FILTER_MODE_HUE, ID_FILTER_RGB),
-                radio!(ID_FILTER_SAT, "Saturat", config.filter_mode == FILTER_MODE_SAT, ID_FILTER_RGB),
-                radio!(ID_FILTER_LUMA, "Luma", config.filter_mode == FILTER_MODE_LUMA, ID_FILTER_RGB)
-            ),
-            space!(8),
-            check!(ID_ENABLE_HISTOGRAM, "Histogram", config.enable_histogram),
-            col!(
-                indent: 16,
-                radio!(ID_HISTOGRAM_RGB, "RGB", config.histogram_mode == HISTOGRAM_MODE_RGB, ID_HISTOGRAM_RGB),
-                radio!(ID_HISTOGRAM_RGBL, "RGBL", config.histogram_mode == HISTOGRAM_MODE_RGBL, ID_HISTOGRAM_RGB),
-                radio!(ID_HISTOGRAM_LUMA, "Luma", config.histogram_mode == HISTOGRAM_MODE_LUMA, ID_HISTOGRAM_RGB),
-                radio!(ID_HISTOGRAM_HUE, "Hue", config.histogram_mode == HISTOGRAM_MODE_HUE, ID_HISTOGRAM_RGB),
-                text!(" Scale"),
-                slider!(ID_HISTOGRAM_SCALE, 0, 100, (100.0 * config.histogram_scale) as i32),
-            ),
-            space!(8),
-            check!(ID_ENABLE_COLORCLOUD, "Colod-Cloud", config.enable_color_cloud),
-            col!(
-                indent: 16,
-                radio!(ID_COLORCLOUD_RGB, "RGB", config.color_cloud_mode == COLORCLOUD_MODE_RGB, ID_COLORCLOUD_RGB),
-                radio!(ID_COLORCLOUD_HSL, "HSL", config.color_cloud_mode == COLORCLOUD_MODE_HSL, ID_COLORCLOUD_RGB),
-                check!(ID_COLORCLOUD_GRID, "Show Grid", config.show_grid),
-            ),
-            space!(8),
-            text!(" Transparency"),
-            slider!(ID_COLORCLOUD_BG, 0, 100, (100.0 * config.bg_opacity) as i32),
-        ))
-    }
-}
+use core::f32;
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use windows::Win32::Foundation::{HWND, RECT};
+
+use crate::{
+    button, check, col, combo,
+    config::*,
+    edit,
+    graphics::duplicate::output_rect,
+    gui::{
+        control::{Builder, Ctrl},
+        hwnd::Hwnd,
+        menu::MenuEdge,
+        utils::{self, Rect as _},
+    },
+    locale::{self, LANGUAGES},
+    number, radio, row, slider, space, text,
+    visualize::Visualizer,
+};
+
+const ID_PRESET_LIST: u32 = 0x0901;
+const ID_PRESET_SAVE: u32 = 0x0902;
+const ID_PRESET_DELETE: u32 = 0x0903;
+const ID_PRESET_NAME: u32 = 0x0921;
+const ID_ENABLE_FILTER: u32 = 0x0100;
+const ID_FILTER_RGB: u32 = 0x0101;
+const ID_FILTER_CH_R: u32 = 0x0111;
+const ID_FILTER_CH_G: u32 = 0x0112;
+const ID_FILTER_CH_B: u32 = 0x0113;
+const ID_FILTER_HUE: u32 = 0x0102;
+const ID_FILTER_SAT: u32 = 0x0103;
+const ID_FILTER_LUMA: u32 = 0x0104;
+const ID_FILTER_ZEBRA: u32 = 0x0105;
+const ID_ANALYZE_FILTERED: u32 = 0x0106;
+const ID_FILTER_MONO: u32 = 0x0107;
+const ID_FILTER_BLUE: u32 = 0x0108;
+const ID_FILTER_RANGE: u32 = 0x0109;
+const ID_ZEBRA_HIGH: u32 = 0x0121;
+const ID_ZEBRA_LOW: u32 = 0x0122;
+const ID_DESATURATION: u32 = 0x0123;
+const ID_ENABLE_HISTOGRAM: u32 = 0x0200;
+const ID_HISTOGRAM_RGB: u32 = 0x0201;
+const ID_HISTOGRAM_RGBL: u32 = 0x0202;
+const ID_HISTOGRAM_LUMA: u32 = 0x0203;
+const ID_HISTOGRAM_HUE: u32 = 0x0204;
+const ID_HISTOGRAM_SAT: u32 = 0x0205;
+const ID_HISTOGRAM_CHROMA: u32 = 0x0206;
+const ID_HISTOGRAM_SUGGEST_LEVELS: u32 = 0x0207;
+const ID_HISTOGRAM_PREVIEW_LEVELS: u32 = 0x0208;
+const ID_HISTOGRAM_DELTA: u32 = 0x0209;
+const ID_HISTOGRAM_AUTO_SCALE: u32 = 0x020A;
+const ID_HISTOGRAM_BINS_64: u32 = 0x020B;
+const ID_HISTOGRAM_BINS_128: u32 = 0x020C;
+const ID_HISTOGRAM_BINS_256: u32 = 0x020D;
+const ID_HISTOGRAM_BINS_512: u32 = 0x020E;
+const ID_HISTOGRAM_BINS_1024: u32 = 0x020F;
+const ID_HISTOGRAM_SCALE: u32 = 0x0211;
+const ID_HISTOGRAM_SIZE: u32 = 0x0212;
+const ID_HISTOGRAM_COLOR_R: u32 = 0x0213;
+const ID_HISTOGRAM_COLOR_G: u32 = 0x0214;
+const ID_HISTOGRAM_COLOR_B: u32 = 0x0215;
+const ID_HISTOGRAM_COLOR_L: u32 = 0x0216;
+const ID_HISTOGRAM_HISTORY: u32 = 0x0210;
+const ID_HISTOGRAM_EXPORT: u32 = 0x0221;
+// the layout radios of a scope are numbered from its anchor id in ANCHOR_* order
+const ID_HISTOGRAM_ANCHOR: u32 = 0x0230;
+const ID_HISTOGRAM_ANCHOR_LAST: u32 = ID_HISTOGRAM_ANCHOR + ANCHOR_BOTTOM_RIGHT;
+const ID_ENABLE_COLORCLOUD: u32 = 0x0300;
+const ID_COLORCLOUD_RGB: u32 = 0x0301;
+const ID_COLORCLOUD_HSL: u32 = 0x0302;
+const ID_COLORCLOUD_SOLID: u32 = 0x0303;
+const ID_COLORCLOUD_SPLAT: u32 = 0x0304;
+const ID_TRANSPARENCY_BLEND: u32 = 0x0305;
+const ID_TRANSPARENCY_DITHER: u32 = 0x0306;
+const ID_TRANSPARENCY_AUTO: u32 = 0x0307;
+const ID_COLORCLOUD_HSV: u32 = 0x0308;
+const ID_COLORCLOUD_YCBCR: u32 = 0x0309;
+const ID_COLORCLOUD_LAB: u32 = 0x030A;
+const ID_COLORCLOUD_OKLAB: u32 = 0x030B;
+const ID_COLORCLOUD_BG: u32 = 0x0311;
+const ID_COLORCLOUD_GRID: u32 = 0x0312;
+const ID_COLORCLOUD_INTERLEAVE: u32 = 0x0313;
+const ID_COLORCLOUD_FILL: u32 = 0x0314;
+const ID_COLORCLOUD_MARGIN: u32 = 0x0315;
+const ID_COLORCLOUD_OIT: u32 = 0x0316;
+const ID_COLORCLOUD_ISOSURFACE: u32 = 0x0317;
+const ID_COLORCLOUD_ISO_LEVEL: u32 = 0x0318;
+const ID_COLORCLOUD_VOLUME: u32 = 0x0319;
+const ID_COLORCLOUD_RAMP_LOW: u32 = 0x031A;
+const ID_COLORCLOUD_RAMP_HIGH: u32 = 0x031B;
+const ID_COLORCLOUD_HEATMAPS: u32 = 0x031C;
+const ID_COLORCLOUD_ACCUMULATE: u32 = 0x031D;
+const ID_COLORCLOUD_AGE: u32 = 0x031E;
+const ID_COLORCLOUD_PERSPECTIVE: u32 = 0x031F;
+const ID_COLORCLOUD_LABELS: u32 = 0x0320;
+const ID_COLORCLOUD_RESET_VIEW: u32 = 0x0321;
+const ID_COLORCLOUD_SIZE: u32 = 0x0322;
+const ID_COLORCLOUD_ANCHOR: u32 = 0x0330;
+const ID_COLORCLOUD_ANCHOR_LAST: u32 = ID_COLORCLOUD_ANCHOR + ANCHOR_BOTTOM_RIGHT;
+const ID_ENABLE_VECTORSCOPE: u32 = 0x0500;
+const ID_VECTORSCOPE_SCALE: u32 = 0x0511;
+const ID_VECTORSCOPE_FILL: u32 = 0x0501;
+const ID_VECTORSCOPE_MARGIN: u32 = 0x0512;
+const ID_VECTORSCOPE_SIZE: u32 = 0x0513;
+const ID_VECTORSCOPE_SKIN_TONE: u32 = 0x0502;
+const ID_VECTORSCOPE_ANCHOR: u32 = 0x0520;
+const ID_VECTORSCOPE_ANCHOR_LAST: u32 = ID_VECTORSCOPE_ANCHOR + ANCHOR_BOTTOM_RIGHT;
+const ID_ENABLE_HUE_LUMA: u32 = 0x0A00;
+const ID_ALERT_CLIPPING: u32 = 0x0B00;
+const ID_ALERT_CLIPPING_SOUND: u32 = 0x0B01;
+const ID_ALERT_CLIPPING_FLASH: u32 = 0x0B02;
+const ID_ALERT_CONTRAST: u32 = 0x0B03;
+const ID_ALERT_CONTRAST_SOUND: u32 = 0x0B04;
+const ID_ALERT_CONTRAST_FLASH: u32 = 0x0B05;
+const ID_ALERT_FLICKER: u32 = 0x0B06;
+const ID_ALERT_FLICKER_SOUND: u32 = 0x0B07;
+const ID_ALERT_FLICKER_FLASH: u32 = 0x0B08;
+const ID_HUE_LUMA_SCALE: u32 = 0x0A11;
+const ID_HUE_LUMA_SIZE: u32 = 0x0A12;
+const ID_HUE_LUMA_ANCHOR: u32 = 0x0A20;
+const ID_HUE_LUMA_ANCHOR_LAST: u32 = ID_HUE_LUMA_ANCHOR + ANCHOR_BOTTOM_RIGHT;
+const ID_ENABLE_WAVEFORM: u32 = 0x0800;
+const ID_WAVEFORM_COMPOSITE: u32 = 0x0801;
+const ID_WAVEFORM_PARADE: u32 = 0x0802;
+const ID_WAVEFORM_SCALE: u32 = 0x0811;
+const ID_WAVEFORM_SIZE: u32 = 0x0812;
+const ID_WAVEFORM_ANCHOR: u32 = 0x0820;
+const ID_WAVEFORM_ANCHOR_LAST: u32 = ID_WAVEFORM_ANCHOR + ANCHOR_BOTTOM_RIGHT;
+const ID_ENABLE_ROI: u32 = 0x0600;
+const ID_HDR_MODE: u32 = 0x0601;
+const ID_REJECT_OUTLIERS: u32 = 0x0602;
+const ID_COMPARE_ROI: u32 = 0x0603;
+const ID_ENABLE_REPORTS: u32 = 0x0604;
+const ID_COMPARE_OUTPUTS: u32 = 0x0605;
+const ID_ENABLE_EYEDROPPER: u32 = 0x0606;
+const ID_FREEZE: u32 = 0x0607;
+const ID_ENABLE_LUT: u32 = 0x0608;
+const ID_LOAD_LUT: u32 = 0x0609;
+const ID_CALIBRATE: u32 = 0x060A;
+const ID_ESTIMATE_GAMMA: u32 = 0x060B;
+const ID_ENABLE_UNIFORMITY: u32 = 0x060C;
+const ID_ENABLE_TEXT_CONTRAST: u32 = 0x060D;
+const ID_ENABLE_FRAME_PACING: u32 = 0x060E;
+const ID_ENABLE_SESSION: u32 = 0x060F;
+const ID_SESSION_THUMBNAILS: u32 = 0x0610;
+const ID_SAMPLE_FRAMES: u32 = 0x0611;
+const ID_REPORT_INTERVAL: u32 = 0x0612;
+const ID_UNIFORMITY_GRID: u32 = 0x0613;
+const ID_SESSION_BOOKMARK: u32 = 0x0614;
+const ID_SESSION_EXPORT: u32 = 0x0615;
+const ID_ENABLE_FRAME_DIFFERENCE: u32 = 0x0616;
+const ID_DIFFERENCE_HEATMAP: u32 = 0x0617;
+const ID_DIFFERENCE_THRESHOLD: u32 = 0x0618;
+const ID_DIFFERENCE_LEVEL: u32 = 0x0619;
+const ID_INCLUDE_CURSOR: u32 = 0x061A;
+const ID_SOURCE_DESKTOP: u32 = 0x061B;
+const ID_SOURCE_FILE: u32 = 0x061C;
+const ID_OPEN_IMAGE: u32 = 0x061D;
+const ID_SOURCE_VIDEO: u32 = 0x061E;
+const ID_OPEN_VIDEO: u32 = 0x061F;
+const ID_VIDEO_PLAY: u32 = 0x0620;
+const ID_ROI_X: u32 = 0x0621;
+const ID_ROI_Y: u32 = 0x0622;
+const ID_ROI_WIDTH: u32 = 0x0623;
+const ID_ROI_HEIGHT: u32 = 0x0624;
+const ID_COMPARE_OUTPUT: u32 = 0x0625;
+const ID_VIDEO_SEEK: u32 = 0x0626;
+const ID_SOURCE_SHARED: u32 = 0x0627;
+const ID_SHARED_NAME: u32 = 0x0628;
+const ID_RECORD: u32 = 0x0629;
+const ID_RECORD_SCOPES: u32 = 0x062A;
+const ID_RECORD_CAPTURE: u32 = 0x062B;
+const ID_SCREENSHOT_CAPTURE: u32 = 0x062C;
+const ID_SCREENSHOT_OUTPUT: u32 = 0x062D;
+const ID_PRIMARIES_BT601: u32 = 0x062E;
+const ID_PRIMARIES_BT709: u32 = 0x062F;
+const ID_PRIMARIES_BT2020: u32 = 0x0630;
+const ID_TRANSFER_SRGB: u32 = 0x0631;
+const ID_TRANSFER_BT709: u32 = 0x0632;
+const ID_TRANSFER_BT2020: u32 = 0x0633;
+const ID_COUNT_OUT_OF_RANGE: u32 = 0x0634;
+const ID_LINEAR_DOWNSAMPLE: u32 = 0x0635;
+const ID_ENABLE_DDC: u32 = 0x0700;
+const ID_DDC_BRIGHTNESS: u32 = 0x0711;
+const ID_DDC_CONTRAST: u32 = 0x0712;
+const ID_LOCK_WINDOW: u32 = 0x0400;
+const ID_FOLLOW_WINDOW: u32 = 0x0404;
+const ID_EXCLUDE_CAPTURE: u32 = 0x0405;
+const ID_PAUSE_OCCLUDED: u32 = 0x0406;
+const ID_SHARE_STATS: u32 = 0x0407;
+const ID_SHOW_LEGEND: u32 = 0x0408;
+const ID_MSAA_OFF: u32 = 0x0409;
+const ID_MSAA_2X: u32 = 0x040A;
+const ID_MSAA_4X: u32 = 0x040B;
+const ID_SHOW_DIAGNOSTICS: u32 = 0x040C;
+const ID_REUSE_FRAMES: u32 = 0x040D;
+const ID_SPLIT_SCREEN: u32 = 0x040E;
+const ID_LOG_OCCLUDED: u32 = 0x040F;
+const ID_MENU_EDGE_LEFT: u32 = 0x0401;
+const ID_MENU_EDGE_RIGHT: u32 = 0x0402;
+const ID_MENU_EDGE_TOP: u32 = 0x0403;
+const ID_MENU_ZONE: u32 = 0x0411;
+const ID_TARGET_FPS: u32 = 0x0412;
+const ID_CAPTURE_TIMEOUT: u32 = 0x0413;
+const ID_WINDOW_X: u32 = 0x0421;
+const ID_WINDOW_Y: u32 = 0x0422;
+const ID_WINDOW_WIDTH: u32 = 0x0423;
+const ID_WINDOW_HEIGHT: u32 = 0x0424;
+const ID_LANGUAGE_LIST: u32 = 0x0431;
+const ID_BACKGROUND_TRANSPARENT: u32 = 0x0441;
+const ID_BACKGROUND_SOLID: u32 = 0x0442;
+const ID_BACKGROUND_CHECKER: u32 = 0x0443;
+const ID_BACKGROUND_COLOR: u32 = 0x0444;
+const ID_CLEAR_COLOR: u32 = 0x0445;
+
+// virtual key codes, letters match their uppercase ascii
+const KEY_FREEZE: u16 = b'F' as u16;
+
+const CONFIG_PATH: &str = "colormel.toml";
+const PRESET_DIR: &str = "presets";
+
+const MENU_HINT_LIMIT: u32 = 5;
+
+// a drag that stops moving this long before the release doesn't keep spinning
+const DRAG_PAUSE: Duration = Duration::from_millis(50);
+
+pub struct App {
+    hwnd: HWND,
+
+    config: Arc<Mutex<Config>>,
+    // where the settings are saved on exit, none when the file didn't parse
+    config_path: Option<PathBuf>,
+    // the settings before the command line overrides, which are put back to these when saving
+    loaded: Config,
+
+    transparency: bool,
+
+    // name in the preset field, what save and delete act on
+    preset: String,
+    rebuild_menu: bool,
+
+    // when the drag last moved and its smoothed velocity (rad/s), flung on release
+    drag: Option<(Instant, [f32; 2])>,
+
+    #[allow(unused)]
+    visualizer: Visualizer,
+}
+
+impl crate::gui::app::App for App {
+    fn new(hwnd: HWND) -> Result<Self> {
+        let args = crate::args();
+        let config_path = args
+            .config
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(CONFIG_PATH));
+
+        // a config that doesn't parse is left alone for the user to fix, nothing is saved over it
+        let (mut config, config_path) = match Config::load(&config_path) {
+            Ok(config) => (config, Some(config_path)),
+            Err(e) => {
+                utils::error_box(&format!(
+                    "{e:#}\n\nthe settings won't be saved this session"
+                ));
+                (Config::load("")?, None)
+            }
+        };
+        let loaded = config.clone();
+        args.apply(&mut config);
+
+        // --monitor without --pos brings the window along, centered on that output
+        if let (Some(index), None) = (args.monitor, args.pos) {
+            match output_rect(index) {
+                Ok(output) => {
+                    let width = config.window_rect.width().min(output.width());
+                    let height = config.window_rect.height().min(output.height());
+                    config.window_rect = RECT::new(
+                        output.left + (output.width() - width) / 2,
+                        output.top + (output.height() - height) / 2,
+                        width,
+                        height,
+                    );
+                }
+                Err(e) => utils::error_box(&format!("there is no monitor {index}\n\n{e}")),
+            }
+        }
+        let config = Arc::new(Mutex::new(config));
+        let transparency = config
+            .lock()
+            .map_or(true, |config| !config.enable_color_cloud);
+
+        let visualizer = Visualizer::new(hwnd, Arc::clone(&config))?;
+
+        Ok(Self {
+            hwnd,
+            config,
+            config_path,
+            loaded,
+            transparency,
+            preset: String::new(),
+            rebuild_menu: false,
+            drag: None,
+            visualizer,
+        })
+    }
+
+    fn on_destroy(&mut self) -> Result<()> {
+        if let (Ok(config), Some(path)) = (self.config.lock(), &self.config_path) {
+            let mut config = config.clone();
+            crate::args().restore(&self.loaded, &mut config);
+            config.save(path);
+        }
+        Ok(())
+    }
+
+    fn on_pos_changed(&mut self, x: i32, y: i32, width: i32, height: i32) -> Result<()> {
+        if let Ok(mut config) = self.config.lock() {
+            config.window_rect = RECT::new(x, y, width, height);
+        }
+        Ok(())
+    }
+
+    fn on_button(&mut self, id: u32, checked: bool) {
+        // the dialogs are modal, so they run before the config is locked
+        let lut_path = match id {
+            ID_LOAD_LUT => utils::open_file_dialog(self.hwnd, ("Cube LUT", "*.cube")),
+            _ => None,
+        };
+        let current = self
+            .config
+            .lock()
+            .ok()
+            .and_then(|config| config.source_path.clone());
+        let reopen = |source| {
+            current
+                .as_deref()
+                .is_some_and(|path| source_for(path) == source)
+        };
+        // the radios go back to the last file of their kind without asking
+        let (reopened, source_path) = match id {
+            ID_SOURCE_FILE if reopen(SOURCE_FILE) => (Some(SOURCE_FILE), None),
+            ID_SOURCE_VIDEO if reopen(SOURCE_VIDEO) => (Some(SOURCE_VIDEO), None),
+            ID_SOURCE_FILE | ID_OPEN_IMAGE => (
+                None,
+                utils::open_file_dialog(
+                    self.hwnd,
+                    ("Images", "*.png;*.jpg;*.jpeg;*.bmp;*.tif;*.tiff;*.exr"),
+                ),
+            ),
+            ID_SOURCE_VIDEO | ID_OPEN_VIDEO => (
+                None,
+                utils::open_file_dialog(
+                    self.hwnd,
+                    ("Videos", "*.mp4;*.m4v;*.mov;*.mkv;*.avi;*.wmv;*.webm"),
+                ),
+            ),
+            _ => (None, None),
+        };
+        let picked_color = match id {
+            ID_BACKGROUND_COLOR | ID_CLEAR_COLOR | ID_HISTOGRAM_COLOR_R..=ID_HISTOGRAM_COLOR_L => {
+                let initial = self.config.lock().map_or(0, |config| match id {
+                    ID_BACKGROUND_COLOR => config.bg_color,
+                    ID_CLEAR_COLOR => config.clear_color,
+                    _ => config.histogram_colors[(id - ID_HISTOGRAM_COLOR_R) as usize],
+                });
+                utils::choose_color(self.hwnd, initial)
+            }
+            _ => None,
+        };
+
+        let mut config = match self.config.lock() {
+            Ok(config) => config,
+            _ => return,
+        };
+
+        match id {
+            // rebuilt so the list shows the new preset
+            ID_PRESET_SAVE => match config.save_preset(PRESET_DIR, &self.preset) {
+                Ok(_) => self.rebuild_menu = true,
+                Err(e) => println!("{e:?}"),
+            },
+            ID_PRESET_DELETE => match delete_preset(PRESET_DIR, &self.preset) {
+                Ok(_) => {
+                    self.preset.clear();
+                    self.rebuild_menu = true;
+                }
+                Err(e) => println!("{e:?}"),
+            },
+            ID_ENABLE_FILTER => {
+                config.enable_filter = checked;
+            }
+            ID_FILTER_RGB => {
+                config.filter_mode = FILTER_MODE_RGB;
+            }
+            ID_FILTER_HUE => {
+                config.filter_mode = FILTER_MODE_HUE;
+            }
+            ID_FILTER_SAT => {
+                config.filter_mode = FILTER_MODE_SAT;
+            }
+            ID_FILTER_LUMA => {
+                config.filter_mode = FILTER_MODE_LUMA;
+            }
+            ID_FILTER_ZEBRA => {
+                config.filter_mode = FILTER_MODE_ZEBRA;
+            }
+            ID_FILTER_MONO => {
+                config.filter_mode = FILTER_MODE_MONO;
+            }
+            ID_FILTER_BLUE => {
+                config.filter_mode = FILTER_MODE_BLUE;
+            }
+            ID_FILTER_RANGE => {
+                config.filter_mode = FILTER_MODE_RANGE;
+            }
+            ID_ANALYZE_FILTERED => {
+                config.analyze_filtered = checked;
+            }
+            ID_FILTER_CH_R => {
+                config.filter_channels[0] = checked;
+                config.filter_solo = false;
+            }
+            ID_FILTER_CH_G => {
+                config.filter_channels[1] = checked;
+                config.filter_solo = false;
+            }
+            ID_FILTER_CH_B => {
+                config.filter_channels[2] = checked;
+                config.filter_solo = false;
+            }
+            ID_ENABLE_HISTOGRAM => {
+                config.enable_histogram = checked;
+            }
+            ID_HISTOGRAM_RGB => {
+                config.histogram_mode = HISTOGRAM_MODE_RGB;
+            }
+            ID_HISTOGRAM_RGBL => {
+                config.histogram_mode = HISTOGRAM_MODE_RGBL;
+            }
+            ID_HISTOGRAM_LUMA => {
+                config.histogram_mode = HISTOGRAM_MODE_LUMA;
+            }
+            ID_HISTOGRAM_HUE => {
+                config.histogram_mode = HISTOGRAM_MODE_HUE;
+            }
+            ID_HISTOGRAM_SAT => {
+                config.histogram_mode = HISTOGRAM_MODE_SAT;
+            }
+            ID_HISTOGRAM_CHROMA => {
+                config.histogram_mode = HISTOGRAM_MODE_CHROMA;
+            }
+            ID_HISTOGRAM_BINS_64 => {
+                config.histogram_bins = 64;
+            }
+            ID_HISTOGRAM_BINS_128 => {
+                config.histogram_bins = 128;
+            }
+            ID_HISTOGRAM_BINS_256 => {
+                config.histogram_bins = 256;
+            }
+            ID_HISTOGRAM_BINS_512 => {
+                config.histogram_bins = 512;
+            }
+            ID_HISTOGRAM_BINS_1024 => {
+                config.histogram_bins = 1024;
+            }
+            ID_HISTOGRAM_AUTO_SCALE => {
+                config.histogram_auto_scale = checked;
+            }
+            ID_HISTOGRAM_DELTA => {
+                config.histogram_delta = checked;
+            }
+            ID_HISTOGRAM_HISTORY => {
+                config.histogram_history = checked;
+            }
+            ID_HISTOGRAM_SUGGEST_LEVELS => {
+                config.suggest_levels = checked;
+            }
+            ID_HISTOGRAM_PREVIEW_LEVELS => {
+                config.preview_levels = checked;
+            }
+            ID_HISTOGRAM_EXPORT => {
+                config.export_request = config.export_request.wrapping_add(1);
+            }
+            ID_HISTOGRAM_ANCHOR..=ID_HISTOGRAM_ANCHOR_LAST => {
+                config.layouts[LAYOUT_HISTOGRAM].anchor = id - ID_HISTOGRAM_ANCHOR;
+            }
+            ID_ENABLE_COLORCLOUD => {
+                config.enable_color_cloud = checked;
+                self.transparency = !config.enable_color_cloud;
+            }
+            ID_COLORCLOUD_RGB => {
+                config.color_cloud_mode = COLORCLOUD_MODE_RGB;
+            }
+            ID_COLORCLOUD_HSL => {
+                config.color_cloud_mode = COLORCLOUD_MODE_HSL;
+            }
+            ID_COLORCLOUD_HSV => {
+                config.color_cloud_mode = COLORCLOUD_MODE_HSV;
+            }
+            ID_COLORCLOUD_YCBCR => {
+                config.color_cloud_mode = COLORCLOUD_MODE_YCBCR;
+            }
+            ID_COLORCLOUD_LAB => {
+                config.color_cloud_mode = COLORCLOUD_MODE_LAB;
+            }
+            ID_COLORCLOUD_OKLAB => {
+                config.color_cloud_mode = COLORCLOUD_MODE_OKLAB;
+            }
+            ID_COLORCLOUD_SOLID => {
+                config.color_cloud_style = COLORCLOUD_STYLE_SOLID;
+            }
+            ID_COLORCLOUD_SPLAT => {
+                config.color_cloud_style = COLORCLOUD_STYLE_SPLAT;
+            }
+            ID_COLORCLOUD_VOLUME => {
+                config.color_cloud_style = COLORCLOUD_STYLE_VOLUME;
+            }
+            ID_COLORCLOUD_OIT => {
+                config.color_cloud_oit = checked;
+            }
+            ID_COLORCLOUD_ISOSURFACE => {
+                config.color_cloud_isosurface = checked;
+            }
+            ID_COLORCLOUD_HEATMAPS => {
+                config.color_cloud_heatmaps = checked;
+            }
+            ID_COLORCLOUD_ACCUMULATE => {
+                config.color_cloud_accumulate = checked;
+            }
+            ID_COLORCLOUD_AGE => {
+                config.color_cloud_age = checked;
+            }
+            ID_COLORCLOUD_PERSPECTIVE => {
+                config.camera.perspective = checked;
+            }
+            ID_COLORCLOUD_RESET_VIEW => {
+                config.camera.reset();
+            }
+            ID_COLORCLOUD_ANCHOR..=ID_COLORCLOUD_ANCHOR_LAST => {
+                config.layouts[LAYOUT_COLOR_CLOUD].anchor = id - ID_COLORCLOUD_ANCHOR;
+            }
+            ID_HUE_LUMA_ANCHOR..=ID_HUE_LUMA_ANCHOR_LAST => {
+                config.layouts[LAYOUT_HUE_LUMA].anchor = id - ID_HUE_LUMA_ANCHOR;
+            }
+            ID_TRANSPARENCY_BLEND => {
+                config.transparency_mode = TRANSPARENCY_MODE_BLEND;
+            }
+            ID_TRANSPARENCY_DITHER => {
+                config.transparency_mode = TRANSPARENCY_MODE_DITHER;
+            }
+            ID_TRANSPARENCY_AUTO => {
+                config.transparency_mode = TRANSPARENCY_MODE_AUTO;
+            }
+            ID_COLORCLOUD_GRID => {
+                config.show_grid = checked;
+            }
+            ID_COLORCLOUD_LABELS => {
+                config.grid_labels = checked;
+            }
+            ID_COLORCLOUD_INTERLEAVE => {
+                config.color_cloud_interleave = checked;
+            }
+            ID_COLORCLOUD_FILL => {
+                config.color_cloud_fill = checked;
+            }
+            ID_ENABLE_VECTORSCOPE => {
+                config.enable_vectorscope = checked;
+            }
+            ID_VECTORSCOPE_FILL => {
+                config.vectorscope_fill = checked;
+            }
+            ID_VECTORSCOPE_SKIN_TONE => {
+                config.vectorscope_skin_tone = checked;
+            }
+            ID_VECTORSCOPE_ANCHOR..=ID_VECTORSCOPE_ANCHOR_LAST => {
+                config.layouts[LAYOUT_VECTORSCOPE].anchor = id - ID_VECTORSCOPE_ANCHOR;
+            }
+            ID_ENABLE_HUE_LUMA => {
+                config.enable_hue_luma = checked;
+            }
+            ID_ENABLE_WAVEFORM => {
+                config.enable_waveform = checked;
+            }
+            ID_WAVEFORM_COMPOSITE => {
+                config.waveform_layout = WAVEFORM_LAYOUT_COMPOSITE;
+            }
+            ID_WAVEFORM_PARADE => {
+                config.waveform_layout = WAVEFORM_LAYOUT_PARADE;
+            }
+            ID_WAVEFORM_ANCHOR..=ID_WAVEFORM_ANCHOR_LAST => {
+                config.layouts[LAYOUT_WAVEFORM].anchor = id - ID_WAVEFORM_ANCHOR;
+            }
+            ID_ENABLE_ROI => {
+                config.enable_roi = checked;
+            }
+            ID_HDR_MODE => {
+                config.hdr_mode = checked;
+            }
+            ID_COMPARE_ROI => {
+                config.compare_roi = checked;
+            }
+            ID_ENABLE_LUT => {
+                config.enable_lut = checked;
+            }
+            ID_LOAD_LUT => {
+                if lut_path.is_some() {
+                    config.lut_path = lut_path;
+                }
+            }
+            ID_FREEZE => {
+                config.freeze = checked;
+            }
+            ID_INCLUDE_CURSOR => {
+                config.include_cursor = checked;
+            }
+            ID_LINEAR_DOWNSAMPLE => {
+                config.linear_downsample = checked;
+            }
+            ID_SOURCE_DESKTOP => {
+                config.capture_source = SOURCE_DESKTOP;
+            }
+            ID_SOURCE_SHARED => {
+                config.capture_source = SOURCE_SHARED;
+            }
+            // a canceled dialog leaves the previous source checked
+            ID_SOURCE_FILE | ID_OPEN_IMAGE | ID_SOURCE_VIDEO | ID_OPEN_VIDEO => {
+                if let Some(path) = source_path {
+                    config.capture_source = source_for(&path);
+                    config.source_path = Some(path);
+                    config.video_playing = true;
+                } else if let Some(source) = reopened {
+                    config.capture_source = source;
+                }
+                self.rebuild_menu = true;
+            }
+            ID_VIDEO_PLAY => {
+                config.video_playing = checked;
+            }
+            ID_ENABLE_EYEDROPPER => {
+                config.enable_eyedropper = checked;
+                config.pick_point = None;
+            }
+            ID_COMPARE_OUTPUTS => {
+                config.compare_outputs = checked;
+            }
+            ID_REJECT_OUTLIERS => {
+                config.reject_outliers = checked;
+            }
+            ID_ESTIMATE_GAMMA => {
+                config.estimate_gamma = checked;
+            }
+            ID_COUNT_OUT_OF_RANGE => {
+                config.count_out_of_range = checked;
+            }
+            ID_ENABLE_UNIFORMITY => {
+                config.enable_uniformity = checked;
+            }
+            ID_ENABLE_TEXT_CONTRAST => {
+                config.enable_text_contrast = checked;
+            }
+            ID_ENABLE_FRAME_PACING => {
+                config.enable_frame_pacing = checked;
+            }
+            ID_ENABLE_FRAME_DIFFERENCE => {
+                config.enable_frame_difference = checked;
+            }
+            ID_DIFFERENCE_HEATMAP => {
+                config.frame_difference_mode = DIFFERENCE_HEATMAP;
+            }
+            ID_DIFFERENCE_THRESHOLD => {
+                config.frame_difference_mode = DIFFERENCE_THRESHOLD;
+            }
+            ID_CALIBRATE => {
+                config.calibrate_request = config.calibrate_request.wrapping_add(1);
+            }
+            ID_ENABLE_REPORTS => {
+                config.enable_reports = checked;
+            }
+            ID_ENABLE_SESSION => {
+                config.enable_session = checked;
+            }
+            ID_SESSION_THUMBNAILS => {
+                config.session_thumbnails = checked;
+            }
+            ID_SESSION_BOOKMARK => {
+                config.bookmark_request = config.bookmark_request.wrapping_add(1);
+            }
+            ID_SESSION_EXPORT => {
+                config.session_export_request = config.session_export_request.wrapping_add(1);
+            }
+            ID_SCREENSHOT_CAPTURE => {
+                config.capture_screenshot_request =
+                    config.capture_screenshot_request.wrapping_add(1);
+            }
+            ID_SCREENSHOT_OUTPUT => {
+                config.output_screenshot_request = config.output_screenshot_request.wrapping_add(1);
+            }
+            ID_RECORD => {
+                config.recording = checked;
+            }
+            ID_RECORD_SCOPES => {
+                config.record_source = RECORD_SCOPES;
+            }
+            ID_RECORD_CAPTURE => {
+                config.record_source = RECORD_CAPTURE;
+            }
+            ID_ENABLE_DDC => {
+                config.enable_ddc = checked;
+                // the worker stops, so the readings go stale
+                config.ddc_readings = [-1, -1];
+            }
+            ID_ALERT_CLIPPING => {
+                config.alerts[ALERT_CLIPPING].enable = checked;
+            }
+            ID_ALERT_CLIPPING_SOUND => {
+                config.alerts[ALERT_CLIPPING].sound = checked;
+            }
+            ID_ALERT_CLIPPING_FLASH => {
+                config.alerts[ALERT_CLIPPING].flash = checked;
+            }
+            ID_ALERT_CONTRAST => {
+                config.alerts[ALERT_LOW_CONTRAST].enable = checked;
+            }
+            ID_ALERT_CONTRAST_SOUND => {
+                config.alerts[ALERT_LOW_CONTRAST].sound = checked;
+            }
+            ID_ALERT_CONTRAST_FLASH => {
+                config.alerts[ALERT_LOW_CONTRAST].flash = checked;
+            }
+            ID_ALERT_FLICKER => {
+                config.alerts[ALERT_FLICKER].enable = checked;
+            }
+            ID_ALERT_FLICKER_SOUND => {
+                config.alerts[ALERT_FLICKER].sound = checked;
+            }
+            ID_ALERT_FLICKER_FLASH => {
+                config.alerts[ALERT_FLICKER].flash = checked;
+            }
+            ID_LOCK_WINDOW => {
+                config.lock_window = checked;
+            }
+            ID_FOLLOW_WINDOW => {
+                config.follow_window = checked;
+            }
+            ID_EXCLUDE_CAPTURE => {
+                config.exclude_from_capture = checked;
+            }
+            ID_PAUSE_OCCLUDED => {
+                config.pause_when_occluded = checked;
+            }
+            ID_LOG_OCCLUDED => {
+                config.log_when_occluded = checked;
+            }
+            ID_REUSE_FRAMES => {
+                config.reuse_frames = checked;
+            }
+            ID_SHARE_STATS => {
+                config.share_stats = checked;
+            }
+            ID_SHOW_LEGEND => {
+                config.show_legend = checked;
+            }
+            ID_SPLIT_SCREEN => {
+                config.split_screen = checked;
+            }
+            ID_SHOW_DIAGNOSTICS => {
+                config.show_diagnostics = checked;
+            }
+            ID_MSAA_OFF => {
+                config.msaa_samples = 1;
+            }
+            ID_BACKGROUND_TRANSPARENT => {
+                config.bg_mode = BACKGROUND_TRANSPARENT;
+            }
+            ID_BACKGROUND_SOLID => {
+                config.bg_mode = BACKGROUND_SOLID;
+            }
+            ID_PRIMARIES_BT601 => {
+                config.source_primaries = PRIMARIES_BT601;
+            }
+            ID_PRIMARIES_BT709 => {
+                config.source_primaries = PRIMARIES_BT709;
+            }
+            ID_PRIMARIES_BT2020 => {
+                config.source_primaries = PRIMARIES_BT2020;
+            }
+            ID_TRANSFER_SRGB => {
+                config.source_transfer = TRANSFER_SRGB;
+            }
+            ID_TRANSFER_BT709 => {
+                config.source_transfer = TRANSFER_BT709;
+            }
+            ID_TRANSFER_BT2020 => {
+                config.source_transfer = TRANSFER_BT2020;
+            }
+            ID_BACKGROUND_CHECKER => {
+                config.bg_mode = BACKGROUND_CHECKER;
+            }
+            // a picked color shows right away
+            ID_BACKGROUND_COLOR => {
+                if let Some(color) = picked_color {
+                    config.bg_color = color;
+                    config.bg_mode = BACKGROUND_SOLID;
+                    self.rebuild_menu = true;
+                }
+            }
+            ID_CLEAR_COLOR => {
+                if let Some(color) = picked_color {
+                    config.clear_color = color;
+                }
+            }
+            ID_HISTOGRAM_COLOR_R..=ID_HISTOGRAM_COLOR_L => {
+                if let Some(color) = picked_color {
+                    config.histogram_colors[(id - ID_HISTOGRAM_COLOR_R) as usize] = color;
+                }
+            }
+            ID_MSAA_2X => {
+                config.msaa_samples = 2;
+            }
+            ID_MSAA_4X => {
+                config.msaa_samples = 4;
+            }
+            ID_MENU_EDGE_LEFT => {
+                config.menu_edge = MENU_EDGE_LEFT;
+            }
+            ID_MENU_EDGE_RIGHT => {
+                config.menu_edge = MENU_EDGE_RIGHT;
+            }
+            ID_MENU_EDGE_TOP => {
+                config.menu_edge = MENU_EDGE_TOP;
+            }
+            _ => {}
+        }
+    }
+
+    // alt+click on a channel shows it alone in gray, again on the same channel brings the others back
+    fn on_alt_button(&mut self, id: u32) -> bool {
+        let channel = match id {
+            ID_FILTER_CH_R => 0,
+            ID_FILTER_CH_G => 1,
+            ID_FILTER_CH_B => 2,
+            _ => return false,
+        };
+
+        let Ok(mut config) = self.config.lock() else {
+            return false;
+        };
+
+        if config.filter_solo && config.filter_channels[channel] {
+            config.filter_channels[..3].fill(true);
+            config.filter_solo = false;
+        } else {
+            config.filter_channels[..3].fill(false);
+            config.filter_channels[channel] = true;
+            config.filter_solo = true;
+            config.filter_mode = FILTER_MODE_RGB;
+            config.enable_filter = true;
+        }
+
+        // the clicked box toggled itself, the rebuild shows the channels as they are
+        self.rebuild_menu = true;
+        true
+    }
+
+    fn on_drop_file(&mut self, path: &Path) {
+        if let Ok(mut config) = self.config.lock() {
+            config.source_path = Some(path.to_path_buf());
+            config.capture_source = source_for(path);
+            config.video_playing = true;
+            self.rebuild_menu = true;
+        }
+    }
+
+    fn on_slider(&mut self, id: u32, val: i32) {
+        let mut config = match self.config.lock() {
+            Ok(config) => config,
+            _ => return,
+        };
+
+        match id {
+            ID_ZEBRA_HIGH => {
+                config.zebra_high = val as f32 / 100.0;
+            }
+            ID_ZEBRA_LOW => {
+                config.zebra_low = val as f32 / 100.0;
+            }
+            ID_DESATURATION => {
+                config.desaturation = val as f32 / 100.0;
+            }
+            ID_VIDEO_SEEK => {
+                config.video_seek = val as f32 / 1000.0;
+                config.video_seek_request = config.video_seek_request.wrapping_add(1);
+            }
+            ID_DIFFERENCE_LEVEL => {
+                config.frame_difference_threshold = val as f32 / 100.0;
+            }
+            ID_HISTOGRAM_SCALE => {
+                config.histogram_scale = val as f32 / 100.0;
+            }
+            ID_VECTORSCOPE_SCALE => {
+                config.vectorscope_scale = val as f32 / 100.0;
+            }
+            ID_VECTORSCOPE_MARGIN => {
+                config.vectorscope_margin = val.max(0) as u32;
+            }
+            ID_HISTOGRAM_SIZE => {
+                config.layouts[LAYOUT_HISTOGRAM].size = val as f32 / 100.0;
+            }
+            ID_VECTORSCOPE_SIZE => {
+                config.layouts[LAYOUT_VECTORSCOPE].size = val as f32 / 100.0;
+            }
+            ID_WAVEFORM_SIZE => {
+                config.layouts[LAYOUT_WAVEFORM].size = val as f32 / 100.0;
+            }
+            ID_HUE_LUMA_SCALE => {
+                config.hue_luma_scale = val as f32 / 100.0;
+            }
+            ID_HUE_LUMA_SIZE => {
+                config.layouts[LAYOUT_HUE_LUMA].size = val as f32 / 100.0;
+            }
+            ID_COLORCLOUD_SIZE => {
+                config.layouts[LAYOUT_COLOR_CLOUD].size = val as f32 / 100.0;
+            }
+            ID_COLORCLOUD_MARGIN => {
+                config.color_cloud_margin = val.max(0) as u32;
+            }
+            ID_COLORCLOUD_ISO_LEVEL => {
+                config.color_cloud_iso_level = val as f32 / 100.0;
+            }
+            ID_COLORCLOUD_RAMP_LOW => {
+                config.color_cloud_ramp[0] = val as f32 / 100.0;
+            }
+            ID_COLORCLOUD_RAMP_HIGH => {
+                config.color_cloud_ramp[1] = val as f32 / 100.0;
+            }
+            ID_WAVEFORM_SCALE => {
+                config.waveform_scale = val as f32 / 100.0;
+            }
+            ID_SAMPLE_FRAMES => {
+                config.sample_frames = val.max(1) as u32;
+            }
+            ID_REPORT_INTERVAL => {
+                config.report_interval = val.max(1) as u32;
+            }
+            ID_UNIFORMITY_GRID => {
+                config.uniformity_grid = val.max(1) as u32;
+            }
+            ID_DDC_BRIGHTNESS => {
+                config.ddc_brightness = val;
+            }
+            ID_DDC_CONTRAST => {
+                config.ddc_contrast = val;
+            }
+            ID_COLORCLOUD_BG => {
+                config.bg_opacity = val as f32 / 100.0;
+            }
+            ID_MENU_ZONE => {
+                config.menu_zone = val;
+            }
+            ID_TARGET_FPS => {
+                config.target_fps = val.max(0) as u32;
+            }
+            ID_CAPTURE_TIMEOUT => {
+                config.capture_timeout = val.max(0) as u32;
+            }
+            _ => {}
+        }
+    }
+
+    fn on_number(&mut self, id: u32, val: i32) {
+        let mut config = match self.config.lock() {
+            Ok(config) => config,
+            _ => return,
+        };
+
+        let RECT { left, top, .. } = config.window_rect;
+        let (width, height) = config.window_rect.size();
+        let RECT {
+            left: roi_x,
+            top: roi_y,
+            ..
+        } = config.roi;
+        let (roi_width, roi_height) = config.roi.size();
+
+        match id {
+            ID_WINDOW_X => config.window_rect = RECT::new(val, top, width, height),
+            ID_WINDOW_Y => config.window_rect = RECT::new(left, val, width, height),
+            ID_WINDOW_WIDTH => config.window_rect = RECT::new(left, top, val.max(1), height),
+            ID_WINDOW_HEIGHT => config.window_rect = RECT::new(left, top, width, val.max(1)),
+            ID_ROI_X => config.roi = RECT::new(val.max(0), roi_y, roi_width, roi_height),
+            ID_ROI_Y => config.roi = RECT::new(roi_x, val.max(0), roi_width, roi_height),
+            ID_ROI_WIDTH => config.roi = RECT::new(roi_x, roi_y, val.max(1), roi_height),
+            ID_ROI_HEIGHT => config.roi = RECT::new(roi_x, roi_y, roi_width, val.max(1)),
+            ID_COMPARE_OUTPUT => config.compare_output = val.max(0) as u32,
+            _ => {}
+        }
+    }
+
+    fn number_fields(&mut self) -> Vec<(u32, i32)> {
+        let (rect, roi) = match self.config.lock() {
+            Ok(config) => (config.window_rect, config.roi),
+            _ => return vec![],
+        };
+
+        vec![
+            (ID_WINDOW_X, rect.left),
+            (ID_WINDOW_Y, rect.top),
+            (ID_WINDOW_WIDTH, rect.width()),
+            (ID_WINDOW_HEIGHT, rect.height()),
+            (ID_ROI_X, roi.left),
+            (ID_ROI_Y, roi.top),
+            (ID_ROI_WIDTH, roi.width()),
+            (ID_ROI_HEIGHT, roi.height()),
+        ]
+    }
+
+    fn on_monitor_readings(&mut self, brightness: i32, contrast: i32) -> Vec<(u32, i32)> {
+        let Ok(mut config) = self.config.lock() else {
+            return vec![];
+        };
+        config.ddc_readings = [brightness, contrast];
+
+        [(ID_DDC_BRIGHTNESS, brightness), (ID_DDC_CONTRAST, contrast)]
+            .into_iter()
+            .filter(|&(_, val)| val >= 0)
+            .collect()
+    }
+
+    fn on_video_position(&mut self, permille: i32) -> Vec<(u32, i32)> {
+        let Ok(mut config) = self.config.lock() else {
+            return vec![];
+        };
+        // no seek request, the player is already there
+        config.video_seek = permille as f32 / 1000.0;
+
+        vec![(ID_VIDEO_SEEK, permille)]
+    }
+
+    fn on_text(&mut self, id: u32, text: &str) {
+        if id == ID_PRESET_NAME {
+            self.preset = text.trim().to_string();
+        } else if id == ID_SHARED_NAME {
+            // committed by leaving the field or enter, the receiver reopens for a new name only
+            let name = text.trim();
+            if let Ok(mut config) = self.config.lock() {
+                if config.shared_name != name {
+                    config.shared_name = name.to_string();
+                }
+            }
+        }
+    }
+
+    fn on_combo(&mut self, id: u32, index: usize) {
+        let mut config = match self.config.lock() {
+            Ok(config) => config,
+            _ => return,
+        };
+
+        if id == ID_PRESET_LIST {
+            let Some(name) = preset_names(PRESET_DIR).into_iter().nth(index) else {
+                return;
+            };
+
+            match config.load_preset(PRESET_DIR, &name) {
+                Ok(_) => {
+                    self.transparency = !config.enable_color_cloud;
+                    self.preset = name;
+                    self.rebuild_menu = true;
+                }
+                Err(e) => println!("{e:?}"),
+            }
+        } else if id == ID_LANGUAGE_LIST {
+            if let Some(&(language, _)) = LANGUAGES.get(index) {
+                config.language = language.to_string();
+                self.rebuild_menu = true;
+            }
+        }
+    }
+
+    fn take_menu_rebuild(&mut self) -> bool {
+        std::mem::take(&mut self.rebuild_menu)
+    }
+
+    fn on_drag(&mut self, dx: i32, dy: i32) {
+        let rect = self.hwnd.rect();
+        let div = rect.width().min(rect.height()) as f32;
+        let dx = dx as f32 / div;
+        let dy = dy as f32 / div;
+
+        let yaw = f32::consts::PI * -dx;
+        let pitch = f32::consts::PI * -dy;
+
+        // mouse moves come in uneven steps, so the velocity is smoothed
+        let now = Instant::now();
+        let velocity = match self.drag {
+            Some((last, [vyaw, vpitch])) if now - last < DRAG_PAUSE => {
+                let dt = (now - last).as_secs_f32().max(0.001);
+                [0.5 * vyaw + 0.5 * yaw / dt, 0.5 * vpitch + 0.5 * pitch / dt]
+            }
+            _ => [0.0; 2],
+        };
+        self.drag = Some((now, velocity));
+
+        if let Ok(mut config) = self.config.lock() {
+            config.camera.orbit(yaw, pitch);
+        }
+    }
+
+    fn on_drag_end(&mut self) {
+        // held still before the release, it stays put
+        let Some((_, [yaw, pitch])) = self
+            .drag
+            .take()
+            .filter(|(last, _)| last.elapsed() < DRAG_PAUSE)
+        else {
+            return;
+        };
+
+        if let Ok(mut config) = self.config.lock() {
+            config.camera.fling(yaw, pitch);
+        }
+    }
+
+    fn on_key(&mut self, key: u16) -> Option<(u32, bool)> {
+        let mut config = self.config.lock().ok()?;
+
+        match key {
+            KEY_FREEZE => {
+                config.freeze = !config.freeze;
+                Some((ID_FREEZE, config.freeze))
+            }
+            _ => None,
+        }
+    }
+
+    fn hotkeys(&mut self) -> Vec<(i32, u32, u32)> {
+        let Ok(config) = self.config.lock() else {
+            return Vec::new();
+        };
+
+        config
+            .hotkeys
+            .iter()
+            .enumerate()
+            .filter_map(|(i, hotkey)| hotkey.map(|hotkey| (i as i32, hotkey.modifiers, hotkey.key)))
+            .collect()
+    }
+
+    fn on_hotkey(&mut self, id: i32) -> Option<(u32, bool)> {
+        let mut config = self.config.lock().ok()?;
+
+        match id as usize {
+            HOTKEY_FILTER => {
+                config.enable_filter = !config.enable_filter;
+                Some((ID_ENABLE_FILTER, config.enable_filter))
+            }
+            HOTKEY_HISTOGRAM => {
+                config.enable_histogram = !config.enable_histogram;
+                Some((ID_ENABLE_HISTOGRAM, config.enable_histogram))
+            }
+            HOTKEY_COLORCLOUD => {
+                config.enable_color_cloud = !config.enable_color_cloud;
+                self.transparency = !config.enable_color_cloud;
+                Some((ID_ENABLE_COLORCLOUD, config.enable_color_cloud))
+            }
+            HOTKEY_FREEZE => {
+                config.freeze = !config.freeze;
+                Some((ID_FREEZE, config.freeze))
+            }
+            HOTKEY_EXPORT => {
+                config.export_request = config.export_request.wrapping_add(1);
+                None
+            }
+            HOTKEY_BOOKMARK => {
+                config.bookmark_request = config.bookmark_request.wrapping_add(1);
+                None
+            }
+            HOTKEY_CAPTURE_SCREENSHOT => {
+                config.capture_screenshot_request =
+                    config.capture_screenshot_request.wrapping_add(1);
+                None
+            }
+            HOTKEY_OUTPUT_SCREENSHOT => {
+                config.output_screenshot_request = config.output_screenshot_request.wrapping_add(1);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn on_click(&mut self, x: i32, y: i32) {
+        if let Ok(mut config) = self.config.lock() {
+            if config.enable_eyedropper {
+                config.pick_point = Some((x, y));
+            }
+        }
+    }
+
+    fn on_menu_shown(&mut self) {
+        if let Ok(mut config) = self.config.lock() {
+            config.menu_open_count = config.menu_open_count.saturating_add(1);
+        }
+    }
+
+    fn on_occluded(&mut self, occluded: bool) {
+        if let Ok(mut config) = self.config.lock() {
+            config.occluded = occluded;
+        }
+    }
+
+    // dimmer each time the menu is opened, gone after MENU_HINT_LIMIT
+    fn menu_hint(&mut self) -> f32 {
+        self.config.lock().map_or(0.0, |config| {
+            1.0 - config.menu_open_count.min(MENU_HINT_LIMIT) as f32 / MENU_HINT_LIMIT as f32
+        })
+    }
+
+    fn menu_activation(&mut self) -> (MenuEdge, i32) {
+        let (edge, zone) = self.config.lock().map_or((MENU_EDGE_LEFT, 32), |config| {
+            (config.menu_edge, config.menu_zone)
+        });
+
+        let edge = match edge {
+            MENU_EDGE_RIGHT => MenuEdge::Right,
+            MENU_EDGE_TOP => MenuEdge::Top,
+            _ => MenuEdge::Left,
+        };
+
+        (edge, zone)
+    }
+
+    fn window_rect(&mut self) -> RECT {
+        if let Ok(config) = self.config.lock() {
+            config.window_rect
+        } else {
+            RECT::new(100, 100, 1280, 720)
+        }
+    }
+
+    fn locked(&mut self) -> bool {
+        self.config.lock().is_ok_and(|config| config.lock_window)
+    }
+
+    fn follow_window(&mut self) -> bool {
+        self.config.lock().is_ok_and(|config| config.follow_window)
+    }
+
+    fn exclude_from_capture(&mut self) -> bool {
+        self.config
+            .lock()
+            .map_or(true, |config| config.exclude_from_capture)
+    }
+
+    fn transparency(&mut self) -> bool {
+        self.transparency
+    }
+
+    fn build_menu(&mut self, builder: &mut Builder) -> Result<()> {
+        let config = self.config.lock().unwrap().to_owned();
+
+        let presets = preset_names(PRESET_DIR);
+        let preset = presets.iter().position(|name| *name == self.preset);
+
+        let languages = LANGUAGES.iter().map(|(_, name)| name.to_string()).collect();
+        let language = LANGUAGES
+            .iter()
+            .position(|(language, _)| *language == config.language);
+        let [clipping, contrast, flicker] = config.alerts;
+        builder.set_strings(locale::strings(&config.language));
+
+        builder.build(col!(
+            text!(" Preset"),
+            col!(
+                indent: 16,
+                combo!(width: 128, ID_PRESET_LIST, presets, preset),
+                edit!(width: 128, ID_PRESET_NAME, self.preset.clone()),
+                row!(
+                    button!(width: 64, ID_PRESET_SAVE, "Save"),
+                    button!(width: 64, ID_PRESET_DELETE, "Delete"),
+                ),
+            ),
+            space!(8),
+            check!(ID_ENABLE_FILTER, "Filter", config.enable_filter),
+            col!(
+                indent: 16,
+                radio!(ID_FILTER_RGB, "RGB", config.filter_mode == FILTER_MODE_RGB, ID_FILTER_RGB),
+                row!(
+                    indent: 12,
+                    check!(width: 36, ID_FILTER_CH_R, "R", config.filter_channels[0]),
+                    check!(width: 36, ID_FILTER_CH_G, "G", config.filter_channels[1]),
+                    check!(width: 36, ID_FILTER_CH_B, "B", config.filter_channels[2])
+                ),
+                radio!(ID_FILTER_HUE, "Hue", config.filter_mode == FILTER_MODE_HUE, ID_FILTER_RGB),
+                radio!(ID_FILTER_SAT, "Saturat", config.filter_mode == FILTER_MODE_SAT, ID_FILTER_RGB),
+                radio!(ID_FILTER_LUMA, "Luma", config.filter_mode == FILTER_MODE_LUMA, ID_FILTER_RGB),
+                radio!(ID_FILTER_ZEBRA, "Zebra", config.filter_mode == FILTER_MODE_ZEBRA, ID_FILTER_RGB),
+                col!(
+                    indent: 12,
+                    text!(" Above"),
+                    slider!(ID_ZEBRA_HIGH, 0, 100, (100.0 * config.zebra_high) as i32),
+                    text!(" Below"),
+                    slider!(ID_ZEBRA_LOW, 0, 100, (100.0 * config.zebra_low) as i32),
+                ),
+                radio!(ID_FILTER_MONO, "Mono", config.filter_mode == FILTER_MODE_MONO, ID_FILTER_RGB),
+                col!(
+                    indent: 12,
+                    text!(" Amount"),
+                    slider!(ID_DESATURATION, 0, 100, (100.0 * config.desaturation) as i32),
+                ),
+                radio!(ID_FILTER_BLUE, "Blue Only", config.filter_mode == FILTER_MODE_BLUE, ID_FILTER_RGB),
+                radio!(ID_FILTER_RANGE, "Out of Range", config.filter_mode == FILTER_MODE_RANGE, ID_FILTER_RGB),
+                check!(ID_ANALYZE_FILTERED, "Scopes Use Filtered", config.analyze_filtered),
+            ),
+            space!(8),
+            check!(ID_ENABLE_HISTOGRAM, "Histogram", config.enable_histogram),
+            col!(
+                indent: 16,
+                radio!(ID_HISTOGRAM_RGB, "RGB", config.histogram_mode == HISTOGRAM_MODE_RGB, ID_HISTOGRAM_RGB),
+                radio!(ID_HISTOGRAM_RGBL, "RGBL", config.histogram_mode == HISTOGRAM_MODE_RGBL, ID_HISTOGRAM_RGB),
+                radio!(ID_HISTOGRAM_LUMA, "Luma", config.histogram_mode == HISTOGRAM_MODE_LUMA, ID_HISTOGRAM_RGB),
+                radio!(ID_HISTOGRAM_HUE, "Hue", config.histogram_mode == HISTOGRAM_MODE_HUE, ID_HISTOGRAM_RGB),
+                radio!(ID_HISTOGRAM_SAT, "Saturation", config.histogram_mode == HISTOGRAM_MODE_SAT, ID_HISTOGRAM_RGB),
+                radio!(ID_HISTOGRAM_CHROMA, "Chroma (Lab)", config.histogram_mode == HISTOGRAM_MODE_CHROMA, ID_HISTOGRAM_RGB),
+                text!(" Scale"),
+                slider!(ID_HISTOGRAM_SCALE, 0, 100, (100.0 * config.histogram_scale) as i32),
+                check!(ID_HISTOGRAM_AUTO_SCALE, "Auto", config.histogram_auto_scale),
+                text!(" Bins"),
+                radio!(ID_HISTOGRAM_BINS_64, "64", config.histogram_bins == 64, ID_HISTOGRAM_BINS_64),
+                radio!(ID_HISTOGRAM_BINS_128, "128", config.histogram_bins == 128, ID_HISTOGRAM_BINS_64),
+                radio!(ID_HISTOGRAM_BINS_256, "256", config.histogram_bins == 256, ID_HISTOGRAM_BINS_64),
+                radio!(ID_HISTOGRAM_BINS_512, "512", config.histogram_bins == 512, ID_HISTOGRAM_BINS_64),
+                radio!(ID_HISTOGRAM_BINS_1024, "1024", config.histogram_bins == 1024, ID_HISTOGRAM_BINS_64),
+                check!(ID_HISTOGRAM_DELTA, "Frame Delta", config.histogram_delta),
+                check!(ID_HISTOGRAM_HISTORY, "History", config.histogram_history),
+                text!(" Trace Colors"),
+                row!(
+                    button!(width: 36, ID_HISTOGRAM_COLOR_R, "R"),
+                    button!(width: 36, ID_HISTOGRAM_COLOR_G, "G"),
+                    button!(width: 36, ID_HISTOGRAM_COLOR_B, "B"),
+                    button!(width: 36, ID_HISTOGRAM_COLOR_L, "L"),
+                ),
+                check!(ID_HISTOGRAM_SUGGEST_LEVELS, "Suggest Levels", config.suggest_levels),
+                check!(ID_HISTOGRAM_PREVIEW_LEVELS, "Apply to Preview", config.preview_levels),
+                button!(ID_HISTOGRAM_EXPORT, "Export"),
+                layout_menu(ID_HISTOGRAM_ANCHOR, ID_HISTOGRAM_SIZE, &config.layouts[LAYOUT_HISTOGRAM]),
+            ),
+            space!(8),
+            check!(ID_ENABLE_COLORCLOUD, "Colod-Cloud", config.enable_color_cloud),
+            col!(
+                indent: 16,
+                radio!(ID_COLORCLOUD_RGB, "RGB", config.color_cloud_mode == COLORCLOUD_MODE_RGB, ID_COLORCLOUD_RGB),
+                radio!(ID_COLORCLOUD_HSL, "HSL", config.color_cloud_mode == COLORCLOUD_MODE_HSL, ID_COLORCLOUD_RGB),
+                radio!(ID_COLORCLOUD_HSV, "HSV", config.color_cloud_mode == COLORCLOUD_MODE_HSV, ID_COLORCLOUD_RGB),
+                radio!(ID_COLORCLOUD_YCBCR, "YCbCr", config.color_cloud_mode == COLORCLOUD_MODE_YCBCR, ID_COLORCLOUD_RGB),
+                radio!(ID_COLORCLOUD_LAB, "CIELAB", config.color_cloud_mode == COLORCLOUD_MODE_LAB, ID_COLORCLOUD_RGB),
+                radio!(ID_COLORCLOUD_OKLAB, "OKLab", config.color_cloud_mode == COLORCLOUD_MODE_OKLAB, ID_COLORCLOUD_RGB),
+                radio!(ID_COLORCLOUD_SOLID, "Solid", config.color_cloud_style == COLORCLOUD_STYLE_SOLID, ID_COLORCLOUD_SOLID),
+                radio!(ID_COLORCLOUD_SPLAT, "Splat", config.color_cloud_style == COLORCLOUD_STYLE_SPLAT, ID_COLORCLOUD_SOLID),
+                radio!(ID_COLORCLOUD_VOLUME, "Volume", config.color_cloud_style == COLORCLOUD_STYLE_VOLUME, ID_COLORCLOUD_SOLID),
+                check!(ID_COLORCLOUD_OIT, "Sorted Blending (OIT)", config.color_cloud_oit),
+                check!(ID_COLORCLOUD_ISOSURFACE, "Isosurface", config.color_cloud_isosurface),
+                text!(" Iso Level"),
+                slider!(ID_COLORCLOUD_ISO_LEVEL, 0, 100, (100.0 * config.color_cloud_iso_level) as i32),
+                check!(ID_COLORCLOUD_HEATMAPS, "Plane Heatmaps", config.color_cloud_heatmaps),
+                text!(" Volume Ramp"),
+                slider!(ID_COLORCLOUD_RAMP_LOW, 0, 100, (100.0 * config.color_cloud_ramp[0]) as i32),
+                slider!(ID_COLORCLOUD_RAMP_HIGH, 0, 100, (100.0 * config.color_cloud_ramp[1]) as i32),
+                text!(" Transparency"),
+                radio!(ID_TRANSPARENCY_BLEND, "Blend", config.transparency_mode == TRANSPARENCY_MODE_BLEND, ID_TRANSPARENCY_BLEND),
+                radio!(ID_TRANSPARENCY_DITHER, "Dither", config.transparency_mode == TRANSPARENCY_MODE_DITHER, ID_TRANSPARENCY_BLEND),
+                radio!(ID_TRANSPARENCY_AUTO, "Auto", config.transparency_mode == TRANSPARENCY_MODE_AUTO, ID_TRANSPARENCY_BLEND),
+                check!(ID_COLORCLOUD_GRID, "Show Grid", config.show_grid),
+                check!(ID_COLORCLOUD_LABELS, "Grid Labels", config.grid_labels),
+                check!(ID_COLORCLOUD_INTERLEAVE, "Interleave", config.color_cloud_interleave),
+                check!(ID_COLORCLOUD_ACCUMULATE, "Accumulate", config.color_cloud_accumulate),
+                check!(ID_COLORCLOUD_AGE, "Color by Age", config.color_cloud_age),
+                check!(ID_COLORCLOUD_PERSPECTIVE, "Perspective", config.camera.perspective),
+                button!(ID_COLORCLOUD_RESET_VIEW, "Reset View"),
+                check!(ID_COLORCLOUD_FILL, "Fill Window", config.color_cloud_fill),
+                text!(" Margin"),
+                slider!(ID_COLORCLOUD_MARGIN, 0, 200, config.color_cloud_margin as i32),
+                layout_menu(ID_COLORCLOUD_ANCHOR, ID_COLORCLOUD_SIZE, &config.layouts[LAYOUT_COLOR_CLOUD]),
+            ),
+            space!(8),
+            check!(ID_ENABLE_VECTORSCOPE, "Vectorscope", config.enable_vectorscope),
+            col!(
+                indent: 16,
+                text!(" Scale"),
+                slider!(ID_VECTORSCOPE_SCALE, 0, 100, (100.0 * config.vectorscope_scale) as i32),
+                check!(ID_VECTORSCOPE_SKIN_TONE, "Skin Tone Line", config.vectorscope_skin_tone),
+                check!(ID_VECTORSCOPE_FILL, "Fill Window", config.vectorscope_fill),
+                text!(" Margin"),
+                slider!(ID_VECTORSCOPE_MARGIN, 0, 200, config.vectorscope_margin as i32),
+                layout_menu(ID_VECTORSCOPE_ANCHOR, ID_VECTORSCOPE_SIZE, &config.layouts[LAYOUT_VECTORSCOPE]),
+            ),
+            space!(8),
+            check!(ID_ENABLE_HUE_LUMA, "Hue vs Luma", config.enable_hue_luma),
+            col!(
+                indent: 16,
+                text!(" Scale"),
+                slider!(ID_HUE_LUMA_SCALE, 0, 100, (100.0 * config.hue_luma_scale) as i32),
+                layout_menu(ID_HUE_LUMA_ANCHOR, ID_HUE_LUMA_SIZE, &config.layouts[LAYOUT_HUE_LUMA]),
+            ),
+            space!(8),
+            check!(ID_ENABLE_WAVEFORM, "Waveform", config.enable_waveform),
+            col!(
+                indent: 16,
+                radio!(ID_WAVEFORM_COMPOSITE, "Composite", config.waveform_layout == WAVEFORM_LAYOUT_COMPOSITE, ID_WAVEFORM_COMPOSITE),
+                radio!(ID_WAVEFORM_PARADE, "RGB Parade", config.waveform_layout == WAVEFORM_LAYOUT_PARADE, ID_WAVEFORM_COMPOSITE),
+                text!(" Scale"),
+                slider!(ID_WAVEFORM_SCALE, 0, 100, (100.0 * config.waveform_scale) as i32),
+                layout_menu(ID_WAVEFORM_ANCHOR, ID_WAVEFORM_SIZE, &config.layouts[LAYOUT_WAVEFORM]),
+            ),
+            space!(8),
+            check!(ID_ENABLE_ROI, "Region", config.enable_roi),
+            row!(
+                indent: 16,
+                text!(" X"),
+                number!(width: 40, ID_ROI_X, config.roi.left),
+                text!(" Y"),
+                number!(width: 40, ID_ROI_Y, config.roi.top),
+            ),
+            row!(
+                indent: 16,
+                text!(" W"),
+                number!(width: 40, ID_ROI_WIDTH, config.roi.width()),
+                text!(" H"),
+                number!(width: 40, ID_ROI_HEIGHT, config.roi.height()),
+            ),
+            col!(
+                indent: 16,
+                check!(ID_COMPARE_ROI, "Compare Full", config.compare_roi),
+            ),
+            check!(ID_COMPARE_OUTPUTS, "Compare Output", config.compare_outputs),
+            row!(
+                indent: 16,
+                text!(" Output"),
+                number!(width: 40, ID_COMPARE_OUTPUT, config.compare_output as i32),
+            ),
+            check!(ID_ENABLE_EYEDROPPER, "Eyedropper", config.enable_eyedropper),
+            check!(ID_ESTIMATE_GAMMA, "Gamma From Ramp", config.estimate_gamma),
+            check!(ID_COUNT_OUT_OF_RANGE, "Count Out of Range", config.count_out_of_range),
+            check!(ID_ENABLE_UNIFORMITY, "Uniformity Map", config.enable_uniformity),
+            col!(
+                indent: 16,
+                text!(" Grid"),
+                slider!(ID_UNIFORMITY_GRID, 3, 15, config.uniformity_grid as i32),
+            ),
+            check!(
+                ID_ENABLE_TEXT_CONTRAST,
+                "Text Contrast",
+                config.enable_text_contrast
+            ),
+            check!(ID_ENABLE_FRAME_PACING, "Frame Pacing", config.enable_frame_pacing),
+            check!(
+                ID_ENABLE_FRAME_DIFFERENCE,
+                "Frame Difference",
+                config.enable_frame_difference
+            ),
+            col!(
+                indent: 16,
+                radio!(ID_DIFFERENCE_HEATMAP, "Heatmap", config.frame_difference_mode == DIFFERENCE_HEATMAP, ID_DIFFERENCE_HEATMAP),
+                radio!(ID_DIFFERENCE_THRESHOLD, "Threshold", config.frame_difference_mode == DIFFERENCE_THRESHOLD, ID_DIFFERENCE_HEATMAP),
+                col!(
+                    indent: 12,
+                    text!(" Above"),
+                    slider!(ID_DIFFERENCE_LEVEL, 0, 50, (100.0 * config.frame_difference_threshold) as i32),
+                ),
+            ),
+            text!(" Source"),
+            col!(
+                indent: 16,
+                radio!(ID_SOURCE_DESKTOP, "Desktop", config.capture_source == SOURCE_DESKTOP, ID_SOURCE_DESKTOP),
+                radio!(ID_SOURCE_FILE, "File", config.capture_source == SOURCE_FILE, ID_SOURCE_DESKTOP),
+                button!(ID_OPEN_IMAGE, "Open Image"),
+                col!(
+                    indent: 12,
+                    check!(ID_LINEAR_DOWNSAMPLE, "Downsample in Linear Light", config.linear_downsample),
+                ),
+                radio!(ID_SOURCE_VIDEO, "Video", config.capture_source == SOURCE_VIDEO, ID_SOURCE_DESKTOP),
+                button!(ID_OPEN_VIDEO, "Open Video"),
+                col!(
+                    indent: 12,
+                    check!(ID_VIDEO_PLAY, "Play", config.video_playing),
+                    text!(" Seek"),
+                    slider!(ID_VIDEO_SEEK, 0, 1000, (1000.0 * config.video_seek) as i32),
+                ),
+                radio!(ID_SOURCE_SHARED, "Shared Texture", config.capture_source == SOURCE_SHARED, ID_SOURCE_DESKTOP),
+                col!(
+                    indent: 12,
+                    text!(" Sender"),
+                    edit!(width: 128, ID_SHARED_NAME, config.shared_name.clone()),
+                ),
+            ),
+            check!(ID_FREEZE, "Freeze (F)", config.freeze),
+            check!(ID_INCLUDE_CURSOR, "Include Cursor", config.include_cursor),
+            check!(ID_ENABLE_LUT, "3D LUT", config.enable_lut),
+            col!(
+                indent: 16,
+                button!(ID_LOAD_LUT, "Load .cube"),
+            ),
+            check!(ID_HDR_MODE, "HDR (PQ)", config.hdr_mode),
+            text!(" Primaries"),
+            col!(
+                indent: 16,
+                radio!(ID_PRIMARIES_BT601, "Rec.601", config.source_primaries == PRIMARIES_BT601, ID_PRIMARIES_BT601),
+                radio!(ID_PRIMARIES_BT709, "Rec.709", config.source_primaries == PRIMARIES_BT709, ID_PRIMARIES_BT601),
+                radio!(ID_PRIMARIES_BT2020, "Rec.2020", config.source_primaries == PRIMARIES_BT2020, ID_PRIMARIES_BT601),
+            ),
+            text!(" Transfer"),
+            col!(
+                indent: 16,
+                radio!(ID_TRANSFER_SRGB, "sRGB", config.source_transfer == TRANSFER_SRGB, ID_TRANSFER_SRGB),
+                radio!(ID_TRANSFER_BT709, "Rec.709", config.source_transfer == TRANSFER_BT709, ID_TRANSFER_SRGB),
+                radio!(ID_TRANSFER_BT2020, "Rec.2020", config.source_transfer == TRANSFER_BT2020, ID_TRANSFER_SRGB),
+            ),
+            space!(8),
+            text!(" Transparency"),
+            slider!(ID_COLORCLOUD_BG, 0, 100, (100.0 * config.bg_opacity) as i32),
+            text!(" Background"),
+            col!(
+                indent: 16,
+                radio!(ID_BACKGROUND_TRANSPARENT, "Transparent", config.bg_mode == BACKGROUND_TRANSPARENT, ID_BACKGROUND_TRANSPARENT),
+                radio!(ID_BACKGROUND_SOLID, "Solid", config.bg_mode == BACKGROUND_SOLID, ID_BACKGROUND_TRANSPARENT),
+                radio!(ID_BACKGROUND_CHECKER, "Checkerboard", config.bg_mode == BACKGROUND_CHECKER, ID_BACKGROUND_TRANSPARENT),
+                button!(ID_BACKGROUND_COLOR, "Pick Color"),
+                button!(ID_CLEAR_COLOR, "Clear Color"),
+            ),
+            check!(ID_SHOW_LEGEND, "Legend", config.show_legend),
+            check!(ID_SPLIT_SCREEN, "Split Screen", config.split_screen),
+            check!(ID_SHOW_DIAGNOSTICS, "Diagnostics", config.show_diagnostics),
+            space!(8),
+            check!(ID_LOCK_WINDOW, "Lock Window", config.lock_window),
+            check!(ID_FOLLOW_WINDOW, "Follow Window", config.follow_window),
+            text!(" Window"),
+            row!(
+                indent: 16,
+                text!(" X"),
+                number!(width: 40, ID_WINDOW_X, config.window_rect.left),
+                text!(" Y"),
+                number!(width: 40, ID_WINDOW_Y, config.window_rect.top),
+            ),
+            row!(
+                indent: 16,
+                text!(" W"),
+                number!(width: 40, ID_WINDOW_WIDTH, config.window_rect.width()),
+                text!(" H"),
+                number!(width: 40, ID_WINDOW_HEIGHT, config.window_rect.height()),
+            ),
+            check!(
+                ID_EXCLUDE_CAPTURE,
+                "Hide From Capture",
+                config.exclude_from_capture
+            ),
+            check!(ID_PAUSE_OCCLUDED, "Pause When Hidden", config.pause_when_occluded),
+            check!(
+                ID_LOG_OCCLUDED,
+                "Keep Logging When Hidden",
+                config.log_when_occluded
+            ),
+            text!(" Target FPS (0 = Unlimited)"),
+            slider!(ID_TARGET_FPS, 0, 240, config.target_fps as i32),
+            text!(" Capture Timeout (ms)"),
+            slider!(ID_CAPTURE_TIMEOUT, 0, 1000, config.capture_timeout as i32),
+            check!(ID_REUSE_FRAMES, "Reuse Last Frame", config.reuse_frames),
+            text!(" Line Antialiasing"),
+            col!(
+                indent: 16,
+                radio!(ID_MSAA_OFF, "MSAA Off", config.msaa_samples <= 1, ID_MSAA_OFF),
+                radio!(ID_MSAA_2X, "MSAA 2x", config.msaa_samples == 2, ID_MSAA_OFF),
+                radio!(ID_MSAA_4X, "MSAA 4x", config.msaa_samples == 4, ID_MSAA_OFF),
+            ),
+            check!(ID_SHARE_STATS, "Share Stats", config.share_stats),
+            col!(
+                indent: 16,
+                text!(" Average Frames"),
+                slider!(ID_SAMPLE_FRAMES, 1, 60, config.sample_frames as i32),
+                check!(ID_REJECT_OUTLIERS, "Reject Outliers", config.reject_outliers),
+            ),
+            check!(ID_ENABLE_REPORTS, "Periodic Report", config.enable_reports),
+            col!(
+                indent: 16,
+                text!(" Interval (min)"),
+                slider!(ID_REPORT_INTERVAL, 1, 60, config.report_interval as i32),
+            ),
+            check!(ID_ENABLE_SESSION, "Session Timeline", config.enable_session),
+            col!(
+                indent: 16,
+                check!(ID_SESSION_THUMBNAILS, "Bookmark Thumbnails", config.session_thumbnails),
+                row!(
+                    button!(width: 80, ID_SESSION_BOOKMARK, "Bookmark"),
+                    button!(width: 80, ID_SESSION_EXPORT, "Export"),
+                ),
+            ),
+            text!(" Screenshot"),
+            col!(
+                indent: 16,
+                row!(
+                    button!(width: 80, ID_SCREENSHOT_CAPTURE, "Capture"),
+                    button!(width: 80, ID_SCREENSHOT_OUTPUT, "Output"),
+                ),
+            ),
+            check!(ID_RECORD, "Record Video", config.recording),
+            col!(
+                indent: 16,
+                radio!(ID_RECORD_SCOPES, "Scopes", config.record_source == RECORD_SCOPES, ID_RECORD_SCOPES),
+                radio!(ID_RECORD_CAPTURE, "Desktop", config.record_source == RECORD_CAPTURE, ID_RECORD_SCOPES),
+            ),
+            button!(ID_CALIBRATE, "Capture Self-Check"),
+            check!(ID_ENABLE_DDC, "Monitor DDC/CI", config.enable_ddc),
+            col!(
+                indent: 16,
+                text!(" Brightness"),
+                slider!(ID_DDC_BRIGHTNESS, 0, 100, ddc_slider(config.ddc_readings[0], config.ddc_brightness)),
+                text!(" Contrast"),
+                slider!(ID_DDC_CONTRAST, 0, 100, ddc_slider(config.ddc_readings[1], config.ddc_contrast)),
+            ),
+            text!(" Alerts"),
+            col!(
+                indent: 16,
+                check!(ID_ALERT_CLIPPING, "Clipping", clipping.enable),
+                row!(
+                    indent: 16,
+                    check!(width: 64, ID_ALERT_CLIPPING_SOUND, "Sound", clipping.sound),
+                    check!(width: 64, ID_ALERT_CLIPPING_FLASH, "Flash", clipping.flash),
+                ),
+                check!(ID_ALERT_CONTRAST, "Low Contrast", contrast.enable),
+                row!(
+                    indent: 16,
+                    check!(width: 64, ID_ALERT_CONTRAST_SOUND, "Sound", contrast.sound),
+                    check!(width: 64, ID_ALERT_CONTRAST_FLASH, "Flash", contrast.flash),
+                ),
+                check!(ID_ALERT_FLICKER, "Flicker", flicker.enable),
+                row!(
+                    indent: 16,
+                    check!(width: 64, ID_ALERT_FLICKER_SOUND, "Sound", flicker.sound),
+                    check!(width: 64, ID_ALERT_FLICKER_FLASH, "Flash", flicker.flash),
+                ),
+            ),
+            text!(" Menu Edge"),
+            col!(
+                indent: 16,
+                radio!(ID_MENU_EDGE_LEFT, "Left", config.menu_edge == MENU_EDGE_LEFT, ID_MENU_EDGE_LEFT),
+                radio!(ID_MENU_EDGE_RIGHT, "Right", config.menu_edge == MENU_EDGE_RIGHT, ID_MENU_EDGE_LEFT),
+                radio!(ID_MENU_EDGE_TOP, "Top", config.menu_edge == MENU_EDGE_TOP, ID_MENU_EDGE_LEFT),
+                text!(" Zone"),
+                slider!(ID_MENU_ZONE, 8, 128, config.menu_zone),
+            ),
+            text!(" Language"),
+            col!(
+                indent: 16,
+                combo!(width: 128, ID_LANGUAGE_LIST, languages, language),
+            ),
+        ))
+    }
+}
+
+// the monitor's own value once it has been read, then the last slider position, else the middle
+fn ddc_slider(reading: i32, set: i32) -> i32 {
+    if reading >= 0 {
+        reading
+    } else if set >= 0 {
+        set
+    } else {
+        50
+    }
+}
+
+// the default placement or a corner of the window, sized by share of the window
+fn layout_menu(anchor_id: u32, size_id: u32, layout: &ScopeLayout) -> Ctrl {
+    let anchor = layout.anchor;
+    col!(
+        text!(" Position"),
+        radio!(
+            anchor_id + ANCHOR_DEFAULT,
+            "Default",
+            anchor == ANCHOR_DEFAULT,
+            anchor_id
+        ),
+        radio!(
+            anchor_id + ANCHOR_TOP_LEFT,
+            "Top Left",
+            anchor == ANCHOR_TOP_LEFT,
+            anchor_id
+        ),
+        radio!(
+            anchor_id + ANCHOR_TOP_RIGHT,
+            "Top Right",
+            anchor == ANCHOR_TOP_RIGHT,
+            anchor_id
+        ),
+        radio!(
+            anchor_id + ANCHOR_BOTTOM_LEFT,
+            "Bottom Left",
+            anchor == ANCHOR_BOTTOM_LEFT,
+            anchor_id
+        ),
+        radio!(
+            anchor_id + ANCHOR_BOTTOM_RIGHT,
+            "Bottom Right",
+            anchor == ANCHOR_BOTTOM_RIGHT,
+            anchor_id
+        ),
+        text!(" Size"),
+        slider!(size_id, 10, 100, (100.0 * layout.size) as i32),
+    )
+}
@@ -1,165 +1,2217 @@
-#![allow(unused)]
-
-use std::path::{Path, PathBuf};
-
-use ini::{Ini, SectionSetter};
-use windows::Win32::Foundation::RECT;
-
-use crate::{graphics::math::Matrix, gui::utils::Rect};
-
-pub const FILTER_MODE_RGB: u32 = 0;
-pub const FILTER_MODE_HUE: u32 = 1;
-pub const FILTER_MODE_SAT: u32 = 2;
-pub const FILTER_MODE_LUMA: u32 = 3;
-pub const HISTOGRAM_MODE_RGB: u32 = 0;
-pub const HISTOGRAM_MODE_RGBL: u32 = 1;
-pub const HISTOGRAM_MODE_LUMA: u32 = 2;
-pub const HISTOGRAM_MODE_HUE: u32 = 3;
-pub const COLORCLOUD_MODE_RGB: u32 = 0;
-pub const COLORCLOUD_MODE_HSL: u32 = 1;
-
-#[derive(Clone, Debug)]
-pub struct Config {
-    pub enable_filter: bool,
-    pub filter_mode: u32,
-    pub filter_channels: [bool; 4],
-    pub enable_histogram: bool,
-    pub histogram_mode: u32,
-    pub histogram_scale: f32,
-    pub enable_color_cloud: bool,
-    pub color_cloud_mode: u32,
-    pub show_grid: bool,
-    pub bg_opacity: f32,
-    pub window_rect: RECT,
-    pub rotation: Matrix,
-}
-
-impl Config {
-    pub fn load(path: impl AsRef<Path>) -> Self {
-        if let Ok(conf) = Ini::load_from_file_noescape(&path) {
-            let window_x = conf.get_i32("window-x", 100).max(0);
-            let window_y = conf.get_i32("window-y", 100).max(0);
-            let window_width = conf.get_i32("window-width", 640).max(0);
-            let window_height = conf.get_i32("window-height", 480).max(0);
-
-            Self {
-                enable_filter: conf.get_bool("enable-filter"),
-                filter_mode: conf.get_u32("filter-mode", 0),
-                filter_channels: [true; 4],
-                enable_histogram: conf.get_bool("enable-histogram"),
-                histogram_mode: conf.get_u32("histogram-mode", 0),
-                histogram_scale: conf.get_f32("histogram-scale", 0.5),
-                enable_color_cloud: conf.get_bool("enable-color-cloud"),
-                color_cloud_mode: conf.get_u32("color-cloud-mode", 0),
-                show_grid: conf.get_bool("show-grid"),
-                bg_opacity: conf.get_f32("bg-opacity", 1.0),
-                window_rect: RECT::new(window_x, window_y, window_width, window_height),
-                rotation: Matrix::identity(),
-            }
-        } else {
-            Self {
-                enable_filter: false,
-                filter_mode: 0,
-                filter_channels: [true; 4],
-                enable_histogram: false,
-                histogram_mode: 0,
-                histogram_scale: 0.5,
-                enable_color_cloud: false,
-                color_cloud_mode: 0,
-                show_grid: false,
-                bg_opacity: 1.0,
-                window_rect: RECT::new(100, 100, 1280, 720),
-                rotation: Matrix::identity(),
-            }
-        }
-    }
-
-    pub fn save(&self, path: impl AsRef<Path>) {
-        let mut conf = Ini::new();
-
-        conf.with_general_section()
-            .set_bool("enable-filter", self.enable_filter)
-            .set_u32("filter-mode", self.filter_mode)
-            .set_bool("enable-histogram", self.enable_histogram)
-            .set_u32("histogram-mode", self.histogram_mode)
-            .set_f32("histogram-scale", self.histogram_scale)
-            .set_bool("enable-color-cloud", self.enable_color_cloud)
-            .set_u32("color-cloud-mode", self.color_cloud_mode)
-            .set_bool("show-grid", self.show_grid)
-            .set_f32("bg-opacity", self.bg_opacity)
-            .set_i32("window-x", self.window_rect.left)
-            .set_i32("window-y", self.window_rect.top)
-            .set_i32("window-width", self.window_rect.width())
-            .set_i32("window-height", self.window_rect.height());
-
-        _ = conf.write_to_file(path);
-    }
-
-    pub fn projection_matrix(&self) -> Matrix {
-        let (width, height) = self.window_rect.size();
-        let scale = 0.9 * width.min(height) as f32 / width.max(height) as f32;
-
-        self.rotation
-            .mul(&Matrix::scale(scale, scale, 0.25))
-            .mul(&Matrix::translate(0.0, 0.0, 0.5))
-    }
-}
-
-trait IniSetter<'a> {
-    fn set_bool(&'a mut self, key: &str, value: bool) -> &'a mut SectionSetter<'a>;
-    fn set_i32(&'a mut self, key: &str, value: i32) -> &'a mut SectionSetter<'a>;
-    fn set_u32(&'a mut self, key: &str, value: u32) -> &'a mut SectionSetter<'a>;
-    fn set_f32(&'a mut self, key: &str, value: f32) -> &'a mut SectionSetter<'a>;
-}
-
-impl<'a> IniSetter<'a> for SectionSetter<'a> {
-    fn set_bool(&'a mut self, key: &str, value: bool) -> &'a mut SectionSetter<'a> {
-        self.set(key, (value as u32).to_string())
-    }
-
-    fn set_i32(&'a mut self, key: &str, value: i32) -> &'a mut SectionSetter<'a> {
-        self.set(key, value.to_string())
-    }
-
-    fn set_u32(&'a mut self, key: &str, value: u32) -> &'a mut SectionSetter<'a> {
-        self.set(key, value.to_string())
-    }
-
-    fn set_f32(&'a mut self, key: &str, value: f32) -> &'a mut SectionSetter<'a> {
-        self.set(key, value.to_string())
-    }
-}
-
-trait IniGetter {
-    fn get_bool(&self, key: &str) -> bool;
-    fn get_i32(&self, key: &str, default: i32) -> i32;
-    fn get_u32(&self, key: &str, default: u32) -> u32;
-    fn get_f32(&self, key: &str, default: f32) -> f32;
-}
-
-impl IniGetter for Ini {
-    fn get_bool(&self, key: &str) -> bool {
-        matches!(self.get_from::<String>(None, key), Some("1"))
-    }
-
-    fn get_i32(&self, key: &str, default: i32) -> i32 {
-        self.get_from::<String>(None, key)
-            .unwrap_or_default()
-            .parse::<i32>()
-            .unwrap_or(default)
-    }
-
-    fn get_u32(&self, key: &str, default: u32) -> u32 {
-        self.get_from::<String>(None, key)
-            .unwrap_or_default()
-            .parse::<u32>()
-            .unwrap_or(default)
-    }
-
-    fn get_f32(&self, key: &str, default: f32) -> f32 {
-        self.get_from::<String>(None, key)
-            .unwrap_or_default()
-            .parse::<f32>()
-            .unwrap_or(default)
-    }
-}
+#![allow(unused)]
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use ini::Ini;
+use serde::{Deserialize, Serialize};
+use windows::Win32::{
+    Foundation::RECT,
+    UI::Input::KeyboardAndMouse::{
+        MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN, VK_DELETE, VK_END, VK_F1, VK_HOME, VK_INSERT,
+        VK_NEXT, VK_PAUSE, VK_PRIOR, VK_SNAPSHOT, VK_SPACE,
+    },
+};
+
+use crate::{
+    graphics::{
+        math::{Camera, Matrix},
+        renderer::{split, ViewportKind},
+    },
+    gui::utils::Rect,
+};
+
+pub const FILTER_MODE_RGB: u32 = 0;
+pub const FILTER_MODE_HUE: u32 = 1;
+pub const FILTER_MODE_SAT: u32 = 2;
+pub const FILTER_MODE_LUMA: u32 = 3;
+pub const FILTER_MODE_ZEBRA: u32 = 4;
+pub const FILTER_MODE_MONO: u32 = 5;
+pub const FILTER_MODE_BLUE: u32 = 6;
+pub const FILTER_MODE_RANGE: u32 = 7;
+pub const HISTOGRAM_MODE_RGB: u32 = 0;
+pub const HISTOGRAM_MODE_RGBL: u32 = 1;
+pub const HISTOGRAM_MODE_LUMA: u32 = 2;
+pub const HISTOGRAM_MODE_HUE: u32 = 3;
+pub const HISTOGRAM_MODE_SAT: u32 = 4;
+pub const HISTOGRAM_MODE_CHROMA: u32 = 5;
+pub const HISTOGRAM_BINS: [u32; 5] = [64, 128, 256, 512, 1024];
+pub const COLORCLOUD_MODE_RGB: u32 = 0;
+pub const COLORCLOUD_MODE_HSL: u32 = 1;
+pub const COLORCLOUD_MODE_HSV: u32 = 2;
+pub const COLORCLOUD_MODE_YCBCR: u32 = 3;
+pub const COLORCLOUD_MODE_LAB: u32 = 4;
+pub const COLORCLOUD_MODE_OKLAB: u32 = 5;
+
+pub const COLORCLOUD_STYLE_SOLID: u32 = 0;
+pub const COLORCLOUD_STYLE_SPLAT: u32 = 1;
+pub const COLORCLOUD_STYLE_VOLUME: u32 = 2;
+
+pub const TRANSPARENCY_MODE_BLEND: u32 = 0;
+pub const TRANSPARENCY_MODE_DITHER: u32 = 1;
+pub const TRANSPARENCY_MODE_AUTO: u32 = 2;
+pub const BACKGROUND_TRANSPARENT: u32 = 0;
+pub const BACKGROUND_SOLID: u32 = 1;
+pub const BACKGROUND_CHECKER: u32 = 2;
+pub const DIFFERENCE_HEATMAP: u32 = 0;
+pub const DIFFERENCE_THRESHOLD: u32 = 1;
+pub const RECORD_SCOPES: u32 = 0;
+pub const RECORD_CAPTURE: u32 = 1;
+pub const SOURCE_DESKTOP: u32 = 0;
+pub const SOURCE_FILE: u32 = 1;
+pub const SOURCE_VIDEO: u32 = 2;
+pub const SOURCE_SHARED: u32 = 3;
+pub const PRIMARIES_BT709: u32 = 0;
+pub const PRIMARIES_BT601: u32 = 1;
+pub const PRIMARIES_BT2020: u32 = 2;
+pub const TRANSFER_SRGB: u32 = 0;
+pub const TRANSFER_BT709: u32 = 1;
+pub const TRANSFER_BT2020: u32 = 2;
+pub const WAVEFORM_LAYOUT_COMPOSITE: u32 = 0;
+pub const WAVEFORM_LAYOUT_PARADE: u32 = 1;
+pub const MENU_EDGE_LEFT: u32 = 0;
+pub const MENU_EDGE_RIGHT: u32 = 1;
+pub const MENU_EDGE_TOP: u32 = 2;
+
+// indices into Config::hotkeys
+pub const HOTKEY_FILTER: usize = 0;
+pub const HOTKEY_HISTOGRAM: usize = 1;
+pub const HOTKEY_COLORCLOUD: usize = 2;
+pub const HOTKEY_FREEZE: usize = 3;
+pub const HOTKEY_EXPORT: usize = 4;
+pub const HOTKEY_BOOKMARK: usize = 5;
+pub const HOTKEY_CAPTURE_SCREENSHOT: usize = 6;
+pub const HOTKEY_OUTPUT_SCREENSHOT: usize = 7;
+pub const HOTKEY_COUNT: usize = 8;
+
+// indices into Config::alerts
+pub const ALERT_CLIPPING: usize = 0;
+pub const ALERT_LOW_CONTRAST: usize = 1;
+pub const ALERT_FLICKER: usize = 2;
+pub const ALERT_COUNT: usize = 3;
+
+// indices into Config::layouts
+pub const LAYOUT_HISTOGRAM: usize = 0;
+pub const LAYOUT_WAVEFORM: usize = 1;
+pub const LAYOUT_VECTORSCOPE: usize = 2;
+pub const LAYOUT_COLOR_CLOUD: usize = 3;
+pub const LAYOUT_HUE_LUMA: usize = 4;
+pub const LAYOUT_COUNT: usize = 5;
+
+pub const ANCHOR_DEFAULT: u32 = 0;
+pub const ANCHOR_TOP_LEFT: u32 = 1;
+pub const ANCHOR_TOP_RIGHT: u32 = 2;
+pub const ANCHOR_BOTTOM_LEFT: u32 = 3;
+pub const ANCHOR_BOTTOM_RIGHT: u32 = 4;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub enable_filter: bool,
+    pub filter_mode: u32,
+    pub filter_channels: [bool; 4],
+    // the single channel left in filter_channels is shown in gray instead of its own color
+    pub filter_solo: bool,
+    pub zebra_high: f32,
+    pub zebra_low: f32,
+    // how far FILTER_MODE_MONO pulls the colors towards their luma
+    pub desaturation: f32,
+    pub analyze_filtered: bool,
+    pub enable_histogram: bool,
+    pub histogram_mode: u32,
+    pub histogram_scale: f32,
+    // one of HISTOGRAM_BINS
+    pub histogram_bins: u32,
+    // fits the tallest bin to the height, the scale slider is ignored
+    pub histogram_auto_scale: bool,
+    // signed change of each bin since the previous frame
+    pub histogram_delta: bool,
+    // the bins of the last frames side by side as a heatmap instead of the bars
+    pub histogram_history: bool,
+    // red, green, blue and luma traces, 0x00bbggrr like bg_color
+    pub histogram_colors: [u32; 4],
+    // black and white points from the histogram percentiles, optionally applied by the filter
+    pub suggest_levels: bool,
+    pub preview_levels: bool,
+    // set by the pipeline for the frame it draws, never persisted
+    pub suggested_levels: Option<[f32; 2]>,
+    pub export_request: u32,
+    pub enable_color_cloud: bool,
+    pub color_cloud_mode: u32,
+    pub color_cloud_interleave: bool,
+    pub show_grid: bool,
+    // tick labels along the grid axes
+    pub grid_labels: bool,
+    pub color_cloud_fill: bool,
+    pub color_cloud_margin: u32,
+    pub color_cloud_style: u32,
+    pub color_cloud_oit: bool,
+    pub color_cloud_isosurface: bool,
+    pub color_cloud_iso_level: f32,
+    // volume opacity ramps from 0 to 1 between these log scaled densities
+    pub color_cloud_ramp: [f32; 2],
+    pub color_cloud_heatmaps: bool,
+    // counts fade out over a few frames instead of being cleared
+    pub color_cloud_accumulate: bool,
+    // dims bins by how many frames ago they were last hit
+    pub color_cloud_age: bool,
+    pub transparency_mode: u32,
+    pub enable_vectorscope: bool,
+    pub vectorscope_scale: f32,
+    pub vectorscope_fill: bool,
+    pub vectorscope_margin: u32,
+    // i line and saturation rings over the graticule
+    pub vectorscope_skin_tone: bool,
+    // hue across, luma up, gray pixels left out
+    pub enable_hue_luma: bool,
+    pub hue_luma_scale: f32,
+    pub enable_waveform: bool,
+    pub waveform_layout: u32,
+    pub waveform_scale: f32,
+    pub bg_opacity: f32,
+    // BACKGROUND_*, all of them are made transparent by bg_opacity
+    pub bg_mode: u32,
+    // 0x00bbggrr like COLORREF
+    pub bg_color: u32,
+    // what the window is cleared to under everything, seen where the background is transparent
+    pub clear_color: u32,
+    pub window_rect: RECT,
+    pub enable_roi: bool,
+    pub roi: RECT,
+    pub compare_roi: bool,
+    // mean of each histogram channel in the roi minus the whole window's, set like estimated_gamma
+    pub roi_difference: Option<Vec<(&'static str, f32)>>,
+    pub compare_outputs: bool,
+    pub compare_output: u32,
+    // (r, g, b, delta e) of the compared output minus the primary, set like estimated_gamma
+    pub output_difference: Option<[f32; 4]>,
+    pub enable_eyedropper: bool,
+    // fits a gamma to a gray ramp across the middle of the analyzed region, the source's encoding
+    // as captured rather than the display's response
+    pub estimate_gamma: bool,
+    // set by the pipeline for the frame it draws, never persisted
+    pub estimated_gamma: Option<f32>,
+    // counts pixels with a channel above 1.0 or below 0.0, only scrgb and lut output reach them
+    pub count_out_of_range: bool,
+    // (above, below) as fractions of the analyzed pixels, set like estimated_gamma
+    pub out_of_range: Option<[f32; 2]>,
+    pub enable_uniformity: bool,
+    // cells along each side of the uniformity map, made odd so it has a center
+    pub uniformity_grid: u32,
+    // outlines text-like tiles below the wcag contrast minimums
+    pub enable_text_contrast: bool,
+    // charts the intervals between the captured desktop's updates
+    pub enable_frame_pacing: bool,
+    // marks what changed between the last two desktop updates
+    pub enable_frame_difference: bool,
+    pub frame_difference_mode: u32,
+    pub frame_difference_threshold: f32,
+    pub enable_lut: bool,
+    pub lut_path: Option<PathBuf>,
+    pub pick_point: Option<(i32, i32)>,
+    pub hdr_mode: bool,
+    // luma weights for luma, ycbcr and hue, and the curve the source was encoded with
+    pub source_primaries: u32,
+    pub source_transfer: u32,
+    pub freeze: bool,
+    // what the scopes analyze, an image file replaces the desktop while it loads. never persisted
+    pub capture_source: u32,
+    pub source_path: Option<PathBuf>,
+    pub video_playing: bool,
+    // where to seek, 0 to 1 over the video's duration, applied when the request changes
+    pub video_seek: f32,
+    pub video_seek_request: u32,
+    // (position, duration) in seconds, set by the pipeline for the frame it draws
+    pub video_time: Option<(f32, f32)>,
+    pub lock_window: bool,
+    pub follow_window: bool,
+    pub exclude_from_capture: bool,
+    pub pause_when_occluded: bool,
+    // sessions, reports and shared stats go on while paused, without drawing
+    pub log_when_occluded: bool,
+    pub occluded: bool,
+    pub target_fps: u32,
+    // how long to wait for a desktop update (ms) before the frame is skipped or reused
+    pub capture_timeout: u32,
+    pub reuse_frames: bool,
+    // draws the mouse pointer into the analyzed image, duplication leaves it out
+    pub include_cursor: bool,
+    // averages in linear light when an image is shrunk for analysis, off averages the encoded
+    // values like a naive resize
+    pub linear_downsample: bool,
+    // the spout sender or shared texture name read by the shared texture source
+    pub shared_name: String,
+    pub msaa_samples: u32,
+    pub share_stats: bool,
+    pub show_legend: bool,
+    pub show_diagnostics: bool,
+    // the output analyzed, only set from the command line
+    pub monitor: u32,
+    pub sample_frames: u32,
+    pub reject_outliers: bool,
+    pub enable_reports: bool,
+    // records per-second stats and bookmarks until turned off, then writes the timeline
+    pub enable_session: bool,
+    // a downscaled capture of the region with each bookmark
+    pub session_thumbnails: bool,
+    // bumped to bookmark the current moment of the session
+    pub bookmark_request: u32,
+    // bumped to write the timeline so far without ending the session
+    pub session_export_request: u32,
+    // bumped to save the duplicated frame as captured, or the window's output as drawn
+    pub capture_screenshot_request: u32,
+    pub output_screenshot_request: u32,
+    // writes the scopes or the capture to an mp4 while on, never persisted
+    pub recording: bool,
+    // the window's output or the desktop behind it
+    pub record_source: u32,
+    // bumped to start the capture self-check, again to restart it
+    pub calibrate_request: u32,
+    pub enable_ddc: bool,
+    pub ddc_brightness: i32,
+    pub ddc_contrast: i32,
+    // what the monitor last reported as brightness and contrast percentages, -1 when unknown,
+    // never persisted
+    pub ddc_readings: [i32; 2],
+    pub report_interval: u32,
+    // the view of the cloud, only whether it is in perspective is saved
+    pub camera: Camera,
+    pub menu_open_count: u32,
+    pub menu_edge: u32,
+    pub menu_zone: i32,
+    // menu text language, empty follows the windows display language
+    pub language: String,
+    // registered system-wide when the window is created
+    pub hotkeys: [Option<Hotkey>; HOTKEY_COUNT],
+    pub alerts: [AlertRule; ALERT_COUNT],
+    // where each scope is drawn, see scope_viewport()
+    pub layouts: [ScopeLayout; LAYOUT_COUNT],
+    // the visible scopes share the window in a grid, their layouts are ignored
+    pub split_screen: bool,
+}
+
+impl Config {
+    // reads the toml file, or migrates the old ini next to it when there is none yet. a file that
+    // exists but doesn't parse is an error, so it isn't replaced by the defaults
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        let file = match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str::<ConfigFile>(&text)
+                .with_context(|| format!("{} is not a valid config", path.display()))?,
+            Err(_) => Ini::load_from_file_noescape(path.with_extension("ini"))
+                .map(|conf| ConfigFile::from_ini(&conf))
+                .unwrap_or_default(),
+        };
+
+        Ok(file.upgrade().into_config())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) {
+        if let Ok(text) = toml::to_string_pretty(&ConfigFile::from_config(self)) {
+            _ = std::fs::write(path, text);
+        }
+    }
+
+    // a preset replaces every persisted setting except where the window sits and the hotkeys
+    pub fn load_preset(&mut self, dir: impl AsRef<Path>, name: &str) -> Result<()> {
+        let path = preset_path(dir, name).context("invalid preset name")?;
+        let text = std::fs::read_to_string(path)?;
+        let preset = toml::from_str::<ConfigFile>(&text)?.upgrade().into_config();
+
+        *self = Config {
+            window_rect: self.window_rect,
+            menu_open_count: self.menu_open_count,
+            freeze: self.freeze,
+            capture_source: self.capture_source,
+            source_path: self.source_path.clone(),
+            video_playing: self.video_playing,
+            video_seek: self.video_seek,
+            video_seek_request: self.video_seek_request,
+            follow_window: self.follow_window,
+            occluded: self.occluded,
+            show_diagnostics: self.show_diagnostics,
+            monitor: self.monitor,
+            camera: Camera {
+                perspective: preset.camera.perspective,
+                ..self.camera
+            },
+            export_request: self.export_request,
+            bookmark_request: self.bookmark_request,
+            session_export_request: self.session_export_request,
+            capture_screenshot_request: self.capture_screenshot_request,
+            output_screenshot_request: self.output_screenshot_request,
+            recording: self.recording,
+            calibrate_request: self.calibrate_request,
+            hotkeys: self.hotkeys,
+            language: self.language.clone(),
+            ..preset
+        };
+
+        Ok(())
+    }
+
+    pub fn save_preset(&self, dir: impl AsRef<Path>, name: &str) -> Result<()> {
+        let path = preset_path(&dir, name).context("invalid preset name")?;
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(
+            path,
+            toml::to_string_pretty(&ConfigFile::from_config(self))?,
+        )?;
+        Ok(())
+    }
+
+    // the part of the window that is analyzed, in desktop coordinates. the roi is window-relative
+    pub fn analysis_rect(&self) -> RECT {
+        let RECT {
+            left,
+            top,
+            right,
+            bottom,
+        } = self.window_rect;
+
+        if !self.enable_roi {
+            return self.window_rect;
+        }
+
+        RECT {
+            left: (left + self.roi.left).clamp(left, right),
+            top: (top + self.roi.top).clamp(top, bottom),
+            right: (left + self.roi.right).clamp(left, right),
+            bottom: (top + self.roi.bottom).clamp(top, bottom),
+        }
+    }
+
+    // 0 renders as fast as duplication delivers frames
+    pub fn frame_interval(&self) -> Option<Duration> {
+        (self.target_fps > 0).then(|| Duration::from_secs(1) / self.target_fps)
+    }
+
+    // nothing is drawn or published, so frames need not be processed
+    pub fn is_idle(&self) -> bool {
+        !(self.enable_filter
+            || self.enable_histogram
+            || self.enable_color_cloud
+            || self.enable_vectorscope
+            || self.enable_hue_luma
+            || self.enable_waveform
+            || self.enable_eyedropper
+            || self.estimate_gamma
+            || self.count_out_of_range
+            || self.enable_uniformity
+            || self.enable_text_contrast
+            || self.enable_frame_pacing
+            || self.enable_frame_difference
+            || self.capture_source != SOURCE_DESKTOP
+            || self.alerts.iter().any(|rule| rule.enable)
+            || self.show_legend
+            || self.show_diagnostics
+            || self.enable_roi
+            || self.compare_outputs
+            || self.enable_reports
+            || self.enable_session
+            || self.recording
+            || self.enable_ddc
+            || self.share_stats)
+    }
+
+    // features that keep a record of the statistics over time
+    pub fn is_logging(&self) -> bool {
+        self.enable_session || self.enable_reports || self.share_stats
+    }
+
+    // turns off what only draws, the histogram and stats behind the logs stay
+    pub fn hide_overlays(&mut self) {
+        self.enable_filter = false;
+        self.enable_color_cloud = false;
+        self.enable_vectorscope = false;
+        self.enable_hue_luma = false;
+        self.enable_waveform = false;
+        self.enable_eyedropper = false;
+        self.enable_text_contrast = false;
+        self.enable_frame_pacing = false;
+        self.enable_frame_difference = false;
+        self.show_legend = false;
+        self.show_diagnostics = false;
+    }
+
+    // the cloud is drawn into a square viewport, so the aspect is handled there
+    pub fn projection_matrix(&self) -> Matrix {
+        self.camera.view_projection(Instant::now())
+    }
+
+    // where the scope at LAYOUT_* draws instead of its default viewport
+    pub fn scope_viewport(&self, scope: usize, default: ViewportKind) -> ViewportKind {
+        if self.split_screen {
+            let scopes = self.visible_scopes();
+            if let Some(index) = scopes.iter().position(|&visible| visible == scope) {
+                let (width, height) = self.window_rect.usize();
+                return ViewportKind::Region {
+                    rect: split(scopes.len(), width, height)[index],
+                    square: matches!(default, ViewportKind::Square { .. }),
+                };
+            }
+        }
+
+        self.layouts[scope].viewport(default)
+    }
+
+    // the square the cloud, its grid and the volume views are drawn in
+    pub fn cloud_viewport(&self) -> ViewportKind {
+        self.scope_viewport(
+            LAYOUT_COLOR_CLOUD,
+            ViewportKind::Square {
+                fill: self.color_cloud_fill,
+                margin: self.color_cloud_margin,
+            },
+        )
+    }
+
+    // in menu order, which is also the order of the split screen cells
+    fn visible_scopes(&self) -> Vec<usize> {
+        [
+            (LAYOUT_COLOR_CLOUD, self.enable_color_cloud),
+            (LAYOUT_HISTOGRAM, self.enable_histogram),
+            (LAYOUT_VECTORSCOPE, self.enable_vectorscope),
+            (LAYOUT_HUE_LUMA, self.enable_hue_luma),
+            (LAYOUT_WAVEFORM, self.enable_waveform),
+        ]
+        .into_iter()
+        .filter_map(|(scope, enabled)| enabled.then_some(scope))
+        .collect()
+    }
+
+    // the orientation part of projection_matrix, with the camera still spinning
+    pub fn view_rotation(&self) -> Matrix {
+        self.camera.rotation_at(Instant::now())
+    }
+}
+
+// presets are full configs stored as <name>.toml in their own directory
+pub fn preset_names(dir: impl AsRef<Path>) -> Vec<String> {
+    let mut names: Vec<_> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+// the source that opens a file, videos are told apart by their extension
+pub fn source_for(path: &Path) -> u32 {
+    const VIDEO: [&str; 7] = ["mp4", "m4v", "mov", "mkv", "avi", "wmv", "webm"];
+
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    if VIDEO.iter().any(|video| video.eq_ignore_ascii_case(ext)) {
+        SOURCE_VIDEO
+    } else {
+        SOURCE_FILE
+    }
+}
+
+pub fn delete_preset(dir: impl AsRef<Path>, name: &str) -> Result<()> {
+    let path = preset_path(dir, name).context("invalid preset name")?;
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+// the name becomes the file name, so nothing that would leave the directory or upset windows
+fn preset_path(dir: impl AsRef<Path>, name: &str) -> Option<PathBuf> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && !name.ends_with('.')
+        && !name
+            .chars()
+            .any(|c| c.is_control() || r#"<>:"/\|?*"#.contains(c));
+
+    valid.then(|| dir.as_ref().join(format!("{name}.toml")))
+}
+
+// bumped when keys are renamed or their meaning changes, see ConfigFile::upgrade
+const CONFIG_VERSION: u32 = 1;
+
+// the persisted part of Config, missing sections and keys take their defaults
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+struct ConfigFile {
+    version: u32,
+    window: WindowSection,
+    filter: FilterSection,
+    histogram: HistogramSection,
+    colorcloud: ColorCloudSection,
+    vectorscope: VectorscopeSection,
+    hue_luma: HueLumaSection,
+    waveform: WaveformSection,
+    roi: RoiSection,
+    analysis: AnalysisSection,
+    lut: LutSection,
+    output: OutputSection,
+    hotkeys: HotkeySection,
+    alerts: AlertSection,
+    layout: LayoutSection,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct WindowSection {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    bg_opacity: f32,
+    bg_mode: u32,
+    bg_color: u32,
+    clear_color: u32,
+    transparency_mode: u32,
+    lock: bool,
+    exclude_from_capture: bool,
+    pause_when_occluded: bool,
+    log_when_occluded: bool,
+    target_fps: u32,
+    msaa_samples: u32,
+    show_legend: bool,
+    menu_open_count: u32,
+    menu_edge: u32,
+    menu_zone: i32,
+    language: String,
+}
+
+impl Default for WindowSection {
+    fn default() -> Self {
+        Self {
+            x: 100,
+            y: 100,
+            width: 1280,
+            height: 720,
+            bg_opacity: 1.0,
+            bg_mode: BACKGROUND_TRANSPARENT,
+            bg_color: 0x202020,
+            clear_color: 0x000000,
+            transparency_mode: TRANSPARENCY_MODE_BLEND,
+            lock: false,
+            exclude_from_capture: true,
+            pause_when_occluded: true,
+            log_when_occluded: true,
+            target_fps: 60,
+            msaa_samples: 1,
+            show_legend: false,
+            menu_open_count: 0,
+            menu_edge: MENU_EDGE_LEFT,
+            menu_zone: 32,
+            language: String::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct FilterSection {
+    enable: bool,
+    mode: u32,
+    zebra_high: f32,
+    zebra_low: f32,
+    desaturation: f32,
+    analyze_filtered: bool,
+}
+
+impl Default for FilterSection {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            mode: FILTER_MODE_RGB,
+            zebra_high: 0.95,
+            zebra_low: 0.05,
+            desaturation: 1.0,
+            analyze_filtered: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct HistogramSection {
+    enable: bool,
+    mode: u32,
+    scale: f32,
+    bins: u32,
+    auto_scale: bool,
+    delta: bool,
+    history: bool,
+    colors: [u32; 4],
+    suggest_levels: bool,
+    preview_levels: bool,
+}
+
+impl Default for HistogramSection {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            mode: HISTOGRAM_MODE_RGB,
+            scale: 0.5,
+            bins: 256,
+            auto_scale: false,
+            delta: false,
+            history: false,
+            colors: [0x0000ff, 0x00ff00, 0xff0000, 0xffffff],
+            suggest_levels: false,
+            preview_levels: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct ColorCloudSection {
+    enable: bool,
+    mode: u32,
+    interleave: bool,
+    show_grid: bool,
+    grid_labels: bool,
+    fill: bool,
+    margin: u32,
+    style: u32,
+    oit: bool,
+    isosurface: bool,
+    iso_level: f32,
+    ramp: [f32; 2],
+    heatmaps: bool,
+    accumulate: bool,
+    age: bool,
+    perspective: bool,
+}
+
+impl Default for ColorCloudSection {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            mode: COLORCLOUD_MODE_RGB,
+            interleave: false,
+            show_grid: false,
+            grid_labels: true,
+            fill: false,
+            margin: 0,
+            style: COLORCLOUD_STYLE_SOLID,
+            oit: false,
+            isosurface: false,
+            iso_level: 0.35,
+            ramp: [0.1, 0.6],
+            heatmaps: false,
+            accumulate: false,
+            age: false,
+            perspective: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct VectorscopeSection {
+    enable: bool,
+    scale: f32,
+    fill: bool,
+    margin: u32,
+    skin_tone: bool,
+}
+
+impl Default for VectorscopeSection {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            scale: 0.5,
+            fill: false,
+            margin: 0,
+            skin_tone: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct HueLumaSection {
+    enable: bool,
+    scale: f32,
+}
+
+impl Default for HueLumaSection {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            scale: 0.5,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct WaveformSection {
+    enable: bool,
+    layout: u32,
+    scale: f32,
+}
+
+impl Default for WaveformSection {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            layout: WAVEFORM_LAYOUT_COMPOSITE,
+            scale: 0.5,
+        }
+    }
+}
+
+// window relative
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct RoiSection {
+    enable: bool,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    compare: bool,
+}
+
+impl Default for RoiSection {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            x: 0,
+            y: 0,
+            width: 320,
+            height: 240,
+            compare: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct AnalysisSection {
+    hdr_mode: bool,
+    source_primaries: u32,
+    source_transfer: u32,
+    sample_frames: u32,
+    reject_outliers: bool,
+    enable_eyedropper: bool,
+    estimate_gamma: bool,
+    count_out_of_range: bool,
+    enable_uniformity: bool,
+    uniformity_grid: u32,
+    enable_text_contrast: bool,
+    enable_frame_pacing: bool,
+    enable_frame_difference: bool,
+    frame_difference_mode: u32,
+    frame_difference_threshold: f32,
+    compare_outputs: bool,
+    compare_output: u32,
+    capture_timeout: u32,
+    reuse_frames: bool,
+    include_cursor: bool,
+    linear_downsample: bool,
+    shared_name: String,
+}
+
+impl Default for AnalysisSection {
+    fn default() -> Self {
+        Self {
+            hdr_mode: false,
+            source_primaries: PRIMARIES_BT709,
+            source_transfer: TRANSFER_SRGB,
+            sample_frames: 1,
+            reject_outliers: true,
+            enable_eyedropper: false,
+            estimate_gamma: false,
+            count_out_of_range: false,
+            enable_uniformity: false,
+            uniformity_grid: 9,
+            enable_text_contrast: false,
+            enable_frame_pacing: false,
+            enable_frame_difference: false,
+            frame_difference_mode: DIFFERENCE_HEATMAP,
+            frame_difference_threshold: 0.05,
+            compare_outputs: false,
+            compare_output: 1,
+            capture_timeout: 100,
+            reuse_frames: true,
+            include_cursor: false,
+            linear_downsample: true,
+            shared_name: String::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+struct LutSection {
+    enable: bool,
+    path: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct OutputSection {
+    share_stats: bool,
+    enable_reports: bool,
+    report_interval: u32,
+    enable_session: bool,
+    session_thumbnails: bool,
+    record_source: u32,
+    enable_ddc: bool,
+}
+
+impl Default for OutputSection {
+    fn default() -> Self {
+        Self {
+            share_stats: false,
+            enable_reports: false,
+            report_interval: 10,
+            enable_session: false,
+            session_thumbnails: true,
+            record_source: RECORD_SCOPES,
+            enable_ddc: false,
+        }
+    }
+}
+
+// written like "Ctrl+Alt+F", empty for none
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct HotkeySection {
+    filter: String,
+    histogram: String,
+    colorcloud: String,
+    freeze: String,
+    export: String,
+    bookmark: String,
+    capture_screenshot: String,
+    output_screenshot: String,
+}
+
+impl Default for HotkeySection {
+    fn default() -> Self {
+        Self {
+            filter: "Ctrl+Alt+1".to_string(),
+            histogram: "Ctrl+Alt+2".to_string(),
+            colorcloud: "Ctrl+Alt+3".to_string(),
+            freeze: "Ctrl+Alt+F".to_string(),
+            export: "Ctrl+Alt+S".to_string(),
+            bookmark: "Ctrl+Alt+B".to_string(),
+            capture_screenshot: "Ctrl+Alt+P".to_string(),
+            output_screenshot: "Ctrl+Alt+O".to_string(),
+        }
+    }
+}
+
+impl HotkeySection {
+    fn from_hotkeys(hotkeys: &[Option<Hotkey>; HOTKEY_COUNT]) -> Self {
+        let text = |i: usize| {
+            hotkeys[i]
+                .map(|hotkey| hotkey.to_string())
+                .unwrap_or_default()
+        };
+
+        Self {
+            filter: text(HOTKEY_FILTER),
+            histogram: text(HOTKEY_HISTOGRAM),
+            colorcloud: text(HOTKEY_COLORCLOUD),
+            freeze: text(HOTKEY_FREEZE),
+            export: text(HOTKEY_EXPORT),
+            bookmark: text(HOTKEY_BOOKMARK),
+            capture_screenshot: text(HOTKEY_CAPTURE_SCREENSHOT),
+            output_screenshot: text(HOTKEY_OUTPUT_SCREENSHOT),
+        }
+    }
+
+    fn into_hotkeys(self) -> [Option<Hotkey>; HOTKEY_COUNT] {
+        [
+            self.filter,
+            self.histogram,
+            self.colorcloud,
+            self.freeze,
+            self.export,
+            self.bookmark,
+            self.capture_screenshot,
+            self.output_screenshot,
+        ]
+        .map(|text| {
+            let hotkey = Hotkey::parse(&text);
+            if hotkey.is_none() && !text.trim().is_empty() {
+                println!("hotkey {text:?} is not understood");
+            }
+            hotkey
+        })
+    }
+}
+
+// what happens when an alert condition starts to hold
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct AlertRule {
+    pub enable: bool,
+    pub sound: bool,
+    // a blinking border around the window
+    pub flash: bool,
+}
+
+impl Default for AlertRule {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            sound: true,
+            flash: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+struct AlertSection {
+    clipping: AlertRule,
+    low_contrast: AlertRule,
+    flicker: AlertRule,
+}
+
+impl AlertSection {
+    fn from_rules(rules: &[AlertRule; ALERT_COUNT]) -> Self {
+        Self {
+            clipping: rules[ALERT_CLIPPING],
+            low_contrast: rules[ALERT_LOW_CONTRAST],
+            flicker: rules[ALERT_FLICKER],
+        }
+    }
+
+    fn into_rules(self) -> [AlertRule; ALERT_COUNT] {
+        [self.clipping, self.low_contrast, self.flicker]
+    }
+}
+
+// a scope's placement, the default one or a corner of the window
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ScopeLayout {
+    // ANCHOR_*
+    pub anchor: u32,
+    // share of the window's width and height
+    pub size: f32,
+}
+
+impl Default for ScopeLayout {
+    fn default() -> Self {
+        Self {
+            anchor: ANCHOR_DEFAULT,
+            size: 0.4,
+        }
+    }
+}
+
+impl ScopeLayout {
+    // left, top, right and bottom normalized to the window, none for the default placement
+    pub fn rect(&self) -> Option<[f32; 4]> {
+        let size = self.size;
+        let (left, top) = match self.anchor {
+            ANCHOR_TOP_LEFT => (0.0, 0.0),
+            ANCHOR_TOP_RIGHT => (1.0 - size, 0.0),
+            ANCHOR_BOTTOM_LEFT => (0.0, 1.0 - size),
+            ANCHOR_BOTTOM_RIGHT => (1.0 - size, 1.0 - size),
+            _ => return None,
+        };
+
+        Some([left, top, left + size, top + size])
+    }
+
+    // where the scope draws, default unless anchored. square scopes stay square
+    pub fn viewport(&self, default: ViewportKind) -> ViewportKind {
+        match self.rect() {
+            Some(rect) => ViewportKind::Region {
+                rect,
+                square: matches!(default, ViewportKind::Square { .. }),
+            },
+            None => default,
+        }
+    }
+
+    fn clamped(self) -> Self {
+        Self {
+            anchor: self.anchor.min(ANCHOR_BOTTOM_RIGHT),
+            size: self.size.clamp(0.1, 1.0),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+struct LayoutSection {
+    split_screen: bool,
+    histogram: ScopeLayout,
+    waveform: ScopeLayout,
+    vectorscope: ScopeLayout,
+    color_cloud: ScopeLayout,
+    hue_luma: ScopeLayout,
+}
+
+impl LayoutSection {
+    fn from_config(config: &Config) -> Self {
+        let layouts = &config.layouts;
+        Self {
+            split_screen: config.split_screen,
+            histogram: layouts[LAYOUT_HISTOGRAM],
+            waveform: layouts[LAYOUT_WAVEFORM],
+            vectorscope: layouts[LAYOUT_VECTORSCOPE],
+            color_cloud: layouts[LAYOUT_COLOR_CLOUD],
+            hue_luma: layouts[LAYOUT_HUE_LUMA],
+        }
+    }
+
+    fn layouts(&self) -> [ScopeLayout; LAYOUT_COUNT] {
+        [
+            self.histogram,
+            self.waveform,
+            self.vectorscope,
+            self.color_cloud,
+            self.hue_luma,
+        ]
+        .map(ScopeLayout::clamped)
+    }
+}
+
+// a system-wide shortcut, MOD_* flags and a virtual key
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hotkey {
+    pub modifiers: u32,
+    pub key: u32,
+}
+
+const HOTKEY_MODIFIERS: [(&str, u32); 4] = [
+    ("Ctrl", MOD_CONTROL.0),
+    ("Alt", MOD_ALT.0),
+    ("Shift", MOD_SHIFT.0),
+    ("Win", MOD_WIN.0),
+];
+
+// keys besides letters, digits and F1-F24
+const HOTKEY_KEYS: [(&str, u16); 9] = [
+    ("Space", VK_SPACE.0),
+    ("PrintScreen", VK_SNAPSHOT.0),
+    ("Pause", VK_PAUSE.0),
+    ("Insert", VK_INSERT.0),
+    ("Delete", VK_DELETE.0),
+    ("Home", VK_HOME.0),
+    ("End", VK_END.0),
+    ("PageUp", VK_PRIOR.0),
+    ("PageDown", VK_NEXT.0),
+];
+
+impl Hotkey {
+    // modifiers and one key joined by '+', case-insensitive
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = 0;
+        let mut key = None;
+
+        for part in text.split('+').map(str::trim) {
+            let modifier = HOTKEY_MODIFIERS
+                .iter()
+                .find(|(name, _)| part.eq_ignore_ascii_case(name));
+
+            match modifier {
+                Some(&(_, modifier)) => modifiers |= modifier,
+                None if key.is_none() => key = Some(Self::key_code(part)?),
+                None => return None,
+            }
+        }
+
+        Some(Self {
+            modifiers,
+            key: key?,
+        })
+    }
+
+    fn key_code(name: &str) -> Option<u32> {
+        let upper = name.to_ascii_uppercase();
+
+        if let [c] = upper.as_bytes() {
+            return c.is_ascii_alphanumeric().then_some(*c as u32);
+        }
+
+        if let Some(n) = upper.strip_prefix('F').and_then(|n| n.parse::<u16>().ok()) {
+            return (1..=24).contains(&n).then_some((VK_F1.0 + n - 1) as u32);
+        }
+
+        HOTKEY_KEYS
+            .iter()
+            .find(|(key, _)| name.eq_ignore_ascii_case(key))
+            .map(|&(_, code)| code as u32)
+    }
+
+    fn key_name(key: u32) -> String {
+        let f1 = VK_F1.0 as u32;
+
+        match key {
+            0x30..=0x39 | 0x41..=0x5a => char::from(key as u8).to_string(),
+            key if (f1..f1 + 24).contains(&key) => format!("F{}", key - f1 + 1),
+            key => HOTKEY_KEYS
+                .iter()
+                .find(|&&(_, code)| code as u32 == key)
+                .map_or(format!("{key:#x}"), |(name, _)| name.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, modifier) in HOTKEY_MODIFIERS {
+            if self.modifiers & modifier != 0 {
+                write!(f, "{name}+")?;
+            }
+        }
+        write!(f, "{}", Self::key_name(self.key))
+    }
+}
+
+impl ConfigFile {
+    // nothing has changed since version 1, older files only get the current version
+    fn upgrade(mut self) -> Self {
+        if self.version > CONFIG_VERSION {
+            println!(
+                "config version {} is newer than {CONFIG_VERSION}, unknown keys are ignored",
+                self.version
+            );
+        }
+        self.version = CONFIG_VERSION;
+        self
+    }
+
+    // the flat colormel.ini used before the toml file, keys missing there keep their defaults
+    fn from_ini(conf: &Ini) -> Self {
+        let window = WindowSection::default();
+        let filter = FilterSection::default();
+        let histogram = HistogramSection::default();
+        let colorcloud = ColorCloudSection::default();
+        let vectorscope = VectorscopeSection::default();
+        let waveform = WaveformSection::default();
+        let roi = RoiSection::default();
+        let analysis = AnalysisSection::default();
+        let output = OutputSection::default();
+
+        Self {
+            version: CONFIG_VERSION,
+            window: WindowSection {
+                x: conf.get_i32("window-x", window.x),
+                y: conf.get_i32("window-y", window.y),
+                width: conf.get_i32("window-width", window.width),
+                height: conf.get_i32("window-height", window.height),
+                bg_opacity: conf.get_f32("bg-opacity", window.bg_opacity),
+                bg_mode: window.bg_mode,
+                bg_color: window.bg_color,
+                clear_color: window.clear_color,
+                transparency_mode: conf.get_u32("transparency-mode", window.transparency_mode),
+                lock: conf.get_bool("lock-window", window.lock),
+                exclude_from_capture: conf
+                    .get_bool("exclude-from-capture", window.exclude_from_capture),
+                pause_when_occluded: conf
+                    .get_bool("pause-when-occluded", window.pause_when_occluded),
+                log_when_occluded: conf.get_bool("log-when-occluded", window.log_when_occluded),
+                target_fps: conf.get_u32("target-fps", window.target_fps),
+                msaa_samples: conf.get_u32("msaa-samples", window.msaa_samples),
+                show_legend: conf.get_bool("show-legend", window.show_legend),
+                menu_open_count: conf.get_u32("menu-open-count", window.menu_open_count),
+                menu_edge: conf.get_u32("menu-edge", window.menu_edge),
+                menu_zone: conf.get_i32("menu-zone", window.menu_zone),
+                language: window.language,
+            },
+            filter: FilterSection {
+                enable: conf.get_bool("enable-filter", filter.enable),
+                mode: conf.get_u32("filter-mode", filter.mode),
+                zebra_high: conf.get_f32("zebra-high", filter.zebra_high),
+                zebra_low: conf.get_f32("zebra-low", filter.zebra_low),
+                desaturation: filter.desaturation,
+                analyze_filtered: conf.get_bool("analyze-filtered", filter.analyze_filtered),
+            },
+            histogram: HistogramSection {
+                enable: conf.get_bool("enable-histogram", histogram.enable),
+                mode: conf.get_u32("histogram-mode", histogram.mode),
+                scale: conf.get_f32("histogram-scale", histogram.scale),
+                bins: histogram.bins,
+                auto_scale: histogram.auto_scale,
+                delta: histogram.delta,
+                history: histogram.history,
+                colors: histogram.colors,
+                suggest_levels: histogram.suggest_levels,
+                preview_levels: histogram.preview_levels,
+            },
+            colorcloud: ColorCloudSection {
+                enable: conf.get_bool("enable-color-cloud", colorcloud.enable),
+                mode: conf.get_u32("color-cloud-mode", colorcloud.mode),
+                interleave: conf.get_bool("color-cloud-interleave", colorcloud.interleave),
+                show_grid: conf.get_bool("show-grid", colorcloud.show_grid),
+                grid_labels: colorcloud.grid_labels,
+                fill: conf.get_bool("color-cloud-fill", colorcloud.fill),
+                margin: conf.get_u32("color-cloud-margin", colorcloud.margin),
+                style: conf.get_u32("color-cloud-style", colorcloud.style),
+                oit: conf.get_bool("color-cloud-oit", colorcloud.oit),
+                isosurface: colorcloud.isosurface,
+                iso_level: colorcloud.iso_level,
+                ramp: colorcloud.ramp,
+                heatmaps: colorcloud.heatmaps,
+                accumulate: colorcloud.accumulate,
+                age: colorcloud.age,
+                perspective: colorcloud.perspective,
+            },
+            vectorscope: VectorscopeSection {
+                enable: conf.get_bool("enable-vectorscope", vectorscope.enable),
+                scale: conf.get_f32("vectorscope-scale", vectorscope.scale),
+                fill: conf.get_bool("vectorscope-fill", vectorscope.fill),
+                margin: conf.get_u32("vectorscope-margin", vectorscope.margin),
+                skin_tone: vectorscope.skin_tone,
+            },
+            hue_luma: HueLumaSection::default(),
+            waveform: WaveformSection {
+                enable: conf.get_bool("enable-waveform", waveform.enable),
+                layout: conf.get_u32("waveform-layout", waveform.layout),
+                scale: conf.get_f32("waveform-scale", waveform.scale),
+            },
+            roi: RoiSection {
+                enable: conf.get_bool("enable-roi", roi.enable),
+                x: conf.get_i32("roi-x", roi.x),
+                y: conf.get_i32("roi-y", roi.y),
+                width: conf.get_i32("roi-width", roi.width),
+                height: conf.get_i32("roi-height", roi.height),
+                compare: conf.get_bool("compare-roi", roi.compare),
+            },
+            analysis: AnalysisSection {
+                hdr_mode: conf.get_bool("hdr-mode", analysis.hdr_mode),
+                source_primaries: analysis.source_primaries,
+                source_transfer: analysis.source_transfer,
+                sample_frames: conf.get_u32("sample-frames", analysis.sample_frames),
+                reject_outliers: conf.get_bool("reject-outliers", analysis.reject_outliers),
+                enable_eyedropper: conf.get_bool("enable-eyedropper", analysis.enable_eyedropper),
+                estimate_gamma: analysis.estimate_gamma,
+                count_out_of_range: analysis.count_out_of_range,
+                enable_uniformity: analysis.enable_uniformity,
+                uniformity_grid: analysis.uniformity_grid,
+                enable_text_contrast: analysis.enable_text_contrast,
+                enable_frame_pacing: analysis.enable_frame_pacing,
+                enable_frame_difference: analysis.enable_frame_difference,
+                frame_difference_mode: analysis.frame_difference_mode,
+                frame_difference_threshold: analysis.frame_difference_threshold,
+                compare_outputs: conf.get_bool("compare-outputs", analysis.compare_outputs),
+                compare_output: conf.get_u32("compare-output", analysis.compare_output),
+                capture_timeout: analysis.capture_timeout,
+                reuse_frames: analysis.reuse_frames,
+                include_cursor: analysis.include_cursor,
+                linear_downsample: analysis.linear_downsample,
+                shared_name: analysis.shared_name,
+            },
+            lut: LutSection {
+                enable: conf.get_bool("enable-lut", false),
+                path: conf.get_path("lut-path"),
+            },
+            output: OutputSection {
+                share_stats: conf.get_bool("share-stats", output.share_stats),
+                enable_reports: conf.get_bool("enable-reports", output.enable_reports),
+                report_interval: conf.get_u32("report-interval", output.report_interval),
+                enable_session: output.enable_session,
+                session_thumbnails: output.session_thumbnails,
+                record_source: output.record_source,
+                enable_ddc: conf.get_bool("enable-ddc", output.enable_ddc),
+            },
+            hotkeys: HotkeySection::default(),
+            alerts: AlertSection::default(),
+            layout: LayoutSection::default(),
+        }
+    }
+
+    fn from_config(config: &Config) -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            window: WindowSection {
+                x: config.window_rect.left,
+                y: config.window_rect.top,
+                width: config.window_rect.width(),
+                height: config.window_rect.height(),
+                bg_opacity: config.bg_opacity,
+                bg_mode: config.bg_mode,
+                bg_color: config.bg_color,
+                clear_color: config.clear_color,
+                transparency_mode: config.transparency_mode,
+                lock: config.lock_window,
+                exclude_from_capture: config.exclude_from_capture,
+                pause_when_occluded: config.pause_when_occluded,
+                log_when_occluded: config.log_when_occluded,
+                target_fps: config.target_fps,
+                msaa_samples: config.msaa_samples,
+                show_legend: config.show_legend,
+                menu_open_count: config.menu_open_count,
+                menu_edge: config.menu_edge,
+                menu_zone: config.menu_zone,
+                language: config.language.clone(),
+            },
+            filter: FilterSection {
+                enable: config.enable_filter,
+                mode: config.filter_mode,
+                zebra_high: config.zebra_high,
+                zebra_low: config.zebra_low,
+                desaturation: config.desaturation,
+                analyze_filtered: config.analyze_filtered,
+            },
+            histogram: HistogramSection {
+                enable: config.enable_histogram,
+                mode: config.histogram_mode,
+                scale: config.histogram_scale,
+                bins: config.histogram_bins,
+                auto_scale: config.histogram_auto_scale,
+                delta: config.histogram_delta,
+                history: config.histogram_history,
+                colors: config.histogram_colors,
+                suggest_levels: config.suggest_levels,
+                preview_levels: config.preview_levels,
+            },
+            colorcloud: ColorCloudSection {
+                enable: config.enable_color_cloud,
+                mode: config.color_cloud_mode,
+                interleave: config.color_cloud_interleave,
+                show_grid: config.show_grid,
+                grid_labels: config.grid_labels,
+                fill: config.color_cloud_fill,
+                margin: config.color_cloud_margin,
+                style: config.color_cloud_style,
+                oit: config.color_cloud_oit,
+                isosurface: config.color_cloud_isosurface,
+                iso_level: config.color_cloud_iso_level,
+                ramp: config.color_cloud_ramp,
+                heatmaps: config.color_cloud_heatmaps,
+                accumulate: config.color_cloud_accumulate,
+                age: config.color_cloud_age,
+                perspective: config.camera.perspective,
+            },
+            vectorscope: VectorscopeSection {
+                enable: config.enable_vectorscope,
+                scale: config.vectorscope_scale,
+                fill: config.vectorscope_fill,
+                margin: config.vectorscope_margin,
+                skin_tone: config.vectorscope_skin_tone,
+            },
+            hue_luma: HueLumaSection {
+                enable: config.enable_hue_luma,
+                scale: config.hue_luma_scale,
+            },
+            waveform: WaveformSection {
+                enable: config.enable_waveform,
+                layout: config.waveform_layout,
+                scale: config.waveform_scale,
+            },
+            roi: RoiSection {
+                enable: config.enable_roi,
+                x: config.roi.left,
+                y: config.roi.top,
+                width: config.roi.width(),
+                height: config.roi.height(),
+                compare: config.compare_roi,
+            },
+            analysis: AnalysisSection {
+                hdr_mode: config.hdr_mode,
+                source_primaries: config.source_primaries,
+                source_transfer: config.source_transfer,
+                sample_frames: config.sample_frames,
+                reject_outliers: config.reject_outliers,
+                enable_eyedropper: config.enable_eyedropper,
+                estimate_gamma: config.estimate_gamma,
+                count_out_of_range: config.count_out_of_range,
+                enable_uniformity: config.enable_uniformity,
+                uniformity_grid: config.uniformity_grid,
+                enable_text_contrast: config.enable_text_contrast,
+                enable_frame_pacing: config.enable_frame_pacing,
+                enable_frame_difference: config.enable_frame_difference,
+                frame_difference_mode: config.frame_difference_mode,
+                frame_difference_threshold: config.frame_difference_threshold,
+                compare_outputs: config.compare_outputs,
+                compare_output: config.compare_output,
+                capture_timeout: config.capture_timeout,
+                reuse_frames: config.reuse_frames,
+                include_cursor: config.include_cursor,
+                linear_downsample: config.linear_downsample,
+                shared_name: config.shared_name.clone(),
+            },
+            lut: LutSection {
+                enable: config.enable_lut,
+                path: config.lut_path.clone(),
+            },
+            output: OutputSection {
+                share_stats: config.share_stats,
+                enable_reports: config.enable_reports,
+                report_interval: config.report_interval,
+                enable_session: config.enable_session,
+                session_thumbnails: config.session_thumbnails,
+                record_source: config.record_source,
+                enable_ddc: config.enable_ddc,
+            },
+            hotkeys: HotkeySection::from_hotkeys(&config.hotkeys),
+            alerts: AlertSection::from_rules(&config.alerts),
+            layout: LayoutSection::from_config(config),
+        }
+    }
+
+    // values out of range are clamped, runtime state starts over
+    fn into_config(self) -> Config {
+        let Self {
+            window,
+            filter,
+            histogram,
+            colorcloud,
+            vectorscope,
+            hue_luma,
+            waveform,
+            roi,
+            analysis,
+            lut,
+            output,
+            hotkeys,
+            alerts,
+            layout,
+            ..
+        } = self;
+
+        Config {
+            enable_filter: filter.enable,
+            filter_mode: filter.mode,
+            filter_channels: [true; 4],
+            filter_solo: false,
+            zebra_high: filter.zebra_high,
+            zebra_low: filter.zebra_low,
+            desaturation: filter.desaturation.clamp(0.0, 1.0),
+            analyze_filtered: filter.analyze_filtered,
+            enable_histogram: histogram.enable,
+            histogram_mode: histogram.mode,
+            histogram_scale: histogram.scale,
+            histogram_bins: histogram.bins,
+            histogram_auto_scale: histogram.auto_scale,
+            histogram_delta: histogram.delta,
+            histogram_history: histogram.history,
+            histogram_colors: histogram.colors.map(|color| color & 0xffffff),
+            suggest_levels: histogram.suggest_levels,
+            preview_levels: histogram.preview_levels,
+            suggested_levels: None,
+            export_request: 0,
+            bookmark_request: 0,
+            session_export_request: 0,
+            capture_screenshot_request: 0,
+            output_screenshot_request: 0,
+            recording: false,
+            record_source: output.record_source.min(RECORD_CAPTURE),
+            calibrate_request: 0,
+            enable_color_cloud: colorcloud.enable,
+            color_cloud_mode: colorcloud.mode.min(COLORCLOUD_MODE_OKLAB),
+            color_cloud_interleave: colorcloud.interleave,
+            show_grid: colorcloud.show_grid,
+            grid_labels: colorcloud.grid_labels,
+            color_cloud_fill: colorcloud.fill,
+            color_cloud_margin: colorcloud.margin,
+            color_cloud_style: colorcloud.style,
+            color_cloud_oit: colorcloud.oit,
+            color_cloud_isosurface: colorcloud.isosurface,
+            color_cloud_iso_level: colorcloud.iso_level.clamp(0.0, 1.0),
+            color_cloud_ramp: colorcloud.ramp.map(|v| v.clamp(0.0, 1.0)),
+            color_cloud_heatmaps: colorcloud.heatmaps,
+            color_cloud_accumulate: colorcloud.accumulate,
+            color_cloud_age: colorcloud.age,
+            transparency_mode: window.transparency_mode,
+            enable_vectorscope: vectorscope.enable,
+            vectorscope_scale: vectorscope.scale,
+            vectorscope_fill: vectorscope.fill,
+            vectorscope_margin: vectorscope.margin,
+            vectorscope_skin_tone: vectorscope.skin_tone,
+            enable_hue_luma: hue_luma.enable,
+            hue_luma_scale: hue_luma.scale.clamp(0.0, 1.0),
+            enable_waveform: waveform.enable,
+            waveform_layout: waveform.layout,
+            waveform_scale: waveform.scale,
+            bg_opacity: window.bg_opacity,
+            bg_mode: window.bg_mode.min(BACKGROUND_CHECKER),
+            bg_color: window.bg_color & 0xffffff,
+            clear_color: window.clear_color & 0xffffff,
+            window_rect: RECT::new(
+                window.x.max(0),
+                window.y.max(0),
+                window.width.max(0),
+                window.height.max(0),
+            ),
+            enable_roi: roi.enable,
+            roi: RECT::new(
+                roi.x.max(0),
+                roi.y.max(0),
+                roi.width.max(0),
+                roi.height.max(0),
+            ),
+            compare_roi: roi.compare,
+            roi_difference: None,
+            compare_outputs: analysis.compare_outputs,
+            compare_output: analysis.compare_output,
+            output_difference: None,
+            enable_eyedropper: analysis.enable_eyedropper,
+            estimate_gamma: analysis.estimate_gamma,
+            estimated_gamma: None,
+            count_out_of_range: analysis.count_out_of_range,
+            out_of_range: None,
+            enable_uniformity: analysis.enable_uniformity,
+            uniformity_grid: analysis.uniformity_grid,
+            enable_text_contrast: analysis.enable_text_contrast,
+            enable_frame_pacing: analysis.enable_frame_pacing,
+            enable_frame_difference: analysis.enable_frame_difference,
+            frame_difference_mode: analysis.frame_difference_mode.min(DIFFERENCE_THRESHOLD),
+            frame_difference_threshold: analysis.frame_difference_threshold.clamp(0.0, 1.0),
+            enable_lut: lut.enable,
+            lut_path: lut.path.filter(|path| !path.as_os_str().is_empty()),
+            pick_point: None,
+            hdr_mode: analysis.hdr_mode,
+            source_primaries: analysis.source_primaries.min(PRIMARIES_BT2020),
+            source_transfer: analysis.source_transfer.min(TRANSFER_BT2020),
+            freeze: false,
+            capture_source: SOURCE_DESKTOP,
+            source_path: None,
+            video_playing: true,
+            video_seek: 0.0,
+            video_seek_request: 0,
+            video_time: None,
+            lock_window: window.lock,
+            follow_window: false,
+            exclude_from_capture: window.exclude_from_capture,
+            pause_when_occluded: window.pause_when_occluded,
+            log_when_occluded: window.log_when_occluded,
+            occluded: false,
+            target_fps: window.target_fps,
+            capture_timeout: analysis.capture_timeout.min(1000),
+            reuse_frames: analysis.reuse_frames,
+            include_cursor: analysis.include_cursor,
+            linear_downsample: analysis.linear_downsample,
+            shared_name: analysis.shared_name,
+            msaa_samples: window.msaa_samples,
+            share_stats: output.share_stats,
+            show_legend: window.show_legend,
+            show_diagnostics: false,
+            monitor: 0,
+            sample_frames: analysis.sample_frames.clamp(1, 60),
+            reject_outliers: analysis.reject_outliers,
+            enable_reports: output.enable_reports,
+            enable_session: output.enable_session,
+            session_thumbnails: output.session_thumbnails,
+            enable_ddc: output.enable_ddc,
+            ddc_brightness: -1,
+            ddc_contrast: -1,
+            ddc_readings: [-1, -1],
+            report_interval: output.report_interval.clamp(1, 60),
+            camera: Camera {
+                perspective: colorcloud.perspective,
+                ..Camera::default()
+            },
+            menu_open_count: window.menu_open_count,
+            menu_edge: window.menu_edge,
+            menu_zone: window.menu_zone.clamp(8, 128),
+            language: window.language,
+            hotkeys: hotkeys.into_hotkeys(),
+            alerts: alerts.into_rules(),
+            layouts: layout.layouts(),
+            split_screen: layout.split_screen,
+        }
+    }
+}
+
+// reads the old ini during migration
+trait IniGetter {
+    fn get_bool(&self, key: &str, default: bool) -> bool;
+    fn get_i32(&self, key: &str, default: i32) -> i32;
+    fn get_u32(&self, key: &str, default: u32) -> u32;
+    fn get_f32(&self, key: &str, default: f32) -> f32;
+    fn get_path(&self, key: &str) -> Option<PathBuf>;
+}
+
+impl IniGetter for Ini {
+    fn get_bool(&self, key: &str, default: bool) -> bool {
+        self.get_from::<String>(None, key)
+            .map_or(default, |value| value == "1")
+    }
+
+    fn get_i32(&self, key: &str, default: i32) -> i32 {
+        self.get_from::<String>(None, key)
+            .unwrap_or_default()
+            .parse::<i32>()
+            .unwrap_or(default)
+    }
+
+    fn get_u32(&self, key: &str, default: u32) -> u32 {
+        self.get_from::<String>(None, key)
+            .unwrap_or_default()
+            .parse::<u32>()
+            .unwrap_or(default)
+    }
+
+    fn get_f32(&self, key: &str, default: f32) -> f32 {
+        self.get_from::<String>(None, key)
+            .unwrap_or_default()
+            .parse::<f32>()
+            .unwrap_or(default)
+    }
+
+    fn get_path(&self, key: &str) -> Option<PathBuf> {
+        self.get_from::<String>(None, key)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::math::Vec4;
+
+    use super::*;
+
+    const EPS: f32 = 1e-5;
+
+    fn config() -> Config {
+        let mut config = Config::load("").unwrap();
+        config.camera = Camera::default();
+        config
+    }
+
+    // saved and read back through the toml file
+    fn round_trip(config: &Config) -> Config {
+        let text = toml::to_string_pretty(&ConfigFile::from_config(config)).unwrap();
+        parse(&text)
+    }
+
+    fn parse(text: &str) -> Config {
+        toml::from_str::<ConfigFile>(text).unwrap().into_config()
+    }
+
+    fn project(config: &Config, x: f32, y: f32, z: f32) -> Vec4 {
+        config
+            .projection_matrix()
+            .transform(&Vec4::new(x, y, z, 1.0))
+    }
+
+    fn assert_vec_eq(a: &Vec4, b: &Vec4) {
+        for i in 0..4 {
+            assert!((a[i] - b[i]).abs() < EPS, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn projection_golden() {
+        let config = config();
+        let s = 0.9;
+
+        assert_vec_eq(
+            &project(&config, 0.0, 0.0, 0.0),
+            &Vec4::new(0.0, 0.0, 0.5, 1.0),
+        );
+        assert_vec_eq(
+            &project(&config, 1.0, 0.0, 0.0),
+            &Vec4::new(s, 0.0, 0.5, 1.0),
+        );
+        assert_vec_eq(
+            &project(&config, 0.0, -1.0, 0.0),
+            &Vec4::new(0.0, -s, 0.5, 1.0),
+        );
+        assert_vec_eq(
+            &project(&config, 0.0, 0.0, 1.0),
+            &Vec4::new(0.0, 0.0, 0.75, 1.0),
+        );
+        assert_vec_eq(
+            &project(&config, 0.0, 0.0, -1.0),
+            &Vec4::new(0.0, 0.0, 0.25, 1.0),
+        );
+    }
+
+    #[test]
+    fn projection_applies_rotation_before_scale() {
+        let mut config = config();
+        config.camera.rotation = Matrix::rot_y(std::f32::consts::FRAC_PI_2);
+        let s = 0.9;
+
+        assert_vec_eq(
+            &project(&config, 0.0, 0.0, 1.0),
+            &Vec4::new(s, 0.0, 0.5, 1.0),
+        );
+    }
+
+    #[test]
+    fn analysis_rect_follows_window() {
+        let mut config = config();
+        config.window_rect = RECT::new(100, 50, 640, 480);
+        config.roi = RECT::new(10, 20, 100, 200);
+
+        config.enable_roi = false;
+        assert_eq!(config.analysis_rect(), config.window_rect);
+
+        config.enable_roi = true;
+        assert_eq!(config.analysis_rect(), RECT::new(110, 70, 100, 200));
+    }
+
+    #[test]
+    fn analysis_rect_is_clamped_to_window() {
+        let mut config = config();
+        config.window_rect = RECT::new(0, 0, 640, 480);
+        config.enable_roi = true;
+
+        config.roi = RECT::new(600, 400, 100, 200);
+        assert_eq!(config.analysis_rect(), RECT::new(600, 400, 40, 80));
+
+        config.roi = RECT::new(700, 0, 100, 100);
+        assert_eq!(config.analysis_rect().width(), 0);
+    }
+
+    #[test]
+    fn frame_interval_follows_target_fps() {
+        let mut config = config();
+
+        config.target_fps = 0;
+        assert_eq!(config.frame_interval(), None);
+
+        config.target_fps = 50;
+        assert_eq!(config.frame_interval(), Some(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn idle_without_any_output() {
+        let mut config = config();
+        assert!(config.is_idle());
+
+        config.enable_waveform = true;
+        assert!(!config.is_idle());
+
+        config.enable_waveform = false;
+        config.share_stats = true;
+        assert!(!config.is_idle());
+    }
+
+    #[test]
+    fn projection_keeps_depth_in_range() {
+        let mut config = config();
+        config.camera.rotation = Matrix::rot_x(0.7).mul(&Matrix::rot_y(-1.3));
+
+        for (x, y, z) in [(1.0, 1.0, 1.0), (-1.0, -1.0, -1.0), (1.0, -1.0, 1.0)] {
+            let v = Vec4::new(x, y, z, 1.0);
+            let len = (x * x + y * y + z * z).sqrt();
+            let p = project(&config, x / len, y / len, z / len);
+            assert!((0.25 - EPS..=0.75 + EPS).contains(&p.z()), "{v} -> {p}");
+        }
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let mut config = config();
+        config.window_rect = RECT::new(0, 0, 800, 600);
+        config.enable_histogram = true;
+        config.histogram_scale = 0.75;
+        config.color_cloud_style = 2;
+        config.roi = RECT::new(10, 20, 100, 50);
+        config.lut_path = Some(PathBuf::from("looks/warm.cube"));
+
+        let text = toml::to_string_pretty(&ConfigFile::from_config(&config)).unwrap();
+        let file = toml::from_str::<ConfigFile>(&text).unwrap();
+        assert_eq!(file.version, CONFIG_VERSION);
+
+        let loaded = file.into_config();
+        assert!(loaded.enable_histogram);
+        assert_eq!(loaded.histogram_scale, 0.75);
+        assert_eq!(loaded.color_cloud_style, 2);
+        assert_eq!(loaded.window_rect.width(), 800);
+        assert_eq!(loaded.roi.left, 10);
+        assert_eq!(loaded.roi.height(), 50);
+        assert_eq!(loaded.lut_path, config.lut_path);
+    }
+
+    #[test]
+    fn partial_toml_keeps_defaults() {
+        let text = "version = 1\n[histogram]\nenable = true\n[window]\nmenu-zone = 500\n";
+        let config = parse(text);
+
+        assert!(config.enable_histogram);
+        assert_eq!(config.histogram_scale, 0.5);
+        assert_eq!(config.menu_zone, 128);
+        assert_eq!(config.target_fps, 60);
+        assert!(config.exclude_from_capture);
+        assert_eq!(config.lut_path, None);
+    }
+
+    #[test]
+    fn unparsable_file_is_an_error() {
+        let path = std::env::temp_dir().join(format!("colormel-bad-{}.toml", std::process::id()));
+        std::fs::write(&path, "version = 1\n[histogram\nenable = true\n").unwrap();
+
+        assert!(Config::load(&path).is_err());
+        // a missing file is a fresh start
+        _ = std::fs::remove_file(&path);
+        assert!(Config::load(&path).is_ok());
+    }
+
+    #[test]
+    fn window_section_round_trip() {
+        let mut config = config();
+        config.bg_mode = BACKGROUND_CHECKER;
+        config.bg_color = 0x804020;
+        config.clear_color = 0x101010;
+        config.log_when_occluded = false;
+
+        let loaded = round_trip(&config);
+        assert_eq!(loaded.bg_mode, BACKGROUND_CHECKER);
+        assert_eq!(loaded.bg_color, 0x804020);
+        assert_eq!(loaded.clear_color, 0x101010);
+        assert!(!loaded.log_when_occluded);
+
+        let defaults = parse("version = 1\n[window]\n");
+        assert_eq!(defaults.bg_mode, BACKGROUND_TRANSPARENT);
+        assert_eq!(defaults.clear_color, 0);
+        assert_eq!(defaults.language, "");
+        assert!(defaults.log_when_occluded);
+    }
+
+    #[test]
+    fn filter_section_round_trip() {
+        let mut config = config();
+        config.filter_mode = FILTER_MODE_MONO;
+        config.desaturation = 0.4;
+
+        let loaded = round_trip(&config);
+        assert_eq!(loaded.filter_mode, FILTER_MODE_MONO);
+        assert_eq!(loaded.desaturation, 0.4);
+    }
+
+    #[test]
+    fn histogram_section_round_trip() {
+        let mut config = config();
+        config.histogram_auto_scale = true;
+        config.histogram_bins = 1024;
+        config.histogram_history = true;
+        config.histogram_colors[3] = 0x00ffff;
+        config.suggest_levels = true;
+
+        let loaded = round_trip(&config);
+        assert!(loaded.histogram_auto_scale);
+        assert_eq!(loaded.histogram_bins, 1024);
+        assert!(loaded.histogram_history);
+        assert_eq!(loaded.histogram_colors[3], 0x00ffff);
+        assert!(loaded.suggest_levels);
+        assert!(!loaded.preview_levels);
+
+        let defaults = parse("version = 1\n[histogram]\nenable = true\n");
+        assert!(!defaults.histogram_auto_scale);
+        assert_eq!(defaults.histogram_bins, 256);
+        assert!(!defaults.histogram_history);
+        assert_eq!(
+            defaults.histogram_colors,
+            [0x0000ff, 0x00ff00, 0xff0000, 0xffffff]
+        );
+    }
+
+    #[test]
+    fn colorcloud_section_round_trip() {
+        let mut config = config();
+        config.color_cloud_isosurface = true;
+        config.color_cloud_iso_level = 0.6;
+        config.color_cloud_ramp = [0.2, 0.8];
+        config.camera.perspective = true;
+        config.grid_labels = false;
+
+        let loaded = round_trip(&config);
+        assert!(loaded.color_cloud_isosurface);
+        assert_eq!(loaded.color_cloud_iso_level, 0.6);
+        assert_eq!(loaded.color_cloud_ramp, [0.2, 0.8]);
+        assert!(loaded.camera.perspective);
+        assert_eq!(loaded.camera.rotation, Matrix::identity());
+        assert!(!loaded.grid_labels);
+
+        let defaults = parse("version = 1\n[colorcloud]\n");
+        assert_eq!(defaults.color_cloud_iso_level, 0.35);
+        assert_eq!(defaults.color_cloud_ramp, [0.1, 0.6]);
+        assert!(!defaults.camera.perspective);
+        assert!(defaults.grid_labels);
+    }
+
+    #[test]
+    fn scope_sections_round_trip() {
+        let mut config = config();
+        config.enable_hue_luma = true;
+        config.hue_luma_scale = 0.25;
+        config.vectorscope_skin_tone = false;
+
+        let loaded = round_trip(&config);
+        assert!(loaded.enable_hue_luma);
+        assert_eq!(loaded.hue_luma_scale, 0.25);
+        assert!(!loaded.vectorscope_skin_tone);
+
+        let defaults = parse("version = 1\n");
+        assert_eq!(defaults.hue_luma_scale, 0.5);
+        assert!(defaults.vectorscope_skin_tone);
+    }
+
+    #[test]
+    fn analysis_scans_round_trip() {
+        let mut config = config();
+        config.estimate_gamma = true;
+        config.count_out_of_range = true;
+        config.enable_uniformity = true;
+        config.uniformity_grid = 5;
+        config.enable_text_contrast = true;
+        config.enable_frame_pacing = true;
+        config.enable_frame_difference = true;
+        config.frame_difference_mode = DIFFERENCE_THRESHOLD;
+        config.frame_difference_threshold = 0.2;
+
+        let loaded = round_trip(&config);
+        assert!(loaded.estimate_gamma);
+        assert!(loaded.count_out_of_range);
+        assert!(loaded.enable_uniformity);
+        assert_eq!(loaded.uniformity_grid, 5);
+        assert!(loaded.enable_text_contrast);
+        assert!(loaded.enable_frame_pacing);
+        assert!(loaded.enable_frame_difference);
+        assert_eq!(loaded.frame_difference_mode, DIFFERENCE_THRESHOLD);
+        assert_eq!(loaded.frame_difference_threshold, 0.2);
+
+        let defaults = parse("version = 1\n[analysis]\n");
+        assert!(!defaults.estimate_gamma);
+        assert!(!defaults.count_out_of_range);
+        assert!(!defaults.enable_uniformity);
+        assert_eq!(defaults.uniformity_grid, 9);
+        assert!(!defaults.enable_text_contrast);
+        assert!(!defaults.enable_frame_pacing);
+        assert!(!defaults.enable_frame_difference);
+        assert_eq!(defaults.frame_difference_mode, DIFFERENCE_HEATMAP);
+        assert_eq!(defaults.frame_difference_threshold, 0.05);
+    }
+
+    #[test]
+    fn analysis_sources_round_trip() {
+        let mut config = config();
+        config.include_cursor = true;
+        config.linear_downsample = false;
+        config.shared_name = "Arena - Composition".to_string();
+        config.source_primaries = PRIMARIES_BT2020;
+        config.source_transfer = TRANSFER_BT709;
+        config.capture_timeout = 250;
+        config.reuse_frames = false;
+
+        let loaded = round_trip(&config);
+        assert!(loaded.include_cursor);
+        assert!(!loaded.linear_downsample);
+        assert_eq!(loaded.shared_name, "Arena - Composition");
+        assert_eq!(loaded.source_primaries, PRIMARIES_BT2020);
+        assert_eq!(loaded.source_transfer, TRANSFER_BT709);
+        assert_eq!(loaded.capture_timeout, 250);
+        assert!(!loaded.reuse_frames);
+
+        let defaults = parse("version = 1\n[analysis]\ncapture-timeout = 5000\n");
+        assert!(!defaults.include_cursor);
+        assert!(defaults.linear_downsample);
+        assert_eq!(defaults.shared_name, "");
+        assert_eq!(defaults.source_primaries, PRIMARIES_BT709);
+        assert_eq!(defaults.source_transfer, TRANSFER_SRGB);
+        assert_eq!(defaults.capture_timeout, 1000);
+        assert!(defaults.reuse_frames);
+    }
+
+    #[test]
+    fn output_section_round_trip() {
+        let mut config = config();
+        config.enable_session = true;
+        config.record_source = RECORD_CAPTURE;
+        config.session_thumbnails = false;
+
+        let loaded = round_trip(&config);
+        assert!(loaded.enable_session);
+        assert_eq!(loaded.record_source, RECORD_CAPTURE);
+        assert!(!loaded.session_thumbnails);
+
+        let defaults = parse("version = 1\n[output]\n");
+        assert!(!defaults.enable_session);
+        assert_eq!(defaults.record_source, RECORD_SCOPES);
+        assert!(defaults.session_thumbnails);
+    }
+
+    #[test]
+    fn alerts_section_round_trip() {
+        let mut config = config();
+        config.alerts[ALERT_FLICKER] = AlertRule {
+            enable: true,
+            sound: false,
+            flash: true,
+        };
+
+        assert_eq!(round_trip(&config).alerts, config.alerts);
+        assert_eq!(
+            parse("version = 1\n").alerts,
+            [AlertRule::default(); ALERT_COUNT]
+        );
+    }
+
+    #[test]
+    fn layout_section_round_trip() {
+        let mut config = config();
+        config.layouts[LAYOUT_WAVEFORM] = ScopeLayout {
+            anchor: ANCHOR_BOTTOM_RIGHT,
+            size: 0.3,
+        };
+        config.split_screen = true;
+
+        let loaded = round_trip(&config);
+        assert_eq!(loaded.layouts, config.layouts);
+        assert!(loaded.split_screen);
+
+        let defaults = parse("version = 1\n[layout]\n");
+        assert_eq!(defaults.layouts, [ScopeLayout::default(); LAYOUT_COUNT]);
+        assert!(!defaults.split_screen);
+    }
+
+    #[test]
+    fn migrates_flat_ini() {
+        let conf = Ini::load_from_str(
+            "enable-filter=1\nfilter-mode=4\nzebra-high=0.9\nwindow-width=1024\nsample-frames=0\nlut-path=\n",
+        )
+        .unwrap();
+        let file = ConfigFile::from_ini(&conf);
+        assert_eq!(file.version, CONFIG_VERSION);
+
+        let config = file.into_config();
+        assert!(config.enable_filter);
+        assert_eq!(config.filter_mode, FILTER_MODE_ZEBRA);
+        assert_eq!(config.zebra_high, 0.9);
+        assert_eq!(config.zebra_low, 0.05);
+        assert_eq!(config.desaturation, 1.0);
+        assert_eq!(config.window_rect.width(), 1024);
+        assert_eq!(config.sample_frames, 1);
+        assert_eq!(config.lut_path, None);
+    }
+
+    #[test]
+    fn preset_names_stay_in_their_directory() {
+        let dir = Path::new("presets");
+        assert_eq!(
+            preset_path(dir, " HDR grading "),
+            Some(dir.join("HDR grading.toml"))
+        );
+        assert_eq!(preset_path(dir, ""), None);
+        assert_eq!(preset_path(dir, "   "), None);
+        assert_eq!(preset_path(dir, "../colormel"), None);
+        assert_eq!(preset_path(dir, "a\\b"), None);
+        assert_eq!(preset_path(dir, "what?"), None);
+        assert_eq!(preset_path(dir, ".."), None);
+    }
+
+    #[test]
+    fn preset_round_trip_keeps_window() {
+        let dir = std::env::temp_dir().join(format!("colormel-presets-{}", std::process::id()));
+
+        let mut saved = config();
+        saved.enable_waveform = true;
+        saved.waveform_layout = WAVEFORM_LAYOUT_PARADE;
+        saved.save_preset(&dir, "Screenshot check").unwrap();
+        assert_eq!(preset_names(&dir), vec!["Screenshot check".to_string()]);
+
+        let mut config = config();
+        config.window_rect = RECT::new(0, 0, 1920, 1080);
+        config.freeze = true;
+        config.capture_source = SOURCE_FILE;
+        config.load_preset(&dir, "Screenshot check").unwrap();
+        assert!(config.enable_waveform);
+        assert_eq!(config.waveform_layout, WAVEFORM_LAYOUT_PARADE);
+        assert_eq!(config.window_rect.width(), 1920);
+        assert!(config.freeze);
+        assert_eq!(config.capture_source, SOURCE_FILE);
+
+        delete_preset(&dir, "Screenshot check").unwrap();
+        assert!(preset_names(&dir).is_empty());
+        _ = std::fs::remove_dir(&dir);
+    }
+
+    #[test]
+    fn hotkeys_parse_and_print() {
+        let hotkey = Hotkey::parse("ctrl + alt+f").unwrap();
+        assert_eq!(hotkey.modifiers, MOD_CONTROL.0 | MOD_ALT.0);
+        assert_eq!(hotkey.key, b'F' as u32);
+        assert_eq!(hotkey.to_string(), "Ctrl+Alt+F");
+
+        assert_eq!(Hotkey::parse("Shift+F12").unwrap().to_string(), "Shift+F12");
+        assert_eq!(
+            Hotkey::parse("Win+PageUp").unwrap().to_string(),
+            "Win+PageUp"
+        );
+        assert_eq!(Hotkey::parse("Ctrl+Alt"), None);
+        assert_eq!(Hotkey::parse("Ctrl+A+B"), None);
+        assert_eq!(Hotkey::parse("F25"), None);
+        assert_eq!(Hotkey::parse(""), None);
+    }
+
+    #[test]
+    fn hotkeys_round_trip() {
+        let mut config = config();
+        config.hotkeys[HOTKEY_FREEZE] = Hotkey::parse("Ctrl+Shift+Space");
+        config.hotkeys[HOTKEY_EXPORT] = None;
+        config.hotkeys[HOTKEY_OUTPUT_SCREENSHOT] = Hotkey::parse("Ctrl+Alt+PrintScreen");
+
+        let text = toml::to_string_pretty(&ConfigFile::from_config(&config)).unwrap();
+        let loaded = toml::from_str::<ConfigFile>(&text).unwrap().into_config();
+
+        assert_eq!(loaded.hotkeys, config.hotkeys);
+        assert_eq!(
+            loaded.hotkeys[HOTKEY_FILTER].map(|hotkey| hotkey.to_string()),
+            Some("Ctrl+Alt+1".to_string())
+        );
+    }
+
+    #[test]
+    fn anchored_layouts_keep_to_their_corner() {
+        let layout = |anchor| ScopeLayout { anchor, size: 0.25 };
+
+        assert_eq!(layout(ANCHOR_DEFAULT).rect(), None);
+        assert_eq!(layout(ANCHOR_TOP_LEFT).rect(), Some([0.0, 0.0, 0.25, 0.25]));
+        assert_eq!(
+            layout(ANCHOR_BOTTOM_RIGHT).rect(),
+            Some([0.75, 0.75, 1.0, 1.0])
+        );
+
+        let text = "[layout.vectorscope]\nanchor = 9\nsize = 5.0\n";
+        let config = toml::from_str::<ConfigFile>(text).unwrap().into_config();
+        assert_eq!(
+            config.layouts[LAYOUT_VECTORSCOPE].anchor,
+            ANCHOR_BOTTOM_RIGHT
+        );
+        assert_eq!(config.layouts[LAYOUT_VECTORSCOPE].size, 1.0);
+    }
+
+    #[test]
+    fn split_screen_gives_each_scope_a_cell() {
+        let mut config = config();
+        config.window_rect = RECT::new(0, 0, 1200, 600);
+        config.enable_color_cloud = true;
+        config.enable_waveform = true;
+        config.split_screen = true;
+
+        let region = |config: &Config, scope| match config.scope_viewport(scope, ViewportKind::Full)
+        {
+            ViewportKind::Region { rect, .. } => Some(rect),
+            _ => None,
+        };
+        assert_eq!(
+            region(&config, LAYOUT_COLOR_CLOUD),
+            Some([0.0, 0.0, 0.5, 1.0])
+        );
+        assert_eq!(region(&config, LAYOUT_WAVEFORM), Some([0.5, 0.0, 1.0, 1.0]));
+        assert_eq!(region(&config, LAYOUT_HISTOGRAM), None);
+
+        config.split_screen = false;
+        assert_eq!(region(&config, LAYOUT_WAVEFORM), None);
+    }
+
+    #[test]
+    fn videos_are_told_apart_by_extension() {
+        assert_eq!(source_for(Path::new("clip.MP4")), SOURCE_VIDEO);
+        assert_eq!(source_for(Path::new("a/b.webm")), SOURCE_VIDEO);
+        assert_eq!(source_for(Path::new("shot.png")), SOURCE_FILE);
+        assert_eq!(source_for(Path::new("noext")), SOURCE_FILE);
+    }
+}
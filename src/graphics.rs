@@ -1,8 +1,11 @@
-mod composite;
-pub mod context;
-pub mod core;
-pub mod duplicate;
-pub mod initializer;
-pub mod math;
-pub mod renderer;
-pub mod resource;
+mod composite;
+pub mod context;
+pub mod core;
+pub mod duplicate;
+pub mod initializer;
+pub mod math;
+pub mod mips;
+pub mod profiler;
+pub mod renderer;
+pub mod resource;
+pub mod spout;
@@ -1,162 +1,372 @@
-use anyhow::Result;
-use windows::Win32::{
-    Foundation::HWND,
-    Graphics::{
-        Direct3D12::*,
-        Dxgi::{
-            CreateDXGIFactory2, IDXGIFactory2, DXGI_CREATE_FACTORY_DEBUG, DXGI_CREATE_FACTORY_FLAGS,
-        },
-    },
-};
-
-use crate::gui::hwnd::Hwnd;
-
-use super::{
-    composite::CompositionHost,
-    core::{
-        command_queue::CommandQueue,
-        descriptor::{DescriptorHeap, NonShaderVisibleSrvHeap, ShaderVisibleSrvHeap},
-        device::Device,
-        fence::Fence,
-        query::TimestampQueryPool,
-        root_signature::RootSignature,
-        swap_chain::SwapChain,
-    },
-    initializer::Initializer,
-    renderer::Renderer,
-};
-
-pub struct Context {
-    #[allow(unused)]
-    compositor: CompositionHost,
-    _debug: Option<ID3D12Debug6>,
-    device: Device,
-    command_queue: CommandQueue,
-    fence: Fence,
-    swap_chain: SwapChain,
-    root_signature: RootSignature,
-    shader_visible_srv_heap: ShaderVisibleSrvHeap,
-    non_shader_visible_srv_heap: NonShaderVisibleSrvHeap,
-    timestamp_query_heap: TimestampQueryPool,
-}
-
-impl Context {
-    pub fn new(hwnd: HWND) -> Result<Self> {
-        let mut compositor = CompositionHost::new()?;
-
-        let _debug = unsafe {
-            if cfg!(debug_assertions) {
-                let mut debug: Option<ID3D12Debug6> = None;
-                D3D12GetDebugInterface(&mut debug)?;
-                debug.inspect(|debug| {
-                    debug.EnableDebugLayer();
-                })
-            } else {
-                None
-            }
-        };
-
-        let factory: IDXGIFactory2 = unsafe {
-            let flags = if cfg!(debug_assertions) {
-                DXGI_CREATE_FACTORY_DEBUG
-            } else {
-                DXGI_CREATE_FACTORY_FLAGS::default()
-            };
-            CreateDXGIFactory2(flags)?
-        };
-
-        let adatper = unsafe { factory.EnumAdapters1(0) }?;
-        let device = Device::new(adatper)?;
-
-        let command_queue = CommandQueue::new(&device)?;
-
-        let fence = Fence::new(&device)?;
-
-        let (width, height) = hwnd.size();
-        let swap_chain = SwapChain::new(&factory, &device, &command_queue, width, height)?;
-
-        compositor.bind_swap_chain(hwnd, &swap_chain)?;
-
-        let root_signature = RootSignature::new(&device)?;
-
-        let shader_visible_srv_heap = DescriptorHeap::new(&device, 16)?;
-        let non_shader_visible_srv_heap = DescriptorHeap::new(&device, 16)?;
-
-        let timestamp_query_heap = TimestampQueryPool::new(&device)?;
-
-        Ok(Self {
-            compositor,
-            _debug,
-            device,
-            command_queue,
-            fence,
-            swap_chain,
-            root_signature,
-            shader_visible_srv_heap,
-            non_shader_visible_srv_heap,
-            timestamp_query_heap,
-        })
-    }
-
-    pub fn create_initializer(&mut self) -> Result<Initializer> {
-        Initializer::new(
-            self.device.clone(),
-            self.root_signature.clone(),
-            self.non_shader_visible_srv_heap.iter(),
-        )
-    }
-
-    pub fn create_renderer(
-        &mut self,
-        width: u32,
-        height: u32,
-        clear_color: &[f32; 4],
-    ) -> Result<Renderer> {
-        self.swap_chain.resize(&self.device, width, height)?;
-
-        let command_list = self.command_queue.command_list()?;
-        let render_target = self.swap_chain.render_target()?;
-
-        Renderer::new(
-            &self.device,
-            &self.root_signature,
-            command_list,
-            render_target,
-            &self.shader_visible_srv_heap,
-            &self.timestamp_query_heap,
-            clear_color,
-        )
-    }
-
-    pub fn execute(&mut self, mut renderer: Renderer) -> Result<()> {
-        let mut labels = renderer.resolve_query(self.timestamp_query_heap.buffer());
-
-        let command_list = renderer.close()?;
-        self.command_queue.execute(command_list)?;
-
-        self.swap_chain.present()?;
-        self.fence.wait(&self.command_queue)?;
-
-        let freq = unsafe { self.command_queue.GetTimestampFrequency()? };
-
-        if let Some(labels) = labels.take_if(|labels| !labels.is_empty()) {
-            self.timestamp_query_heap.dump(freq, &labels)?;
-        }
-
-        Ok(())
-    }
-}
-
-impl AsRef<Device> for Context {
-    fn as_ref(&self) -> &Device {
-        &self.device
-    }
-}
-
-impl std::ops::Deref for Context {
-    type Target = Device;
-
-    fn deref(&self) -> &Self::Target {
-        self.as_ref()
-    }
-}
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::HWND,
+    Graphics::{
+        Direct3D12::*,
+        Dxgi::{
+            CreateDXGIFactory2, IDXGIFactory2, DXGI_CREATE_FACTORY_DEBUG, DXGI_CREATE_FACTORY_FLAGS,
+        },
+    },
+};
+
+use crate::gui::hwnd::Hwnd;
+
+use super::{
+    composite::CompositionHost,
+    core::{
+        command_queue::{CommandQueue, QueueKind, Submission, FRAME_COUNT},
+        constant_buffer::ConstantBufferRing,
+        descriptor::{DescriptorHeap, DescriptorRing, NonShaderVisibleSrvHeap},
+        device::Device,
+        fence::Fence,
+        query::{PipelineStats, PipelineStatsQueryPool, TimestampQueryPool},
+        resource::Resource,
+        root_signature::RootSignature,
+        swap_chain::SwapChain,
+    },
+    initializer::Initializer,
+    profiler::{self, Calibration, CpuProfiler, FrameProfile, Span},
+    renderer::Renderer,
+};
+
+pub struct Context {
+    #[allow(unused)]
+    compositor: CompositionHost,
+    _debug: Option<ID3D12Debug6>,
+    device: Device,
+    command_queue: CommandQueue,
+    fence: Fence,
+    compute_queue: CommandQueue,
+    compute_fence: Fence,
+    swap_chain: SwapChain,
+    root_signature: RootSignature,
+    descriptor_ring: DescriptorRing,
+    non_shader_visible_srv_heap: NonShaderVisibleSrvHeap,
+    timestamp_query_heap: TimestampQueryPool,
+    pipeline_stats_pool: PipelineStatsQueryPool,
+    // what the last finished frame counted per pass
+    pipeline_stats: Vec<(String, PipelineStats)>,
+    profiler: CpuProfiler,
+    // cpu scopes of each frame in flight, joined with its gpu timestamps once it has finished
+    cpu_spans: [Vec<Span>; FRAME_COUNT],
+    profile: FrameProfile,
+    constant_buffer_ring: ConstantBufferRing,
+    frame: usize,
+    // fence value and resources released by each frame in flight
+    frames: [(u64, Vec<Resource>); FRAME_COUNT],
+}
+
+impl Context {
+    pub fn new(hwnd: HWND) -> Result<Self> {
+        let mut compositor = CompositionHost::new()?;
+
+        let _debug = unsafe {
+            if cfg!(debug_assertions) {
+                let mut debug: Option<ID3D12Debug6> = None;
+                D3D12GetDebugInterface(&mut debug)?;
+                debug.inspect(|debug| {
+                    debug.EnableDebugLayer();
+                })
+            } else {
+                None
+            }
+        };
+
+        let factory: IDXGIFactory2 = unsafe {
+            let flags = if cfg!(debug_assertions) {
+                DXGI_CREATE_FACTORY_DEBUG
+            } else {
+                DXGI_CREATE_FACTORY_FLAGS::default()
+            };
+            CreateDXGIFactory2(flags)?
+        };
+
+        let adatper = unsafe { factory.EnumAdapters1(0) }?;
+        let device = Device::new(adatper)?;
+
+        let command_queue = CommandQueue::new(&device)?;
+
+        let fence = Fence::new(&device)?;
+
+        let compute_queue = CommandQueue::compute(&device)?;
+        let compute_fence = Fence::new(&device)?;
+
+        let (width, height) = hwnd.size();
+        let swap_chain = SwapChain::new(&factory, &device, &command_queue, width, height)?;
+
+        compositor.bind_swap_chain(hwnd, &swap_chain)?;
+
+        let root_signature = RootSignature::new(&device)?;
+
+        let descriptor_ring = DescriptorRing::new(&device, 1024)?;
+        let non_shader_visible_srv_heap = DescriptorHeap::new(&device, 64)?;
+
+        let timestamp_query_heap = TimestampQueryPool::new(&device)?;
+        let pipeline_stats_pool = PipelineStatsQueryPool::new(&device)?;
+
+        let constant_buffer_ring = ConstantBufferRing::new(&device, 64 * 1024)?;
+
+        Ok(Self {
+            compositor,
+            _debug,
+            device,
+            command_queue,
+            fence,
+            compute_queue,
+            compute_fence,
+            swap_chain,
+            root_signature,
+            descriptor_ring,
+            non_shader_visible_srv_heap,
+            timestamp_query_heap,
+            pipeline_stats_pool,
+            pipeline_stats: vec![],
+            profiler: CpuProfiler::default(),
+            cpu_spans: Default::default(),
+            profile: FrameProfile::default(),
+            constant_buffer_ring,
+            frame: 0,
+            frames: Default::default(),
+        })
+    }
+
+    pub fn create_initializer(&mut self) -> Result<Initializer> {
+        Initializer::new(
+            self.device.clone(),
+            self.root_signature.clone(),
+            self.non_shader_visible_srv_heap.iter(),
+        )
+    }
+
+    // multisampled targets for line passes
+    pub fn set_msaa_samples(&mut self, samples: u32) -> Result<()> {
+        if samples != self.swap_chain.samples() {
+            self.wait_idle()?;
+        }
+
+        self.swap_chain.set_samples(&self.device, samples)
+    }
+
+    // the frames executed next wait on the gpu until fence reaches value, for work submitted
+    // from another device
+    pub fn queue_wait(&self, fence: &ID3D12Fence, value: u64) -> Result<()> {
+        unsafe { self.command_queue.Wait(fence, value)? };
+
+        Ok(())
+    }
+
+    // fence reaches value once the frames executed so far have finished, for work waiting on
+    // another device
+    pub fn queue_signal(&self, fence: &ID3D12Fence, value: u64) -> Result<()> {
+        unsafe { self.command_queue.Signal(fence, value)? };
+
+        Ok(())
+    }
+
+    // for readbacks and anything else that needs the submitted frames to have finished
+    pub fn wait_idle(&mut self) -> Result<()> {
+        self.fence.wait(&self.command_queue)?;
+
+        for (_, retired) in &mut self.frames {
+            retired.clear();
+        }
+
+        Ok(())
+    }
+
+    pub fn create_renderer(
+        &mut self,
+        width: u32,
+        height: u32,
+        clear_color: &[f32; 4],
+    ) -> Result<Renderer> {
+        // window sized targets are recreated after this, none may be in use then
+        if (width, height) != self.swap_chain.size() {
+            self.wait_idle()?;
+        }
+        self.swap_chain.resize(&self.device, width, height)?;
+
+        // only the frame recorded last may still be running
+        self.frame = (self.frame + 1) % FRAME_COUNT;
+        let (fence, retired) = &mut self.frames[self.frame];
+        self.profiler.begin("frame wait");
+        self.fence.wait_for(*fence)?;
+        self.profiler.end();
+        retired.clear();
+
+        let gpu = self
+            .timestamp_query_heap
+            .begin_frame(&self.device, self.frame)?;
+        self.profile = self.frame_profile(gpu)?;
+        self.pipeline_stats = self
+            .pipeline_stats_pool
+            .begin_frame(&self.device, self.frame)?;
+
+        // the frames recorded before this one are done once the last of their fences is reached
+        let in_flight = self
+            .frames
+            .iter()
+            .map(|(fence, _)| *fence)
+            .max()
+            .unwrap_or(0);
+
+        let direct_lists = self.command_queue.command_lists(self.frame)?;
+        let compute_lists = self.compute_queue.command_lists(self.frame)?;
+        let render_target = self.swap_chain.render_target()?;
+
+        Renderer::new(
+            &self.device,
+            &self.root_signature,
+            direct_lists,
+            compute_lists,
+            render_target,
+            self.descriptor_ring
+                .begin_frame(self.frame, self.fence.as_ref(), in_flight),
+            self.timestamp_query_heap.iter(self.frame),
+            self.pipeline_stats_pool.iter(self.frame),
+            self.constant_buffer_ring.next_frame(),
+            clear_color,
+        )
+    }
+
+    // cpu and gpu spans of the same frame on the qpc timeline
+    fn frame_profile(&mut self, gpu: Vec<(String, u64, u64)>) -> Result<FrameProfile> {
+        let cpu = std::mem::take(&mut self.cpu_spans[self.frame]);
+        if gpu.is_empty() && cpu.is_empty() {
+            return Ok(FrameProfile::default());
+        }
+
+        let calibration = unsafe {
+            let (mut gpu, mut cpu) = (0, 0);
+            self.command_queue.GetClockCalibration(&mut gpu, &mut cpu)?;
+            Calibration {
+                gpu,
+                cpu,
+                gpu_frequency: self.command_queue.GetTimestampFrequency()?,
+                cpu_frequency: profiler::frequency(),
+            }
+        };
+
+        let gpu = gpu
+            .into_iter()
+            .map(|(label, start, end)| Span {
+                label,
+                start: calibration.to_cpu(start),
+                end: calibration.to_cpu(end),
+            })
+            .collect();
+
+        Ok(FrameProfile {
+            cpu,
+            gpu,
+            frequency: calibration.cpu_frequency,
+        })
+    }
+
+    // slot of the frame being recorded, the frame that used it before has finished once
+    // create_renderer returns
+    pub fn frame_index(&self) -> usize {
+        self.frame
+    }
+
+    // scopes recorded here end up in the profile of the frame executed next
+    pub fn profiler(&mut self) -> &mut CpuProfiler {
+        &mut self.profiler
+    }
+
+    // the last finished frame, a couple of frames behind the one being recorded
+    pub fn profile(&self) -> &FrameProfile {
+        &self.profile
+    }
+
+    // pipeline statistics per pass from a frame back, the mesh shader counters stay 0 when the
+    // device can't count them
+    pub fn pipeline_stats(&self) -> &[(String, PipelineStats)] {
+        &self.pipeline_stats
+    }
+
+    pub fn execute(&mut self, mut renderer: Renderer) -> Result<()> {
+        let labels = renderer.resolve_query(self.timestamp_query_heap.buffer(self.frame));
+        let stats_labels =
+            renderer.resolve_pipeline_stats(self.pipeline_stats_pool.buffer(self.frame));
+        let retired = renderer.take_retired();
+        let grow_descriptors = self
+            .descriptor_ring
+            .end_frame(self.frame, renderer.descriptor_head());
+
+        // each queue signals its own fence after every list, the other one waits on it when asked
+        let (mut direct, mut compute) = (None, None);
+        for Submission {
+            queue,
+            wait,
+            command_list,
+        } in renderer.close()?
+        {
+            match queue {
+                QueueKind::Direct => {
+                    if let (true, Some(compute)) = (wait, compute) {
+                        self.compute_fence
+                            .queue_wait(&self.command_queue, compute)?;
+                    }
+                    self.command_queue.execute(command_list)?;
+                    direct = Some(self.fence.signal(&self.command_queue)?);
+                }
+                QueueKind::Compute => {
+                    if let (true, Some(direct)) = (wait, direct) {
+                        self.fence.queue_wait(&self.compute_queue, direct)?;
+                    }
+                    self.compute_queue.execute(command_list)?;
+                    compute = Some(self.compute_fence.signal(&self.compute_queue)?);
+                }
+            }
+        }
+
+        self.profiler.begin("present");
+        self.swap_chain.present()?;
+        self.profiler.end();
+        self.cpu_spans[self.frame] = self.profiler.take();
+
+        // the next frame is recorded while this one runs
+        let fence = self.fence.signal(&self.command_queue)?;
+        self.frames[self.frame] = (fence, retired);
+
+        // read back when this frame comes around again
+        if let Some((labels, dropped)) = labels {
+            self.timestamp_query_heap
+                .end_frame(self.frame, labels, dropped);
+        }
+        if let Some((labels, dropped)) = stats_labels {
+            self.pipeline_stats_pool
+                .end_frame(self.frame, labels, dropped);
+        }
+
+        // rare, the ring starts over in a bigger heap once nothing uses the old one
+        if grow_descriptors {
+            self.wait_idle()?;
+            self.descriptor_ring.grow(&self.device)?;
+        }
+
+        Ok(())
+    }
+}
+
+// nothing may be released while the gpu still uses it
+impl Drop for Context {
+    fn drop(&mut self) {
+        let _ = self.wait_idle();
+    }
+}
+
+impl AsRef<Device> for Context {
+    fn as_ref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl std::ops::Deref for Context {
+    type Target = Device;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
@@ -1,10 +1,12 @@
-pub mod command_queue;
-pub mod descriptor;
-pub mod device;
-pub mod fence;
-pub mod pso;
-pub mod query;
-pub mod resource;
-pub mod root_signature;
-pub mod swap_chain;
-pub mod wrap;
+pub mod command_queue;
+pub mod command_signature;
+pub mod constant_buffer;
+pub mod descriptor;
+pub mod device;
+pub mod fence;
+pub mod pso;
+pub mod query;
+pub mod resource;
+pub mod root_signature;
+pub mod swap_chain;
+pub mod wrap;
@@ -1,170 +1,299 @@
-use anyhow::Result;
-use windows::{
-    core::Interface,
-    Win32::Graphics::{Direct3D::D3D_PRIMITIVE_TOPOLOGY, Direct3D12::*},
-};
-
-use super::{
-    device::Device, pso::PipelineState, root_signature::RootSignature, wrap::CommandQueueDesc,
-};
-
-pub struct CommandQueue {
-    command_queue: ID3D12CommandQueue,
-    command_allocator: ID3D12CommandAllocator,
-    command_list: ID3D12GraphicsCommandList6,
-}
-
-impl CommandQueue {
-    pub fn new(device: &Device) -> Result<Self> {
-        unsafe {
-            let command_queue: ID3D12CommandQueue =
-                device.CreateCommandQueue(&CommandQueueDesc::direct())?;
-
-            let command_allocator =
-                device.CreateCommandAllocator(D3D12_COMMAND_LIST_TYPE_DIRECT)?;
-
-            let command_list: ID3D12GraphicsCommandList6 = device.CreateCommandList(
-                0,
-                D3D12_COMMAND_LIST_TYPE_DIRECT,
-                &command_allocator,
-                None,
-            )?;
-            command_list.Close()?;
-
-            Ok(Self {
-                command_queue,
-                command_allocator,
-                command_list,
-            })
-        }
-    }
-
-    pub fn command_list(&self) -> Result<CommandList> {
-        unsafe {
-            self.command_allocator.Reset()?;
-            self.command_list.Reset(&self.command_allocator, None)?;
-
-            Ok(CommandList(self.command_list.clone()))
-        }
-    }
-
-    pub fn execute(&self, command_list: ClosedCommandList) -> Result<()> {
-        unsafe {
-            self.command_queue
-                .ExecuteCommandLists(&[Some(command_list.0)]);
-
-            Ok(())
-        }
-    }
-}
-
-impl AsRef<ID3D12CommandQueue> for CommandQueue {
-    fn as_ref(&self) -> &ID3D12CommandQueue {
-        &self.command_queue
-    }
-}
-
-impl std::ops::Deref for CommandQueue {
-    type Target = ID3D12CommandQueue;
-
-    fn deref(&self) -> &Self::Target {
-        self.as_ref()
-    }
-}
-
-#[derive(Clone)]
-pub struct CommandList(ID3D12GraphicsCommandList6);
-
-impl CommandList {
-    pub fn resource_barrier(&self, barriers: &[D3D12_RESOURCE_BARRIER]) {
-        unsafe {
-            self.ResourceBarrier(barriers);
-        }
-    }
-
-    pub fn set_descriptor_heaps(&self, heaps: &[&ID3D12DescriptorHeap]) {
-        unsafe {
-            let heaps: Vec<_> = heaps.iter().map(|&heap| Some(heap.clone())).collect();
-            self.SetDescriptorHeaps(&heaps);
-        }
-    }
-
-    pub fn set_root_signature(&self, root_signature: &RootSignature) {
-        unsafe {
-            self.SetComputeRootSignature(root_signature.as_ref());
-            self.SetGraphicsRootSignature(root_signature.as_ref());
-        }
-    }
-
-    pub fn set_pipeline_state(&self, pso: &PipelineState) {
-        unsafe {
-            self.SetPipelineState(pso.as_ref());
-        }
-    }
-
-    pub fn set_primitive_topology(&self, primitive_topology: D3D_PRIMITIVE_TOPOLOGY) {
-        unsafe {
-            self.IASetPrimitiveTopology(primitive_topology);
-        }
-    }
-
-    pub fn set_vertex_buffers(&self, views: &[D3D12_VERTEX_BUFFER_VIEW]) {
-        unsafe {
-            self.IASetVertexBuffers(0, Some(views));
-        }
-    }
-
-    pub fn draw(&self, vertex_count: u32, instance_count: u32) {
-        unsafe {
-            self.DrawInstanced(vertex_count, instance_count, 0, 0);
-        }
-    }
-
-    pub fn dispatch(&self, x: u32, y: u32, z: u32) {
-        unsafe {
-            self.Dispatch(x, y, z);
-        }
-    }
-
-    pub fn dispatch_mesh(&self, x: u32, y: u32, z: u32) {
-        unsafe {
-            self.DispatchMesh(x, y, z);
-        }
-    }
-
-    pub fn resolve_query(
-        &self,
-        query_heap: &ID3D12QueryHeap,
-        query_type: D3D12_QUERY_TYPE,
-        num_queries: u32,
-        buffer: &ID3D12Resource,
-    ) {
-        unsafe {
-            self.ResolveQueryData(query_heap, query_type, 0, num_queries, buffer, 0);
-        }
-    }
-
-    pub fn close(self) -> Result<ClosedCommandList> {
-        unsafe {
-            self.Close()?;
-
-            Ok(ClosedCommandList(self.0.cast()?))
-        }
-    }
-}
-
-impl AsRef<ID3D12GraphicsCommandList6> for CommandList {
-    fn as_ref(&self) -> &ID3D12GraphicsCommandList6 {
-        &self.0
-    }
-}
-
-impl std::ops::Deref for CommandList {
-    type Target = ID3D12GraphicsCommandList6;
-
-    fn deref(&self) -> &Self::Target {
-        self.as_ref()
-    }
-}
-
-pub struct ClosedCommandList(ID3D12CommandList);
+use anyhow::Result;
+use windows::{
+    core::Interface,
+    Win32::Graphics::{Direct3D::D3D_PRIMITIVE_TOPOLOGY, Direct3D12::*},
+};
+
+use super::{
+    command_signature::CommandSignature,
+    device::Device,
+    pso::PipelineState,
+    resource::Resource,
+    root_signature::RootSignature,
+    wrap::{CommandQueueDesc, TextureCopyLocation},
+};
+
+// frames recorded on the cpu while earlier ones still run on the gpu
+pub const FRAME_COUNT: usize = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QueueKind {
+    Direct,
+    Compute,
+}
+
+// a closed list and whether it waits for the other queue's last submission first
+pub struct Submission {
+    pub queue: QueueKind,
+    pub wait: bool,
+    pub command_list: ClosedCommandList,
+}
+
+pub struct CommandQueue {
+    command_queue: ID3D12CommandQueue,
+    // one per frame in flight, reset only after the gpu is done with that frame
+    command_allocators: Vec<ID3D12CommandAllocator>,
+    // recorded one after another within a frame
+    command_lists: Vec<ID3D12GraphicsCommandList6>,
+}
+
+impl CommandQueue {
+    // graphics work is split around the async compute passes, at most three lists a frame
+    pub fn new(device: &Device) -> Result<Self> {
+        Self::create(device, D3D12_COMMAND_LIST_TYPE_DIRECT, 3)
+    }
+
+    // runs compute passes next to the graphics queue
+    pub fn compute(device: &Device) -> Result<Self> {
+        Self::create(device, D3D12_COMMAND_LIST_TYPE_COMPUTE, 1)
+    }
+
+    fn create(device: &Device, kind: D3D12_COMMAND_LIST_TYPE, num_lists: usize) -> Result<Self> {
+        unsafe {
+            let command_queue: ID3D12CommandQueue =
+                device.CreateCommandQueue(&CommandQueueDesc::new(kind))?;
+
+            let command_allocators = (0..FRAME_COUNT)
+                .map(|_| device.CreateCommandAllocator(kind))
+                .collect::<windows::core::Result<Vec<ID3D12CommandAllocator>>>()?;
+
+            let command_lists = (0..num_lists)
+                .map(|_| {
+                    let command_list: ID3D12GraphicsCommandList6 =
+                        device.CreateCommandList(0, kind, &command_allocators[0], None)?;
+                    command_list.Close()?;
+                    Ok(command_list)
+                })
+                .collect::<windows::core::Result<Vec<_>>>()?;
+
+            Ok(Self {
+                command_queue,
+                command_allocators,
+                command_lists,
+            })
+        }
+    }
+
+    // the frame's previous use must have finished on the gpu
+    pub fn command_lists(&self, frame: usize) -> Result<CommandLists> {
+        let allocator = self.command_allocators[frame].clone();
+        unsafe {
+            allocator.Reset()?;
+        }
+
+        Ok(CommandLists {
+            allocator,
+            lists: self.command_lists.clone(),
+            next: 0,
+        })
+    }
+
+    pub fn execute(&self, command_list: ClosedCommandList) -> Result<()> {
+        unsafe {
+            self.command_queue
+                .ExecuteCommandLists(&[Some(command_list.0)]);
+
+            Ok(())
+        }
+    }
+}
+
+// the lists of one frame, all recorded from the frame's allocator
+pub struct CommandLists {
+    allocator: ID3D12CommandAllocator,
+    lists: Vec<ID3D12GraphicsCommandList6>,
+    next: usize,
+}
+
+impl CommandLists {
+    // the list returned before must have been closed
+    pub fn next(&mut self) -> Result<CommandList> {
+        let command_list = self.lists.get(self.next).expect("too many command lists");
+        self.next += 1;
+
+        unsafe {
+            command_list.Reset(&self.allocator, None)?;
+        }
+
+        Ok(CommandList(command_list.clone()))
+    }
+}
+
+impl AsRef<ID3D12CommandQueue> for CommandQueue {
+    fn as_ref(&self) -> &ID3D12CommandQueue {
+        &self.command_queue
+    }
+}
+
+impl std::ops::Deref for CommandQueue {
+    type Target = ID3D12CommandQueue;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+#[derive(Clone)]
+pub struct CommandList(ID3D12GraphicsCommandList6);
+
+impl CommandList {
+    pub fn resource_barrier(&self, barriers: &[D3D12_RESOURCE_BARRIER]) {
+        unsafe {
+            self.ResourceBarrier(barriers);
+        }
+    }
+
+    pub fn set_descriptor_heaps(&self, heaps: &[&ID3D12DescriptorHeap]) {
+        unsafe {
+            let heaps: Vec<_> = heaps.iter().map(|&heap| Some(heap.clone())).collect();
+            self.SetDescriptorHeaps(&heaps);
+        }
+    }
+
+    pub fn set_root_signature(&self, root_signature: &RootSignature) {
+        unsafe {
+            self.SetComputeRootSignature(root_signature.as_ref());
+            self.SetGraphicsRootSignature(root_signature.as_ref());
+        }
+    }
+
+    pub fn set_pipeline_state(&self, pso: &PipelineState) {
+        unsafe {
+            self.SetPipelineState(pso.as_ref());
+        }
+    }
+
+    pub fn set_primitive_topology(&self, primitive_topology: D3D_PRIMITIVE_TOPOLOGY) {
+        unsafe {
+            self.IASetPrimitiveTopology(primitive_topology);
+        }
+    }
+
+    pub fn set_vertex_buffers(&self, views: &[D3D12_VERTEX_BUFFER_VIEW]) {
+        unsafe {
+            self.IASetVertexBuffers(0, Some(views));
+        }
+    }
+
+    pub fn draw(&self, vertex_count: u32, instance_count: u32) {
+        unsafe {
+            self.DrawInstanced(vertex_count, instance_count, 0, 0);
+        }
+    }
+
+    pub fn execute_indirect(
+        &self,
+        signature: &CommandSignature,
+        count: u32,
+        args: &Resource,
+        offset: u64,
+    ) {
+        unsafe {
+            self.ExecuteIndirect(signature.as_ref(), count, args.as_ref(), offset, None, 0);
+        }
+    }
+
+    pub fn dispatch(&self, x: u32, y: u32, z: u32) {
+        unsafe {
+            self.Dispatch(x, y, z);
+        }
+    }
+
+    pub fn dispatch_mesh(&self, x: u32, y: u32, z: u32) {
+        unsafe {
+            self.DispatchMesh(x, y, z);
+        }
+    }
+
+    pub fn copy_resource(&self, dst: &Resource, src: &Resource) {
+        unsafe {
+            self.CopyResource(dst.as_ref(), src.as_ref());
+        }
+    }
+
+    pub fn copy_buffer_region(
+        &self,
+        dst: &Resource,
+        dst_offset: u64,
+        src: &Resource,
+        src_offset: u64,
+        size: u64,
+    ) {
+        unsafe {
+            self.CopyBufferRegion(dst.as_ref(), dst_offset, src.as_ref(), src_offset, size);
+        }
+    }
+
+    pub fn copy_texture_to_buffer(
+        &self,
+        dst: &Resource,
+        footprint: D3D12_PLACED_SUBRESOURCE_FOOTPRINT,
+        src: &Resource,
+        src_box: &D3D12_BOX,
+    ) {
+        unsafe {
+            self.CopyTextureRegion(
+                &TextureCopyLocation::footprint(dst, footprint),
+                0,
+                0,
+                0,
+                &TextureCopyLocation::subresource(src, 0),
+                Some(src_box),
+            );
+        }
+    }
+
+    pub fn copy_buffer_to_texture(
+        &self,
+        dst: &Resource,
+        src: &Resource,
+        footprint: D3D12_PLACED_SUBRESOURCE_FOOTPRINT,
+    ) {
+        unsafe {
+            self.CopyTextureRegion(
+                &TextureCopyLocation::subresource(dst, 0),
+                0,
+                0,
+                0,
+                &TextureCopyLocation::footprint(src, footprint),
+                None,
+            );
+        }
+    }
+
+    pub fn resolve_query(
+        &self,
+        query_heap: &ID3D12QueryHeap,
+        query_type: D3D12_QUERY_TYPE,
+        num_queries: u32,
+        buffer: &ID3D12Resource,
+    ) {
+        unsafe {
+            self.ResolveQueryData(query_heap, query_type, 0, num_queries, buffer, 0);
+        }
+    }
+
+    pub fn close(self) -> Result<ClosedCommandList> {
+        unsafe {
+            self.Close()?;
+
+            Ok(ClosedCommandList(self.0.cast()?))
+        }
+    }
+}
+
+impl AsRef<ID3D12GraphicsCommandList6> for CommandList {
+    fn as_ref(&self) -> &ID3D12GraphicsCommandList6 {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for CommandList {
+    type Target = ID3D12GraphicsCommandList6;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+pub struct ClosedCommandList(ID3D12CommandList);
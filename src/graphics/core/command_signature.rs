@@ -0,0 +1,57 @@
+use anyhow::Result;
+use windows::Win32::Graphics::Direct3D12::{
+    ID3D12CommandSignature, D3D12_DRAW_ARGUMENTS, D3D12_INDIRECT_ARGUMENT_DESC,
+};
+
+use super::{
+    device::Device,
+    wrap::{CommandSignatureDesc, IndirectArgumentDesc},
+};
+
+// layout of the commands read by execute_indirect, one argument struct per command
+pub struct CommandSignature {
+    signature: ID3D12CommandSignature,
+    stride: u32,
+}
+
+impl CommandSignature {
+    pub fn draw(device: &Device) -> Result<Self> {
+        Self::new::<D3D12_DRAW_ARGUMENTS>(device, IndirectArgumentDesc::draw())
+    }
+
+    // other command types only need an argument desc and their argument struct
+    fn new<T>(device: &Device, arg: D3D12_INDIRECT_ARGUMENT_DESC) -> Result<Self> {
+        unsafe {
+            let stride = std::mem::size_of::<T>() as u32;
+            let args = [arg];
+            let desc = CommandSignatureDesc::default(stride, &args);
+
+            let mut signature: Option<ID3D12CommandSignature> = None;
+            device.CreateCommandSignature(&desc, None, &mut signature)?;
+
+            Ok(Self {
+                signature: signature.unwrap(),
+                stride,
+            })
+        }
+    }
+
+    // bytes per command in the argument buffer
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+}
+
+impl AsRef<ID3D12CommandSignature> for CommandSignature {
+    fn as_ref(&self) -> &ID3D12CommandSignature {
+        &self.signature
+    }
+}
+
+impl std::ops::Deref for CommandSignature {
+    type Target = ID3D12CommandSignature;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use windows::Win32::Graphics::Direct3D12::{
+    D3D12_CONSTANT_BUFFER_DATA_PLACEMENT_ALIGNMENT, D3D12_RESOURCE_FLAG_NONE,
+    D3D12_RESOURCE_STATE_GENERIC_READ,
+};
+
+use super::{device::Device, resource::Resource, wrap::HeapProps};
+
+const ALIGNMENT: u64 = D3D12_CONSTANT_BUFFER_DATA_PLACEMENT_ALIGNMENT as u64;
+
+// persistently mapped upload heap for constants that don't fit in the root constants,
+// each frame allocates from its own range so blocks still read by the gpu stay intact
+pub struct ConstantBufferRing {
+    buffer: Resource,
+    mapped: *mut u8,
+    frame_size: u64,
+    frame: u64,
+}
+
+// the mapping is owned by the ring and only written from the render thread
+unsafe impl Send for ConstantBufferRing {}
+
+impl ConstantBufferRing {
+    const FRAME_COUNT: u64 = 2;
+
+    pub fn new(device: &Device, frame_size: u64) -> Result<Self> {
+        let frame_size = frame_size.next_multiple_of(ALIGNMENT);
+
+        let buffer = Resource::new_buffer(
+            device,
+            &HeapProps::upload(),
+            None,
+            Self::FRAME_COUNT * frame_size,
+            D3D12_RESOURCE_FLAG_NONE,
+            D3D12_RESOURCE_STATE_GENERIC_READ,
+        )?;
+
+        let mut mapped = std::ptr::null_mut();
+        unsafe { buffer.Map(0, None, Some(&mut mapped))? };
+
+        Ok(Self {
+            buffer,
+            mapped: mapped as _,
+            frame_size,
+            frame: 0,
+        })
+    }
+
+    // the range of the next frame, the one before it may still be in flight
+    pub fn next_frame(&mut self) -> ConstantAllocator {
+        self.frame = (self.frame + 1) % Self::FRAME_COUNT;
+        let offset = self.frame * self.frame_size;
+
+        ConstantAllocator {
+            cpu: unsafe { self.mapped.add(offset as usize) },
+            gpu: unsafe { self.buffer.GetGPUVirtualAddress() } + offset,
+            size: self.frame_size,
+            offset: 0,
+        }
+    }
+}
+
+impl Drop for ConstantBufferRing {
+    fn drop(&mut self) {
+        unsafe { self.buffer.Unmap(0, None) };
+    }
+}
+
+// linear allocator over one frame of the ring
+pub struct ConstantAllocator {
+    cpu: *mut u8,
+    gpu: u64,
+    size: u64,
+    offset: u64,
+}
+
+impl ConstantAllocator {
+    // copies the constants and returns their gpu address for a root cbv
+    pub fn push<T>(&mut self, data: &T) -> Result<u64> {
+        let len = std::mem::size_of_val(data) as u64;
+        let aligned = len.next_multiple_of(ALIGNMENT);
+        if self.offset + aligned > self.size {
+            anyhow::bail!("constant buffer ring exhausted");
+        }
+
+        unsafe {
+            let dest = self.cpu.add(self.offset as usize);
+            dest.copy_from_nonoverlapping(data as *const T as *const u8, len as usize);
+        }
+
+        let address = self.gpu + self.offset;
+        self.offset += aligned;
+
+        Ok(address)
+    }
+}
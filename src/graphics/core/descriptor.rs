@@ -1,136 +1,254 @@
-use anyhow::Result;
-use windows::Win32::Graphics::Direct3D12::*;
-
-use super::{device::Device, wrap::*};
-
-#[derive(Clone, Copy)]
-pub struct Descriptor {
-    pub cpu: D3D12_CPU_DESCRIPTOR_HANDLE,
-    pub gpu: D3D12_GPU_DESCRIPTOR_HANDLE,
-}
-
-impl Descriptor {
-    pub fn offset(&mut self, count: u32, increment_size: u32) -> Self {
-        self.cpu.offset(count, increment_size);
-        self.gpu.offset(count, increment_size);
-
-        *self
-    }
-}
-
-pub const DESCRIPTOR_HEAP_TYPE_SRV: i32 = D3D12_DESCRIPTOR_HEAP_TYPE_CBV_SRV_UAV.0;
-pub const DESCRIPTOR_HEAP_TYPE_SAMPLER: i32 = D3D12_DESCRIPTOR_HEAP_TYPE_SAMPLER.0;
-pub const DESCRIPTOR_HEAP_TYPE_RTV: i32 = D3D12_DESCRIPTOR_HEAP_TYPE_RTV.0;
-pub const DESCRIPTOR_HEAP_TYPE_DSV: i32 = D3D12_DESCRIPTOR_HEAP_TYPE_DSV.0;
-
-pub struct DescriptorHeap<const TYPE: i32, const SHADER_VISIBLE: bool> {
-    heap: ID3D12DescriptorHeap,
-    num_descriptors: u32,
-    increment_size: u32,
-}
-
-impl<const TYPE: i32, const SHADER_VISIBLE: bool> DescriptorHeap<TYPE, SHADER_VISIBLE> {
-    pub fn new(device: &Device, num_descriptors: u32) -> Result<Self> {
-        unsafe {
-            let desc = match TYPE {
-                DESCRIPTOR_HEAP_TYPE_SRV => {
-                    DescriptorHeapDesc::srv(num_descriptors, SHADER_VISIBLE)
-                }
-                DESCRIPTOR_HEAP_TYPE_SAMPLER => DescriptorHeapDesc::sampler(num_descriptors),
-                DESCRIPTOR_HEAP_TYPE_RTV => DescriptorHeapDesc::rtv(num_descriptors),
-                DESCRIPTOR_HEAP_TYPE_DSV => DescriptorHeapDesc::dsv(num_descriptors),
-                _ => unreachable!("invalid descriptor heap type {TYPE}"),
-            };
-            let heap = device.CreateDescriptorHeap(&desc)?;
-
-            let increment_size =
-                device.GetDescriptorHandleIncrementSize(D3D12_DESCRIPTOR_HEAP_TYPE(TYPE));
-
-            Ok(Self {
-                heap,
-                num_descriptors,
-                increment_size,
-            })
-        }
-    }
-
-    pub fn descriptor(&self, index: u32) -> Descriptor {
-        debug_assert!(index < self.num_descriptors);
-
-        unsafe {
-            let cpu = self
-                .heap
-                .GetCPUDescriptorHandleForHeapStart()
-                .offset(index, self.increment_size);
-
-            let gpu = if SHADER_VISIBLE {
-                self.heap
-                    .GetGPUDescriptorHandleForHeapStart()
-                    .offset(index, self.increment_size)
-            } else {
-                D3D12_GPU_DESCRIPTOR_HANDLE::default()
-            };
-
-            Descriptor { cpu, gpu }
-        }
-    }
-
-    pub fn iter(&self) -> DescriptorIter {
-        let cur = self.descriptor(0);
-        let end = self
-            .descriptor(0)
-            .offset(self.num_descriptors, self.increment_size);
-
-        DescriptorIter {
-            cur,
-            end,
-            increment_size: self.increment_size,
-        }
-    }
-}
-
-impl<const TYPE: i32, const SHADER_VISIBLE: bool> AsRef<ID3D12DescriptorHeap>
-    for DescriptorHeap<TYPE, SHADER_VISIBLE>
-{
-    fn as_ref(&self) -> &ID3D12DescriptorHeap {
-        &self.heap
-    }
-}
-
-impl<const TYPE: i32, const SHADER_VISIBLE: bool> std::ops::Deref
-    for DescriptorHeap<TYPE, SHADER_VISIBLE>
-{
-    type Target = ID3D12DescriptorHeap;
-
-    fn deref(&self) -> &Self::Target {
-        self.as_ref()
-    }
-}
-
-pub type ShaderVisibleSrvHeap = DescriptorHeap<DESCRIPTOR_HEAP_TYPE_SRV, true>;
-pub type NonShaderVisibleSrvHeap = DescriptorHeap<DESCRIPTOR_HEAP_TYPE_SRV, false>;
-pub type SamplerViewHeap = DescriptorHeap<DESCRIPTOR_HEAP_TYPE_SAMPLER, false>;
-pub type RtvHeap = DescriptorHeap<DESCRIPTOR_HEAP_TYPE_RTV, false>;
-pub type DsvHeap = DescriptorHeap<DESCRIPTOR_HEAP_TYPE_DSV, false>;
-
-pub struct DescriptorIter {
-    cur: Descriptor,
-    end: Descriptor,
-    increment_size: u32,
-}
-
-impl Iterator for DescriptorIter {
-    type Item = Descriptor;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.cur.cpu.ptr < self.end.cpu.ptr {
-            let descriptor = self.cur;
-
-            self.cur.offset(1, self.increment_size);
-
-            Some(descriptor)
-        } else {
-            None
-        }
-    }
-}
+use anyhow::Result;
+use windows::Win32::{Foundation::HANDLE, Graphics::Direct3D12::*};
+
+use super::{command_queue::FRAME_COUNT, device::Device, wrap::*};
+
+#[derive(Clone, Copy)]
+pub struct Descriptor {
+    pub cpu: D3D12_CPU_DESCRIPTOR_HANDLE,
+    pub gpu: D3D12_GPU_DESCRIPTOR_HANDLE,
+}
+
+impl Descriptor {
+    pub fn offset(&mut self, count: u32, increment_size: u32) -> Self {
+        self.cpu.offset(count, increment_size);
+        self.gpu.offset(count, increment_size);
+
+        *self
+    }
+}
+
+pub const DESCRIPTOR_HEAP_TYPE_SRV: i32 = D3D12_DESCRIPTOR_HEAP_TYPE_CBV_SRV_UAV.0;
+pub const DESCRIPTOR_HEAP_TYPE_SAMPLER: i32 = D3D12_DESCRIPTOR_HEAP_TYPE_SAMPLER.0;
+pub const DESCRIPTOR_HEAP_TYPE_RTV: i32 = D3D12_DESCRIPTOR_HEAP_TYPE_RTV.0;
+pub const DESCRIPTOR_HEAP_TYPE_DSV: i32 = D3D12_DESCRIPTOR_HEAP_TYPE_DSV.0;
+
+pub struct DescriptorHeap<const TYPE: i32, const SHADER_VISIBLE: bool> {
+    heap: ID3D12DescriptorHeap,
+    num_descriptors: u32,
+    increment_size: u32,
+}
+
+impl<const TYPE: i32, const SHADER_VISIBLE: bool> DescriptorHeap<TYPE, SHADER_VISIBLE> {
+    pub fn new(device: &Device, num_descriptors: u32) -> Result<Self> {
+        unsafe {
+            let desc = match TYPE {
+                DESCRIPTOR_HEAP_TYPE_SRV => {
+                    DescriptorHeapDesc::srv(num_descriptors, SHADER_VISIBLE)
+                }
+                DESCRIPTOR_HEAP_TYPE_SAMPLER => DescriptorHeapDesc::sampler(num_descriptors),
+                DESCRIPTOR_HEAP_TYPE_RTV => DescriptorHeapDesc::rtv(num_descriptors),
+                DESCRIPTOR_HEAP_TYPE_DSV => DescriptorHeapDesc::dsv(num_descriptors),
+                _ => unreachable!("invalid descriptor heap type {TYPE}"),
+            };
+            let heap = device.CreateDescriptorHeap(&desc)?;
+
+            let increment_size =
+                device.GetDescriptorHandleIncrementSize(D3D12_DESCRIPTOR_HEAP_TYPE(TYPE));
+
+            Ok(Self {
+                heap,
+                num_descriptors,
+                increment_size,
+            })
+        }
+    }
+
+    pub fn descriptor(&self, index: u32) -> Descriptor {
+        debug_assert!(index < self.num_descriptors);
+
+        unsafe {
+            let cpu = self
+                .heap
+                .GetCPUDescriptorHandleForHeapStart()
+                .offset(index, self.increment_size);
+
+            let gpu = if SHADER_VISIBLE {
+                self.heap
+                    .GetGPUDescriptorHandleForHeapStart()
+                    .offset(index, self.increment_size)
+            } else {
+                D3D12_GPU_DESCRIPTOR_HANDLE::default()
+            };
+
+            Descriptor { cpu, gpu }
+        }
+    }
+
+    pub fn iter(&self) -> DescriptorIter {
+        let cur = self.descriptor(0);
+        let end = self
+            .descriptor(0)
+            .offset(self.num_descriptors, self.increment_size);
+
+        DescriptorIter {
+            cur,
+            end,
+            increment_size: self.increment_size,
+        }
+    }
+}
+
+impl<const TYPE: i32, const SHADER_VISIBLE: bool> AsRef<ID3D12DescriptorHeap>
+    for DescriptorHeap<TYPE, SHADER_VISIBLE>
+{
+    fn as_ref(&self) -> &ID3D12DescriptorHeap {
+        &self.heap
+    }
+}
+
+impl<const TYPE: i32, const SHADER_VISIBLE: bool> std::ops::Deref
+    for DescriptorHeap<TYPE, SHADER_VISIBLE>
+{
+    type Target = ID3D12DescriptorHeap;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+pub type ShaderVisibleSrvHeap = DescriptorHeap<DESCRIPTOR_HEAP_TYPE_SRV, true>;
+pub type NonShaderVisibleSrvHeap = DescriptorHeap<DESCRIPTOR_HEAP_TYPE_SRV, false>;
+pub type SamplerViewHeap = DescriptorHeap<DESCRIPTOR_HEAP_TYPE_SAMPLER, false>;
+pub type RtvHeap = DescriptorHeap<DESCRIPTOR_HEAP_TYPE_RTV, false>;
+pub type DsvHeap = DescriptorHeap<DESCRIPTOR_HEAP_TYPE_DSV, false>;
+
+// shader visible descriptors handed out as a ring, what a frame used is free again once the gpu
+// has finished it, a frame that fills more than a quarter of it makes it grow
+pub struct DescriptorRing {
+    heap: ShaderVisibleSrvHeap,
+    capacity: u32,
+    // positions count up without wrapping, the heap index is position % capacity
+    head: u64,
+    frame_starts: [u64; FRAME_COUNT],
+}
+
+impl DescriptorRing {
+    pub fn new(device: &Device, capacity: u32) -> Result<Self> {
+        Ok(Self {
+            heap: DescriptorHeap::new(device, capacity)?,
+            capacity,
+            head: 0,
+            frame_starts: [0; FRAME_COUNT],
+        })
+    }
+
+    // the frame's previous use must have finished, the other frames may still be running until
+    // fence reaches in_flight
+    pub fn begin_frame(
+        &mut self,
+        frame: usize,
+        fence: &ID3D12Fence,
+        in_flight: u64,
+    ) -> FrameDescriptors {
+        self.frame_starts[frame] = self.head;
+
+        let oldest = (0..FRAME_COUNT)
+            .filter(|&other| other != frame)
+            .map(|other| self.frame_starts[other])
+            .min()
+            .unwrap_or(self.head);
+
+        FrameDescriptors {
+            heap: self.heap.as_ref().clone(),
+            start: self.heap.descriptor(0),
+            increment_size: self.heap.increment_size,
+            capacity: self.capacity as u64,
+            first: self.head,
+            head: self.head,
+            limit: oldest + self.capacity as u64,
+            in_flight: Some((fence.clone(), in_flight)),
+        }
+    }
+
+    // head is where the frame's allocations ended, true when grow() should run before the next
+    pub fn end_frame(&mut self, frame: usize, head: u64) -> bool {
+        self.head = head;
+        4 * (head - self.frame_starts[frame]) > self.capacity as u64
+    }
+
+    // nothing may be in flight
+    pub fn grow(&mut self, device: &Device) -> Result<()> {
+        self.capacity *= 2;
+        self.heap = DescriptorHeap::new(device, self.capacity)?;
+        self.head = 0;
+        self.frame_starts = [0; FRAME_COUNT];
+
+        Ok(())
+    }
+}
+
+// the part of the ring one frame allocates from, tables are contiguous and never wrap
+pub struct FrameDescriptors {
+    heap: ID3D12DescriptorHeap,
+    start: Descriptor,
+    increment_size: u32,
+    capacity: u64,
+    // where the frame started
+    first: u64,
+    head: u64,
+    limit: u64,
+    // the other frames are done once the fence reaches the value
+    in_flight: Option<(ID3D12Fence, u64)>,
+}
+
+impl FrameDescriptors {
+    // waits for the frames still in flight when they are in the way, None once this frame alone
+    // fills the ring
+    pub fn allocate(&mut self, count: u32) -> Option<Descriptor> {
+        let count = count as u64;
+
+        loop {
+            let mut head = self.head;
+            if head % self.capacity + count > self.capacity {
+                head = head.next_multiple_of(self.capacity);
+            }
+            if head + count <= self.limit {
+                self.head = head + count;
+
+                let mut descriptor = self.start;
+                return Some(descriptor.offset((head % self.capacity) as u32, self.increment_size));
+            }
+
+            // a frame that needs a lot more than the one before it, the whole ring is its own once
+            // the others have finished
+            let (fence, value) = self.in_flight.take()?;
+            unsafe { fence.SetEventOnCompletion(value, HANDLE::default()).ok()? };
+            self.limit = self.first + self.capacity;
+        }
+    }
+
+    pub fn head(&self) -> u64 {
+        self.head
+    }
+
+    pub fn increment_size(&self) -> u32 {
+        self.increment_size
+    }
+
+    pub fn heap(&self) -> &ID3D12DescriptorHeap {
+        &self.heap
+    }
+}
+
+pub struct DescriptorIter {
+    cur: Descriptor,
+    end: Descriptor,
+    increment_size: u32,
+}
+
+impl Iterator for DescriptorIter {
+    type Item = Descriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur.cpu.ptr < self.end.cpu.ptr {
+            let descriptor = self.cur;
+
+            self.cur.offset(1, self.increment_size);
+
+            Some(descriptor)
+        } else {
+            None
+        }
+    }
+}
@@ -1,73 +1,125 @@
-use anyhow::Result;
-use windows::Win32::{
-    Foundation::HANDLE,
-    Graphics::{Direct3D::D3D_FEATURE_LEVEL_12_0, Direct3D12::*, Dxgi::*},
-};
-
-#[derive(Clone)]
-pub struct Device {
-    adapter: IDXGIAdapter1,
-    device: ID3D12Device5,
-}
-
-impl Device {
-    pub fn new(adapter: IDXGIAdapter1) -> Result<Self> {
-        unsafe {
-            let mut device = None;
-            D3D12CreateDevice(&adapter, D3D_FEATURE_LEVEL_12_0, &mut device)?;
-            let device: ID3D12Device5 = device.unwrap();
-
-            Ok(Self { adapter, device })
-        }
-    }
-
-    pub fn open_shared_handle(&self, handle: HANDLE) -> Result<ID3D12Resource> {
-        unsafe {
-            let mut resource: Option<ID3D12Resource> = None;
-            self.OpenSharedHandle(handle, &mut resource)?;
-            Ok(resource.unwrap())
-        }
-    }
-
-    pub fn create_srv(
-        &self,
-        resource: &ID3D12Resource,
-        desc: Option<*const D3D12_SHADER_RESOURCE_VIEW_DESC>,
-        descriptor: D3D12_CPU_DESCRIPTOR_HANDLE,
-    ) {
-        unsafe {
-            self.device
-                .CreateShaderResourceView(resource, desc, descriptor);
-        }
-    }
-
-    pub fn create_uav(
-        &self,
-        resource: &ID3D12Resource,
-        desc: Option<*const D3D12_UNORDERED_ACCESS_VIEW_DESC>,
-        descriptor: D3D12_CPU_DESCRIPTOR_HANDLE,
-    ) {
-        unsafe {
-            self.device
-                .CreateUnorderedAccessView(resource, None, desc, descriptor);
-        }
-    }
-
-    pub fn adapter(&self) -> &IDXGIAdapter1 {
-        &self.adapter
-    }
-}
-
-impl AsRef<ID3D12Device5> for Device {
-    fn as_ref(&self) -> &ID3D12Device5 {
-        &self.device
-    }
-}
-
-impl std::ops::Deref for Device {
-    type Target = ID3D12Device5;
-
-    fn deref(&self) -> &Self::Target {
-        self.as_ref()
-    }
-}
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::HANDLE,
+    Graphics::{Direct3D::D3D_FEATURE_LEVEL_12_0, Direct3D12::*, Dxgi::*},
+};
+
+#[derive(Clone)]
+pub struct Device {
+    adapter: IDXGIAdapter1,
+    device: ID3D12Device5,
+}
+
+impl Device {
+    pub fn new(adapter: IDXGIAdapter1) -> Result<Self> {
+        unsafe {
+            let mut device = None;
+            D3D12CreateDevice(&adapter, D3D_FEATURE_LEVEL_12_0, &mut device)?;
+            let device: ID3D12Device5 = device.unwrap();
+
+            Ok(Self { adapter, device })
+        }
+    }
+
+    pub fn open_shared_handle(&self, handle: HANDLE) -> Result<ID3D12Resource> {
+        unsafe {
+            let mut resource: Option<ID3D12Resource> = None;
+            self.OpenSharedHandle(handle, &mut resource)?;
+            Ok(resource.unwrap())
+        }
+    }
+
+    pub fn create_srv(
+        &self,
+        resource: &ID3D12Resource,
+        desc: Option<*const D3D12_SHADER_RESOURCE_VIEW_DESC>,
+        descriptor: D3D12_CPU_DESCRIPTOR_HANDLE,
+    ) {
+        unsafe {
+            self.device
+                .CreateShaderResourceView(resource, desc, descriptor);
+        }
+    }
+
+    pub fn create_null_srv(
+        &self,
+        desc: Option<*const D3D12_SHADER_RESOURCE_VIEW_DESC>,
+        descriptor: D3D12_CPU_DESCRIPTOR_HANDLE,
+    ) {
+        unsafe {
+            self.device.CreateShaderResourceView(None, desc, descriptor);
+        }
+    }
+
+    pub fn create_uav(
+        &self,
+        resource: &ID3D12Resource,
+        desc: Option<*const D3D12_UNORDERED_ACCESS_VIEW_DESC>,
+        descriptor: D3D12_CPU_DESCRIPTOR_HANDLE,
+    ) {
+        unsafe {
+            self.device
+                .CreateUnorderedAccessView(resource, None, desc, descriptor);
+        }
+    }
+
+    pub fn create_rtv(
+        &self,
+        resource: &ID3D12Resource,
+        desc: Option<*const D3D12_RENDER_TARGET_VIEW_DESC>,
+        descriptor: D3D12_CPU_DESCRIPTOR_HANDLE,
+    ) {
+        unsafe {
+            self.device
+                .CreateRenderTargetView(resource, desc, descriptor);
+        }
+    }
+
+    // older gpus and drivers lack mesh shaders, the color cloud then falls back to vertex shaders
+    pub fn supports_mesh_shaders(&self) -> bool {
+        unsafe {
+            let mut options = D3D12_FEATURE_DATA_D3D12_OPTIONS7::default();
+            self.device
+                .CheckFeatureSupport(
+                    D3D12_FEATURE_D3D12_OPTIONS7,
+                    &mut options as *mut _ as *mut _,
+                    std::mem::size_of_val(&options) as u32,
+                )
+                .is_ok()
+                && options.MeshShaderTier != D3D12_MESH_SHADER_TIER_NOT_SUPPORTED
+        }
+    }
+
+    // amplification and mesh shader counters in pipeline statistics queries
+    pub fn supports_mesh_pipeline_stats(&self) -> bool {
+        unsafe {
+            let mut options = D3D12_FEATURE_DATA_D3D12_OPTIONS9::default();
+            self.device
+                .CheckFeatureSupport(
+                    D3D12_FEATURE_D3D12_OPTIONS9,
+                    &mut options as *mut _ as *mut _,
+                    std::mem::size_of_val(&options) as u32,
+                )
+                .is_ok()
+                && options.MeshShaderPipelineStatsSupported.as_bool()
+        }
+    }
+
+    pub fn adapter(&self) -> &IDXGIAdapter1 {
+        &self.adapter
+    }
+}
+
+impl AsRef<ID3D12Device5> for Device {
+    fn as_ref(&self) -> &ID3D12Device5 {
+        &self.device
+    }
+}
+
+impl std::ops::Deref for Device {
+    type Target = ID3D12Device5;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
@@ -1,47 +1,75 @@
-use anyhow::Result;
-use windows::Win32::{
-    Foundation::*,
-    Graphics::Direct3D12::*,
-    System::Threading::{CreateEventA, WaitForSingleObject, INFINITE},
-};
-
-use super::device::Device;
-
-pub struct Fence {
-    fence: ID3D12Fence,
-    fence_value: u64,
-    fence_event: HANDLE,
-}
-
-impl Fence {
-    pub fn new(device: &Device) -> Result<Self> {
-        unsafe {
-            let fence = device.CreateFence(0, D3D12_FENCE_FLAG_NONE)?;
-            let fence_value = 1;
-            let fence_event = CreateEventA(None, FALSE, FALSE, None)?;
-
-            Ok(Self {
-                fence,
-                fence_value,
-                fence_event,
-            })
-        }
-    }
-
-    pub fn wait(&mut self, command_queue: &ID3D12CommandQueue) -> Result<()> {
-        unsafe {
-            let fence = self.fence_value;
-            command_queue.Signal(&self.fence, fence)?;
-            self.fence_value += 1;
-
-            if self.fence.GetCompletedValue() < fence {
-                self.fence.SetEventOnCompletion(fence, self.fence_event)?;
-                WaitForSingleObject(self.fence_event, INFINITE);
-            }
-
-            Ok(())
-        }
-    }
-}
-
-unsafe impl Send for Fence {}
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::*,
+    Graphics::Direct3D12::*,
+    System::Threading::{CreateEventA, WaitForSingleObject, INFINITE},
+};
+
+use super::device::Device;
+
+pub struct Fence {
+    fence: ID3D12Fence,
+    fence_value: u64,
+    fence_event: HANDLE,
+}
+
+impl Fence {
+    pub fn new(device: &Device) -> Result<Self> {
+        unsafe {
+            let fence = device.CreateFence(0, D3D12_FENCE_FLAG_NONE)?;
+            let fence_value = 1;
+            let fence_event = CreateEventA(None, FALSE, FALSE, None)?;
+
+            Ok(Self {
+                fence,
+                fence_value,
+                fence_event,
+            })
+        }
+    }
+
+    // blocks until everything submitted so far has finished
+    pub fn wait(&mut self, command_queue: &ID3D12CommandQueue) -> Result<()> {
+        let fence = self.signal(command_queue)?;
+        self.wait_for(fence)
+    }
+
+    // the returned value is reached once the work submitted so far has finished
+    pub fn signal(&mut self, command_queue: &ID3D12CommandQueue) -> Result<u64> {
+        unsafe {
+            let fence = self.fence_value;
+            command_queue.Signal(&self.fence, fence)?;
+            self.fence_value += 1;
+
+            Ok(fence)
+        }
+    }
+
+    // makes the queue wait without blocking the cpu
+    pub fn queue_wait(&self, command_queue: &ID3D12CommandQueue, fence: u64) -> Result<()> {
+        unsafe {
+            command_queue.Wait(&self.fence, fence)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn wait_for(&self, fence: u64) -> Result<()> {
+        unsafe {
+            if self.fence.GetCompletedValue() < fence {
+                self.fence.SetEventOnCompletion(fence, self.fence_event)?;
+                WaitForSingleObject(self.fence_event, INFINITE);
+            }
+
+            Ok(())
+        }
+    }
+}
+
+impl AsRef<ID3D12Fence> for Fence {
+    fn as_ref(&self) -> &ID3D12Fence {
+        &self.fence
+    }
+}
+
+unsafe impl Send for Fence {}
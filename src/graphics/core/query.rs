@@ -1,158 +1,345 @@
-use std::collections::HashMap;
-
-use anyhow::Result;
-use windows::Win32::Graphics::Direct3D12::{
-    ID3D12QueryHeap, D3D12_QUERY_HEAP_TYPE, D3D12_QUERY_HEAP_TYPE_TIMESTAMP,
-    D3D12_RESOURCE_FLAG_NONE, D3D12_RESOURCE_STATE_COPY_DEST,
-};
-
-use super::{
-    device::Device,
-    resource::Resource,
-    wrap::{HeapProps, QueryHeapDesc, ResourceDesc},
-};
-
-const QUERY_HEAP_TYPE_TIMESTAMP: i32 = D3D12_QUERY_HEAP_TYPE_TIMESTAMP.0;
-
-#[derive(Clone)]
-pub struct QueryHeap<const TYPE: i32> {
-    heap: ID3D12QueryHeap,
-    size: u32,
-}
-
-impl<const TYPE: i32> QueryHeap<TYPE> {
-    fn new(device: &Device, size: u32) -> Result<Self> {
-        unsafe {
-            let desc = QueryHeapDesc::default(D3D12_QUERY_HEAP_TYPE(TYPE), size);
-            let mut heap: Option<ID3D12QueryHeap> = None;
-            device.CreateQueryHeap(&desc, &mut heap)?;
-
-            Ok(Self {
-                heap: heap.unwrap(),
-                size,
-            })
-        }
-    }
-
-    fn len(&self) -> usize {
-        self.size as _
-    }
-}
-
-impl<const TYPE: i32> AsRef<ID3D12QueryHeap> for QueryHeap<TYPE> {
-    fn as_ref(&self) -> &ID3D12QueryHeap {
-        &self.heap
-    }
-}
-
-impl<const TYPE: i32> std::ops::Deref for QueryHeap<TYPE> {
-    type Target = ID3D12QueryHeap;
-
-    fn deref(&self) -> &Self::Target {
-        self.as_ref()
-    }
-}
-
-pub type TimestampQueryHeap = QueryHeap<QUERY_HEAP_TYPE_TIMESTAMP>;
-
-pub struct TimestampQueryPool {
-    heap: TimestampQueryHeap,
-    staging: Resource,
-}
-
-impl TimestampQueryPool {
-    pub fn new(device: &Device) -> Result<Self> {
-        let count = 64;
-
-        let heap = QueryHeap::new(device, count)?;
-
-        let staging = Resource::new(
-            device,
-            &HeapProps::readback(),
-            None,
-            &ResourceDesc::buffer(8 * count as u64, D3D12_RESOURCE_FLAG_NONE),
-            D3D12_RESOURCE_STATE_COPY_DEST,
-            None,
-        )?;
-
-        Ok(Self { heap, staging })
-    }
-
-    pub fn iter(&self) -> TimestampQueryIter {
-        TimestampQueryIter {
-            heap: self.heap.clone(),
-            labels: Some(vec![]),
-        }
-    }
-
-    pub fn buffer(&self) -> &Resource {
-        &self.staging
-    }
-
-    pub fn dump(&self, freq: u64, labels: &[String]) -> Result<()> {
-        let mut label_time = HashMap::<String, u64>::new();
-
-        for (i, &time) in self
-            .staging
-            .read::<u64>(self.heap.len() as _)?
-            .iter()
-            .enumerate()
-        {
-            match label_time.entry(labels[i].clone()) {
-                std::collections::hash_map::Entry::Occupied(v) => {
-                    let t0 = *v.get();
-                    let dt = time - t0;
-                    println!(
-                        "{}\t{:>4}us",
-                        labels[i],
-                        (1000.0 * 1000.0 * dt as f64 / freq as f64) as u32
-                    );
-                }
-                std::collections::hash_map::Entry::Vacant(v) => {
-                    v.insert(time);
-                }
-            }
-        }
-
-        Ok(())
-    }
-}
-
-pub struct TimestampQueryIter {
-    heap: TimestampQueryHeap,
-    labels: Option<Vec<String>>,
-}
-
-impl TimestampQueryIter {
-    pub fn next(&mut self, label: &str) -> Option<u32> {
-        if let Some(labels) = &mut self.labels {
-            let index = labels.len();
-
-            if index < self.heap.len() {
-                labels.push(label.to_string());
-
-                Some(index as u32)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    }
-
-    pub fn heap(&self) -> &TimestampQueryHeap {
-        &self.heap
-    }
-
-    pub fn take_labels(&mut self) -> Option<Vec<String>> {
-        self.labels.take()
-    }
-
-    pub fn count(&self) -> u32 {
-        if let Some(labels) = &self.labels {
-            labels.len() as u32
-        } else {
-            0
-        }
-    }
-}
+use std::collections::HashMap;
+
+use anyhow::Result;
+use windows::Win32::Graphics::Direct3D12::{
+    ID3D12QueryHeap, D3D12_QUERY_DATA_PIPELINE_STATISTICS, D3D12_QUERY_DATA_PIPELINE_STATISTICS1,
+    D3D12_QUERY_HEAP_TYPE, D3D12_QUERY_HEAP_TYPE_PIPELINE_STATISTICS,
+    D3D12_QUERY_HEAP_TYPE_PIPELINE_STATISTICS1, D3D12_QUERY_HEAP_TYPE_TIMESTAMP, D3D12_QUERY_TYPE,
+    D3D12_QUERY_TYPE_PIPELINE_STATISTICS, D3D12_QUERY_TYPE_PIPELINE_STATISTICS1,
+    D3D12_QUERY_TYPE_TIMESTAMP, D3D12_RESOURCE_FLAG_NONE, D3D12_RESOURCE_STATE_COPY_DEST,
+};
+
+use super::{
+    command_queue::FRAME_COUNT,
+    device::Device,
+    resource::Resource,
+    wrap::{HeapProps, QueryHeapDesc, ResourceDesc},
+};
+
+const QUERY_HEAP_TYPE_TIMESTAMP: i32 = D3D12_QUERY_HEAP_TYPE_TIMESTAMP.0;
+const QUERY_HEAP_TYPE_PIPELINE_STATISTICS1: i32 = D3D12_QUERY_HEAP_TYPE_PIPELINE_STATISTICS1.0;
+
+// TYPE is the kind of pool, the heap itself may be of a plainer type the device supports
+#[derive(Clone)]
+pub struct QueryHeap<const TYPE: i32> {
+    heap: ID3D12QueryHeap,
+    heap_type: D3D12_QUERY_HEAP_TYPE,
+    size: u32,
+}
+
+impl<const TYPE: i32> QueryHeap<TYPE> {
+    fn new(device: &Device, heap_type: D3D12_QUERY_HEAP_TYPE, size: u32) -> Result<Self> {
+        unsafe {
+            let desc = QueryHeapDesc::default(heap_type, size);
+            let mut heap: Option<ID3D12QueryHeap> = None;
+            device.CreateQueryHeap(&desc, &mut heap)?;
+
+            Ok(Self {
+                heap: heap.unwrap(),
+                heap_type,
+                size,
+            })
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.size as _
+    }
+
+    // what BeginQuery, EndQuery and ResolveQueryData take for this heap
+    pub fn query_type(&self) -> D3D12_QUERY_TYPE {
+        match self.heap_type {
+            D3D12_QUERY_HEAP_TYPE_PIPELINE_STATISTICS1 => D3D12_QUERY_TYPE_PIPELINE_STATISTICS1,
+            D3D12_QUERY_HEAP_TYPE_PIPELINE_STATISTICS => D3D12_QUERY_TYPE_PIPELINE_STATISTICS,
+            _ => D3D12_QUERY_TYPE_TIMESTAMP,
+        }
+    }
+}
+
+impl<const TYPE: i32> AsRef<ID3D12QueryHeap> for QueryHeap<TYPE> {
+    fn as_ref(&self) -> &ID3D12QueryHeap {
+        &self.heap
+    }
+}
+
+impl<const TYPE: i32> std::ops::Deref for QueryHeap<TYPE> {
+    type Target = ID3D12QueryHeap;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+pub type TimestampQueryHeap = QueryHeap<QUERY_HEAP_TYPE_TIMESTAMP>;
+
+pub type PipelineStatsQueryHeap = QueryHeap<QUERY_HEAP_TYPE_PIPELINE_STATISTICS1>;
+
+// the pools double up to this many queries per frame
+const MAX_QUERIES: u32 = 4096;
+
+struct QueryFrame<const TYPE: i32> {
+    heap: QueryHeap<TYPE>,
+    staging: Resource,
+    labels: Vec<String>,
+    dropped: u32,
+}
+
+impl<const TYPE: i32> QueryFrame<TYPE> {
+    fn new(
+        device: &Device,
+        heap_type: D3D12_QUERY_HEAP_TYPE,
+        count: u32,
+        stride: u64,
+    ) -> Result<Self> {
+        let heap = QueryHeap::new(device, heap_type, count)?;
+
+        let staging = Resource::new(
+            device,
+            &HeapProps::readback(),
+            None,
+            &ResourceDesc::buffer(stride * count as u64, D3D12_RESOURCE_FLAG_NONE),
+            D3D12_RESOURCE_STATE_COPY_DEST,
+            None,
+        )?;
+
+        Ok(Self {
+            heap,
+            staging,
+            labels: vec![],
+            dropped: 0,
+        })
+    }
+}
+
+// one heap and readback buffer per frame in flight, results are read once the frame's fence has passed
+pub struct QueryPool<const TYPE: i32> {
+    frames: Vec<QueryFrame<TYPE>>,
+    heap_type: D3D12_QUERY_HEAP_TYPE,
+    capacity: u32,
+    // bytes one query resolves to
+    stride: u64,
+}
+
+impl<const TYPE: i32> QueryPool<TYPE> {
+    fn with_stride(
+        device: &Device,
+        heap_type: D3D12_QUERY_HEAP_TYPE,
+        capacity: u32,
+        stride: u64,
+    ) -> Result<Self> {
+        let frames = (0..FRAME_COUNT)
+            .map(|_| QueryFrame::new(device, heap_type, capacity, stride))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            frames,
+            heap_type,
+            capacity,
+            stride,
+        })
+    }
+
+    pub fn iter(&self, frame: usize) -> QueryIter<TYPE> {
+        QueryIter {
+            heap: self.frames[frame].heap.clone(),
+            labels: Some(vec![]),
+            dropped: 0,
+        }
+    }
+
+    pub fn buffer(&self, frame: usize) -> &Resource {
+        &self.frames[frame].staging
+    }
+
+    // keeps what the frame recorded until its results are ready
+    pub fn end_frame(&mut self, frame: usize, labels: Vec<String>, dropped: u32) {
+        let slot = &mut self.frames[frame];
+        slot.labels = labels;
+        slot.dropped = dropped;
+    }
+
+    // the frame's fence must have passed, returns its labels and results and grows its heap if it overflowed
+    fn read_frame<T: Clone>(
+        &mut self,
+        device: &Device,
+        frame: usize,
+    ) -> Result<(Vec<String>, Vec<T>)> {
+        let slot = &mut self.frames[frame];
+        let labels = std::mem::take(&mut slot.labels);
+        let dropped = std::mem::take(&mut slot.dropped);
+
+        let results = if labels.is_empty() {
+            vec![]
+        } else {
+            slot.staging.read::<T>(labels.len())?
+        };
+
+        if dropped > 0 {
+            let needed = labels.len() as u32 + dropped;
+            let capacity = needed.next_power_of_two().clamp(self.capacity, MAX_QUERIES);
+
+            if capacity > self.capacity {
+                println!(
+                    "queries\t{dropped} dropped, pool grows {} -> {capacity}",
+                    self.capacity
+                );
+                self.capacity = capacity;
+            } else {
+                println!("queries\t{dropped} dropped, pool is at its limit of {capacity}");
+            }
+        }
+
+        // the other frames follow once their own fences have passed
+        if slot.heap.len() < self.capacity as usize {
+            *slot = QueryFrame::new(device, self.heap_type, self.capacity, self.stride)?;
+        }
+
+        Ok((labels, results))
+    }
+}
+
+pub type TimestampQueryPool = QueryPool<QUERY_HEAP_TYPE_TIMESTAMP>;
+
+impl TimestampQueryPool {
+    pub fn new(device: &Device) -> Result<Self> {
+        Self::with_stride(device, D3D12_QUERY_HEAP_TYPE_TIMESTAMP, 64, 8)
+    }
+
+    // each label's first and second timestamp, in gpu ticks
+    pub fn begin_frame(
+        &mut self,
+        device: &Device,
+        frame: usize,
+    ) -> Result<Vec<(String, u64, u64)>> {
+        let (labels, times) = self.read_frame::<u64>(device, frame)?;
+        let mut label_time = HashMap::<String, u64>::new();
+        let mut spans = vec![];
+
+        for (label, time) in labels.into_iter().zip(times) {
+            match label_time.entry(label) {
+                std::collections::hash_map::Entry::Occupied(v) => {
+                    let (label, start) = v.remove_entry();
+                    spans.push((label, start, time.max(start)));
+                }
+                std::collections::hash_map::Entry::Vacant(v) => {
+                    v.insert(time);
+                }
+            }
+        }
+
+        Ok(spans)
+    }
+}
+
+pub type PipelineStats = D3D12_QUERY_DATA_PIPELINE_STATISTICS1;
+
+// statistics1 adds the amplification and mesh shader counters. devices without them fall back to
+// the plain statistics, which leave those counters at 0
+pub type PipelineStatsQueryPool = QueryPool<QUERY_HEAP_TYPE_PIPELINE_STATISTICS1>;
+
+impl PipelineStatsQueryPool {
+    pub fn new(device: &Device) -> Result<Self> {
+        if device.supports_mesh_pipeline_stats() {
+            let stride = std::mem::size_of::<PipelineStats>() as u64;
+            Self::with_stride(
+                device,
+                D3D12_QUERY_HEAP_TYPE_PIPELINE_STATISTICS1,
+                16,
+                stride,
+            )
+        } else {
+            let stride = std::mem::size_of::<D3D12_QUERY_DATA_PIPELINE_STATISTICS>() as u64;
+            Self::with_stride(
+                device,
+                D3D12_QUERY_HEAP_TYPE_PIPELINE_STATISTICS,
+                16,
+                stride,
+            )
+        }
+    }
+
+    // false when the amplification and mesh shader counters are missing
+    pub fn counts_mesh_shaders(&self) -> bool {
+        self.heap_type == D3D12_QUERY_HEAP_TYPE_PIPELINE_STATISTICS1
+    }
+
+    // counters of each pass the frame recorded
+    pub fn begin_frame(
+        &mut self,
+        device: &Device,
+        frame: usize,
+    ) -> Result<Vec<(String, PipelineStats)>> {
+        let stats = if self.counts_mesh_shaders() {
+            let (labels, stats) = self.read_frame::<PipelineStats>(device, frame)?;
+            labels.into_iter().zip(stats).collect()
+        } else {
+            let (labels, stats) =
+                self.read_frame::<D3D12_QUERY_DATA_PIPELINE_STATISTICS>(device, frame)?;
+            labels.into_iter().zip(stats.iter().map(extend)).collect()
+        };
+
+        Ok(stats)
+    }
+}
+
+fn extend(stats: &D3D12_QUERY_DATA_PIPELINE_STATISTICS) -> PipelineStats {
+    PipelineStats {
+        IAVertices: stats.IAVertices,
+        IAPrimitives: stats.IAPrimitives,
+        VSInvocations: stats.VSInvocations,
+        GSInvocations: stats.GSInvocations,
+        GSPrimitives: stats.GSPrimitives,
+        CInvocations: stats.CInvocations,
+        CPrimitives: stats.CPrimitives,
+        PSInvocations: stats.PSInvocations,
+        HSInvocations: stats.HSInvocations,
+        DSInvocations: stats.DSInvocations,
+        CSInvocations: stats.CSInvocations,
+        ..Default::default()
+    }
+}
+
+pub struct QueryIter<const TYPE: i32> {
+    heap: QueryHeap<TYPE>,
+    labels: Option<Vec<String>>,
+    dropped: u32,
+}
+
+impl<const TYPE: i32> QueryIter<TYPE> {
+    pub fn next(&mut self, label: &str) -> Option<u32> {
+        if let Some(labels) = &mut self.labels {
+            let index = labels.len();
+
+            if index < self.heap.len() {
+                labels.push(label.to_string());
+
+                Some(index as u32)
+            } else {
+                self.dropped += 1;
+                None
+            }
+        } else {
+            None
+        }
+    }
+
+    pub fn heap(&self) -> &QueryHeap<TYPE> {
+        &self.heap
+    }
+
+    // the recorded labels and how many queries did not fit
+    pub fn take_labels(&mut self) -> Option<(Vec<String>, u32)> {
+        self.labels.take().map(|labels| (labels, self.dropped))
+    }
+
+    pub fn count(&self) -> u32 {
+        if let Some(labels) = &self.labels {
+            labels.len() as u32
+        } else {
+            0
+        }
+    }
+}
+
+pub type TimestampQueryIter = QueryIter<QUERY_HEAP_TYPE_TIMESTAMP>;
+pub type PipelineStatsQueryIter = QueryIter<QUERY_HEAP_TYPE_PIPELINE_STATISTICS1>;
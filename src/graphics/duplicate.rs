@@ -1,112 +1,378 @@
-use anyhow::Result;
-use windows::{
-    core::Interface,
-    Win32::Graphics::{
-        Direct3D::*,
-        Direct3D11::*,
-        Dxgi::{Common::*, *},
-    },
-};
-
-use super::{
-    core::{descriptor::Descriptor, device::Device, resource::Resource, wrap::SrvDesc},
-    initializer::Initializer,
-};
-
-pub struct Duplication {
-    dupl: IDXGIOutputDuplication,
-    #[allow(unused)]
-    resource: Option<Resource>,
-    srv: Descriptor,
-    format: DXGI_FORMAT,
-}
-
-impl Duplication {
-    pub fn new(ctx: &mut Initializer) -> Result<Self> {
-        unsafe {
-            let device: &Device = ctx;
-            let adapter = device.adapter();
-
-            let flags = if cfg!(debug_assertions) {
-                D3D11_CREATE_DEVICE_BGRA_SUPPORT | D3D11_CREATE_DEVICE_DEBUG
-            } else {
-                D3D11_CREATE_DEVICE_BGRA_SUPPORT
-            };
-
-            let mut device_d3d11 = None;
-            D3D11CreateDevice(
-                adapter,
-                D3D_DRIVER_TYPE_UNKNOWN,
-                None,
-                flags,
-                Some(&[D3D_FEATURE_LEVEL_11_0]),
-                D3D11_SDK_VERSION,
-                Some(&mut device_d3d11),
-                None,
-                None,
-            )?;
-            let device_d3d11 = device_d3d11.unwrap();
-
-            let dupl = adapter
-                .EnumOutputs(0)?
-                .cast::<IDXGIOutput6>()?
-                .DuplicateOutput1(
-                    &device_d3d11,
-                    0,
-                    &[DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_B8G8R8A8_UNORM],
-                )?;
-
-            let format = match dupl.GetDesc().ModeDesc.Format {
-                DXGI_FORMAT_B8G8R8A8_UNORM => DXGI_FORMAT_B8G8R8A8_UNORM_SRGB,
-                _ => DXGI_FORMAT_R16G16B16A16_FLOAT,
-            };
-
-            let srv = ctx.next_descriptor();
-
-            Ok(Self {
-                dupl,
-                resource: None,
-                srv,
-                format,
-            })
-        }
-    }
-
-    pub fn duplicate(&mut self, device: &Device) -> Result<Option<Descriptor>> {
-        unsafe {
-            let _ = self.resource.take();
-
-            match self.dupl.ReleaseFrame() {
-                Err(e) if e.code() != DXGI_ERROR_INVALID_CALL => anyhow::bail!(e),
-                _ => {}
-            };
-
-            let mut info = DXGI_OUTDUPL_FRAME_INFO::default();
-            let mut resource = None;
-            let hr = self.dupl.AcquireNextFrame(1000, &mut info, &mut resource);
-
-            match hr {
-                Ok(_) => {
-                    if info.AccumulatedFrames == 0 {
-                        Ok(None)
-                    } else {
-                        let resource: IDXGIResource1 = resource.unwrap().cast()?;
-                        let resource = Resource::from_dxgi(&resource, device)?;
-
-                        device.create_srv(
-                            &resource,
-                            Some(&SrvDesc::texture2d(self.format)),
-                            self.srv.cpu,
-                        );
-
-                        self.resource = Some(resource);
-
-                        Ok(Some(self.srv))
-                    }
-                }
-                Err(e) if e.code() == DXGI_ERROR_WAIT_TIMEOUT => Ok(None),
-                Err(e) => anyhow::bail!(e),
-            }
-        }
-    }
-}
+use anyhow::Result;
+use windows::{
+    core::Interface,
+    Win32::{
+        Foundation::RECT,
+        Graphics::{
+            Direct3D::*,
+            Direct3D11::*,
+            Direct3D12::{
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE, D3D12_RESOURCE_STATE_COPY_DEST,
+            },
+            Dxgi::{Common::*, *},
+        },
+    },
+};
+
+use super::{
+    core::{descriptor::Descriptor, device::Device, resource::Resource, wrap::SrvDesc},
+    initializer::Initializer,
+    renderer::Renderer,
+    resource::Texture2D,
+};
+
+// where an output of the first adapter sits on the desktop, indexed like Duplication::new
+pub fn output_rect(index: u32) -> Result<RECT> {
+    unsafe {
+        let factory: IDXGIFactory2 = CreateDXGIFactory2(DXGI_CREATE_FACTORY_FLAGS::default())?;
+        let output = factory.EnumAdapters1(0)?.EnumOutputs(index)?;
+        Ok(output.GetDesc()?.DesktopCoordinates)
+    }
+}
+
+pub struct Duplication {
+    dupl: IDXGIOutputDuplication,
+    resource: Option<Resource>,
+    srv: Option<Descriptor>,
+    format: DXGI_FORMAT,
+    // top-left of the output on the virtual desktop
+    origin: (i32, i32),
+    // a copy of the last new frame, shown again while the desktop doesn't update
+    kept: Option<Texture2D>,
+    kept_valid: bool,
+    // the held frame hasn't been copied by keep() yet
+    fresh: bool,
+    reusing: bool,
+    present: Option<Present>,
+    pointer: Pointer,
+}
+
+// when the desktop image last changed, in QueryPerformanceCounter ticks
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Present {
+    pub time: i64,
+    // updates since the previous acquired frame, more than one when some were missed
+    pub accumulated: u32,
+}
+
+// on the same adapter, for the apis that only hand out d3d11 textures. video is for devices
+// shared with media foundation
+pub fn d3d11_device(device: &Device, video: bool) -> Result<ID3D11Device> {
+    unsafe {
+        let mut flags = if cfg!(debug_assertions) {
+            D3D11_CREATE_DEVICE_BGRA_SUPPORT | D3D11_CREATE_DEVICE_DEBUG
+        } else {
+            D3D11_CREATE_DEVICE_BGRA_SUPPORT
+        };
+        if video {
+            flags |= D3D11_CREATE_DEVICE_VIDEO_SUPPORT;
+        }
+
+        let mut device_d3d11 = None;
+        D3D11CreateDevice(
+            device.adapter(),
+            D3D_DRIVER_TYPE_UNKNOWN,
+            None,
+            flags,
+            Some(&[D3D_FEATURE_LEVEL_11_0]),
+            D3D11_SDK_VERSION,
+            Some(&mut device_d3d11),
+            None,
+            None,
+        )?;
+
+        Ok(device_d3d11.unwrap())
+    }
+}
+
+// the mouse pointer, duplicated frames never contain it
+#[derive(Clone, Debug, Default)]
+pub struct Pointer {
+    // top-left of the shape on the output
+    pub position: [i32; 2],
+    // false while the pointer is on another output
+    pub visible: bool,
+    // the last acquired frame moved the pointer or changed its shape
+    pub moved: bool,
+    // bumped with each new shape
+    pub shape_id: u32,
+    pub shape: PointerShape,
+}
+
+// bgra rows, tightly packed. masked shapes xor the screen where alpha is set and replace it
+// elsewhere, the others are blended with straight alpha
+#[derive(Clone, Debug, Default)]
+pub struct PointerShape {
+    pub width: u32,
+    pub height: u32,
+    pub masked: bool,
+    pub pixels: Vec<u8>,
+}
+
+impl PointerShape {
+    // the buffer of GetFramePointerShape, monochrome shapes become masked ones
+    pub fn from_dxgi(kind: u32, width: u32, height: u32, pitch: u32, data: &[u8]) -> Self {
+        let pitch = pitch as usize;
+
+        if kind == DXGI_OUTDUPL_POINTER_SHAPE_TYPE_MONOCHROME.0 as u32 {
+            // an and mask over an xor mask, one bit per pixel
+            let height = height / 2;
+            let bit = |row: usize, x: usize| data[row * pitch + x / 8] & (0x80 >> (x % 8)) != 0;
+
+            let mut pixels = Vec::with_capacity((4 * width * height) as usize);
+            for y in 0..height as usize {
+                for x in 0..width as usize {
+                    let and = bit(y, x);
+                    let xor = bit(y + height as usize, x);
+                    let v = if xor { 0xff } else { 0 };
+                    pixels.extend_from_slice(&[v, v, v, if and { 0xff } else { 0 }]);
+                }
+            }
+
+            return Self {
+                width,
+                height,
+                masked: true,
+                pixels,
+            };
+        }
+
+        let row_size = 4 * width as usize;
+        let mut pixels = Vec::with_capacity(row_size * height as usize);
+        for row in data.chunks(pitch).take(height as usize) {
+            pixels.extend_from_slice(&row[..row_size]);
+        }
+
+        Self {
+            width,
+            height,
+            masked: kind == DXGI_OUTDUPL_POINTER_SHAPE_TYPE_MASKED_COLOR.0 as u32,
+            pixels,
+        }
+    }
+}
+
+impl Duplication {
+    // the analyzed output, 0 is the primary, bound to shaders through duplicate()
+    pub fn new(ctx: &mut Initializer, index: u32) -> Result<Self> {
+        let mut dupl = Self::output(ctx, index)?;
+        dupl.srv = Some(ctx.next_descriptor());
+        dupl.kept = Some(Texture2D::new(ctx, 1, 1, dupl.format)?);
+
+        Ok(dupl)
+    }
+
+    // any output of the adapter, only for copies through resource()
+    pub fn output(device: &Device, index: u32) -> Result<Self> {
+        unsafe {
+            let adapter = device.adapter();
+            let device_d3d11 = d3d11_device(device, false)?;
+
+            let output = adapter.EnumOutputs(index)?.cast::<IDXGIOutput6>()?;
+            let coords = output.GetDesc()?.DesktopCoordinates;
+            let dupl = output.DuplicateOutput1(
+                &device_d3d11,
+                0,
+                &[DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_B8G8R8A8_UNORM],
+            )?;
+
+            let format = match dupl.GetDesc().ModeDesc.Format {
+                DXGI_FORMAT_B8G8R8A8_UNORM => DXGI_FORMAT_B8G8R8A8_UNORM_SRGB,
+                _ => DXGI_FORMAT_R16G16B16A16_FLOAT,
+            };
+
+            Ok(Self {
+                dupl,
+                resource: None,
+                srv: None,
+                format,
+                origin: (coords.left, coords.top),
+                kept: None,
+                kept_valid: false,
+                fresh: false,
+                reusing: false,
+                present: None,
+                pointer: Pointer::default(),
+            })
+        }
+    }
+
+    pub fn origin(&self) -> (i32, i32) {
+        self.origin
+    }
+
+    // the desktop texture of the last acquired frame, or its copy while it is reused
+    pub fn resource(&self) -> Option<&Resource> {
+        match &self.kept {
+            Some(kept) if self.reusing => Some(kept),
+            _ => self.resource.as_ref(),
+        }
+    }
+
+    // waits up to timeout (ms) for a desktop update
+    pub fn duplicate(&mut self, device: &Device, timeout: u32) -> Result<Option<Descriptor>> {
+        self.reusing = false;
+        let acquired = self.acquire(device, timeout)?;
+        self.fresh = acquired;
+
+        let (Some(srv), Some(resource)) = (self.srv, &self.resource) else {
+            return Ok(None);
+        };
+
+        device.create_srv(resource, Some(&SrvDesc::texture2d(self.format)), srv.cpu);
+
+        Ok(acquired.then_some(srv))
+    }
+
+    pub fn pointer(&self) -> &Pointer {
+        &self.pointer
+    }
+
+    // the desktop update of the frame acquired last, None without one
+    pub fn present(&self) -> Option<Present> {
+        self.present
+    }
+
+    // the copy made by keep(), for a frozen desktop or when duplicate() had no new frame
+    pub fn reuse(&mut self) -> Option<Descriptor> {
+        let kept = self.kept.as_ref().filter(|_| self.kept_valid)?;
+        self.reusing = true;
+
+        Some(kept.srv)
+    }
+
+    // copies each new frame once for reuse(), the desktop is read after the copy in the same frame
+    pub fn keep(&mut self, ctx: &mut Renderer) -> Result<()> {
+        let (Some(kept), Some(desktop), true) = (&mut self.kept, &self.resource, self.fresh) else {
+            return Ok(());
+        };
+
+        let (width, height) = desktop.size();
+        if kept.size() != (width, height) {
+            let old = kept.resource.clone();
+            kept.resize(ctx.device(), width, height)?;
+            ctx.retire(old);
+        }
+
+        // desktop textures are promoted from the common state, no barrier needed for reads
+        ctx.resource_barrier(&[kept.transition_barrier(
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_COPY_DEST,
+        )]);
+        ctx.copy_resource(kept, desktop);
+        ctx.resource_barrier(&[kept.transition_barrier(
+            D3D12_RESOURCE_STATE_COPY_DEST,
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+        )]);
+
+        self.kept_valid = true;
+        self.fresh = false;
+
+        Ok(())
+    }
+
+    // releases the previous frame, true when a new one was acquired within the timeout (ms)
+    // a frame without desktop updates is still held, it shows the same image as before
+    pub fn acquire(&mut self, device: &Device, timeout: u32) -> Result<bool> {
+        unsafe {
+            let _ = self.resource.take();
+            self.present = None;
+            self.pointer.moved = false;
+
+            match self.dupl.ReleaseFrame() {
+                Err(e) if e.code() != DXGI_ERROR_INVALID_CALL => anyhow::bail!(e),
+                _ => {}
+            };
+
+            let mut info = DXGI_OUTDUPL_FRAME_INFO::default();
+            let mut resource = None;
+            let hr = self
+                .dupl
+                .AcquireNextFrame(timeout, &mut info, &mut resource);
+
+            match hr {
+                Ok(_) => {
+                    let resource: IDXGIResource1 = resource.unwrap().cast()?;
+                    self.resource = Some(Resource::from_dxgi(&resource, device)?);
+
+                    // zero when only the pointer moved
+                    if info.LastPresentTime != 0 {
+                        self.present = Some(Present {
+                            time: info.LastPresentTime,
+                            accumulated: info.AccumulatedFrames,
+                        });
+                    }
+
+                    self.update_pointer(&info)?;
+
+                    Ok(info.AccumulatedFrames != 0)
+                }
+                Err(e) if e.code() == DXGI_ERROR_WAIT_TIMEOUT => Ok(false),
+                Err(e) => anyhow::bail!(e),
+            }
+        }
+    }
+
+    // the position is only reported when the pointer moved, the shape only when it changed
+    fn update_pointer(&mut self, info: &DXGI_OUTDUPL_FRAME_INFO) -> Result<()> {
+        if info.LastMouseUpdateTime != 0 {
+            let position = info.PointerPosition.Position;
+            self.pointer.position = [position.x, position.y];
+            self.pointer.visible = info.PointerPosition.Visible.as_bool();
+            self.pointer.moved = true;
+        }
+
+        if info.PointerShapeBufferSize == 0 {
+            return Ok(());
+        }
+
+        let mut data = vec![0u8; info.PointerShapeBufferSize as usize];
+        let mut required = 0;
+        let mut shape = DXGI_OUTDUPL_POINTER_SHAPE_INFO::default();
+        unsafe {
+            self.dupl.GetFramePointerShape(
+                data.len() as _,
+                data.as_mut_ptr() as _,
+                &mut required,
+                &mut shape,
+            )?;
+        }
+
+        self.pointer.shape =
+            PointerShape::from_dxgi(shape.Type, shape.Width, shape.Height, shape.Pitch, &data);
+        self.pointer.shape_id = self.pointer.shape_id.wrapping_add(1);
+        self.pointer.moved = true;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monochrome_shapes_become_masked() {
+        // 2x1, and mask then xor mask, a 2 byte pitch
+        let and = [0b0100_0000, 0];
+        let xor = [0b1100_0000, 0];
+        let data = [and, xor].concat();
+        let kind = DXGI_OUTDUPL_POINTER_SHAPE_TYPE_MONOCHROME.0 as u32;
+        let shape = PointerShape::from_dxgi(kind, 2, 2, 2, &data);
+
+        assert_eq!((shape.width, shape.height), (2, 1));
+        assert!(shape.masked);
+        // white replacing the screen, then white inverting it
+        assert_eq!(shape.pixels, [0xff, 0xff, 0xff, 0, 0xff, 0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn color_rows_drop_the_pitch_padding() {
+        let data = [[1u8; 4], [9; 4], [2; 4], [9; 4]].concat();
+        let kind = DXGI_OUTDUPL_POINTER_SHAPE_TYPE_COLOR.0 as u32;
+        let shape = PointerShape::from_dxgi(kind, 1, 2, 8, &data);
+
+        assert!(!shape.masked);
+        assert_eq!(shape.pixels, [1, 1, 1, 1, 2, 2, 2, 2]);
+    }
+}
@@ -1,204 +1,927 @@
-#![allow(unused)]
-
-pub fn div_round_up(num: u32, div: u32) -> u32 {
-    (num + div - 1) / div
-}
-
-#[repr(transparent)]
-#[derive(Clone, Copy, Default, Debug)]
-pub struct Vec4(pub [f32; 4]);
-
-impl Vec4 {
-    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
-        Self([x, y, z, w])
-    }
-
-    pub fn zero() -> Self {
-        Self::new(0.0, 0.0, 0.0, 0.0)
-    }
-
-    pub fn x(&self) -> f32 {
-        self[0]
-    }
-
-    pub fn y(&self) -> f32 {
-        self[1]
-    }
-
-    pub fn z(&self) -> f32 {
-        self[2]
-    }
-
-    pub fn w(&self) -> f32 {
-        self[3]
-    }
-
-    pub fn dot(&self, other: &Self) -> f32 {
-        self.x() * other.x() + self.y() * other.y() + self.z() * other.z() + self.w() * other.w()
-    }
-}
-
-impl std::ops::Index<usize> for Vec4 {
-    type Output = f32;
-
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.0[index]
-    }
-}
-
-impl std::ops::IndexMut<usize> for Vec4 {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.0[index]
-    }
-}
-
-impl std::fmt::Display for Vec4 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {} {} {}", self.x(), self.y(), self.z(), self.w())
-    }
-}
-
-#[repr(transparent)]
-#[derive(Clone, Copy, Default, Debug)]
-pub struct Matrix(pub [Vec4; 4]);
-
-impl Matrix {
-    pub fn new(col0: Vec4, col1: Vec4, col2: Vec4, col3: Vec4) -> Self {
-        Self([col0, col1, col2, col3])
-    }
-
-    pub fn zero() -> Self {
-        Self::new(Vec4::zero(), Vec4::zero(), Vec4::zero(), Vec4::zero())
-    }
-
-    pub fn identity() -> Self {
-        Self::new(
-            Vec4::new(1.0, 0.0, 0.0, 0.0),
-            Vec4::new(0.0, 1.0, 0.0, 0.0),
-            Vec4::new(0.0, 0.0, 1.0, 0.0),
-            Vec4::new(0.0, 0.0, 0.0, 1.0),
-        )
-    }
-
-    pub fn rot_x(angle: f32) -> Self {
-        let (s, c) = angle.sin_cos();
-
-        Self::new(
-            Vec4::new(1.0, 0.0, 0.0, 0.0),
-            Vec4::new(0.0, c, -s, 0.0),
-            Vec4::new(0.0, s, c, 0.0),
-            Vec4::new(0.0, 0.0, 0.0, 1.0),
-        )
-    }
-
-    pub fn rot_y(angle: f32) -> Self {
-        let (s, c) = angle.sin_cos();
-
-        Self::new(
-            Vec4::new(c, 0.0, s, 0.0),
-            Vec4::new(0.0, 1.0, 0.0, 0.0),
-            Vec4::new(-s, 0.0, c, 0.0),
-            Vec4::new(0.0, 0.0, 0.0, 1.0),
-        )
-    }
-
-    pub fn rot_z(angle: f32) -> Self {
-        let (s, c) = angle.sin_cos();
-
-        Self::new(
-            Vec4::new(c, -s, 0.0, 0.0),
-            Vec4::new(s, c, 0.0, 0.0),
-            Vec4::new(0.0, 0.0, 1.0, 0.0),
-            Vec4::new(0.0, 0.0, 0.0, 1.0),
-        )
-    }
-
-    pub fn scale(x: f32, y: f32, z: f32) -> Self {
-        Self::new(
-            Vec4::new(x, 0.0, 0.0, 0.0),
-            Vec4::new(0.0, y, 0.0, 0.0),
-            Vec4::new(0.0, 0.0, z, 0.0),
-            Vec4::new(0.0, 0.0, 0.0, 1.0),
-        )
-    }
-
-    pub fn translate(x: f32, y: f32, z: f32) -> Self {
-        Self::new(
-            Vec4::new(1.0, 0.0, 0.0, x),
-            Vec4::new(0.0, 1.0, 0.0, y),
-            Vec4::new(0.0, 0.0, 1.0, z),
-            Vec4::new(0.0, 0.0, 0.0, 1.0),
-        )
-    }
-
-    pub fn row(&self, row: usize) -> Vec4 {
-        Vec4::new(
-            self[(row, 0)],
-            self[(row, 1)],
-            self[(row, 2)],
-            self[(row, 3)],
-        )
-    }
-
-    pub fn col(&self, col: usize) -> &Vec4 {
-        &self.0[col]
-    }
-
-    pub fn mul(&self, other: &Self) -> Self {
-        let mut m = Self::zero();
-
-        for i in 0..4 {
-            let row = self.row(i);
-
-            for j in 0..4 {
-                m[(i, j)] = row.dot(other.col(j));
-            }
-        }
-
-        m
-    }
-
-    pub fn as_4x3(&self) -> [f32; 12] {
-        [
-            self.0[0][0],
-            self.0[0][1],
-            self.0[0][2],
-            self.0[0][3],
-            self.0[1][0],
-            self.0[1][1],
-            self.0[1][2],
-            self.0[1][3],
-            self.0[2][0],
-            self.0[2][1],
-            self.0[2][2],
-            self.0[2][3],
-        ]
-    }
-}
-
-impl std::ops::Index<(usize, usize)> for Matrix {
-    type Output = f32;
-
-    fn index(&self, index: (usize, usize)) -> &Self::Output {
-        &self.0[index.1][index.0]
-    }
-}
-
-impl std::ops::IndexMut<(usize, usize)> for Matrix {
-    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
-        &mut self.0[index.1][index.0]
-    }
-}
-
-impl std::fmt::Display for Matrix {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "[{}\n {}\n {}\n {}]",
-            self.row(0),
-            self.row(1),
-            self.row(2),
-            self.row(3),
-        )
-    }
-}
+#![allow(unused)]
+
+use std::time::Instant;
+
+pub fn div_round_up(num: u32, div: u32) -> u32 {
+    (num + div - 1) / div
+}
+
+pub fn f16_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32 / 1024.0;
+
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-14),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa) * 2f32.powi(exponent - 15),
+    }
+}
+
+pub fn srgb_to_linear(srgb: f32) -> f32 {
+    if srgb <= 0.04045 {
+        srgb / 12.92
+    } else {
+        ((srgb + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb8(linear: f32) -> u8 {
+    let linear = linear.clamp(0.0, 1.0);
+    let srgb = if linear <= 0.0031308 {
+        12.92 * linear
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+
+    (255.0 * srgb + 0.5) as u8
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Vec3(pub [f32; 3]);
+
+impl Vec3 {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self([x, y, z])
+    }
+
+    pub fn zero() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn x(&self) -> f32 {
+        self[0]
+    }
+
+    pub fn y(&self) -> f32 {
+        self[1]
+    }
+
+    pub fn z(&self) -> f32 {
+        self[2]
+    }
+
+    pub fn dot(&self, other: &Self) -> f32 {
+        self.x() * other.x() + self.y() * other.y() + self.z() * other.z()
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        Self::new(
+            self.y() * other.z() - self.z() * other.y(),
+            self.z() * other.x() - self.x() * other.z(),
+            self.x() * other.y() - self.y() * other.x(),
+        )
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Self {
+        let len = self.length();
+        if len > 0.0 {
+            self.scale(1.0 / len)
+        } else {
+            *self
+        }
+    }
+
+    pub fn scale(&self, s: f32) -> Self {
+        Self::new(s * self.x(), s * self.y(), s * self.z())
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        Self::new(
+            self.x() - other.x(),
+            self.y() - other.y(),
+            self.z() - other.z(),
+        )
+    }
+}
+
+impl std::ops::Index<usize> for Vec3 {
+    type Output = f32;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl std::ops::IndexMut<usize> for Vec3 {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl std::fmt::Display for Vec3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.x(), self.y(), self.z())
+    }
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct Vec4(pub [f32; 4]);
+
+impl Vec4 {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self([x, y, z, w])
+    }
+
+    pub fn zero() -> Self {
+        Self::new(0.0, 0.0, 0.0, 0.0)
+    }
+
+    pub fn x(&self) -> f32 {
+        self[0]
+    }
+
+    pub fn y(&self) -> f32 {
+        self[1]
+    }
+
+    pub fn z(&self) -> f32 {
+        self[2]
+    }
+
+    pub fn w(&self) -> f32 {
+        self[3]
+    }
+
+    pub fn dot(&self, other: &Self) -> f32 {
+        self.x() * other.x() + self.y() * other.y() + self.z() * other.z() + self.w() * other.w()
+    }
+}
+
+impl std::ops::Index<usize> for Vec4 {
+    type Output = f32;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl std::ops::IndexMut<usize> for Vec4 {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl std::fmt::Display for Vec4 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {} {}", self.x(), self.y(), self.z(), self.w())
+    }
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct Matrix(pub [Vec4; 4]);
+
+impl Matrix {
+    pub fn new(col0: Vec4, col1: Vec4, col2: Vec4, col3: Vec4) -> Self {
+        Self([col0, col1, col2, col3])
+    }
+
+    pub fn zero() -> Self {
+        Self::new(Vec4::zero(), Vec4::zero(), Vec4::zero(), Vec4::zero())
+    }
+
+    pub fn identity() -> Self {
+        Self::new(
+            Vec4::new(1.0, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 1.0, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, 1.0, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    pub fn rot_x(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+
+        Self::new(
+            Vec4::new(1.0, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, c, -s, 0.0),
+            Vec4::new(0.0, s, c, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    pub fn rot_y(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+
+        Self::new(
+            Vec4::new(c, 0.0, s, 0.0),
+            Vec4::new(0.0, 1.0, 0.0, 0.0),
+            Vec4::new(-s, 0.0, c, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    pub fn rot_z(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+
+        Self::new(
+            Vec4::new(c, -s, 0.0, 0.0),
+            Vec4::new(s, c, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, 1.0, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    pub fn scale(x: f32, y: f32, z: f32) -> Self {
+        Self::new(
+            Vec4::new(x, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, y, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, z, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    pub fn translate(x: f32, y: f32, z: f32) -> Self {
+        Self::new(
+            Vec4::new(1.0, 0.0, 0.0, x),
+            Vec4::new(0.0, 1.0, 0.0, y),
+            Vec4::new(0.0, 0.0, 1.0, z),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    // left-handed, depth mapped to [0, 1]
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let y = 1.0 / (0.5 * fov_y).tan();
+        let x = y / aspect;
+        let z = far / (far - near);
+
+        Self::new(
+            Vec4::new(x, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, y, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, z, -near * z),
+            Vec4::new(0.0, 0.0, 1.0, 0.0),
+        )
+    }
+
+    // left-handed view matrix
+    pub fn look_at(eye: &Vec3, target: &Vec3, up: &Vec3) -> Self {
+        let z = target.sub(eye).normalize();
+        let x = up.cross(&z).normalize();
+        let y = z.cross(&x);
+
+        Self::new(
+            Vec4::new(x.x(), x.y(), x.z(), -x.dot(eye)),
+            Vec4::new(y.x(), y.y(), y.z(), -y.dot(eye)),
+            Vec4::new(z.x(), z.y(), z.z(), -z.dot(eye)),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    pub fn row(&self, row: usize) -> Vec4 {
+        Vec4::new(
+            self[(row, 0)],
+            self[(row, 1)],
+            self[(row, 2)],
+            self[(row, 3)],
+        )
+    }
+
+    pub fn col(&self, col: usize) -> &Vec4 {
+        &self.0[col]
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut m = Self::zero();
+
+        for i in 0..4 {
+            let row = self.row(i);
+
+            for j in 0..4 {
+                m[(i, j)] = row.dot(other.col(j));
+            }
+        }
+
+        m
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = Self::zero();
+
+        for i in 0..4 {
+            for j in 0..4 {
+                m[(i, j)] = self[(j, i)];
+            }
+        }
+
+        m
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let (s, c) = self.sub_determinants();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    // None when the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let (s, c) = self.sub_determinants();
+
+        let a = |i, j| self[(i, j)];
+        let adjugate = [
+            [
+                a(1, 1) * c[5] - a(1, 2) * c[4] + a(1, 3) * c[3],
+                -a(0, 1) * c[5] + a(0, 2) * c[4] - a(0, 3) * c[3],
+                a(3, 1) * s[5] - a(3, 2) * s[4] + a(3, 3) * s[3],
+                -a(2, 1) * s[5] + a(2, 2) * s[4] - a(2, 3) * s[3],
+            ],
+            [
+                -a(1, 0) * c[5] + a(1, 2) * c[2] - a(1, 3) * c[1],
+                a(0, 0) * c[5] - a(0, 2) * c[2] + a(0, 3) * c[1],
+                -a(3, 0) * s[5] + a(3, 2) * s[2] - a(3, 3) * s[1],
+                a(2, 0) * s[5] - a(2, 2) * s[2] + a(2, 3) * s[1],
+            ],
+            [
+                a(1, 0) * c[4] - a(1, 1) * c[2] + a(1, 3) * c[0],
+                -a(0, 0) * c[4] + a(0, 1) * c[2] - a(0, 3) * c[0],
+                a(3, 0) * s[4] - a(3, 1) * s[2] + a(3, 3) * s[0],
+                -a(2, 0) * s[4] + a(2, 1) * s[2] - a(2, 3) * s[0],
+            ],
+            [
+                -a(1, 0) * c[3] + a(1, 1) * c[1] - a(1, 2) * c[0],
+                a(0, 0) * c[3] - a(0, 1) * c[1] + a(0, 2) * c[0],
+                -a(3, 0) * s[3] + a(3, 1) * s[1] - a(3, 2) * s[0],
+                a(2, 0) * s[3] - a(2, 1) * s[1] + a(2, 2) * s[0],
+            ],
+        ];
+
+        let mut m = Self::zero();
+        for (i, row) in adjugate.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                m[(i, j)] = value / det;
+            }
+        }
+
+        Some(m)
+    }
+
+    // 2x2 determinants of the upper (s) and lower (c) row pairs
+    fn sub_determinants(&self) -> ([f32; 6], [f32; 6]) {
+        let a = |i, j| self[(i, j)];
+
+        let s = [
+            a(0, 0) * a(1, 1) - a(1, 0) * a(0, 1),
+            a(0, 0) * a(1, 2) - a(1, 0) * a(0, 2),
+            a(0, 0) * a(1, 3) - a(1, 0) * a(0, 3),
+            a(0, 1) * a(1, 2) - a(1, 1) * a(0, 2),
+            a(0, 1) * a(1, 3) - a(1, 1) * a(0, 3),
+            a(0, 2) * a(1, 3) - a(1, 2) * a(0, 3),
+        ];
+
+        let c = [
+            a(2, 0) * a(3, 1) - a(3, 0) * a(2, 1),
+            a(2, 0) * a(3, 2) - a(3, 0) * a(2, 2),
+            a(2, 0) * a(3, 3) - a(3, 0) * a(2, 3),
+            a(2, 1) * a(3, 2) - a(3, 1) * a(2, 2),
+            a(2, 1) * a(3, 3) - a(3, 1) * a(2, 3),
+            a(2, 2) * a(3, 3) - a(3, 2) * a(2, 3),
+        ];
+
+        (s, c)
+    }
+
+    // gram-schmidt on the 3x3 rotation part, removes scale and skew drift
+    pub fn orthonormalize(&self) -> Self {
+        let axis = |i: usize| {
+            let col = self.col(i);
+            Vec3::new(col.x(), col.y(), col.z())
+        };
+
+        let x = axis(0).normalize();
+        let y = axis(1);
+        let y = y.sub(&x.scale(x.dot(&y))).normalize();
+        let z = x.cross(&y);
+
+        let mut m = *self;
+        for (i, v) in [x, y, z].iter().enumerate() {
+            m.0[i] = Vec4::new(v.x(), v.y(), v.z(), self.col(i).w());
+        }
+
+        m
+    }
+
+    pub fn transform(&self, v: &Vec4) -> Vec4 {
+        Vec4::new(
+            v.dot(self.col(0)),
+            v.dot(self.col(1)),
+            v.dot(self.col(2)),
+            v.dot(self.col(3)),
+        )
+    }
+
+    pub fn as_4x4(&self) -> [f32; 16] {
+        let mut m = [0.0; 16];
+        for (i, col) in self.0.iter().enumerate() {
+            m[4 * i..4 * i + 4].copy_from_slice(&col.0);
+        }
+        m
+    }
+
+    pub fn as_4x3(&self) -> [f32; 12] {
+        [
+            self.0[0][0],
+            self.0[0][1],
+            self.0[0][2],
+            self.0[0][3],
+            self.0[1][0],
+            self.0[1][1],
+            self.0[1][2],
+            self.0[1][3],
+            self.0[2][0],
+            self.0[2][1],
+            self.0[2][2],
+            self.0[2][3],
+        ]
+    }
+}
+
+impl std::ops::Index<(usize, usize)> for Matrix {
+    type Output = f32;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        &self.0[index.1][index.0]
+    }
+}
+
+impl std::ops::IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
+        &mut self.0[index.1][index.0]
+    }
+}
+
+impl std::fmt::Display for Matrix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}\n {}\n {}\n {}]",
+            self.row(0),
+            self.row(1),
+            self.row(2),
+            self.row(3),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    pub fn from_axis_angle(axis: &Vec3, angle: f32) -> Self {
+        let axis = axis.normalize();
+        let (s, c) = (0.5 * angle).sin_cos();
+
+        Self::new(s * axis.x(), s * axis.y(), s * axis.z(), c)
+    }
+
+    pub fn rot_x(angle: f32) -> Self {
+        Self::from_axis_angle(&Vec3::new(1.0, 0.0, 0.0), angle)
+    }
+
+    pub fn rot_y(angle: f32) -> Self {
+        Self::from_axis_angle(&Vec3::new(0.0, 1.0, 0.0), angle)
+    }
+
+    pub fn rot_z(angle: f32) -> Self {
+        Self::from_axis_angle(&Vec3::new(0.0, 0.0, 1.0), angle)
+    }
+
+    pub fn length(&self) -> f32 {
+        (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt()
+    }
+
+    pub fn normalize(&self) -> Self {
+        let len = self.length();
+        if len > 0.0 {
+            Self::new(self.x / len, self.y / len, self.z / len, self.w / len)
+        } else {
+            Self::identity()
+        }
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    // self.mul(other).to_matrix() == other.to_matrix().mul(&self.to_matrix())
+    pub fn mul(&self, other: &Self) -> Self {
+        let (a, b) = (self, other);
+
+        Self::new(
+            a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+            a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+        )
+    }
+
+    pub fn to_matrix(self) -> Matrix {
+        let Self { x, y, z, w } = self;
+
+        Matrix::new(
+            Vec4::new(
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+                0.0,
+            ),
+            Vec4::new(
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+                0.0,
+            ),
+            Vec4::new(
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+// the unit sphere spans this much of the square viewport in either projection
+const CAMERA_SCALE: f32 = 0.9;
+
+// from the origin, in perspective
+const CAMERA_DISTANCE: f32 = 3.0;
+
+// seconds for a released spin to slow down to 1/e, and the speed (rad/s) where it stops
+const SPIN_DECAY: f32 = 0.6;
+const SPIN_STOP: f32 = 0.05;
+
+// orbits the origin. a drag released while moving keeps the view turning, slowing down until it
+// stops. the spin is a function of time, so any copy of the camera shows the same view
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub rotation: Matrix,
+    pub perspective: bool,
+    // rad/s about the view's y and x axes, and when the drag was released
+    spin: Option<([f32; 2], Instant)>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            rotation: Matrix::identity(),
+            perspective: false,
+            spin: None,
+        }
+    }
+}
+
+impl Camera {
+    // turns the view by the angles about its y and x axes, a spin stops where it is
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        self.stop(Instant::now());
+
+        let rot = Matrix::rot_y(yaw).mul(&Matrix::rot_x(pitch));
+        self.rotation = self.rotation.mul(&rot).orthonormalize();
+    }
+
+    // keeps turning at the velocity (rad/s) a drag was released with
+    pub fn fling(&mut self, yaw: f32, pitch: f32) {
+        let now = Instant::now();
+        self.stop(now);
+
+        if yaw.hypot(pitch) > SPIN_STOP {
+            self.spin = Some(([yaw, pitch], now));
+        }
+    }
+
+    // back to the default orientation, the projection is kept
+    pub fn reset(&mut self) {
+        *self = Self {
+            perspective: self.perspective,
+            ..Self::default()
+        };
+    }
+
+    pub fn stop(&mut self, now: Instant) {
+        self.rotation = self.rotation_at(now);
+        self.spin = None;
+    }
+
+    pub fn spinning(&self, now: Instant) -> bool {
+        self.spin.is_some_and(|([yaw, pitch], start)| {
+            let t = now.saturating_duration_since(start).as_secs_f32();
+            yaw.hypot(pitch) * (-t / SPIN_DECAY).exp() > SPIN_STOP
+        })
+    }
+
+    pub fn rotation_at(&self, now: Instant) -> Matrix {
+        let Some(([yaw, pitch], start)) = self.spin else {
+            return self.rotation;
+        };
+
+        // the angle a velocity decaying from the release covers, about a fixed axis
+        let t = now.saturating_duration_since(start).as_secs_f32();
+        let angle = yaw.hypot(pitch) * SPIN_DECAY * (1.0 - (-t / SPIN_DECAY).exp());
+        let rot = Quat::from_axis_angle(&Vec3::new(pitch, yaw, 0.0), angle).to_matrix();
+
+        self.rotation.mul(&rot).orthonormalize()
+    }
+
+    // model, view and projection of the cloud for a square viewport, depth in [0, 1]
+    pub fn view_projection(&self, now: Instant) -> Matrix {
+        let rotation = self.rotation_at(now);
+
+        if !self.perspective {
+            return rotation
+                .mul(&Matrix::scale(CAMERA_SCALE, CAMERA_SCALE, 0.25))
+                .mul(&Matrix::translate(0.0, 0.0, 0.5));
+        }
+
+        let distance = CAMERA_DISTANCE;
+        let fov_y = 2.0 * (1.0 / (CAMERA_SCALE * distance)).atan();
+        let m = rotation
+            .mul(&Matrix::translate(0.0, 0.0, distance))
+            .mul(&Matrix::perspective(
+                fov_y,
+                1.0,
+                distance - 2.0,
+                distance + 2.0,
+            ));
+
+        // w is 1 at the origin, so offsets in clip space keep their ortho size there
+        Matrix(m.0.map(|row| Vec4(row.0.map(|v| v / distance))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPS: f32 = 1e-4;
+
+    #[test]
+    fn decodes_half_floats() {
+        assert_eq!(f16_to_f32(0x0000), 0.0);
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0x3800), 0.5);
+        assert_eq!(f16_to_f32(0xc000), -2.0);
+        assert_eq!(f16_to_f32(0x7bff), 65504.0);
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+    }
+
+    #[test]
+    fn srgb_decoding_matches_the_transfer_function() {
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < EPS);
+        assert!((srgb_to_linear(0.5) - 0.214041).abs() < EPS);
+        assert!((srgb_to_linear(0.02) - 0.02 / 12.92).abs() < EPS);
+    }
+
+    #[test]
+    fn encodes_srgb_and_clips_hdr() {
+        assert_eq!(linear_to_srgb8(0.0), 0);
+        assert_eq!(linear_to_srgb8(1.0), 255);
+        assert_eq!(linear_to_srgb8(0.5), 188);
+        assert_eq!(linear_to_srgb8(12.5), 255);
+        assert_eq!(linear_to_srgb8(-1.0), 0);
+        assert_eq!(linear_to_srgb8(f32::NAN), 0);
+    }
+
+    fn assert_matrix_eq(a: &Matrix, b: &Matrix) {
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!(
+                    (a[(i, j)] - b[(i, j)]).abs() < EPS,
+                    "mismatch at ({i}, {j})\n{a}\n{b}"
+                );
+            }
+        }
+    }
+
+    fn assert_vec_eq(a: &Vec4, b: &Vec4) {
+        for i in 0..4 {
+            assert!((a[i] - b[i]).abs() < EPS, "{a} != {b}");
+        }
+    }
+
+    fn sample() -> Matrix {
+        Matrix::rot_x(0.3)
+            .mul(&Matrix::rot_y(-1.2))
+            .mul(&Matrix::scale(2.0, 0.5, 3.0))
+            .mul(&Matrix::translate(1.0, -2.0, 0.5))
+    }
+
+    #[test]
+    fn transpose_swaps_rows_and_columns() {
+        let m = sample();
+        let t = m.transpose();
+
+        for i in 0..4 {
+            for j in 0..4 {
+                assert_eq!(m[(i, j)], t[(j, i)]);
+            }
+        }
+        assert_matrix_eq(&t.transpose(), &m);
+    }
+
+    #[test]
+    fn inverse_of_product_is_identity() {
+        let m = sample();
+        let inv = m.inverse().unwrap();
+
+        assert_matrix_eq(&m.mul(&inv), &Matrix::identity());
+        assert_matrix_eq(&inv.mul(&m), &Matrix::identity());
+    }
+
+    #[test]
+    fn inverse_of_rotation_is_transpose() {
+        let m = Matrix::rot_z(0.7).mul(&Matrix::rot_x(-0.4));
+
+        assert_matrix_eq(&m.inverse().unwrap(), &m.transpose());
+    }
+
+    #[test]
+    fn singular_matrix_has_no_inverse() {
+        assert!(Matrix::scale(1.0, 0.0, 1.0).inverse().is_none());
+        assert!(Matrix::zero().inverse().is_none());
+    }
+
+    #[test]
+    fn determinant_of_scale() {
+        let det = Matrix::scale(2.0, 3.0, 4.0).determinant();
+        assert!((det - 24.0).abs() < EPS);
+    }
+
+    #[test]
+    fn quaternion_matches_axis_rotations() {
+        for angle in [0.0, 0.25, -1.0, 3.0] {
+            assert_matrix_eq(&Quat::rot_x(angle).to_matrix(), &Matrix::rot_x(angle));
+            assert_matrix_eq(&Quat::rot_y(angle).to_matrix(), &Matrix::rot_y(angle));
+            assert_matrix_eq(&Quat::rot_z(angle).to_matrix(), &Matrix::rot_z(angle));
+        }
+    }
+
+    #[test]
+    fn quaternion_composition_matches_matrix_order() {
+        let a = Quat::rot_y(0.4);
+        let b = Quat::rot_x(-0.9);
+
+        assert_matrix_eq(&a.mul(&b).to_matrix(), &b.to_matrix().mul(&a.to_matrix()));
+    }
+
+    #[test]
+    fn quaternion_accumulation_stays_orthonormal() {
+        let step = Quat::rot_y(0.001).mul(&Quat::rot_x(0.002));
+        let mut q = Quat::identity();
+        for _ in 0..100_000 {
+            q = step.mul(&q).normalize();
+        }
+
+        let m = q.to_matrix();
+        assert_matrix_eq(&m.mul(&m.transpose()), &Matrix::identity());
+    }
+
+    #[test]
+    fn orthonormalize_removes_drift() {
+        let step = Matrix::rot_y(0.001).mul(&Matrix::rot_x(0.002));
+        let mut m = Matrix::identity();
+        for _ in 0..100_000 {
+            m = m.mul(&step).orthonormalize();
+        }
+
+        assert_matrix_eq(&m.mul(&m.transpose()), &Matrix::identity());
+        assert!((m.determinant() - 1.0).abs() < EPS);
+    }
+
+    #[test]
+    fn orthonormalize_keeps_rotation() {
+        let m = Matrix::rot_z(0.5).mul(&Matrix::rot_x(1.1));
+        assert_matrix_eq(&m.orthonormalize(), &m);
+
+        let skewed = m.mul(&Matrix::scale(1.01, 0.98, 1.0));
+        let fixed = skewed.orthonormalize();
+        assert_matrix_eq(&fixed.mul(&fixed.transpose()), &Matrix::identity());
+    }
+
+    #[test]
+    fn perspective_maps_near_and_far_planes() {
+        let m = Matrix::perspective(std::f32::consts::FRAC_PI_2, 2.0, 0.5, 10.0);
+
+        let near = m.transform(&Vec4::new(0.0, 0.0, 0.5, 1.0));
+        assert!((near.z() / near.w()).abs() < EPS);
+
+        let far = m.transform(&Vec4::new(0.0, 0.0, 10.0, 1.0));
+        assert!((far.z() / far.w() - 1.0).abs() < EPS);
+
+        // 90 degree fov puts the edge at x = z / aspect
+        let edge = m.transform(&Vec4::new(2.0, 1.0, 1.0, 1.0));
+        assert!((edge.x() / edge.w() - 1.0).abs() < EPS);
+        assert!((edge.y() / edge.w() - 1.0).abs() < EPS);
+    }
+
+    #[test]
+    fn perspective_camera_fits_like_ortho_at_the_origin() {
+        let now = Instant::now();
+        let mut camera = Camera::default();
+        let ortho = camera.view_projection(now);
+        camera.perspective = true;
+        let perspective = camera.view_projection(now);
+
+        let center = perspective.transform(&Vec4::new(0.0, 0.0, 0.0, 1.0));
+        assert!((center.w() - 1.0).abs() < EPS);
+
+        let edge = Vec4::new(1.0, 0.0, 0.0, 1.0);
+        let (a, b) = (ortho.transform(&edge), perspective.transform(&edge));
+        assert!((a.x() / a.w() - b.x() / b.w()).abs() < EPS);
+
+        // nearer is larger and in front
+        let near = perspective.transform(&Vec4::new(1.0, 0.0, -1.0, 1.0));
+        let far = perspective.transform(&Vec4::new(1.0, 0.0, 1.0, 1.0));
+        assert!(near.x() / near.w() > far.x() / far.w());
+        assert!(near.z() / near.w() < far.z() / far.w());
+        for p in [near, far] {
+            assert!((0.0..=1.0).contains(&(p.z() / p.w())));
+        }
+    }
+
+    #[test]
+    fn released_spin_slows_down_and_stops() {
+        let mut camera = Camera::default();
+        camera.fling(2.0, 0.0);
+        let start = camera.spin.unwrap().1;
+        let at = |s: f32| start + std::time::Duration::from_secs_f32(s);
+
+        assert!(camera.spinning(at(0.5)));
+        assert!(!camera.spinning(at(5.0)));
+
+        // turns about y toward its limit, velocity times decay
+        let angle = |s| {
+            let m = camera.rotation_at(at(s));
+            m[(0, 2)].atan2(m[(0, 0)]).abs()
+        };
+        assert!(angle(0.1) < angle(1.0));
+        assert!((angle(10.0) - 2.0 * SPIN_DECAY).abs() < 1e-3);
+
+        camera.orbit(0.0, 0.0);
+        assert!(!camera.spinning(at(0.5)));
+
+        camera.fling(0.01, 0.0);
+        assert_eq!(camera.spin, None);
+
+        camera.perspective = true;
+        camera.fling(2.0, 0.0);
+        camera.reset();
+        assert!(!camera.spinning(at(0.5)));
+        assert_eq!(camera.rotation, Matrix::identity());
+        assert!(camera.perspective);
+    }
+
+    #[test]
+    fn look_at_moves_eye_to_origin() {
+        let eye = Vec3::new(1.0, 2.0, -3.0);
+        let target = Vec3::new(1.0, 2.0, 5.0);
+        let m = Matrix::look_at(&eye, &target, &Vec3::new(0.0, 1.0, 0.0));
+
+        assert_vec_eq(
+            &m.transform(&Vec4::new(1.0, 2.0, -3.0, 1.0)),
+            &Vec4::new(0.0, 0.0, 0.0, 1.0),
+        );
+        assert_vec_eq(
+            &m.transform(&Vec4::new(1.0, 2.0, 5.0, 1.0)),
+            &Vec4::new(0.0, 0.0, 8.0, 1.0),
+        );
+        assert_vec_eq(
+            &m.transform(&Vec4::new(2.0, 3.0, -3.0, 1.0)),
+            &Vec4::new(1.0, 1.0, 0.0, 1.0),
+        );
+    }
+}
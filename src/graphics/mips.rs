@@ -0,0 +1,100 @@
+use anyhow::Result;
+use windows::Win32::Graphics::Direct3D12::{
+    D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE, D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+    D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+};
+
+use super::{
+    core::{
+        pso::PipelineState,
+        wrap::{Format, SrvDesc, UavDesc},
+    },
+    initializer::Initializer,
+    math,
+    renderer::Renderer,
+    resource::Texture2D,
+};
+
+// fills the mips below the top one of a Texture2D::with_mips, one 2x2 box filter dispatch per level
+pub struct MipGenerator {
+    pso: PipelineState,
+}
+
+impl MipGenerator {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let pso =
+            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/GenerateMipsCs.bin"), None)?;
+
+        Ok(Self { pso })
+    }
+
+    // after the top mip was uploaded. linear averages in linear light, srgb texels are decoded by
+    // their view and encoded again. without it the encoded values are averaged like a naive resize,
+    // which darkens fine detail: srgb texels as stored, linear ones encoded first and decoded after
+    pub fn generate(&self, ctx: &mut Renderer, texture: &Texture2D, linear: bool) {
+        let desc = texture.desc();
+        let mips = desc.MipLevels as u32;
+        if mips < 2 {
+            return;
+        }
+
+        ctx.set_pipeline_state(&self.pso);
+        ctx.resource_barrier(&[texture.transition_barrier(
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        )]);
+
+        for mip in 1..mips {
+            // each level is read once the one above it has been written
+            ctx.resource_barrier(&[texture.subresource_barrier(
+                mip - 1,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            )]);
+
+            let width = (desc.Width as u32 >> mip).max(1);
+            let height = (desc.Height >> mip).max(1);
+
+            #[repr(C)]
+            struct Params {
+                size: [u32; 2],
+                srgb: u32,
+                naive: u32,
+            }
+
+            // the unorm view reads srgb texels as stored
+            let srgb = Format::is_srgb(texture.format());
+            let format = if linear {
+                texture.format()
+            } else {
+                Format::unorm(texture.format())
+            };
+            ctx.set_compute_constants(&Params {
+                size: [width, height],
+                srgb: (srgb && linear) as u32,
+                naive: (!srgb && !linear) as u32,
+            });
+            ctx.set_compute_srv_view(texture, &SrvDesc::texture2d_mips(format, mip - 1, 1));
+            ctx.set_uav_view(texture, &UavDesc::texture2d(Format::unorm(format), mip));
+
+            const THREAD: u32 = 8;
+            ctx.dispatch(
+                math::div_round_up(width, THREAD),
+                math::div_round_up(height, THREAD),
+                1,
+            );
+        }
+
+        let barriers: Vec<_> = (0..mips)
+            .map(|mip| {
+                let before = if mip == mips - 1 {
+                    D3D12_RESOURCE_STATE_UNORDERED_ACCESS
+                } else {
+                    D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE
+                };
+                texture.subresource_barrier(mip, before, D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE)
+            })
+            .collect();
+        ctx.resource_barrier(&barriers);
+    }
+}
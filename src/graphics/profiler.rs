@@ -0,0 +1,135 @@
+use windows::Win32::System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency};
+
+// a labelled interval in QueryPerformanceCounter ticks
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    pub label: String,
+    pub start: u64,
+    pub end: u64,
+}
+
+pub fn now() -> u64 {
+    let mut ticks = 0;
+    _ = unsafe { QueryPerformanceCounter(&mut ticks) };
+    ticks as u64
+}
+
+pub fn frequency() -> u64 {
+    let mut freq = 0;
+    _ = unsafe { QueryPerformanceFrequency(&mut freq) };
+    freq.max(1) as u64
+}
+
+// cpu scopes of the frame being recorded
+#[derive(Default)]
+pub struct CpuProfiler {
+    open: Vec<(String, u64)>,
+    spans: Vec<Span>,
+}
+
+impl CpuProfiler {
+    pub fn begin(&mut self, label: &str) {
+        self.open.push((label.to_string(), now()));
+    }
+
+    // closes the scope begun last
+    pub fn end(&mut self) {
+        if let Some((label, start)) = self.open.pop() {
+            self.record(label, start, now());
+        }
+    }
+
+    pub fn record(&mut self, label: impl Into<String>, start: u64, end: u64) {
+        self.spans.push(Span {
+            label: label.into(),
+            start,
+            end,
+        });
+    }
+
+    // a frame that was skipped leaves nothing behind
+    pub fn reset(&mut self) {
+        self.open.clear();
+        self.spans.clear();
+    }
+
+    pub fn take(&mut self) -> Vec<Span> {
+        self.open.clear();
+        std::mem::take(&mut self.spans)
+    }
+}
+
+// a gpu and a cpu timestamp taken at the same moment, see ID3D12CommandQueue::GetClockCalibration
+#[derive(Clone, Copy, Debug)]
+pub struct Calibration {
+    pub gpu: u64,
+    pub cpu: u64,
+    pub gpu_frequency: u64,
+    pub cpu_frequency: u64,
+}
+
+impl Calibration {
+    pub fn to_cpu(&self, gpu: u64) -> u64 {
+        let dt = (gpu as i128 - self.gpu as i128) * self.cpu_frequency as i128
+            / self.gpu_frequency.max(1) as i128;
+        (self.cpu as i128 + dt).max(0) as u64
+    }
+}
+
+// one finished frame with cpu and gpu work on the same timeline
+#[derive(Clone, Debug, Default)]
+pub struct FrameProfile {
+    pub cpu: Vec<Span>,
+    pub gpu: Vec<Span>,
+    pub frequency: u64,
+}
+
+impl FrameProfile {
+    // where the frame's first scope started
+    pub fn origin(&self) -> Option<u64> {
+        self.cpu
+            .iter()
+            .chain(&self.gpu)
+            .map(|span| span.start)
+            .min()
+    }
+
+    pub fn ms(&self, ticks: i64) -> f64 {
+        1000.0 * ticks as f64 / self.frequency.max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calibration_maps_gpu_ticks_onto_qpc() {
+        let calibration = Calibration {
+            gpu: 1_000_000,
+            cpu: 50_000,
+            gpu_frequency: 100_000_000,
+            cpu_frequency: 10_000_000,
+        };
+
+        assert_eq!(calibration.to_cpu(1_000_000), 50_000);
+        // 1ms later on both clocks
+        assert_eq!(calibration.to_cpu(1_100_000), 60_000);
+        // timestamps taken before the calibration
+        assert_eq!(calibration.to_cpu(900_000), 40_000);
+    }
+
+    #[test]
+    fn profiler_closes_innermost_scope_first() {
+        let mut profiler = CpuProfiler::default();
+        profiler.begin("record");
+        profiler.begin("present");
+        profiler.end();
+        profiler.end();
+
+        let spans = profiler.take();
+        let labels: Vec<_> = spans.iter().map(|span| span.label.as_str()).collect();
+        assert_eq!(labels, ["present", "record"]);
+        assert!(spans[1].start <= spans[0].start && spans[0].end <= spans[1].end);
+    }
+}
@@ -1,249 +1,1057 @@
-use anyhow::Result;
-use windows::Win32::{Foundation::RECT, Graphics::Direct3D12::*};
-
-use super::core::{
-    command_queue::{ClosedCommandList, CommandList},
-    descriptor::{Descriptor, DescriptorIter, ShaderVisibleSrvHeap},
-    device::Device,
-    query::{TimestampQueryIter, TimestampQueryPool},
-    resource::Resource,
-    root_signature::{
-        RootSignature, ROOT_PARAM_INDEX_CONSTANTS, ROOT_PARAM_INDEX_DIRECT_SRV,
-        ROOT_PARAM_INDEX_SRV, ROOT_PARAM_INDEX_UAV,
-    },
-    swap_chain::RenderTarget,
-};
-
-pub enum ViewportKind {
-    Full,
-    Adjust,
-}
-
-pub struct Renderer {
-    device: Device,
-    command_list: CommandList,
-    render_target: RenderTarget,
-    shader_visible_descriptors: DescriptorIter,
-    timestamp_querys: TimestampQueryIter,
-    viewports: [D3D12_VIEWPORT; 2],
-}
-
-impl Renderer {
-    pub fn new(
-        device: &Device,
-        root_signature: &RootSignature,
-        command_list: CommandList,
-        render_target: RenderTarget,
-        shader_visible_descriptor_heap: &ShaderVisibleSrvHeap,
-        timestamp_query_pool: &TimestampQueryPool,
-        clear_color: &[f32; 4],
-    ) -> Result<Self> {
-        unsafe {
-            command_list.SetGraphicsRootSignature(root_signature.as_ref());
-            command_list.SetComputeRootSignature(root_signature.as_ref());
-
-            let descriptor_heap = shader_visible_descriptor_heap.as_ref().clone();
-            command_list.SetDescriptorHeaps(&[Some(descriptor_heap)]);
-
-            command_list.ResourceBarrier(&[render_target.buffer.transition_barrier(
-                D3D12_RESOURCE_STATE_PRESENT,
-                D3D12_RESOURCE_STATE_RENDER_TARGET,
-            )]);
-
-            let rtvs = [render_target.rtv.cpu];
-            let dsv = render_target.dsv.cpu;
-
-            command_list.OMSetRenderTargets(1, Some(rtvs.as_ptr()), false, Some(&dsv));
-
-            for rtv in rtvs {
-                command_list.ClearRenderTargetView(rtv, clear_color, None);
-            }
-            command_list.ClearDepthStencilView(dsv, D3D12_CLEAR_FLAG_DEPTH, 1.0, 0, &[]);
-
-            let (width, height) = render_target.buffer.size();
-            let adjusted = width.max(height) as f32;
-
-            let viewports = [
-                D3D12_VIEWPORT {
-                    Width: width as _,
-                    Height: height as _,
-                    MinDepth: 0.0,
-                    MaxDepth: 1.0,
-                    ..Default::default()
-                },
-                D3D12_VIEWPORT {
-                    TopLeftX: (width as f32 - adjusted) / 2.0,
-                    TopLeftY: (height as f32 - adjusted) / 2.0,
-                    Width: adjusted,
-                    Height: adjusted,
-                    MinDepth: 0.0,
-                    MaxDepth: 1.0,
-                },
-            ];
-
-            command_list.RSSetViewports(&[viewports[0]]);
-
-            command_list.RSSetScissorRects(&[RECT {
-                right: width as _,
-                bottom: height as _,
-                ..Default::default()
-            }]);
-
-            Ok(Self {
-                device: device.clone(),
-                command_list,
-                render_target,
-                shader_visible_descriptors: shader_visible_descriptor_heap.iter(),
-                timestamp_querys: timestamp_query_pool.iter(),
-                viewports,
-            })
-        }
-    }
-
-    pub fn set_viewport(&mut self, viewport_kind: ViewportKind) {
-        let viewport = match viewport_kind {
-            ViewportKind::Full => self.viewports[0],
-            ViewportKind::Adjust => self.viewports[1],
-        };
-
-        unsafe {
-            self.command_list.RSSetViewports(&[viewport]);
-        }
-    }
-
-    pub fn resolve_query(&mut self, buffer: &Resource) -> Option<Vec<String>> {
-        self.command_list.resolve_query(
-            self.timestamp_querys.heap(),
-            D3D12_QUERY_TYPE_TIMESTAMP,
-            self.timestamp_querys.count(),
-            buffer,
-        );
-
-        self.timestamp_querys.take_labels()
-    }
-
-    pub fn close(self) -> Result<ClosedCommandList> {
-        self.command_list
-            .resource_barrier(&[self.render_target.buffer.transition_barrier(
-                D3D12_RESOURCE_STATE_RENDER_TARGET,
-                D3D12_RESOURCE_STATE_PRESENT,
-            )]);
-
-        self.command_list.close()
-    }
-
-    pub fn set_compute_constants<T>(&mut self, params: &T) {
-        unsafe {
-            self.command_list.SetComputeRoot32BitConstants(
-                ROOT_PARAM_INDEX_CONSTANTS,
-                std::mem::size_of_val(params) as u32 / 4,
-                params as *const _ as _,
-                0,
-            );
-        }
-    }
-
-    pub fn set_graphics_constants<T>(&mut self, params: &T) {
-        unsafe {
-            self.command_list.SetGraphicsRoot32BitConstants(
-                ROOT_PARAM_INDEX_CONSTANTS,
-                std::mem::size_of_val(params) as u32 / 4,
-                params as *const _ as _,
-                0,
-            );
-        }
-    }
-
-    pub fn set_compute_srvs(&mut self, descriptors: &[Descriptor]) {
-        unsafe {
-            let descriptor = self.copy_descriptors(descriptors)[0];
-            self.command_list
-                .SetComputeRootDescriptorTable(ROOT_PARAM_INDEX_SRV, descriptor.gpu);
-        }
-    }
-
-    pub fn set_graphics_srvs(&mut self, descriptors: &[Descriptor]) {
-        unsafe {
-            let descriptor = self.copy_descriptors(descriptors)[0];
-            self.SetGraphicsRootDescriptorTable(ROOT_PARAM_INDEX_SRV, descriptor.gpu);
-        }
-    }
-
-    pub fn set_shared_srv(&mut self, srv: Descriptor) {
-        unsafe {
-            let descriptor = self.copy_descriptors(&[srv])[0];
-            self.SetComputeRootDescriptorTable(ROOT_PARAM_INDEX_DIRECT_SRV, descriptor.gpu);
-            self.SetGraphicsRootDescriptorTable(ROOT_PARAM_INDEX_DIRECT_SRV, descriptor.gpu);
-        }
-    }
-
-    pub fn set_uavs(&mut self, descriptors: &[Descriptor]) {
-        unsafe {
-            let descriptor = self.copy_descriptors(descriptors)[0];
-            self.SetComputeRootDescriptorTable(ROOT_PARAM_INDEX_UAV, descriptor.gpu);
-        }
-    }
-
-    pub fn clear_uav(&mut self, uav: Descriptor, resource: &Resource) {
-        unsafe {
-            let uav_shader_visible = self.copy_descriptors(&[uav])[0];
-            self.ClearUnorderedAccessViewUint(
-                uav_shader_visible.gpu,
-                uav.cpu,
-                resource.as_ref(),
-                &[0; 4],
-                &[],
-            );
-        }
-    }
-
-    fn copy_descriptors(&mut self, descriptors: &[Descriptor]) -> Vec<Descriptor> {
-        let mut copied_descriptors = vec![];
-
-        for &src in descriptors {
-            let dst = self
-                .shader_visible_descriptors
-                .next()
-                .expect("too many descriptors");
-
-            unsafe {
-                self.device.CopyDescriptorsSimple(
-                    1,
-                    dst.cpu,
-                    src.cpu,
-                    D3D12_DESCRIPTOR_HEAP_TYPE_CBV_SRV_UAV,
-                );
-            }
-
-            copied_descriptors.push(dst);
-        }
-
-        copied_descriptors
-    }
-
-    pub fn timestamp(&mut self, label: &str) {
-        if let Some(index) = self.timestamp_querys.next(label) {
-            unsafe {
-                self.command_list.EndQuery(
-                    self.timestamp_querys.heap().as_ref(),
-                    D3D12_QUERY_TYPE_TIMESTAMP,
-                    index,
-                )
-            };
-        }
-    }
-}
-
-impl AsRef<CommandList> for Renderer {
-    fn as_ref(&self) -> &CommandList {
-        &self.command_list
-    }
-}
-
-impl std::ops::Deref for Renderer {
-    type Target = CommandList;
-
-    fn deref(&self) -> &Self::Target {
-        self.as_ref()
-    }
-}
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{Direct3D12::*, Dxgi::Common::DXGI_FORMAT_R16G16B16A16_FLOAT},
+};
+
+use super::core::{
+    command_queue::{CommandList, CommandLists, QueueKind, Submission},
+    constant_buffer::ConstantAllocator,
+    descriptor::{Descriptor, FrameDescriptors},
+    device::Device,
+    query::{PipelineStatsQueryIter, TimestampQueryIter},
+    resource::Resource,
+    root_signature::{
+        RootSignature, ROOT_PARAM_INDEX_CBV, ROOT_PARAM_INDEX_CONSTANTS,
+        ROOT_PARAM_INDEX_DIRECT_SRV, ROOT_PARAM_INDEX_SHARED_CONSTANTS, ROOT_PARAM_INDEX_SRV,
+        ROOT_PARAM_INDEX_UAV,
+    },
+    swap_chain::RenderTarget,
+};
+
+// sample counts line passes have pipelines for, 1 is msaa off
+pub const MSAA_SAMPLES: [u32; 3] = [1, 2, 4];
+
+pub fn msaa_index(samples: u32) -> usize {
+    MSAA_SAMPLES
+        .iter()
+        .position(|&count| count == samples)
+        .unwrap_or(0)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ViewportKind {
+    Full,
+    // a square inset by margin pixels, either fitting inside the rest of the window or filling it
+    Square { fill: bool, margin: u32 },
+    // a rect in window pixels, drawing is clipped to the window
+    Rect(RECT),
+    // left, top, right and bottom as shares of the window, optionally the largest square inside
+    Region { rect: [f32; 4], square: bool },
+    // all of an offscreen target of another size than the window
+    Target { width: u32, height: u32 },
+}
+
+impl ViewportKind {
+    // window pixels of a point given in the viewport's normalized device coordinates, none when
+    // it falls outside the area drawn
+    pub fn ndc_to_pixel(self, [x, y]: [f32; 2], width: u32, height: u32) -> Option<[f32; 2]> {
+        let (viewport, scissor) = viewport(self, width, height);
+        let pixel = [
+            viewport.TopLeftX + 0.5 * (x + 1.0) * viewport.Width,
+            viewport.TopLeftY + 0.5 * (1.0 - y) * viewport.Height,
+        ];
+
+        let inside = (scissor.left as f32..scissor.right as f32).contains(&pixel[0])
+            && (scissor.top as f32..scissor.bottom as f32).contains(&pixel[1]);
+        inside.then_some(pixel)
+    }
+
+    // size of the area drawn in window pixels
+    pub fn size(self, width: u32, height: u32) -> (f32, f32) {
+        let (viewport, _) = viewport(self, width, height);
+        (viewport.Width, viewport.Height)
+    }
+}
+
+pub struct Renderer {
+    device: Device,
+    root_signature: RootSignature,
+    command_list: CommandList,
+    direct_lists: CommandLists,
+    compute_lists: CommandLists,
+    // the queue the current list goes to and whether it waits for the other one
+    queue: QueueKind,
+    wait: bool,
+    // compute recorded since graphics last waited for it
+    pending_compute: bool,
+    submissions: Vec<Submission>,
+    // bound again on every new list
+    shared_srvs: Option<D3D12_GPU_DESCRIPTOR_HANDLE>,
+    shared_constants: Vec<u32>,
+    render_target: RenderTarget,
+    shader_visible_descriptors: FrameDescriptors,
+    timestamp_querys: TimestampQueryIter,
+    pipeline_stats: PipelineStatsQueryIter,
+    constants: ConstantAllocator,
+    retired: Vec<Resource>,
+    size: (u32, u32),
+    msaa_active: bool,
+}
+
+impl Renderer {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device: &Device,
+        root_signature: &RootSignature,
+        mut direct_lists: CommandLists,
+        compute_lists: CommandLists,
+        render_target: RenderTarget,
+        shader_visible_descriptors: FrameDescriptors,
+        timestamp_querys: TimestampQueryIter,
+        pipeline_stats: PipelineStatsQueryIter,
+        constants: ConstantAllocator,
+        clear_color: &[f32; 4],
+    ) -> Result<Self> {
+        let command_list = direct_lists.next()?;
+        let size = render_target.buffer.size();
+
+        let mut renderer = Self {
+            device: device.clone(),
+            root_signature: root_signature.clone(),
+            command_list,
+            direct_lists,
+            compute_lists,
+            queue: QueueKind::Direct,
+            wait: false,
+            pending_compute: false,
+            submissions: vec![],
+            shared_srvs: None,
+            shared_constants: vec![],
+            render_target,
+            shader_visible_descriptors,
+            timestamp_querys,
+            pipeline_stats,
+            constants,
+            retired: vec![],
+            size,
+            msaa_active: false,
+        };
+        renderer.bind();
+        renderer.timestamp("frame");
+
+        unsafe {
+            let command_list = &renderer.command_list;
+            let render_target = &renderer.render_target;
+
+            command_list.ResourceBarrier(&[render_target.buffer.transition_barrier(
+                D3D12_RESOURCE_STATE_PRESENT,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+            )]);
+
+            command_list.ClearRenderTargetView(render_target.rtv.cpu, clear_color, None);
+            command_list.ClearDepthStencilView(
+                render_target.dsv.cpu,
+                D3D12_CLEAR_FLAG_DEPTH,
+                1.0,
+                0,
+                &[],
+            );
+        }
+
+        Ok(renderer)
+    }
+
+    // the following passes go to the compute queue and start once everything before has finished,
+    // they may only dispatch, copy and use compute resource states
+    pub fn begin_async_compute(&mut self) -> Result<()> {
+        self.switch(QueueKind::Compute, true)
+    }
+
+    // graphics goes on next to the compute passes
+    pub fn end_async_compute(&mut self) -> Result<()> {
+        self.switch(QueueKind::Direct, false)?;
+        self.pending_compute = true;
+
+        Ok(())
+    }
+
+    // the following passes see the results of the compute passes
+    pub fn join_async_compute(&mut self) -> Result<()> {
+        if self.pending_compute {
+            self.switch(QueueKind::Direct, true)?;
+            self.pending_compute = false;
+        }
+
+        Ok(())
+    }
+
+    fn switch(&mut self, queue: QueueKind, wait: bool) -> Result<()> {
+        let command_list = match queue {
+            QueueKind::Direct => self.direct_lists.next()?,
+            QueueKind::Compute => self.compute_lists.next()?,
+        };
+        let previous = std::mem::replace(&mut self.command_list, command_list);
+
+        self.submissions.push(Submission {
+            queue: self.queue,
+            wait: self.wait,
+            command_list: previous.close()?,
+        });
+        self.queue = queue;
+        self.wait = wait;
+        self.bind();
+
+        Ok(())
+    }
+
+    // state a new list starts without
+    fn bind(&mut self) {
+        let command_list = &self.command_list;
+
+        unsafe {
+            command_list.SetComputeRootSignature(self.root_signature.as_ref());
+            let heap = self.shader_visible_descriptors.heap().clone();
+            command_list.SetDescriptorHeaps(&[Some(heap)]);
+
+            if let Some(srvs) = self.shared_srvs {
+                command_list.SetComputeRootDescriptorTable(ROOT_PARAM_INDEX_DIRECT_SRV, srvs);
+            }
+            if !self.shared_constants.is_empty() {
+                command_list.SetComputeRoot32BitConstants(
+                    ROOT_PARAM_INDEX_SHARED_CONSTANTS,
+                    self.shared_constants.len() as _,
+                    self.shared_constants.as_ptr() as _,
+                    0,
+                );
+            }
+
+            if self.queue == QueueKind::Compute {
+                return;
+            }
+
+            command_list.SetGraphicsRootSignature(self.root_signature.as_ref());
+
+            if let Some(srvs) = self.shared_srvs {
+                command_list.SetGraphicsRootDescriptorTable(ROOT_PARAM_INDEX_DIRECT_SRV, srvs);
+            }
+            if !self.shared_constants.is_empty() {
+                command_list.SetGraphicsRoot32BitConstants(
+                    ROOT_PARAM_INDEX_SHARED_CONSTANTS,
+                    self.shared_constants.len() as _,
+                    self.shared_constants.as_ptr() as _,
+                    0,
+                );
+            }
+        }
+
+        self.reset_render_targets();
+        self.set_viewport(ViewportKind::Full);
+    }
+
+    // also resets the scissor to the viewport, squares are cropped to their area
+    pub fn set_viewport(&mut self, viewport_kind: ViewportKind) {
+        let (viewport, scissor) = viewport(viewport_kind, self.size.0, self.size.1);
+
+        unsafe {
+            self.command_list.RSSetViewports(&[viewport]);
+            self.command_list.RSSetScissorRects(&[scissor]);
+        }
+    }
+
+    // narrows drawing of the following passes to a rect in window pixels
+    pub fn set_scissor(&mut self, rect: RECT) {
+        let scissor = clip(rect, self.size.0, self.size.1);
+
+        unsafe {
+            self.command_list.RSSetScissorRects(&[scissor]);
+        }
+    }
+
+    // offscreen targets without depth, until reset_render_targets()
+    pub fn set_render_targets(&mut self, rtvs: &[Descriptor]) {
+        let rtvs: Vec<_> = rtvs.iter().map(|rtv| rtv.cpu).collect();
+
+        unsafe {
+            self.command_list
+                .OMSetRenderTargets(rtvs.len() as _, Some(rtvs.as_ptr()), false, None);
+        }
+    }
+
+    // back to the swap chain buffer and its depth
+    pub fn reset_render_targets(&mut self) {
+        let rtvs = [self.render_target.rtv.cpu];
+        let dsv = self.render_target.dsv.cpu;
+
+        unsafe {
+            self.command_list
+                .OMSetRenderTargets(1, Some(rtvs.as_ptr()), false, Some(&dsv));
+        }
+    }
+
+    // sample count of the bound targets
+    pub fn samples(&self) -> u32 {
+        match &self.render_target.msaa {
+            Some(msaa) if self.msaa_active => msaa.samples,
+            _ => 1,
+        }
+    }
+
+    // redirects drawing into cleared multisampled targets, false when msaa is off
+    pub fn begin_msaa(&mut self) -> bool {
+        let Some(msaa) = &self.render_target.msaa else {
+            return false;
+        };
+
+        let rtvs = [msaa.rtv.cpu];
+        let dsv = msaa.dsv.cpu;
+
+        unsafe {
+            self.command_list
+                .OMSetRenderTargets(1, Some(rtvs.as_ptr()), false, Some(&dsv));
+            self.command_list
+                .ClearRenderTargetView(rtvs[0], &[0.0; 4], None);
+            self.command_list
+                .ClearDepthStencilView(dsv, D3D12_CLEAR_FLAG_DEPTH, 1.0, 0, &[]);
+        }
+
+        self.msaa_active = true;
+        true
+    }
+
+    // resolves into dst, a window sized rgba16f texture, and returns to the swap chain buffer
+    pub fn end_msaa(&mut self, dst: &Resource) {
+        let Some(msaa) = self.render_target.msaa.clone().filter(|_| self.msaa_active) else {
+            return;
+        };
+        self.msaa_active = false;
+
+        self.command_list.resource_barrier(&[
+            msaa.color.transition_barrier(
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+                D3D12_RESOURCE_STATE_RESOLVE_SOURCE,
+            ),
+            dst.transition_barrier(
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_RESOLVE_DEST,
+            ),
+        ]);
+
+        unsafe {
+            self.command_list.ResolveSubresource(
+                dst.as_ref(),
+                0,
+                msaa.color.as_ref(),
+                0,
+                DXGI_FORMAT_R16G16B16A16_FLOAT,
+            );
+        }
+
+        self.command_list.resource_barrier(&[
+            msaa.color.transition_barrier(
+                D3D12_RESOURCE_STATE_RESOLVE_SOURCE,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+            ),
+            dst.transition_barrier(
+                D3D12_RESOURCE_STATE_RESOLVE_DEST,
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            ),
+        ]);
+
+        self.reset_render_targets();
+    }
+
+    pub fn clear_render_target(&mut self, rtv: Descriptor, color: &[f32; 4]) {
+        unsafe {
+            self.command_list
+                .ClearRenderTargetView(rtv.cpu, color, None);
+        }
+    }
+
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    pub fn render_target(&self) -> &Resource {
+        &self.render_target.buffer
+    }
+
+    // the swap chain buffer's depth, single sampled
+    pub fn depth(&self) -> &Resource {
+        &self.render_target.depth
+    }
+
+    pub fn resolve_query(&mut self, buffer: &Resource) -> Option<(Vec<String>, u32)> {
+        self.timestamp("frame");
+        self.command_list.resolve_query(
+            self.timestamp_querys.heap(),
+            D3D12_QUERY_TYPE_TIMESTAMP,
+            self.timestamp_querys.count(),
+            buffer,
+        );
+
+        self.timestamp_querys.take_labels()
+    }
+
+    pub fn resolve_pipeline_stats(&mut self, buffer: &Resource) -> Option<(Vec<String>, u32)> {
+        let stats = &mut self.pipeline_stats;
+        self.command_list.resolve_query(
+            stats.heap(),
+            stats.heap().query_type(),
+            stats.count(),
+            buffer,
+        );
+
+        stats.take_labels()
+    }
+
+    // keeps a replaced resource alive until the gpu has finished this frame
+    pub fn retire(&mut self, resource: Resource) {
+        self.retired.push(resource);
+    }
+
+    pub fn take_retired(&mut self) -> Vec<Resource> {
+        std::mem::take(&mut self.retired)
+    }
+
+    // graphics waits for any compute left so the frame's fence covers both queues
+    pub fn close(mut self) -> Result<Vec<Submission>> {
+        self.join_async_compute()?;
+
+        self.command_list
+            .resource_barrier(&[self.render_target.buffer.transition_barrier(
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+                D3D12_RESOURCE_STATE_PRESENT,
+            )]);
+
+        self.submissions.push(Submission {
+            queue: self.queue,
+            wait: self.wait,
+            command_list: self.command_list.close()?,
+        });
+
+        Ok(self.submissions)
+    }
+
+    pub fn set_compute_constants<T>(&mut self, params: &T) {
+        unsafe {
+            self.command_list.SetComputeRoot32BitConstants(
+                ROOT_PARAM_INDEX_CONSTANTS,
+                std::mem::size_of_val(params) as u32 / 4,
+                params as *const _ as _,
+                0,
+            );
+        }
+    }
+
+    pub fn set_graphics_constants<T>(&mut self, params: &T) {
+        unsafe {
+            self.command_list.SetGraphicsRoot32BitConstants(
+                ROOT_PARAM_INDEX_CONSTANTS,
+                std::mem::size_of_val(params) as u32 / 4,
+                params as *const _ as _,
+                0,
+            );
+        }
+    }
+
+    pub fn set_compute_srvs(&mut self, descriptors: &[Descriptor]) {
+        unsafe {
+            let descriptor = self.copy_descriptors(descriptors)[0];
+            self.command_list
+                .SetComputeRootDescriptorTable(ROOT_PARAM_INDEX_SRV, descriptor.gpu);
+        }
+    }
+
+    pub fn set_graphics_srvs(&mut self, descriptors: &[Descriptor]) {
+        unsafe {
+            let descriptor = self.copy_descriptors(descriptors)[0];
+            self.SetGraphicsRootDescriptorTable(ROOT_PARAM_INDEX_SRV, descriptor.gpu);
+        }
+    }
+
+    pub fn set_shared_srvs(&mut self, srvs: &[Descriptor]) {
+        let descriptor = self.copy_descriptors(srvs)[0];
+        self.shared_srvs = Some(descriptor.gpu);
+
+        unsafe {
+            self.SetComputeRootDescriptorTable(ROOT_PARAM_INDEX_DIRECT_SRV, descriptor.gpu);
+            if self.queue == QueueKind::Direct {
+                self.SetGraphicsRootDescriptorTable(ROOT_PARAM_INDEX_DIRECT_SRV, descriptor.gpu);
+            }
+        }
+    }
+
+    // constants over the 32 dword root limit, read as a cbuffer at b1
+    pub fn set_graphics_cbv<T>(&mut self, params: &T) -> Result<()> {
+        let address = self.constants.push(params)?;
+        unsafe {
+            self.SetGraphicsRootConstantBufferView(ROOT_PARAM_INDEX_CBV, address);
+        }
+
+        Ok(())
+    }
+
+    // up to 4 dwords seen by every pass next to the shared srvs
+    pub fn set_shared_constants<T>(&mut self, params: &T) {
+        let num = std::mem::size_of_val(params) / 4;
+        self.shared_constants =
+            unsafe { std::slice::from_raw_parts(params as *const _ as *const u32, num).to_vec() };
+
+        unsafe {
+            let params = params as *const _ as _;
+            self.SetComputeRoot32BitConstants(
+                ROOT_PARAM_INDEX_SHARED_CONSTANTS,
+                num as _,
+                params,
+                0,
+            );
+            if self.queue == QueueKind::Direct {
+                self.SetGraphicsRoot32BitConstants(
+                    ROOT_PARAM_INDEX_SHARED_CONSTANTS,
+                    num as _,
+                    params,
+                    0,
+                );
+            }
+        }
+    }
+
+    pub fn set_uavs(&mut self, descriptors: &[Descriptor]) {
+        unsafe {
+            let descriptor = self.copy_descriptors(descriptors)[0];
+            self.SetComputeRootDescriptorTable(ROOT_PARAM_INDEX_UAV, descriptor.gpu);
+        }
+    }
+
+    // one-off views such as single mips, created straight in this frame's range
+    pub fn set_compute_srv_view(
+        &mut self,
+        resource: &Resource,
+        desc: &D3D12_SHADER_RESOURCE_VIEW_DESC,
+    ) {
+        let descriptor = self.next_shader_visible();
+        self.device.create_srv(resource, Some(desc), descriptor.cpu);
+
+        unsafe {
+            self.SetComputeRootDescriptorTable(ROOT_PARAM_INDEX_SRV, descriptor.gpu);
+        }
+    }
+
+    pub fn set_uav_view(&mut self, resource: &Resource, desc: &D3D12_UNORDERED_ACCESS_VIEW_DESC) {
+        let descriptor = self.next_shader_visible();
+        self.device.create_uav(resource, Some(desc), descriptor.cpu);
+
+        unsafe {
+            self.SetComputeRootDescriptorTable(ROOT_PARAM_INDEX_UAV, descriptor.gpu);
+        }
+    }
+
+    pub fn clear_uav(&mut self, uav: Descriptor, resource: &Resource) {
+        unsafe {
+            let uav_shader_visible = self.copy_descriptors(&[uav])[0];
+            self.ClearUnorderedAccessViewUint(
+                uav_shader_visible.gpu,
+                uav.cpu,
+                resource.as_ref(),
+                &[0; 4],
+                &[],
+            );
+        }
+    }
+
+    // contiguous so the first one can be bound as a table
+    fn copy_descriptors(&mut self, descriptors: &[Descriptor]) -> Vec<Descriptor> {
+        let increment_size = self.shader_visible_descriptors.increment_size();
+        let mut dst = self.allocate_shader_visible(descriptors.len() as u32);
+
+        let mut copied_descriptors = vec![];
+        for &src in descriptors {
+            unsafe {
+                self.device.CopyDescriptorsSimple(
+                    1,
+                    dst.cpu,
+                    src.cpu,
+                    D3D12_DESCRIPTOR_HEAP_TYPE_CBV_SRV_UAV,
+                );
+            }
+
+            copied_descriptors.push(dst);
+            dst.offset(1, increment_size);
+        }
+
+        copied_descriptors
+    }
+
+    fn next_shader_visible(&mut self) -> Descriptor {
+        self.allocate_shader_visible(1)
+    }
+
+    // an allocation that reaches the frames in flight waits for them and the ring grows after a
+    // frame that used more than a quarter of it, so this only fails when a single frame needs
+    // more than the whole ring
+    fn allocate_shader_visible(&mut self, count: u32) -> Descriptor {
+        self.shader_visible_descriptors
+            .allocate(count)
+            .expect("descriptor ring full")
+    }
+
+    // where this frame's descriptors end in the ring
+    pub fn descriptor_head(&self) -> u64 {
+        self.shader_visible_descriptors.head()
+    }
+
+    // times and counts the work f records under label, f must stay on the current queue
+    pub fn pass<T>(&mut self, label: &str, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let query = self
+            .pipeline_stats
+            .next(label)
+            .map(|index| (self.pipeline_stats.heap().clone(), index));
+
+        if let Some((heap, index)) = &query {
+            unsafe {
+                self.command_list
+                    .BeginQuery(heap.as_ref(), heap.query_type(), *index)
+            };
+        }
+
+        self.timestamp(label);
+        let result = f(self);
+        self.timestamp(label);
+
+        if let Some((heap, index)) = &query {
+            unsafe {
+                self.command_list
+                    .EndQuery(heap.as_ref(), heap.query_type(), *index)
+            };
+        }
+
+        result
+    }
+
+    pub fn timestamp(&mut self, label: &str) {
+        if let Some(index) = self.timestamp_querys.next(label) {
+            unsafe {
+                self.command_list.EndQuery(
+                    self.timestamp_querys.heap().as_ref(),
+                    D3D12_QUERY_TYPE_TIMESTAMP,
+                    index,
+                )
+            };
+        }
+    }
+}
+
+impl AsRef<CommandList> for Renderer {
+    fn as_ref(&self) -> &CommandList {
+        &self.command_list
+    }
+}
+
+impl std::ops::Deref for Renderer {
+    type Target = CommandList;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+fn viewport(kind: ViewportKind, width: u32, height: u32) -> (D3D12_VIEWPORT, RECT) {
+    let (fill, margin) = match kind {
+        ViewportKind::Full => {
+            let viewport = D3D12_VIEWPORT {
+                Width: width as _,
+                Height: height as _,
+                MinDepth: 0.0,
+                MaxDepth: 1.0,
+                ..Default::default()
+            };
+            let scissor = RECT {
+                right: width as _,
+                bottom: height as _,
+                ..Default::default()
+            };
+            return (viewport, scissor);
+        }
+        ViewportKind::Square { fill, margin } => (fill, margin),
+        ViewportKind::Rect(rect) => {
+            let viewport = D3D12_VIEWPORT {
+                TopLeftX: rect.left as _,
+                TopLeftY: rect.top as _,
+                Width: (rect.right - rect.left) as _,
+                Height: (rect.bottom - rect.top) as _,
+                MinDepth: 0.0,
+                MaxDepth: 1.0,
+            };
+            return (viewport, clip(rect, width, height));
+        }
+        ViewportKind::Region { rect, square } => return region(rect, square, width, height),
+        ViewportKind::Target { width, height } => {
+            return viewport(ViewportKind::Full, width, height);
+        }
+    };
+
+    // keep at least one pixel of area
+    let margin = margin.min(width.min(height).saturating_sub(1) / 2);
+    let area_width = width - 2 * margin;
+    let area_height = height - 2 * margin;
+
+    let side = if fill {
+        area_width.max(area_height)
+    } else {
+        area_width.min(area_height)
+    } as f32;
+
+    let viewport = D3D12_VIEWPORT {
+        TopLeftX: margin as f32 + (area_width as f32 - side) / 2.0,
+        TopLeftY: margin as f32 + (area_height as f32 - side) / 2.0,
+        Width: side,
+        Height: side,
+        MinDepth: 0.0,
+        MaxDepth: 1.0,
+    };
+    let square = RECT {
+        left: viewport.TopLeftX.floor() as _,
+        top: viewport.TopLeftY.floor() as _,
+        right: (viewport.TopLeftX + side).ceil() as _,
+        bottom: (viewport.TopLeftY + side).ceil() as _,
+    };
+    let area = RECT {
+        left: margin as _,
+        top: margin as _,
+        right: (width - margin) as _,
+        bottom: (height - margin) as _,
+    };
+    let scissor = RECT {
+        left: square.left.max(area.left),
+        top: square.top.max(area.top),
+        right: square.right.min(area.right),
+        bottom: square.bottom.min(area.bottom),
+    };
+
+    (viewport, scissor)
+}
+
+// count regions for ViewportKind::Region in a grid over the window, row by row. the columns are
+// chosen so the squares that fit the cells are largest
+pub fn split(count: usize, width: u32, height: u32) -> Vec<[f32; 4]> {
+    let count = count.max(1);
+    let side = |columns: usize| {
+        let rows = count.div_ceil(columns);
+        (width as f32 / columns as f32).min(height as f32 / rows as f32)
+    };
+    let columns = (1..=count).fold(1, |best, columns| {
+        if side(columns) > side(best) {
+            columns
+        } else {
+            best
+        }
+    });
+    let rows = count.div_ceil(columns);
+
+    let (w, h) = (1.0 / columns as f32, 1.0 / rows as f32);
+    (0..count)
+        .map(|i| {
+            let (x, y) = ((i % columns) as f32 * w, (i / columns) as f32 * h);
+            [x, y, x + w, y + h]
+        })
+        .collect()
+}
+
+// squares keep to the side of the window the region is nearer to, so an anchored corner stays put
+fn region(rect: [f32; 4], square: bool, width: u32, height: u32) -> (D3D12_VIEWPORT, RECT) {
+    let (width, height) = (width as f32, height as f32);
+    let [left, top, right, bottom] = [
+        rect[0] * width,
+        rect[1] * height,
+        rect[2] * width,
+        rect[3] * height,
+    ];
+    let (mut x, mut y, mut w, mut h) = (left, top, right - left, bottom - top);
+
+    if square {
+        let side = w.min(h);
+        if left + right > width {
+            x += w - side;
+        }
+        if top + bottom > height {
+            y += h - side;
+        }
+        (w, h) = (side, side);
+    }
+
+    let viewport = D3D12_VIEWPORT {
+        TopLeftX: x,
+        TopLeftY: y,
+        Width: w,
+        Height: h,
+        MinDepth: 0.0,
+        MaxDepth: 1.0,
+    };
+    let scissor = RECT {
+        left: x.floor() as _,
+        top: y.floor() as _,
+        right: (x + w).ceil() as _,
+        bottom: (y + h).ceil() as _,
+    };
+
+    (viewport, clip(scissor, width as _, height as _))
+}
+
+// empty rects stay empty instead of turning inside out
+fn clip(rect: RECT, width: u32, height: u32) -> RECT {
+    let left = rect.left.clamp(0, width as _);
+    let top = rect.top.clamp(0, height as _);
+
+    RECT {
+        left,
+        top,
+        right: rect.right.clamp(left, width as _),
+        bottom: rect.bottom.clamp(top, height as _),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZES: [(u32, u32); 5] = [
+        (1280, 720),
+        (720, 1280),
+        (3440, 1440),
+        (5120, 1440),
+        (512, 512),
+    ];
+
+    #[test]
+    fn full_viewport_covers_window() {
+        for (width, height) in SIZES {
+            let (full, scissor) = viewport(ViewportKind::Full, width, height);
+
+            assert_eq!((full.TopLeftX, full.TopLeftY), (0.0, 0.0));
+            assert_eq!((full.Width, full.Height), (width as f32, height as f32));
+            assert_eq!((full.MinDepth, full.MaxDepth), (0.0, 1.0));
+            assert_eq!((scissor.right, scissor.bottom), (width as _, height as _));
+        }
+    }
+
+    #[test]
+    fn filled_square_is_centered_on_longer_side() {
+        for (width, height) in SIZES {
+            let kind = ViewportKind::Square {
+                fill: true,
+                margin: 0,
+            };
+            let (square, _) = viewport(kind, width, height);
+
+            assert_eq!(square.Width, square.Height);
+            assert_eq!(square.Width, width.max(height) as f32);
+
+            // centered on the window
+            assert_eq!(2.0 * square.TopLeftX + square.Width, width as f32);
+            assert_eq!(2.0 * square.TopLeftY + square.Height, height as f32);
+
+            // the shorter axis overflows symmetrically, the longer one fits exactly
+            assert!(square.TopLeftX <= 0.0 && square.TopLeftY <= 0.0);
+            assert!(square.TopLeftX == 0.0 || square.TopLeftY == 0.0);
+        }
+    }
+
+    #[test]
+    fn fitted_square_stays_inside_margin() {
+        for (width, height) in SIZES {
+            let margin = 16;
+            let kind = ViewportKind::Square {
+                fill: false,
+                margin,
+            };
+            let (square, scissor) = viewport(kind, width, height);
+
+            assert_eq!(square.Width, square.Height);
+            assert_eq!(square.Width, (width.min(height) - 2 * margin) as f32);
+
+            assert!(square.TopLeftX >= margin as f32 && square.TopLeftY >= margin as f32);
+            assert!(square.TopLeftX + square.Width <= (width - margin) as f32);
+            assert!(square.TopLeftY + square.Height <= (height - margin) as f32);
+
+            // nothing is drawn outside the square
+            assert_eq!(scissor.left as f32, square.TopLeftX);
+            assert_eq!(scissor.top as f32, square.TopLeftY);
+            assert_eq!(scissor.right as f32, square.TopLeftX + square.Width);
+            assert_eq!(scissor.bottom as f32, square.TopLeftY + square.Height);
+        }
+    }
+
+    #[test]
+    fn filled_square_is_cropped_to_margin() {
+        for (width, height) in SIZES {
+            let margin = 16;
+            let kind = ViewportKind::Square { fill: true, margin };
+            let (_, scissor) = viewport(kind, width, height);
+
+            assert_eq!((scissor.left, scissor.top), (margin as _, margin as _));
+            assert_eq!(
+                (scissor.right, scissor.bottom),
+                ((width - margin) as _, (height - margin) as _)
+            );
+        }
+    }
+
+    #[test]
+    fn fitted_square_letterboxes_the_longer_side() {
+        let kind = ViewportKind::Square {
+            fill: false,
+            margin: 20,
+        };
+
+        // equal bars left and right on a wide window, above and below on a tall one
+        let (landscape, _) = viewport(kind, 1280, 720);
+        assert_eq!((landscape.Width, landscape.Height), (680.0, 680.0));
+        assert_eq!((landscape.TopLeftX, landscape.TopLeftY), (300.0, 20.0));
+
+        let (portrait, _) = viewport(kind, 720, 1280);
+        assert_eq!((portrait.Width, portrait.Height), (680.0, 680.0));
+        assert_eq!((portrait.TopLeftX, portrait.TopLeftY), (20.0, 300.0));
+    }
+
+    #[test]
+    fn oversized_margin_keeps_some_area() {
+        for fill in [false, true] {
+            let kind = ViewportKind::Square { fill, margin: 1000 };
+            let (square, scissor) = viewport(kind, 640, 480);
+
+            assert!(square.Width >= 1.0);
+            assert!(scissor.right > scissor.left && scissor.bottom > scissor.top);
+        }
+    }
+
+    #[test]
+    fn rect_viewport_is_clipped_to_window() {
+        let rect = RECT {
+            left: 600,
+            top: 400,
+            right: 728,
+            bottom: 528,
+        };
+        let (viewport, scissor) = viewport(ViewportKind::Rect(rect), 640, 480);
+
+        assert_eq!((viewport.TopLeftX, viewport.TopLeftY), (600.0, 400.0));
+        assert_eq!((viewport.Width, viewport.Height), (128.0, 128.0));
+        assert_eq!(
+            (scissor.left, scissor.top, scissor.right, scissor.bottom),
+            (600, 400, 640, 480)
+        );
+    }
+
+    #[test]
+    fn scissor_is_clipped_to_target() {
+        let rect = clip(
+            RECT {
+                left: -10,
+                top: 20,
+                right: 700,
+                bottom: 30,
+            },
+            640,
+            480,
+        );
+        assert_eq!(
+            (rect.left, rect.top, rect.right, rect.bottom),
+            (0, 20, 640, 30)
+        );
+
+        let rect = clip(
+            RECT {
+                left: 700,
+                top: 500,
+                right: 650,
+                bottom: 600,
+            },
+            640,
+            480,
+        );
+        assert_eq!(rect.left, rect.right);
+        assert_eq!(rect.top, rect.bottom);
+    }
+
+    #[test]
+    fn ultrawide_keeps_pixels_square() {
+        for fill in [false, true] {
+            let kind = ViewportKind::Square { fill, margin: 0 };
+            let (square, _) = viewport(kind, 5120, 1440);
+
+            // one ndc unit covers the same number of pixels on both axes
+            assert_eq!(square.Width / 2.0, square.Height / 2.0);
+        }
+
+        let kind = ViewportKind::Square {
+            fill: false,
+            margin: 0,
+        };
+        let (square, _) = viewport(kind, 5120, 1440);
+        assert_eq!(square.TopLeftX, (5120.0 - 1440.0) / 2.0);
+        assert_eq!(square.TopLeftY, 0.0);
+    }
+
+    #[test]
+    fn split_fits_the_largest_squares() {
+        assert_eq!(split(1, 1280, 720), vec![[0.0, 0.0, 1.0, 1.0]]);
+
+        // side by side on a wide window, stacked on a tall one
+        assert_eq!(split(2, 1280, 720)[1], [0.5, 0.0, 1.0, 1.0]);
+        assert_eq!(split(2, 720, 1280)[1], [0.0, 0.5, 1.0, 1.0]);
+
+        // 3 by 2 cells fit squares of 360 pixels, more than 2 or 4 columns
+        let cells = split(5, 1280, 720);
+        assert_eq!(cells.len(), 5);
+        assert_eq!(cells[3], [0.0, 0.5, 1.0 / 3.0, 1.0]);
+    }
+
+    #[test]
+    fn square_region_keeps_to_its_corner() {
+        let kind = ViewportKind::Region {
+            rect: [0.5, 0.5, 1.0, 1.0],
+            square: true,
+        };
+        let (square, scissor) = viewport(kind, 1280, 720);
+
+        assert_eq!((square.Width, square.Height), (360.0, 360.0));
+        assert_eq!((square.TopLeftX, square.TopLeftY), (920.0, 360.0));
+        assert_eq!((scissor.right, scissor.bottom), (1280, 720));
+
+        let kind = ViewportKind::Region {
+            rect: [0.0, 0.0, 0.5, 0.5],
+            square: false,
+        };
+        let (region, _) = viewport(kind, 1280, 720);
+        assert_eq!((region.TopLeftX, region.TopLeftY), (0.0, 0.0));
+        assert_eq!((region.Width, region.Height), (640.0, 360.0));
+    }
+
+    #[test]
+    fn ndc_maps_onto_the_square() {
+        let kind = ViewportKind::Square {
+            fill: false,
+            margin: 0,
+        };
+        assert_eq!(
+            kind.ndc_to_pixel([0.0, 0.0], 1280, 720),
+            Some([640.0, 360.0])
+        );
+        assert_eq!(
+            kind.ndc_to_pixel([-1.0, 1.0], 1280, 720),
+            Some([280.0, 0.0])
+        );
+        assert_eq!(kind.ndc_to_pixel([1.0, -1.0], 1280, 720), None);
+
+        // filled squares are cropped to the window
+        let kind = ViewportKind::Square {
+            fill: true,
+            margin: 0,
+        };
+        assert_eq!(kind.ndc_to_pixel([0.0, 0.9], 1280, 720), None);
+        assert!(kind.ndc_to_pixel([0.9, 0.0], 1280, 720).is_some());
+    }
+
+    #[test]
+    fn oversized_margin_keeps_a_pixel() {
+        let kind = ViewportKind::Square {
+            fill: false,
+            margin: 1000,
+        };
+        let (square, scissor) = viewport(kind, 640, 480);
+
+        assert!(square.Width >= 1.0);
+        assert!(scissor.right > scissor.left && scissor.bottom > scissor.top);
+    }
+}
@@ -1,135 +1,967 @@
-use anyhow::Result;
-use windows::Win32::Graphics::{
-    Direct3D12::{
-        D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS, D3D12_RESOURCE_FLAG_NONE,
-        D3D12_RESOURCE_STATE_COMMON, D3D12_VERTEX_BUFFER_VIEW,
-    },
-    Dxgi::Common::{
-        DXGI_FORMAT, DXGI_FORMAT_R32_FLOAT, DXGI_FORMAT_R32_SINT, DXGI_FORMAT_R32_UINT,
-        DXGI_FORMAT_R8_SINT, DXGI_FORMAT_R8_SNORM, DXGI_FORMAT_R8_UINT, DXGI_FORMAT_R8_UNORM,
-    },
-};
-
-use super::{
-    core::{
-        descriptor::Descriptor,
-        device::Device,
-        resource::Resource,
-        wrap::{HeapProps, SrvDesc, UavDesc},
-    },
-    initializer::Initializer,
-};
-
-pub struct Texture2D {
-    pub resource: Resource,
-    pub srv: Descriptor,
-}
-
-pub struct RwBuffer {
-    pub resource: Resource,
-    pub srv: Descriptor,
-    pub uav: Descriptor,
-    pub raw_uav: Descriptor,
-}
-
-impl RwBuffer {
-    pub fn new(ctx: &mut Initializer, num_elems: u32, format: DXGI_FORMAT) -> Result<Self> {
-        let elem_size = match format {
-            DXGI_FORMAT_R8_SINT | DXGI_FORMAT_R8_UINT | DXGI_FORMAT_R8_SNORM
-            | DXGI_FORMAT_R8_UNORM => 1,
-            DXGI_FORMAT_R32_FLOAT | DXGI_FORMAT_R32_UINT | DXGI_FORMAT_R32_SINT => 4,
-
-            _ => unreachable!("unsupported format {format:?}"),
-        };
-
-        let size = elem_size as usize * num_elems as usize;
-
-        let resource = Resource::new_buffer(
-            ctx,
-            &HeapProps::default(),
-            None,
-            size as _,
-            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
-            D3D12_RESOURCE_STATE_COMMON,
-        )?;
-
-        let srv = ctx.next_descriptor();
-        let desc = SrvDesc::buffer(num_elems, format);
-        ctx.create_srv(&resource, Some(&desc), srv.cpu);
-
-        let uav = ctx.next_descriptor();
-        let desc = UavDesc::buffer(num_elems, format);
-        ctx.create_uav(&resource, Some(&desc), uav.cpu);
-
-        let raw_uav = ctx.next_descriptor();
-        let desc = UavDesc::raw((size / 4) as _);
-        ctx.create_uav(&resource, Some(&desc), raw_uav.cpu);
-
-        Ok(Self {
-            resource,
-            srv,
-            uav,
-            raw_uav,
-        })
-    }
-}
-
-impl AsRef<Resource> for RwBuffer {
-    fn as_ref(&self) -> &Resource {
-        &self.resource
-    }
-}
-
-impl std::ops::Deref for RwBuffer {
-    type Target = Resource;
-
-    fn deref(&self) -> &Self::Target {
-        self.as_ref()
-    }
-}
-
-pub struct VertexBuffer {
-    #[allow(unused)]
-    buffer: Resource,
-    view: D3D12_VERTEX_BUFFER_VIEW,
-    n_vertices: u32,
-}
-
-impl VertexBuffer {
-    pub fn new<T>(device: &Device, vertices: &[T]) -> Result<Self> {
-        let stride = std::mem::size_of::<T>();
-        let n_vertices = vertices.len();
-        let size = std::mem::size_of_val(vertices);
-
-        let buffer = Resource::new_buffer(
-            device,
-            &HeapProps::upload(),
-            None,
-            size as _,
-            D3D12_RESOURCE_FLAG_NONE,
-            D3D12_RESOURCE_STATE_COMMON,
-        )?;
-
-        buffer.write(vertices)?;
-
-        let view = D3D12_VERTEX_BUFFER_VIEW {
-            BufferLocation: unsafe { buffer.GetGPUVirtualAddress() },
-            SizeInBytes: size as _,
-            StrideInBytes: stride as _,
-        };
-
-        Ok(Self {
-            buffer,
-            view,
-            n_vertices: n_vertices as _,
-        })
-    }
-
-    pub fn view(&self) -> D3D12_VERTEX_BUFFER_VIEW {
-        self.view
-    }
-
-    pub fn vertex_count(&self) -> u32 {
-        self.n_vertices
-    }
-}
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{
+        Direct3D12::{
+            D3D12_BOX, D3D12_PLACED_SUBRESOURCE_FOOTPRINT, D3D12_RESOURCE_BARRIER,
+            D3D12_RESOURCE_FLAG_ALLOW_RENDER_TARGET, D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_FLAG_NONE, D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_COMMON, D3D12_RESOURCE_STATE_COPY_DEST,
+            D3D12_RESOURCE_STATE_COPY_SOURCE, D3D12_RESOURCE_STATE_GENERIC_READ,
+            D3D12_RESOURCE_STATE_INDIRECT_ARGUMENT, D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_RENDER_TARGET, D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_SHADER_RESOURCE_VIEW_DESC, D3D12_SUBRESOURCE_FOOTPRINT,
+            D3D12_TEXTURE_DATA_PITCH_ALIGNMENT, D3D12_UNORDERED_ACCESS_VIEW_DESC,
+            D3D12_VERTEX_BUFFER_VIEW,
+        },
+        Dxgi::Common::{
+            DXGI_FORMAT, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32G32B32A32_FLOAT,
+            DXGI_FORMAT_R32_FLOAT, DXGI_FORMAT_R32_SINT, DXGI_FORMAT_R32_UINT, DXGI_FORMAT_R8_SINT,
+            DXGI_FORMAT_R8_SNORM, DXGI_FORMAT_R8_UINT, DXGI_FORMAT_R8_UNORM,
+        },
+    },
+};
+
+use super::{
+    core::{
+        command_signature::CommandSignature,
+        descriptor::Descriptor,
+        device::Device,
+        resource::Resource,
+        wrap::{ClearValue, Format, HeapProps, ResourceDesc, RtvDesc, SrvDesc, UavDesc},
+    },
+    initializer::Initializer,
+    math,
+    renderer::Renderer,
+};
+
+pub struct Texture2D {
+    pub resource: Resource,
+    pub srv: Descriptor,
+    // the srv format, srgb ones are stored typeless
+    format: DXGI_FORMAT,
+}
+
+impl Texture2D {
+    pub fn new(
+        ctx: &mut Initializer,
+        width: u32,
+        height: u32,
+        format: DXGI_FORMAT,
+    ) -> Result<Self> {
+        let srv = ctx.next_descriptor();
+        let resource = Self::create(ctx, width, height, format, false, srv)?;
+
+        Ok(Self {
+            resource,
+            srv,
+            format,
+        })
+    }
+
+    // a full mip chain for MipGenerator, srgb formats can be averaged in linear light
+    pub fn with_mips(
+        ctx: &mut Initializer,
+        width: u32,
+        height: u32,
+        format: DXGI_FORMAT,
+    ) -> Result<Self> {
+        let srv = ctx.next_descriptor();
+        let resource = Self::create(ctx, width, height, format, true, srv)?;
+
+        Ok(Self {
+            resource,
+            srv,
+            format,
+        })
+    }
+
+    // rows are tightly packed, the copy is recorded on the renderer's command list, pixels in an
+    // srgb format are decoded to linear when sampled like the desktop
+    pub fn from_pixels(
+        ctx: &mut Renderer,
+        srv: Descriptor,
+        width: u32,
+        height: u32,
+        format: DXGI_FORMAT,
+        pixels: &[u8],
+    ) -> Result<Self> {
+        let resource = Self::create(ctx.device(), width, height, format, false, srv)?;
+        let mut texture = Self {
+            resource,
+            srv,
+            format,
+        };
+        texture.upload(ctx, width, height, pixels)?;
+
+        Ok(texture)
+    }
+
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) -> Result<()> {
+        let mips = self.resource.desc().MipLevels > 1;
+        self.resource = Self::create(device, width, height, self.format, mips, self.srv)?;
+
+        Ok(())
+    }
+
+    // replaces the top mip, the texture is recreated when the size changes
+    pub fn upload(
+        &mut self,
+        ctx: &mut Renderer,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<()> {
+        let desc = self.resource.desc();
+        let format = self.format;
+        let row_size = (bytes_per_pixel(format) * width) as usize;
+        anyhow::ensure!(
+            pixels.len() == row_size * height as usize,
+            "expected {width}x{height} pixels of {} bytes, got {} bytes",
+            bytes_per_pixel(format),
+            pixels.len()
+        );
+
+        if self.resource.size() != (width, height) {
+            let mips = desc.MipLevels > 1;
+            let resource = Self::create(ctx.device(), width, height, format, mips, self.srv)?;
+            ctx.retire(std::mem::replace(&mut self.resource, resource));
+        }
+
+        let footprint = D3D12_PLACED_SUBRESOURCE_FOOTPRINT {
+            Offset: 0,
+            Footprint: D3D12_SUBRESOURCE_FOOTPRINT {
+                Format: Format::typeless(format),
+                Width: width,
+                Height: height,
+                Depth: 1,
+                RowPitch: (row_size as u32).next_multiple_of(D3D12_TEXTURE_DATA_PITCH_ALIGNMENT),
+            },
+        };
+
+        // upload rows must start on the pitch alignment
+        let pitch = footprint.Footprint.RowPitch as usize;
+        let mut data = vec![0u8; pitch * height as usize];
+        for (dst, src) in data
+            .chunks_exact_mut(pitch)
+            .zip(pixels.chunks_exact(row_size))
+        {
+            dst[..row_size].copy_from_slice(src);
+        }
+
+        let staging = Resource::new_buffer(
+            ctx.device(),
+            &HeapProps::upload(),
+            None,
+            data.len() as u64,
+            D3D12_RESOURCE_FLAG_NONE,
+            D3D12_RESOURCE_STATE_GENERIC_READ,
+        )?;
+        staging.write(&data)?;
+
+        ctx.resource_barrier(&[self.resource.transition_barrier(
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_COPY_DEST,
+        )]);
+        ctx.copy_buffer_to_texture(&self.resource, &staging, footprint);
+        ctx.resource_barrier(&[self.resource.transition_barrier(
+            D3D12_RESOURCE_STATE_COPY_DEST,
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+        )]);
+
+        // kept alive until the copy has run
+        ctx.retire(staging);
+
+        Ok(())
+    }
+
+    pub fn format(&self) -> DXGI_FORMAT {
+        self.format
+    }
+
+    fn create(
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: DXGI_FORMAT,
+        mips: bool,
+        srv: Descriptor,
+    ) -> Result<Resource> {
+        // the lower mips are written by a compute pass
+        let (levels, flags) = if mips {
+            (
+                32 - width.max(height).max(1).leading_zeros(),
+                D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            )
+        } else {
+            (1, D3D12_RESOURCE_FLAG_NONE)
+        };
+
+        let resource = Resource::new(
+            device,
+            &HeapProps::default(),
+            None,
+            &ResourceDesc::texture2d_mips(
+                width,
+                height,
+                Format::typeless(format),
+                levels as _,
+                flags,
+            ),
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            None,
+        )?;
+
+        let srv_desc = SrvDesc::texture2d_mips(format, 0, levels);
+        device.create_srv(&resource, Some(&srv_desc), srv.cpu);
+
+        Ok(resource)
+    }
+}
+
+impl AsRef<Resource> for Texture2D {
+    fn as_ref(&self) -> &Resource {
+        &self.resource
+    }
+}
+
+impl std::ops::Deref for Texture2D {
+    type Target = Resource;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+// cube shaped rgba32f volume, filled from the cpu through an upload buffer
+pub struct Texture3D {
+    pub resource: Resource,
+    pub srv: Descriptor,
+    staging: Option<Resource>,
+}
+
+impl Texture3D {
+    const FORMAT: DXGI_FORMAT = DXGI_FORMAT_R32G32B32A32_FLOAT;
+
+    pub fn new(ctx: &mut Initializer, size: u32) -> Result<Self> {
+        let srv = ctx.next_descriptor();
+        let resource = Self::create(ctx, size, srv)?;
+
+        Ok(Self {
+            resource,
+            srv,
+            staging: None,
+        })
+    }
+
+    // texels are x (fastest), y, z ordered
+    pub fn upload(&mut self, ctx: &mut Renderer, size: u32, texels: &[[f32; 4]]) -> Result<()> {
+        anyhow::ensure!(
+            texels.len() == (size * size * size) as usize,
+            "expected {size}^3 texels, got {}",
+            texels.len()
+        );
+
+        if self.resource.desc().Width != size as u64 {
+            let resource = Self::create(ctx.device(), size, self.srv)?;
+            ctx.retire(std::mem::replace(&mut self.resource, resource));
+        }
+
+        let row_size = size as usize * std::mem::size_of::<[f32; 4]>();
+        let footprint = D3D12_PLACED_SUBRESOURCE_FOOTPRINT {
+            Offset: 0,
+            Footprint: D3D12_SUBRESOURCE_FOOTPRINT {
+                Format: Self::FORMAT,
+                Width: size,
+                Height: size,
+                Depth: size,
+                RowPitch: (row_size as u32).next_multiple_of(D3D12_TEXTURE_DATA_PITCH_ALIGNMENT),
+            },
+        };
+
+        let pitch = footprint.Footprint.RowPitch as usize;
+        let mut data = vec![0u8; pitch * (size * size) as usize];
+        for (row, texels) in data
+            .chunks_exact_mut(pitch)
+            .zip(texels.chunks_exact(size as _))
+        {
+            for (dst, texel) in row[..row_size]
+                .chunks_exact_mut(4)
+                .zip(texels.iter().flatten())
+            {
+                dst.copy_from_slice(&texel.to_le_bytes());
+            }
+        }
+
+        let staging = Resource::new_buffer(
+            ctx.device(),
+            &HeapProps::upload(),
+            None,
+            data.len() as u64,
+            D3D12_RESOURCE_FLAG_NONE,
+            D3D12_RESOURCE_STATE_GENERIC_READ,
+        )?;
+        staging.write(&data)?;
+
+        ctx.resource_barrier(&[self.resource.transition_barrier(
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_COPY_DEST,
+        )]);
+        ctx.copy_buffer_to_texture(&self.resource, &staging, footprint);
+        ctx.resource_barrier(&[self.resource.transition_barrier(
+            D3D12_RESOURCE_STATE_COPY_DEST,
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+        )]);
+
+        // kept alive until the copy has run
+        if let Some(staging) = self.staging.replace(staging) {
+            ctx.retire(staging);
+        }
+
+        Ok(())
+    }
+
+    fn create(device: &Device, size: u32, srv: Descriptor) -> Result<Resource> {
+        let resource = Resource::new(
+            device,
+            &HeapProps::default(),
+            None,
+            &ResourceDesc::texture3d(size, Self::FORMAT, D3D12_RESOURCE_FLAG_NONE),
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            None,
+        )?;
+
+        device.create_srv(&resource, Some(&SrvDesc::texture3d(Self::FORMAT)), srv.cpu);
+
+        Ok(resource)
+    }
+}
+
+// a texture drawn to as a render target and read back by later passes
+pub struct RenderTexture {
+    pub resource: Resource,
+    pub srv: Descriptor,
+    pub rtv: Descriptor,
+    pub clear: [f32; 4],
+}
+
+impl RenderTexture {
+    pub fn new(
+        ctx: &mut Initializer,
+        rtv: Descriptor,
+        width: u32,
+        height: u32,
+        format: DXGI_FORMAT,
+        clear: [f32; 4],
+    ) -> Result<Self> {
+        let srv = ctx.next_descriptor();
+        let resource = Self::create(ctx, width, height, format, clear, srv, rtv)?;
+
+        Ok(Self {
+            resource,
+            srv,
+            rtv,
+            clear,
+        })
+    }
+
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) -> Result<()> {
+        let format = self.resource.desc().Format;
+        self.resource = Self::create(
+            device, width, height, format, self.clear, self.srv, self.rtv,
+        )?;
+
+        Ok(())
+    }
+
+    fn create(
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: DXGI_FORMAT,
+        clear: [f32; 4],
+        srv: Descriptor,
+        rtv: Descriptor,
+    ) -> Result<Resource> {
+        let resource = Resource::new(
+            device,
+            &HeapProps::default(),
+            None,
+            &ResourceDesc::texture2d(
+                width.max(1),
+                height.max(1),
+                format,
+                D3D12_RESOURCE_FLAG_ALLOW_RENDER_TARGET,
+            ),
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            Some(&ClearValue::color(format, clear)),
+        )?;
+
+        device.create_srv(&resource, Some(&SrvDesc::texture2d(format)), srv.cpu);
+        device.create_rtv(&resource, Some(&RtvDesc::default(format)), rtv.cpu);
+
+        Ok(resource)
+    }
+}
+
+impl std::ops::Deref for RenderTexture {
+    type Target = Resource;
+
+    fn deref(&self) -> &Self::Target {
+        &self.resource
+    }
+}
+
+// written by compute passes and loaded by later ones, idle in NON_PIXEL_SHADER_RESOURCE like the
+// buffers
+pub struct RwTexture2D {
+    pub resource: Resource,
+    pub srv: Descriptor,
+    pub uav: Descriptor,
+}
+
+impl RwTexture2D {
+    pub fn new(
+        ctx: &mut Initializer,
+        width: u32,
+        height: u32,
+        format: DXGI_FORMAT,
+    ) -> Result<Self> {
+        let srv = ctx.next_descriptor();
+        let uav = ctx.next_descriptor();
+        let resource = Self::create(ctx, width, height, format, [srv, uav])?;
+
+        Ok(Self { resource, srv, uav })
+    }
+
+    // a new texture behind the same descriptors, the caller retires the old resource
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) -> Result<()> {
+        let format = self.resource.desc().Format;
+        self.resource = Self::create(device, width, height, format, [self.srv, self.uav])?;
+
+        Ok(())
+    }
+
+    fn create(
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: DXGI_FORMAT,
+        [srv, uav]: [Descriptor; 2],
+    ) -> Result<Resource> {
+        let resource = Resource::new(
+            device,
+            &HeapProps::default(),
+            None,
+            &ResourceDesc::texture2d(
+                width.max(1),
+                height.max(1),
+                format,
+                D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            ),
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            None,
+        )?;
+
+        device.create_srv(&resource, Some(&SrvDesc::texture2d(format)), srv.cpu);
+        device.create_uav(&resource, Some(&UavDesc::texture2d(format, 0)), uav.cpu);
+
+        Ok(resource)
+    }
+}
+
+impl std::ops::Deref for RwTexture2D {
+    type Target = Resource;
+
+    fn deref(&self) -> &Self::Target {
+        &self.resource
+    }
+}
+
+pub struct RwBuffer {
+    pub resource: Resource,
+    pub srv: Descriptor,
+    pub uav: Descriptor,
+    pub raw_uav: Descriptor,
+}
+
+impl RwBuffer {
+    pub fn new(ctx: &mut Initializer, num_elems: u32, format: DXGI_FORMAT) -> Result<Self> {
+        Self::create(
+            ctx,
+            Self::elem_size(format) * num_elems as usize,
+            &SrvDesc::buffer(num_elems, format),
+            &UavDesc::buffer(num_elems, format),
+        )
+    }
+
+    // a new typed buffer behind the same descriptors, the caller retires the old resource
+    pub fn resize(&mut self, device: &Device, num_elems: u32, format: DXGI_FORMAT) -> Result<()> {
+        self.resource = Self::create_resource(
+            device,
+            Self::elem_size(format) * num_elems as usize,
+            &SrvDesc::buffer(num_elems, format),
+            &UavDesc::buffer(num_elems, format),
+            [self.srv, self.uav, self.raw_uav],
+        )?;
+
+        Ok(())
+    }
+
+    fn elem_size(format: DXGI_FORMAT) -> usize {
+        match format {
+            DXGI_FORMAT_R8_SINT | DXGI_FORMAT_R8_UINT | DXGI_FORMAT_R8_SNORM
+            | DXGI_FORMAT_R8_UNORM => 1,
+            DXGI_FORMAT_R32_FLOAT | DXGI_FORMAT_R32_UINT | DXGI_FORMAT_R32_SINT => 4,
+
+            _ => unreachable!("unsupported format {format:?}"),
+        }
+    }
+
+    // StructuredBuffer<T> / RWStructuredBuffer<T>, T has to match the hlsl struct layout
+    pub fn structured<T>(ctx: &mut Initializer, num_elems: u32) -> Result<Self> {
+        let stride = std::mem::size_of::<T>();
+        assert!(stride % 4 == 0, "structure stride must be a multiple of 4");
+
+        Self::create(
+            ctx,
+            stride * num_elems as usize,
+            &SrvDesc::structured(num_elems, stride as _),
+            &UavDesc::structured(num_elems, stride as _),
+        )
+    }
+
+    fn create(
+        ctx: &mut Initializer,
+        size: usize,
+        srv_desc: &D3D12_SHADER_RESOURCE_VIEW_DESC,
+        uav_desc: &D3D12_UNORDERED_ACCESS_VIEW_DESC,
+    ) -> Result<Self> {
+        let srv = ctx.next_descriptor();
+        let uav = ctx.next_descriptor();
+        let raw_uav = ctx.next_descriptor();
+        let resource = Self::create_resource(ctx, size, srv_desc, uav_desc, [srv, uav, raw_uav])?;
+
+        Ok(Self {
+            resource,
+            srv,
+            uav,
+            raw_uav,
+        })
+    }
+
+    fn create_resource(
+        device: &Device,
+        size: usize,
+        srv_desc: &D3D12_SHADER_RESOURCE_VIEW_DESC,
+        uav_desc: &D3D12_UNORDERED_ACCESS_VIEW_DESC,
+        [srv, uav, raw_uav]: [Descriptor; 3],
+    ) -> Result<Resource> {
+        let resource = Resource::new_buffer(
+            device,
+            &HeapProps::default(),
+            None,
+            size as _,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        device.create_srv(&resource, Some(srv_desc), srv.cpu);
+        device.create_uav(&resource, Some(uav_desc), uav.cpu);
+
+        // for clears, whatever the view type
+        let desc = UavDesc::raw((size / 4) as _);
+        device.create_uav(&resource, Some(&desc), raw_uav.cpu);
+
+        Ok(resource)
+    }
+}
+
+impl AsRef<Resource> for RwBuffer {
+    fn as_ref(&self) -> &Resource {
+        &self.resource
+    }
+}
+
+impl std::ops::Deref for RwBuffer {
+    type Target = Resource;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+// execute_indirect arguments written by compute shaders, one slot per command,
+// idle in NON_PIXEL_SHADER_RESOURCE like the other buffers
+pub struct IndirectArgs {
+    buffer: RwBuffer,
+    signature: CommandSignature,
+}
+
+impl IndirectArgs {
+    pub fn draw(ctx: &mut Initializer, count: u32) -> Result<Self> {
+        let signature = CommandSignature::draw(ctx)?;
+        Self::new(ctx, signature, count)
+    }
+
+    fn new(ctx: &mut Initializer, signature: CommandSignature, count: u32) -> Result<Self> {
+        let num_elems = count * signature.stride() / 4;
+        let buffer = RwBuffer::new(ctx, num_elems, DXGI_FORMAT_R32_UINT)?;
+
+        Ok(Self { buffer, signature })
+    }
+
+    // RWBuffer<uint> view for the shader filling the arguments
+    pub fn uav(&self) -> Descriptor {
+        self.buffer.uav
+    }
+
+    // makes the arguments writable, zeroed first when the shader only fills some fields
+    pub fn begin_write(&self, ctx: &mut Renderer, clear: bool) {
+        ctx.resource_barrier(&[self.buffer.transition_barrier(
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        )]);
+
+        if clear {
+            ctx.clear_uav(self.buffer.raw_uav, &self.buffer);
+            ctx.resource_barrier(&[self.buffer.uav_barrier()]);
+        }
+    }
+
+    // between passes that both write the arguments
+    pub fn uav_barrier(&self) -> D3D12_RESOURCE_BARRIER {
+        self.buffer.uav_barrier()
+    }
+
+    pub fn end_write(&self, ctx: &mut Renderer) {
+        ctx.resource_barrier(&[self.buffer.transition_barrier(
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_INDIRECT_ARGUMENT,
+        )]);
+    }
+
+    // runs the commands in slots index..index + count
+    pub fn execute(&self, ctx: &mut Renderer, index: u32, count: u32) {
+        let offset = index as u64 * self.signature.stride() as u64;
+        ctx.execute_indirect(&self.signature, count, &self.buffer, offset);
+    }
+
+    // after the last execute of the frame
+    pub fn finish(&self, ctx: &mut Renderer) {
+        ctx.resource_barrier(&[self.buffer.transition_barrier(
+            D3D12_RESOURCE_STATE_INDIRECT_ARGUMENT,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+    }
+}
+
+pub struct VertexBuffer {
+    #[allow(unused)]
+    buffer: Resource,
+    view: D3D12_VERTEX_BUFFER_VIEW,
+    n_vertices: u32,
+}
+
+impl VertexBuffer {
+    pub fn new<T>(device: &Device, vertices: &[T]) -> Result<Self> {
+        let stride = std::mem::size_of::<T>();
+        let n_vertices = vertices.len();
+        let size = std::mem::size_of_val(vertices);
+
+        let buffer = Resource::new_buffer(
+            device,
+            &HeapProps::upload(),
+            None,
+            size as _,
+            D3D12_RESOURCE_FLAG_NONE,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        buffer.write(vertices)?;
+
+        let view = D3D12_VERTEX_BUFFER_VIEW {
+            BufferLocation: unsafe { buffer.GetGPUVirtualAddress() },
+            SizeInBytes: size as _,
+            StrideInBytes: stride as _,
+        };
+
+        Ok(Self {
+            buffer,
+            view,
+            n_vertices: n_vertices as _,
+        })
+    }
+
+    pub fn view(&self) -> D3D12_VERTEX_BUFFER_VIEW {
+        self.view
+    }
+
+    pub fn vertex_count(&self) -> u32 {
+        self.n_vertices
+    }
+}
+
+// persistently mapped upload heap for geometry that changes every frame,
+// each frame writes its own range so vertices still read by the gpu are never overwritten
+pub struct DynamicVertexBuffer {
+    buffer: Resource,
+    mapped: *mut u8,
+    stride: usize,
+    capacity: usize,
+    frame: usize,
+    view: D3D12_VERTEX_BUFFER_VIEW,
+    n_vertices: u32,
+}
+
+// the mapping is owned by the buffer and only written from the thread using it
+unsafe impl Send for DynamicVertexBuffer {}
+
+impl DynamicVertexBuffer {
+    const FRAME_COUNT: usize = 2;
+
+    pub fn new<T>(device: &Device, capacity: usize) -> Result<Self> {
+        let stride = std::mem::size_of::<T>();
+        let size = Self::FRAME_COUNT * capacity * stride;
+
+        let buffer = Resource::new_buffer(
+            device,
+            &HeapProps::upload(),
+            None,
+            size as _,
+            D3D12_RESOURCE_FLAG_NONE,
+            D3D12_RESOURCE_STATE_GENERIC_READ,
+        )?;
+
+        let mut mapped = std::ptr::null_mut();
+        unsafe { buffer.Map(0, None, Some(&mut mapped))? };
+
+        Ok(Self {
+            buffer,
+            mapped: mapped as _,
+            stride,
+            capacity,
+            frame: 0,
+            view: D3D12_VERTEX_BUFFER_VIEW::default(),
+            n_vertices: 0,
+        })
+    }
+
+    // replaces the vertices for this frame, call once per frame before drawing
+    pub fn write<T: Copy>(&mut self, vertices: &[T]) -> Result<()> {
+        if std::mem::size_of::<T>() != self.stride {
+            anyhow::bail!("vertex stride mismatch");
+        }
+        if vertices.len() > self.capacity {
+            anyhow::bail!(
+                "{} vertices exceed the capacity {}",
+                vertices.len(),
+                self.capacity
+            );
+        }
+
+        self.frame = (self.frame + 1) % Self::FRAME_COUNT;
+        let offset = self.frame * self.capacity * self.stride;
+
+        unsafe {
+            let dest = self.mapped.add(offset) as *mut T;
+            dest.copy_from_nonoverlapping(vertices.as_ptr(), vertices.len());
+        }
+
+        self.view = D3D12_VERTEX_BUFFER_VIEW {
+            BufferLocation: unsafe { self.buffer.GetGPUVirtualAddress() } + offset as u64,
+            SizeInBytes: std::mem::size_of_val(vertices) as _,
+            StrideInBytes: self.stride as _,
+        };
+        self.n_vertices = vertices.len() as _;
+
+        Ok(())
+    }
+
+    pub fn view(&self) -> D3D12_VERTEX_BUFFER_VIEW {
+        self.view
+    }
+
+    pub fn vertex_count(&self) -> u32 {
+        self.n_vertices
+    }
+}
+
+impl Drop for DynamicVertexBuffer {
+    fn drop(&mut self) {
+        unsafe { self.buffer.Unmap(0, None) };
+    }
+}
+
+// copies part of a desktop texture (bgra8 srgb or scRGB float16) to the cpu
+#[derive(Default)]
+pub struct TextureReadback {
+    buffer: Option<Resource>,
+    footprint: Option<D3D12_PLACED_SUBRESOURCE_FOOTPRINT>,
+}
+
+pub struct Pixels {
+    pub width: u32,
+    pub height: u32,
+    // linear, 1.0 is sdr white
+    pub rgb: Vec<[f32; 3]>,
+}
+
+// the copied texels as stored, rows without padding
+pub struct RawPixels {
+    pub format: DXGI_FORMAT,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl TextureReadback {
+    // the rect is clamped to the texture, nothing is copied when it ends up empty
+    pub fn copy(&mut self, ctx: &mut Renderer, texture: &Resource, rect: RECT) -> Result<()> {
+        let (texture_width, texture_height) = texture.size();
+        let left = rect.left.clamp(0, texture_width as _) as u32;
+        let top = rect.top.clamp(0, texture_height as _) as u32;
+        let right = rect.right.clamp(0, texture_width as _) as u32;
+        let bottom = rect.bottom.clamp(0, texture_height as _) as u32;
+
+        self.footprint = None;
+        if right <= left || bottom <= top {
+            return Ok(());
+        }
+
+        let format = texture.desc().Format;
+        let footprint = D3D12_PLACED_SUBRESOURCE_FOOTPRINT {
+            Offset: 0,
+            Footprint: D3D12_SUBRESOURCE_FOOTPRINT {
+                Format: format,
+                Width: right - left,
+                Height: bottom - top,
+                Depth: 1,
+                RowPitch: (bytes_per_pixel(format) * (right - left))
+                    .next_multiple_of(D3D12_TEXTURE_DATA_PITCH_ALIGNMENT),
+            },
+        };
+
+        let size = footprint.Footprint.RowPitch as u64 * footprint.Footprint.Height as u64;
+        let buffer = match &mut self.buffer {
+            Some(buffer) if buffer.desc().Width >= size => buffer,
+            buffer => buffer.insert(Resource::new_buffer(
+                ctx.device(),
+                &HeapProps::readback(),
+                None,
+                size,
+                D3D12_RESOURCE_FLAG_NONE,
+                D3D12_RESOURCE_STATE_COPY_DEST,
+            )?),
+        };
+
+        // desktop textures are promoted from the common state, no barrier needed for reads
+        ctx.copy_texture_to_buffer(
+            buffer,
+            footprint,
+            texture,
+            &D3D12_BOX {
+                left,
+                top,
+                front: 0,
+                right,
+                bottom,
+                back: 1,
+            },
+        );
+
+        self.footprint = Some(footprint);
+        Ok(())
+    }
+
+    // the swap chain's render target, the passes leave it in the render target state
+    pub fn copy_render_target(&mut self, ctx: &mut Renderer, rect: RECT) -> Result<()> {
+        let target = ctx.render_target().clone();
+
+        ctx.resource_barrier(&[target.transition_barrier(
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+            D3D12_RESOURCE_STATE_COPY_SOURCE,
+        )]);
+        self.copy(ctx, &target, rect)?;
+        ctx.resource_barrier(&[target.transition_barrier(
+            D3D12_RESOURCE_STATE_COPY_SOURCE,
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+        )]);
+
+        Ok(())
+    }
+
+    // valid after the frame recorded by copy() has finished on the GPU, None when nothing was copied
+    pub fn read(&mut self) -> Result<Option<Pixels>> {
+        let Some(RawPixels {
+            format,
+            width,
+            height,
+            data,
+        }) = self.read_raw()?
+        else {
+            return Ok(None);
+        };
+
+        let rgb = if format == DXGI_FORMAT_R16G16B16A16_FLOAT {
+            data.chunks_exact(8)
+                .map(|pixel| {
+                    let channel = |i: usize| {
+                        math::f16_to_f32(u16::from_le_bytes([pixel[2 * i], pixel[2 * i + 1]]))
+                    };
+                    [channel(0), channel(1), channel(2)]
+                })
+                .collect()
+        } else {
+            data.chunks_exact(4)
+                .map(|pixel| {
+                    let channel = |i: usize| math::srgb_to_linear(pixel[i] as f32 / 255.0);
+                    [channel(2), channel(1), channel(0)]
+                })
+                .collect()
+        };
+
+        Ok(Some(Pixels { width, height, rgb }))
+    }
+
+    // like read(), without converting the texels
+    pub fn read_raw(&mut self) -> Result<Option<RawPixels>> {
+        let (Some(footprint), Some(buffer)) = (self.footprint.take(), &self.buffer) else {
+            return Ok(None);
+        };
+
+        let D3D12_SUBRESOURCE_FOOTPRINT {
+            Format: format,
+            Width: width,
+            Height: height,
+            RowPitch: pitch,
+            ..
+        } = footprint.Footprint;
+
+        let data = buffer.read::<u8>((pitch * height) as _)?;
+        let row_size = (bytes_per_pixel(format) * width) as usize;
+
+        let mut packed = Vec::with_capacity(row_size * height as usize);
+        for row in data.chunks_exact(pitch as _) {
+            packed.extend_from_slice(&row[..row_size]);
+        }
+
+        Ok(Some(RawPixels {
+            format,
+            width,
+            height,
+            data: packed,
+        }))
+    }
+}
+
+fn bytes_per_pixel(format: DXGI_FORMAT) -> u32 {
+    match format {
+        DXGI_FORMAT_R32G32B32A32_FLOAT => 16,
+        DXGI_FORMAT_R16G16B16A16_FLOAT => 8,
+        DXGI_FORMAT_R8_UNORM | DXGI_FORMAT_R8_UINT | DXGI_FORMAT_R8_SNORM | DXGI_FORMAT_R8_SINT => {
+            1
+        }
+        _ => 4,
+    }
+}
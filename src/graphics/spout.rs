@@ -0,0 +1,343 @@
+use anyhow::{Context as _, Result};
+use windows::{
+    core::{Interface, HSTRING, PCWSTR},
+    Win32::{
+        Foundation::{CloseHandle, GENERIC_ALL, HANDLE, WAIT_ABANDONED, WAIT_OBJECT_0},
+        Graphics::{
+            Direct3D11::*,
+            Direct3D12::ID3D12Fence,
+            Dxgi::{Common::DXGI_SAMPLE_DESC, IDXGIResource1, DXGI_SHARED_RESOURCE_READ},
+        },
+        System::{
+            Memory::{
+                MapViewOfFile, OpenFileMappingW, UnmapViewOfFile, FILE_MAP_READ,
+                MEMORY_MAPPED_VIEW_ADDRESS,
+            },
+            Threading::{
+                OpenMutexW, OpenSemaphoreW, ReleaseMutex, ReleaseSemaphore, WaitForSingleObject,
+                SEMAPHORE_MODIFY_STATE, SYNCHRONIZATION_SYNCHRONIZE,
+            },
+        },
+    },
+};
+
+use super::{
+    context::Context,
+    core::{device::Device, resource::Resource},
+    duplicate::d3d11_device,
+};
+
+// how long a copy waits for the sender to finish writing, about four frames at 60 Hz
+const ACCESS_TIMEOUT: u32 = 67;
+
+// SharedTextureInfo of spout's sender names, stored in a file mapping named after the sender
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SenderInfo {
+    share_handle: u32,
+    width: u32,
+    height: u32,
+    format: u32,
+    usage: u32,
+    description: [u16; 128],
+    partner_id: u32,
+}
+
+struct SenderMapping {
+    handle: HANDLE,
+    view: MEMORY_MAPPED_VIEW_ADDRESS,
+    // held by the sender while it writes its texture, "<name>_SpoutAccessMutex"
+    access: Option<HANDLE>,
+    // released once per frame by senders that count frames, "<name>_Count_Semaphore"
+    counter: Option<HANDLE>,
+    // the count of the frame copied last
+    frame: Option<i32>,
+}
+
+impl SenderMapping {
+    // the sender's frame count, None when it doesn't count frames
+    fn frame_count(&self) -> Option<i32> {
+        let counter = self.counter?;
+        unsafe {
+            // the count is only readable by releasing, the wait takes the release back
+            let mut count = 0;
+            ReleaseSemaphore(counter, 1, Some(&mut count)).ok()?;
+            WaitForSingleObject(counter, 0);
+            Some(count)
+        }
+    }
+}
+
+// the mapping and the sync objects are kernel handles of the process, not of the thread that
+// opened them, and the view is read-only memory. the visualizer thread is the only user
+unsafe impl Send for SenderMapping {}
+
+impl Drop for SenderMapping {
+    fn drop(&mut self) {
+        unsafe {
+            _ = UnmapViewOfFile(self.view);
+            _ = CloseHandle(self.handle);
+            for handle in [self.access, self.counter].into_iter().flatten() {
+                _ = CloseHandle(handle);
+            }
+        }
+    }
+}
+
+// the spout sender's access mutex, held while its texture is copied
+struct AccessGuard(HANDLE);
+
+impl AccessGuard {
+    // None when the sender kept writing for longer than ACCESS_TIMEOUT
+    fn lock(mutex: HANDLE) -> Option<Self> {
+        let wait = unsafe { WaitForSingleObject(mutex, ACCESS_TIMEOUT) };
+        // an abandoned mutex is ours too, its sender has quit
+        (wait == WAIT_OBJECT_0 || wait == WAIT_ABANDONED).then_some(Self(mutex))
+    }
+}
+
+impl Drop for AccessGuard {
+    fn drop(&mut self) {
+        unsafe {
+            _ = ReleaseMutex(self.0);
+        }
+    }
+}
+
+// a d3d11 fence opened on d3d12 too, work on either device waits for the other's copies.
+// both signal the next value in turn
+pub struct SharedFence {
+    fence: ID3D11Fence,
+    fence_d3d12: ID3D12Fence,
+    value: u64,
+}
+
+impl SharedFence {
+    pub fn new(device: &ID3D11Device, device_d3d12: &Device) -> Result<Self> {
+        unsafe {
+            let mut fence: Option<ID3D11Fence> = None;
+            device
+                .cast::<ID3D11Device5>()?
+                .CreateFence(0, D3D11_FENCE_FLAG_SHARED, &mut fence)?;
+            let fence = fence.unwrap();
+            let handle = fence.CreateSharedHandle(None, GENERIC_ALL.0, None)?;
+
+            let mut fence_d3d12: Option<ID3D12Fence> = None;
+            let opened = device_d3d12.OpenSharedHandle(handle, &mut fence_d3d12);
+            CloseHandle(handle)?;
+            opened?;
+
+            Ok(Self {
+                fence,
+                fence_d3d12: fence_d3d12.unwrap(),
+                value: 0,
+            })
+        }
+    }
+
+    // after the work submitted to the d3d11 context so far
+    pub fn signal(&mut self, context: &ID3D11DeviceContext4) -> Result<u64> {
+        self.value += 1;
+        unsafe { context.Signal(&self.fence, self.value)? };
+
+        Ok(self.value)
+    }
+
+    // after the frames executed on the d3d12 queue so far
+    pub fn queue_signal(&mut self, ctx: &Context) -> Result<u64> {
+        self.value += 1;
+        ctx.queue_signal(&self.fence_d3d12, self.value)?;
+
+        Ok(self.value)
+    }
+
+    // the d3d11 work submitted next waits on the gpu
+    pub fn wait(&self, context: &ID3D11DeviceContext4, value: u64) -> Result<()> {
+        unsafe { context.Wait(&self.fence, value)? };
+
+        Ok(())
+    }
+
+    // the d3d12 frames executed next wait on the gpu
+    pub fn queue_wait(&self, ctx: &Context, value: u64) -> Result<()> {
+        ctx.queue_wait(&self.fence_d3d12, value)
+    }
+
+    // blocks until the last value signaled has been reached
+    pub fn wait_idle(&self) -> Result<()> {
+        unsafe {
+            // without an event the call returns once the fence is there
+            self.fence_d3d12
+                .SetEventOnCompletion(self.value, HANDLE::default())?;
+        }
+
+        Ok(())
+    }
+}
+
+// frames from another gpu app, a spout sender or a texture shared under an nt handle name.
+// each frame is copied on a d3d11 device into a texture d3d12 opens through from_dxgi
+pub struct SharedTexture {
+    device: ID3D11Device,
+    context: ID3D11DeviceContext4,
+    fence: SharedFence,
+    sender: Option<SenderMapping>,
+    // the sender's texture and the legacy handle it was opened from
+    source: Option<(u32, ID3D11Texture2D)>,
+    bridge: Option<(ID3D11Texture2D, Resource)>,
+}
+
+impl SharedTexture {
+    pub fn open(device: &Device, name: &str) -> Result<Self> {
+        unsafe {
+            let device_d3d11 = d3d11_device(device, false)?;
+            let context = device_d3d11.GetImmediateContext()?.cast()?;
+            let fence = SharedFence::new(&device_d3d11, device)?;
+            let sync_name = |suffix: &str| HSTRING::from(format!("{name}{suffix}"));
+            let name = HSTRING::from(name);
+
+            let mut shared = Self {
+                device: device_d3d11,
+                context,
+                fence,
+                sender: None,
+                source: None,
+                bridge: None,
+            };
+
+            match OpenFileMappingW(FILE_MAP_READ.0, false, PCWSTR(name.as_ptr())) {
+                Ok(handle) => {
+                    let view = MapViewOfFile(handle, FILE_MAP_READ, 0, 0, size_of::<SenderInfo>());
+                    if view.Value.is_null() {
+                        _ = CloseHandle(handle);
+                        anyhow::bail!("spout sender {name} can't be read");
+                    }
+                    // older senders have no mutex or no frame count
+                    let access = sync_name("_SpoutAccessMutex");
+                    let counter = sync_name("_Count_Semaphore");
+                    shared.sender = Some(SenderMapping {
+                        handle,
+                        view,
+                        access: OpenMutexW(
+                            SYNCHRONIZATION_SYNCHRONIZE,
+                            false,
+                            PCWSTR(access.as_ptr()),
+                        )
+                        .ok(),
+                        counter: OpenSemaphoreW(
+                            SYNCHRONIZATION_SYNCHRONIZE | SEMAPHORE_MODIFY_STATE,
+                            false,
+                            PCWSTR(counter.as_ptr()),
+                        )
+                        .ok(),
+                        frame: None,
+                    });
+                }
+                Err(_) => {
+                    let mut texture: Option<ID3D11Texture2D> = None;
+                    shared
+                        .device
+                        .cast::<ID3D11Device1>()?
+                        .OpenSharedResourceByName(
+                            PCWSTR(name.as_ptr()),
+                            DXGI_SHARED_RESOURCE_READ.0,
+                            &mut texture,
+                        )
+                        .with_context(|| format!("no spout sender or shared texture {name}"))?;
+                    shared.source = Some((0, texture.unwrap()));
+                }
+            }
+
+            Ok(shared)
+        }
+    }
+
+    // copies the sender's frame when it has a new one, the replaced copy is returned for
+    // retiring. frames of a plain shared texture can't be told apart, each one counts as new
+    pub fn receive(&mut self, ctx: &Context) -> Result<(Option<&Resource>, Option<Resource>)> {
+        unsafe {
+            // spout senders publish their texture's handle, a new one when they resize
+            let (mut count, mut access) = (None, None);
+            if let Some(sender) = &self.sender {
+                count = sender.frame_count();
+                if count.is_some() && count == sender.frame && self.bridge.is_some() {
+                    return Ok((None, None));
+                }
+
+                if let Some(mutex) = sender.access {
+                    // still being written, tried again next frame
+                    let Some(guard) = AccessGuard::lock(mutex) else {
+                        return Ok((None, None));
+                    };
+                    access = Some(guard);
+                }
+
+                let info = std::ptr::read_volatile(sender.view.Value as *const SenderInfo);
+                if self.source.as_ref().map(|(handle, _)| *handle) != Some(info.share_handle) {
+                    // legacy handles are 32-bit and sign extended
+                    let handle = HANDLE(info.share_handle as i32 as isize as _);
+                    let mut texture: Option<ID3D11Texture2D> = None;
+                    self.device.OpenSharedResource(handle, &mut texture)?;
+                    self.source = Some((info.share_handle, texture.unwrap()));
+                }
+            }
+            let (_, source) = self.source.as_ref().context("no shared texture")?;
+
+            let mut desc = D3D11_TEXTURE2D_DESC::default();
+            source.GetDesc(&mut desc);
+
+            let mut retired = None;
+            let stale = self.bridge.as_ref().is_none_or(|(bridge, _)| {
+                let mut current = D3D11_TEXTURE2D_DESC::default();
+                bridge.GetDesc(&mut current);
+                (current.Width, current.Height, current.Format)
+                    != (desc.Width, desc.Height, desc.Format)
+            });
+            if stale {
+                let bridge_desc = D3D11_TEXTURE2D_DESC {
+                    Width: desc.Width,
+                    Height: desc.Height,
+                    MipLevels: 1,
+                    ArraySize: 1,
+                    Format: desc.Format,
+                    SampleDesc: DXGI_SAMPLE_DESC {
+                        Count: 1,
+                        Quality: 0,
+                    },
+                    Usage: D3D11_USAGE_DEFAULT,
+                    BindFlags: D3D11_BIND_SHADER_RESOURCE.0 as u32,
+                    CPUAccessFlags: 0,
+                    MiscFlags: (D3D11_RESOURCE_MISC_SHARED_NTHANDLE.0
+                        | D3D11_RESOURCE_MISC_SHARED.0) as u32,
+                };
+
+                let mut bridge = None;
+                self.device
+                    .CreateTexture2D(&bridge_desc, None, Some(&mut bridge))?;
+                let bridge = bridge.unwrap();
+                let resource = Resource::from_dxgi(&bridge.cast::<IDXGIResource1>()?, ctx)?;
+                retired = self.bridge.replace((bridge, resource)).map(|(_, old)| old);
+            }
+
+            let (bridge, resource) = self.bridge.as_ref().unwrap();
+            self.context
+                .CopySubresourceRegion(bridge, 0, 0, 0, 0, source, 0, None);
+            // the frames executed next read the copy only after it has run
+            let copied = self.fence.signal(&self.context)?;
+            self.context.Flush();
+            drop(access);
+            self.fence.queue_wait(ctx, copied)?;
+
+            if let Some(sender) = &mut self.sender {
+                sender.frame = count;
+            }
+
+            Ok((Some(resource), retired))
+        }
+    }
+
+    // the last copy, frames in flight may still draw it
+    pub fn into_bridge(self) -> Option<Resource> {
+        self.bridge.map(|(_, resource)| resource)
+    }
+}
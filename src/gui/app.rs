@@ -1,24 +1,81 @@
-use anyhow::Result;
-use windows::Win32::Foundation::{HWND, RECT};
-
-use super::control::Builder;
-
-pub trait App: Sized {
-    fn new(hwnd: HWND) -> Result<Self>;
-
-    fn on_destroy(&mut self) -> Result<()>;
-
-    fn on_pos_changed(&mut self, x: i32, y: i32, width: i32, height: i32) -> Result<()>;
-
-    fn on_button(&mut self, id: u32, checked: bool);
-
-    fn on_slider(&mut self, id: u32, val: i32);
-
-    fn on_drag(&mut self, dx: i32, dy: i32);
-
-    fn window_rect(&mut self) -> RECT;
-
-    fn transparency(&mut self) -> bool;
-
-    fn build_menu(&mut self, builder: &mut Builder) -> Result<()>;
-}
+use anyhow::Result;
+use std::path::Path;
+
+use windows::Win32::Foundation::{HWND, RECT};
+
+use super::{control::Builder, menu::MenuEdge};
+
+pub trait App: Sized {
+    fn new(hwnd: HWND) -> Result<Self>;
+
+    fn on_destroy(&mut self) -> Result<()>;
+
+    fn on_pos_changed(&mut self, x: i32, y: i32, width: i32, height: i32) -> Result<()>;
+
+    fn on_button(&mut self, id: u32, checked: bool);
+
+    // a button clicked with alt held, returns false to handle it as a plain click
+    fn on_alt_button(&mut self, id: u32) -> bool;
+
+    // a file dropped onto the window, the first one when there are several
+    fn on_drop_file(&mut self, path: &Path);
+
+    fn on_slider(&mut self, id: u32, val: i32);
+
+    fn on_number(&mut self, id: u32, val: i32);
+
+    fn number_fields(&mut self) -> Vec<(u32, i32)>;
+
+    // brightness and contrast percentages read from the monitor, -1 when unknown. returns the
+    // sliders to move
+    fn on_monitor_readings(&mut self, brightness: i32, contrast: i32) -> Vec<(u32, i32)>;
+
+    // playback position in thousandths of the video's duration. returns the sliders to move
+    fn on_video_position(&mut self, permille: i32) -> Vec<(u32, i32)>;
+
+    // text fields report their content when they lose focus
+    fn on_text(&mut self, id: u32, text: &str);
+
+    // the index of the newly selected item in a combo box's list
+    fn on_combo(&mut self, id: u32, index: usize);
+
+    // true once after the config changed in a way the menu controls don't show
+    fn take_menu_rebuild(&mut self) -> bool;
+
+    fn on_drag(&mut self, dx: i32, dy: i32);
+
+    // the left button was released, after a drag or a click
+    fn on_drag_end(&mut self);
+
+    fn on_click(&mut self, x: i32, y: i32);
+
+    // returns the check box toggled by the key and its new state
+    fn on_key(&mut self, key: u16) -> Option<(u32, bool)>;
+
+    // (id, MOD_* modifiers, virtual key), registered system-wide on create
+    fn hotkeys(&mut self) -> Vec<(i32, u32, u32)>;
+
+    // like on_key, for a registered hotkey pressed anywhere
+    fn on_hotkey(&mut self, id: i32) -> Option<(u32, bool)>;
+
+    fn on_menu_shown(&mut self);
+
+    fn on_occluded(&mut self, occluded: bool);
+
+    // how strongly the menu hint shows, 0 hides it
+    fn menu_hint(&mut self) -> f32;
+
+    fn menu_activation(&mut self) -> (MenuEdge, i32);
+
+    fn window_rect(&mut self) -> RECT;
+
+    fn locked(&mut self) -> bool;
+
+    fn follow_window(&mut self) -> bool;
+
+    fn exclude_from_capture(&mut self) -> bool;
+
+    fn transparency(&mut self) -> bool;
+
+    fn build_menu(&mut self, builder: &mut Builder) -> Result<()>;
+}
@@ -1,469 +1,693 @@
-#![allow(unused, clippy::too_many_arguments)]
-
-use std::collections::HashMap;
-
-use anyhow::Result;
-use windows::{
-    core::{s, w, PCSTR},
-    Win32::{
-        Foundation::*,
-        Graphics::Gdi::{CreateFontIndirectA, HFONT},
-        UI::{Controls::*, WindowsAndMessaging::*},
-    },
-};
-
-use super::{
-    hwnd::{CheckBox, Hwnd, Trackbar},
-    utils::create_window,
-};
-
-pub enum Ctrl {
-    Space {
-        size: i32,
-    },
-    Row {
-        indent: i32,
-        elems: Vec<Ctrl>,
-    },
-    Col {
-        indent: i32,
-        elems: Vec<Ctrl>,
-    },
-    Text {
-        width: i32,
-        height: i32,
-        text: PCSTR,
-    },
-    Check {
-        width: i32,
-        height: i32,
-        id: u32,
-        text: PCSTR,
-        checked: bool,
-    },
-    Radio {
-        width: i32,
-        height: i32,
-        id: u32,
-        text: PCSTR,
-        checked: bool,
-        group: u32,
-    },
-    Slider {
-        width: i32,
-        height: i32,
-        id: u32,
-        min: i32,
-        max: i32,
-        val: i32,
-    },
-}
-
-pub struct Builder {
-    parent: HWND,
-    font: HFONT,
-    groups: HashMap<u32, Vec<RadioParam>>,
-    width: u32,
-    height: u32,
-}
-
-impl Builder {
-    pub fn new(parent: HWND) -> Result<Self> {
-        unsafe {
-            let size = std::mem::size_of::<NONCLIENTMETRICSA>() as u32;
-            let mut ncm = NONCLIENTMETRICSA {
-                cbSize: size,
-                ..Default::default()
-            };
-            SystemParametersInfoA(
-                SPI_GETNONCLIENTMETRICS,
-                size,
-                Some(&mut ncm as *mut _ as _),
-                SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS::default(),
-            )?;
-
-            ncm.lfCaptionFont.lfHeight *= 125;
-            ncm.lfCaptionFont.lfHeight /= 100;
-            let font = CreateFontIndirectA(&ncm.lfCaptionFont);
-
-            Ok(Self {
-                parent,
-                font,
-                groups: HashMap::new(),
-                width: 0,
-                height: 0,
-            })
-        }
-    }
-
-    pub fn size(&self) -> (u32, u32) {
-        (self.width, self.height)
-    }
-
-    pub fn build(&mut self, root: Ctrl) -> Result<()> {
-        let x = 12;
-        let y = 8;
-        let (w, h) = self.build_control(12, 8, root)?;
-
-        self.create_radios()?;
-
-        self.width = 2 * x + w as u32;
-        self.height = 2 * y + h as u32;
-
-        Ok(())
-    }
-
-    fn build_control(&mut self, x: i32, y: i32, ctrl: Ctrl) -> Result<(i32, i32)> {
-        use Ctrl::*;
-
-        match ctrl {
-            Space { size } => Ok((size, size)),
-            Row { indent, elems } => self.build_row(x, y, indent, elems),
-            Col { indent, elems } => self.build_col(x, y, indent, elems),
-            Text {
-                width,
-                height,
-                text,
-            } => self.create_text(x, y, width, height, text),
-            Check {
-                width,
-                height,
-                id,
-                text,
-                checked,
-            } => self.create_check(x, y, width, height, id, text, checked),
-            Radio {
-                width,
-                height,
-                id,
-                text,
-                checked,
-                group,
-            } => self.add_radio(x, y, width, height, id, text, checked, group),
-            Slider {
-                width,
-                height,
-                id,
-                min,
-                max,
-                val,
-            } => self.create_slider(x, y, width, height, id, min, max, val),
-        }
-    }
-
-    fn build_row(
-        &mut self,
-        mut x: i32,
-        y: i32,
-        indent: i32,
-        elems: Vec<Ctrl>,
-    ) -> Result<(i32, i32)> {
-        let mut width = 0;
-        let mut height = 0;
-
-        for ctrl in elems {
-            let (w, h) = self.build_control(x + indent, y, ctrl)?;
-            x += w;
-            width += w;
-            height = height.max(h);
-        }
-
-        Ok((width + indent, height))
-    }
-
-    fn build_col(
-        &mut self,
-        x: i32,
-        mut y: i32,
-        indent: i32,
-        elems: Vec<Ctrl>,
-    ) -> Result<(i32, i32)> {
-        let mut width = 0;
-        let mut height = 0;
-
-        for ctrl in elems {
-            let (w, h) = self.build_control(x + indent, y, ctrl)?;
-            y += h;
-            width = width.max(w);
-            height += h;
-        }
-
-        Ok((width + indent, height))
-    }
-
-    fn create_text(
-        &mut self,
-        x: i32,
-        y: i32,
-        width: i32,
-        height: i32,
-        text: PCSTR,
-    ) -> Result<(i32, i32)> {
-        let hwnd =
-            self.create_control(WINDOW_STYLE(0), s!("STATIC"), text, x, y, width, height, 0)?;
-
-        Ok((width, height))
-    }
-
-    fn create_check(
-        &mut self,
-        x: i32,
-        y: i32,
-        width: i32,
-        height: i32,
-        id: u32,
-        text: PCSTR,
-        checked: bool,
-    ) -> Result<(i32, i32)> {
-        let style = WINDOW_STYLE(BS_AUTOCHECKBOX as _);
-        let hwnd = self.create_control(style, s!("BUTTON"), text, x, y, width, height, id)?;
-
-        hwnd.checkbox_set_check(checked);
-
-        Ok((width, height))
-    }
-
-    fn add_radio(
-        &mut self,
-        x: i32,
-        y: i32,
-        width: i32,
-        height: i32,
-        id: u32,
-        text: PCSTR,
-        checked: bool,
-        group: u32,
-    ) -> Result<(i32, i32)> {
-        let radio = RadioParam {
-            text,
-            x,
-            y,
-            width,
-            height,
-            id,
-            checked,
-        };
-
-        self.groups
-            .entry(group)
-            .and_modify(|v| v.push(radio))
-            .or_insert(vec![radio]);
-
-        Ok((width, height))
-    }
-
-    fn create_radio(
-        &self,
-        x: i32,
-        y: i32,
-        width: i32,
-        height: i32,
-        id: u32,
-        text: PCSTR,
-        checked: bool,
-        group: bool,
-    ) -> Result<()> {
-        let style = WINDOW_STYLE(BS_AUTORADIOBUTTON as _)
-            | if group {
-                WS_GROUP
-            } else {
-                WINDOW_STYLE::default()
-            };
-        let hwnd = self.create_control(style, s!("BUTTON"), text, x, y, width, height, id)?;
-
-        hwnd.checkbox_set_check(checked);
-
-        Ok(())
-    }
-
-    fn create_radios(&self) -> Result<()> {
-        for radios in self.groups.values() {
-            let mut group = true;
-            for radio in radios {
-                self.create_radio(
-                    radio.x,
-                    radio.y,
-                    radio.width,
-                    radio.height,
-                    radio.id,
-                    radio.text,
-                    radio.checked,
-                    group,
-                )?;
-                group = false;
-            }
-        }
-
-        Ok(())
-    }
-
-    fn create_slider(
-        &self,
-        x: i32,
-        y: i32,
-        width: i32,
-        height: i32,
-        id: u32,
-        min: i32,
-        max: i32,
-        val: i32,
-    ) -> Result<(i32, i32)> {
-        let hwnd = self.create_control(
-            WINDOW_STYLE(0),
-            TRACKBAR_CLASSA,
-            s!("Trackbar"),
-            x,
-            y,
-            width,
-            height,
-            id,
-        )?;
-
-        hwnd.trackbar_set_min_max(min, max);
-        hwnd.trackbar_set_pos(val);
-
-        Ok((width, height))
-    }
-
-    fn create_control(
-        &self,
-        style: WINDOW_STYLE,
-        class_name: PCSTR,
-        window_name: PCSTR,
-        x: i32,
-        y: i32,
-        width: i32,
-        height: i32,
-        id: u32,
-    ) -> Result<HWND> {
-        let hwnd = create_window(
-            WINDOW_EX_STYLE::default(),
-            class_name,
-            window_name,
-            style | WS_CHILD | WS_VISIBLE,
-            x,
-            y,
-            width,
-            height,
-            self.parent,
-            HMENU(id as _),
-            None,
-        )?;
-
-        hwnd.send_message(WM_SETFONT, WPARAM(self.font.0 as _), LPARAM(1));
-        hwnd.set_theme(w!("DarkMode_Explorer"));
-
-        Ok(hwnd)
-    }
-}
-
-#[macro_export]
-macro_rules! space {
-    ($size:expr) => {
-        $crate::gui::control::Ctrl::Space { size: $size }
-    };
-}
-
-#[macro_export]
-macro_rules! col {
-    (indent: $indent:expr, $($elem:expr),* $(,)?) => {
-        $crate::gui::control::Ctrl::Col {
-            indent: $indent,
-            elems: vec![$($elem),*],
-        }
-    };
-    ($($elem:expr),* $(,)?) => {
-        $crate::gui::control::Ctrl::Col {
-            indent: 0,
-            elems: vec![$($elem),*],
-        }
-    };
-}
-
-#[macro_export]
-macro_rules! row {
-    (indent: $indent:expr, $($elem:expr),* $(,)?) => {
-        $crate::gui::control::Ctrl::Row {
-            indent: $indent,
-            elems: vec![$($elem),*],
-        }
-    };
-    ($($elem:expr),* $(,)?) => {
-        $crate::gui::control::Ctrl::Row {
-            indent: 0,
-            elems: vec![$($elem),*],
-        }
-    };
-}
-
-#[macro_export]
-macro_rules! text {
-    ($text:literal) => {
-        $crate::gui::control::Ctrl::Text {
-            width: 10 * $text.len() as i32,
-            height: 24,
-            text: ::windows::core::s!($text),
-        }
-    };
-}
-
-#[macro_export]
-macro_rules! check {
-    ($id:expr, $text:literal, $checked:expr) => {
-        $crate::gui::control::Ctrl::Check {
-            width: 80.max(10 * $text.len() as i32),
-            height: 24,
-            id: $id,
-            text: ::windows::core::s!($text),
-            checked: $checked,
-        }
-    };
-    (width: $width:expr, $id:expr, $text:literal, $checked:expr) => {
-        $crate::gui::control::Ctrl::Check {
-            width: $width,
-            height: 24,
-            id: $id,
-            text: ::windows::core::s!($text),
-            checked: $checked,
-        }
-    };
-}
-
-#[macro_export]
-macro_rules! radio {
-    ($id:expr, $text:literal, $checked:expr, $group:expr) => {
-        $crate::gui::control::Ctrl::Radio {
-            width: 80.max(10 * $text.len() as i32),
-            height: 24,
-            id: $id,
-            text: ::windows::core::s!($text),
-            checked: $checked,
-            group: $group,
-        }
-    };
-}
-
-#[macro_export]
-macro_rules! slider {
-    ($id:expr, $min:expr, $max:expr, $val:expr) => {
-        $crate::gui::control::Ctrl::Slider {
-            width: 100,
-            height: 24,
-            id: $id,
-            min: $min,
-            max: $max,
-            val: $val,
-        }
-    };
-}
-
-#[derive(Clone, Copy)]
-struct RadioParam {
-    text: PCSTR,
-    x: i32,
-    y: i32,
-    width: i32,
-    height: i32,
-    id: u32,
-    checked: bool,
-}
+#![allow(unused, clippy::too_many_arguments)]
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use windows::{
+    core::{w, HSTRING, PCWSTR},
+    Win32::{
+        Foundation::*,
+        Graphics::Gdi::{CreateFontIndirectA, HFONT},
+        UI::{Controls::*, HiDpi::SystemParametersInfoForDpi, WindowsAndMessaging::*},
+    },
+};
+
+use super::{
+    hwnd::{CheckBox, ComboBox, Hwnd, Trackbar},
+    utils::{create_window_w, scale},
+};
+
+// the text of labels, check boxes, buttons and radio buttons is a string id, looked up in the
+// builder's strings when the control is created
+pub enum Ctrl {
+    Space {
+        size: i32,
+    },
+    Row {
+        indent: i32,
+        elems: Vec<Ctrl>,
+    },
+    Col {
+        indent: i32,
+        elems: Vec<Ctrl>,
+    },
+    Text {
+        width: i32,
+        height: i32,
+        text: &'static str,
+    },
+    Check {
+        width: i32,
+        height: i32,
+        id: u32,
+        text: &'static str,
+        checked: bool,
+    },
+    Button {
+        width: i32,
+        height: i32,
+        id: u32,
+        text: &'static str,
+    },
+    Radio {
+        width: i32,
+        height: i32,
+        id: u32,
+        text: &'static str,
+        checked: bool,
+        group: u32,
+    },
+    Slider {
+        width: i32,
+        height: i32,
+        id: u32,
+        min: i32,
+        max: i32,
+        val: i32,
+    },
+    Number {
+        width: i32,
+        height: i32,
+        id: u32,
+        val: i32,
+    },
+    Edit {
+        width: i32,
+        height: i32,
+        id: u32,
+        text: String,
+    },
+    Combo {
+        width: i32,
+        height: i32,
+        id: u32,
+        items: Vec<String>,
+        selected: Option<usize>,
+    },
+}
+
+// sizes and positions are laid out at 96 dpi and scaled when the controls are created
+pub struct Builder {
+    parent: HWND,
+    dpi: u32,
+    font: HFONT,
+    groups: HashMap<u32, Vec<RadioParam>>,
+    strings: Option<&'static HashMap<String, String>>,
+    width: u32,
+    height: u32,
+}
+
+// the caption font scaled up for the menu's controls, owned by the caller which deletes it once
+// the controls using it are gone
+pub fn create_font(dpi: u32) -> Result<HFONT> {
+    unsafe {
+        let size = std::mem::size_of::<NONCLIENTMETRICSA>() as u32;
+        let mut ncm = NONCLIENTMETRICSA {
+            cbSize: size,
+            ..Default::default()
+        };
+        SystemParametersInfoForDpi(
+            SPI_GETNONCLIENTMETRICS.0,
+            size,
+            Some(&mut ncm as *mut _ as _),
+            0,
+            dpi,
+        )?;
+
+        ncm.lfCaptionFont.lfHeight *= 125;
+        ncm.lfCaptionFont.lfHeight /= 100;
+        let font = CreateFontIndirectA(&ncm.lfCaptionFont);
+        if font.is_invalid() {
+            anyhow::bail!("failed to create the menu font");
+        }
+
+        Ok(font)
+    }
+}
+
+impl Builder {
+    pub fn new(parent: HWND, dpi: u32, font: HFONT) -> Self {
+        Self {
+            parent,
+            dpi,
+            font,
+            groups: HashMap::new(),
+            strings: None,
+            width: 0,
+            height: 0,
+        }
+    }
+
+    // translations by string id, ids without one are shown as they are
+    pub fn set_strings(&mut self, strings: Option<&'static HashMap<String, String>>) {
+        self.strings = strings;
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn build(&mut self, root: Ctrl) -> Result<()> {
+        let x = 12;
+        let y = 8;
+        let (w, h) = self.build_control(12, 8, root)?;
+
+        self.create_radios()?;
+
+        self.width = scale(2 * x + w, self.dpi) as u32;
+        self.height = scale(2 * y + h, self.dpi) as u32;
+
+        Ok(())
+    }
+
+    fn build_control(&mut self, x: i32, y: i32, ctrl: Ctrl) -> Result<(i32, i32)> {
+        use Ctrl::*;
+
+        match ctrl {
+            Space { size } => Ok((size, size)),
+            Row { indent, elems } => self.build_row(x, y, indent, elems),
+            Col { indent, elems } => self.build_col(x, y, indent, elems),
+            Text {
+                width,
+                height,
+                text,
+            } => self.create_text(x, y, width, height, text),
+            Check {
+                width,
+                height,
+                id,
+                text,
+                checked,
+            } => self.create_check(x, y, width, height, id, text, checked),
+            Button {
+                width,
+                height,
+                id,
+                text,
+            } => self.create_button(x, y, width, height, id, text),
+            Radio {
+                width,
+                height,
+                id,
+                text,
+                checked,
+                group,
+            } => self.add_radio(x, y, width, height, id, text, checked, group),
+            Slider {
+                width,
+                height,
+                id,
+                min,
+                max,
+                val,
+            } => self.create_slider(x, y, width, height, id, min, max, val),
+            Number {
+                width,
+                height,
+                id,
+                val,
+            } => self.create_number(x, y, width, height, id, val),
+            Edit {
+                width,
+                height,
+                id,
+                text,
+            } => self.create_edit(x, y, width, height, id, &text),
+            Combo {
+                width,
+                height,
+                id,
+                items,
+                selected,
+            } => self.create_combo(x, y, width, height, id, &items, selected),
+        }
+    }
+
+    fn build_row(
+        &mut self,
+        mut x: i32,
+        y: i32,
+        indent: i32,
+        elems: Vec<Ctrl>,
+    ) -> Result<(i32, i32)> {
+        let mut width = 0;
+        let mut height = 0;
+
+        for ctrl in elems {
+            let (w, h) = self.build_control(x + indent, y, ctrl)?;
+            x += w;
+            width += w;
+            height = height.max(h);
+        }
+
+        Ok((width + indent, height))
+    }
+
+    fn build_col(
+        &mut self,
+        x: i32,
+        mut y: i32,
+        indent: i32,
+        elems: Vec<Ctrl>,
+    ) -> Result<(i32, i32)> {
+        let mut width = 0;
+        let mut height = 0;
+
+        for ctrl in elems {
+            let (w, h) = self.build_control(x + indent, y, ctrl)?;
+            y += h;
+            width = width.max(w);
+            height += h;
+        }
+
+        Ok((width + indent, height))
+    }
+
+    // leading spaces indent the text and are not part of the id
+    fn localize(&self, id: &str) -> String {
+        let key = id.trim_start();
+        let indent = &id[..id.len() - key.len()];
+
+        match self.strings.and_then(|strings| strings.get(key)) {
+            Some(text) => format!("{indent}{text}"),
+            None => id.to_string(),
+        }
+    }
+
+    fn create_text(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        text: &'static str,
+    ) -> Result<(i32, i32)> {
+        let text = self.localize(text);
+        let hwnd =
+            self.create_control(WINDOW_STYLE(0), w!("STATIC"), &text, x, y, width, height, 0)?;
+
+        Ok((width, height))
+    }
+
+    fn create_check(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        id: u32,
+        text: &'static str,
+        checked: bool,
+    ) -> Result<(i32, i32)> {
+        let text = self.localize(text);
+        let style = WINDOW_STYLE(BS_AUTOCHECKBOX as _);
+        let hwnd = self.create_control(style, w!("BUTTON"), &text, x, y, width, height, id)?;
+
+        hwnd.checkbox_set_check(checked);
+
+        Ok((width, height))
+    }
+
+    fn create_button(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        id: u32,
+        text: &'static str,
+    ) -> Result<(i32, i32)> {
+        let text = self.localize(text);
+        let style = WINDOW_STYLE(BS_PUSHBUTTON as _);
+        self.create_control(style, w!("BUTTON"), &text, x, y, width, height, id)?;
+
+        Ok((width, height))
+    }
+
+    fn add_radio(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        id: u32,
+        text: &'static str,
+        checked: bool,
+        group: u32,
+    ) -> Result<(i32, i32)> {
+        let radio = RadioParam {
+            text,
+            x,
+            y,
+            width,
+            height,
+            id,
+            checked,
+        };
+
+        self.groups
+            .entry(group)
+            .and_modify(|v| v.push(radio))
+            .or_insert(vec![radio]);
+
+        Ok((width, height))
+    }
+
+    fn create_radio(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        id: u32,
+        text: &'static str,
+        checked: bool,
+        group: bool,
+    ) -> Result<()> {
+        let style = WINDOW_STYLE(BS_AUTORADIOBUTTON as _)
+            | if group {
+                WS_GROUP
+            } else {
+                WINDOW_STYLE::default()
+            };
+        let text = self.localize(text);
+        let hwnd = self.create_control(style, w!("BUTTON"), &text, x, y, width, height, id)?;
+
+        hwnd.checkbox_set_check(checked);
+
+        Ok(())
+    }
+
+    fn create_radios(&self) -> Result<()> {
+        for radios in self.groups.values() {
+            let mut group = true;
+            for radio in radios {
+                self.create_radio(
+                    radio.x,
+                    radio.y,
+                    radio.width,
+                    radio.height,
+                    radio.id,
+                    radio.text,
+                    radio.checked,
+                    group,
+                )?;
+                group = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_slider(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        id: u32,
+        min: i32,
+        max: i32,
+        val: i32,
+    ) -> Result<(i32, i32)> {
+        let hwnd = self.create_control(
+            WINDOW_STYLE(0),
+            TRACKBAR_CLASSW,
+            "Trackbar",
+            x,
+            y,
+            width,
+            height,
+            id,
+        )?;
+
+        hwnd.trackbar_set_min_max(min, max);
+        hwnd.trackbar_set_pos(val);
+
+        Ok((width, height))
+    }
+
+    fn create_number(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        id: u32,
+        val: i32,
+    ) -> Result<(i32, i32)> {
+        self.create_edit(x, y, width, height, id, &val.to_string())
+    }
+
+    fn create_edit(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        id: u32,
+        text: &str,
+    ) -> Result<(i32, i32)> {
+        let style = WINDOW_STYLE(ES_AUTOHSCROLL as _) | WS_BORDER;
+        self.create_control(style, w!("EDIT"), text, x, y, width, height, id)?;
+
+        Ok((width, height))
+    }
+
+    fn create_combo(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        id: u32,
+        items: &[String],
+        selected: Option<usize>,
+    ) -> Result<(i32, i32)> {
+        // the window height includes the dropped down list
+        const VISIBLE_ITEMS: i32 = 8;
+        let list_height = height + VISIBLE_ITEMS * 20;
+
+        let style = WINDOW_STYLE(CBS_DROPDOWNLIST as _) | WS_VSCROLL;
+        let hwnd = self.create_control(style, w!("COMBOBOX"), "", x, y, width, list_height, id)?;
+
+        for item in items {
+            hwnd.combobox_add(item);
+        }
+        hwnd.combobox_set_selection(selected);
+
+        Ok((width, height))
+    }
+
+    fn create_control(
+        &self,
+        style: WINDOW_STYLE,
+        class_name: PCWSTR,
+        window_name: &str,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        id: u32,
+    ) -> Result<HWND> {
+        // everything but the labels can be reached with tab and the arrow keys
+        let style = if id != 0 { style | WS_TABSTOP } else { style };
+
+        // wide, for text outside the ansi code page. the string outlives the call
+        let text = HSTRING::from(window_name);
+        let hwnd = create_window_w(
+            WINDOW_EX_STYLE::default(),
+            class_name,
+            PCWSTR(text.as_ptr()),
+            style | WS_CHILD | WS_VISIBLE,
+            scale(x, self.dpi),
+            scale(y, self.dpi),
+            scale(width, self.dpi),
+            scale(height, self.dpi),
+            self.parent,
+            HMENU(id as _),
+            None,
+        )?;
+
+        hwnd.send_message(WM_SETFONT, WPARAM(self.font.0 as _), LPARAM(1));
+        hwnd.set_theme(w!("DarkMode_Explorer"));
+
+        Ok(hwnd)
+    }
+}
+
+#[macro_export]
+macro_rules! space {
+    ($size:expr) => {
+        $crate::gui::control::Ctrl::Space { size: $size }
+    };
+}
+
+#[macro_export]
+macro_rules! col {
+    (indent: $indent:expr, $($elem:expr),* $(,)?) => {
+        $crate::gui::control::Ctrl::Col {
+            indent: $indent,
+            elems: vec![$($elem),*],
+        }
+    };
+    ($($elem:expr),* $(,)?) => {
+        $crate::gui::control::Ctrl::Col {
+            indent: 0,
+            elems: vec![$($elem),*],
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! row {
+    (indent: $indent:expr, $($elem:expr),* $(,)?) => {
+        $crate::gui::control::Ctrl::Row {
+            indent: $indent,
+            elems: vec![$($elem),*],
+        }
+    };
+    ($($elem:expr),* $(,)?) => {
+        $crate::gui::control::Ctrl::Row {
+            indent: 0,
+            elems: vec![$($elem),*],
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! text {
+    ($text:literal) => {
+        $crate::gui::control::Ctrl::Text {
+            width: 10 * $text.len() as i32,
+            height: 24,
+            text: $text,
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! check {
+    ($id:expr, $text:literal, $checked:expr) => {
+        $crate::gui::control::Ctrl::Check {
+            width: 80.max(10 * $text.len() as i32),
+            height: 24,
+            id: $id,
+            text: $text,
+            checked: $checked,
+        }
+    };
+    (width: $width:expr, $id:expr, $text:literal, $checked:expr) => {
+        $crate::gui::control::Ctrl::Check {
+            width: $width,
+            height: 24,
+            id: $id,
+            text: $text,
+            checked: $checked,
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! button {
+    ($id:expr, $text:literal) => {
+        $crate::gui::control::Ctrl::Button {
+            width: 80.max(10 * $text.len() as i32),
+            height: 24,
+            id: $id,
+            text: $text,
+        }
+    };
+    (width: $width:expr, $id:expr, $text:literal) => {
+        $crate::gui::control::Ctrl::Button {
+            width: $width,
+            height: 24,
+            id: $id,
+            text: $text,
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! radio {
+    ($id:expr, $text:literal, $checked:expr, $group:expr) => {
+        $crate::gui::control::Ctrl::Radio {
+            width: 80.max(10 * $text.len() as i32),
+            height: 24,
+            id: $id,
+            text: $text,
+            checked: $checked,
+            group: $group,
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! slider {
+    ($id:expr, $min:expr, $max:expr, $val:expr) => {
+        $crate::gui::control::Ctrl::Slider {
+            width: 100,
+            height: 24,
+            id: $id,
+            min: $min,
+            max: $max,
+            val: $val,
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! number {
+    ($id:expr, $val:expr) => {
+        $crate::gui::control::Ctrl::Number {
+            width: 48,
+            height: 24,
+            id: $id,
+            val: $val,
+        }
+    };
+    (width: $width:expr, $id:expr, $val:expr) => {
+        $crate::gui::control::Ctrl::Number {
+            width: $width,
+            height: 24,
+            id: $id,
+            val: $val,
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! edit {
+    (width: $width:expr, $id:expr, $text:expr) => {
+        $crate::gui::control::Ctrl::Edit {
+            width: $width,
+            height: 24,
+            id: $id,
+            text: $text,
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! combo {
+    (width: $width:expr, $id:expr, $items:expr, $selected:expr) => {
+        $crate::gui::control::Ctrl::Combo {
+            width: $width,
+            height: 24,
+            id: $id,
+            items: $items,
+            selected: $selected,
+        }
+    };
+}
+
+#[derive(Clone, Copy)]
+struct RadioParam {
+    text: &'static str,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    id: u32,
+    checked: bool,
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use windows::{
+    core::s,
+    Win32::{
+        Foundation::{COLORREF, HWND, LPARAM, LRESULT, WPARAM},
+        Graphics::Gdi::CreateSolidBrush,
+        UI::WindowsAndMessaging::*,
+    },
+};
+
+use super::{
+    hwnd::Hwnd,
+    menu::MenuEdge,
+    utils,
+    window::{wndproc, Window},
+};
+
+const THICKNESS: i32 = 6;
+const LENGTH: i32 = 96;
+
+pub struct MenuHint {
+    hwnd: HWND,
+    // scales the alpha, lowered as the menu gets opened
+    strength: f32,
+    visible: bool,
+    phase: f32,
+}
+
+impl MenuHint {
+    pub fn create<'a>(parent: HWND) -> Result<&'a mut Self> {
+        unsafe {
+            let brush = CreateSolidBrush(COLORREF(0x909090));
+
+            utils::register_window_class(
+                CS_HREDRAW | CS_VREDRAW,
+                Some(wndproc::<Self>),
+                None,
+                None,
+                Some(brush),
+                s!("MenuHint"),
+            )?;
+
+            let hwnd = utils::create_window(
+                WS_EX_LAYERED | WS_EX_TRANSPARENT | WS_EX_NOACTIVATE | WS_EX_TOOLWINDOW,
+                s!("MenuHint"),
+                s!("MenuHint"),
+                WS_POPUP,
+                0,
+                0,
+                0,
+                0,
+                parent,
+                None,
+                None,
+            )?;
+
+            if let Some(mut this) = std::ptr::NonNull::new(hwnd.user_data() as *mut Self) {
+                Ok(this.as_mut())
+            } else {
+                anyhow::bail!(windows::core::Error::from_win32())
+            }
+        }
+    }
+
+    pub fn adjust_rect(
+        &mut self,
+        edge: MenuEdge,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        dpi: u32,
+    ) {
+        let thickness = utils::scale(THICKNESS, dpi);
+        let length = utils::scale(LENGTH, dpi);
+
+        let (x, y, width, height) = match edge {
+            MenuEdge::Left => {
+                let length = length.min(height);
+                (x, y + (height - length) / 2, thickness, length)
+            }
+            MenuEdge::Right => {
+                let length = length.min(height);
+                (
+                    x + width - thickness,
+                    y + (height - length) / 2,
+                    thickness,
+                    length,
+                )
+            }
+            MenuEdge::Top => {
+                let length = length.min(width);
+                (x + (width - length) / 2, y, length, thickness)
+            }
+        };
+
+        self.hwnd
+            .set_pos(x, y, width, height, SWP_NOZORDER | SWP_NOACTIVATE);
+    }
+
+    pub fn set_strength(&mut self, strength: f32) {
+        self.strength = strength.clamp(0.0, 1.0);
+
+        if self.strength == 0.0 && self.visible {
+            self.show(false);
+        }
+    }
+
+    pub fn update(&mut self, menu_visible: bool, hovered: bool) {
+        let visible = self.strength > 0.0 && !menu_visible;
+        if visible != self.visible {
+            self.show(visible);
+        }
+
+        if visible {
+            self.phase = (self.phase + 0.1) % std::f32::consts::TAU;
+
+            let alpha = if hovered {
+                192.0
+            } else {
+                48.0 + 32.0 * self.phase.sin()
+            };
+            self.hwnd.set_layered_alpha((self.strength * alpha) as u8);
+        }
+    }
+
+    fn show(&mut self, show: bool) {
+        self.visible = show;
+        self.hwnd
+            .show(if show { SW_SHOWNOACTIVATE } else { SW_HIDE });
+    }
+}
+
+impl Window for MenuHint {
+    fn new(hwnd: HWND, _cs: &mut CREATESTRUCTA) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            hwnd,
+            strength: 0.0,
+            visible: false,
+            phase: 0.0,
+        }))
+    }
+
+    #[allow(unused_variables)]
+    fn wndproc(&mut self, hwnd: HWND, msg: u32, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        match msg {
+            WM_CREATE => {
+                self.hwnd.set_display_affinity(WDA_EXCLUDEFROMCAPTURE);
+                self.hwnd.set_layered_alpha(0);
+                Some(LRESULT(0))
+            }
+            WM_NCHITTEST => Some(LRESULT(HTTRANSPARENT as _)),
+            _ => None,
+        }
+    }
+}
@@ -1,274 +1,507 @@
-use anyhow::Result;
-use windows::{
-    core::{s, w},
-    Win32::{
-        Foundation::{COLORREF, HWND, LPARAM, LRESULT, RECT, WPARAM},
-        Graphics::Gdi::{
-            CreateSolidBrush, DrawTextA, SetBkMode, SetTextColor, DT_SINGLELINE, DT_VCENTER,
-            HBRUSH, HDC, TRANSPARENT,
-        },
-        UI::{
-            Controls::{CDDS_PREPAINT, CDRF_SKIPDEFAULT, NMCUSTOMDRAW, NM_CUSTOMDRAW},
-            Input::KeyboardAndMouse::VK_ESCAPE,
-            WindowsAndMessaging::*,
-        },
-    },
-};
-
-use crate::{cast, gui::hwnd::Hwnd};
-
-use super::{
-    control::Builder,
-    scroll::ScrollBar,
-    utils::{self, Rect},
-    window::{wndproc, Window},
-};
-
-pub struct Menu {
-    hwnd: HWND,
-    parent: HWND,
-    hittest: HitTest,
-    scrollbar: ScrollBar,
-    bg: HBRUSH,
-    visible: bool,
-}
-
-impl Menu {
-    pub fn create<'a>(parent: HWND) -> Result<&'a mut Self> {
-        unsafe {
-            let brush = CreateSolidBrush(COLORREF(0x171717));
-
-            utils::register_window_class(
-                CS_HREDRAW | CS_VREDRAW,
-                Some(wndproc::<Self>),
-                None,
-                None,
-                Some(brush),
-                s!("Menu"),
-            )?;
-
-            let hwnd = utils::create_window(
-                WINDOW_EX_STYLE::default(),
-                s!("Menu"),
-                s!("Menu"),
-                WS_CLIPCHILDREN | WS_POPUP | WS_VSCROLL,
-                0,
-                0,
-                0,
-                0,
-                parent,
-                None,
-                None,
-            )?;
-
-            if let Some(mut this) = std::ptr::NonNull::new(hwnd.user_data() as *mut Self) {
-                Ok(this.as_mut())
-            } else {
-                anyhow::bail!(windows::core::Error::from_win32())
-            }
-        }
-    }
-
-    pub fn adjust_rect(&mut self, x: i32, y: i32, _width: i32, height: i32) {
-        self.hwnd.set_pos(x, y, 168, height, SWP_NOZORDER);
-    }
-
-    pub fn get_builder(&mut self) -> Result<Builder> {
-        Builder::new(self.hwnd)
-    }
-
-    pub fn build(&mut self, builder: Builder) {
-        let (_width, height) = builder.size();
-        let (_cx, cy) = self.hwnd.client_size();
-        self.scrollbar.init(height as _, cy);
-    }
-
-    fn on_create(&mut self, _wp: WPARAM, _lp: LPARAM) -> Result<()> {
-        self.hwnd.set_display_affinity(WDA_EXCLUDEFROMCAPTURE);
-        self.hwnd.set_theme(w!("DarkMode_Explorer"));
-
-        self.hwnd.set_timer(0x02, 100);
-
-        Ok(())
-    }
-
-    fn on_window_pos_changed(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
-        let WINDOWPOS {
-            x,
-            y,
-            cx: width,
-            cy: height,
-            ..
-        } = *cast!(lp.0, WINDOWPOS);
-
-        self.hittest.update(x, y, width, height);
-        self.scrollbar.set_page_size(height as _);
-
-        Some(LRESULT(0))
-    }
-
-    fn on_nc_hit_test(&mut self, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
-        if self.hwnd.def_proc(WM_NCHITTEST, wp, lp) == LRESULT(HTVSCROLL as _) {
-            Some(LRESULT(HTVSCROLL as _))
-        } else {
-            Some(LRESULT(HTTRANSPARENT as _))
-        }
-    }
-
-    fn on_close(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
-        self.hwnd.parent().destroy();
-        Some(LRESULT(0))
-    }
-
-    fn on_static(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
-        unsafe {
-            let hdc = HDC(wp.0 as _);
-            SetTextColor(hdc, COLORREF(0xf0f0f0));
-            SetBkMode(hdc, TRANSPARENT);
-            Some(LRESULT(self.bg.0 as _))
-        }
-    }
-
-    fn on_notify(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
-        let nmc = cast!(lp.0, NMCUSTOMDRAW);
-
-        if nmc.hdr.code == NM_CUSTOMDRAW
-            && nmc.dwDrawStage == CDDS_PREPAINT
-            && nmc.dwItemSpec >= 0x0100
-        // not slider
-        {
-            let mut text = nmc.hdr.hwndFrom.text();
-            if !text.is_empty() {
-                unsafe {
-                    SetBkMode(nmc.hdc, TRANSPARENT);
-                    SetTextColor(nmc.hdc, COLORREF(0xf0f0f0));
-
-                    nmc.rc.left += 17;
-
-                    DrawTextA(
-                        nmc.hdc,
-                        text.as_bytes_mut(),
-                        &mut nmc.rc,
-                        DT_VCENTER | DT_SINGLELINE,
-                    );
-                }
-                return Some(LRESULT(CDRF_SKIPDEFAULT as _));
-            }
-        }
-        None
-    }
-
-    fn on_vscroll(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
-        self.scrollbar.on_scroll(wp);
-        Some(LRESULT(0))
-    }
-
-    fn on_mouse_wheel(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
-        self.scrollbar.on_wheel(wp);
-        Some(LRESULT(0))
-    }
-
-    fn on_timer(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
-        let (x, y) = utils::cursor_pos();
-
-        if self.visible && !self.hittest.on_window(x, y) {
-            self.show(false);
-        } else if !self.visible && self.hittest.on_toggle(x, y) {
-            self.show(true);
-        }
-
-        Some(LRESULT(0))
-    }
-
-    fn on_show(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
-        self.visible = wp == WPARAM(1);
-        Some(LRESULT(0))
-    }
-
-    fn show(&self, show: bool) {
-        self.hwnd.show(if show { SW_SHOW } else { SW_HIDE });
-    }
-}
-
-impl Window for Menu {
-    fn new(hwnd: HWND, _cs: &mut CREATESTRUCTA) -> Result<Box<Self>> {
-        let parent = hwnd.parent();
-        let hittest = HitTest::new(hwnd);
-        let scrollbar = ScrollBar::new_vert(hwnd);
-        let bg = unsafe { CreateSolidBrush(COLORREF(0x171717)) };
-
-        Ok(Box::new(Self {
-            parent,
-            hwnd,
-            hittest,
-            scrollbar,
-            bg,
-            visible: false,
-        }))
-    }
-
-    #[allow(unused_variables)]
-    fn wndproc(
-        &mut self,
-        hwnd: HWND,
-        msg: u32,
-        wp: windows::Win32::Foundation::WPARAM,
-        lp: windows::Win32::Foundation::LPARAM,
-    ) -> Option<windows::Win32::Foundation::LRESULT> {
-        match msg {
-            WM_CREATE => match self.on_create(wp, lp) {
-                Ok(_) => Some(LRESULT::default()),
-                Err(e) => {
-                    println!("{e:?}");
-                    Some(LRESULT(-1))
-                }
-            },
-            WM_KEYDOWN if wp.0 == VK_ESCAPE.0 as usize => self.on_close(wp, lp),
-            WM_WINDOWPOSCHANGED => self.on_window_pos_changed(wp, lp),
-            WM_NCHITTEST => self.on_nc_hit_test(wp, lp),
-            WM_NOTIFY => self.on_notify(wp, lp),
-            WM_MOUSEWHEEL => self.on_mouse_wheel(wp, lp),
-            WM_CTLCOLORSTATIC => self.on_static(wp, lp),
-            WM_SHOWWINDOW => self.on_show(wp, lp),
-            WM_TIMER => self.on_timer(wp, lp),
-            WM_COMMAND | WM_HSCROLL => Some(self.parent.send_message(msg, wp, lp)),
-            WM_VSCROLL => self.on_vscroll(wp, lp),
-            _ => None,
-        }
-    }
-}
-
-struct HitTest {
-    window: RECT,
-    toggle: RECT,
-}
-
-impl HitTest {
-    fn new(hwnd: HWND) -> Self {
-        let window = hwnd.rect();
-
-        let toggle = RECT {
-            right: window.left + 32,
-            ..window
-        };
-
-        Self { window, toggle }
-    }
-
-    fn update(&mut self, x: i32, y: i32, width: i32, height: i32) {
-        self.window = RECT::new(x, y, width, height);
-
-        self.toggle = RECT {
-            right: self.window.left + 32,
-            ..self.window
-        };
-    }
-
-    fn on_window(&self, x: i32, y: i32) -> bool {
-        self.window.is_in(x, y)
-    }
-
-    fn on_toggle(&self, x: i32, y: i32) -> bool {
-        self.toggle.is_in(x, y)
-    }
-}
+use anyhow::Result;
+use windows::{
+    core::{s, w},
+    Win32::{
+        Foundation::{COLORREF, HWND, LPARAM, LRESULT, RECT, WPARAM},
+        Graphics::Gdi::{
+            CreateSolidBrush, DeleteObject, DrawTextW, SetBkColor, SetBkMode, SetTextColor,
+            DT_SINGLELINE, DT_VCENTER, HBRUSH, HDC, HFONT, TRANSPARENT,
+        },
+        UI::{
+            Controls::{CDDS_PREPAINT, CDRF_SKIPDEFAULT, NMCUSTOMDRAW, NM_CUSTOMDRAW},
+            Input::KeyboardAndMouse::{
+                GetCapture, VIRTUAL_KEY, VK_DOWN, VK_ESCAPE, VK_RETURN, VK_SHIFT, VK_TAB, VK_UP,
+            },
+            WindowsAndMessaging::*,
+        },
+    },
+};
+
+use crate::{
+    cast,
+    gui::hwnd::{CheckBox, ComboBox, Hwnd, Trackbar},
+};
+
+use super::{
+    control::{create_font, Builder},
+    hint::MenuHint,
+    scroll::ScrollBar,
+    utils::{self, scale, Rect},
+    window::{wndproc, Window},
+};
+
+pub const WM_MENU_SHOWN: u32 = WM_APP + 1;
+// a key pressed in one of the menu's controls, handled when the result is 1
+const WM_MENU_KEY: u32 = WM_APP + 3;
+
+// posted to the viewer by the ddc/ci worker with the brightness and contrast percentages in
+// wparam and lparam, -1 where the monitor doesn't report one
+pub const WM_MONITOR_READINGS: u32 = WM_APP + 5;
+
+// posted to the viewer by the video player with the playback position in thousandths of the
+// duration in wparam
+pub const WM_VIDEO_POSITION: u32 = WM_APP + 6;
+
+const MENU_WIDTH: i32 = 168;
+const DEFAULT_ZONE: i32 = 32;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuEdge {
+    Left,
+    Right,
+    Top,
+}
+
+pub struct Menu {
+    hwnd: HWND,
+    parent: HWND,
+    hittest: HitTest,
+    hint: &'static mut MenuHint,
+    scrollbar: ScrollBar,
+    bg: HBRUSH,
+    visible: bool,
+    // opened with the keyboard, stays open until the mouse has been over it
+    keyboard: bool,
+    edge: MenuEdge,
+    zone: i32,
+    parent_rect: RECT,
+    dpi: u32,
+    // shared by every control, made for font_dpi when the menu is built
+    font: HFONT,
+    font_dpi: u32,
+}
+
+impl Menu {
+    pub fn create<'a>(parent: HWND) -> Result<&'a mut Self> {
+        unsafe {
+            let brush = CreateSolidBrush(COLORREF(0x171717));
+
+            utils::register_window_class(
+                CS_HREDRAW | CS_VREDRAW,
+                Some(wndproc::<Self>),
+                None,
+                None,
+                Some(brush),
+                s!("Menu"),
+            )?;
+
+            let hwnd = utils::create_window(
+                WINDOW_EX_STYLE::default(),
+                s!("Menu"),
+                s!("Menu"),
+                WS_CLIPCHILDREN | WS_POPUP | WS_VSCROLL,
+                0,
+                0,
+                0,
+                0,
+                parent,
+                None,
+                None,
+            )?;
+
+            if let Some(mut this) = std::ptr::NonNull::new(hwnd.user_data() as *mut Self) {
+                Ok(this.as_mut())
+            } else {
+                anyhow::bail!(windows::core::Error::from_win32())
+            }
+        }
+    }
+
+    pub fn adjust_rect(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        self.parent_rect = RECT::new(x, y, width, height);
+        self.hittest
+            .update_toggle(&self.parent_rect, self.edge, scale(self.zone, self.dpi));
+        self.hint
+            .adjust_rect(self.edge, x, y, width, height, self.dpi);
+
+        let menu_width = scale(MENU_WIDTH, self.dpi);
+        let x = match self.edge {
+            MenuEdge::Right => x + width - menu_width,
+            MenuEdge::Left | MenuEdge::Top => x,
+        };
+        self.hwnd.set_pos(x, y, menu_width, height, SWP_NOZORDER);
+    }
+
+    // the controls are built again by the caller at the new size
+    pub fn set_dpi(&mut self, dpi: u32) {
+        if dpi != self.dpi {
+            self.dpi = dpi;
+
+            let (width, height) = self.parent_rect.size();
+            self.adjust_rect(self.parent_rect.left, self.parent_rect.top, width, height);
+        }
+    }
+
+    pub fn set_activation(&mut self, edge: MenuEdge, zone: i32) {
+        if (edge, zone) != (self.edge, self.zone) {
+            self.edge = edge;
+            self.zone = zone;
+
+            let (width, height) = self.parent_rect.size();
+            self.adjust_rect(self.parent_rect.left, self.parent_rect.top, width, height);
+        }
+    }
+
+    // shows the menu and focuses its first control
+    pub fn open_with_keyboard(&mut self) {
+        self.keyboard = true;
+        self.show(true);
+        self.move_focus(HWND::default(), false);
+    }
+
+    pub fn set_hint(&mut self, strength: f32) {
+        self.hint.set_strength(strength);
+    }
+
+    pub fn set_number(&mut self, id: u32, val: i32) {
+        self.hwnd.dlg_item(id).set_text(&val.to_string());
+    }
+
+    // a slider the user is dragging keeps its thumb
+    pub fn set_slider(&mut self, id: u32, val: i32) {
+        let slider = self.hwnd.dlg_item(id);
+        if unsafe { GetCapture() } != slider {
+            slider.trackbar_set_pos(val);
+        }
+    }
+
+    pub fn set_check(&mut self, id: u32, checked: bool) {
+        self.hwnd.dlg_item(id).checkbox_set_check(checked);
+    }
+
+    // drops every control so the menu can be built again, and with them the font when it was made
+    // for another dpi
+    pub fn clear(&mut self) {
+        loop {
+            let child = self.hwnd.window(GW_CHILD);
+            if child.is_invalid() {
+                break;
+            }
+            child.destroy();
+        }
+
+        if self.font_dpi != self.dpi {
+            self.delete_font();
+        }
+    }
+
+    pub fn get_builder(&mut self) -> Result<Builder> {
+        if self.font.is_invalid() {
+            self.font = create_font(self.dpi)?;
+            self.font_dpi = self.dpi;
+        }
+
+        Ok(Builder::new(self.hwnd, self.dpi, self.font))
+    }
+
+    fn delete_font(&mut self) {
+        if !self.font.is_invalid() {
+            unsafe {
+                _ = DeleteObject(self.font);
+            }
+            self.font = HFONT::default();
+        }
+    }
+
+    pub fn build(&mut self, builder: Builder) {
+        let (_width, height) = builder.size();
+        let (_cx, cy) = self.hwnd.client_size();
+        self.scrollbar.init(height as _, cy);
+    }
+
+    // the controls are destroyed before this, so nothing uses the font any more
+    fn on_nc_destroy(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        self.delete_font();
+        None
+    }
+
+    fn on_create(&mut self, _wp: WPARAM, _lp: LPARAM) -> Result<()> {
+        self.hwnd.set_display_affinity(WDA_EXCLUDEFROMCAPTURE);
+        self.hwnd.set_theme(w!("DarkMode_Explorer"));
+
+        self.hwnd.set_timer(0x02, 100);
+
+        Ok(())
+    }
+
+    fn on_window_pos_changed(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        let WINDOWPOS {
+            x,
+            y,
+            cx: width,
+            cy: height,
+            ..
+        } = *cast!(lp.0, WINDOWPOS);
+
+        self.hittest.update(x, y, width, height);
+        self.scrollbar.set_page_size(height as _);
+
+        Some(LRESULT(0))
+    }
+
+    fn on_nc_hit_test(&mut self, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        if self.hwnd.def_proc(WM_NCHITTEST, wp, lp) == LRESULT(HTVSCROLL as _) {
+            Some(LRESULT(HTVSCROLL as _))
+        } else {
+            Some(LRESULT(HTTRANSPARENT as _))
+        }
+    }
+
+    fn on_close(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        self.hwnd.parent().destroy();
+        Some(LRESULT(0))
+    }
+
+    fn on_static(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        unsafe {
+            let hdc = HDC(wp.0 as _);
+            SetTextColor(hdc, COLORREF(0xf0f0f0));
+            SetBkMode(hdc, TRANSPARENT);
+            Some(LRESULT(self.bg.0 as _))
+        }
+    }
+
+    fn on_edit(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        unsafe {
+            let hdc = HDC(wp.0 as _);
+            SetTextColor(hdc, COLORREF(0xf0f0f0));
+            SetBkColor(hdc, COLORREF(0x171717));
+            Some(LRESULT(self.bg.0 as _))
+        }
+    }
+
+    fn on_notify(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        let nmc = cast!(lp.0, NMCUSTOMDRAW);
+
+        if nmc.hdr.code == NM_CUSTOMDRAW
+            && nmc.dwDrawStage == CDDS_PREPAINT
+            && nmc.dwItemSpec >= 0x0100
+        // not slider
+        {
+            let mut text: Vec<u16> = nmc.hdr.hwndFrom.text().encode_utf16().collect();
+            if !text.is_empty() {
+                unsafe {
+                    SetBkMode(nmc.hdc, TRANSPARENT);
+                    SetTextColor(nmc.hdc, COLORREF(0xf0f0f0));
+
+                    nmc.rc.left += scale(17, self.dpi);
+
+                    DrawTextW(nmc.hdc, &mut text, &mut nmc.rc, DT_VCENTER | DT_SINGLELINE);
+                }
+                return Some(LRESULT(CDRF_SKIPDEFAULT as _));
+            }
+        }
+        None
+    }
+
+    fn on_vscroll(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        self.scrollbar.on_scroll(wp);
+        Some(LRESULT(0))
+    }
+
+    fn on_mouse_wheel(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        self.scrollbar.on_wheel(wp);
+        Some(LRESULT(0))
+    }
+
+    fn on_key_down(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        // an open combo box list takes every key
+        let focus = utils::focus();
+        if focus.combobox_dropped() {
+            return Some(LRESULT(0));
+        }
+
+        let handled = match VIRTUAL_KEY(wp.0 as _) {
+            VK_ESCAPE => {
+                self.show(false);
+                self.parent.set_focus();
+                true
+            }
+            VK_TAB => {
+                self.move_focus(focus, utils::key_down(VK_SHIFT));
+                true
+            }
+            // fields apply their text when they lose focus, enter applies it in place
+            VK_RETURN if focus.is_edit() => {
+                let wp = WPARAM((EN_KILLFOCUS as usize) << 16 | focus.control_id() as usize);
+                self.parent
+                    .send_message(WM_COMMAND, wp, LPARAM(focus.0 as _));
+                true
+            }
+            // the sliders keep left and right
+            key @ (VK_UP | VK_DOWN) => {
+                self.move_focus(focus, key == VK_UP);
+                true
+            }
+            _ => false,
+        };
+
+        Some(LRESULT(handled as _))
+    }
+
+    fn move_focus(&mut self, from: HWND, previous: bool) {
+        let next = self.hwnd.next_tab_item(from, previous);
+        if next.is_invalid() {
+            return;
+        }
+
+        next.set_focus();
+
+        let top = self.hwnd.client_rect_on_screen().top;
+        let rect = next.rect();
+        self.scrollbar.show_range(rect.top - top, rect.bottom - top);
+    }
+
+    fn on_timer(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        let (x, y) = utils::cursor_pos();
+
+        if self.hittest.on_window(x, y) {
+            self.keyboard = false;
+        }
+
+        if self.visible && !self.keyboard && !self.hittest.on_window(x, y) {
+            self.show(false);
+        } else if !self.visible && self.hittest.on_toggle(x, y) {
+            self.show(true);
+        }
+
+        let hovered = self.hittest.on_toggle(x, y);
+        self.hint.update(self.visible, hovered);
+
+        Some(LRESULT(0))
+    }
+
+    fn on_show(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        self.visible = wp == WPARAM(1);
+        if !self.visible {
+            self.keyboard = false;
+        }
+        Some(LRESULT(0))
+    }
+
+    fn show(&self, show: bool) {
+        self.hwnd.show(if show { SW_SHOW } else { SW_HIDE });
+
+        if show {
+            self.parent
+                .post_message(WM_MENU_SHOWN, WPARAM::default(), LPARAM::default());
+        }
+    }
+}
+
+impl Window for Menu {
+    fn new(hwnd: HWND, _cs: &mut CREATESTRUCTA) -> Result<Box<Self>> {
+        let parent = hwnd.parent();
+        let hittest = HitTest::new(hwnd);
+        let hint = MenuHint::create(parent)?;
+        let scrollbar = ScrollBar::new_vert(hwnd);
+        let bg = unsafe { CreateSolidBrush(COLORREF(0x171717)) };
+
+        Ok(Box::new(Self {
+            parent,
+            hwnd,
+            hittest,
+            hint,
+            scrollbar,
+            bg,
+            visible: false,
+            keyboard: false,
+            edge: MenuEdge::Left,
+            zone: DEFAULT_ZONE,
+            parent_rect: RECT::default(),
+            dpi: parent.dpi(),
+            font: HFONT::default(),
+            font_dpi: 0,
+        }))
+    }
+
+    #[allow(unused_variables)]
+    fn wndproc(
+        &mut self,
+        hwnd: HWND,
+        msg: u32,
+        wp: windows::Win32::Foundation::WPARAM,
+        lp: windows::Win32::Foundation::LPARAM,
+    ) -> Option<windows::Win32::Foundation::LRESULT> {
+        match msg {
+            WM_CREATE => match self.on_create(wp, lp) {
+                Ok(_) => Some(LRESULT::default()),
+                Err(e) => {
+                    println!("{e:?}");
+                    Some(LRESULT(-1))
+                }
+            },
+            WM_KEYDOWN | WM_MENU_KEY => self.on_key_down(wp, lp),
+            WM_CLOSE => self.on_close(wp, lp),
+            WM_WINDOWPOSCHANGED => self.on_window_pos_changed(wp, lp),
+            WM_NCHITTEST => self.on_nc_hit_test(wp, lp),
+            WM_NOTIFY => self.on_notify(wp, lp),
+            WM_MOUSEWHEEL => self.on_mouse_wheel(wp, lp),
+            WM_CTLCOLORSTATIC => self.on_static(wp, lp),
+            WM_CTLCOLOREDIT => self.on_edit(wp, lp),
+            WM_SHOWWINDOW => self.on_show(wp, lp),
+            WM_TIMER => self.on_timer(wp, lp),
+            WM_COMMAND | WM_HSCROLL => Some(self.parent.send_message(msg, wp, lp)),
+            WM_VSCROLL => self.on_vscroll(wp, lp),
+            WM_NCDESTROY => self.on_nc_destroy(wp, lp),
+            _ => None,
+        }
+    }
+}
+
+// sends navigation keys pressed in a control to the menu it sits in, true when the menu used it
+pub fn translate_key(msg: &MSG) -> bool {
+    if msg.message != WM_KEYDOWN {
+        return false;
+    }
+
+    let root = msg.hwnd.root();
+    root != msg.hwnd && root.send_message(WM_MENU_KEY, msg.wParam, msg.lParam) == LRESULT(1)
+}
+
+struct HitTest {
+    window: RECT,
+    toggle: RECT,
+}
+
+impl HitTest {
+    fn new(hwnd: HWND) -> Self {
+        let window = hwnd.rect();
+        let toggle = Self::toggle_rect(&window, MenuEdge::Left, DEFAULT_ZONE);
+
+        Self { window, toggle }
+    }
+
+    fn update(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        self.window = RECT::new(x, y, width, height);
+    }
+
+    fn update_toggle(&mut self, parent: &RECT, edge: MenuEdge, zone: i32) {
+        self.toggle = Self::toggle_rect(parent, edge, zone);
+    }
+
+    fn toggle_rect(parent: &RECT, edge: MenuEdge, zone: i32) -> RECT {
+        match edge {
+            MenuEdge::Left => RECT {
+                right: parent.left + zone,
+                ..*parent
+            },
+            MenuEdge::Right => RECT {
+                left: parent.right - zone,
+                ..*parent
+            },
+            MenuEdge::Top => RECT {
+                bottom: parent.top + zone,
+                ..*parent
+            },
+        }
+    }
+
+    fn on_window(&self, x: i32, y: i32) -> bool {
+        self.window.is_in(x, y)
+    }
+
+    fn on_toggle(&self, x: i32, y: i32) -> bool {
+        self.toggle.is_in(x, y)
+    }
+}
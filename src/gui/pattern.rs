@@ -0,0 +1,171 @@
+use anyhow::Result;
+use windows::{
+    core::s,
+    Win32::{
+        Foundation::{COLORREF, HWND, LPARAM, LRESULT, RECT, TRUE, WPARAM},
+        Graphics::Gdi::{CreateSolidBrush, DeleteObject, FillRect, InvalidateRect, HDC},
+        UI::WindowsAndMessaging::*,
+    },
+};
+
+use super::{
+    hwnd::Hwnd,
+    utils::{self, Rect as _},
+    window::{wndproc, Window},
+};
+
+// posted to the viewer with a fill packed by Fill::message
+pub const WM_SHOW_PATTERN: u32 = WM_APP + 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fill {
+    Solid(COLORREF),
+    // black to white, left to right
+    Ramp,
+}
+
+impl Fill {
+    // the colorref goes in wparam, lparam tells hidden, solid and ramp apart
+    pub fn message(fill: Option<Fill>) -> (WPARAM, LPARAM) {
+        match fill {
+            None => (WPARAM(0), LPARAM(0)),
+            Some(Fill::Solid(color)) => (WPARAM(color.0 as _), LPARAM(1)),
+            Some(Fill::Ramp) => (WPARAM(0), LPARAM(2)),
+        }
+    }
+
+    pub fn from_message(wp: WPARAM, lp: LPARAM) -> Option<Fill> {
+        match lp.0 {
+            1 => Some(Fill::Solid(COLORREF(wp.0 as _))),
+            2 => Some(Fill::Ramp),
+            _ => None,
+        }
+    }
+}
+
+// the patch sits in the middle of the viewer, a third of its smaller side
+pub fn patch_rect(rect: RECT) -> RECT {
+    let (width, height) = rect.size();
+    let side = width.min(height) / 3;
+
+    RECT::new(
+        rect.left + (width - side) / 2,
+        rect.top + (height - side) / 2,
+        side,
+        side,
+    )
+}
+
+// a known patch for the capture self-check, unlike the viewer it is captured
+pub struct PatternWindow {
+    hwnd: HWND,
+    fill: Option<Fill>,
+}
+
+impl PatternWindow {
+    pub fn create<'a>(parent: HWND) -> Result<&'a mut Self> {
+        unsafe {
+            utils::register_window_class(
+                CS_HREDRAW | CS_VREDRAW,
+                Some(wndproc::<Self>),
+                None,
+                None,
+                None,
+                s!("PatternWindow"),
+            )?;
+
+            let hwnd = utils::create_window(
+                WS_EX_TRANSPARENT | WS_EX_NOACTIVATE | WS_EX_TOOLWINDOW,
+                s!("PatternWindow"),
+                s!("PatternWindow"),
+                WS_POPUP,
+                0,
+                0,
+                0,
+                0,
+                parent,
+                None,
+                None,
+            )?;
+
+            if let Some(mut this) = std::ptr::NonNull::new(hwnd.user_data() as *mut Self) {
+                Ok(this.as_mut())
+            } else {
+                anyhow::bail!(windows::core::Error::from_win32())
+            }
+        }
+    }
+
+    pub fn adjust_rect(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        let rect = patch_rect(RECT::new(x, y, width, height));
+        self.hwnd.set_pos(
+            rect.left,
+            rect.top,
+            rect.width(),
+            rect.height(),
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+    }
+
+    pub fn show(&mut self, fill: Option<Fill>) {
+        self.fill = fill;
+
+        if fill.is_some() {
+            unsafe {
+                _ = InvalidateRect(self.hwnd, None, TRUE);
+            }
+            self.hwnd.show(SW_SHOWNOACTIVATE);
+        } else {
+            self.hwnd.show(SW_HIDE);
+        }
+    }
+
+    fn on_erase_background(&mut self, wp: WPARAM) -> Option<LRESULT> {
+        let fill = self.fill?;
+        let hdc = HDC(wp.0 as _);
+        let (width, height) = self.hwnd.client_size();
+        let (width, height) = (width as i32, height as i32);
+
+        let fill_rect = |rect: RECT, color: COLORREF| unsafe {
+            let brush = CreateSolidBrush(color);
+            FillRect(hdc, &rect, brush);
+            _ = DeleteObject(brush);
+        };
+
+        match fill {
+            Fill::Solid(color) => fill_rect(RECT::new(0, 0, width, height), color),
+            Fill::Ramp => {
+                // one rect per run of columns with the same 8 bit gray
+                let last = (width - 1).max(1);
+                let gray = |x: i32| (255 * x + last / 2) / last;
+
+                let mut start = 0;
+                for x in 1..=width {
+                    if x == width || gray(x) != gray(start) {
+                        let value = gray(start) as u32;
+                        let color = COLORREF(value | value << 8 | value << 16);
+                        fill_rect(RECT::new(start, 0, x - start, height), color);
+                        start = x;
+                    }
+                }
+            }
+        }
+
+        Some(LRESULT(1))
+    }
+}
+
+impl Window for PatternWindow {
+    fn new(hwnd: HWND, _cs: &mut CREATESTRUCTA) -> Result<Box<Self>> {
+        Ok(Box::new(Self { hwnd, fill: None }))
+    }
+
+    #[allow(unused_variables)]
+    fn wndproc(&mut self, hwnd: HWND, msg: u32, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        match msg {
+            WM_ERASEBKGND => self.on_erase_background(wp),
+            WM_NCHITTEST => Some(LRESULT(HTTRANSPARENT as _)),
+            _ => None,
+        }
+    }
+}
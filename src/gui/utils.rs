@@ -1,238 +1,357 @@
-#![allow(unused, non_snake_case)]
-
-use std::mem::size_of;
-
-use anyhow::{Error as E, Result};
-use windows::{
-    core::{Param, PCSTR},
-    Win32::{
-        Foundation::*,
-        Graphics::{
-            Dwm::{DwmGetWindowAttribute, DWMWA_CAPTION_BUTTON_BOUNDS},
-            Gdi::{COLOR_WINDOW, HBRUSH},
-        },
-        System::LibraryLoader::GetModuleHandleA,
-        UI::WindowsAndMessaging::*,
-    },
-};
-
-#[macro_export]
-macro_rules! LOWORD {
-    ($dw:expr) => {
-        ($dw & 0xffff) as u32
-    };
-}
-
-#[macro_export]
-macro_rules! HIWORD {
-    ($dw:expr) => {
-        (($dw >> 16) & 0xffff) as u32
-    };
-}
-
-#[macro_export]
-macro_rules! GET_X_LPARAM {
-    ($lp:ident) => {
-        (($lp.0 & 0xffff) as i16) as i32
-    };
-}
-
-#[macro_export]
-macro_rules! GET_Y_LPARAM {
-    ($lp:ident) => {
-        ((($lp.0 >> 16) & 0xffff) as i16) as i32
-    };
-}
-
-#[macro_export]
-macro_rules! GET_WHEEL_DELTA_WPARAM {
-    ($wp:ident) => {
-        ((($wp.0 >> 16) & 0xffff) as i16) as i32
-    };
-}
-
-#[macro_export]
-macro_rules! cast {
-    ($src:expr, $type:ty) => {
-        unsafe { ($src as *mut $type).as_mut().unwrap() }
-    };
-}
-
-pub trait Word: Sized {
-    fn dw(self) -> u64;
-
-    fn lo(self) -> u32 {
-        LOWORD!(self.dw())
-    }
-
-    fn hi(self) -> u32 {
-        HIWORD!(self.dw())
-    }
-}
-
-impl Word for WPARAM {
-    fn dw(self) -> u64 {
-        self.0 as _
-    }
-}
-
-impl Word for LPARAM {
-    fn dw(self) -> u64 {
-        self.0 as _
-    }
-}
-
-pub trait Rect {
-    fn new(x: i32, y: i32, width: i32, height: i32) -> Self;
-    fn inner(&self, x: i32, y: i32) -> Self;
-    fn width(&self) -> i32;
-    fn height(&self) -> i32;
-    fn size(&self) -> (i32, i32);
-    fn usize(&self) -> (u32, u32);
-    fn is_in(&self, x: i32, y: i32) -> bool;
-}
-
-impl Rect for RECT {
-    fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
-        Self {
-            left: x,
-            top: y,
-            right: x + width,
-            bottom: y + height,
-        }
-    }
-
-    fn inner(&self, width: i32, height: i32) -> Self {
-        Self {
-            left: self.left + width,
-            top: self.top + height,
-            right: self.right - width,
-            bottom: self.bottom - height,
-        }
-    }
-
-    fn width(&self) -> i32 {
-        self.right - self.left
-    }
-
-    fn height(&self) -> i32 {
-        self.bottom - self.top
-    }
-
-    fn size(&self) -> (i32, i32) {
-        (self.width(), self.height())
-    }
-
-    fn usize(&self) -> (u32, u32) {
-        (self.width() as u32, self.height() as u32)
-    }
-
-    fn is_in(&self, x: i32, y: i32) -> bool {
-        self.left <= x && x < self.right && self.top <= y && y < self.bottom
-    }
-}
-
-pub fn module_handle() -> HINSTANCE {
-    unsafe { HINSTANCE(GetModuleHandleA(None).expect("failed GetModuleHandleA").0) }
-}
-
-pub fn register_window_class(
-    style: WNDCLASS_STYLES,
-    proc: WNDPROC,
-    icon: Option<HICON>,
-    cursor: Option<HCURSOR>,
-    bg: Option<HBRUSH>,
-    name: PCSTR,
-) -> Result<()> {
-    unsafe {
-        let wc = WNDCLASSA {
-            style: CS_HREDRAW | CS_VREDRAW,
-            lpfnWndProc: proc,
-            hInstance: module_handle(),
-            hIcon: icon.unwrap_or_else(|| {
-                LoadIconW(None, IDI_APPLICATION).expect("failed to get default app icon")
-            }),
-            hCursor: cursor.unwrap_or_else(|| {
-                LoadCursorW(None, IDC_ARROW).expect("failed to get default cursor.")
-            }),
-            hbrBackground: bg.unwrap_or(HBRUSH((COLOR_WINDOW.0 as isize + 1) as _)),
-            lpszClassName: name,
-            ..Default::default()
-        };
-        if RegisterClassA(&wc) == 0 {
-            anyhow::bail!(windows::core::Error::from_win32())
-        } else {
-            Ok(())
-        }
-    }
-}
-
-#[allow(clippy::too_many_arguments)]
-pub fn create_window<P0: Param<HWND>, P1: Param<HMENU>>(
-    ex_style: WINDOW_EX_STYLE,
-    class_name: PCSTR,
-    window_name: PCSTR,
-    style: WINDOW_STYLE,
-    x: i32,
-    y: i32,
-    width: i32,
-    height: i32,
-    parent: P0,
-    menu: P1,
-    param: Option<*const std::ffi::c_void>,
-) -> Result<HWND> {
-    unsafe {
-        CreateWindowExA(
-            ex_style,
-            class_name,
-            window_name,
-            style,
-            x,
-            y,
-            width,
-            height,
-            parent,
-            menu,
-            module_handle(),
-            param,
-        )
-        .map_err(E::msg)
-    }
-}
-
-pub fn adjust_window_rect(
-    ex_style: WINDOW_EX_STYLE,
-    style: WINDOW_STYLE,
-    width: u32,
-    height: u32,
-) -> RECT {
-    unsafe {
-        let mut rc = RECT {
-            left: 0,
-            top: 0,
-            right: width as i32,
-            bottom: height as i32,
-        };
-        AdjustWindowRectEx(&mut rc, style, None, ex_style)
-            .expect("failed to adjust window rect ex.");
-        rc
-    }
-}
-
-pub fn cursor_pos() -> (i32, i32) {
-    unsafe {
-        let mut point = POINT::default();
-        GetCursorPos(&mut point);
-        (point.x, point.y)
-    }
-}
-
-pub fn quit(code: i32) {
-    unsafe {
-        PostQuitMessage(code);
-    }
-}
-
-pub fn system_metrics(index: SYSTEM_METRICS_INDEX) -> i32 {
-    unsafe { GetSystemMetrics(index) }
-}
+#![allow(unused, non_snake_case)]
+
+use std::{mem::size_of, path::PathBuf, sync::Mutex};
+
+use anyhow::{Error as E, Result};
+use windows::{
+    core::{w, Param, HSTRING, PCSTR, PCWSTR, PWSTR},
+    Win32::{
+        Foundation::*,
+        Graphics::{
+            Dwm::{DwmGetWindowAttribute, DWMWA_CAPTION_BUTTON_BOUNDS},
+            Gdi::{COLOR_WINDOW, HBRUSH},
+        },
+        System::LibraryLoader::GetModuleHandleA,
+        UI::{
+            Controls::Dialogs::{
+                ChooseColorW, GetOpenFileNameW, CC_FULLOPEN, CC_RGBINIT, CHOOSECOLORW,
+                OFN_FILEMUSTEXIST, OFN_NOCHANGEDIR, OPENFILENAMEW,
+            },
+            HiDpi::AdjustWindowRectExForDpi,
+            Input::KeyboardAndMouse::{GetFocus, GetKeyState, VIRTUAL_KEY},
+            WindowsAndMessaging::*,
+        },
+    },
+};
+
+#[macro_export]
+macro_rules! LOWORD {
+    ($dw:expr) => {
+        ($dw & 0xffff) as u32
+    };
+}
+
+#[macro_export]
+macro_rules! HIWORD {
+    ($dw:expr) => {
+        (($dw >> 16) & 0xffff) as u32
+    };
+}
+
+#[macro_export]
+macro_rules! GET_X_LPARAM {
+    ($lp:ident) => {
+        (($lp.0 & 0xffff) as i16) as i32
+    };
+}
+
+#[macro_export]
+macro_rules! GET_Y_LPARAM {
+    ($lp:ident) => {
+        ((($lp.0 >> 16) & 0xffff) as i16) as i32
+    };
+}
+
+#[macro_export]
+macro_rules! GET_WHEEL_DELTA_WPARAM {
+    ($wp:ident) => {
+        ((($wp.0 >> 16) & 0xffff) as i16) as i32
+    };
+}
+
+#[macro_export]
+macro_rules! cast {
+    ($src:expr, $type:ty) => {
+        unsafe { ($src as *mut $type).as_mut().unwrap() }
+    };
+}
+
+pub trait Word: Sized {
+    fn dw(self) -> u64;
+
+    fn lo(self) -> u32 {
+        LOWORD!(self.dw())
+    }
+
+    fn hi(self) -> u32 {
+        HIWORD!(self.dw())
+    }
+}
+
+impl Word for WPARAM {
+    fn dw(self) -> u64 {
+        self.0 as _
+    }
+}
+
+impl Word for LPARAM {
+    fn dw(self) -> u64 {
+        self.0 as _
+    }
+}
+
+pub trait Rect {
+    fn new(x: i32, y: i32, width: i32, height: i32) -> Self;
+    fn inner(&self, x: i32, y: i32) -> Self;
+    fn width(&self) -> i32;
+    fn height(&self) -> i32;
+    fn size(&self) -> (i32, i32);
+    fn usize(&self) -> (u32, u32);
+    fn is_in(&self, x: i32, y: i32) -> bool;
+}
+
+impl Rect for RECT {
+    fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            left: x,
+            top: y,
+            right: x + width,
+            bottom: y + height,
+        }
+    }
+
+    fn inner(&self, width: i32, height: i32) -> Self {
+        Self {
+            left: self.left + width,
+            top: self.top + height,
+            right: self.right - width,
+            bottom: self.bottom - height,
+        }
+    }
+
+    fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    fn size(&self) -> (i32, i32) {
+        (self.width(), self.height())
+    }
+
+    fn usize(&self) -> (u32, u32) {
+        (self.width() as u32, self.height() as u32)
+    }
+
+    fn is_in(&self, x: i32, y: i32) -> bool {
+        self.left <= x && x < self.right && self.top <= y && y < self.bottom
+    }
+}
+
+pub fn module_handle() -> HINSTANCE {
+    unsafe { HINSTANCE(GetModuleHandleA(None).expect("failed GetModuleHandleA").0) }
+}
+
+pub fn register_window_class(
+    style: WNDCLASS_STYLES,
+    proc: WNDPROC,
+    icon: Option<HICON>,
+    cursor: Option<HCURSOR>,
+    bg: Option<HBRUSH>,
+    name: PCSTR,
+) -> Result<()> {
+    unsafe {
+        let wc = WNDCLASSA {
+            style: CS_HREDRAW | CS_VREDRAW,
+            lpfnWndProc: proc,
+            hInstance: module_handle(),
+            hIcon: icon.unwrap_or_else(|| {
+                LoadIconW(None, IDI_APPLICATION).expect("failed to get default app icon")
+            }),
+            hCursor: cursor.unwrap_or_else(|| {
+                LoadCursorW(None, IDC_ARROW).expect("failed to get default cursor.")
+            }),
+            hbrBackground: bg.unwrap_or(HBRUSH((COLOR_WINDOW.0 as isize + 1) as _)),
+            lpszClassName: name,
+            ..Default::default()
+        };
+        if RegisterClassA(&wc) == 0 {
+            anyhow::bail!(windows::core::Error::from_win32())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_window<P0: Param<HWND>, P1: Param<HMENU>>(
+    ex_style: WINDOW_EX_STYLE,
+    class_name: PCSTR,
+    window_name: PCSTR,
+    style: WINDOW_STYLE,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    parent: P0,
+    menu: P1,
+    param: Option<*const std::ffi::c_void>,
+) -> Result<HWND> {
+    unsafe {
+        CreateWindowExA(
+            ex_style,
+            class_name,
+            window_name,
+            style,
+            x,
+            y,
+            width,
+            height,
+            parent,
+            menu,
+            module_handle(),
+            param,
+        )
+        .map_err(E::msg)
+    }
+}
+
+// like create_window, for controls whose text may be outside the ansi code page
+#[allow(clippy::too_many_arguments)]
+pub fn create_window_w<P0: Param<HWND>, P1: Param<HMENU>>(
+    ex_style: WINDOW_EX_STYLE,
+    class_name: PCWSTR,
+    window_name: PCWSTR,
+    style: WINDOW_STYLE,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    parent: P0,
+    menu: P1,
+    param: Option<*const std::ffi::c_void>,
+) -> Result<HWND> {
+    unsafe {
+        CreateWindowExW(
+            ex_style,
+            class_name,
+            window_name,
+            style,
+            x,
+            y,
+            width,
+            height,
+            parent,
+            menu,
+            module_handle(),
+            param,
+        )
+        .map_err(E::msg)
+    }
+}
+
+pub fn adjust_window_rect(
+    ex_style: WINDOW_EX_STYLE,
+    style: WINDOW_STYLE,
+    width: u32,
+    height: u32,
+    dpi: u32,
+) -> RECT {
+    unsafe {
+        let mut rc = RECT {
+            left: 0,
+            top: 0,
+            right: width as i32,
+            bottom: height as i32,
+        };
+        AdjustWindowRectExForDpi(&mut rc, style, None, ex_style, dpi)
+            .expect("failed to adjust window rect ex.");
+        rc
+    }
+}
+
+// layout sizes are given at 96 dpi
+pub const DEFAULT_DPI: u32 = 96;
+
+pub fn scale(value: i32, dpi: u32) -> i32 {
+    (value * dpi as i32 + DEFAULT_DPI as i32 / 2).div_euclid(DEFAULT_DPI as i32)
+}
+
+pub fn cursor_pos() -> (i32, i32) {
+    unsafe {
+        let mut point = POINT::default();
+        GetCursorPos(&mut point);
+        (point.x, point.y)
+    }
+}
+
+pub fn foreground_window() -> HWND {
+    unsafe { GetForegroundWindow() }
+}
+
+// the window with keyboard focus in this thread
+pub fn focus() -> HWND {
+    unsafe { GetFocus() }
+}
+
+pub fn key_down(key: VIRTUAL_KEY) -> bool {
+    unsafe { GetKeyState(key.0 as _) < 0 }
+}
+
+pub fn quit(code: i32) {
+    unsafe {
+        PostQuitMessage(code);
+    }
+}
+
+// for errors a windows subsystem binary has no console to print
+pub fn error_box(text: &str) {
+    unsafe {
+        MessageBoxW(
+            None,
+            &HSTRING::from(text),
+            w!("colormel"),
+            MB_OK | MB_ICONERROR,
+        );
+    }
+}
+
+pub fn system_metrics(index: SYSTEM_METRICS_INDEX) -> i32 {
+    unsafe { GetSystemMetrics(index) }
+}
+
+// modal open dialog, filter is a description and a pattern like ("Cube LUT", "*.cube")
+pub fn open_file_dialog(owner: HWND, filter: (&str, &str)) -> Option<PathBuf> {
+    let filter: Vec<u16> = format!("{}\0{}\0\0", filter.0, filter.1)
+        .encode_utf16()
+        .collect();
+    let mut file = vec![0u16; 1024];
+
+    let mut ofn = OPENFILENAMEW {
+        lStructSize: size_of::<OPENFILENAMEW>() as _,
+        hwndOwner: owner,
+        lpstrFilter: PCWSTR(filter.as_ptr()),
+        lpstrFile: PWSTR(file.as_mut_ptr()),
+        nMaxFile: file.len() as _,
+        Flags: OFN_FILEMUSTEXIST | OFN_NOCHANGEDIR,
+        ..Default::default()
+    };
+
+    unsafe { GetOpenFileNameW(&mut ofn) }.as_bool().then(|| {
+        let len = file.iter().position(|&c| c == 0).unwrap_or(file.len());
+        PathBuf::from(String::from_utf16_lossy(&file[..len]))
+    })
+}
+
+// the dialog's custom color slots, kept while the app runs
+static CUSTOM_COLORS: Mutex<[COLORREF; 16]> = Mutex::new([COLORREF(0xffffff); 16]);
+
+// 0x00bbggrr in and out
+pub fn choose_color(owner: HWND, initial: u32) -> Option<u32> {
+    let mut custom = CUSTOM_COLORS.lock().unwrap();
+
+    let mut cc = CHOOSECOLORW {
+        lStructSize: size_of::<CHOOSECOLORW>() as _,
+        hwndOwner: owner,
+        rgbResult: COLORREF(initial),
+        lpCustColors: custom.as_mut_ptr(),
+        Flags: CC_RGBINIT | CC_FULLOPEN,
+        ..Default::default()
+    };
+
+    unsafe { ChooseColorW(&mut cc) }
+        .as_bool()
+        .then_some(cc.rgbResult.0)
+}
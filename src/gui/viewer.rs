@@ -1,354 +1,658 @@
-use anyhow::Result;
-use windows::{
-    core::{s, PCSTR, PCWSTR},
-    Win32::{
-        Foundation::{HWND, LPARAM, LRESULT, RECT, TRUE, WPARAM},
-        Graphics::Dwm::{
-            DWMNCRP_ENABLED, DWMWA_NCRENDERING_POLICY, DWMWA_USE_IMMERSIVE_DARK_MODE,
-            DWMWA_WINDOW_CORNER_PREFERENCE, DWMWCP_DONOTROUND,
-        },
-        System::SystemServices::MK_LBUTTON,
-        UI::{Input::KeyboardAndMouse::VK_ESCAPE, WindowsAndMessaging::*},
-    },
-};
-
-use crate::{
-    cast,
-    gui::utils::{self, module_handle},
-    GET_X_LPARAM, GET_Y_LPARAM,
-};
-
-use super::{
-    app::App,
-    hwnd::{CheckBox, Hwnd, Trackbar},
-    menu::Menu,
-    utils::{quit, Rect as _, Word},
-    window::{wndproc, Window},
-};
-
-const EX_STYLE: WINDOW_EX_STYLE =
-    WINDOW_EX_STYLE(WS_EX_NOREDIRECTIONBITMAP.0 | WS_EX_APPWINDOW.0 | WS_EX_TOPMOST.0);
-
-pub struct Viewer<T: App> {
-    app: Option<T>,
-    hwnd: HWND,
-    transparent: bool,
-    hittest: HitTest,
-    menu: &'static mut Menu,
-    mx: i32,
-    my: i32,
-}
-
-impl<T: App> Viewer<T> {
-    pub fn create<'a>() -> Result<&'a mut Self> {
-        unsafe {
-            const CLASS_NAME: PCSTR = s!("Viewer");
-
-            utils::register_window_class(
-                CS_HREDRAW | CS_VREDRAW,
-                Some(wndproc::<Self>),
-                Some(LoadIconW(module_handle(), PCWSTR(1 as _))?),
-                None,
-                None,
-                CLASS_NAME,
-            )?;
-
-            let hwnd = utils::create_window(
-                EX_STYLE,
-                CLASS_NAME,
-                s!("Colormel"),
-                WS_OVERLAPPEDWINDOW,
-                0,
-                0,
-                800,
-                800,
-                None,
-                None,
-                None,
-            )?;
-
-            hwnd.update();
-            hwnd.show(SW_SHOW);
-
-            if let Some(mut this) = std::ptr::NonNull::new(hwnd.user_data() as *mut Self) {
-                Ok(this.as_mut())
-            } else {
-                anyhow::bail!(windows::core::Error::from_win32())
-            }
-        }
-    }
-
-    fn set_transparency(&mut self, transparent: bool) {
-        self.transparent = transparent;
-
-        if transparent {
-            self.hwnd
-                .set_ex_style(EX_STYLE | WS_EX_LAYERED | WS_EX_TRANSPARENT);
-        } else {
-            self.hwnd.set_ex_style(EX_STYLE);
-        }
-    }
-
-    fn on_create(&mut self, _wp: WPARAM, _lp: LPARAM) -> Result<()> {
-        self.hwnd.set_display_affinity(WDA_EXCLUDEFROMCAPTURE);
-
-        let rect = self
-            .app
-            .as_mut()
-            .expect("no app when on_create")
-            .window_rect();
-
-        self.hwnd.set_pos(
-            rect.left,
-            rect.top,
-            rect.width(),
-            rect.height(),
-            SWP_FRAMECHANGED,
-        );
-
-        self.hwnd.dwm_extend_frame(-1);
-        self.hwnd.dwm_enable_blur_behind(true);
-        self.hwnd
-            .dwm_set_attribute(DWMWA_NCRENDERING_POLICY, &DWMNCRP_ENABLED);
-        self.hwnd
-            .dwm_set_attribute(DWMWA_USE_IMMERSIVE_DARK_MODE, &TRUE);
-        self.hwnd
-            .dwm_set_attribute(DWMWA_WINDOW_CORNER_PREFERENCE, &DWMWCP_DONOTROUND);
-
-        self.hwnd.set_timer(0x01, 100);
-
-        if let Some(app) = &mut self.app {
-            let mut builder = self.menu.get_builder()?;
-            app.build_menu(&mut builder)?;
-            self.menu.build(builder);
-        }
-
-        Ok(())
-    }
-
-    fn on_close(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
-        if let Some(mut app) = self.app.take() {
-            _ = app.on_destroy();
-        }
-
-        self.hwnd.destroy();
-
-        Some(LRESULT(0))
-    }
-
-    fn on_destroy(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
-        if let Some(mut app) = self.app.take() {
-            _ = app.on_destroy();
-        }
-
-        quit(0);
-
-        Some(LRESULT(0))
-    }
-
-    fn on_window_pos_changed(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
-        let WINDOWPOS {
-            x,
-            y,
-            cx: width,
-            cy: height,
-            ..
-        } = *cast!(lp.0, WINDOWPOS);
-
-        self.hittest.update(x, y, width, height);
-        self.menu.adjust_rect(x, y, width, height);
-
-        if width > 0 && height > 0 {
-            if let Some(app) = &mut self.app {
-                _ = app.on_pos_changed(x, y, width, height);
-            }
-        }
-
-        Some(LRESULT(0))
-    }
-
-    fn on_timer(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
-        if self.transparent && self.hittest.on_frame() {
-            self.set_transparency(false);
-        }
-
-        Some(LRESULT(0))
-    }
-
-    fn on_nc_hit_test(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
-        let mx = GET_X_LPARAM!(lp);
-        let my = GET_Y_LPARAM!(lp);
-        let hit = self.hittest.nc_hit_test(mx, my);
-        Some(LRESULT(hit as _))
-    }
-
-    fn on_control(&mut self, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
-        if let Some(app) = self.app.as_mut() {
-            let code = wp.hi();
-            let id = wp.lo();
-            let ctrl = HWND::from_lparam(lp);
-
-            if code == BN_CLICKED {
-                app.on_button(id, ctrl.checkbox_checked());
-            }
-        }
-
-        Some(LRESULT(0))
-    }
-
-    fn on_hscroll(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
-        if let Some(app) = self.app.as_mut() {
-            let trackbar = HWND::from_lparam(lp);
-            let id = trackbar.menu().0 as u32;
-            let val = trackbar.trackbar_pos();
-
-            app.on_slider(id, val);
-        }
-
-        Some(LRESULT(0))
-    }
-
-    fn on_mouse_move(&mut self, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
-        let mx = GET_X_LPARAM!(lp);
-        let my = GET_Y_LPARAM!(lp);
-
-        if wp == WPARAM(MK_LBUTTON.0 as _) {
-            let dx = mx - self.mx;
-            let dy = my - self.my;
-
-            if let Some(app) = self.app.as_mut() {
-                app.on_drag(dx, dy);
-            }
-        }
-
-        self.mx = mx;
-        self.my = my;
-
-        Some(LRESULT(0))
-    }
-}
-
-impl<T: App> Window for Viewer<T> {
-    fn new(hwnd: HWND, _cs: &mut CREATESTRUCTA) -> Result<Box<Self>> {
-        let hittest = HitTest::new(hwnd, WINDOW_EX_STYLE::default(), WS_OVERLAPPEDWINDOW);
-        let menu = Menu::create(hwnd)?;
-        let app = Some(T::new(hwnd)?);
-
-        Ok(Box::new(Self {
-            app,
-            hwnd,
-            transparent: false,
-            hittest,
-            menu,
-            mx: 0,
-            my: 0,
-        }))
-    }
-
-    #[allow(unused_variables)]
-    fn wndproc(&mut self, hwnd: HWND, msg: u32, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
-        match msg {
-            WM_CREATE => match self.on_create(wp, lp) {
-                Ok(_) => Some(LRESULT(0)),
-                Err(e) => {
-                    println!("{e:?}");
-                    Some(LRESULT(-1))
-                }
-            },
-            WM_KEYDOWN if wp.0 == VK_ESCAPE.0 as usize => self.on_close(wp, lp),
-            WM_CLOSE => self.on_close(wp, lp),
-            WM_DESTROY => self.on_destroy(wp, lp),
-            WM_NCCALCSIZE if wp == WPARAM(1) => Some(LRESULT(0)),
-            WM_WINDOWPOSCHANGED => self.on_window_pos_changed(wp, lp),
-            WM_NCHITTEST => self.on_nc_hit_test(wp, lp),
-            WM_TIMER => self.on_timer(wp, lp),
-            WM_COMMAND => self.on_control(wp, lp),
-            WM_HSCROLL => self.on_hscroll(wp, lp),
-            WM_MOUSEMOVE => self.on_mouse_move(wp, lp),
-            _ => None,
-        }
-    }
-}
-
-struct HitTest {
-    window: RECT,
-    caption: RECT,
-    client: RECT,
-    frame_x: i32,
-    frame_y: i32,
-    caption_y: i32,
-}
-
-impl HitTest {
-    fn new(hwnd: HWND, ex_style: WINDOW_EX_STYLE, style: WINDOW_STYLE) -> Self {
-        let frame = utils::adjust_window_rect(ex_style, style, 0, 0);
-        let frame_x = frame.right;
-        let frame_y = frame.bottom;
-        let caption_y = -frame.top;
-
-        let window = hwnd.rect();
-        let caption = window.inner(frame_x, frame_y);
-        let client = window.inner(caption_y, caption_y);
-
-        Self {
-            window,
-            caption,
-            client,
-            frame_x,
-            frame_y,
-            caption_y,
-        }
-    }
-
-    fn update(&mut self, x: i32, y: i32, width: i32, height: i32) {
-        self.window.left = x;
-        self.window.top = y;
-        self.window.right = x + width;
-        self.window.bottom = y + height;
-
-        self.caption = self.window.inner(self.frame_x, self.frame_y);
-
-        self.client = self.window.inner(self.caption_y, self.caption_y);
-    }
-
-    fn on_frame(&self) -> bool {
-        let (mx, my) = utils::cursor_pos();
-        self.window.is_in(mx, my) && !self.client.is_in(mx, my)
-    }
-
-    fn nc_hit_test(&self, x: i32, y: i32) -> u32 {
-        if self.client.is_in(x, y) {
-            HTCLIENT
-        } else if self.caption.is_in(x, y) {
-            HTCAPTION
-        } else {
-            let row = if y < self.caption.top {
-                0
-            } else if self.caption.bottom <= y {
-                2
-            } else {
-                1
-            };
-
-            let col = if x < self.caption.left {
-                0
-            } else if self.caption.right <= x {
-                2
-            } else {
-                1
-            };
-
-            match (row, col) {
-                (0, 0) => HTTOPLEFT,
-                (0, 1) => HTTOP,
-                (0, 2) => HTTOPRIGHT,
-                (1, 0) => HTLEFT,
-                (1, 2) => HTRIGHT,
-                (2, 0) => HTBOTTOMLEFT,
-                (2, 1) => HTBOTTOM,
-                (2, 2) => HTBOTTOMRIGHT,
-                _ => unreachable!("unreachable hit test pattern!"),
-            }
-        }
-    }
-}
+use anyhow::Result;
+use windows::{
+    core::{s, PCSTR, PCWSTR},
+    Win32::{
+        Foundation::{HWND, LPARAM, LRESULT, RECT, TRUE, WPARAM},
+        Graphics::Dwm::{
+            DWMNCRP_ENABLED, DWMWA_NCRENDERING_POLICY, DWMWA_USE_IMMERSIVE_DARK_MODE,
+            DWMWA_WINDOW_CORNER_PREFERENCE, DWMWCP_DONOTROUND,
+        },
+        System::SystemServices::MK_LBUTTON,
+        UI::{
+            Input::KeyboardAndMouse::{VK_ESCAPE, VK_MENU, VK_TAB},
+            Shell::{DragAcceptFiles, DragFinish, DragQueryFileW, HDROP},
+            WindowsAndMessaging::*,
+        },
+    },
+};
+
+use crate::{
+    cast,
+    gui::utils::{self, module_handle},
+    GET_X_LPARAM, GET_Y_LPARAM,
+};
+
+use super::{
+    app::App,
+    hwnd::{CheckBox, ComboBox, Hwnd, Trackbar},
+    menu::{Menu, WM_MENU_SHOWN, WM_MONITOR_READINGS, WM_VIDEO_POSITION},
+    pattern::{Fill, PatternWindow, WM_SHOW_PATTERN},
+    utils::{quit, Rect as _, Word},
+    window::{wndproc, Window},
+};
+
+const TIMER_FRAME: usize = 0x01;
+const TIMER_FOLLOW: usize = 0x03;
+
+const WM_MENU_REBUILD: u32 = WM_APP + 2;
+
+const EX_STYLE: WINDOW_EX_STYLE =
+    WINDOW_EX_STYLE(WS_EX_NOREDIRECTIONBITMAP.0 | WS_EX_APPWINDOW.0 | WS_EX_TOPMOST.0);
+
+pub struct Viewer<T: App> {
+    app: Option<T>,
+    hwnd: HWND,
+    transparent: bool,
+    hittest: HitTest,
+    menu: &'static mut Menu,
+    pattern: &'static mut PatternWindow,
+    follow: Option<HWND>,
+    hotkeys: Vec<i32>,
+    mx: i32,
+    my: i32,
+}
+
+impl<T: App> Viewer<T> {
+    pub fn create<'a>() -> Result<&'a mut Self> {
+        unsafe {
+            const CLASS_NAME: PCSTR = s!("Viewer");
+
+            utils::register_window_class(
+                CS_HREDRAW | CS_VREDRAW,
+                Some(wndproc::<Self>),
+                Some(LoadIconW(module_handle(), PCWSTR(1 as _))?),
+                None,
+                None,
+                CLASS_NAME,
+            )?;
+
+            let hwnd = utils::create_window(
+                EX_STYLE,
+                CLASS_NAME,
+                s!("Colormel"),
+                WS_OVERLAPPEDWINDOW,
+                0,
+                0,
+                800,
+                800,
+                None,
+                None,
+                None,
+            )?;
+
+            hwnd.update();
+            hwnd.show(SW_SHOW);
+
+            if let Some(mut this) = std::ptr::NonNull::new(hwnd.user_data() as *mut Self) {
+                Ok(this.as_mut())
+            } else {
+                anyhow::bail!(windows::core::Error::from_win32())
+            }
+        }
+    }
+
+    fn set_transparency(&mut self, transparent: bool) {
+        self.transparent = transparent;
+
+        if transparent {
+            self.hwnd
+                .set_ex_style(EX_STYLE | WS_EX_LAYERED | WS_EX_TRANSPARENT);
+        } else {
+            self.hwnd.set_ex_style(EX_STYLE);
+        }
+    }
+
+    fn update_display_affinity(&mut self) {
+        let exclude = self
+            .app
+            .as_mut()
+            .is_none_or(|app| app.exclude_from_capture());
+
+        self.hwnd.set_display_affinity(if exclude {
+            WDA_EXCLUDEFROMCAPTURE
+        } else {
+            WDA_NONE
+        });
+    }
+
+    fn on_create(&mut self, _wp: WPARAM, _lp: LPARAM) -> Result<()> {
+        self.update_display_affinity();
+
+        let rect = self
+            .app
+            .as_mut()
+            .expect("no app when on_create")
+            .window_rect();
+
+        self.hwnd.set_pos(
+            rect.left,
+            rect.top,
+            rect.width(),
+            rect.height(),
+            SWP_FRAMECHANGED,
+        );
+
+        self.hwnd.dwm_extend_frame(-1);
+        self.hwnd.dwm_enable_blur_behind(true);
+        self.hwnd
+            .dwm_set_attribute(DWMWA_NCRENDERING_POLICY, &DWMNCRP_ENABLED);
+        self.hwnd
+            .dwm_set_attribute(DWMWA_USE_IMMERSIVE_DARK_MODE, &TRUE);
+        self.hwnd
+            .dwm_set_attribute(DWMWA_WINDOW_CORNER_PREFERENCE, &DWMWCP_DONOTROUND);
+
+        self.hwnd.set_timer(TIMER_FRAME, 100);
+        self.hwnd.set_timer(TIMER_FOLLOW, 16);
+
+        self.build_menu()?;
+        self.register_hotkeys();
+
+        // image files dropped onto the window are analyzed in place of the desktop
+        unsafe { DragAcceptFiles(self.hwnd, true) };
+
+        if let Some(app) = &mut self.app {
+            self.menu.set_hint(app.menu_hint());
+
+            let (edge, zone) = app.menu_activation();
+            self.menu.set_activation(edge, zone);
+        }
+
+        Ok(())
+    }
+
+    // a combination taken by another application is skipped
+    fn register_hotkeys(&mut self) {
+        let Some(app) = &mut self.app else {
+            return;
+        };
+
+        for (id, modifiers, key) in app.hotkeys() {
+            match self.hwnd.register_hotkey(id, modifiers, key) {
+                Ok(_) => self.hotkeys.push(id),
+                Err(e) => println!("hotkey {id}: {e:?}"),
+            }
+        }
+    }
+
+    fn build_menu(&mut self) -> Result<()> {
+        if let Some(app) = &mut self.app {
+            let mut builder = self.menu.get_builder()?;
+            app.build_menu(&mut builder)?;
+            self.menu.build(builder);
+        }
+
+        Ok(())
+    }
+
+    fn on_close(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        if let Some(mut app) = self.app.take() {
+            _ = app.on_destroy();
+        }
+
+        self.hwnd.destroy();
+
+        Some(LRESULT(0))
+    }
+
+    fn on_destroy(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        if let Some(mut app) = self.app.take() {
+            _ = app.on_destroy();
+        }
+
+        for id in self.hotkeys.drain(..) {
+            self.hwnd.unregister_hotkey(id);
+        }
+
+        quit(0);
+
+        Some(LRESULT(0))
+    }
+
+    fn on_window_pos_changed(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        let WINDOWPOS {
+            x,
+            y,
+            cx: width,
+            cy: height,
+            ..
+        } = *cast!(lp.0, WINDOWPOS);
+
+        self.hittest.update(x, y, width, height);
+        self.menu.adjust_rect(x, y, width, height);
+        self.pattern.adjust_rect(x, y, width, height);
+
+        if width > 0 && height > 0 {
+            if let Some(app) = &mut self.app {
+                _ = app.on_pos_changed(x, y, width, height);
+            }
+        }
+
+        Some(LRESULT(0))
+    }
+
+    fn on_timer(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        match wp.0 {
+            TIMER_FOLLOW => self.follow(),
+            _ => {
+                if self.transparent && self.hittest.on_frame() {
+                    self.set_transparency(false);
+                }
+
+                if let Some(app) = self.app.as_mut() {
+                    app.on_occluded(self.hwnd.is_occluded());
+                }
+            }
+        }
+
+        Some(LRESULT(0))
+    }
+
+    fn follow(&mut self) {
+        if !self.app.as_mut().is_some_and(|app| app.follow_window()) {
+            self.follow = None;
+            return;
+        }
+
+        let target = match self.follow.filter(|target| target.is_window()) {
+            Some(target) => target,
+            None => {
+                // not the viewer, the menu, dialogs or anything else of ours
+                let foreground = utils::foreground_window();
+                if foreground.0.is_null() || foreground.process_id() == std::process::id() {
+                    self.follow = None;
+                    return;
+                }
+                foreground
+            }
+        };
+        self.follow = Some(target);
+
+        let rect = target.client_rect_on_screen();
+        if rect.width() > 0 && rect.height() > 0 && rect != self.hwnd.rect() {
+            self.hwnd.set_pos(
+                rect.left,
+                rect.top,
+                rect.width(),
+                rect.height(),
+                SWP_NOZORDER | SWP_NOACTIVATE,
+            );
+        }
+    }
+
+    // moved to a monitor with another scale, lp is the rect suggested for it
+    fn on_dpi_changed(&mut self, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        let dpi = wp.hi();
+        let rect = *cast!(lp.0, RECT);
+
+        self.hittest = HitTest::new(self.hwnd, WINDOW_EX_STYLE::default(), WS_OVERLAPPEDWINDOW);
+        self.menu.set_dpi(dpi);
+        self.hwnd.set_pos(
+            rect.left,
+            rect.top,
+            rect.width(),
+            rect.height(),
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+
+        self.menu.clear();
+        if let Err(e) = self.build_menu() {
+            println!("{e:?}");
+        }
+
+        Some(LRESULT(0))
+    }
+
+    fn on_nc_hit_test(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        if self.app.as_mut().is_some_and(|app| app.locked()) {
+            return Some(LRESULT(HTCLIENT as _));
+        }
+
+        let mx = GET_X_LPARAM!(lp);
+        let my = GET_Y_LPARAM!(lp);
+        let hit = self.hittest.nc_hit_test(mx, my);
+        Some(LRESULT(hit as _))
+    }
+
+    fn on_control(&mut self, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        if let Some(app) = self.app.as_mut() {
+            let code = wp.hi();
+            let id = wp.lo();
+            let ctrl = HWND::from_lparam(lp);
+
+            if code == BN_CLICKED {
+                if !(utils::key_down(VK_MENU) && app.on_alt_button(id)) {
+                    app.on_button(id, ctrl.checkbox_checked());
+                }
+            } else if code == CBN_SELCHANGE {
+                if let Some(index) = ctrl.combobox_selection() {
+                    app.on_combo(id, index);
+                }
+            } else if code == EN_KILLFOCUS {
+                let text = ctrl.text();
+                app.on_text(id, &text);
+
+                if let Ok(val) = text.trim().parse() {
+                    app.on_number(id, val);
+
+                    let rect = app.window_rect();
+                    if rect != self.hwnd.rect() {
+                        self.hwnd.set_pos(
+                            rect.left,
+                            rect.top,
+                            rect.width(),
+                            rect.height(),
+                            SWP_NOZORDER | SWP_NOACTIVATE,
+                        );
+                    }
+                }
+            }
+
+            let (edge, zone) = app.menu_activation();
+            self.menu.set_activation(edge, zone);
+
+            // posted, the control that sent this is among the ones destroyed
+            if app.take_menu_rebuild() {
+                self.hwnd
+                    .post_message(WM_MENU_REBUILD, WPARAM::default(), LPARAM::default());
+            }
+        }
+
+        if wp.hi() == BN_CLICKED {
+            self.update_display_affinity();
+        }
+
+        Some(LRESULT(0))
+    }
+
+    fn on_hscroll(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        if let Some(app) = self.app.as_mut() {
+            let trackbar = HWND::from_lparam(lp);
+            let id = trackbar.menu().0 as u32;
+            let val = trackbar.trackbar_pos();
+
+            app.on_slider(id, val);
+
+            let (edge, zone) = app.menu_activation();
+            self.menu.set_activation(edge, zone);
+        }
+
+        Some(LRESULT(0))
+    }
+
+    fn on_menu_rebuild(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        self.menu.clear();
+        if let Err(e) = self.build_menu() {
+            println!("{e:?}");
+        }
+
+        Some(LRESULT(0))
+    }
+
+    fn on_menu_shown(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        if let Some(app) = self.app.as_mut() {
+            app.on_menu_shown();
+            self.menu.set_hint(app.menu_hint());
+
+            for (id, val) in app.number_fields() {
+                self.menu.set_number(id, val);
+            }
+        }
+
+        Some(LRESULT(0))
+    }
+
+    // posted by the visualizer thread during the capture self-check
+    fn on_show_pattern(&mut self, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        self.pattern.show(Fill::from_message(wp, lp));
+        Some(LRESULT(0))
+    }
+
+    // posted by the ddc/ci worker whenever the monitor reports other values
+    fn on_monitor_readings(&mut self, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        if let Some(app) = self.app.as_mut() {
+            for (id, val) in app.on_monitor_readings(wp.0 as i32, lp.0 as i32) {
+                self.menu.set_slider(id, val);
+            }
+        }
+
+        Some(LRESULT(0))
+    }
+
+    // posted by the video player as playback moves on
+    fn on_video_position(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        if let Some(app) = self.app.as_mut() {
+            for (id, val) in app.on_video_position(wp.0 as i32) {
+                self.menu.set_slider(id, val);
+            }
+        }
+
+        Some(LRESULT(0))
+    }
+
+    fn on_mouse_move(&mut self, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        let mx = GET_X_LPARAM!(lp);
+        let my = GET_Y_LPARAM!(lp);
+
+        if wp == WPARAM(MK_LBUTTON.0 as _) {
+            let dx = mx - self.mx;
+            let dy = my - self.my;
+
+            if let Some(app) = self.app.as_mut() {
+                app.on_drag(dx, dy);
+            }
+        }
+
+        self.mx = mx;
+        self.my = my;
+
+        Some(LRESULT(0))
+    }
+
+    fn on_key_down(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        if let Some((id, checked)) = self.app.as_mut().and_then(|app| app.on_key(wp.0 as u16)) {
+            self.menu.set_check(id, checked);
+        }
+
+        Some(LRESULT(0))
+    }
+
+    // the way into the menu without a mouse
+    fn on_tab(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        self.menu.open_with_keyboard();
+        Some(LRESULT(0))
+    }
+
+    fn on_hotkey(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        if let Some((id, checked)) = self.app.as_mut().and_then(|app| app.on_hotkey(wp.0 as i32)) {
+            self.menu.set_check(id, checked);
+        }
+
+        Some(LRESULT(0))
+    }
+
+    fn on_drop_files(&mut self, wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        let hdrop = HDROP(wp.0 as _);
+        let path = unsafe {
+            let len = DragQueryFileW(hdrop, 0, None) as usize;
+            let mut path = vec![0u16; len + 1];
+            DragQueryFileW(hdrop, 0, Some(&mut path));
+            DragFinish(hdrop);
+
+            String::from_utf16_lossy(&path[..len])
+        };
+
+        if let Some(app) = self.app.as_mut() {
+            app.on_drop_file(path.as_ref());
+
+            if app.take_menu_rebuild() {
+                self.hwnd
+                    .post_message(WM_MENU_REBUILD, WPARAM::default(), LPARAM::default());
+            }
+        }
+
+        Some(LRESULT(0))
+    }
+
+    fn on_lbutton_down(&mut self, _wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        let mx = GET_X_LPARAM!(lp);
+        let my = GET_Y_LPARAM!(lp);
+
+        if let Some(app) = self.app.as_mut() {
+            app.on_click(mx, my);
+        }
+
+        self.mx = mx;
+        self.my = my;
+
+        Some(LRESULT(0))
+    }
+
+    fn on_lbutton_up(&mut self, _wp: WPARAM, _lp: LPARAM) -> Option<LRESULT> {
+        if let Some(app) = self.app.as_mut() {
+            app.on_drag_end();
+        }
+
+        Some(LRESULT(0))
+    }
+}
+
+impl<T: App> Window for Viewer<T> {
+    fn new(hwnd: HWND, _cs: &mut CREATESTRUCTA) -> Result<Box<Self>> {
+        let hittest = HitTest::new(hwnd, WINDOW_EX_STYLE::default(), WS_OVERLAPPEDWINDOW);
+        let menu = Menu::create(hwnd)?;
+        let pattern = PatternWindow::create(hwnd)?;
+        let app = Some(T::new(hwnd)?);
+
+        Ok(Box::new(Self {
+            app,
+            hwnd,
+            transparent: false,
+            hittest,
+            menu,
+            pattern,
+            follow: None,
+            hotkeys: Vec::new(),
+            mx: 0,
+            my: 0,
+        }))
+    }
+
+    #[allow(unused_variables)]
+    fn wndproc(&mut self, hwnd: HWND, msg: u32, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
+        match msg {
+            WM_CREATE => match self.on_create(wp, lp) {
+                Ok(_) => Some(LRESULT(0)),
+                Err(e) => {
+                    println!("{e:?}");
+                    Some(LRESULT(-1))
+                }
+            },
+            WM_KEYDOWN if wp.0 == VK_ESCAPE.0 as usize => self.on_close(wp, lp),
+            WM_KEYDOWN if wp.0 == VK_TAB.0 as usize => self.on_tab(wp, lp),
+            WM_KEYDOWN => self.on_key_down(wp, lp),
+            WM_HOTKEY => self.on_hotkey(wp, lp),
+            WM_CLOSE => self.on_close(wp, lp),
+            WM_DESTROY => self.on_destroy(wp, lp),
+            WM_NCCALCSIZE if wp == WPARAM(1) => Some(LRESULT(0)),
+            WM_WINDOWPOSCHANGED => self.on_window_pos_changed(wp, lp),
+            WM_NCHITTEST => self.on_nc_hit_test(wp, lp),
+            WM_DPICHANGED => self.on_dpi_changed(wp, lp),
+            WM_TIMER => self.on_timer(wp, lp),
+            WM_COMMAND => self.on_control(wp, lp),
+            WM_HSCROLL => self.on_hscroll(wp, lp),
+            WM_MOUSEMOVE => self.on_mouse_move(wp, lp),
+            WM_LBUTTONDOWN => self.on_lbutton_down(wp, lp),
+            WM_LBUTTONUP => self.on_lbutton_up(wp, lp),
+            WM_DROPFILES => self.on_drop_files(wp, lp),
+            WM_MENU_SHOWN => self.on_menu_shown(wp, lp),
+            WM_MENU_REBUILD => self.on_menu_rebuild(wp, lp),
+            WM_SHOW_PATTERN => self.on_show_pattern(wp, lp),
+            WM_MONITOR_READINGS => self.on_monitor_readings(wp, lp),
+            WM_VIDEO_POSITION => self.on_video_position(wp, lp),
+            _ => None,
+        }
+    }
+}
+
+struct HitTest {
+    window: RECT,
+    caption: RECT,
+    client: RECT,
+    frame_x: i32,
+    frame_y: i32,
+    caption_y: i32,
+}
+
+impl HitTest {
+    fn new(hwnd: HWND, ex_style: WINDOW_EX_STYLE, style: WINDOW_STYLE) -> Self {
+        let frame = utils::adjust_window_rect(ex_style, style, 0, 0, hwnd.dpi());
+        let frame_x = frame.right;
+        let frame_y = frame.bottom;
+        let caption_y = -frame.top;
+
+        let window = hwnd.rect();
+        let caption = window.inner(frame_x, frame_y);
+        let client = window.inner(caption_y, caption_y);
+
+        Self {
+            window,
+            caption,
+            client,
+            frame_x,
+            frame_y,
+            caption_y,
+        }
+    }
+
+    fn update(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        self.window.left = x;
+        self.window.top = y;
+        self.window.right = x + width;
+        self.window.bottom = y + height;
+
+        self.caption = self.window.inner(self.frame_x, self.frame_y);
+
+        self.client = self.window.inner(self.caption_y, self.caption_y);
+    }
+
+    fn on_frame(&self) -> bool {
+        let (mx, my) = utils::cursor_pos();
+        self.window.is_in(mx, my) && !self.client.is_in(mx, my)
+    }
+
+    fn nc_hit_test(&self, x: i32, y: i32) -> u32 {
+        if self.client.is_in(x, y) {
+            HTCLIENT
+        } else if self.caption.is_in(x, y) {
+            HTCAPTION
+        } else {
+            let row = if y < self.caption.top {
+                0
+            } else if self.caption.bottom <= y {
+                2
+            } else {
+                1
+            };
+
+            let col = if x < self.caption.left {
+                0
+            } else if self.caption.right <= x {
+                2
+            } else {
+                1
+            };
+
+            match (row, col) {
+                (0, 0) => HTTOPLEFT,
+                (0, 1) => HTTOP,
+                (0, 2) => HTTOPRIGHT,
+                (1, 0) => HTLEFT,
+                (1, 2) => HTRIGHT,
+                (2, 0) => HTBOTTOMLEFT,
+                (2, 1) => HTBOTTOM,
+                (2, 2) => HTBOTTOMRIGHT,
+                _ => unreachable!("unreachable hit test pattern!"),
+            }
+        }
+    }
+}
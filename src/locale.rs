@@ -0,0 +1,60 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use windows::Win32::Globalization::GetUserDefaultUILanguage;
+
+// control text is written in english, which doubles as the string id. the other languages map
+// those ids to their translation, anything missing stays english
+
+// (config value, name shown in the menu), the empty value follows the windows display language
+pub const LANGUAGES: [(&str, &str); 3] = [("", "System"), ("en", "English"), ("ja", "日本語")];
+
+const LANG_JAPANESE: u16 = 0x11;
+
+pub type Strings = HashMap<String, String>;
+
+// None for english
+pub fn strings(language: &str) -> Option<&'static Strings> {
+    static JA: OnceLock<Strings> = OnceLock::new();
+
+    match resolve(language) {
+        "ja" => Some(JA.get_or_init(|| parse(include_str!("locale/ja.toml")))),
+        _ => None,
+    }
+}
+
+fn resolve(language: &str) -> &str {
+    if !language.is_empty() {
+        return language;
+    }
+
+    // the primary language is the low 10 bits of a LANGID
+    match unsafe { GetUserDefaultUILanguage() } & 0x3ff {
+        LANG_JAPANESE => "ja",
+        _ => "en",
+    }
+}
+
+fn parse(text: &str) -> Strings {
+    toml::from_str(text).unwrap_or_else(|e| {
+        println!("{e:?}");
+        Strings::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn japanese_table_parses() {
+        let strings = toml::from_str::<Strings>(include_str!("locale/ja.toml")).unwrap();
+        assert_eq!(strings["Filter"], "フィルター");
+        assert!(strings.keys().all(|id| id.trim() == id));
+    }
+
+    #[test]
+    fn english_has_no_table() {
+        assert!(strings("en").is_none());
+        assert!(strings("ja").is_some());
+    }
+}
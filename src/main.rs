@@ -1,13 +1,307 @@
 #![windows_subsystem = "windows"]
 
-use anyhow::Result;
+use std::{path::PathBuf, sync::OnceLock};
+
+use anyhow::{bail, Context, Result};
+use windows::Win32::Foundation::RECT;
+
+use config::*;
+use gui::utils::Rect as _;
 
 pub mod app;
 pub mod config;
 pub mod graphics;
 pub mod gui;
+pub mod locale;
 pub mod visualize;
 
+const USAGE: &str = "usage: colormel [--config FILE.toml|FILE.ini] [--monitor N] [--pos X,Y,W,H]
+    [--filter [rgb|hue|sat|luma|zebra|mono|blue]] [--no-filter]
+    [--histogram [rgb|rgbl|luma|hue|sat|chroma]] [--no-histogram]
+    [--cloud [rgb|hsl|hsv|ycbcr|lab|oklab]] [--no-cloud]
+    [--vectorscope] [--no-vectorscope]
+    [--waveform [composite|parade]] [--no-waveform]";
+
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+// overrides from the command line, applied over the loaded config and put back before it's saved
+#[derive(Default, Debug, PartialEq)]
+pub struct Args {
+    pub config: Option<PathBuf>,
+    pub monitor: Option<u32>,
+    pub pos: Option<RECT>,
+    // a scope switched on or off, optionally with its mode
+    pub filter: Option<(bool, Option<u32>)>,
+    pub histogram: Option<(bool, Option<u32>)>,
+    pub cloud: Option<(bool, Option<u32>)>,
+    pub vectorscope: Option<bool>,
+    pub waveform: Option<(bool, Option<u32>)>,
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            // a mode follows its scope unless the next word is another option
+            let mut mode = |modes: &[(&str, u32)]| -> Result<Option<u32>> {
+                let Some(word) = args.next_if(|next| !next.starts_with("--")) else {
+                    return Ok(None);
+                };
+                match modes
+                    .iter()
+                    .find(|(name, _)| word.eq_ignore_ascii_case(name))
+                {
+                    Some(&(_, mode)) => Ok(Some(mode)),
+                    None => bail!("unknown mode {word} for {arg}"),
+                }
+            };
+
+            match arg.as_str() {
+                "--filter" => {
+                    parsed.filter = Some((
+                        true,
+                        mode(&[
+                            ("rgb", FILTER_MODE_RGB),
+                            ("hue", FILTER_MODE_HUE),
+                            ("sat", FILTER_MODE_SAT),
+                            ("luma", FILTER_MODE_LUMA),
+                            ("zebra", FILTER_MODE_ZEBRA),
+                            ("mono", FILTER_MODE_MONO),
+                            ("blue", FILTER_MODE_BLUE),
+                        ])?,
+                    ))
+                }
+                "--histogram" => {
+                    parsed.histogram = Some((
+                        true,
+                        mode(&[
+                            ("rgb", HISTOGRAM_MODE_RGB),
+                            ("rgbl", HISTOGRAM_MODE_RGBL),
+                            ("luma", HISTOGRAM_MODE_LUMA),
+                            ("hue", HISTOGRAM_MODE_HUE),
+                            ("sat", HISTOGRAM_MODE_SAT),
+                            ("chroma", HISTOGRAM_MODE_CHROMA),
+                        ])?,
+                    ))
+                }
+                "--cloud" => {
+                    parsed.cloud = Some((
+                        true,
+                        mode(&[
+                            ("rgb", COLORCLOUD_MODE_RGB),
+                            ("hsl", COLORCLOUD_MODE_HSL),
+                            ("hsv", COLORCLOUD_MODE_HSV),
+                            ("ycbcr", COLORCLOUD_MODE_YCBCR),
+                            ("lab", COLORCLOUD_MODE_LAB),
+                            ("oklab", COLORCLOUD_MODE_OKLAB),
+                        ])?,
+                    ))
+                }
+                "--vectorscope" => parsed.vectorscope = Some(true),
+                "--waveform" => {
+                    parsed.waveform = Some((
+                        true,
+                        mode(&[
+                            ("composite", WAVEFORM_LAYOUT_COMPOSITE),
+                            ("parade", WAVEFORM_LAYOUT_PARADE),
+                        ])?,
+                    ))
+                }
+                "--no-filter" => parsed.filter = Some((false, None)),
+                "--no-histogram" => parsed.histogram = Some((false, None)),
+                "--no-cloud" => parsed.cloud = Some((false, None)),
+                "--no-vectorscope" => parsed.vectorscope = Some(false),
+                "--no-waveform" => parsed.waveform = Some((false, None)),
+                "--config" => {
+                    let mut path = PathBuf::from(args.next().context("--config needs a path")?);
+                    // an old ini is migrated into the toml next to it, which is what gets saved
+                    if path
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("ini"))
+                    {
+                        path.set_extension("toml");
+                    }
+                    parsed.config = Some(path);
+                }
+                "--monitor" => {
+                    let index = args.next().context("--monitor needs an index")?;
+                    parsed.monitor = Some(index.parse().context("--monitor needs an index")?);
+                }
+                "--pos" => {
+                    let pos = args.next().context("--pos needs x,y,w,h")?;
+                    let values = pos
+                        .split(',')
+                        .map(|value| value.trim().parse::<i32>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .ok()
+                        .filter(|values| values.len() == 4 && values[2] > 0 && values[3] > 0)
+                        .context("--pos needs x,y,w,h")?;
+                    parsed.pos = Some(RECT::new(values[0], values[1], values[2], values[3]));
+                }
+                _ => bail!("unknown option {arg}"),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(monitor) = self.monitor {
+            config.monitor = monitor;
+        }
+        if let Some(pos) = self.pos {
+            config.window_rect = pos;
+        }
+        if let Some((enable, mode)) = self.filter {
+            config.enable_filter = enable;
+            config.filter_mode = mode.unwrap_or(config.filter_mode);
+        }
+        if let Some((enable, mode)) = self.histogram {
+            config.enable_histogram = enable;
+            config.histogram_mode = mode.unwrap_or(config.histogram_mode);
+        }
+        if let Some((enable, mode)) = self.cloud {
+            config.enable_color_cloud = enable;
+            config.color_cloud_mode = mode.unwrap_or(config.color_cloud_mode);
+        }
+        if let Some(enable) = self.vectorscope {
+            config.enable_vectorscope = enable;
+        }
+        if let Some((enable, layout)) = self.waveform {
+            config.enable_waveform = enable;
+            config.waveform_layout = layout.unwrap_or(config.waveform_layout);
+        }
+    }
+
+    // undoes apply, the overridden settings go back to what was loaded while the rest keeps the
+    // changes made this session. --monitor moves the window, so its rect goes back too
+    pub fn restore(&self, loaded: &Config, config: &mut Config) {
+        if self.monitor.is_some() {
+            config.monitor = loaded.monitor;
+        }
+        if self.monitor.is_some() || self.pos.is_some() {
+            config.window_rect = loaded.window_rect;
+        }
+        if self.filter.is_some() {
+            config.enable_filter = loaded.enable_filter;
+            config.filter_mode = loaded.filter_mode;
+        }
+        if self.histogram.is_some() {
+            config.enable_histogram = loaded.enable_histogram;
+            config.histogram_mode = loaded.histogram_mode;
+        }
+        if self.cloud.is_some() {
+            config.enable_color_cloud = loaded.enable_color_cloud;
+            config.color_cloud_mode = loaded.color_cloud_mode;
+        }
+        if self.vectorscope.is_some() {
+            config.enable_vectorscope = loaded.enable_vectorscope;
+        }
+        if self.waveform.is_some() {
+            config.enable_waveform = loaded.enable_waveform;
+            config.waveform_layout = loaded.waveform_layout;
+        }
+    }
+}
+
+// parsed once in main, empty in tests
+pub fn args() -> &'static Args {
+    ARGS.get_or_init(Args::default)
+}
+
 fn main() -> Result<()> {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            gui::utils::error_box(&format!("{e:#}\n\n{USAGE}"));
+            std::process::exit(2);
+        }
+    };
+    ARGS.get_or_init(|| args);
+
     gui::run::<app::App>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<Args> {
+        Args::parse(line.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn parses_scopes_and_modes() {
+        let args = parse(
+            "--monitor 1 --histogram luma --no-cloud --waveform --config my.toml --pos 0,0,800,600",
+        )
+        .unwrap();
+
+        assert_eq!(args.monitor, Some(1));
+        assert_eq!(args.histogram, Some((true, Some(HISTOGRAM_MODE_LUMA))));
+        assert_eq!(args.cloud, Some((false, None)));
+        assert_eq!(args.waveform, Some((true, None)));
+        assert_eq!(args.config, Some(PathBuf::from("my.toml")));
+        assert_eq!(args.pos, Some(RECT::new(0, 0, 800, 600)));
+        assert_eq!(args.filter, None);
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse("--histogram bars").is_err());
+        assert!(parse("--monitor").is_err());
+        assert!(parse("--monitor one").is_err());
+        assert!(parse("--pos 0,0,800").is_err());
+        assert!(parse("--pos 0,0,0,600").is_err());
+        assert!(parse("--fullscreen").is_err());
+        assert_eq!(parse("").unwrap(), Args::default());
+    }
+
+    #[test]
+    fn an_ini_config_is_saved_as_the_toml_next_to_it() {
+        assert_eq!(
+            parse("--config old/colormel.ini").unwrap().config,
+            Some(PathBuf::from("old/colormel.toml"))
+        );
+        assert_eq!(
+            parse("--config my.INI").unwrap().config,
+            Some(PathBuf::from("my.toml"))
+        );
+    }
+
+    #[test]
+    fn overrides_only_what_was_given() {
+        let mut config = Config::load("").unwrap();
+        config.enable_vectorscope = true;
+        config.histogram_mode = HISTOGRAM_MODE_HUE;
+
+        parse("--histogram --no-waveform")
+            .unwrap()
+            .apply(&mut config);
+
+        assert!(config.enable_histogram);
+        assert_eq!(config.histogram_mode, HISTOGRAM_MODE_HUE);
+        assert!(config.enable_vectorscope);
+        assert!(!config.enable_waveform);
+    }
+
+    #[test]
+    fn restore_puts_back_only_what_was_overridden() {
+        let loaded = Config::load("").unwrap();
+        let args = parse("--no-histogram --pos 0,0,800,600 --waveform parade").unwrap();
+
+        let mut config = loaded.clone();
+        args.apply(&mut config);
+        config.enable_vectorscope = !loaded.enable_vectorscope;
+        config.histogram_mode = HISTOGRAM_MODE_SAT;
+        args.restore(&loaded, &mut config);
+
+        assert_eq!(config.enable_histogram, loaded.enable_histogram);
+        assert_eq!(config.histogram_mode, loaded.histogram_mode);
+        assert_eq!(config.window_rect, loaded.window_rect);
+        assert_eq!(config.waveform_layout, loaded.waveform_layout);
+        assert_eq!(config.enable_vectorscope, !loaded.enable_vectorscope);
+    }
+}
@@ -0,0 +1,30 @@
+#include "common.hlsli"
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    float4 Color;
+    float4 Checker;
+    uint Size;
+};
+
+struct VertexOut {
+    float4 position : SV_Position;
+};
+
+VertexOut BackgroundVs(uint vid: SV_VertexID) {
+    float2 uv = float2(vid & 1, vid >> 1);
+
+    VertexOut output;
+    output.position = float4(2.0 * uv.x - 1.0, 1.0 - 2.0 * uv.y, 0.0, 1.0);
+
+    return output;
+}
+
+// both colors come premultiplied, a solid fill passes the same color twice
+float4 BackgroundPs(VertexOut input) : SV_Target {
+    uint2 square = uint2(input.position.xy) / Size;
+    return ((square.x + square.y) & 1) ? Checker : Color;
+}
+
+#endif // GRAPHICS
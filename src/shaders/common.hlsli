@@ -1,159 +1,571 @@
-Texture2D<float4> Desktop : register(t0, space1);
-
-static const float PI = 3.14159265358979323846;
-
-float Max3(float a, float b, float c)  {
-    return max(a, max(b, c));
-}
-
-float Min3(float a, float b, float c) {
-    return min(a, min(b, c));
-}
-
-float Luma(float3 rgb) {
-    static const float3 LUMA = {
-        0.2126, 0.7152, 0.0722f
-    };
-
-    return dot(rgb, LUMA);
-}
-
-float3 RgbToHsv(float3 rgb) {
-    float ma = Max3(rgb.r, rgb.g, rgb.b);
-    float mi = Min3(rgb.r, rgb.g, rgb.b);
-
-    float H = 0.0;
-    if (mi == ma) {
-        H = 0.0;
-    }
-    else if (mi == rgb.b) {
-        H = ((rgb.g - rgb.r) / (ma - mi) + 1.0) / 6.0;
-    }
-    else if (mi == rgb.r) {
-        H = ((rgb.b - rgb.g) / (ma - mi) + 3.0) / 6.0;
-    }
-    else {
-        H = ((rgb.r - rgb.b) / (ma - mi) + 5.0) / 6.0;
-    }
-
-    float S = ma - mi;
-
-    float V = ma;
-
-    return float3(H, S, V);
-}
-
-float3 RgbToHsl(float3 rgb) {
-    float ma = Max3(rgb.r, rgb.g, rgb.b);
-    float mi = Min3(rgb.r, rgb.g, rgb.b);
-
-    float H = 0.0;
-    if (mi == ma) {
-        H = 0.0;
-    }
-    else if (mi == rgb.b) {
-        H = ((rgb.g - rgb.r) / (ma - mi) + 1.0) / 6.0;
-    }
-    else if (mi == rgb.r) {
-        H = ((rgb.b - rgb.g) / (ma - mi) + 3.0) / 6.0;
-    }
-    else {
-        H = ((rgb.r - rgb.b) / (ma - mi) + 5.0) / 6.0;
-    }
-
-    float S = ma - mi;
-
-    float L = (ma + mi) / 2.0;
-
-    return float3(H, S, L);
-}
-
-float3 RgbToXyz(float3 rgb) {
-    static const float3x3 RGB_TO_XYZ = {
-        0.412391, 0.357584, 0.180481,
-        0.212639, 0.715169, 0.072192,
-        0.019331, 0.119195, 0.950532,
-    };
-
-    return mul(RGB_TO_XYZ, rgb);
-}
-
-float3 RgbToYuv(float3 rgb) {
-    static const float3x3 RgbToYuv = {
-        +0.212600, +0.715200, +0.072200,
-        -0.114572, -0.385428, +0.500000,
-        +0.500000, -0.451453, -0.045847
-    };
-
-    return mul(RgbToYuv, rgb) + float3(0.0, 0.5, 0.5);
-}
-
-float3 HsvToRgb(float hue, float saturation, float luminance) {
-    float r = luminance;
-    float g = luminance;
-    float b = luminance;
-
-    float h = frac(hue + 1.0);
-    uint i = (uint)(6.0 * h);
-    float f = 6.0 * h - (float)i;
-    float s = saturation;
-
-    switch (i) {
-    case 0:
-        g *= 1.0 - s * (1.0 - f);
-        b *= 1.0 - s;
-        break;
-    case 1:
-        r *= 1.0 - s * f;
-        b *= 1.0 - s;
-        break;
-    case 2:
-        r *= 1.0 - s;
-        b *= 1.0 - s * (1.0 - f);
-        break;
-    case 3:
-        r *= 1.0 - s;
-        g *= 1.0 - s * f;
-        break;
-    case 4:
-        r *= 1.0 - s * (1.0 - f);
-        g *= 1.0 - s;
-        break;
-    case 5:
-        g *= 1.0 - s;
-        b *= 1.0 - s * f;
-        break;
-    }
-
-    return float3(r, g, b);
-}
-
-float3 HslToRgb(float hue, float saturation, float luminance) {
-    float h = 360.0 * frac(hue + 1.0);
-    float ma = luminance + 0.5 * saturation;
-    float mi = luminance - 0.5 * saturation;
-    float mm = ma - mi;
-
-    if (h < 60.0) {
-        return float3(ma, mi + mm * h / 60.0, mi);
-    } else if (h < 120.0) {
-        return float3(mi + mm * (120.0 - h) / 60.0, ma, mi);
-    } else if (h < 180.0) {
-        return float3(mi, ma, mi + mm * (h - 120.0) / 60.0);
-    } else if (h < 240.0) {
-        return float3(mi, mi + mm * (240.0 - h)/60.0, ma);
-    } else if (h < 300.0) {
-        return float3(mi + mm * (h-240.0) / 60.0, mi, ma);
-    } else {
-        return float3(ma, mi, mi + mm*(360.0 - h) / 60.0);
-    }
-}
-
-uint RgbToInt(float3 rgb) {
-    uint3 color = uint3(255.0 * rgb);
-    return color.r | color.g << 8 | color.b << 16;
-}
-
-float3 IntToRgb(uint color) {
-    return float3(color & 0xff, (color & 0xff00) >> 8, (color & 0xff0000) >> 16) / 255.0;
-}
+Texture2D<float4> Desktop : register(t0, space1);
+Texture2D<float4> Overlay : register(t1, space1);
+Texture3D<float4> Lut : register(t2, space1);
+
+SamplerState LinearClamp : register(s0);
+
+// set once per frame for every pass
+cbuffer Shared : register(b0, space1) {
+    // Desktop holds the filter output, the copy with the pointer or an image file, window sized
+    // and already graded. alpha is 0 where nothing should be analyzed
+    uint SourceFiltered;
+    // what the source was made for, PRIMARIES_* and TRANSFER_* in config.rs
+    uint Primaries;
+    uint Transfer;
+    // the desktop is linear scRGB, nothing was encoded for it to undo
+    uint SourceHdr;
+}
+
+// matches config.rs
+#define PRIMARIES_BT709 0
+#define PRIMARIES_BT601 1
+#define PRIMARIES_BT2020 2
+#define TRANSFER_SRGB 0
+#define TRANSFER_BT709 1
+#define TRANSFER_BT2020 2
+
+static const float PI = 3.14159265358979323846;
+
+float Max3(float a, float b, float c)  {
+    return max(a, max(b, c));
+}
+
+float Min3(float a, float b, float c) {
+    return min(a, min(b, c));
+}
+
+// kr and kb of bt.709, bt.601 and bt.2020, green takes the rest
+float2 LumaWeights() {
+    switch (Primaries) {
+    case PRIMARIES_BT601: return float2(0.2990, 0.1140);
+    case PRIMARIES_BT2020: return float2(0.2627, 0.0593);
+    default: return float2(0.2126, 0.0722);
+    }
+}
+
+float Luma(float3 rgb) {
+    float2 k = LumaWeights();
+    return dot(rgb, float3(k.x, 1.0 - k.x - k.y, k.y));
+}
+
+// scRGB has bt.709 primaries whatever the source was made for
+float ScRgbLuma(float3 rgb) {
+    return dot(rgb, float3(0.2126, 0.7152, 0.0722));
+}
+
+// black through red and yellow to white
+float3 Heat(float t) {
+    return saturate(3.0 * t - float3(0.0, 1.0, 2.0));
+}
+
+float3 RgbToHsv(float3 rgb) {
+    float ma = Max3(rgb.r, rgb.g, rgb.b);
+    float mi = Min3(rgb.r, rgb.g, rgb.b);
+
+    float H = 0.0;
+    if (mi == ma) {
+        H = 0.0;
+    }
+    else if (mi == rgb.b) {
+        H = ((rgb.g - rgb.r) / (ma - mi) + 1.0) / 6.0;
+    }
+    else if (mi == rgb.r) {
+        H = ((rgb.b - rgb.g) / (ma - mi) + 3.0) / 6.0;
+    }
+    else {
+        H = ((rgb.r - rgb.b) / (ma - mi) + 5.0) / 6.0;
+    }
+
+    float S = ma - mi;
+
+    float V = ma;
+
+    return float3(H, S, V);
+}
+
+float3 RgbToHsl(float3 rgb) {
+    float ma = Max3(rgb.r, rgb.g, rgb.b);
+    float mi = Min3(rgb.r, rgb.g, rgb.b);
+
+    float H = 0.0;
+    if (mi == ma) {
+        H = 0.0;
+    }
+    else if (mi == rgb.b) {
+        H = ((rgb.g - rgb.r) / (ma - mi) + 1.0) / 6.0;
+    }
+    else if (mi == rgb.r) {
+        H = ((rgb.b - rgb.g) / (ma - mi) + 3.0) / 6.0;
+    }
+    else {
+        H = ((rgb.r - rgb.b) / (ma - mi) + 5.0) / 6.0;
+    }
+
+    float S = ma - mi;
+
+    float L = (ma + mi) / 2.0;
+
+    return float3(H, S, L);
+}
+
+float3 RgbToXyz(float3 rgb) {
+    static const float3x3 RGB_TO_XYZ = {
+        0.412391, 0.357584, 0.180481,
+        0.212639, 0.715169, 0.072192,
+        0.019331, 0.119195, 0.950532,
+    };
+
+    return mul(RGB_TO_XYZ, rgb);
+}
+
+float LabF(float t) {
+    return t > 0.008856 ? pow(t, 1.0 / 3.0) : 7.787 * t + 16.0 / 116.0;
+}
+
+// cie l*a*b* from xyz, d65 white
+float3 XyzToLab(float3 xyz) {
+    static const float3 WHITE = { 0.950470, 1.000000, 1.088830 };
+
+    float3 t = xyz / WHITE;
+    float3 f = float3(LabF(t.x), LabF(t.y), LabF(t.z));
+
+    return float3(116.0 * f.y - 16.0, 500.0 * (f.x - f.y), 200.0 * (f.y - f.z));
+}
+
+// y'cbcr with the weights of the source's primaries, cb and cr offset to [0, 1]
+float3 RgbToYuv(float3 rgb) {
+    float2 k = LumaWeights();
+    float y = Luma(rgb);
+    float cb = 0.5 * (rgb.b - y) / (1.0 - k.y);
+    float cr = 0.5 * (rgb.r - y) / (1.0 - k.x);
+
+    return float3(y, cb + 0.5, cr + 0.5);
+}
+
+float3 YuvToRgb(float3 yuv) {
+    float2 k = LumaWeights();
+    float r = yuv.x + 2.0 * (1.0 - k.x) * (yuv.z - 0.5);
+    float b = yuv.x + 2.0 * (1.0 - k.y) * (yuv.y - 0.5);
+    float g = (yuv.x - k.x * r - k.y * b) / (1.0 - k.x - k.y);
+
+    return float3(r, g, b);
+}
+
+float3 HsvToRgb(float hue, float saturation, float luminance) {
+    float r = luminance;
+    float g = luminance;
+    float b = luminance;
+
+    float h = frac(hue + 1.0);
+    uint i = (uint)(6.0 * h);
+    float f = 6.0 * h - (float)i;
+    float s = saturation;
+
+    switch (i) {
+    case 0:
+        g *= 1.0 - s * (1.0 - f);
+        b *= 1.0 - s;
+        break;
+    case 1:
+        r *= 1.0 - s * f;
+        b *= 1.0 - s;
+        break;
+    case 2:
+        r *= 1.0 - s;
+        b *= 1.0 - s * (1.0 - f);
+        break;
+    case 3:
+        r *= 1.0 - s;
+        g *= 1.0 - s * f;
+        break;
+    case 4:
+        r *= 1.0 - s * (1.0 - f);
+        g *= 1.0 - s;
+        break;
+    case 5:
+        g *= 1.0 - s;
+        b *= 1.0 - s * f;
+        break;
+    }
+
+    return float3(r, g, b);
+}
+
+float3 HslToRgb(float hue, float saturation, float luminance) {
+    float h = 360.0 * frac(hue + 1.0);
+    float ma = luminance + 0.5 * saturation;
+    float mi = luminance - 0.5 * saturation;
+    float mm = ma - mi;
+
+    if (h < 60.0) {
+        return float3(ma, mi + mm * h / 60.0, mi);
+    } else if (h < 120.0) {
+        return float3(mi + mm * (120.0 - h) / 60.0, ma, mi);
+    } else if (h < 180.0) {
+        return float3(mi, ma, mi + mm * (h - 120.0) / 60.0);
+    } else if (h < 240.0) {
+        return float3(mi, mi + mm * (240.0 - h)/60.0, ma);
+    } else if (h < 300.0) {
+        return float3(mi + mm * (h-240.0) / 60.0, mi, ma);
+    } else {
+        return float3(ma, mi, mi + mm*(360.0 - h) / 60.0);
+    }
+}
+
+// color cloud bins count in fixed point, so halving them in the decay keeps small counts
+#define COUNT_FRAC 4
+
+// the count of pixels a bin or a sum of bins holds, rounded
+uint CloudCount(uint raw) {
+    return (raw + (1 << (COUNT_FRAC - 1))) >> COUNT_FRAC;
+}
+
+uint RgbToInt(float3 rgb) {
+    uint3 color = uint3(255.0 * rgb);
+    return color.r | color.g << 8 | color.b << 16;
+}
+
+float3 IntToRgb(uint color) {
+    return float3(color & 0xff, (color & 0xff00) >> 8, (color & 0xff0000) >> 16) / 255.0;
+}
+
+// color cloud coordinates, the rgb cube or the hsl bicone
+float3 RgbToPosition(float3 rgb) {
+    return 1.25 * (rgb - 0.5);
+}
+
+float3 RgbToHslPosition(float3 rgb) {
+    float3 hsl = RgbToHsl(rgb);
+
+    float h = 2.0 * PI * hsl.x; // 0 - 2pi
+    float s = hsl.y; // 0 - 1
+    float l = 2.0 * hsl.z - 1.0; // -1 - 1
+
+    float a = s + abs(l);
+    float b = sqrt(s * s + l * l);
+    if (b > 0) {
+        float n = a / b;
+        s *= n;
+        l *= n;
+    }
+    
+    float y = l;
+    float x, z;
+    sincos(h, z, x);
+
+    x *= s;
+    z *= s;
+
+    return float3(x, y, -z);
+}
+
+float3 PositionToRgb(float3 position) {
+    return position / 1.25 + 0.5;
+}
+
+// inverse of RgbToHslPosition, valid inside the bicone
+float3 HslPositionToRgb(float3 position, out bool valid) {
+    float s = length(position.xz);
+    float l = position.y;
+
+    // the bicone was stretched along each direction to length s + |l|
+    float r = sqrt(s * s + l * l);
+    float a = s + abs(l);
+    if (a > 0) {
+        float n = r / a;
+        s *= n;
+        l *= n;
+    }
+    valid = r <= 1.0;
+
+    float h = atan2(-position.z, position.x) / (2.0 * PI);
+    return HslToRgb(h, s, 0.5 * (l + 1.0));
+}
+
+// scRGB is linear with 1.0 at 80 nits
+float3 ScRgbToNits(float3 rgb) {
+    return 80.0 * rgb;
+}
+
+// SMPTE ST 2084, 10000 nits maps to 1.0
+float3 NitsToPq(float3 nits) {
+    static const float m1 = 0.1593017578125;
+    static const float m2 = 78.84375;
+    static const float c1 = 0.8359375;
+    static const float c2 = 18.8515625;
+    static const float c3 = 18.6875;
+
+    float3 y = pow(saturate(nits / 10000.0), m1);
+    return pow((c1 + c2 * y) / (1.0 + c3 * y), m2);
+}
+
+float PqToNits(float pq) {
+    static const float m1 = 0.1593017578125;
+    static const float m2 = 78.84375;
+    static const float c1 = 0.8359375;
+    static const float c2 = 18.8515625;
+    static const float c3 = 18.6875;
+
+    float e = pow(saturate(pq), 1.0 / m2);
+    return 10000.0 * pow(max(e - c1, 0.0) / (c2 - c3 * e), 1.0 / m1);
+}
+
+// linear scRGB to PQ so the whole HDR range fits in [0, 1] for binning
+float3 EncodeHdr(float3 rgb) {
+    return NitsToPq(ScRgbToNits(max(rgb, 0.0)));
+}
+
+float LinearToSrgb(float c) {
+    return c <= 0.0031308 ? 12.92 * c : 1.055 * pow(c, 1.0 / 2.4) - 0.055;
+}
+
+float SrgbToLinear(float c) {
+    return c <= 0.04045 ? c / 12.92 : pow((c + 0.055) / 1.055, 2.4);
+}
+
+// the inverse of the bt.709 and bt.2020 camera curves, alpha and beta differ in precision only
+float RecToLinear(float c, float alpha, float beta) {
+    return c < 4.5 * beta ? c / 4.5 : pow((c + alpha - 1.0) / alpha, 1.0 / 0.45);
+}
+
+// the desktop is decoded as srgb, a source encoded with another curve is decoded again with it
+float3 DecodeTransfer(float3 rgb) {
+    if (SourceHdr || Transfer == TRANSFER_SRGB) {
+        return rgb;
+    }
+
+    float3 encoded = float3(LinearToSrgb(rgb.r), LinearToSrgb(rgb.g), LinearToSrgb(rgb.b));
+    float2 curve = Transfer == TRANSFER_BT2020 ? float2(1.0993, 0.0181) : float2(1.099, 0.018);
+
+    return float3(
+        RecToLinear(encoded.r, curve.x, curve.y),
+        RecToLinear(encoded.g, curve.x, curve.y),
+        RecToLinear(encoded.b, curve.x, curve.y));
+}
+
+// spreads one 8 bit srgb step over the 4x4 pattern, so smooth gradients out of a lut don't band
+float3 DitherSrgb8(float3 rgb, float2 position) {
+    float3 srgb = float3(LinearToSrgb(rgb.r), LinearToSrgb(rgb.g), LinearToSrgb(rgb.b));
+    srgb = saturate(srgb + (BayerThreshold(position) - 0.5) / 255.0);
+    return float3(SrgbToLinear(srgb.r), SrgbToLinear(srgb.g), SrgbToLinear(srgb.b));
+}
+
+float3 XyzToRgb(float3 xyz) {
+    static const float3x3 XYZ_TO_RGB = {
+        +3.240970, -1.537383, -0.498611,
+        -0.969244, +1.875968, +0.041555,
+        +0.055630, -0.203977, +1.056972,
+    };
+
+    return mul(XYZ_TO_RGB, xyz);
+}
+
+float LabFInv(float f) {
+    static const float DELTA = 6.0 / 29.0;
+    return f > DELTA ? f * f * f : 3.0 * DELTA * DELTA * (f - 4.0 / 29.0);
+}
+
+// inverse of XyzToLab
+float3 LabToXyz(float3 lab) {
+    static const float3 WHITE = { 0.950470, 1.000000, 1.088830 };
+
+    float fy = (lab.x + 16.0) / 116.0;
+    float3 f = float3(fy + lab.y / 500.0, fy, fy - lab.z / 200.0);
+
+    return WHITE * float3(LabFInv(f.x), LabFInv(f.y), LabFInv(f.z));
+}
+
+float3 Cbrt(float3 v) {
+    return sign(v) * pow(abs(v), 1.0 / 3.0);
+}
+
+// oklab from linear srgb, https://bottosson.github.io/posts/oklab/
+float3 RgbToOklab(float3 rgb) {
+    static const float3x3 RGB_TO_LMS = {
+        0.4122214708, 0.5363325363, 0.0514459929,
+        0.2119034982, 0.6806995451, 0.1073969566,
+        0.0883024619, 0.2817188376, 0.6299787005,
+    };
+    static const float3x3 LMS_TO_LAB = {
+        0.2104542553, +0.7936177850, -0.0040720468,
+        1.9779984951, -2.4285922050, +0.4505937099,
+        0.0259040371, +0.7827717662, -0.8086757660,
+    };
+
+    return mul(LMS_TO_LAB, Cbrt(mul(RGB_TO_LMS, rgb)));
+}
+
+float3 OklabToRgb(float3 lab) {
+    static const float3x3 LAB_TO_LMS = {
+        1.0, +0.3963377774, +0.2158037573,
+        1.0, -0.1055613458, -0.0638541728,
+        1.0, -0.0894841775, -1.2914855480,
+    };
+    static const float3x3 LMS_TO_RGB = {
+        +4.0767416621, -3.3077115913, +0.2309699292,
+        -1.2684380046, +2.6097574011, -0.3413193965,
+        -0.0041960863, -0.7034186147, +1.7076147010,
+    };
+
+    float3 lms = mul(LAB_TO_LMS, lab);
+    return mul(LMS_TO_RGB, lms * lms * lms);
+}
+
+// the other color cloud spaces, all with lightness up the y axis and red toward +x. the cloud
+// bins linear rgb
+
+// hsv cylinder, saturation as the radius
+float3 RgbToHsvPosition(float3 rgb) {
+    float3 hsv = RgbToHsv(rgb);
+    float s = hsv.z > 0.0 ? hsv.y / hsv.z : 0.0;
+
+    float x, z;
+    sincos(2.0 * PI * hsv.x, z, x);
+
+    return float3(s * x, 2.0 * hsv.z - 1.0, -s * z);
+}
+
+float3 HsvPositionToRgb(float3 position, out bool valid) {
+    float s = length(position.xz);
+    float v = 0.5 * (position.y + 1.0);
+    valid = s <= 1.0 && abs(position.y) <= 1.0;
+
+    float h = atan2(-position.z, position.x) / (2.0 * PI);
+    return HsvToRgb(h, s, v);
+}
+
+// y'cbcr of the srgb encoded color with the source's weights, scaled like the rgb cube with cr
+// along x
+float3 RgbToYcbcrPosition(float3 rgb) {
+    float3 encoded = float3(LinearToSrgb(rgb.r), LinearToSrgb(rgb.g), LinearToSrgb(rgb.b));
+    float3 ycbcr = RgbToYuv(encoded);
+
+    return 1.25 * (ycbcr.zxy - 0.5);
+}
+
+float3 YcbcrPositionToRgb(float3 position, out bool valid) {
+    float3 encoded = YuvToRgb(position.yzx / 1.25 + 0.5);
+    valid = all(encoded >= -1e-3) && all(encoded <= 1.0 + 1e-3);
+
+    encoded = saturate(encoded);
+    return float3(SrgbToLinear(encoded.r), SrgbToLinear(encoded.g), SrgbToLinear(encoded.b));
+}
+
+// a* and b* at this distance from the axis reach the edge of the cloud, srgb stays inside it
+#define LAB_EXTENT 110.0
+#define OKLAB_EXTENT 0.33
+
+float3 RgbToLabPosition(float3 rgb) {
+    float3 lab = XyzToLab(RgbToXyz(max(rgb, 0.0)));
+    return float3(lab.y / LAB_EXTENT, lab.x / 50.0 - 1.0, -lab.z / LAB_EXTENT);
+}
+
+float3 LabPositionToRgb(float3 position, out bool valid) {
+    float3 lab = float3(50.0 * (position.y + 1.0), LAB_EXTENT * position.x, -LAB_EXTENT * position.z);
+    float3 rgb = XyzToRgb(LabToXyz(lab));
+    valid = all(rgb >= -1e-3) && all(rgb <= 1.0 + 1e-3);
+
+    return saturate(rgb);
+}
+
+float3 RgbToOklabPosition(float3 rgb) {
+    float3 lab = RgbToOklab(max(rgb, 0.0));
+    return float3(lab.y / OKLAB_EXTENT, 2.0 * lab.x - 1.0, -lab.z / OKLAB_EXTENT);
+}
+
+float3 OklabPositionToRgb(float3 position, out bool valid) {
+    float3 lab = float3(0.5 * (position.y + 1.0), OKLAB_EXTENT * position.x, -OKLAB_EXTENT * position.z);
+    float3 rgb = OklabToRgb(lab);
+    valid = all(rgb >= -1e-3) && all(rgb <= 1.0 + 1e-3);
+
+    return saturate(rgb);
+}
+
+// COLORCLOUD_MODE_* in config.rs
+float3 CloudPosition(uint space, float3 rgb) {
+    switch (space) {
+    case 1: return RgbToHslPosition(rgb);
+    case 2: return RgbToHsvPosition(rgb);
+    case 3: return RgbToYcbcrPosition(rgb);
+    case 4: return RgbToLabPosition(rgb);
+    case 5: return RgbToOklabPosition(rgb);
+    default: return RgbToPosition(rgb);
+    }
+}
+
+float3 CloudPositionToRgb(uint space, float3 position, out bool valid) {
+    valid = true;
+    switch (space) {
+    case 1: return HslPositionToRgb(position, valid);
+    case 2: return HsvPositionToRgb(position, valid);
+    case 3: return YcbcrPositionToRgb(position, valid);
+    case 4: return LabPositionToRgb(position, valid);
+    case 5: return OklabPositionToRgb(position, valid);
+    default: return PositionToRgb(position);
+    }
+}
+
+// half the side of the box around the space
+float CloudExtent(uint space) {
+    return space == 0 || space == 3 ? 0.625 : 1.0;
+}
+
+// 4x4 ordered dither, in (0, 1)
+float BayerThreshold(float2 position) {
+    static const float BAYER[16] = {
+        0.0, 8.0, 2.0, 10.0,
+        12.0, 4.0, 14.0, 6.0,
+        3.0, 11.0, 1.0, 9.0,
+        15.0, 7.0, 13.0, 5.0,
+    };
+
+    uint2 pixel = uint2(position) % 4;
+    return (BAYER[pixel.y * 4 + pixel.x] + 0.5) / 16.0;
+}
+
+// a 1x1x1 lut means none is loaded
+bool LutLoaded() {
+    uint size, height, depth;
+    Lut.GetDimensions(size, height, depth);
+    return size >= 2;
+}
+
+// cube luts grade srgb encoded values, so hdr highlights are clipped
+float3 ApplyLut(float3 rgb) {
+    if (!LutLoaded()) {
+        return rgb;
+    }
+
+    uint size, height, depth;
+    Lut.GetDimensions(size, height, depth);
+
+    float3 srgb = saturate(rgb);
+    srgb = float3(LinearToSrgb(srgb.r), LinearToSrgb(srgb.g), LinearToSrgb(srgb.b));
+
+    // samples at texel centers so 0 and 1 hit the first and last entries
+    float3 uvw = (srgb * (size - 1) + 0.5) / size;
+    float3 graded = saturate(Lut.SampleLevel(LinearClamp, uvw, 0).rgb);
+
+    return float3(SrgbToLinear(graded.r), SrgbToLinear(graded.g), SrgbToLinear(graded.b));
+}
+
+// removes our own overlay (premultiplied, last frame) from the captured pixel, origin is the window's top-left
+bool LoadDesktop(uint2 position, uint2 origin, out float3 color) {
+    // the roi can reach past the window's top-left, where position - origin would wrap around.
+    // past the bottom-right the window sized reads return zero
+    bool inside = all(position >= origin);
+    uint2 local = position - origin;
+
+    if (SourceFiltered) {
+        // nothing was filtered outside the window
+        float4 source = inside ? Desktop[local] : (float4) 0;
+        color = source.rgb;
+        return source.a > 0.0;
+    }
+
+    // and nothing of ours is drawn there
+    float4 overlay = inside ? Overlay[local] : (float4) 0;
+    color = max(Desktop[position].rgb - overlay.rgb, 0.0) / max(1.0 - overlay.a, 0.001);
+    color = DecodeTransfer(ApplyLut(color));
+    return overlay.a < 0.99;
+}
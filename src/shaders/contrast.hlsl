@@ -0,0 +1,177 @@
+#include "common.hlsli"
+
+// tiles that look like text, flagged when their darkest and lightest pixels fall short of the
+// wcag 2 contrast minimums
+
+#define TILE 16
+
+// step in encoded luma between neighbors that counts as an edge
+#define EDGE 0.05
+// text has some edges without being all edges like noise or dithering
+#define MIN_EDGES 0.04
+#define MAX_EDGES 0.6
+// share of pixels near the darker or lighter end, anti-aliasing makes up the rest
+#define MIN_TWO_TONE 0.75
+#define NEAR_END 0.25
+
+// wcag 2 minimums for normal and large text
+#define AA_NORMAL 4.5
+#define AA_LARGE 3.0
+
+#define FLAG_NONE 0
+#define FLAG_NORMAL 1
+#define FLAG_LARGE 2
+
+#ifdef COMPUTE
+
+cbuffer Params : register(b0) {
+    int4 Rect;
+    int2 Origin;
+    uint TilesX;
+};
+
+RWBuffer<uint> Flags : register(u0);
+// flagged tiles by flag, cleared before the dispatch
+RWBuffer<uint> Counts : register(u1);
+
+// luminance is non-negative, so the bits order like the values
+groupshared uint group_min;
+groupshared uint group_max;
+groupshared uint group_count;
+groupshared uint group_edges;
+groupshared uint group_two_tone;
+
+// wcag relative luminance, hdr values above sdr white are clipped
+bool LoadLuminance(int2 position, out float luminance) {
+    float3 color;
+    luminance = 0.0;
+    if (all(position < Rect.zw) && LoadDesktop(position, Origin, color)) {
+        luminance = saturate(Luma(color));
+        return true;
+    }
+
+    return false;
+}
+
+// one group per tile, one thread per pixel
+[numthreads(TILE, TILE, 1)]
+void ContrastCs(uint2 tile: SV_GroupID, uint2 gtid: SV_GroupThreadID, uint tig: SV_GroupIndex) {
+    if (tig == 0) {
+        group_min = asuint(1.0);
+        group_max = 0;
+        group_count = 0;
+        group_edges = 0;
+        group_two_tone = 0;
+    }
+
+    GroupMemoryBarrierWithGroupSync();
+
+    int2 position = Rect.xy + int2(TILE * tile + gtid);
+    float luminance, right, below;
+    bool valid = LoadLuminance(position, luminance);
+
+    if (valid) {
+        InterlockedMin(group_min, asuint(luminance));
+        InterlockedMax(group_max, asuint(luminance));
+        InterlockedAdd(group_count, 1);
+
+        float encoded = LinearToSrgb(luminance);
+        bool edge = LoadLuminance(position + int2(1, 0), right) && abs(LinearToSrgb(right) - encoded) > EDGE;
+        edge = edge || LoadLuminance(position + int2(0, 1), below) && abs(LinearToSrgb(below) - encoded) > EDGE;
+        if (edge) {
+            InterlockedAdd(group_edges, 1);
+        }
+    }
+
+    GroupMemoryBarrierWithGroupSync();
+
+    float lo = asfloat(group_min);
+    float hi = asfloat(group_max);
+    if (valid) {
+        float encoded = LinearToSrgb(luminance);
+        float near = NEAR_END * (LinearToSrgb(hi) - LinearToSrgb(lo));
+        if (encoded - LinearToSrgb(lo) <= near || LinearToSrgb(hi) - encoded <= near) {
+            InterlockedAdd(group_two_tone, 1);
+        }
+    }
+
+    GroupMemoryBarrierWithGroupSync();
+
+    if (tig == 0) {
+        float count = max(group_count, 1);
+        float edges = group_edges / count;
+        bool text = group_count >= TILE * TILE / 2
+            && edges >= MIN_EDGES && edges <= MAX_EDGES
+            && group_two_tone / count >= MIN_TWO_TONE;
+
+        float ratio = (hi + 0.05) / (lo + 0.05);
+        uint flag = FLAG_NONE;
+        if (text && ratio < AA_LARGE) {
+            flag = FLAG_LARGE;
+        } else if (text && ratio < AA_NORMAL) {
+            flag = FLAG_NORMAL;
+        }
+
+        Flags[tile.y * TilesX + tile.x] = flag;
+        if (flag != FLAG_NONE) {
+            InterlockedAdd(Counts[flag - 1], 1);
+        }
+    }
+}
+
+#endif // COMPUTE
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    // the region relative to the window, in pixels
+    int2 Offset;
+    int2 Size;
+    uint2 Tiles;
+    uint2 WindowSize;
+};
+
+Buffer<uint> Flags : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float4 color : COLOR;
+};
+
+uint FlagAt(int2 tile) {
+    if (any(tile < 0) || any(tile >= int2(Tiles))) {
+        return FLAG_NONE;
+    }
+
+    return Flags[tile.y * Tiles.x + tile.x];
+}
+
+// a line list of the four sides per tile. sides shared with another flagged tile collapse, so
+// neighbors merge into one box
+VertexOut ContrastVs(uint vid: SV_VertexID, uint iid: SV_InstanceID) {
+    static const int2 Sides[4] = { int2(0, -1), int2(1, 0), int2(0, 1), int2(-1, 0) };
+    static const int2 Corners[5] = { int2(0, 0), int2(1, 0), int2(1, 1), int2(0, 1), int2(0, 0) };
+
+    int2 tile = int2(iid % Tiles.x, iid / Tiles.x);
+    uint side = vid / 2;
+    uint flag = FlagAt(tile);
+    bool hidden = flag == FLAG_NONE || FlagAt(tile + Sides[side]) != FLAG_NONE;
+
+    // through the outermost pixel centers, like the roi outline
+    int2 pixel = min(TILE * (tile + Corners[side + (vid & 1)]), Size - 1);
+    float2 position = Offset + pixel + 0.5;
+
+    VertexOut output;
+    output.position = hidden
+        ? float4(-2.0, -2.0, 0.0, 1.0)
+        : float4(2.0 * position.x / WindowSize.x - 1.0, 1.0 - 2.0 * position.y / WindowSize.y, 0.0, 1.0);
+    output.color = flag == FLAG_LARGE ? float4(1.0, 0.2, 0.2, 1.0) : float4(1.0, 0.6, 0.1, 1.0);
+
+    return output;
+}
+
+float4 ContrastPs(VertexOut input) : SV_Target {
+    return input.color;
+}
+
+#endif // GRAPHICS
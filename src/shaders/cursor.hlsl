@@ -0,0 +1,61 @@
+#include "common.hlsli"
+
+// the desktop under the window with the mouse pointer drawn in, the way it is on screen
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    int4 Rect;
+    // top-left of the shape on the desktop texture
+    int2 CursorPos;
+    uint Visible;
+    // alpha picks xor with the screen instead of a blend, see duplicate.rs
+    uint Masked;
+};
+
+Texture2D<float4> Cursor : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+};
+
+VertexOut CursorVs(uint id: SV_VertexID) {
+    static const float2 Positions[3] = {
+        float2(-1, +3),
+        float2(+3, -1),
+        float2(-1, -1),
+    };
+
+    VertexOut output;
+    output.position = float4(Positions[id], 0.0, 1.0);
+
+    return output;
+}
+
+float4 CursorPs(VertexOut input) : SV_Target {
+    int2 pixpos = Rect.xy + int2(input.position.xy);
+
+    float3 color;
+    bool visible = LoadDesktop(pixpos, Rect.xy, color);
+
+    uint width, height;
+    Cursor.GetDimensions(width, height);
+
+    int2 texel = pixpos - CursorPos;
+    if (Visible && all(texel >= 0) && all(texel < int2(width, height))) {
+        float4 cursor = Cursor.Load(int3(texel, 0));
+
+        if (!Masked) {
+            color = lerp(color, cursor.rgb, cursor.a);
+        } else if (cursor.a > 0.5) {
+            // xor, black leaves the screen alone and white inverts it
+            color = abs(saturate(color) - cursor.rgb);
+        } else {
+            color = cursor.rgb;
+        }
+    }
+
+    return float4(color, visible ? 1.0 : 0.0);
+}
+
+#endif // GRAPHICS
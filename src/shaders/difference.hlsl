@@ -0,0 +1,75 @@
+#include "common.hlsli"
+
+// the desktop under the window at the last two new frames and what changed between them
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    int4 Rect;
+    uint Mode;
+    float Threshold;
+};
+
+#define DIFFERENCE_HEATMAP 0
+#define DIFFERENCE_THRESHOLD 1
+
+// a change this large reaches white on the heat ramp
+#define FULL_HEAT 0.25
+
+Texture2D<float4> Current : register(t0);
+Texture2D<float4> Previous : register(t1);
+
+struct VertexOut {
+    float4 position : SV_Position;
+};
+
+VertexOut DifferenceVs(uint id: SV_VertexID) {
+    static const float2 Positions[3] = {
+        float2(-1, +3),
+        float2(+3, -1),
+        float2(-1, -1),
+    };
+
+    VertexOut output;
+    output.position = float4(Positions[id], 0.0, 1.0);
+
+    return output;
+}
+
+// our own overlay is taken out, alpha is 0 where it hides the desktop
+float4 DifferenceCapturePs(VertexOut input) : SV_Target {
+    uint2 pixpos = Rect.xy + uint2(input.position.xy);
+
+    float3 color;
+    bool visible = LoadDesktop(pixpos, Rect.xy, color);
+
+    return float4(color, visible ? 1.0 : 0.0);
+}
+
+// the largest channel change, unchanged pixels are left alone
+float4 DifferencePs(VertexOut input) : SV_Target {
+    float4 current = Current.Load(int3(input.position.xy, 0));
+    float4 previous = Previous.Load(int3(input.position.xy, 0));
+    if (current.a == 0.0 || previous.a == 0.0) {
+        discard;
+    }
+
+    float3 d = abs(current.rgb - previous.rgb);
+    float change = Max3(d.r, d.g, d.b);
+
+    switch (Mode) {
+        case DIFFERENCE_THRESHOLD:
+            if (change <= Threshold) {
+                discard;
+            }
+            return float4(1.0, 0.0, 1.0, 0.8);
+
+        default:
+            if (change <= 0.0) {
+                discard;
+            }
+            return float4(Heat(0.15 + 0.85 * saturate(change / FULL_HEAT)), 0.8);
+    }
+}
+
+#endif // GRAPHICS
@@ -1,90 +1,173 @@
-#include "common.hlsli"
-
-#ifdef GRAPHICS
-
-cbuffer Params : register(b0) {
-    int4 Rect;
-    int Mode;
-    float3 ColorMask;
-}
-
-#define FILTER_MODE_RGB 0
-#define FILTER_MODE_HUE 1
-#define FILTER_MODE_SAT 2
-#define FILTER_MODE_LUMA 3
-
-struct PsInput {
-    float4 position: SV_Position;
-};
-
-PsInput FilterVs(uint id: SV_VertexID) {
-    static const float2 Positions[3] = {
-        float2(-1, +3),
-        float2(+3, -1),
-        float2(-1, -1),
-    };
-    
-    PsInput output;
-    output.position = float4(Positions[id], 0.0, 1.0);
-
-    return output;
-}
-
-float3 remap(float3 v, float v_min, float v_max) {
-    return (v - v_min) / (v_max - v_min);
-}
-
-float4 FilterPs(float4 position: SV_Position) : SV_Target {
-    int2 pixpos = Rect.xy + int2(position.xy);
-    float3 rgb = Desktop[pixpos].rgb;
-
-    float3 out_color;
-
-    switch (Mode) {
-        case FILTER_MODE_RGB: {
-            out_color = ColorMask * rgb;
-
-            break;
-        }
-
-        case FILTER_MODE_HUE: {
-            float mi = Min3(rgb.r, rgb.g, rgb.b);
-            float ma = Max3(rgb.r, rgb.g, rgb.b);
-            float d = ma - mi;
-
-            if (d > 0.0) {
-                out_color = remap(rgb, mi, ma);
-            } else {
-                out_color = rgb;
-            }
-
-            break;
-        }
-
-        case FILTER_MODE_SAT: {
-            float mi = Min3(rgb.r, rgb.g, rgb.b);
-            float ma = Max3(rgb.r, rgb.g, rgb.b);
-            float l = 0.5 * (mi + ma);
-
-            if (l == 0.0 || l == 1.0) {
-                out_color = (float3) 0;
-            }else {
-                float s = (ma - mi) / (1.0 - abs(ma + mi - 1.0));
-                out_color = float3(s, s, s);
-            }
-
-            break;
-        }
-
-        case FILTER_MODE_LUMA: {
-            float l = Luma(rgb);
-            out_color = float3(l, l, l);
-
-            break;
-        }
-    }
-
-    return float4(out_color, 1.0);
-}
-
-#endif // GRAPHICS
+#include "common.hlsli"
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    int4 Rect;
+    int Mode;
+    float3 ColorMask;
+    uint Frame;
+    float ZebraHigh;
+    float ZebraLow;
+    // 0 and 1 leave the color as is
+    float LevelsBlack;
+    float LevelsWhite;
+    float Desaturation;
+    // the masked channels summed into gray
+    uint Solo;
+    // dithers the graded output in the dithered transparency mode, never for the scopes' copy
+    uint Dither;
+    // the scopes' copy takes our own overlay out like LoadDesktop and keeps where it hid the desktop
+    // in alpha
+    uint Masked;
+}
+
+#define FILTER_MODE_RGB 0
+#define FILTER_MODE_HUE 1
+#define FILTER_MODE_SAT 2
+#define FILTER_MODE_LUMA 3
+#define FILTER_MODE_ZEBRA 4
+#define FILTER_MODE_MONO 5
+#define FILTER_MODE_BLUE 6
+#define FILTER_MODE_RANGE 7
+
+#define ZEBRA_WIDTH 8.0
+
+struct PsInput {
+    float4 position: SV_Position;
+};
+
+PsInput FilterVs(uint id: SV_VertexID) {
+    static const float2 Positions[3] = {
+        float2(-1, +3),
+        float2(+3, -1),
+        float2(-1, -1),
+    };
+    
+    PsInput output;
+    output.position = float4(Positions[id], 0.0, 1.0);
+
+    return output;
+}
+
+float3 remap(float3 v, float v_min, float v_max) {
+    return (v - v_min) / (v_max - v_min);
+}
+
+// diagonal stripes moving one pixel per frame
+bool ZebraStripe(float2 position) {
+    return frac((position.x + position.y + Frame) / (2.0 * ZEBRA_WIDTH)) < 0.5;
+}
+
+float4 FilterPs(float4 position: SV_Position) : SV_Target {
+    int2 pixpos = Rect.xy + int2(position.xy);
+    float3 rgb;
+    bool visible = true;
+    if (Masked) {
+        visible = LoadDesktop(pixpos, Rect.xy, rgb);
+    } else {
+        // the copy with the pointer drawn in is window sized and already graded
+        rgb = SourceFiltered ? Desktop[pixpos - Rect.xy].rgb : DecodeTransfer(ApplyLut(Desktop[pixpos].rgb));
+    }
+    float3 source = rgb;
+    rgb = (rgb - LevelsBlack) / (LevelsWhite - LevelsBlack);
+
+    float3 out_color;
+
+    switch (Mode) {
+        case FILTER_MODE_RGB: {
+            out_color = Solo ? dot(ColorMask, rgb) : ColorMask * rgb;
+
+            break;
+        }
+
+        case FILTER_MODE_HUE: {
+            float mi = Min3(rgb.r, rgb.g, rgb.b);
+            float ma = Max3(rgb.r, rgb.g, rgb.b);
+            float d = ma - mi;
+
+            if (d > 0.0) {
+                out_color = remap(rgb, mi, ma);
+            } else {
+                out_color = rgb;
+            }
+
+            break;
+        }
+
+        case FILTER_MODE_SAT: {
+            float mi = Min3(rgb.r, rgb.g, rgb.b);
+            float ma = Max3(rgb.r, rgb.g, rgb.b);
+            float l = 0.5 * (mi + ma);
+
+            if (l == 0.0 || l == 1.0) {
+                out_color = (float3) 0;
+            }else {
+                float s = (ma - mi) / (1.0 - abs(ma + mi - 1.0));
+                out_color = float3(s, s, s);
+            }
+
+            break;
+        }
+
+        case FILTER_MODE_LUMA: {
+            float l = Luma(rgb);
+            out_color = float3(l, l, l);
+
+            break;
+        }
+
+        case FILTER_MODE_ZEBRA: {
+            float l = Luma(rgb);
+            out_color = rgb;
+
+            if (ZebraStripe(position.xy)) {
+                if (l > ZebraHigh) {
+                    out_color = float3(1.0, 0.0, 1.0);
+                } else if (l < ZebraLow) {
+                    out_color = float3(0.0, 0.5, 1.0);
+                }
+            }
+
+            break;
+        }
+
+        // like luma, but only part of the way when checking contrast against the colors
+        case FILTER_MODE_MONO: {
+            float l = Luma(rgb);
+            out_color = lerp(rgb, float3(l, l, l), Desaturation);
+
+            break;
+        }
+
+        // like a monitor's blue only mode, color bars line up when hue and saturation are right
+        case FILTER_MODE_BLUE: {
+            out_color = rgb.bbb;
+
+            break;
+        }
+
+        // zebra stripes where a channel of the source leaves [0, 1], like the stats counters
+        case FILTER_MODE_RANGE: {
+            out_color = rgb;
+
+            if (ZebraStripe(position.xy)) {
+                if (any(source > 1.0)) {
+                    out_color = float3(1.0, 0.0, 1.0);
+                } else if (any(source < 0.0)) {
+                    out_color = float3(0.0, 0.5, 1.0);
+                }
+            }
+
+            break;
+        }
+    }
+
+    if (Dither && LutLoaded()) {
+        out_color = DitherSrgb8(out_color, position.xy);
+    }
+
+    return float4(out_color, visible ? 1.0 : 0.0);
+}
+
+#endif // GRAPHICS
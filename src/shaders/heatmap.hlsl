@@ -0,0 +1,70 @@
+#include "common.hlsli"
+
+// the color cloud counters summed along one axis onto the rg, rb and gb planes
+
+#define SIZE 256
+#define THREAD 16
+
+#ifdef COMPUTE
+
+RWBuffer<uint> CountBuf : register(u0);
+RWBuffer<uint> Planes : register(u1);
+
+// one thread per plane cell, the plane index in z
+[numthreads(THREAD, THREAD, 1)]
+void HeatmapCs(uint3 id: SV_DispatchThreadID) {
+    uint sum = 0;
+
+    for (uint k = 0; k < SIZE; ++k) {
+        uint3 rgb;
+        switch (id.z) {
+        case 0: rgb = uint3(id.x, id.y, k); break;
+        case 1: rgb = uint3(id.x, k, id.y); break;
+        default: rgb = uint3(k, id.x, id.y); break;
+        }
+        sum += CountBuf[rgb.r | rgb.g << 8 | rgb.b << 16];
+    }
+
+    Planes[(id.z * SIZE + id.y) * SIZE + id.x] = CloudCount(sum);
+}
+
+#endif // COMPUTE
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    uint Plane;
+    float InvLogTotal;
+};
+
+Buffer<uint> Planes : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float2 uv : TEXCOORD;
+};
+
+VertexOut HeatmapVs(uint vid: SV_VertexID) {
+    float2 uv = float2(vid & 1, vid >> 1);
+
+    VertexOut output;
+    output.position = float4(2.0 * uv.x - 1.0, 1.0 - 2.0 * uv.y, 0.0, 1.0);
+    output.uv = uv;
+
+    return output;
+}
+
+// the first channel to the right, the second up
+float4 HeatmapPs(VertexOut input) : SV_Target {
+    uint2 cell = min(uint2(SIZE * input.uv.x, SIZE * (1.0 - input.uv.y)), SIZE - 1);
+    uint count = Planes[(Plane * SIZE + cell.y) * SIZE + cell.x];
+
+    if (count == 0) {
+        return float4(0.05, 0.05, 0.05, 0.8);
+    }
+
+    float density = log2(1.0 + count) * InvLogTotal;
+    return float4(Heat(0.15 + 0.85 * density), 1.0);
+}
+
+#endif // GRAPHICS
@@ -1,145 +1,387 @@
-#include "common.hlsli"
-
-#ifdef COMPUTE
-
-cbuffer Params : register(b0) {
-    int4 Rect;
-    uint Mode;
-    uint Ch;
-};
-
-#define MAX_CH 4
-RWBuffer<uint> HistogramBuf[MAX_CH] : register(u0);
-
-#define N_BINS 256
-groupshared uint bins[MAX_CH][N_BINS];
-
-#define SCALE 2
-#define THREAD_X 8
-#define THREADS (THREAD_X * THREAD_X)
-[numthreads(THREAD_X, THREAD_X, 1)]
-void HistogramCs(uint2 id: SV_DispatchThreadID, uint2 gid: SV_GroupID, uint tig: SV_GroupIndex) {
-    for (uint i = tig; i < N_BINS; i += THREADS) {
-        for (uint ch = 0; ch < Ch; ++ch) {
-            bins[ch][i] = 0;
-        }
-    }
-    
-    GroupMemoryBarrierWithGroupSync();
-
-    uint2 pixpos0 = Rect.xy + SCALE * id;
-    uint ibins[MAX_CH] = {0, 0, 0, 0};
-    uint counts[MAX_CH] = {1, 1, 1, 1};
-
-    for (uint y = 0; y < SCALE; ++y) {
-        for (uint x = 0; x < SCALE; ++x) {
-            uint2 pixpos = pixpos0 + uint2(x, y);
-            if (all(pixpos < Rect.zw)) {
-                float3 color = Desktop[pixpos].rgb;
-
-                switch (Mode) {
-                    case 0: // RGB
-                        ibins[0] = (N_BINS - 1) * color.g;
-                        ibins[1] = (N_BINS - 1) * color.r;
-                        ibins[2] = (N_BINS - 1) * color.b;
-                        break;
-
-                    case 1: // RGBL
-                        ibins[0] = (N_BINS - 1) * color.g;
-                        ibins[1] = (N_BINS - 1) * color.r;
-                        ibins[2] = (N_BINS - 1) * color.b;
-                        ibins[3] = (N_BINS - 1) * Luma(color);
-                        break;
-
-                    case 2: // Luma
-                        ibins[0] = (N_BINS - 1) * Luma(color);
-                        break;
-
-                    case 3: { // Hue 
-                        float3 hsl = RgbToHsl(color);
-                        ibins[0] = (N_BINS - 1) * hsl.x;
-                        counts[0] = hsl.y > 0 ? (127.0 * (0.5 * hsl.y + 0.5)) : 0;
-                        break;
-                    }
-                }
-
-                for (uint ch = 0; ch < Ch; ++ch) {
-                    uint i = ibins[ch];
-                    InterlockedAdd(bins[ch][i], counts[ch]);
-                }
-            }
-        }
-    }
-    
-    GroupMemoryBarrierWithGroupSync();
-    
-    for(uint i = tig; i < N_BINS; i += THREADS) {
-        for (uint ch = 0; ch < Ch; ++ch) {
-            uint count = bins[ch][i];
-            if (count > 0) {
-                InterlockedAdd(HistogramBuf[ch][i], count);
-            }
-        }
-    }
-}
-
-#endif // COMPUTE
-
-#ifdef GRAPHICS
-
-#define MAX_CH 4
-
-cbuffer Params : register(b0) {
-    float4 Colors[MAX_CH];
-    uint Mode;
-    float Scale;
-};
-
-Buffer<uint> HistogramBuf[MAX_CH] : register(t0);
-
-struct VertexOut {
-    float4 position : SV_Position;
-    float4 color : COLOR;
-};
-
-VertexOut HistogramVs(uint vid: SV_VertexID, uint iid: SV_InstanceID) {
-    uint index = vid / 2;
-    uint count = HistogramBuf[iid][index];
-    bool bottom = vid % 2 == 0;
-
-    float x = 2.f * (float(index) / 255.f) - 1.f;
-    float y = bottom ? -1.f : (Scale * count - 1.f);
-
-    VertexOut output;
-    output.position = float4(x, y, 0.f, 1.f);
-
-    switch (Mode) {
-        case 0: // RGB
-            output.color = Colors[iid];
-            break;
-
-        case 1: // RGBL
-            output.color = Colors[iid];
-            break;
-
-        case 2: // Luma
-            output.color = Colors[3];
-            break;
-
-        case 3: { // Hue 
-            float3 rgb = HslToRgb((float)index / 256.f, 1.0f, 0.5f);
-            output.color = float4(rgb, Colors[3].a);
-            break;
-        }
-    }
-
-    return output;
-}
-
-float4 HistogramPs(VertexOut input) : SV_Target {
-    float4 color = input.color;
-    color.rgb *= color.a;
-    return color;
-}
-
-#endif // GRAPHICS
+#include "common.hlsli"
+
+#ifdef COMPUTE
+
+cbuffer Params : register(b0) {
+    int4 Rect;
+    int2 Origin;
+    uint Mode;
+    uint Ch;
+    uint Hdr;
+    uint NumBins;
+    // for the history, log2(1 + pixels) inverted and whether the old columns are dropped
+    float InvLogTotal;
+    uint Reset;
+};
+
+#define MAX_CH 4
+
+// each pass sees only its own resources, so no register is bound twice in a pass
+#ifdef ENTRY_HistogramCs
+RWBuffer<uint> HistogramBuf[MAX_CH] : register(u0);
+#endif
+
+// largest bin count the menu offers, matches histogram.rs
+#define MAX_BINS 1024
+groupshared uint bins[MAX_CH][MAX_BINS];
+
+// a little above the most chromatic srgb primary, blue at about 134
+#define MAX_CHROMA 150.0
+
+#define SCALE 2
+#define THREAD_X 8
+#define THREADS (THREAD_X * THREAD_X)
+#ifdef ENTRY_HistogramCs
+[numthreads(THREAD_X, THREAD_X, 1)]
+void HistogramCs(uint2 id: SV_DispatchThreadID, uint2 gid: SV_GroupID, uint tig: SV_GroupIndex) {
+    for (uint i = tig; i < NumBins; i += THREADS) {
+        for (uint ch = 0; ch < Ch; ++ch) {
+            bins[ch][i] = 0;
+        }
+    }
+    
+    GroupMemoryBarrierWithGroupSync();
+
+    uint2 pixpos0 = Rect.xy + SCALE * id;
+    uint ibins[MAX_CH] = {0, 0, 0, 0};
+    uint counts[MAX_CH] = {1, 1, 1, 1};
+
+    for (uint y = 0; y < SCALE; ++y) {
+        for (uint x = 0; x < SCALE; ++x) {
+            uint2 pixpos = pixpos0 + uint2(x, y);
+            float3 color;
+            if (all(pixpos < Rect.zw) && LoadDesktop(pixpos, Origin, color)) {
+                // hdr bins are pq encoded nits, luma is taken before encoding
+                float3 value = Hdr ? EncodeHdr(color) : color;
+                float luma = Hdr ? NitsToPq(ScRgbToNits(max(ScRgbLuma(color), 0.0))).x : Luma(color);
+
+                switch (Mode) {
+                    case 0: // RGB
+                        ibins[0] = (NumBins - 1) * value.g;
+                        ibins[1] = (NumBins - 1) * value.r;
+                        ibins[2] = (NumBins - 1) * value.b;
+                        break;
+
+                    case 1: // RGBL
+                        ibins[0] = (NumBins - 1) * value.g;
+                        ibins[1] = (NumBins - 1) * value.r;
+                        ibins[2] = (NumBins - 1) * value.b;
+                        ibins[3] = (NumBins - 1) * luma;
+                        break;
+
+                    case 2: // Luma
+                        ibins[0] = (NumBins - 1) * luma;
+                        break;
+
+                    case 3: { // Hue 
+                        float3 hsl = RgbToHsl(color);
+                        ibins[0] = (NumBins - 1) * hsl.x;
+                        counts[0] = hsl.y > 0 ? (127.0 * (0.5 * hsl.y + 0.5)) : 0;
+                        break;
+                    }
+
+                    case 4: { // Saturation, hsv
+                        float ma = Max3(color.r, color.g, color.b);
+                        float mi = Min3(color.r, color.g, color.b);
+                        ibins[0] = (NumBins - 1) * (ma > 0.0 ? saturate((ma - mi) / ma) : 0.0);
+                        break;
+                    }
+
+                    case 5: { // Chroma, lab c*ab of the linear color
+                        float3 linear_rgb = max(color, 0.0);
+                        if (!Hdr) {
+                            linear_rgb = float3(SrgbToLinear(color.r), SrgbToLinear(color.g), SrgbToLinear(color.b));
+                        }
+                        float3 lab = XyzToLab(RgbToXyz(linear_rgb));
+                        ibins[0] = (NumBins - 1) * saturate(length(lab.yz) / MAX_CHROMA);
+                        break;
+                    }
+                }
+
+                for (uint ch = 0; ch < Ch; ++ch) {
+                    uint i = ibins[ch];
+                    InterlockedAdd(bins[ch][i], counts[ch]);
+                }
+            }
+        }
+    }
+    
+    GroupMemoryBarrierWithGroupSync();
+    
+    for(uint i = tig; i < NumBins; i += THREADS) {
+        for (uint ch = 0; ch < Ch; ++ch) {
+            uint count = bins[ch][i];
+            if (count > 0) {
+                uint before;
+                InterlockedAdd(HistogramBuf[ch][i], count, before);
+                // the tallest bin of all channels, one word past the first channel's bins
+                InterlockedMax(HistogramBuf[0][NumBins], before + count);
+            }
+        }
+    }
+}
+#endif
+
+#if defined(ENTRY_HistogramCullCs) || defined(ENTRY_HistogramDeltaCs)
+Buffer<uint> Bins[MAX_CH] : register(t0);
+#endif
+
+#ifdef ENTRY_HistogramCullCs
+RWBuffer<uint> DrawArgs : register(u0);
+
+groupshared uint nonempty[MAX_CH];
+
+[numthreads(MAX_BINS, 1, 1)]
+void HistogramCullCs(uint tid: SV_GroupThreadID) {
+    if (tid < MAX_CH) {
+        nonempty[tid] = 0;
+    }
+
+    GroupMemoryBarrierWithGroupSync();
+
+    for (uint ch = 0; ch < Ch && tid < NumBins; ++ch) {
+        if (Bins[ch][tid] > 0) {
+            InterlockedOr(nonempty[ch], 1);
+        }
+    }
+
+    GroupMemoryBarrierWithGroupSync();
+
+    if (tid < MAX_CH) { // D3D12_DRAW_ARGUMENTS per channel, empty channels get no instance
+        DrawArgs[4 * tid + 0] = 2 * NumBins;
+        DrawArgs[4 * tid + 1] = nonempty[tid];
+        DrawArgs[4 * tid + 2] = 0;
+        DrawArgs[4 * tid + 3] = 0;
+    }
+}
+#endif
+
+#ifdef ENTRY_HistogramDeltaCs
+RWBuffer<uint> PreviousBins : register(u0);
+RWBuffer<int> DeltaBins : register(u1);
+
+groupshared uint peak_delta;
+
+// change of each bin summed over the channels since the last frame, which it then replaces. the
+// largest change goes one word past the bins
+[numthreads(MAX_BINS, 1, 1)]
+void HistogramDeltaCs(uint tid: SV_GroupThreadID) {
+    if (tid == 0) {
+        peak_delta = 0;
+    }
+
+    GroupMemoryBarrierWithGroupSync();
+
+    if (tid < NumBins) {
+        uint sum = 0;
+        for (uint ch = 0; ch < Ch; ++ch) {
+            sum += Bins[ch][tid];
+        }
+
+        int delta = int(sum) - int(PreviousBins[tid]);
+        DeltaBins[tid] = delta;
+        PreviousBins[tid] = sum;
+        InterlockedMax(peak_delta, uint(abs(delta)));
+    }
+
+    GroupMemoryBarrierWithGroupSync();
+
+    if (tid == 0) {
+        DeltaBins[NumBins] = int(peak_delta);
+    }
+}
+#endif
+
+// frames kept by the history, one column each, matches histogram.rs
+#define HISTORY 512
+
+#ifdef ENTRY_HistogramScrollCs
+Buffer<uint> SummedBins : register(t0);
+Texture2D<float> History : register(t1);
+RWTexture2D<float> NextHistory : register(u0);
+
+// copies the history one column to the left and appends this frame's bins on the right, as log
+// densities like the cloud's heatmaps
+[numthreads(THREAD_X, THREAD_X, 1)]
+void HistogramScrollCs(uint2 id: SV_DispatchThreadID) {
+    if (id.x >= HISTORY || id.y >= NumBins) {
+        return;
+    }
+
+    float density = 0.0;
+    if (id.x == HISTORY - 1) {
+        density = saturate(log2(1.0 + float(SummedBins[id.y]) / float(Ch)) * InvLogTotal);
+    } else if (!Reset) {
+        density = History[uint2(id.x + 1, id.y)];
+    }
+
+    NextHistory[id] = density;
+}
+#endif
+
+#endif // COMPUTE
+
+#ifdef GRAPHICS
+
+#define MAX_CH 4
+
+cbuffer Params : register(b0) {
+    uint Mode;
+    float Scale;
+    uint Channel;
+    uint AutoScale;
+    uint NumBins;
+};
+
+cbuffer Palette : register(b1) {
+    float4 Colors[MAX_CH];
+};
+
+Buffer<uint> HistogramBuf[MAX_CH] : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float4 color : COLOR;
+};
+
+#define MARKER MAX_CH
+
+// auto scale leaves this much of the height above the tallest bin
+#define AUTO_HEADROOM 0.05f
+
+// tallest bin of the drawn channels in this frame, kept past the bins by HistogramCs
+uint PeakCount() {
+    return max(HistogramBuf[0][NumBins], 1);
+}
+
+// thin bar at the highest non-empty bin, the frame's peak (max-CLL in rgb modes)
+VertexOut MarkerVs(uint vid) {
+    uint n = Mode == 2 ? 1 : 3;
+    uint peak = 0;
+    for (uint i = 0; i < NumBins; ++i) {
+        for (uint ch = 0; ch < n; ++ch) {
+            if (HistogramBuf[ch][i] > 0) {
+                peak = i;
+            }
+        }
+    }
+
+    float x = 2.f * ((float(peak) + 0.5f * float(vid & 1)) / float(NumBins - 1)) - 1.f;
+    float y = (vid >> 1) ? 1.f : -1.f;
+
+    VertexOut output;
+    output.position = float4(x, y, 0.f, 1.f);
+    output.color = Colors[3];
+
+    return output;
+}
+
+VertexOut HistogramVs(uint vid: SV_VertexID) {
+    if (Channel == MARKER) {
+        return MarkerVs(vid);
+    }
+
+    uint index = vid / 2;
+    uint count = HistogramBuf[Channel][index];
+    bool bottom = vid % 2 == 0;
+
+    float scale = AutoScale ? 2.f * (1.f - AUTO_HEADROOM) / float(PeakCount()) : Scale;
+
+    float x = 2.f * (float(index) / float(NumBins - 1)) - 1.f;
+    float y = bottom ? -1.f : (scale * count - 1.f);
+
+    VertexOut output;
+    output.position = float4(x, y, 0.f, 1.f);
+
+    switch (Mode) {
+        case 0: // RGB
+            output.color = Colors[Channel];
+            break;
+
+        case 1: // RGBL
+            output.color = Colors[Channel];
+            break;
+
+        case 2: // Luma
+            output.color = Colors[3];
+            break;
+
+        case 3: { // Hue 
+            float3 rgb = HslToRgb((float)index / float(NumBins), 1.0f, 0.5f);
+            output.color = float4(rgb, Colors[3].a);
+            break;
+        }
+
+        default: { // Saturation and chroma, gray to a vivid orange
+            float3 rgb = lerp(0.6f, float3(1.0f, 0.45f, 0.1f), (float)index / float(NumBins - 1));
+            output.color = float4(rgb, Colors[3].a);
+            break;
+        }
+    }
+
+    return output;
+}
+
+Buffer<int> Delta : register(t0);
+Texture2D<float> History : register(t0);
+
+// one bar per bin from the middle of the viewport, 6 vertices each
+VertexOut HistogramDeltaVs(uint vid: SV_VertexID) {
+    static const uint2 Corners[6] = {
+        uint2(0, 0), uint2(1, 0), uint2(0, 1),
+        uint2(0, 1), uint2(1, 0), uint2(1, 1),
+    };
+
+    uint index = vid / 6;
+    uint2 corner = Corners[vid % 6];
+    int delta = Delta[index];
+
+    // the largest change, kept past the bins by HistogramDeltaCs
+    float scale = AutoScale ? (1.f - AUTO_HEADROOM) / float(max(Delta[NumBins], 1)) : Scale;
+
+    float x = 2.f * (float(index + corner.x) / float(NumBins)) - 1.f;
+    float y = corner.y ? clamp(scale * delta, -1.f, 1.f) : 0.f;
+
+    VertexOut output;
+    output.position = float4(x, y, 0.f, 1.f);
+    output.color = delta > 0 ? float4(0.2f, 1.f, 0.2f, 0.8f) : float4(1.f, 0.2f, 0.2f, 0.8f);
+
+    return output;
+}
+
+struct HistoryOut {
+    float4 position : SV_Position;
+    float2 uv : TEXCOORD;
+};
+
+HistoryOut HistogramHistoryVs(uint vid: SV_VertexID) {
+    float2 uv = float2(vid & 1, vid >> 1);
+
+    HistoryOut output;
+    output.position = float4(2.0 * uv.x - 1.0, 1.0 - 2.0 * uv.y, 0.0, 1.0);
+    output.uv = uv;
+
+    return output;
+}
+
+// time to the right, the newest frame at the edge, and bins upwards
+float4 HistogramHistoryPs(HistoryOut input) : SV_Target {
+    uint width, height;
+    History.GetDimensions(width, height);
+
+    uint2 texel = min(uint2(width * input.uv.x, height * (1.0 - input.uv.y)), uint2(width, height) - 1);
+    float density = History[texel];
+    if (density <= 0.0) {
+        discard;
+    }
+
+    return float4(Heat(0.15 + 0.85 * density), 0.9);
+}
+
+float4 HistogramPs(VertexOut input) : SV_Target {
+    float4 color = input.color;
+    color.rgb *= color.a;
+    return color;
+}
+
+#endif // GRAPHICS
@@ -0,0 +1,72 @@
+#include "common.hlsli"
+
+// hue across, luma up, as a density map
+#define N_BINS 256
+
+#ifdef COMPUTE
+
+cbuffer Params : register(b0) {
+    int4 Rect;
+    int2 Origin;
+    uint Hdr;
+};
+
+RWBuffer<uint> HueLumaBuf : register(u0);
+
+// below this chroma the hue is mostly noise
+#define MIN_CHROMA 0.02
+
+#define THREAD_X 8
+[numthreads(THREAD_X, THREAD_X, 1)]
+void HueLumaCs(uint2 id: SV_DispatchThreadID) {
+    uint2 pixpos = Rect.xy + id;
+    float3 color;
+    if (all(pixpos < Rect.zw) && LoadDesktop(pixpos, Origin, color)) {
+        float3 value = Hdr ? EncodeHdr(color) : saturate(color);
+        float chroma = max(value.r, max(value.g, value.b)) - min(value.r, min(value.g, value.b));
+
+        if (chroma >= MIN_CHROMA) {
+            float hue = RgbToHsl(value).x;
+            uint2 bin = (N_BINS - 1) * saturate(float2(hue, Luma(value)));
+            InterlockedAdd(HueLumaBuf[N_BINS * bin.y + bin.x], 1);
+        }
+    }
+}
+
+#endif // COMPUTE
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    float Extent;
+    float Scale;
+};
+
+Buffer<uint> HueLumaBuf : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float2 uv : TEXCOORD;
+};
+
+VertexOut HueLumaVs(uint vid: SV_VertexID) {
+    float2 uv = float2(vid & 1, vid >> 1);
+
+    VertexOut output;
+    output.position = float4(Extent * (2.0 * uv - 1.0), 0.0, 1.0);
+    output.uv = float2(uv.x, 1.0 - uv.y);
+
+    return output;
+}
+
+float4 HueLumaPs(VertexOut input) : SV_Target {
+    uint2 bin = min(N_BINS * input.uv, N_BINS - 1);
+    uint count = HueLumaBuf[N_BINS * bin.y + bin.x];
+
+    float intensity = 1.0 - exp(-Scale * count);
+    float3 color = HslToRgb(input.uv.x, 0.8, lerp(0.3, 0.9, input.uv.y));
+
+    return float4(color, intensity);
+}
+
+#endif // GRAPHICS
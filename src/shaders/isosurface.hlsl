@@ -0,0 +1,202 @@
+#include "common.hlsli"
+
+// the color cloud counters summed into a coarser density grid, its level set triangulated by
+// marching cubes with each cell split into six tetrahedra, which needs no case tables
+
+#define GRID 64
+#define BLOCK 4 // bins per grid cell along each axis
+#define THREAD 4
+
+#ifdef COMPUTE
+
+cbuffer Params : register(b0) {
+    float InvLogTotal;
+    float Level;
+    uint MaxTriangles;
+};
+
+RWBuffer<uint> CountBuf : register(u0);
+RWBuffer<float> Density : register(u1);
+RWStructuredBuffer<float3> Vertices : register(u2);
+RWBuffer<uint> DrawArgs : register(u3);
+
+uint GridIndex(uint3 cell) {
+    return (cell.z * GRID + cell.y) * GRID + cell.x;
+}
+
+// log scaled, 1 when every analyzed pixel falls into the cell
+[numthreads(THREAD, THREAD, THREAD)]
+void IsosurfaceDensityCs(uint3 id: SV_DispatchThreadID) {
+    uint3 base = BLOCK * id;
+    uint sum = 0;
+
+    for (uint z = 0; z < BLOCK; ++z) {
+        for (uint y = 0; y < BLOCK; ++y) {
+            for (uint x = 0; x < BLOCK; ++x) {
+                uint3 bin = base + uint3(x, y, z);
+                sum += CountBuf[bin.r | bin.g << 8 | bin.b << 16];
+            }
+        }
+    }
+
+    Density[GridIndex(id)] = log2(1.0 + CloudCount(sum)) * InvLogTotal;
+}
+
+// around the diagonal from corner 0 to 7, corner bits are x, y and z
+static const uint4 TETRAHEDRA[6] = {
+    uint4(0, 7, 1, 3), uint4(0, 7, 3, 2), uint4(0, 7, 2, 6),
+    uint4(0, 7, 6, 4), uint4(0, 7, 4, 5), uint4(0, 7, 5, 1),
+};
+
+float3 Corner(uint c) {
+    return float3(c & 1, (c >> 1) & 1, c >> 2);
+}
+
+// one vertex alone on its side of the level gives a triangle, two on each side a quad
+uint TetrahedronTriangles(uint inside) {
+    return inside == 2 ? 2 : inside & 1;
+}
+
+// where the level crosses the edge between two corners, as an rgb color
+float3 Crossing(uint3 cell, float density[8], uint a, uint b) {
+    float t = saturate((Level - density[a]) / (density[b] - density[a]));
+    float3 grid = cell + lerp(Corner(a), Corner(b), t);
+
+    // a grid cell's center sits in the middle of its bins
+    return (BLOCK * grid + 0.5 * (BLOCK - 1)) / 255.0;
+}
+
+void Emit(inout uint index, float3 a, float3 b, float3 c) {
+    if (index < MaxTriangles) {
+        Vertices[3 * index + 0] = a;
+        Vertices[3 * index + 1] = b;
+        Vertices[3 * index + 2] = c;
+    }
+    ++index;
+}
+
+[numthreads(THREAD, THREAD, THREAD)]
+void IsosurfaceMeshCs(uint3 id: SV_DispatchThreadID) {
+    bool valid = all(id < GRID - 1);
+
+    float density[8];
+    uint mask = 0;
+    for (uint c = 0; c < 8; ++c) {
+        density[c] = valid ? Density[GridIndex(id + uint3(Corner(c)))] : 0.0;
+        mask |= (density[c] >= Level ? 1u : 0u) << c;
+    }
+
+    uint triangles = 0;
+    if (mask != 0 && mask != 0xff) {
+        for (uint i = 0; i < 6; ++i) {
+            uint4 tetra = TETRAHEDRA[i];
+            uint inside = ((mask >> tetra.x) & 1) + ((mask >> tetra.y) & 1)
+                + ((mask >> tetra.z) & 1) + ((mask >> tetra.w) & 1);
+            triangles += TetrahedronTriangles(inside);
+        }
+    }
+
+    // reserved per wave like the cloud's compaction
+    uint prefix = WavePrefixSum(triangles);
+    uint triangles_in_wave = WaveActiveSum(triangles);
+
+    uint base;
+    if (WaveIsFirstLane()) {
+        InterlockedAdd(DrawArgs[0], 3 * triangles_in_wave, base);
+    }
+    uint index = WaveReadLaneFirst(base) / 3 + prefix;
+
+    if (triangles == 0) {
+        return;
+    }
+
+    for (uint i = 0; i < 6; ++i) {
+        uint4 tetra = TETRAHEDRA[i];
+        uint corners[4] = { tetra.x, tetra.y, tetra.z, tetra.w };
+
+        // inside corners first
+        uint sorted[4];
+        uint inside = 0;
+        uint outside = 3;
+        for (uint k = 0; k < 4; ++k) {
+            if ((mask >> corners[k]) & 1) {
+                sorted[inside++] = corners[k];
+            } else {
+                sorted[outside--] = corners[k];
+            }
+        }
+
+        if (inside == 1 || inside == 3) {
+            // the corner alone on its side and the three edges leaving it
+            uint alone = inside == 1 ? sorted[0] : sorted[3];
+            uint others[3] = {
+                inside == 1 ? sorted[1] : sorted[0],
+                inside == 1 ? sorted[2] : sorted[1],
+                inside == 1 ? sorted[3] : sorted[2],
+            };
+
+            Emit(index,
+                Crossing(id, density, alone, others[0]),
+                Crossing(id, density, alone, others[1]),
+                Crossing(id, density, alone, others[2]));
+        } else if (inside == 2) {
+            float3 ac = Crossing(id, density, sorted[0], sorted[2]);
+            float3 ad = Crossing(id, density, sorted[0], sorted[3]);
+            float3 bd = Crossing(id, density, sorted[1], sorted[3]);
+            float3 bc = Crossing(id, density, sorted[1], sorted[2]);
+
+            Emit(index, ac, ad, bd);
+            Emit(index, ac, bd, bc);
+        }
+    }
+}
+
+// D3D12_DRAW_ARGUMENTS, triangles that didn't fit aren't drawn
+[numthreads(1, 1, 1)]
+void IsosurfaceArgsCs() {
+    DrawArgs[0] = min(DrawArgs[0], 3 * MaxTriangles);
+    DrawArgs[1] = 1;
+}
+
+#endif // COMPUTE
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    float4x4 Projection;
+    float4x3 Rotation;
+    uint ColorSpace;
+};
+
+StructuredBuffer<float3> Vertices : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float3 color : COLOR;
+    float3 view : POSITION;
+};
+
+VertexOut IsosurfaceVs(uint vid: SV_VertexID) {
+    float3 rgb = Vertices[vid];
+
+    float3 position = CloudPosition(ColorSpace, rgb);
+
+    VertexOut vert;
+    vert.position = mul(float4(position, 1.0), Projection);
+    vert.color = rgb;
+    vert.view = mul(float4(position, 1.0), Rotation);
+
+    return vert;
+}
+
+// flat shaded from the derivatives of the rotated position, lit the same from both sides
+float4 IsosurfacePs(VertexOut input) : SV_Target {
+    static const float3 LIGHT = normalize(float3(-0.4, 0.6, -1.0));
+
+    float3 normal = normalize(cross(ddx(input.view), ddy(input.view)));
+    float diffuse = abs(dot(normal, LIGHT));
+
+    return float4(input.color * (0.35 + 0.65 * diffuse), 1.0);
+}
+
+#endif // GRAPHICS
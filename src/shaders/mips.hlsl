@@ -0,0 +1,53 @@
+#include "common.hlsli"
+
+#ifdef COMPUTE
+
+cbuffer Params : register(b0) {
+    uint2 Size;
+    uint Srgb;
+    // linear texels averaged as srgb encoded values, for comparison with the correct average
+    uint Naive;
+};
+
+Texture2D<float4> SrcMip : register(t0);
+RWTexture2D<float4> DstMip : register(u0);
+
+#define THREAD_X 8
+// 2x2 box filter, odd source edges are clamped to their last texel
+// srgb sources are decoded by their srv, the unorm uav needs them encoded again. with Srgb off
+// the values are averaged as read, linear or as stored
+float4 Fetch(uint2 position, uint2 last) {
+    float4 texel = SrcMip.Load(int3(min(position, last), 0));
+    if (Naive) {
+        texel.rgb = float3(LinearToSrgb(texel.r), LinearToSrgb(texel.g), LinearToSrgb(texel.b));
+    }
+    return texel;
+}
+
+[numthreads(THREAD_X, THREAD_X, 1)]
+void GenerateMipsCs(uint2 id: SV_DispatchThreadID) {
+    if (any(id >= Size)) {
+        return;
+    }
+
+    uint2 last;
+    SrcMip.GetDimensions(last.x, last.y);
+    last -= 1;
+
+    uint2 src = 2 * id;
+    float4 sum = Fetch(src, last)
+        + Fetch(src + uint2(1, 0), last)
+        + Fetch(src + uint2(0, 1), last)
+        + Fetch(src + uint2(1, 1), last);
+
+    float4 average = 0.25 * sum;
+    if (Srgb) {
+        average.rgb = float3(LinearToSrgb(average.r), LinearToSrgb(average.g), LinearToSrgb(average.b));
+    } else if (Naive) {
+        average.rgb = float3(SrgbToLinear(average.r), SrgbToLinear(average.g), SrgbToLinear(average.b));
+    }
+
+    DstMip[id] = average;
+}
+
+#endif // COMPUTE
@@ -0,0 +1,36 @@
+#include "common.hlsli"
+
+#ifdef GRAPHICS
+
+// the resolved layer, or the scene's depth for MsaaDepthPs
+Texture2D<float4> Resolved : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+};
+
+VertexOut MsaaCompositeVs(uint vid: SV_VertexID) {
+    float2 uv = float2(vid & 1, vid >> 1);
+
+    VertexOut output;
+    output.position = float4(2.0 * uv.x - 1.0, 1.0 - 2.0 * uv.y, 0.0, 1.0);
+
+    return output;
+}
+
+// the resolve averages coverage into alpha, so the edges stay premultiplied
+float4 MsaaCompositePs(VertexOut input) : SV_Target {
+    float4 color = Resolved.Load(int3(input.position.xy, 0));
+    if (color.a <= 0.0) {
+        discard;
+    }
+
+    return color;
+}
+
+// the scene's depth into every sample, so the color cloud still hides the grid behind it
+float MsaaDepthPs(VertexOut input) : SV_Depth {
+    return Resolved.Load(int3(input.position.xy, 0)).r;
+}
+
+#endif // GRAPHICS
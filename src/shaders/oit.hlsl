@@ -0,0 +1,35 @@
+#include "common.hlsli"
+
+#ifdef GRAPHICS
+
+Texture2D<float4> Accum : register(t0);
+Texture2D<float> Reveal : register(t1);
+
+struct VertexOut {
+    float4 position : SV_Position;
+};
+
+VertexOut OitCompositeVs(uint vid: SV_VertexID) {
+    float2 uv = float2(vid & 1, vid >> 1);
+
+    VertexOut output;
+    output.position = float4(2.0 * uv.x - 1.0, 1.0 - 2.0 * uv.y, 0.0, 1.0);
+
+    return output;
+}
+
+// weighted average of the layers, covering as much as they let through
+float4 OitCompositePs(VertexOut input) : SV_Target {
+    int3 pixel = int3(input.position.xy, 0);
+    float reveal = Reveal.Load(pixel);
+    if (reveal >= 1.0) {
+        discard;
+    }
+
+    float4 accum = Accum.Load(pixel);
+    float3 color = accum.rgb / max(accum.a, 1e-5);
+
+    return float4(color, 1.0 - reveal);
+}
+
+#endif // GRAPHICS
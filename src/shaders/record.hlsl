@@ -0,0 +1,50 @@
+#include "common.hlsli"
+
+// a recorded frame fitted into the encoder's size, the srgb target encodes it
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    // top-left and size of the recorded rect in the source
+    float4 Rect;
+    // the encoder's frame size
+    float2 Target;
+};
+
+// the output, or the desktop
+Texture2D<float4> Source : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+};
+
+VertexOut RecordVs(uint id: SV_VertexID) {
+    static const float2 Positions[3] = {
+        float2(-1, +3),
+        float2(+3, -1),
+        float2(-1, -1),
+    };
+
+    VertexOut output;
+    output.position = float4(Positions[id], 0.0, 1.0);
+
+    return output;
+}
+
+// a window resized while recording is scaled to keep its aspect, the bars are black
+float4 RecordPs(VertexOut input) : SV_Target {
+    float2 source;
+    Source.GetDimensions(source.x, source.y);
+
+    float scale = min(Target.x / Rect.z, Target.y / Rect.w);
+    float2 fitted = scale * Rect.zw;
+    float2 uv = (input.position.xy - 0.5 * (Target - fitted)) / fitted;
+    float3 color = Source.SampleLevel(LinearClamp, (Rect.xy + uv * Rect.zw) / source, 0).rgb;
+    if (any(uv < 0.0) || any(uv > 1.0)) {
+        return float4(0.0, 0.0, 0.0, 1.0);
+    }
+
+    return float4(color, 1.0);
+}
+
+#endif // GRAPHICS
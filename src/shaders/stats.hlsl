@@ -0,0 +1,126 @@
+#include "common.hlsli"
+
+// word offsets in the stats buffer, matches stats.rs
+#define MIN 0
+#define MAX 4
+#define SUM_LO 8
+#define SUM_HI 12
+#define COUNT 16
+#define PERCENTILES 17
+#define N_PERCENTILES 5
+#define OVER 22
+#define UNDER 23
+#define HISTOGRAM 32
+#define N_BINS 1024
+
+// sums are fixed point, carried into a second word
+#define FIXED 65536.0
+
+cbuffer Params : register(b0) {
+    int4 Rect;
+    int2 Origin;
+    uint Hdr;
+};
+
+RWBuffer<uint> Stats : register(u0);
+
+// non-negative floats order like their bits, the minimum is kept as the maximum of the inverted bits
+groupshared uint group_min[4];
+groupshared uint group_max[4];
+groupshared uint group_sum[4];
+groupshared uint group_count;
+groupshared uint group_over;
+groupshared uint group_under;
+
+#define SCALE 2
+#define THREAD_X 8
+[numthreads(THREAD_X, THREAD_X, 1)]
+void StatsCs(uint2 id: SV_DispatchThreadID, uint tig: SV_GroupIndex) {
+    if (tig < 4) {
+        group_min[tig] = 0;
+        group_max[tig] = 0;
+        group_sum[tig] = 0;
+    }
+    if (tig == 0) {
+        group_count = 0;
+        group_over = 0;
+        group_under = 0;
+    }
+
+    GroupMemoryBarrierWithGroupSync();
+
+    uint2 pixpos0 = Rect.xy + SCALE * id;
+    for (uint y = 0; y < SCALE; ++y) {
+        for (uint x = 0; x < SCALE; ++x) {
+            uint2 pixpos = pixpos0 + uint2(x, y);
+            float3 color;
+            if (all(pixpos < Rect.zw) && LoadDesktop(pixpos, Origin, color)) {
+                // the values the histogram bins, pq encoded nits in hdr
+                float3 value = Hdr ? EncodeHdr(color) : color;
+                float luma = Hdr ? NitsToPq(ScRgbToNits(max(ScRgbLuma(color), 0.0))).x : Luma(color);
+                float4 v = saturate(float4(value, luma));
+
+                for (uint ch = 0; ch < 4; ++ch) {
+                    InterlockedMax(group_min[ch], ~asuint(v[ch]));
+                    InterlockedMax(group_max[ch], asuint(v[ch]));
+                    InterlockedAdd(group_sum[ch], uint(FIXED * v[ch]));
+                }
+                InterlockedAdd(group_count, 1);
+                // before the clamp, a channel past either end of scrgb's sdr range
+                if (any(color > 1.0)) {
+                    InterlockedAdd(group_over, 1);
+                }
+                if (any(color < 0.0)) {
+                    InterlockedAdd(group_under, 1);
+                }
+                InterlockedAdd(Stats[HISTOGRAM + uint((N_BINS - 1) * v.a)], 1);
+            }
+        }
+    }
+
+    GroupMemoryBarrierWithGroupSync();
+
+    if (tig < 4) {
+        InterlockedMax(Stats[MIN + tig], group_min[tig]);
+        InterlockedMax(Stats[MAX + tig], group_max[tig]);
+
+        uint sum = group_sum[tig];
+        uint before;
+        InterlockedAdd(Stats[SUM_LO + tig], sum, before);
+        if (before + sum < before) {
+            InterlockedAdd(Stats[SUM_HI + tig], 1);
+        }
+    }
+    if (tig == 0) {
+        InterlockedAdd(Stats[COUNT], group_count);
+        InterlockedAdd(Stats[OVER], group_over);
+        InterlockedAdd(Stats[UNDER], group_under);
+    }
+}
+
+groupshared uint prefix[N_BINS];
+
+// luma percentiles as bin indices, from an inclusive scan of the luma histogram
+[numthreads(N_BINS, 1, 1)]
+void StatsPercentileCs(uint tid: SV_GroupThreadID) {
+    static const float RANKS[N_PERCENTILES] = { 0.01, 0.05, 0.5, 0.95, 0.99 };
+
+    prefix[tid] = Stats[HISTOGRAM + tid];
+    GroupMemoryBarrierWithGroupSync();
+
+    for (uint offset = 1; offset < N_BINS; offset <<= 1) {
+        uint add = tid >= offset ? prefix[tid - offset] : 0;
+        GroupMemoryBarrierWithGroupSync();
+        prefix[tid] += add;
+        GroupMemoryBarrierWithGroupSync();
+    }
+
+    uint total = prefix[N_BINS - 1];
+    uint below = tid > 0 ? prefix[tid - 1] : 0;
+    for (uint i = 0; i < N_PERCENTILES; ++i) {
+        uint rank = uint(RANKS[i] * total);
+        if (below <= rank && rank < prefix[tid]) {
+            Stats[PERCENTILES + i] = tid;
+        }
+    }
+}
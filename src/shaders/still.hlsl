@@ -0,0 +1,42 @@
+#include "common.hlsli"
+
+// an image file fitted into the window, the bars around it are left transparent
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    // top-left and size of the image in window pixels
+    float4 Fit;
+};
+
+Texture2D<float4> Image : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+};
+
+VertexOut StillVs(uint id: SV_VertexID) {
+    static const float2 Positions[3] = {
+        float2(-1, +3),
+        float2(+3, -1),
+        float2(-1, -1),
+    };
+
+    VertexOut output;
+    output.position = float4(Positions[id], 0.0, 1.0);
+
+    return output;
+}
+
+float4 StillPs(VertexOut input) : SV_Target {
+    float2 uv = (input.position.xy - Fit.xy) / Fit.zw;
+    // the mip follows the fitted scale, sampled before the branch so the derivatives are defined
+    float3 color = Image.Sample(LinearClamp, uv).rgb;
+    if (any(uv < 0.0) || any(uv > 1.0)) {
+        return 0.0;
+    }
+
+    return float4(DecodeTransfer(ApplyLut(color)), 1.0);
+}
+
+#endif // GRAPHICS
@@ -0,0 +1,40 @@
+#include "common.hlsli"
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    int2 Position;
+    uint2 Size;
+    uint2 Target;
+    float Opacity;
+};
+
+// gdi text cropped to Size, bgra so the channels come out in order
+Texture2D<float4> TextTex : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+};
+
+VertexOut TextVs(uint vid: SV_VertexID) {
+    float2 corner = float2(vid & 1, vid >> 1);
+    float2 pixel = Position + corner * Size;
+
+    VertexOut output;
+    output.position = float4(2.0 * pixel.x / Target.x - 1.0, 1.0 - 2.0 * pixel.y / Target.y, 0.0, 1.0);
+
+    return output;
+}
+
+// the background is dimmed, text is drawn opaque
+float4 TextPs(VertexOut input) : SV_Target {
+    uint2 pixel = uint2(input.position.xy) - Position;
+    float3 rgb = TextTex.Load(int3(pixel, 0)).rgb;
+
+    float coverage = Max3(rgb.r, rgb.g, rgb.b);
+    float3 color = coverage > 0.0 ? rgb / coverage : 0.0;
+
+    return float4(color, lerp(Opacity, 1.0, coverage));
+}
+
+#endif // GRAPHICS
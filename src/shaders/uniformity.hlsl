@@ -0,0 +1,111 @@
+#include "common.hlsli"
+
+// the analyzed region averaged over a grid of cells, luminance compared against the center cell
+
+#define THREAD 16
+
+// luminance deviation from the center drawn at full heat
+#define FULL_SCALE 0.2
+
+#ifdef COMPUTE
+
+cbuffer Params : register(b0) {
+    int4 Rect;
+    int2 Origin;
+    uint Grid;
+};
+
+// mean linear rgb and the share of the cell that was counted, 4 floats per cell
+RWBuffer<float> Cells : register(u0);
+
+groupshared float4 group_sum[THREAD * THREAD];
+
+// one group per cell, its threads stride over the cell's pixels
+[numthreads(THREAD, THREAD, 1)]
+void UniformityCs(uint2 cell: SV_GroupID, uint2 gtid: SV_GroupThreadID, uint tig: SV_GroupIndex) {
+    int2 size = Rect.zw - Rect.xy;
+    int2 lo = Rect.xy + size * int2(cell) / int(Grid);
+    int2 hi = Rect.xy + size * int2(cell + 1) / int(Grid);
+
+    float4 sum = 0.0;
+    for (int y = lo.y + gtid.y; y < hi.y; y += THREAD) {
+        for (int x = lo.x + gtid.x; x < hi.x; x += THREAD) {
+            float3 color;
+            if (LoadDesktop(uint2(x, y), Origin, color)) {
+                sum += float4(color, 1.0);
+            }
+        }
+    }
+
+    group_sum[tig] = sum;
+    GroupMemoryBarrierWithGroupSync();
+
+    for (uint stride = THREAD * THREAD / 2; stride > 0; stride >>= 1) {
+        if (tig < stride) {
+            group_sum[tig] += group_sum[tig + stride];
+        }
+        GroupMemoryBarrierWithGroupSync();
+    }
+
+    if (tig == 0) {
+        float4 total = group_sum[0];
+        float3 mean = total.rgb / max(total.a, 1.0);
+        int2 area = max(hi - lo, 1);
+
+        uint index = 4 * (cell.y * Grid + cell.x);
+        Cells[index + 0] = mean.r;
+        Cells[index + 1] = mean.g;
+        Cells[index + 2] = mean.b;
+        Cells[index + 3] = total.a / float(area.x * area.y);
+    }
+}
+
+#endif // COMPUTE
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    uint Grid;
+};
+
+Buffer<float> Cells : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float2 uv : TEXCOORD;
+};
+
+VertexOut UniformityVs(uint vid: SV_VertexID) {
+    float2 uv = float2(vid & 1, vid >> 1);
+
+    VertexOut output;
+    output.position = float4(2.0 * uv.x - 1.0, 1.0 - 2.0 * uv.y, 0.0, 1.0);
+    output.uv = uv;
+
+    return output;
+}
+
+float4 LoadCell(uint2 cell) {
+    uint index = 4 * (cell.y * Grid + cell.x);
+    return float4(Cells[index], Cells[index + 1], Cells[index + 2], Cells[index + 3]);
+}
+
+// cells laid out like the region, thin dark lines between them
+float4 UniformityPs(VertexOut input) : SV_Target {
+    float2 position = Grid * input.uv;
+    if (any(frac(position) < fwidth(position))) {
+        return float4(0.0, 0.0, 0.0, 0.8);
+    }
+
+    uint2 cell = min(uint2(position), Grid - 1);
+    float4 value = LoadCell(cell);
+    float center = Luma(LoadCell(Grid / 2).rgb);
+    if (value.a == 0.0 || center <= 0.0) {
+        return float4(0.05, 0.05, 0.05, 0.8);
+    }
+
+    float deviation = abs(Luma(value.rgb) / center - 1.0);
+    return float4(Heat(0.15 + 0.85 * saturate(deviation / FULL_SCALE)), 1.0);
+}
+
+#endif // GRAPHICS
@@ -0,0 +1,63 @@
+#include "common.hlsli"
+
+#define N_BINS 256
+
+#ifdef COMPUTE
+
+cbuffer Params : register(b0) {
+    int4 Rect;
+    int2 Origin;
+    uint Hdr;
+};
+
+RWBuffer<uint> VectorscopeBuf : register(u0);
+
+#define THREAD_X 8
+[numthreads(THREAD_X, THREAD_X, 1)]
+void VectorscopeCs(uint2 id: SV_DispatchThreadID) {
+    uint2 pixpos = Rect.xy + id;
+    float3 color;
+    if (all(pixpos < Rect.zw) && LoadDesktop(pixpos, Origin, color)) {
+        float3 yuv = RgbToYuv(Hdr ? EncodeHdr(color) : saturate(color));
+        uint2 bin = (N_BINS - 1) * saturate(yuv.yz);
+        InterlockedAdd(VectorscopeBuf[N_BINS * bin.y + bin.x], 1);
+    }
+}
+
+#endif // COMPUTE
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    float Extent;
+    float Scale;
+};
+
+Buffer<uint> VectorscopeBuf : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float2 uv : TEXCOORD;
+};
+
+VertexOut VectorscopeVs(uint vid: SV_VertexID) {
+    float2 uv = float2(vid & 1, vid >> 1);
+
+    VertexOut output;
+    output.position = float4(Extent * (2.0 * uv - 1.0), 0.0, 1.0);
+    output.uv = uv;
+
+    return output;
+}
+
+float4 VectorscopePs(VertexOut input) : SV_Target {
+    uint2 bin = min(N_BINS * input.uv, N_BINS - 1);
+    uint count = VectorscopeBuf[N_BINS * bin.y + bin.x];
+
+    float intensity = 1.0 - exp(-Scale * count);
+    float3 color = lerp(saturate(YuvToRgb(float3(0.6, input.uv))), 1.0, 0.3);
+
+    return float4(color, intensity);
+}
+
+#endif // GRAPHICS
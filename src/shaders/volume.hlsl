@@ -0,0 +1,94 @@
+#include "common.hlsli"
+
+// the color cloud counters ray marched as an emitting and absorbing volume
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    float4x4 InvProjection;
+    uint ColorSpace;
+    float InvLogTotal;
+    float RampLow;
+    float RampHigh;
+};
+
+Buffer<uint> CountBuf : register(t0);
+
+// samples per cube edge and extinction per unit of length at full opacity
+#define STEPS 256
+#define EXTINCTION 24.0
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float2 ndc : TEXCOORD;
+};
+
+VertexOut VolumeVs(uint vid: SV_VertexID) {
+    float2 uv = float2(vid & 1, vid >> 1);
+
+    VertexOut output;
+    output.ndc = float2(2.0 * uv.x - 1.0, 1.0 - 2.0 * uv.y);
+    output.position = float4(output.ndc, 0.0, 1.0);
+
+    return output;
+}
+
+// the transfer function, a linear ramp over the log scaled count
+float Opacity(float3 rgb) {
+    uint3 bin = (uint3)min(255.0 * rgb + 0.5, 255.0);
+    uint count = CloudCount(CountBuf[bin.r | bin.g << 8 | bin.b << 16]);
+
+    float density = log2(1.0 + count) * InvLogTotal;
+    return saturate((density - RampLow) / max(RampHigh - RampLow, 1e-3));
+}
+
+float InterleavedGradientNoise(float2 pixel) {
+    return frac(52.9829189 * frac(dot(pixel, float2(0.06711056, 0.00583715))));
+}
+
+float4 VolumePs(VertexOut input) : SV_Target {
+    // a ray from the near to the far plane, diverging in perspective
+    float4 near = mul(float4(input.ndc, 0.0, 1.0), InvProjection);
+    float4 far = mul(float4(input.ndc, 1.0, 1.0), InvProjection);
+    float3 origin = near.xyz / near.w;
+    float3 dir = far.xyz / far.w - origin;
+
+    // clipped to the box around the color space
+    float extent = CloudExtent(ColorSpace);
+    float3 t0 = (-extent - origin) / dir;
+    float3 t1 = (extent - origin) / dir;
+    float enter = max(Max3(min(t0.x, t1.x), min(t0.y, t1.y), min(t0.z, t1.z)), 0.0);
+    float leave = min(Min3(max(t0.x, t1.x), max(t0.y, t1.y), max(t0.z, t1.z)), 1.0);
+    if (enter >= leave) {
+        discard;
+    }
+
+    float spacing = 2.0 * extent / STEPS;
+    float dt = spacing / length(dir);
+    float alpha = 1.0 - exp(-EXTINCTION * spacing);
+
+    // jittered per pixel so the slices don't band
+    float t = enter + dt * InterleavedGradientNoise(input.position.xy);
+
+    float3 color = 0.0;
+    float transmittance = 1.0;
+
+    [loop]
+    for (; t < leave && transmittance > 0.01; t += dt) {
+        float3 position = origin + t * dir;
+
+        bool valid;
+        float3 rgb = CloudPositionToRgb(ColorSpace, position, valid);
+
+        if (valid) {
+            float a = alpha * Opacity(saturate(rgb));
+            color += transmittance * a * rgb;
+            transmittance *= 1.0 - a;
+        }
+    }
+
+    // premultiplied
+    return float4(color, 1.0 - transmittance);
+}
+
+#endif // GRAPHICS
@@ -0,0 +1,97 @@
+#include "common.hlsli"
+
+#define N_COLUMNS 256
+#define N_LEVELS 256
+
+#define WAVEFORM_LAYOUT_COMPOSITE 0
+#define WAVEFORM_LAYOUT_PARADE 1
+
+// bins are [channel][level][column], channels r, g, b
+uint BinIndex(uint channel, uint level, uint column) {
+    return (channel * N_LEVELS + level) * N_COLUMNS + column;
+}
+
+#ifdef COMPUTE
+
+cbuffer Params : register(b0) {
+    int4 Rect;
+    int2 Origin;
+    uint Columns;
+    uint Hdr;
+};
+
+RWBuffer<uint> WaveformBuf : register(u0);
+
+#define THREAD_X 8
+[numthreads(THREAD_X, THREAD_X, 1)]
+void WaveformCs(uint2 id: SV_DispatchThreadID) {
+    uint2 pixpos = Rect.xy + id;
+    float3 color;
+    if (all(pixpos < Rect.zw) && LoadDesktop(pixpos, Origin, color)) {
+        float3 value = Hdr ? EncodeHdr(color) : saturate(color);
+        uint3 level = (N_LEVELS - 1) * value;
+        uint column = id.x * Columns / (Rect.z - Rect.x);
+
+        [unroll]
+        for (uint ch = 0; ch < 3; ++ch) {
+            InterlockedAdd(WaveformBuf[BinIndex(ch, level[ch], column)], 1);
+        }
+    }
+}
+
+#endif // COMPUTE
+
+#ifdef GRAPHICS
+
+cbuffer Params : register(b0) {
+    uint Columns;
+    uint Layout;
+    float Scale;
+};
+
+Buffer<uint> WaveformBuf : register(t0);
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float2 uv : TEXCOORD;
+};
+
+VertexOut WaveformVs(uint vid: SV_VertexID) {
+    float2 uv = float2(vid & 1, vid >> 1);
+
+    VertexOut output;
+    output.position = float4(2.0 * uv - 1.0, 0.0, 1.0);
+    output.uv = uv;
+
+    return output;
+}
+
+float Intensity(uint channel, uint level, float x) {
+    uint column = min(Columns * x, Columns - 1);
+    return 1.0 - exp(-Scale * WaveformBuf[BinIndex(channel, level, column)]);
+}
+
+float4 WaveformPs(VertexOut input) : SV_Target {
+    uint level = min(N_LEVELS * input.uv.y, N_LEVELS - 1);
+
+    float3 trace;
+    if (Layout == WAVEFORM_LAYOUT_PARADE) {
+        // r, g and b side by side, each in a third of the width
+        uint ch = min(3.0 * input.uv.x, 2.0);
+        trace = 0.0;
+        trace[ch] = Intensity(ch, level, frac(3.0 * input.uv.x));
+    } else {
+        trace = float3(
+            Intensity(0, level, input.uv.x),
+            Intensity(1, level, input.uv.x),
+            Intensity(2, level, input.uv.x));
+    }
+
+    // the graticule is drawn as lines afterwards
+    float alpha = Max3(trace.r, trace.g, trace.b);
+    float3 color = alpha > 0.0 ? trace / alpha : 0.0;
+
+    return float4(color, alpha);
+}
+
+#endif // GRAPHICS
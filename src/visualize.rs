@@ -1,132 +1,808 @@
-mod colorcloud;
-mod filter;
-mod grid;
-mod histogram;
-
-use std::{
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
-    },
-    thread::JoinHandle,
-    time::Duration,
-};
-
-use anyhow::Result;
-use colorcloud::ColorCloud;
-use filter::Filter;
-use grid::Grids;
-use histogram::Histogram;
-use windows::Win32::Foundation::HWND;
-
-use crate::{
-    config::Config,
-    graphics::{context::Context, duplicate::Duplication},
-    gui::utils::Rect,
-};
-
-pub struct Visualizer {
-    keep_running: Arc<AtomicBool>,
-    join_handle: Option<JoinHandle<()>>,
-}
-
-impl Visualizer {
-    pub fn new(hwnd: HWND, config: Arc<Mutex<Config>>) -> Result<Self> {
-        let mut pipeline = Pipeline::new(hwnd)?;
-
-        let keep_running = Arc::new(AtomicBool::new(true));
-        let keep_running2 = Arc::clone(&keep_running);
-
-        let join_handle = std::thread::spawn(move || {
-            while keep_running2.load(Ordering::Relaxed) {
-                let config = if let Ok(config) = config.lock() {
-                    config.to_owned()
-                } else {
-                    break;
-                };
-
-                if let Err(e) = pipeline.process(config) {
-                    println!("{e:?}");
-                    break;
-                }
-            }
-        });
-
-        Ok(Self {
-            keep_running,
-            join_handle: Some(join_handle),
-        })
-    }
-
-    pub fn terminate(&mut self) {
-        self.keep_running.store(false, Ordering::Relaxed);
-
-        if let Some(join_handle) = self.join_handle.take() {
-            _ = join_handle.join();
-        }
-    }
-}
-
-impl Drop for Visualizer {
-    fn drop(&mut self) {
-        self.terminate();
-    }
-}
-
-struct Pipeline {
-    ctx: Context,
-    dupl: Duplication,
-    colorcloud: ColorCloud,
-    filter: Filter,
-    histogram: Histogram,
-    grids: Grids,
-}
-
-impl Pipeline {
-    fn new(hwnd: HWND) -> Result<Self> {
-        let mut ctx = Context::new(hwnd)?;
-        let mut initializer = ctx.create_initializer()?;
-
-        let dupl = Duplication::new(&mut initializer)?;
-        let colorcloud = ColorCloud::new(&mut initializer)?;
-        let filter = Filter::new(&mut initializer)?;
-        let histogram = Histogram::new(&mut initializer)?;
-        let grids = Grids::new(&mut initializer)?;
-
-        Ok(Self {
-            ctx,
-            dupl,
-            colorcloud,
-            filter,
-            histogram,
-            grids,
-        })
-    }
-
-    fn process(&mut self, config: Config) -> Result<()> {
-        let srv = if let Some(srv) = self.dupl.duplicate(&self.ctx)? {
-            srv
-        } else {
-            std::thread::sleep(Duration::from_millis(10));
-            return Ok(());
-        };
-
-        let opacity = 1.0 - config.bg_opacity;
-        let mut renderer = self.ctx.create_renderer(
-            config.window_rect.width() as _,
-            config.window_rect.height() as _,
-            &[0.0, 0.0, 0.0, opacity],
-        )?;
-
-        renderer.set_shared_srv(srv);
-
-        self.filter.process(&mut renderer, &config)?;
-        self.colorcloud.process(&mut renderer, &config)?;
-        self.grids.process(&mut renderer, &config)?;
-        self.histogram.process(&mut renderer, &config)?;
-
-        self.ctx.execute(renderer)?;
-
-        Ok(())
-    }
-}
+mod alert;
+mod background;
+mod calibration;
+mod color;
+mod colorcloud;
+mod contrast;
+mod cursor;
+mod ddc;
+mod diagnostics;
+mod difference;
+mod export;
+mod eyedropper;
+mod filter;
+mod governor;
+mod grid;
+mod heatmap;
+mod histogram;
+mod hueluma;
+mod isosurface;
+mod legend;
+mod lut;
+mod msaa;
+mod oit;
+mod outputs;
+mod pacing;
+mod ramp;
+mod receiver;
+mod record;
+mod report;
+mod roi;
+mod sampling;
+mod screenshot;
+mod selfmask;
+mod session;
+mod shared;
+mod stats;
+mod still;
+mod text;
+mod uniformity;
+mod vectorscope;
+mod video;
+mod volume;
+mod waveform;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use alert::{Alerts, Observation};
+use anyhow::Result;
+use background::{premultiplied, Background};
+use calibration::Calibration;
+use colorcloud::ColorCloud;
+use contrast::ContrastScanner;
+use cursor::CursorLayer;
+use ddc::MonitorControl;
+use diagnostics::Diagnostics;
+use difference::FrameDifference;
+use eyedropper::Eyedropper;
+use filter::Filter;
+use governor::Governor;
+use grid::Grids;
+use histogram::Histogram;
+use hueluma::HueLuma;
+use legend::Legend;
+use lut::Lut;
+use msaa::MsaaLayer;
+use outputs::OutputCompare;
+use pacing::FramePacing;
+use ramp::RampGamma;
+use receiver::SharedReceiver;
+use record::{RecordPass, Recorder};
+use report::Reporter;
+use roi::RoiOutline;
+use screenshot::Screenshots;
+use selfmask::SelfMask;
+use session::Session;
+use shared::SharedStats;
+use stats::{FrameStats, Stats};
+use still::StillImage;
+use uniformity::UniformityMap;
+use vectorscope::Vectorscope;
+use video::VideoPlayer;
+use waveform::Waveform;
+use windows::Win32::{
+    Foundation::{HWND, RECT},
+    System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED},
+};
+
+use crate::{
+    config::{Config, ALERT_LOW_CONTRAST, SOURCE_FILE, SOURCE_SHARED, SOURCE_VIDEO},
+    graphics::{
+        context::Context,
+        duplicate::Duplication,
+        renderer::{msaa_index, MSAA_SAMPLES},
+    },
+    gui::utils::Rect,
+};
+
+pub struct Visualizer {
+    keep_running: Arc<AtomicBool>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl Visualizer {
+    pub fn new(hwnd: HWND, config: Arc<Mutex<Config>>) -> Result<Self> {
+        let monitor = config.lock().map_or(0, |config| config.monitor);
+        let mut pipeline = Pipeline::new(hwnd, monitor)?;
+
+        let keep_running = Arc::new(AtomicBool::new(true));
+        let keep_running2 = Arc::clone(&keep_running);
+
+        let join_handle = std::thread::spawn(move || {
+            // images, videos, recordings and exports use wic and media foundation from here
+            let com = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+
+            while keep_running2.load(Ordering::Relaxed) {
+                let start = Instant::now();
+
+                // skipped frames leave no scopes behind
+                let profiler = pipeline.ctx.profiler();
+                profiler.reset();
+                profiler.begin("config lock");
+                let config = if let Ok(config) = config.lock() {
+                    config.to_owned()
+                } else {
+                    break;
+                };
+                profiler.end();
+                let interval = config.frame_interval();
+
+                if let Err(e) = pipeline.process(config) {
+                    println!("{e:?}");
+                    break;
+                }
+
+                // sleep off what is left of the frame budget
+                if let Some(rest) = interval.and_then(|i| i.checked_sub(start.elapsed())) {
+                    std::thread::sleep(rest);
+                }
+            }
+
+            // the com objects are released before com is
+            drop(pipeline);
+            if com.is_ok() {
+                unsafe { CoUninitialize() };
+            }
+        });
+
+        Ok(Self {
+            keep_running,
+            join_handle: Some(join_handle),
+        })
+    }
+
+    pub fn terminate(&mut self) {
+        self.keep_running.store(false, Ordering::Relaxed);
+
+        if let Some(join_handle) = self.join_handle.take() {
+            _ = join_handle.join();
+        }
+    }
+}
+
+impl Drop for Visualizer {
+    fn drop(&mut self) {
+        self.terminate();
+    }
+}
+
+// sessions sample once a second, so a hidden window logs no more often than that
+const HIDDEN_LOG_INTERVAL: Duration = Duration::from_secs(1);
+
+// the Shared cbuffer in common.hlsli
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SharedParams {
+    source_filtered: u32,
+    primaries: u32,
+    transfer: u32,
+    source_hdr: u32,
+}
+
+struct Pipeline {
+    ctx: Context,
+    dupl: Duplication,
+    background: Background,
+    colorcloud: ColorCloud,
+    cursor: CursorLayer,
+    still: StillImage,
+    video: VideoPlayer,
+    receiver: SharedReceiver,
+    filter: Filter,
+    difference: FrameDifference,
+    histogram: Histogram,
+    grids: Grids,
+    vectorscope: Vectorscope,
+    hue_luma: HueLuma,
+    waveform: Waveform,
+    stats: Stats,
+    uniformity: UniformityMap,
+    contrast: ContrastScanner,
+    pacing: FramePacing,
+    alerts: Alerts,
+    legend: Legend,
+    diagnostics: Diagnostics,
+    lut: Lut,
+    msaa: MsaaLayer,
+    governor: Governor,
+    eyedropper: Eyedropper,
+    ramp: RampGamma,
+    calibration: Calibration,
+    roi: RoiOutline,
+    selfmask: SelfMask,
+    // the monitor the stats are shared for, without a block when another instance has it
+    shared: Option<(u32, Option<SharedStats>)>,
+    reporter: Option<Reporter>,
+    session: Option<Session>,
+    recorder: Option<Recorder>,
+    record_pass: RecordPass,
+    screenshots: Screenshots,
+    monitor: MonitorControl,
+    outputs: Option<OutputCompare>,
+    export_request: u32,
+    bookmark_request: u32,
+    session_export_request: u32,
+    capture_screenshot_request: u32,
+    output_screenshot_request: u32,
+    calibrate_request: u32,
+    // read from the last frame's histogram, previewed and listed in the next one
+    levels: Option<[f32; 2]>,
+    // the settings of the last analyzed frame
+    analyzed: Option<Config>,
+    idle: Option<(RECT, u32, u32, u32, u32)>,
+    // when the last statistics were logged while the window was hidden
+    logged_hidden: Instant,
+}
+
+impl Pipeline {
+    fn new(hwnd: HWND, monitor: u32) -> Result<Self> {
+        let mut ctx = Context::new(hwnd)?;
+        let mut initializer = ctx.create_initializer()?;
+
+        let dupl = Duplication::new(&mut initializer, monitor).or_else(|e| {
+            println!("output {monitor}: {e:?}");
+            Duplication::new(&mut initializer, 0)
+        })?;
+        let background = Background::new(&mut initializer)?;
+        let colorcloud = ColorCloud::new(&mut initializer)?;
+        let cursor = CursorLayer::new(&mut initializer)?;
+        let still = StillImage::new(&mut initializer)?;
+        let filter = Filter::new(&mut initializer)?;
+        let difference = FrameDifference::new(&mut initializer)?;
+        let histogram = Histogram::new(&mut initializer)?;
+        let grids = Grids::new(&mut initializer)?;
+        let vectorscope = Vectorscope::new(&mut initializer)?;
+        let hue_luma = HueLuma::new(&mut initializer)?;
+        let waveform = Waveform::new(&mut initializer)?;
+        let stats = Stats::new(&mut initializer)?;
+        let uniformity = UniformityMap::new(&mut initializer)?;
+        let contrast = ContrastScanner::new(&mut initializer)?;
+        let pacing = FramePacing::new(&mut initializer)?;
+        let alerts = Alerts::new(&mut initializer)?;
+        let legend = Legend::new(&mut initializer)?;
+        let diagnostics = Diagnostics::new(&mut initializer)?;
+        let lut = Lut::new(&mut initializer)?;
+        let msaa = MsaaLayer::new(&mut initializer)?;
+        let eyedropper = Eyedropper::new(&mut initializer)?;
+        let calibration = Calibration::new(&mut initializer, hwnd)?;
+        let roi = RoiOutline::new(&mut initializer)?;
+        let selfmask = SelfMask::new(&mut initializer)?;
+        let record_pass = RecordPass::new(&mut initializer)?;
+
+        Ok(Self {
+            ctx,
+            dupl,
+            background,
+            colorcloud,
+            cursor,
+            still,
+            video: VideoPlayer::new(hwnd),
+            receiver: SharedReceiver::new(),
+            filter,
+            difference,
+            histogram,
+            grids,
+            vectorscope,
+            hue_luma,
+            waveform,
+            stats,
+            uniformity,
+            contrast,
+            pacing,
+            alerts,
+            legend,
+            diagnostics,
+            lut,
+            msaa,
+            governor: Governor::new(),
+            eyedropper,
+            ramp: RampGamma::new(),
+            calibration,
+            roi,
+            selfmask,
+            shared: None,
+            reporter: None,
+            session: None,
+            recorder: None,
+            record_pass,
+            screenshots: Screenshots::default(),
+            monitor: MonitorControl::new(hwnd),
+            outputs: None,
+            export_request: 0,
+            bookmark_request: 0,
+            session_export_request: 0,
+            capture_screenshot_request: 0,
+            output_screenshot_request: 0,
+            calibrate_request: 0,
+            levels: None,
+            analyzed: None,
+            idle: None,
+            logged_hidden: Instant::now(),
+        })
+    }
+
+    fn process(&mut self, mut config: Config) -> Result<()> {
+        if config.pause_when_occluded && config.occluded {
+            // nobody sees the drawing, but the logs can go on at their own pace
+            let log = config.log_when_occluded && config.is_logging();
+            if !log || self.logged_hidden.elapsed() < HIDDEN_LOG_INTERVAL {
+                std::thread::sleep(Duration::from_millis(100));
+                return Ok(());
+            }
+            self.logged_hidden = Instant::now();
+            config.hide_overlays();
+        }
+
+        if config.calibrate_request != self.calibrate_request {
+            self.calibrate_request = config.calibrate_request;
+            self.calibration.start();
+        }
+
+        // with nothing enabled a cleared frame is presented once, again only on resize or background changes
+        let idle = (config.is_idle() && !self.calibration.active()).then_some((
+            config.window_rect,
+            config.bg_opacity.to_bits(),
+            config.bg_mode,
+            config.bg_color,
+            config.clear_color,
+        ));
+        if idle.is_some() && idle == self.idle {
+            std::thread::sleep(Duration::from_millis(100));
+            return Ok(());
+        }
+
+        // each new frame is copied once, a frozen desktop or one without updates is analyzed
+        // again from the copy. changed settings and a spinning view don't wait for the next
+        // desktop update
+        let changed =
+            self.analyzed.as_ref() != Some(&config) || config.camera.spinning(Instant::now());
+        // an image file, a video or a shared texture stands in for the desktop until it fails to
+        // load. a video or a sender is closed when another source is picked and starts over when
+        // it is picked again
+        if config.capture_source != SOURCE_VIDEO {
+            self.video.close();
+        }
+        if config.capture_source != SOURCE_SHARED {
+            self.receiver.close();
+        }
+        let still = match config.capture_source {
+            SOURCE_VIDEO => self.video.update(&config, &mut self.still),
+            SOURCE_SHARED => self.receiver.update(&self.ctx, &config, &mut self.still),
+            source => source == SOURCE_FILE && self.still.update(&config),
+        };
+        let cached = if still {
+            Some((self.still.srv(), self.still.take_fresh()))
+        } else {
+            let cached = config.freeze.then(|| self.dupl.reuse()).flatten();
+            cached.map(|srv| (srv, false))
+        };
+        let srv = match cached {
+            Some(cached) => {
+                self.pacing.update(&config, None);
+                Some(cached)
+            }
+            None => {
+                let timeout = if changed && config.reuse_frames {
+                    0
+                } else {
+                    config.capture_timeout
+                };
+                self.ctx.profiler().begin("duplication");
+                let srv = self.dupl.duplicate(&self.ctx, timeout)?;
+                self.ctx.profiler().end();
+                self.pacing.update(&config, self.dupl.present());
+                match srv {
+                    // a moved pointer redraws the last frame when it is drawn in
+                    None if config.reuse_frames
+                        || (config.include_cursor && self.dupl.pointer().moved) =>
+                    {
+                        self.dupl.reuse().map(|srv| (srv, false))
+                    }
+                    srv => srv.map(|srv| (srv, true)),
+                }
+            }
+        };
+
+        // fresh frames are the ones the desktop actually updated
+        let (srv, fresh) = if let Some(srv) = srv {
+            srv
+        } else {
+            std::thread::sleep(Duration::from_millis(10));
+            return Ok(());
+        };
+
+        self.analyzed = Some(config.clone());
+
+        // the passes address the desktop texture, which starts at the captured output's corner
+        let desktop_rect = config.window_rect;
+        let (x, y) = self.dupl.origin();
+        config.window_rect = RECT::new(
+            desktop_rect.left - x,
+            desktop_rect.top - y,
+            desktop_rect.width(),
+            desktop_rect.height(),
+        );
+
+        // auto transparency is resolved here so the passes only see blend or dither
+        let start = Instant::now();
+        config.transparency_mode = self.governor.transparency_mode(&config);
+        config.msaa_samples = MSAA_SAMPLES[msaa_index(config.msaa_samples)];
+
+        let suggest = config.suggest_levels && config.enable_histogram;
+        config.suggested_levels = self.levels.filter(|_| suggest);
+        config.estimated_gamma = self.ramp.gamma().filter(|_| config.estimate_gamma);
+        config.out_of_range = self
+            .stats
+            .latest()
+            .map(FrameStats::out_of_range)
+            .filter(|_| config.count_out_of_range);
+        config.output_difference = self
+            .outputs
+            .as_ref()
+            .and_then(OutputCompare::comparison)
+            .map(|outputs| {
+                let [r, g, b] = outputs.delta;
+                [r, g, b, outputs.delta_e]
+            });
+        config.video_time = self.video.time().filter(|_| still);
+
+        let export = config.export_request != self.export_request;
+        self.export_request = config.export_request;
+
+        if !config.enable_reports {
+            self.reporter = None;
+        } else if self.reporter.is_none() {
+            self.reporter = Some(Reporter::new(export::EXPORT_DIR));
+        }
+        let report = self
+            .reporter
+            .as_mut()
+            .is_some_and(|reporter| reporter.due(&config));
+
+        // ending a session writes what it recorded
+        if !config.enable_session {
+            self.session = None;
+        } else if self.session.is_none() {
+            self.session = Some(Session::new(export::EXPORT_DIR, &config));
+        }
+
+        let capture_screenshot =
+            config.capture_screenshot_request != self.capture_screenshot_request;
+        self.capture_screenshot_request = config.capture_screenshot_request;
+        let output_screenshot = config.output_screenshot_request != self.output_screenshot_request;
+        self.output_screenshot_request = config.output_screenshot_request;
+
+        // turning recording off ends the file
+        if !config.recording {
+            if let Some(recorder) = self.recorder.take() {
+                match recorder.finish(&self.ctx) {
+                    Ok((path, frames)) => println!("recorded {frames} frames {}", path.display()),
+                    Err(e) => println!("{e:?}"),
+                }
+            }
+        } else if self.recorder.is_none() {
+            self.recorder = Some(Recorder::new(export::EXPORT_DIR));
+        }
+
+        let bookmark = config.bookmark_request != self.bookmark_request;
+        self.bookmark_request = config.bookmark_request;
+        if bookmark && self.session.is_none() {
+            println!("bookmarks need a session timeline recording");
+        }
+
+        let export_session = config.session_export_request != self.session_export_request;
+        self.session_export_request = config.session_export_request;
+
+        if !config.compare_outputs {
+            self.outputs = None;
+        } else if self.outputs.as_ref().map(OutputCompare::index) != Some(config.compare_output) {
+            self.outputs = Some(OutputCompare::new(&self.ctx, config.compare_output));
+        }
+
+        if export || report || suggest {
+            self.histogram.request_readback();
+        }
+
+        // reduced only for the readouts and alerts that use it
+        let reduce = config.show_diagnostics
+            || config.count_out_of_range
+            || report
+            || Alerts::needs_stats(&config)
+            || self.session.as_ref().is_some_and(Session::wants_stats);
+
+        self.ctx.set_msaa_samples(config.msaa_samples)?;
+
+        let opacity = 1.0 - config.bg_opacity;
+        let mut renderer = self.ctx.create_renderer(
+            config.window_rect.width() as _,
+            config.window_rect.height() as _,
+            &premultiplied(config.clear_color, opacity),
+        )?;
+        self.ctx.profiler().begin("record");
+
+        // the reduction recorded in this slot a couple of frames ago has finished by now
+        let frame = self.ctx.frame_index();
+        let mut fresh_stats = false;
+        if reduce {
+            fresh_stats = self.stats.read(frame)?;
+        } else {
+            self.stats.reset();
+        }
+        self.ramp.read(&config, frame)?;
+        config.roi_difference = if Histogram::shows_difference(&config) {
+            self.histogram
+                .read(&config, frame)?
+                .and_then(|histogram| histogram.roi_difference())
+        } else {
+            None
+        };
+
+        self.dupl.keep(&mut renderer)?;
+        self.receiver.retire(&mut renderer);
+
+        if let (true, Some(reporter), Some(desktop)) =
+            (report, &mut self.reporter, self.dupl.resource())
+        {
+            reporter.capture(&mut renderer, desktop, config.analysis_rect())?;
+        }
+
+        if let (true, Some(session)) = (bookmark, &mut self.session) {
+            session.bookmark(&mut renderer, self.dupl.resource(), &config)?;
+        }
+
+        if let (Some(outputs), Some(desktop)) = (&mut self.outputs, self.dupl.resource()) {
+            outputs.capture(&mut renderer, desktop, config.analysis_rect())?;
+        }
+
+        let overlay = self.selfmask.prepare(&mut renderer, &config)?;
+        let lut = self.lut.prepare(&mut renderer, &config)?;
+        renderer.set_shared_srvs(&[srv, overlay, lut]);
+        let shared = SharedParams {
+            source_filtered: 0,
+            primaries: config.source_primaries,
+            transfer: config.source_transfer,
+            source_hdr: config.hdr_mode as u32,
+        };
+        renderer.set_shared_constants(&shared);
+
+        // the composed copy is window sized and graded like the filter output
+        let composed = if still {
+            Some(self.still.compose(&mut renderer, &config)?)
+        } else {
+            self.cursor
+                .compose(&mut renderer, &config, self.dupl.pointer())?
+        };
+        if let Some(composed) = composed {
+            renderer.set_shared_srvs(&[composed, overlay, lut]);
+            renderer.set_shared_constants(&SharedParams {
+                source_filtered: 1,
+                ..shared
+            });
+        }
+
+        self.background.process(&mut renderer, &config)?;
+        if still {
+            self.still.show(&mut renderer, &config)?;
+        }
+        self.filter.process(&mut renderer, &config)?;
+        self.difference.process(&mut renderer, &config, fresh)?;
+        if let Some(filtered) = self.filter.render_output(&mut renderer, &config)? {
+            renderer.set_shared_srvs(&[filtered, overlay, lut]);
+            renderer.set_shared_constants(&SharedParams {
+                source_filtered: 1,
+                ..shared
+            });
+        }
+
+        // cloud and histogram counting runs on the compute queue next to the other scopes'
+        if config.enable_color_cloud || config.enable_histogram {
+            renderer.begin_async_compute()?;
+            self.colorcloud.accumulate(&mut renderer, &config)?;
+            self.histogram.accumulate(&mut renderer, &config)?;
+            renderer.end_async_compute()?;
+        }
+        self.vectorscope.accumulate(&mut renderer, &config)?;
+        self.hue_luma.accumulate(&mut renderer, &config)?;
+        self.waveform.accumulate(&mut renderer, &config)?;
+        if reduce {
+            self.stats.accumulate(&mut renderer, &config, frame)?;
+        }
+        self.uniformity.accumulate(&mut renderer, &config)?;
+        self.contrast.accumulate(&mut renderer, &config)?;
+        renderer.join_async_compute()?;
+
+        renderer.pass("cloud", |r| self.colorcloud.process(r, &config))?;
+
+        // line heavy passes, the grid is hidden behind the cloud
+        self.msaa.begin(&mut renderer, &config, true)?;
+        renderer.pass("grids", |r| self.grids.process(r, &config))?;
+        self.msaa.end(&mut renderer)?;
+        self.grids.process_labels(&mut renderer, &config)?;
+
+        renderer.pass("histogram", |r| self.histogram.process(r, &config, frame))?;
+        renderer.pass("vectorscope", |r| self.vectorscope.process(r, &config))?;
+        renderer.pass("hue-luma", |r| self.hue_luma.process(r, &config))?;
+        renderer.pass("waveform", |r| self.waveform.process(r, &config))?;
+
+        // and the graticules over their scopes
+        self.msaa.begin(&mut renderer, &config, false)?;
+        renderer.pass("graticules", |r| {
+            self.vectorscope.process_graticule(r, &config)?;
+            self.waveform.process_graticule(r, &config)
+        })?;
+        self.msaa.end(&mut renderer)?;
+        self.roi.process(&mut renderer, &config)?;
+        self.contrast.process(&mut renderer, &config)?;
+        self.uniformity.process(&mut renderer, &config)?;
+        self.pacing.process(&mut renderer, &config)?;
+        self.alerts.process(&mut renderer, &config)?;
+        self.legend.process(&mut renderer, &config)?;
+        self.diagnostics.process(
+            &mut renderer,
+            &config,
+            self.ctx.pipeline_stats(),
+            self.ctx.profile(),
+            self.stats.latest(),
+        )?;
+        // these read the desktop texture itself
+        if let (false, Some(desktop)) = (still, self.dupl.resource()) {
+            self.eyedropper.process(&mut renderer, desktop, &config)?;
+            self.ramp.process(&mut renderer, desktop, &config, frame)?;
+            self.calibration.process(&mut renderer, desktop, &config)?;
+        }
+        self.selfmask.capture(&mut renderer, &config)?;
+        if capture_screenshot {
+            match self.dupl.resource() {
+                Some(desktop) if !still => self.screenshots.copy_capture(&mut renderer, desktop)?,
+                _ => println!("there is no duplicated frame to save"),
+            }
+        }
+        if output_screenshot {
+            self.screenshots.copy_output(&mut renderer)?;
+        }
+        if let Some(recorder) = &mut self.recorder {
+            let desktop = self.dupl.resource().filter(|_| !still);
+            if let Err(e) = recorder.capture(&mut renderer, &self.record_pass, desktop, &config) {
+                println!("recording stopped: {e:?}");
+            }
+        }
+        self.ctx.profiler().end();
+
+        self.ctx.execute(renderer)?;
+        // the encoder's device takes the frame once this one's work has run
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.write(&self.ctx) {
+                println!("recording stopped: {e:?}");
+            }
+        }
+        self.governor.update(&config, start.elapsed());
+        self.idle = idle;
+
+        // frames overlap unless something is read back below
+        let readback = export
+            || report
+            || bookmark
+            || suggest
+            || config.share_stats
+            || self.outputs.as_ref().is_some_and(OutputCompare::copied)
+            || capture_screenshot
+            || output_screenshot
+            || self.calibration.active()
+            || config.enable_uniformity
+            || config.alerts[ALERT_LOW_CONTRAST].enable
+            || config.enable_eyedropper && config.pick_point.is_some();
+        if readback {
+            self.ctx.wait_idle()?;
+            // everything has finished, no need to wait for the slot to come around
+            if reduce && self.stats.read(frame)? {
+                fresh_stats = true;
+            }
+        }
+
+        if export {
+            if let Some(histogram) = self.histogram.read(&config, frame)? {
+                match export::export_histogram(export::EXPORT_DIR, &histogram) {
+                    Ok(path) => println!("exported {}", path.display()),
+                    Err(e) => println!("{e:?}"),
+                }
+            } else {
+                println!("exporting needs the histogram");
+            }
+        }
+
+        if let Some(outputs) = &mut self.outputs {
+            outputs.read()?;
+        }
+
+        match self.screenshots.write(export::EXPORT_DIR) {
+            Ok(paths) => {
+                for path in paths {
+                    println!("saved {}", path.display());
+                }
+            }
+            Err(e) => println!("{e:?}"),
+        }
+
+        self.eyedropper.read(&config)?;
+        self.uniformity.read(&config)?;
+        self.contrast.read(&config)?;
+        self.alerts.update(
+            &config,
+            &Observation {
+                frame: self.stats.latest(),
+                low_contrast: self.contrast.flagged(),
+            },
+        );
+
+        if let Some(session) = &mut self.session {
+            if let Err(e) = session.read(self.stats.latest().filter(|_| fresh_stats)) {
+                println!("{e:?}");
+            }
+            if export_session {
+                match session.write() {
+                    Ok(path) => println!("session {}", path.display()),
+                    Err(e) => println!("{e:?}"),
+                }
+            }
+        }
+
+        match self.calibration.read(&config) {
+            Ok(Some(path)) => println!("capture checked {}", path.display()),
+            Ok(None) => {}
+            Err(e) => println!("{e:?}"),
+        }
+
+        self.levels = if suggest {
+            self.histogram
+                .read(&config, frame)?
+                .and_then(|histogram| histogram.levels())
+        } else {
+            None
+        };
+        config.window_rect = desktop_rect;
+
+        if report {
+            let histogram = self.histogram.read(&config, frame)?;
+            if let Some(reporter) = &mut self.reporter {
+                match reporter.write(&config, histogram.as_ref(), self.stats.latest()) {
+                    Ok(path) => println!("reported {}", path.display()),
+                    Err(e) => println!("{e:?}"),
+                }
+            }
+        }
+
+        self.monitor.update(&config);
+
+        if config.share_stats {
+            // a block another instance already writes is left to it, this one tries again once
+            // the monitor changes or sharing is switched off and on
+            if self.shared.as_ref().map(|(monitor, _)| *monitor) != Some(config.monitor) {
+                let shared = SharedStats::new(config.monitor)
+                    .inspect_err(|e| println!("{e:?}"))
+                    .ok();
+                self.shared = Some((config.monitor, shared));
+            }
+            if let Some((_, Some(shared))) = &mut self.shared {
+                let histogram = self.histogram.read(&config, frame)?;
+                let outputs = self.outputs.as_ref().and_then(OutputCompare::comparison);
+                shared.publish(
+                    &config,
+                    histogram.as_ref(),
+                    config.enable_ddc.then_some(&self.monitor),
+                    outputs.as_ref(),
+                );
+            }
+        } else {
+            self.shared = None;
+        }
+
+        Ok(())
+    }
+}
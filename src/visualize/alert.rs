@@ -0,0 +1,365 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use windows::{
+    core::s,
+    Win32::{
+        Graphics::{
+            Direct3D::D3D_PRIMITIVE_TOPOLOGY_LINELIST,
+            Direct3D12::D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+            Dxgi::Common::{DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32G32B32_FLOAT},
+        },
+        System::Diagnostics::Debug::MessageBeep,
+        UI::WindowsAndMessaging::MB_ICONWARNING,
+    },
+};
+
+use crate::{
+    config::{Config, ALERT_CLIPPING, ALERT_COUNT, ALERT_FLICKER, ALERT_LOW_CONTRAST},
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        math::Matrix,
+        renderer::{Renderer, ViewportKind},
+        resource::DynamicVertexBuffer,
+    },
+    gui::utils::Rect as _,
+};
+
+use super::stats::FrameStats;
+
+const NAMES: [&str; ALERT_COUNT] = ["clipping", "low contrast", "flicker"];
+
+// a condition that keeps coming and going alerts at most this often
+const REPEAT: Duration = Duration::from_secs(5);
+
+// a change in mean luma this large, linear in sdr and pq encoded in hdr, is a flash edge. more than three flashes in a
+// second, each a rise and a fall, is the wcag flash threshold
+const FLASH_STEP: f32 = 0.1;
+const FLASH_EDGES: usize = 6;
+const FLASH_WINDOW: Duration = Duration::from_secs(1);
+
+// how long the border blinks and how fast
+const FLASH_TIME: Duration = Duration::from_secs(2);
+const BLINK: Duration = Duration::from_millis(250);
+
+const BORDER: [f32; 3] = [1.0, 0.15, 0.15];
+
+// what the last analyzed frame showed, None where it wasn't measured
+pub struct Observation<'a> {
+    pub frame: Option<&'a FrameStats>,
+    // tiles below 4.5:1 and below 3:1
+    pub low_contrast: Option<[u32; 2]>,
+}
+
+// tracks when each enabled condition starts to hold
+#[derive(Default)]
+struct Rules {
+    holding: [bool; ALERT_COUNT],
+    fired: [Option<Instant>; ALERT_COUNT],
+    last_mean: Option<f32>,
+    // when mean luma jumped and whether it rose
+    edges: VecDeque<(Instant, bool)>,
+}
+
+impl Rules {
+    // the rules that start to alert now
+    fn evaluate(&mut self, config: &Config, now: Instant, seen: &Observation) -> Vec<usize> {
+        let mut conditions = [false; ALERT_COUNT];
+        conditions[ALERT_CLIPPING] = seen.frame.is_some_and(clipped);
+        conditions[ALERT_LOW_CONTRAST] = seen
+            .low_contrast
+            .is_some_and(|[normal, large]| normal + large > 0);
+        conditions[ALERT_FLICKER] = self.flickering(now, seen.frame);
+
+        let mut started = vec![];
+        for (i, holds) in conditions.into_iter().enumerate() {
+            let holds = holds && config.alerts[i].enable;
+            let rested = self.fired[i].is_none_or(|fired| now - fired >= REPEAT);
+            if holds && !self.holding[i] && rested {
+                self.fired[i] = Some(now);
+                started.push(i);
+            }
+            self.holding[i] = holds;
+        }
+
+        started
+    }
+
+    // alternating jumps in mean luma, faster than the flash threshold
+    fn flickering(&mut self, now: Instant, frame: Option<&FrameStats>) -> bool {
+        let Some(mean) = frame.map(|frame| frame.mean[3]) else {
+            self.last_mean = None;
+            self.edges.clear();
+            return false;
+        };
+
+        if let Some(last) = self.last_mean.replace(mean) {
+            let step = mean - last;
+            let rose = step > 0.0;
+            // a second jump the same way is still the same edge
+            if step.abs() >= FLASH_STEP && self.edges.back().is_none_or(|e| e.1 != rose) {
+                self.edges.push_back((now, rose));
+            }
+        }
+
+        while self
+            .edges
+            .front()
+            .is_some_and(|&(time, _)| now - time > FLASH_WINDOW)
+        {
+            self.edges.pop_front();
+        }
+
+        self.edges.len() >= FLASH_EDGES
+    }
+}
+
+// any of r, g and b at full scale, a single clipped channel is enough
+fn clipped(frame: &FrameStats) -> bool {
+    frame.max[..3].iter().any(|&max| max >= 1.0)
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+// sounds and flashes the window border when a rule starts to hold, for unattended captures
+pub struct Alerts {
+    pso: PipelineState,
+    border: DynamicVertexBuffer,
+    rules: Rules,
+    flash_start: Option<Instant>,
+}
+
+impl Alerts {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/PrimitiveVs.bin"),
+            include_bytes!("../shaders/bin/PrimitivePs.bin"),
+            BlendDesc::none(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[
+                InputElementDesc::per_vertex(s!("POSITION"), DXGI_FORMAT_R32G32B32_FLOAT),
+                InputElementDesc::per_vertex(s!("COLOR"), DXGI_FORMAT_R32G32B32_FLOAT),
+            ],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let border = DynamicVertexBuffer::new::<Vertex>(ctx, 24)?;
+
+        Ok(Self {
+            pso,
+            border,
+            rules: Rules::default(),
+            flash_start: None,
+        })
+    }
+
+    // the frame stats reduction is only needed for these
+    pub fn needs_stats(config: &Config) -> bool {
+        config.alerts[ALERT_CLIPPING].enable || config.alerts[ALERT_FLICKER].enable
+    }
+
+    pub fn update(&mut self, config: &Config, seen: &Observation) {
+        let now = Instant::now();
+        for rule in self.rules.evaluate(config, now, seen) {
+            println!("alert: {}", NAMES[rule]);
+
+            let rule = config.alerts[rule];
+            if rule.sound {
+                _ = unsafe { MessageBeep(MB_ICONWARNING) };
+            }
+            if rule.flash {
+                self.flash_start = Some(now);
+            }
+        }
+    }
+
+    // three nested outlines along the window edge, blinking
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        let Some(start) = self.flash_start else {
+            return Ok(());
+        };
+
+        let elapsed = start.elapsed();
+        if elapsed > FLASH_TIME {
+            self.flash_start = None;
+            return Ok(());
+        }
+        if (elapsed.as_millis() / BLINK.as_millis()) % 2 == 1 {
+            return Ok(());
+        }
+
+        ctx.set_pipeline_state(&self.pso);
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+
+        self.border.write(&border(config))?;
+        ctx.set_vertex_buffers(&[self.border.view()]);
+
+        #[repr(C)]
+        struct Params {
+            projection: [f32; 16],
+        }
+        ctx.set_graphics_constants(&Params {
+            projection: Matrix::identity().as_4x4(),
+        });
+        ctx.draw(self.border.vertex_count(), 1);
+
+        Ok(())
+    }
+}
+
+// in ndc of the full viewport, through pixel centers like the roi outline
+fn border(config: &Config) -> Vec<Vertex> {
+    let (width, height) = config.window_rect.size();
+    let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+
+    (0..3)
+        .flat_map(|inset| {
+            let lo = inset as f32 + 0.5;
+            let (right, bottom) = (width - lo, height - lo);
+            let corners = [[lo, lo], [right, lo], [right, bottom], [lo, bottom]]
+                .map(|[x, y]| [2.0 * x / width - 1.0, 1.0 - 2.0 * y / height]);
+
+            (0..4).flat_map(move |i| [corners[i], corners[(i + 1) % 4]])
+        })
+        .map(|[x, y]| Vertex {
+            position: [x, y, 0.5],
+            color: BORDER,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::RECT;
+
+    use super::*;
+
+    fn frame(mean: f32, peak: f32) -> FrameStats {
+        FrameStats {
+            count: 1,
+            min: [0.0; 4],
+            max: [peak; 4],
+            mean: [mean; 4],
+            percentiles: [0.0, 0.0, mean, peak, peak],
+            over: 0,
+            under: 0,
+        }
+    }
+
+    fn config() -> Config {
+        let mut config = Config::load("").unwrap();
+        for rule in &mut config.alerts {
+            rule.enable = true;
+        }
+        config
+    }
+
+    fn seen(frame: &FrameStats) -> Observation {
+        Observation {
+            frame: Some(frame),
+            low_contrast: Some([0, 0]),
+        }
+    }
+
+    #[test]
+    fn alerts_once_while_a_condition_holds() {
+        let config = config();
+        let mut rules = Rules::default();
+        let start = Instant::now();
+        let (fine, clipped) = (frame(0.5, 0.9), frame(0.5, 1.0));
+
+        assert!(rules.evaluate(&config, start, &seen(&fine)).is_empty());
+        assert_eq!(
+            rules.evaluate(&config, start, &seen(&clipped)),
+            [ALERT_CLIPPING]
+        );
+        assert!(rules.evaluate(&config, start, &seen(&clipped)).is_empty());
+
+        // back again too soon, then after the rest
+        rules.evaluate(&config, start, &seen(&fine));
+        let later = start + Duration::from_secs(1);
+        assert!(rules.evaluate(&config, later, &seen(&clipped)).is_empty());
+        rules.evaluate(&config, later, &seen(&fine));
+        let after = start + REPEAT;
+        assert_eq!(
+            rules.evaluate(&config, after, &seen(&clipped)),
+            [ALERT_CLIPPING]
+        );
+    }
+
+    #[test]
+    fn a_single_clipped_channel_is_clipping() {
+        let mut frame = frame(0.5, 0.9);
+        assert!(!clipped(&frame));
+
+        // luma stays well below full scale
+        frame.max[2] = 1.0;
+        assert!(clipped(&frame));
+    }
+
+    #[test]
+    fn only_enabled_rules_alert() {
+        let mut config = config();
+        config.alerts[ALERT_LOW_CONTRAST].enable = false;
+        let mut rules = Rules::default();
+        let seen = Observation {
+            frame: None,
+            low_contrast: Some([3, 1]),
+        };
+        assert!(rules.evaluate(&config, Instant::now(), &seen).is_empty());
+
+        config.alerts[ALERT_LOW_CONTRAST].enable = true;
+        assert_eq!(
+            rules.evaluate(&config, Instant::now(), &seen),
+            [ALERT_LOW_CONTRAST]
+        );
+    }
+
+    #[test]
+    fn flicker_needs_alternating_jumps_within_a_second() {
+        let mut rules = Rules::default();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        // a slow fade never flickers
+        for i in 0..10 {
+            let frame = frame(0.05 * i as f32, 0.5);
+            assert!(!rules.flickering(at(20 * i), Some(&frame)));
+        }
+
+        // light and dark every 50 ms
+        let mut rules = Rules::default();
+        let flickering: Vec<_> = (0..8)
+            .map(|i| rules.flickering(at(50 * i), Some(&frame((i % 2) as f32 * 0.5, 0.5))))
+            .collect();
+        assert_eq!(
+            flickering,
+            [false, false, false, false, false, false, true, true]
+        );
+    }
+
+    #[test]
+    fn border_has_three_outlines() {
+        let mut config = config();
+        config.window_rect = RECT::new(0, 0, 100, 50);
+
+        let vertices = border(&config);
+        assert_eq!(vertices.len(), 24);
+        let [x, y, _] = vertices[0].position;
+        assert!((x + 0.99).abs() < 1e-6 && (y - 0.98).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use windows::Win32::Graphics::{
+    Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+    Direct3D12::D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+    Dxgi::Common::DXGI_FORMAT_R16G16B16A16_FLOAT,
+};
+
+use crate::{
+    config::{Config, BACKGROUND_CHECKER, BACKGROUND_TRANSPARENT},
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        renderer::{Renderer, ViewportKind},
+    },
+};
+
+use super::color::colorref_to_linear;
+
+// light and dark squares like image editors use, in pixels
+const CHECKER_SIZE: u32 = 16;
+const CHECKER_LIGHT: u32 = 0x999999;
+const CHECKER_DARK: u32 = 0x666666;
+
+// fills the window before the scopes, a transparent background is left to the clear
+pub struct Background {
+    pso: PipelineState,
+}
+
+impl Background {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/BackgroundVs.bin"),
+            include_bytes!("../shaders/bin/BackgroundPs.bin"),
+            BlendDesc::none(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        Ok(Self { pso })
+    }
+
+    pub fn process(&self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.bg_mode == BACKGROUND_TRANSPARENT {
+            return Ok(());
+        }
+
+        #[repr(C)]
+        struct Params {
+            color: [f32; 4],
+            checker: [f32; 4],
+            size: u32,
+        }
+
+        // the opacity slider still applies on top of the chosen fill
+        let opacity = 1.0 - config.bg_opacity;
+        let (color, checker) = if config.bg_mode == BACKGROUND_CHECKER {
+            (CHECKER_LIGHT, CHECKER_DARK)
+        } else {
+            (config.bg_color, config.bg_color)
+        };
+
+        ctx.set_pipeline_state(&self.pso);
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+        ctx.set_graphics_constants(&Params {
+            color: premultiplied(color, opacity),
+            checker: premultiplied(checker, opacity),
+            size: CHECKER_SIZE,
+        });
+        ctx.draw(4, 1);
+
+        Ok(())
+    }
+}
+
+// 0x00bbggrr to the swap chain's linear premultiplied color
+pub fn premultiplied(color: u32, opacity: f32) -> [f32; 4] {
+    let [r, g, b] = colorref_to_linear(color);
+    [opacity * r, opacity * g, opacity * b, opacity]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_linear_and_premultiplied() {
+        assert_eq!(premultiplied(0xffffff, 1.0), [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(premultiplied(0x0000ff, 0.5), [0.5, 0.0, 0.0, 0.5]);
+
+        let [r, g, b, a] = premultiplied(0x808080, 1.0);
+        assert!((r - 0.2158).abs() < 1e-3 && r == g && g == b && a == 1.0);
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use windows::Win32::Foundation::{COLORREF, HWND, RECT};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::resource::Resource, initializer::Initializer, renderer::Renderer,
+        resource::TextureReadback,
+    },
+    gui::{
+        hwnd::Hwnd,
+        pattern::{self, Fill, WM_SHOW_PATTERN},
+        utils::Rect as _,
+    },
+};
+
+use super::{
+    color, export, ramp,
+    sampling::SampleFilter,
+    text::{TextLine, TextPanel},
+};
+
+// time for the pattern window to repaint and the new color to reach the capture
+const SETTLE: Duration = Duration::from_millis(300);
+
+// a continuous gray ramp first, then grays in 10% steps with white last, then the primaries.
+// the patch indices below don't count the ramp. the ramp is drawn as srgb too, its fitted gamma
+// only checks that the capture decodes it back to about 2.2
+const RAMP: usize = 0;
+const GRAY_STEPS: usize = 11;
+const WHITE: usize = GRAY_STEPS - 1;
+const PRIMARIES: [(&str, [u8; 3]); 3] = [
+    ("red", [255, 0, 0]),
+    ("green", [0, 255, 0]),
+    ("blue", [0, 0, 255]),
+];
+
+struct Session {
+    index: usize,
+    shown: Instant,
+    samples: u32,
+    measured: Vec<[f32; 3]>,
+    ramp_gamma: Option<f32>,
+}
+
+// a self-check of the capture pipeline, not a display calibration: shows known patches in the
+// pattern window one after another, averages the captured middle of each like the eyedropper and
+// writes how far gamma, gray tracking and primaries came back from what was drawn. anything other
+// than srgb with a delta e near 0 points at the capture path, a lut, the hdr mode or a color
+// filter, the display itself is never seen. the ramp is fitted like the live gamma estimate
+pub struct Calibration {
+    hwnd: HWND,
+    readback: TextureReadback,
+    filter: SampleFilter<3>,
+    ramp_filter: SampleFilter<1>,
+    panel: TextPanel,
+    session: Option<Session>,
+}
+
+// the window handle is only used to post messages
+unsafe impl Send for Calibration {}
+
+impl Calibration {
+    pub fn new(ctx: &mut Initializer, hwnd: HWND) -> Result<Self> {
+        Ok(Self {
+            hwnd,
+            readback: TextureReadback::default(),
+            filter: SampleFilter::new(),
+            ramp_filter: SampleFilter::new(),
+            panel: TextPanel::new(ctx)?,
+            session: None,
+        })
+    }
+
+    pub fn active(&self) -> bool {
+        self.session.is_some()
+    }
+
+    // starts over from the first patch when already running
+    pub fn start(&mut self) {
+        self.session = Some(Session {
+            index: 0,
+            shown: Instant::now(),
+            samples: 0,
+            measured: vec![],
+            ramp_gamma: None,
+        });
+        self.show(0);
+    }
+
+    fn show(&mut self, index: usize) {
+        self.filter.reset();
+        self.ramp_filter.reset();
+        if let Some(session) = &mut self.session {
+            session.index = index;
+            session.shown = Instant::now();
+            session.samples = 0;
+        }
+
+        let (_, fill) = step(index);
+        let (wp, lp) = Fill::message(Some(fill));
+        self.hwnd.post_message(WM_SHOW_PATTERN, wp, lp);
+    }
+
+    fn hide(&mut self) {
+        self.session = None;
+        let (wp, lp) = Fill::message(None);
+        self.hwnd.post_message(WM_SHOW_PATTERN, wp, lp);
+    }
+
+    // records a copy of the middle of the patch and tells the user what is being measured
+    pub fn process(
+        &mut self,
+        ctx: &mut Renderer,
+        desktop: &Resource,
+        config: &Config,
+    ) -> Result<()> {
+        let Some(session) = &self.session else {
+            return Ok(());
+        };
+
+        // the ramp is read across its middle row, the patch edges may blend with what is around it
+        let patch_rect = pattern::patch_rect(config.window_rect);
+        let rect = if session.index == RAMP {
+            let y = patch_rect.top + patch_rect.height() / 2;
+            RECT::new(patch_rect.left, y, patch_rect.width(), 1)
+        } else {
+            let margin = patch_rect.width() / 4;
+            patch_rect.inner(margin, margin)
+        };
+        self.readback.copy(ctx, desktop, rect)?;
+
+        let (name, _) = step(session.index);
+        self.panel.set_lines(
+            ctx,
+            vec![
+                TextLine::new(
+                    format!(
+                        "Capture self-check {}/{}: {name}",
+                        session.index + 1,
+                        step_count()
+                    ),
+                    None,
+                ),
+                TextLine::new("Keep the patch uncovered until the report is written", None),
+            ],
+        )?;
+
+        let (_, height) = config.window_rect.size();
+        let (_, panel_height) = self.panel.size();
+        let x = patch_rect.left - config.window_rect.left;
+        let y = (patch_rect.bottom - config.window_rect.top + 8).min(height - panel_height as i32);
+        self.panel
+            .draw(ctx, x.max(0), y.max(0), config.window_rect.usize())
+    }
+
+    // valid after the frame recorded by process() has finished on the GPU, returns the report
+    // path once the last patch is measured
+    pub fn read(&mut self, config: &Config) -> Result<Option<PathBuf>> {
+        let Some(pixels) = self.readback.read()? else {
+            return Ok(None);
+        };
+        let Some(session) = &mut self.session else {
+            return Ok(None);
+        };
+        if session.shown.elapsed() < SETTLE || pixels.rgb.is_empty() {
+            return Ok(None);
+        }
+
+        let (frames, reject) = (config.sample_frames, config.reject_outliers);
+        let average = if session.index == RAMP {
+            // frames where the ramp wasn't recognized still count, so a covered ramp can't stall
+            if let Some(gamma) = ramp::ramp_gamma(&pixels.rgb) {
+                let [gamma] = self.ramp_filter.push([gamma], frames, reject);
+                session.ramp_gamma = Some(gamma);
+            }
+            None
+        } else {
+            let mut mean = [0.0; 3];
+            for rgb in &pixels.rgb {
+                for (mean, c) in mean.iter_mut().zip(rgb) {
+                    *mean += c / pixels.rgb.len() as f32;
+                }
+            }
+            Some(self.filter.push(mean, frames, reject))
+        };
+
+        session.samples += 1;
+        if session.samples < frames.max(1) {
+            return Ok(None);
+        }
+
+        session.measured.extend(average);
+        let next = session.index + 1;
+        if next < step_count() {
+            self.show(next);
+            return Ok(None);
+        }
+
+        let measured = std::mem::take(&mut session.measured);
+        let ramp_gamma = session.ramp_gamma;
+        self.hide();
+
+        write_report(export::EXPORT_DIR, &measured, ramp_gamma).map(Some)
+    }
+}
+
+fn step_count() -> usize {
+    1 + GRAY_STEPS + PRIMARIES.len()
+}
+
+fn step(index: usize) -> (String, Fill) {
+    if index == RAMP {
+        return ("gray ramp".to_owned(), Fill::Ramp);
+    }
+
+    let solid =
+        |[r, g, b]: [u8; 3]| Fill::Solid(COLORREF(r as u32 | (g as u32) << 8 | (b as u32) << 16));
+
+    let patch = index - 1;
+    if patch < GRAY_STEPS {
+        let value = ((255 * patch + 5) / 10) as u8;
+        (format!("gray {}%", 10 * patch), solid([value; 3]))
+    } else {
+        let (name, rgb) = PRIMARIES[patch - GRAY_STEPS];
+        (name.to_owned(), solid(rgb))
+    }
+}
+
+fn write_report(
+    dir: impl AsRef<Path>,
+    measured: &[[f32; 3]],
+    ramp_gamma: Option<f32>,
+) -> Result<PathBuf> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let stamp = export::timestamp();
+    let path = dir.join(format!("capture-check-{stamp}.json"));
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+
+    let xyz: Vec<_> = measured.iter().map(|&rgb| color::rgb_to_xyz(rgb)).collect();
+    let white = xyz[WHITE][1].max(f32::EPSILON);
+
+    // luminance relative to the measured white, chroma of each gray against d65 at its lightness
+    let grays: Vec<_> = (0..GRAY_STEPS)
+        .map(|i| {
+            let signal = i as f32 / (GRAY_STEPS - 1) as f32;
+            let relative = xyz[i].map(|c| c / white);
+            let lab = color::xyz_to_lab(relative);
+            let delta_e = color::delta_e_2000(lab, [lab[0], 0.0, 0.0]);
+            (signal, relative[1], delta_e)
+        })
+        .collect();
+
+    let pairs: Vec<_> = grays
+        .iter()
+        .map(|&(signal, luminance, _)| (signal, luminance))
+        .collect();
+
+    let number = |value: Option<f32>| value.map_or("null".to_owned(), |v| format!("{v:.4}"));
+    let xy = |xyz: [f32; 3]| {
+        color::xyz_to_xy(xyz).map_or("null".to_owned(), |[x, y]| format!("[{x:.4}, {y:.4}]"))
+    };
+
+    writeln!(file, "{{")?;
+    writeln!(file, "  \"time\": \"{stamp}\",")?;
+    writeln!(
+        file,
+        "  \"note\": \"patches drawn as srgb and read back from the capture, not measured on the display\","
+    )?;
+    writeln!(file, "  \"white-luminance\": {white:.4},")?;
+    writeln!(file, "  \"gamma\": {},", number(color::fit_gamma(&pairs)))?;
+    writeln!(file, "  \"ramp-gamma\": {},", number(ramp_gamma))?;
+
+    writeln!(file, "  \"gray\": [")?;
+    for (i, &(signal, luminance, delta_e)) in grays.iter().enumerate() {
+        let gamma = color::fit_gamma(&[(signal, luminance)]);
+        let comma = if i + 1 < grays.len() { "," } else { "" };
+        writeln!(
+            file,
+            "    {{ \"signal\": {signal:.2}, \"luminance\": {luminance:.5}, \"gamma\": {}, \"delta-e\": {delta_e:.2} }}{comma}",
+            number(gamma)
+        )?;
+    }
+    writeln!(file, "  ],")?;
+
+    writeln!(file, "  \"primaries\": {{")?;
+    for (i, (name, _)) in PRIMARIES.iter().enumerate() {
+        writeln!(file, "    \"{name}\": {},", xy(xyz[GRAY_STEPS + i]))?;
+    }
+    writeln!(file, "    \"white\": {}", xy(xyz[WHITE]))?;
+    writeln!(file, "  }}")?;
+    writeln!(file, "}}")?;
+
+    Ok(path)
+}
@@ -0,0 +1,283 @@
+// cpu side color math for readouts, the shaders have their own in common.hlsli
+
+use crate::{
+    config::{PRIMARIES_BT2020, PRIMARIES_BT601},
+    graphics::math::srgb_to_linear,
+};
+
+// 0x00bbggrr, srgb encoded like the color picker shows it
+pub fn colorref_to_linear(color: u32) -> [f32; 3] {
+    [0, 8, 16].map(|shift| srgb_to_linear(((color >> shift) & 0xff) as f32 / 255.0))
+}
+
+// linear rec.709 to xyz, matches RgbToXyz
+pub fn rgb_to_xyz([r, g, b]: [f32; 3]) -> [f32; 3] {
+    [
+        0.412391 * r + 0.357584 * g + 0.180481 * b,
+        0.212639 * r + 0.715169 * g + 0.072192 * b,
+        0.019331 * r + 0.119195 * g + 0.950532 * b,
+    ]
+}
+
+// cie l*a*b* relative to d65 with y = 1.0 as white
+pub fn xyz_to_lab([x, y, z]: [f32; 3]) -> [f32; 3] {
+    const WHITE: [f32; 3] = [0.950456, 1.0, 1.089058];
+
+    fn f(t: f32) -> f32 {
+        const DELTA: f32 = 6.0 / 29.0;
+        if t > DELTA * DELTA * DELTA {
+            t.cbrt()
+        } else {
+            t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+        }
+    }
+
+    let (fx, fy, fz) = (f(x / WHITE[0]), f(y / WHITE[1]), f(z / WHITE[2]));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+pub fn rgb_to_lab(rgb: [f32; 3]) -> [f32; 3] {
+    xyz_to_lab(rgb_to_xyz(rgb))
+}
+
+// oklab from linear srgb, matches RgbToOklab
+pub fn rgb_to_oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = 0.41222146 * r + 0.53633255 * g + 0.051445995 * b;
+    let m = 0.2119035 * r + 0.6806995 * g + 0.10739696 * b;
+    let s = 0.08830246 * r + 0.28171885 * g + 0.6299787 * b;
+    let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+    [
+        0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+    ]
+}
+
+// kr and kb of the luma weights for PRIMARIES_*, matches LumaWeights
+pub fn luma_weights(primaries: u32) -> [f32; 2] {
+    match primaries {
+        PRIMARIES_BT601 => [0.2990, 0.1140],
+        PRIMARIES_BT2020 => [0.2627, 0.0593],
+        _ => [0.2126, 0.0722],
+    }
+}
+
+// y' with cb and cr in [-0.5, 0.5], matches RgbToYuv without the offset
+pub fn rgb_to_ycbcr([r, g, b]: [f32; 3], [kr, kb]: [f32; 2]) -> [f32; 3] {
+    let y = kr * r + (1.0 - kr - kb) * g + kb * b;
+    [y, 0.5 * (b - y) / (1.0 - kb), 0.5 * (r - y) / (1.0 - kr)]
+}
+
+// cie 1931 chromaticity, None for black
+pub fn xyz_to_xy([x, y, z]: [f32; 3]) -> Option<[f32; 2]> {
+    let sum = x + y + z;
+    (sum > f32::EPSILON).then(|| [x / sum, y / sum])
+}
+
+// exponent of the power law through (signal, relative luminance) pairs, a least squares fit
+// of the log-log line through the origin. pairs outside (0, 1) carry no slope and are skipped
+pub fn fit_gamma(pairs: &[(f32, f32)]) -> Option<f32> {
+    let (xy, xx) = pairs
+        .iter()
+        .filter(|&&(signal, luminance)| signal > 0.0 && signal < 1.0 && luminance > 0.0)
+        .map(|&(signal, luminance)| (signal.ln(), luminance.ln()))
+        .fold((0.0, 0.0), |(xy, xx), (x, y)| (xy + x * y, xx + x * x));
+
+    (xx > 0.0).then(|| xy / xx)
+}
+
+// hue in degrees, saturation and lightness in [0, 1] for [0, 1] input
+pub fn rgb_to_hsl([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = 0.5 * (max + min);
+    let d = max - min;
+
+    if d <= 0.0 {
+        return [0.0, 0.0, l];
+    }
+
+    let s = d / (1.0 - (2.0 * l - 1.0).abs()).max(f32::EPSILON);
+    let h = if max == r {
+        (g - b) / d
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+
+    [(60.0 * h).rem_euclid(360.0), s.min(1.0), l]
+}
+
+// ciede2000 with unit weights
+pub fn delta_e_2000(lab1: [f32; 3], lab2: [f32; 3]) -> f32 {
+    let [l1, a1, b1] = lab1.map(f64::from);
+    let [l2, a2, b2] = lab2.map(f64::from);
+
+    let c_mean = 0.5 * (a1.hypot(b1) + a2.hypot(b2));
+    let c7 = c_mean.powi(7);
+    let g = 0.5 * (1.0 - (c7 / (c7 + 25f64.powi(7))).sqrt());
+
+    let (a1, a2) = ((1.0 + g) * a1, (1.0 + g) * a2);
+    let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+
+    let hue = |b: f64, a: f64| {
+        if a == 0.0 && b == 0.0 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        }
+    };
+    let (h1, h2) = (hue(b1, a1), hue(b2, a2));
+
+    let dl = l2 - l1;
+    let dc = c2 - c1;
+    let dh = if c1 * c2 == 0.0 {
+        0.0
+    } else if (h2 - h1).abs() <= 180.0 {
+        h2 - h1
+    } else if h2 <= h1 {
+        h2 - h1 + 360.0
+    } else {
+        h2 - h1 - 360.0
+    };
+    let dh = 2.0 * (c1 * c2).sqrt() * (0.5 * dh).to_radians().sin();
+
+    let l_mean = 0.5 * (l1 + l2);
+    let c_mean = 0.5 * (c1 + c2);
+    let h_mean = if c1 * c2 == 0.0 {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+        0.5 * (h1 + h2)
+    } else if h1 + h2 < 360.0 {
+        0.5 * (h1 + h2 + 360.0)
+    } else {
+        0.5 * (h1 + h2 - 360.0)
+    };
+
+    let t = 1.0 - 0.17 * (h_mean - 30.0).to_radians().cos()
+        + 0.24 * (2.0 * h_mean).to_radians().cos()
+        + 0.32 * (3.0 * h_mean + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * h_mean - 63.0).to_radians().cos();
+
+    let l50 = (l_mean - 50.0).powi(2);
+    let sl = 1.0 + 0.015 * l50 / (20.0 + l50).sqrt();
+    let sc = 1.0 + 0.045 * c_mean;
+    let sh = 1.0 + 0.015 * c_mean * t;
+
+    let c7 = c_mean.powi(7);
+    let rc = 2.0 * (c7 / (c7 + 25f64.powi(7))).sqrt();
+    let d_theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+    let rt = -(2.0 * d_theta).to_radians().sin() * rc;
+
+    let (l, c, h) = (dl / sl, dc / sc, dh / sh);
+    (l * l + c * c + h * h + rt * c * h).sqrt() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PRIMARIES_BT709;
+
+    // pairs from sharma, wu and dalal, "the ciede2000 color-difference formula"
+    #[test]
+    fn delta_e_2000_matches_reference_pairs() {
+        let pairs = [
+            ([50.0, 2.6772, -79.7751], [50.0, 0.0, -82.7485], 2.0425),
+            ([50.0, 0.0, 0.0], [50.0, -1.0, 2.0], 2.3669),
+            ([50.0, 2.5, 0.0], [73.0, 25.0, -18.0], 27.1492),
+            ([50.0, 2.5, 0.0], [50.0, 3.2592, 0.3350], 1.0000),
+            (
+                [2.0776, 0.0795, -1.1350],
+                [0.9033, -0.0636, -0.5514],
+                0.9082,
+            ),
+        ];
+
+        for (lab1, lab2, expected) in pairs {
+            let de = delta_e_2000(lab1, lab2);
+            assert!(
+                (de - expected).abs() < 1e-3,
+                "{lab1:?} {lab2:?}: {de} != {expected}"
+            );
+            assert!((delta_e_2000(lab2, lab1) - de).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn ycbcr_weights_keep_white_neutral() {
+        let red = rgb_to_ycbcr([1.0, 0.0, 0.0], luma_weights(PRIMARIES_BT709));
+        let expected = [0.2126, -0.114572, 0.5];
+        assert!(red.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-5));
+
+        for primaries in [PRIMARIES_BT709, PRIMARIES_BT601, PRIMARIES_BT2020] {
+            let [y, cb, cr] = rgb_to_ycbcr([1.0; 3], luma_weights(primaries));
+            assert!((y - 1.0).abs() < 1e-6 && cb.abs() < 1e-6 && cr.abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn hsl_of_primaries_and_grays() {
+        let cases = [
+            ([1.0, 0.0, 0.0], [0.0, 1.0, 0.5]),
+            ([0.0, 1.0, 0.0], [120.0, 1.0, 0.5]),
+            ([0.0, 0.0, 1.0], [240.0, 1.0, 0.5]),
+            ([1.0, 0.0, 1.0], [300.0, 1.0, 0.5]),
+            ([1.0, 0.5, 0.0], [30.0, 1.0, 0.5]),
+            ([0.5, 0.25, 0.25], [0.0, 1.0 / 3.0, 0.375]),
+            ([0.5, 0.5, 0.5], [0.0, 0.0, 0.5]),
+        ];
+
+        for (rgb, expected) in cases {
+            let hsl = rgb_to_hsl(rgb);
+            for (a, b) in hsl.iter().zip(expected) {
+                assert!((a - b).abs() < 1e-4, "{rgb:?}: {hsl:?} != {expected:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn gamma_of_power_laws() {
+        for gamma in [1.8, 2.2, 2.4] {
+            let pairs: Vec<_> = (0..=10)
+                .map(|i| i as f32 / 10.0)
+                .map(|signal| (signal, signal.powf(gamma)))
+                .collect();
+            let fit = fit_gamma(&pairs).unwrap();
+            assert!((fit - gamma).abs() < 1e-4, "{fit} != {gamma}");
+        }
+
+        assert_eq!(fit_gamma(&[(0.0, 0.0), (1.0, 1.0)]), None);
+    }
+
+    #[test]
+    fn chromaticity_of_white_and_black() {
+        let [x, y] = xyz_to_xy(rgb_to_xyz([1.0; 3])).unwrap();
+        assert!((x - 0.3127).abs() < 1e-3 && (y - 0.3290).abs() < 1e-3);
+
+        assert_eq!(xyz_to_xy([0.0; 3]), None);
+    }
+
+    #[test]
+    fn white_and_gray_are_neutral() {
+        let [l, a, b] = rgb_to_lab([1.0; 3]);
+        assert!((l - 100.0).abs() < 1e-2);
+        assert!(a.abs() < 1e-2 && b.abs() < 1e-2);
+
+        let [l, a, b] = rgb_to_lab([0.18; 3]);
+        assert!((l - 49.5).abs() < 0.1);
+        assert!(a.abs() < 1e-2 && b.abs() < 1e-2);
+    }
+
+    #[test]
+    fn oklab_of_white_and_red() {
+        let [l, a, b] = rgb_to_oklab([1.0; 3]);
+        assert!((l - 1.0).abs() < 1e-4 && a.abs() < 1e-4 && b.abs() < 1e-4);
+
+        let red = rgb_to_oklab([1.0, 0.0, 0.0]);
+        for (c, expected) in red.iter().zip([0.6280, 0.2249, 0.1258]) {
+            assert!((c - expected).abs() < 1e-3, "{red:?}");
+        }
+    }
+}
@@ -1,141 +1,425 @@
-use anyhow::Result;
-use windows::Win32::{
-    Foundation::RECT,
-    Graphics::{
-        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
-        Direct3D12::{
-            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE, D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-        },
-        Dxgi::Common::{
-            DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32_UINT,
-        },
-    },
-};
-
-use crate::{
-    config::Config,
-    graphics::{
-        core::{pso::PipelineState, wrap::*},
-        initializer::Initializer,
-        math,
-        renderer::Renderer,
-        resource::RwBuffer,
-    },
-    gui::utils::Rect as _,
-};
-
-pub struct ColorCloud {
-    compute_pso: PipelineState,
-    draw_pso: PipelineState,
-    counter: RwBuffer,
-}
-
-impl ColorCloud {
-    pub fn new(ctx: &mut Initializer) -> Result<Self> {
-        let compute_pso =
-            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/ColorCloudCs.bin"), None)?;
-
-        let draw_pso = ctx.create_mesh_pipeline(
-            include_bytes!("../shaders/bin/ColorCloudAs.bin"),
-            include_bytes!("../shaders/bin/ColorCloudMs.bin"),
-            include_bytes!("../shaders/bin/ColorCloudPs.bin"),
-            BlendDesc::none(),
-            RasterizerDesc::none(),
-            DepthStencilDesc::depth(),
-            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
-            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
-            Some(DXGI_FORMAT_D16_UNORM),
-            None,
-        )?;
-
-        const NUM_ELEMS: u32 = 256 * 256 * 256;
-        let counter = RwBuffer::new(ctx, NUM_ELEMS, DXGI_FORMAT_R32_UINT)?;
-
-        Ok(Self {
-            compute_pso,
-            draw_pso,
-            counter,
-        })
-    }
-
-    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
-        if config.enable_color_cloud {
-            self.clear(ctx, config)?;
-            self.compute(ctx, config)?;
-            self.draw(ctx, config)?;
-        }
-        Ok(())
-    }
-
-    fn clear(&mut self, ctx: &mut Renderer, _config: &Config) -> Result<()> {
-        ctx.resource_barrier(&[self.counter.transition_barrier(
-            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-        )]);
-
-        ctx.clear_uav(self.counter.raw_uav, &self.counter);
-
-        Ok(())
-    }
-
-    fn compute(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
-        ctx.set_pipeline_state(&self.compute_pso);
-
-        #[repr(C)]
-        struct Params {
-            rect: RECT,
-        }
-
-        const THREAD: u32 = 8;
-        let dim_x = math::div_round_up(config.window_rect.width() as u32, THREAD);
-        let dim_y = math::div_round_up(config.window_rect.height() as u32, THREAD);
-
-        let params = Params {
-            rect: config.window_rect,
-        };
-        ctx.set_uavs(&[self.counter.uav]);
-        ctx.set_compute_constants(&params);
-        ctx.dispatch(dim_x, dim_y, 1);
-
-        Ok(())
-    }
-
-    fn draw(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
-        ctx.set_pipeline_state(&self.draw_pso);
-        ctx.set_viewport(crate::graphics::renderer::ViewportKind::Adjust);
-        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
-
-        ctx.resource_barrier(&[self.counter.transition_barrier(
-            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-        )]);
-
-        let width = config.window_rect.width();
-        let height = config.window_rect.height();
-        let min_count = 0;
-        let max_count = width * height / 9;
-
-        #[repr(C)]
-        struct Params {
-            projection: [f32; 12],
-            min_count: u32,
-            inv_max_count: f32,
-            color_space: u32,
-        }
-
-        let params = Params {
-            projection: config.projection_matrix().as_4x3(),
-            min_count,
-            inv_max_count: 1.0 / (max_count as f32),
-            color_space: config.color_cloud_mode,
-        };
-        ctx.set_graphics_constants(&params);
-        ctx.set_graphics_srvs(&[self.counter.srv]);
-
-        const GRID: u32 = 8;
-        ctx.dispatch_mesh(256 / GRID, 256 / GRID, 256 / GRID);
-
-        Ok(())
-    }
-}
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+        Direct3D12::{
+            D3D12_BLEND_DESC, D3D12_DEPTH_STENCIL_DESC, D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE, D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_RT_FORMAT_ARRAY,
+        },
+        Dxgi::Common::{
+            DXGI_FORMAT, DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT,
+            DXGI_FORMAT_R32_UINT,
+        },
+    },
+};
+
+use crate::{
+    config::{Config, COLORCLOUD_STYLE_SPLAT, COLORCLOUD_STYLE_VOLUME, TRANSPARENCY_MODE_DITHER},
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        math,
+        renderer::Renderer,
+        resource::{IndirectArgs, RwBuffer},
+    },
+    gui::utils::Rect as _,
+};
+
+use super::{
+    heatmap::Heatmaps,
+    isosurface::Isosurface,
+    oit::{self, WeightedOit},
+    volume::Volume,
+};
+
+pub struct ColorCloud {
+    compute_pso: PipelineState,
+    decay_pso: PipelineState,
+    draw_pso: PipelineState,
+    splat_pso: PipelineState,
+    dither_pso: PipelineState,
+    oit_pso: PipelineState,
+    oit: WeightedOit,
+    isosurface: Isosurface,
+    volume: Volume,
+    heatmaps: Heatmaps,
+    counter: RwBuffer,
+    // frame each bin was last hit, for coloring by age
+    ages: RwBuffer,
+    fallback: Option<VertexFallback>,
+    parity: u32,
+    frame: u32,
+}
+
+// instanced triangles for gpus without mesh shaders, the nonzero bins are gathered on the gpu first
+struct VertexFallback {
+    compact_pso: PipelineState,
+    points: RwBuffer,
+    draw_args: IndirectArgs,
+}
+
+// matches CloudPoint in colorcloud.hlsl, only written on the gpu
+#[allow(unused)]
+#[repr(C)]
+struct CloudPoint {
+    color_code: u32,
+    count: u32,
+}
+
+impl VertexFallback {
+    fn new(ctx: &mut Initializer, num_elems: u32) -> Result<Self> {
+        let compact_pso = ctx.create_compute_pipeline(
+            include_bytes!("../shaders/bin/ColorCloudCompactCs.bin"),
+            None,
+        )?;
+
+        let points = RwBuffer::structured::<CloudPoint>(ctx, num_elems)?;
+        let draw_args = IndirectArgs::draw(ctx, 1)?;
+
+        Ok(Self {
+            compact_pso,
+            points,
+            draw_args,
+        })
+    }
+}
+
+impl ColorCloud {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let compute_pso =
+            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/ColorCloudCs.bin"), None)?;
+
+        let decay_pso = ctx.create_compute_pipeline(
+            include_bytes!("../shaders/bin/ColorCloudDecayCs.bin"),
+            None,
+        )?;
+
+        let mesh = ctx.supports_mesh_shaders();
+
+        let draw_pso = Self::create_draw_pipeline(
+            ctx,
+            mesh,
+            include_bytes!("../shaders/bin/ColorCloudPs.bin"),
+            BlendDesc::none(),
+            DepthStencilDesc::depth(),
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            Some(DXGI_FORMAT_D16_UNORM),
+        )?;
+
+        let splat_pso = Self::create_draw_pipeline(
+            ctx,
+            mesh,
+            include_bytes!("../shaders/bin/ColorCloudSplatPs.bin"),
+            BlendDesc::mul(),
+            DepthStencilDesc::none(),
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            Some(DXGI_FORMAT_D16_UNORM),
+        )?;
+
+        let dither_pso = Self::create_draw_pipeline(
+            ctx,
+            mesh,
+            include_bytes!("../shaders/bin/ColorCloudDitherPs.bin"),
+            BlendDesc::none(),
+            DepthStencilDesc::depth(),
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            Some(DXGI_FORMAT_D16_UNORM),
+        )?;
+
+        let oit_pso = Self::create_draw_pipeline(
+            ctx,
+            mesh,
+            include_bytes!("../shaders/bin/ColorCloudOitPs.bin"),
+            BlendDesc::oit(),
+            DepthStencilDesc::none(),
+            RtvFormats::from_slice(&[oit::ACCUM_FORMAT, oit::REVEAL_FORMAT]),
+            None,
+        )?;
+
+        let oit = WeightedOit::new(ctx)?;
+        let isosurface = Isosurface::new(ctx)?;
+        let volume = Volume::new(ctx)?;
+        let heatmaps = Heatmaps::new(ctx)?;
+
+        const NUM_ELEMS: u32 = 256 * 256 * 256;
+        let counter = RwBuffer::new(ctx, NUM_ELEMS, DXGI_FORMAT_R32_UINT)?;
+        let ages = RwBuffer::new(ctx, NUM_ELEMS, DXGI_FORMAT_R32_UINT)?;
+
+        let fallback = if mesh {
+            None
+        } else {
+            Some(VertexFallback::new(ctx, NUM_ELEMS)?)
+        };
+
+        Ok(Self {
+            compute_pso,
+            decay_pso,
+            draw_pso,
+            splat_pso,
+            dither_pso,
+            oit_pso,
+            oit,
+            isosurface,
+            volume,
+            heatmaps,
+            counter,
+            ages,
+            fallback,
+            parity: 0,
+            frame: 0,
+        })
+    }
+
+    // compute only, may be recorded on the async compute queue
+    pub fn accumulate(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_color_cloud {
+            self.clear(ctx, config)?;
+            self.compute(ctx, config)?;
+
+            if config.color_cloud_isosurface {
+                self.isosurface.extract(ctx, &self.counter, config)?;
+            } else if config.color_cloud_style != COLORCLOUD_STYLE_VOLUME {
+                self.compact(ctx)?;
+            }
+
+            if config.color_cloud_heatmaps {
+                self.heatmaps.project(ctx, &self.counter)?;
+            }
+        }
+        Ok(())
+    }
+
+    // draws what accumulate() counted
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_color_cloud {
+            self.draw(ctx, config)?;
+
+            if config.color_cloud_heatmaps {
+                self.heatmaps.draw(ctx, config)?;
+            }
+        }
+        Ok(())
+    }
+
+    // mesh shaders when available, otherwise the instanced vertex shader
+    fn create_draw_pipeline(
+        ctx: &Initializer,
+        mesh: bool,
+        ps: &[u8],
+        blend: D3D12_BLEND_DESC,
+        depth_stencil: D3D12_DEPTH_STENCIL_DESC,
+        rtv_formats: D3D12_RT_FORMAT_ARRAY,
+        dsv_format: Option<DXGI_FORMAT>,
+    ) -> Result<PipelineState> {
+        if mesh {
+            ctx.create_mesh_pipeline(
+                include_bytes!("../shaders/bin/ColorCloudAs.bin"),
+                include_bytes!("../shaders/bin/ColorCloudMs.bin"),
+                ps,
+                blend,
+                RasterizerDesc::none(),
+                depth_stencil,
+                D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+                rtv_formats,
+                dsv_format,
+                None,
+            )
+        } else {
+            ctx.create_graphics_pipeline(
+                include_bytes!("../shaders/bin/ColorCloudVs.bin"),
+                ps,
+                blend,
+                RasterizerDesc::none(),
+                depth_stencil,
+                &[],
+                D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+                rtv_formats,
+                dsv_format,
+                None,
+            )
+        }
+    }
+
+    fn clear(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        ctx.resource_barrier(&[
+            self.counter.transition_barrier(
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            ),
+            self.ages.transition_barrier(
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            ),
+        ]);
+
+        if config.color_cloud_interleave || config.color_cloud_accumulate {
+            // halve the counts so the two checkerboard halves add up to a full frame,
+            // or so accumulated colors fade out over a few frames
+            ctx.set_pipeline_state(&self.decay_pso);
+            ctx.set_uavs(&[self.counter.uav]);
+            ctx.dispatch(256, 256, 1);
+            ctx.resource_barrier(&[self.counter.uav_barrier()]);
+        } else {
+            ctx.clear_uav(self.counter.raw_uav, &self.counter);
+        }
+
+        Ok(())
+    }
+
+    fn compute(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        ctx.set_pipeline_state(&self.compute_pso);
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            origin: [i32; 2],
+            interleave: u32,
+            parity: u32,
+            hdr: u32,
+            track_age: u32,
+            frame: u32,
+        }
+
+        let interleave = config.color_cloud_interleave;
+        self.parity ^= 1;
+        self.frame = self.frame.wrapping_add(1);
+
+        const THREAD: u32 = 8;
+        let rect = config.analysis_rect();
+        let width = if interleave {
+            math::div_round_up(rect.width() as u32, 2)
+        } else {
+            rect.width() as u32
+        };
+        let dim_x = math::div_round_up(width, THREAD);
+        let dim_y = math::div_round_up(rect.height() as u32, THREAD);
+
+        let params = Params {
+            rect,
+            origin: [config.window_rect.left, config.window_rect.top],
+            interleave: interleave as u32,
+            parity: self.parity,
+            hdr: config.hdr_mode as u32,
+            track_age: config.color_cloud_age as u32,
+            frame: self.frame,
+        };
+        ctx.set_uavs(&[self.counter.uav, self.ages.uav]);
+        ctx.set_compute_constants(&params);
+        ctx.dispatch(dim_x, dim_y, 1);
+
+        Ok(())
+    }
+
+    fn compact(&mut self, ctx: &mut Renderer) -> Result<()> {
+        let Some(fallback) = &self.fallback else {
+            return Ok(());
+        };
+
+        ctx.resource_barrier(&[
+            self.counter.uav_barrier(),
+            fallback.points.transition_barrier(
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            ),
+        ]);
+        fallback.draw_args.begin_write(ctx, true);
+
+        ctx.set_pipeline_state(&fallback.compact_pso);
+        ctx.set_uavs(&[
+            self.counter.uav,
+            self.ages.uav,
+            fallback.points.uav,
+            fallback.draw_args.uav(),
+        ]);
+        ctx.dispatch(256, 256, 1);
+
+        ctx.resource_barrier(&[fallback.points.transition_barrier(
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+        fallback.draw_args.end_write(ctx);
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        ctx.resource_barrier(&[
+            self.counter.transition_barrier(
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            ),
+            self.ages.transition_barrier(
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            ),
+        ]);
+
+        if config.color_cloud_isosurface {
+            return self.isosurface.draw(ctx, config);
+        }
+        if config.color_cloud_style == COLORCLOUD_STYLE_VOLUME {
+            return self.volume.draw(ctx, &self.counter, config);
+        }
+
+        let splat = config.color_cloud_style == COLORCLOUD_STYLE_SPLAT;
+        let dither = splat && config.transparency_mode == TRANSPARENCY_MODE_DITHER;
+        let oit = splat && !dither && config.color_cloud_oit;
+
+        if dither {
+            ctx.set_pipeline_state(&self.dither_pso);
+        } else if oit {
+            let (width, height) = config.window_rect.usize();
+            self.oit.begin(ctx, width, height)?;
+            ctx.set_pipeline_state(&self.oit_pso);
+        } else if splat {
+            ctx.set_pipeline_state(&self.splat_pso);
+        } else {
+            ctx.set_pipeline_state(&self.draw_pso);
+        }
+        ctx.set_viewport(config.cloud_viewport());
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+        let (width, height) = config.analysis_rect().size();
+        let min_count = 0;
+        let max_count = (width * height / 9).max(1);
+
+        #[repr(C)]
+        struct Params {
+            projection: [f32; 16],
+            min_count: u32,
+            inv_max_count: f32,
+            color_space: u32,
+            color_by_age: u32,
+            current_frame: u32,
+        }
+
+        let params = Params {
+            projection: config.projection_matrix().as_4x4(),
+            min_count,
+            inv_max_count: 1.0 / (max_count as f32),
+            color_space: config.color_cloud_mode,
+            color_by_age: config.color_cloud_age as u32,
+            current_frame: self.frame,
+        };
+        ctx.set_graphics_constants(&params);
+
+        if let Some(fallback) = &self.fallback {
+            ctx.set_graphics_srvs(&[self.counter.srv, self.ages.srv, fallback.points.srv]);
+            fallback.draw_args.execute(ctx, 0, 1);
+            fallback.draw_args.finish(ctx);
+        } else {
+            ctx.set_graphics_srvs(&[self.counter.srv, self.ages.srv]);
+
+            const GRID: u32 = 8;
+            ctx.dispatch_mesh(256 / GRID, 256 / GRID, 256 / GRID);
+        }
+
+        if oit {
+            self.oit.end(ctx)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_LINELIST,
+        Direct3D12::*,
+        Dxgi::Common::{DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32_UINT},
+    },
+};
+
+use crate::{
+    config::{Config, ALERT_LOW_CONTRAST},
+    graphics::{
+        core::{pso::PipelineState, resource::Resource, wrap::*},
+        initializer::Initializer,
+        math,
+        renderer::{Renderer, ViewportKind},
+        resource::RwBuffer,
+    },
+    gui::utils::Rect as _,
+};
+
+// pixels along each side of a tile, matches contrast.hlsl
+const TILE: u32 = 16;
+
+// enough tiles for a 4k region, more are allocated when the region grows past it
+const INITIAL_TILES: u32 = 240 * 135;
+
+// text-like tiles whose two ends fall below the wcag 2 minimums, outlined over the desktop. a
+// heuristic meant for spotting candidates during an accessibility audit, not for certifying one
+pub struct ContrastScanner {
+    scan_pso: PipelineState,
+    draw_pso: PipelineState,
+    flags: RwBuffer,
+    capacity: u32,
+    // tiles below 4.5:1 and below 3:1, copied for read()
+    counts: RwBuffer,
+    readback: Resource,
+    copied: bool,
+    flagged: Option<[u32; 2]>,
+}
+
+impl ContrastScanner {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let scan_pso =
+            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/ContrastCs.bin"), None)?;
+
+        let draw_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/ContrastVs.bin"),
+            include_bytes!("../shaders/bin/ContrastPs.bin"),
+            BlendDesc::none(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let flags = RwBuffer::new(ctx, INITIAL_TILES, DXGI_FORMAT_R32_UINT)?;
+        let counts = RwBuffer::new(ctx, 2, DXGI_FORMAT_R32_UINT)?;
+
+        let readback = Resource::new_buffer(
+            ctx,
+            &HeapProps::readback(),
+            None,
+            8,
+            D3D12_RESOURCE_FLAG_NONE,
+            D3D12_RESOURCE_STATE_COPY_DEST,
+        )?;
+
+        Ok(Self {
+            scan_pso,
+            draw_pso,
+            flags,
+            capacity: INITIAL_TILES,
+            counts,
+            readback,
+            copied: false,
+            flagged: None,
+        })
+    }
+
+    // the outlines or the low contrast alert need the scan
+    fn scanning(config: &Config) -> bool {
+        config.enable_text_contrast || config.alerts[ALERT_LOW_CONTRAST].enable
+    }
+
+    // tiles below 4.5:1 and below 3:1 in the last scanned frame
+    pub fn flagged(&self) -> Option<[u32; 2]> {
+        self.flagged
+    }
+
+    fn tiles(rect: RECT) -> (u32, u32) {
+        let (width, height) = rect.size();
+        (
+            math::div_round_up(width.max(0) as u32, TILE),
+            math::div_round_up(height.max(0) as u32, TILE),
+        )
+    }
+
+    // graphics queue compute, flags every tile of the analyzed region and copies the counts for
+    // read()
+    pub fn accumulate(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if !Self::scanning(config) {
+            return Ok(());
+        }
+
+        let rect = config.analysis_rect();
+        let (tiles_x, tiles_y) = Self::tiles(rect);
+        if tiles_x * tiles_y > self.capacity {
+            let old = self.flags.resource.clone();
+            self.flags
+                .resize(ctx.device(), tiles_x * tiles_y, DXGI_FORMAT_R32_UINT)?;
+            ctx.retire(old);
+            self.capacity = tiles_x * tiles_y;
+        }
+
+        ctx.resource_barrier(&[
+            self.flags.transition_barrier(
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            ),
+            self.counts.transition_barrier(
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            ),
+        ]);
+        ctx.clear_uav(self.counts.raw_uav, &self.counts);
+
+        ctx.set_pipeline_state(&self.scan_pso);
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            origin: [i32; 2],
+            tiles_x: u32,
+        }
+        ctx.set_compute_constants(&Params {
+            rect,
+            origin: [config.window_rect.left, config.window_rect.top],
+            tiles_x,
+        });
+        ctx.set_uavs(&[self.flags.uav, self.counts.uav]);
+        ctx.dispatch(tiles_x, tiles_y, 1);
+
+        ctx.resource_barrier(&[
+            self.flags.transition_barrier(
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            ),
+            self.counts.transition_barrier(
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                D3D12_RESOURCE_STATE_COPY_SOURCE,
+            ),
+        ]);
+        ctx.copy_buffer_region(&self.readback, 0, &self.counts, 0, 8);
+        ctx.resource_barrier(&[self.counts.transition_barrier(
+            D3D12_RESOURCE_STATE_COPY_SOURCE,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        self.copied = true;
+        Ok(())
+    }
+
+    // valid after the frame recorded by accumulate() has finished on the GPU
+    pub fn read(&mut self, config: &Config) -> Result<()> {
+        if !Self::scanning(config) {
+            self.flagged = None;
+            return Ok(());
+        }
+
+        if std::mem::take(&mut self.copied) {
+            let counts = self.readback.read::<u32>(2)?;
+            self.flagged = Some([counts[0], counts[1]]);
+        }
+
+        Ok(())
+    }
+
+    // outlines the tiles flagged by accumulate(), orange below 4.5:1 and red below 3:1
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if !config.enable_text_contrast {
+            return Ok(());
+        }
+
+        let rect = config.analysis_rect();
+        let (tiles_x, tiles_y) = Self::tiles(rect);
+
+        ctx.set_pipeline_state(&self.draw_pso);
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+        ctx.set_graphics_srvs(&[self.flags.srv]);
+
+        #[repr(C)]
+        struct Params {
+            offset: [i32; 2],
+            size: [i32; 2],
+            tiles: [u32; 2],
+            window_size: [u32; 2],
+        }
+        let (width, height) = config.window_rect.size();
+        ctx.set_graphics_constants(&Params {
+            offset: [
+                rect.left - config.window_rect.left,
+                rect.top - config.window_rect.top,
+            ],
+            size: [rect.width(), rect.height()],
+            tiles: [tiles_x, tiles_y],
+            window_size: [width.max(1) as _, height.max(1) as _],
+        });
+        ctx.draw(8, tiles_x * tiles_y);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+        Direct3D12::{
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE, D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+        },
+        Dxgi::Common::{DXGI_FORMAT_B8G8R8A8_UNORM_SRGB, DXGI_FORMAT_R16G16B16A16_FLOAT},
+    },
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{
+            descriptor::{Descriptor, RtvHeap},
+            pso::PipelineState,
+            wrap::*,
+        },
+        duplicate::Pointer,
+        initializer::Initializer,
+        renderer::{Renderer, ViewportKind},
+        resource::{RenderTexture, Texture2D},
+    },
+    gui::utils::Rect as _,
+};
+
+// draws the mouse pointer into a window sized copy of the desktop that the passes analyze instead
+pub struct CursorLayer {
+    pso: PipelineState,
+    #[allow(unused)]
+    rtv_heap: RtvHeap,
+    output: RenderTexture,
+    cursor: Texture2D,
+    shape_id: Option<u32>,
+}
+
+impl CursorLayer {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/CursorVs.bin"),
+            include_bytes!("../shaders/bin/CursorPs.bin"),
+            BlendDesc::none(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let rtv_heap = RtvHeap::new(ctx, 1)?;
+        let output = RenderTexture::new(
+            ctx,
+            rtv_heap.descriptor(0),
+            1,
+            1,
+            DXGI_FORMAT_R16G16B16A16_FLOAT,
+            [0.0; 4],
+        )?;
+        let cursor = Texture2D::new(ctx, 1, 1, DXGI_FORMAT_B8G8R8A8_UNORM_SRGB)?;
+
+        Ok(Self {
+            pso,
+            rtv_heap,
+            output,
+            cursor,
+            shape_id: None,
+        })
+    }
+
+    // None while the pointer is left out, or before its shape is known
+    pub fn compose(
+        &mut self,
+        ctx: &mut Renderer,
+        config: &Config,
+        pointer: &Pointer,
+    ) -> Result<Option<Descriptor>> {
+        if !config.include_cursor || pointer.shape.pixels.is_empty() {
+            return Ok(None);
+        }
+
+        if self.shape_id != Some(pointer.shape_id) {
+            let shape = &pointer.shape;
+            self.cursor
+                .upload(ctx, shape.width, shape.height, &shape.pixels)?;
+            self.shape_id = Some(pointer.shape_id);
+        }
+
+        let (width, height) = config.window_rect.usize();
+        if self.output.size() != (width, height) {
+            let old = self.output.resource.clone();
+            self.output.resize(ctx.device(), width, height)?;
+            ctx.retire(old);
+        }
+
+        ctx.resource_barrier(&[self.output.transition_barrier(
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+        )]);
+        ctx.set_render_targets(&[self.output.rtv]);
+
+        ctx.set_pipeline_state(&self.pso);
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            position: [i32; 2],
+            visible: u32,
+            masked: u32,
+        }
+
+        let params = Params {
+            rect: config.window_rect,
+            position: pointer.position,
+            visible: pointer.visible as u32,
+            masked: pointer.shape.masked as u32,
+        };
+        ctx.set_graphics_constants(&params);
+        ctx.set_graphics_srvs(&[self.cursor.srv]);
+
+        ctx.draw(3, 1);
+
+        ctx.reset_render_targets();
+        ctx.resource_barrier(&[self.output.transition_barrier(
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+        )]);
+
+        Ok(Some(self.output.srv))
+    }
+}
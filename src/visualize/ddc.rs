@@ -0,0 +1,363 @@
+use std::{
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use windows::Win32::{
+    Devices::Display::{
+        DestroyPhysicalMonitors, GetMonitorBrightness, GetMonitorContrast,
+        GetNumberOfPhysicalMonitorsFromHMONITOR, GetPhysicalMonitorsFromHMONITOR,
+        SetMonitorBrightness, SetMonitorContrast, PHYSICAL_MONITOR,
+    },
+    Foundation::{HANDLE, HWND, LPARAM, RECT, WPARAM},
+    Graphics::Gdi::{MonitorFromRect, HMONITOR, MONITOR_DEFAULTTONEAREST},
+};
+
+use crate::{
+    config::Config,
+    gui::{hwnd::Hwnd, menu::WM_MONITOR_READINGS},
+};
+
+// ddc/ci round trips take tens of milliseconds, so they run on their own thread and poll rarely
+const READ_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Default)]
+pub struct Setting {
+    pub min: u32,
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Setting {
+    // both ways round to the nearest step, so a value read as a percentage sets the same value
+    // again. a percentage reads back as itself when the range has at least 100 steps, a shorter
+    // range shows the percentage of the step it landed on
+    fn at_percent(&self, percent: i32) -> u32 {
+        let range = self.max.saturating_sub(self.min);
+        self.min + (range * percent.clamp(0, 100) as u32 + 50) / 100
+    }
+
+    fn percent(&self) -> i32 {
+        let range = self.max.saturating_sub(self.min);
+        if range == 0 {
+            return 0;
+        }
+        let current = self.current.clamp(self.min, self.max) - self.min;
+        ((current.saturating_mul(100) + range / 2) / range) as i32
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Readings {
+    brightness: Option<Setting>,
+    contrast: Option<Setting>,
+}
+
+impl Readings {
+    // what the sliders show, -1 where the monitor doesn't report a value
+    fn percents(&self) -> (i32, i32) {
+        (
+            self.brightness.map_or(-1, |s| s.percent()),
+            self.contrast.map_or(-1, |s| s.percent()),
+        )
+    }
+}
+
+// the window position and slider percentages, -1 until the user touched them
+#[derive(Clone, Copy, PartialEq)]
+struct Request {
+    rect: RECT,
+    brightness: i32,
+    contrast: i32,
+}
+
+// brightness and contrast of the monitor the window is on. the worker starts with ddc enabled and
+// only hears from the visualizer when the window moves or a slider changes
+pub struct MonitorControl {
+    hwnd: HWND,
+    worker: Option<(Sender<Request>, JoinHandle<()>)>,
+    readings: Arc<Mutex<Readings>>,
+    sent: Option<Request>,
+}
+
+// the viewer handle is only used to post messages
+unsafe impl Send for MonitorControl {}
+
+impl MonitorControl {
+    pub fn new(hwnd: HWND) -> Self {
+        Self {
+            hwnd,
+            worker: None,
+            readings: Arc::new(Mutex::new(Readings::default())),
+            sent: None,
+        }
+    }
+
+    pub fn update(&mut self, config: &Config) {
+        if !config.enable_ddc {
+            self.stop();
+            return;
+        }
+
+        let request = Request {
+            rect: config.window_rect,
+            brightness: config.ddc_brightness,
+            contrast: config.ddc_contrast,
+        };
+        if self.sent == Some(request) {
+            return;
+        }
+
+        if self.worker.is_none() {
+            self.worker = Some(self.spawn());
+        }
+        if let Some((sender, _)) = &self.worker {
+            if sender.send(request).is_ok() {
+                self.sent = Some(request);
+            }
+        }
+    }
+
+    pub fn brightness(&self) -> Option<Setting> {
+        self.readings.lock().ok()?.brightness
+    }
+
+    pub fn contrast(&self) -> Option<Setting> {
+        self.readings.lock().ok()?.contrast
+    }
+
+    fn spawn(&self) -> (Sender<Request>, JoinHandle<()>) {
+        let (sender, requests) = mpsc::channel();
+        // handles aren't Send, the worker is built on its thread and rebuilds the viewer's to
+        // post to
+        let viewer = self.hwnd.0 as isize;
+        let readings = Arc::clone(&self.readings);
+        let join_handle = std::thread::spawn(move || {
+            let worker = Worker {
+                viewer,
+                monitor: HMONITOR::default(),
+                physical: vec![],
+                readings,
+                posted: None,
+                applied: (-1, -1),
+            };
+            worker.run(requests)
+        });
+        (sender, join_handle)
+    }
+
+    fn stop(&mut self) {
+        // dropping the sender ends the worker
+        if let Some((sender, join_handle)) = self.worker.take() {
+            drop(sender);
+            _ = join_handle.join();
+        }
+
+        self.sent = None;
+        if let Ok(mut readings) = self.readings.lock() {
+            *readings = Readings::default();
+        }
+    }
+}
+
+impl Drop for MonitorControl {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+struct Worker {
+    viewer: isize,
+    monitor: HMONITOR,
+    physical: Vec<PHYSICAL_MONITOR>,
+    readings: Arc<Mutex<Readings>>,
+    posted: Option<(i32, i32)>,
+    applied: (i32, i32),
+}
+
+impl Worker {
+    fn run(mut self, requests: Receiver<Request>) {
+        loop {
+            match requests.recv_timeout(READ_INTERVAL) {
+                Ok(mut request) => {
+                    // a dragged slider queues up faster than the monitor answers
+                    while let Ok(next) = requests.try_recv() {
+                        request = next;
+                    }
+                    self.apply(request);
+                }
+                Err(RecvTimeoutError::Timeout) => self.read(),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        self.close();
+    }
+
+    fn handle(&self) -> Option<HANDLE> {
+        self.physical.first().map(|m| m.hPhysicalMonitor)
+    }
+
+    fn apply(&mut self, request: Request) {
+        let monitor = unsafe { MonitorFromRect(&request.rect, MONITOR_DEFAULTTONEAREST) };
+        if monitor != self.monitor {
+            self.open(monitor);
+            self.read();
+        }
+
+        let Some(handle) = self.handle() else {
+            return;
+        };
+        // the visualizer reads these every frame, so don't hold the lock across the round trips
+        let Some(mut readings) = self.readings.lock().ok().map(|r| *r) else {
+            return;
+        };
+
+        if request.brightness >= 0 && request.brightness != self.applied.0 {
+            if let Some(brightness) = &mut readings.brightness {
+                brightness.current = brightness.at_percent(request.brightness);
+                unsafe { SetMonitorBrightness(handle, brightness.current) };
+                self.applied.0 = request.brightness;
+            }
+        }
+
+        if request.contrast >= 0 && request.contrast != self.applied.1 {
+            if let Some(contrast) = &mut readings.contrast {
+                contrast.current = contrast.at_percent(request.contrast);
+                unsafe { SetMonitorContrast(handle, contrast.current) };
+                self.applied.1 = request.contrast;
+            }
+        }
+
+        if let Ok(mut shared) = self.readings.lock() {
+            *shared = readings;
+        }
+    }
+
+    fn read(&mut self) {
+        let readings = match self.handle() {
+            Some(handle) => unsafe {
+                let mut brightness = Setting::default();
+                let mut contrast = Setting::default();
+                Readings {
+                    brightness: (GetMonitorBrightness(
+                        handle,
+                        &mut brightness.min,
+                        &mut brightness.current,
+                        &mut brightness.max,
+                    ) != 0)
+                        .then_some(brightness),
+                    contrast: (GetMonitorContrast(
+                        handle,
+                        &mut contrast.min,
+                        &mut contrast.current,
+                        &mut contrast.max,
+                    ) != 0)
+                        .then_some(contrast),
+                }
+            },
+            None => Readings::default(),
+        };
+
+        if let Ok(mut shared) = self.readings.lock() {
+            *shared = readings;
+        }
+
+        // the viewer moves the sliders to what the monitor reports, e.g. after using its buttons
+        let percents = readings.percents();
+        if self.posted != Some(percents) {
+            self.posted = Some(percents);
+            HWND(self.viewer as _).post_message(
+                WM_MONITOR_READINGS,
+                WPARAM(percents.0 as usize),
+                LPARAM(percents.1 as isize),
+            );
+        }
+    }
+
+    fn open(&mut self, monitor: HMONITOR) {
+        self.close();
+        self.monitor = monitor;
+        // the new monitor starts from its own values, not the previous one's
+        self.applied = (-1, -1);
+
+        unsafe {
+            let mut count = 0;
+            if GetNumberOfPhysicalMonitorsFromHMONITOR(monitor, &mut count).is_err() || count == 0 {
+                return;
+            }
+
+            let mut physical = vec![PHYSICAL_MONITOR::default(); count as usize];
+            if GetPhysicalMonitorsFromHMONITOR(monitor, &mut physical).is_ok() {
+                self.physical = physical;
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        if !self.physical.is_empty() {
+            unsafe {
+                _ = DestroyPhysicalMonitors(&self.physical);
+            }
+        }
+
+        self.physical.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setting(min: u32, max: u32) -> Setting {
+        Setting {
+            min,
+            current: min,
+            max,
+        }
+    }
+
+    #[test]
+    fn percentages_read_back_on_long_ranges() {
+        for (min, max) in [(0, 100), (0, 255), (10, 1000)] {
+            let mut setting = setting(min, max);
+            for percent in 0..=100 {
+                setting.current = setting.at_percent(percent);
+                assert_eq!(setting.percent(), percent, "{min}..{max}");
+            }
+        }
+    }
+
+    #[test]
+    fn values_read_back_on_short_ranges() {
+        let mut short = setting(0, 7);
+        short.current = short.at_percent(50);
+        assert_eq!(short.current, 4);
+        assert_eq!(short.percent(), 57);
+
+        for (min, max) in [(0, 7), (3, 10), (0, 1)] {
+            let mut setting = setting(min, max);
+            for value in min..=max {
+                setting.current = value;
+                setting.current = setting.at_percent(setting.percent());
+                assert_eq!(setting.current, value, "{min}..{max}");
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped() {
+        assert_eq!(setting(5, 5).percent(), 0);
+
+        let mut setting = setting(10, 20);
+        assert_eq!(setting.at_percent(-5), 10);
+        assert_eq!(setting.at_percent(150), 20);
+
+        setting.current = 50;
+        assert_eq!(setting.percent(), 100);
+    }
+}
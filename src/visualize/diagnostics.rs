@@ -0,0 +1,253 @@
+use anyhow::Result;
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::query::PipelineStats, initializer::Initializer, profiler::FrameProfile,
+        renderer::Renderer,
+    },
+    gui::utils::Rect as _,
+};
+
+use super::{
+    stats::{FrameStats, PERCENTILE_RANKS},
+    text::{TextLine, TextPanel},
+};
+
+const MARGIN: i32 = 8;
+
+// per pass gpu work, frame stats and the frame timeline in the bottom-left corner, for tuning
+// the mesh shader cloud
+pub struct Diagnostics {
+    panel: TextPanel,
+    timeline: TextPanel,
+}
+
+impl Diagnostics {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        Ok(Self {
+            panel: TextPanel::new(ctx)?,
+            timeline: TextPanel::new(ctx)?,
+        })
+    }
+
+    pub fn process(
+        &mut self,
+        ctx: &mut Renderer,
+        config: &Config,
+        stats: &[(String, PipelineStats)],
+        profile: &FrameProfile,
+        frame: Option<&FrameStats>,
+    ) -> Result<()> {
+        if !config.show_diagnostics {
+            return Ok(());
+        }
+
+        let mut lines = diagnostic_lines(stats);
+        lines.extend(frame_lines(frame));
+        self.panel.set_lines(ctx, lines)?;
+        self.timeline.set_lines(ctx, timeline_lines(profile))?;
+
+        // the timeline sits on top of the statistics
+        let (width, height) = config.window_rect.usize();
+        let y = height as i32 - self.panel.size().1 as i32 - MARGIN;
+        self.panel
+            .draw(ctx, MARGIN, y.max(MARGIN), (width, height))?;
+
+        let y = y - self.timeline.size().1 as i32 - MARGIN;
+        self.timeline
+            .draw(ctx, MARGIN, y.max(MARGIN), (width, height))
+    }
+}
+
+fn diagnostic_lines(stats: &[(String, PipelineStats)]) -> Vec<TextLine> {
+    let line = |text: String| TextLine::new(text, None);
+
+    let mut lines = vec![line("Pipeline statistics".to_string())];
+    for (label, stats) in stats {
+        let counters = [
+            ("vs", stats.VSInvocations),
+            ("as", stats.ASInvocations),
+            ("ms", stats.MSInvocations),
+            ("prims", stats.CPrimitives),
+            ("ps", stats.PSInvocations),
+            ("cs", stats.CSInvocations),
+        ];
+        let counters: Vec<_> = counters
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(name, count)| format!("{name} {}", abbreviate(*count)))
+            .collect();
+
+        // disabled passes record nothing
+        if counters.is_empty() {
+            continue;
+        }
+        lines.push(line(format!("  {label}: {}", counters.join(", "))));
+    }
+
+    lines
+}
+
+// last frame's reduction, values as the histogram bins them
+fn frame_lines(frame: Option<&FrameStats>) -> Vec<TextLine> {
+    let line = |text: String| TextLine::new(text, None);
+
+    let Some(frame) = frame else {
+        return vec![];
+    };
+
+    let values = |values: &[f32; 4]| {
+        values
+            .iter()
+            .map(|v| format!("{v:.3}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let percentiles: Vec<_> = PERCENTILE_RANKS
+        .iter()
+        .zip(frame.percentiles)
+        .map(|(rank, value)| format!("p{:.0} {value:.3}", 100.0 * rank))
+        .collect();
+
+    vec![
+        line(format!(
+            "Frame stats, RGBL of {} px",
+            abbreviate(frame.count as _)
+        )),
+        line(format!("  min {}", values(&frame.min))),
+        line(format!("  max {}", values(&frame.max))),
+        line(format!("  mean {}", values(&frame.mean))),
+        line(format!("  luma {}", percentiles.join(", "))),
+        line(format!(
+            "  above 1.0 {} px, below 0.0 {} px",
+            abbreviate(frame.over as _),
+            abbreviate(frame.under as _)
+        )),
+    ]
+}
+
+// start and length in ms from the frame's first scope, cpu scopes first
+fn timeline_lines(profile: &FrameProfile) -> Vec<TextLine> {
+    let line = |text: String| TextLine::new(text, None);
+
+    let Some(origin) = profile.origin() else {
+        return vec![];
+    };
+
+    let cpu = profile.cpu.iter().map(|span| ("cpu", span));
+    let gpu = profile.gpu.iter().map(|span| ("gpu", span));
+
+    let mut lines = vec![line("Frame timeline (ms)".to_string())];
+    for (queue, span) in cpu.chain(gpu) {
+        let start = profile.ms(span.start as i64 - origin as i64);
+        let length = profile.ms(span.end as i64 - span.start as i64);
+
+        // passes that were skipped
+        if queue == "gpu" && length < 0.001 {
+            continue;
+        }
+        lines.push(line(format!(
+            "  {queue} {}: {start:.2} +{length:.2}",
+            span.label
+        )));
+    }
+
+    lines
+}
+
+// three significant digits at most, 1234567 reads 1.23M. the bounds are where rounding carries
+// into another digit, so 999999 reads 1.00M rather than 1000k
+fn abbreviate(count: u64) -> String {
+    let mut value = count as f64;
+    let mut suffix = "";
+    for unit in ["k", "M", "G"] {
+        if value < 999.5 {
+            break;
+        }
+        value /= 1000.0;
+        suffix = unit;
+    }
+
+    if suffix.is_empty() {
+        format!("{count}")
+    } else if value < 9.995 {
+        format!("{value:.2}{suffix}")
+    } else if value < 99.95 {
+        format!("{value:.1}{suffix}")
+    } else {
+        format!("{value:.0}{suffix}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::profiler::Span;
+
+    use super::*;
+
+    #[test]
+    fn abbreviates_large_counts() {
+        assert_eq!(abbreviate(0), "0");
+        assert_eq!(abbreviate(999), "999");
+        assert_eq!(abbreviate(1234), "1.23k");
+        assert_eq!(abbreviate(56_700), "56.7k");
+        assert_eq!(abbreviate(1_234_567), "1.23M");
+        assert_eq!(abbreviate(987_000_000), "987M");
+        assert_eq!(abbreviate(9_999), "10.0k");
+        assert_eq!(abbreviate(99_999), "100k");
+        assert_eq!(abbreviate(999_999), "1.00M");
+    }
+
+    #[test]
+    fn timeline_starts_at_first_scope() {
+        let span = |label: &str, start, end| Span {
+            label: label.to_string(),
+            start,
+            end,
+        };
+        let profile = FrameProfile {
+            cpu: vec![
+                span("config lock", 10_000, 10_010),
+                span("record", 12_000, 22_000),
+            ],
+            gpu: vec![span("frame", 20_000, 35_000), span("cloud", 25_000, 25_000)],
+            frequency: 10_000_000,
+        };
+
+        let lines = timeline_lines(&profile);
+        let texts: Vec<_> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "Frame timeline (ms)",
+                "  cpu config lock: 0.00 +0.00",
+                "  cpu record: 0.20 +1.00",
+                "  gpu frame: 1.00 +1.50",
+            ]
+        );
+        assert!(timeline_lines(&FrameProfile::default()).is_empty());
+    }
+
+    #[test]
+    fn lists_only_counted_stages() {
+        let stats = PipelineStats {
+            ASInvocations: 64,
+            MSInvocations: 4096,
+            CPrimitives: 262_144,
+            PSInvocations: 1_500_000,
+            ..Default::default()
+        };
+
+        let idle = PipelineStats::default();
+        let lines = diagnostic_lines(&[
+            ("histogram".to_string(), idle),
+            ("cloud".to_string(), stats),
+        ]);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[1].text,
+            "  cloud: as 64, ms 4.10k, prims 262k, ps 1.50M"
+        );
+    }
+}
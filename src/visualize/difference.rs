@@ -0,0 +1,165 @@
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+        Direct3D12::{
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE, D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+        },
+        Dxgi::Common::DXGI_FORMAT_R16G16B16A16_FLOAT,
+    },
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{descriptor::RtvHeap, pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        renderer::{Renderer, ViewportKind},
+        resource::RenderTexture,
+    },
+    gui::utils::Rect as _,
+};
+
+pub struct FrameDifference {
+    capture_pso: PipelineState,
+    draw_pso: PipelineState,
+    #[allow(unused)]
+    rtv_heap: RtvHeap,
+    frames: [RenderTexture; 2],
+    current: usize,
+    captured: u32,
+    // where the kept frames were captured
+    window_rect: RECT,
+}
+
+impl FrameDifference {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let capture_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/DifferenceVs.bin"),
+            include_bytes!("../shaders/bin/DifferenceCapturePs.bin"),
+            BlendDesc::none(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let draw_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/DifferenceVs.bin"),
+            include_bytes!("../shaders/bin/DifferencePs.bin"),
+            BlendDesc::alpha(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let rtv_heap = RtvHeap::new(ctx, 2)?;
+        let mut frame = |i| {
+            RenderTexture::new(
+                ctx,
+                rtv_heap.descriptor(i),
+                1,
+                1,
+                DXGI_FORMAT_R16G16B16A16_FLOAT,
+                [0.0; 4],
+            )
+        };
+        let frames = [frame(0)?, frame(1)?];
+
+        Ok(Self {
+            capture_pso,
+            draw_pso,
+            rtv_heap,
+            frames,
+            current: 0,
+            captured: 0,
+            window_rect: RECT::default(),
+        })
+    }
+
+    // fresh is set when the desktop was duplicated this frame, a reused or frozen copy
+    // leaves the history alone so the last change stays visible
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config, fresh: bool) -> Result<()> {
+        if !config.enable_frame_difference {
+            self.captured = 0;
+            return Ok(());
+        }
+
+        // moved or resized, the kept frame no longer lines up with the window
+        if self.window_rect != config.window_rect {
+            let (width, height) = config.window_rect.usize();
+            if self.frames[0].size() != (width, height) {
+                for frame in &mut self.frames {
+                    let old = frame.resource.clone();
+                    frame.resize(ctx.device(), width as _, height as _)?;
+                    ctx.retire(old);
+                }
+            }
+            self.window_rect = config.window_rect;
+            self.captured = 0;
+        }
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            mode: u32,
+            threshold: f32,
+        }
+
+        let params = Params {
+            rect: config.window_rect,
+            mode: config.frame_difference_mode,
+            threshold: config.frame_difference_threshold,
+        };
+
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+        if fresh {
+            self.current = 1 - self.current;
+            let frame = &self.frames[self.current];
+
+            ctx.resource_barrier(&[frame.transition_barrier(
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+            )]);
+            ctx.set_render_targets(&[frame.rtv]);
+
+            ctx.set_pipeline_state(&self.capture_pso);
+            ctx.set_graphics_constants(&params);
+            ctx.draw(3, 1);
+
+            ctx.reset_render_targets();
+            ctx.resource_barrier(&[frame.transition_barrier(
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            )]);
+
+            self.captured = (self.captured + 1).min(2);
+        }
+
+        // the first frame has nothing to compare against
+        if self.captured < 2 {
+            return Ok(());
+        }
+
+        ctx.set_pipeline_state(&self.draw_pso);
+        ctx.set_graphics_constants(&params);
+        ctx.set_graphics_srvs(&[
+            self.frames[self.current].srv,
+            self.frames[1 - self.current].srv,
+        ]);
+        ctx.draw(3, 1);
+
+        Ok(())
+    }
+}
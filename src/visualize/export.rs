@@ -0,0 +1,377 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result};
+use windows::{
+    core::{w, GUID, HSTRING, PCWSTR, PWSTR, VARIANT},
+    Win32::{
+        Foundation::{COLORREF, GENERIC_WRITE, POINT, RECT},
+        Graphics::{
+            Gdi::{
+                CreateCompatibleDC, CreateDIBSection, CreatePen, CreateSolidBrush, DeleteDC,
+                DeleteObject, FillRect, GdiFlush, GetStockObject, LineTo, MoveToEx, Polyline,
+                SelectObject, SetBkMode, SetTextColor, TextOutW, BITMAPINFO, BITMAPINFOHEADER,
+                BI_RGB, DEFAULT_GUI_FONT, DIB_RGB_COLORS, HDC, PS_SOLID, TRANSPARENT,
+            },
+            Imaging::{
+                CLSID_WICImagingFactory, GUID_ContainerFormatPng, GUID_ContainerFormatWmp,
+                GUID_WICPixelFormat32bppBGR, GUID_WICPixelFormat64bppRGBAHalf, IWICImagingFactory,
+                WICBitmapEncoderNoCache,
+            },
+        },
+        System::{
+            Com::{CoCreateInstance, StructuredStorage::PROPBAG2, CLSCTX_INPROC_SERVER},
+            SystemInformation::GetLocalTime,
+            Variant::VT_BOOL,
+        },
+    },
+};
+
+use crate::{
+    config::{
+        HISTOGRAM_MODE_CHROMA, HISTOGRAM_MODE_HUE, HISTOGRAM_MODE_LUMA, HISTOGRAM_MODE_RGB,
+        HISTOGRAM_MODE_RGBL, HISTOGRAM_MODE_SAT,
+    },
+    graphics::{math::linear_to_srgb8, resource::Pixels},
+};
+
+use super::histogram::{pq_to_nits, HistogramData, MAX_CHROMA};
+
+pub const EXPORT_DIR: &str = "colormel-export";
+
+const CHART_WIDTH: i32 = 800;
+const CHART_HEIGHT: i32 = 400;
+const MARGIN_LEFT: i32 = 56;
+const MARGIN_RIGHT: i32 = 16;
+const MARGIN_TOP: i32 = 32;
+const MARGIN_BOTTOM: i32 = 40;
+
+// writes <dir>/histogram-<local time>.csv and .png, returns the png path
+pub fn export_histogram(dir: impl AsRef<Path>, data: &HistogramData) -> Result<PathBuf> {
+    std::fs::create_dir_all(&dir)?;
+
+    let stem = dir.as_ref().join(format!("histogram-{}", timestamp()));
+    let csv = stem.with_extension("csv");
+    let png = stem.with_extension("png");
+
+    write_csv(&csv, data)?;
+    write_png(&png, data)?;
+
+    Ok(png)
+}
+
+pub fn timestamp() -> String {
+    let t = unsafe { GetLocalTime() };
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        t.wYear, t.wMonth, t.wDay, t.wHour, t.wMinute, t.wSecond
+    )
+}
+
+pub struct Channel<'a> {
+    pub name: &'static str,
+    pub color: COLORREF,
+    pub bins: &'a [u32],
+}
+
+pub fn channels(data: &HistogramData) -> Vec<Channel> {
+    channels_of(data.mode, &data.bins, data.colors)
+}
+
+// the shader stores rgb histograms as g, r, b, luma, colors are in r, g, b, luma order like the
+// config's and the modes drawn as a spectrum stay white
+pub fn channels_of(mode: u32, bins: &[Vec<u32>; 4], colors: [u32; 4]) -> Vec<Channel> {
+    let channel = |name, color, index: usize| Channel {
+        name,
+        color: COLORREF(color),
+        bins: &bins[index],
+    };
+    let [r, g, b, l] = colors;
+
+    match mode {
+        HISTOGRAM_MODE_RGB => vec![channel("r", r, 1), channel("g", g, 0), channel("b", b, 2)],
+        HISTOGRAM_MODE_RGBL => vec![
+            channel("r", r, 1),
+            channel("g", g, 0),
+            channel("b", b, 2),
+            channel("luma", l, 3),
+        ],
+        HISTOGRAM_MODE_LUMA => vec![channel("luma", l, 0)],
+        HISTOGRAM_MODE_HUE => vec![channel("hue", 0xffffff, 0)],
+        HISTOGRAM_MODE_SAT => vec![channel("saturation", 0xffffff, 0)],
+        HISTOGRAM_MODE_CHROMA => vec![channel("chroma", 0xffffff, 0)],
+        _ => vec![],
+    }
+}
+
+pub fn write_csv(path: &Path, data: &HistogramData) -> Result<()> {
+    let channels = channels(data);
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    write!(file, "bin")?;
+    for channel in &channels {
+        write!(file, ",{}", channel.name)?;
+    }
+    writeln!(file)?;
+
+    for bin in 0..data.bins[0].len() {
+        write!(file, "{bin}")?;
+        for channel in &channels {
+            write!(file, ",{}", channel.bins[bin])?;
+        }
+        writeln!(file)?;
+    }
+
+    Ok(())
+}
+
+fn write_png(path: &Path, data: &HistogramData) -> Result<()> {
+    let pixels = draw_chart(data)?;
+    save_png(path, CHART_WIDTH as _, CHART_HEIGHT as _, &pixels)
+}
+
+// draws with gdi into a top-down 32bpp dib and returns a copy of its bits
+fn draw_chart(data: &HistogramData) -> Result<Vec<u8>> {
+    unsafe {
+        let hdc = CreateCompatibleDC(None);
+
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as _,
+                biWidth: CHART_WIDTH,
+                biHeight: -CHART_HEIGHT,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut bits = std::ptr::null_mut();
+        let bitmap = match CreateDIBSection(hdc, &info, DIB_RGB_COLORS, &mut bits, None, 0) {
+            Ok(bitmap) => bitmap,
+            Err(e) => {
+                _ = DeleteDC(hdc);
+                anyhow::bail!(e)
+            }
+        };
+        let old = SelectObject(hdc, bitmap);
+
+        draw_axes(hdc, data);
+        for channel in channels(data) {
+            draw_channel(hdc, &channel, max_count(data));
+        }
+
+        _ = GdiFlush();
+
+        let size = (4 * CHART_WIDTH * CHART_HEIGHT) as usize;
+        let pixels = std::slice::from_raw_parts(bits as *const u8, size).to_vec();
+
+        SelectObject(hdc, old);
+        _ = DeleteObject(bitmap);
+        _ = DeleteDC(hdc);
+
+        Ok(pixels)
+    }
+}
+
+fn max_count(data: &HistogramData) -> u32 {
+    channels(data)
+        .iter()
+        .flat_map(|channel| channel.bins.iter())
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1)
+}
+
+fn plot_rect() -> RECT {
+    RECT {
+        left: MARGIN_LEFT,
+        top: MARGIN_TOP,
+        right: CHART_WIDTH - MARGIN_RIGHT,
+        bottom: CHART_HEIGHT - MARGIN_BOTTOM,
+    }
+}
+
+fn text(hdc: HDC, x: i32, y: i32, text: &str) {
+    let text: Vec<u16> = text.encode_utf16().collect();
+    unsafe {
+        _ = TextOutW(hdc, x, y, &text);
+    }
+}
+
+fn draw_axes(hdc: HDC, data: &HistogramData) {
+    let plot = plot_rect();
+
+    unsafe {
+        let background = CreateSolidBrush(COLORREF(0x202020));
+        FillRect(
+            hdc,
+            &RECT {
+                right: CHART_WIDTH,
+                bottom: CHART_HEIGHT,
+                ..Default::default()
+            },
+            background,
+        );
+        _ = DeleteObject(background);
+
+        let grid = CreatePen(PS_SOLID, 1, COLORREF(0x505050));
+        let old = SelectObject(hdc, grid);
+        for i in 0..=4 {
+            let x = plot.left + i * (plot.right - plot.left) / 4;
+            _ = MoveToEx(hdc, x, plot.top, None);
+            _ = LineTo(hdc, x, plot.bottom);
+        }
+        _ = MoveToEx(hdc, plot.left, plot.bottom, None);
+        _ = LineTo(hdc, plot.right, plot.bottom);
+        SelectObject(hdc, old);
+        _ = DeleteObject(grid);
+
+        SelectObject(hdc, GetStockObject(DEFAULT_GUI_FONT));
+        SetBkMode(hdc, TRANSPARENT);
+        SetTextColor(hdc, COLORREF(0xc0c0c0));
+
+        let title = match data.mode {
+            HISTOGRAM_MODE_RGB => "RGB",
+            HISTOGRAM_MODE_RGBL => "RGB + Luma",
+            HISTOGRAM_MODE_LUMA => "Luma",
+            HISTOGRAM_MODE_SAT => "Saturation",
+            HISTOGRAM_MODE_CHROMA => "Chroma",
+            _ => "Hue",
+        };
+        let encoded = matches!(
+            data.mode,
+            HISTOGRAM_MODE_RGB | HISTOGRAM_MODE_RGBL | HISTOGRAM_MODE_LUMA
+        );
+        let pq = data.hdr && encoded;
+        let unit = if pq { " (PQ, nits)" } else { "" };
+        text(hdc, plot.left, 8, &format!("{title} histogram{unit}"));
+
+        for i in 0..=4 {
+            let x = plot.left + i * (plot.right - plot.left) / 4;
+            let label = match data.mode {
+                HISTOGRAM_MODE_HUE => format!("{}", 90 * i),
+                HISTOGRAM_MODE_SAT => format!("{}%", 25 * i),
+                HISTOGRAM_MODE_CHROMA => format!("{}", MAX_CHROMA as i32 * i / 4),
+                _ if pq => format!("{:.0}", pq_to_nits(i as f32 / 4.0)),
+                _ => format!("{}", (data.bins[0].len() as i32 - 1) * i / 4),
+            };
+            text(hdc, x - 8, plot.bottom + 8, &label);
+        }
+
+        text(hdc, 8, plot.top, &format!("{}", max_count(data)));
+        text(hdc, 8, plot.bottom - 16, "0");
+    }
+}
+
+fn draw_channel(hdc: HDC, channel: &Channel, max_count: u32) {
+    let plot = plot_rect();
+    let width = (plot.right - plot.left) as f32;
+    let height = (plot.bottom - plot.top) as f32;
+
+    let points: Vec<_> = channel
+        .bins
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| POINT {
+            x: plot.left + (width * i as f32 / (channel.bins.len() - 1) as f32) as i32,
+            y: plot.bottom - (height * count as f32 / max_count as f32) as i32,
+        })
+        .collect();
+
+    unsafe {
+        let pen = CreatePen(PS_SOLID, 1, channel.color);
+        let old = SelectObject(hdc, pen);
+        _ = Polyline(hdc, &points);
+        SelectObject(hdc, old);
+        _ = DeleteObject(pen);
+    }
+}
+
+// opaque srgb bgra for save_png, scRGB is clipped to sdr white and the transparent parts are black
+pub fn bgra(pixels: &Pixels) -> Vec<u8> {
+    pixels
+        .rgb
+        .iter()
+        .flat_map(|&[r, g, b]| {
+            [
+                linear_to_srgb8(b),
+                linear_to_srgb8(g),
+                linear_to_srgb8(r),
+                255,
+            ]
+        })
+        .collect()
+}
+
+// bgra rows, alpha ignored
+pub fn save_png(path: &Path, width: u32, height: u32, pixels: &[u8]) -> Result<()> {
+    save_image(
+        path,
+        &GUID_ContainerFormatPng,
+        GUID_WICPixelFormat32bppBGR,
+        (width, height, 4 * width),
+        pixels,
+        false,
+    )
+}
+
+// lossless jpeg xr keeps scRGB half floats as they are, values outside 0 to 1 included
+pub fn save_jxr(path: &Path, width: u32, height: u32, pixels: &[u8]) -> Result<()> {
+    save_image(
+        path,
+        &GUID_ContainerFormatWmp,
+        GUID_WICPixelFormat64bppRGBAHalf,
+        (width, height, 8 * width),
+        pixels,
+        true,
+    )
+}
+
+fn save_image(
+    path: &Path,
+    container: &GUID,
+    mut format: GUID,
+    (width, height, stride): (u32, u32, u32),
+    pixels: &[u8],
+    lossless: bool,
+) -> Result<()> {
+    unsafe {
+        let factory: IWICImagingFactory =
+            CoCreateInstance(&CLSID_WICImagingFactory, None, CLSCTX_INPROC_SERVER)?;
+
+        let stream = factory.CreateStream()?;
+        let path = HSTRING::from(path.as_os_str());
+        stream.InitializeFromFilename(PCWSTR(path.as_ptr()), GENERIC_WRITE.0)?;
+
+        let encoder = factory.CreateEncoder(container, std::ptr::null())?;
+        encoder.Initialize(&stream, WICBitmapEncoderNoCache)?;
+
+        let mut frame = None;
+        let mut options = None;
+        encoder.CreateNewFrame(&mut frame, &mut options)?;
+        let frame = frame.context("the encoder returned no frame")?;
+
+        if let (true, Some(options)) = (lossless, &options) {
+            let option = PROPBAG2 {
+                vt: VT_BOOL,
+                pstrName: PWSTR(w!("Lossless").as_ptr() as _),
+                ..Default::default()
+            };
+            options.Write(1, &option, &VARIANT::from(true))?;
+        }
+        frame.Initialize(options.as_ref())?;
+        frame.SetSize(width, height)?;
+        frame.SetPixelFormat(&mut format)?;
+        frame.WritePixels(height, stride, pixels)?;
+        frame.Commit()?;
+        encoder.Commit()?;
+
+        Ok(())
+    }
+}
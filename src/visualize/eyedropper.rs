@@ -0,0 +1,142 @@
+use anyhow::Result;
+use windows::Win32::Foundation::RECT;
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::resource::Resource, initializer::Initializer, math::linear_to_srgb8,
+        renderer::Renderer, resource::TextureReadback,
+    },
+    gui::utils::Rect as _,
+};
+
+use super::{
+    color,
+    sampling::SampleFilter,
+    text::{TextLine, TextPanel},
+};
+
+// distance between the picked pixel and the readout
+const OFFSET: i32 = 16;
+
+// samples the captured frame under a clicked point and shows its value next to it
+pub struct Eyedropper {
+    readback: TextureReadback,
+    filter: SampleFilter<3>,
+    point: Option<(i32, i32)>,
+    rgb: Option<[f32; 3]>,
+    panel: TextPanel,
+}
+
+impl Eyedropper {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        Ok(Self {
+            readback: TextureReadback::default(),
+            filter: SampleFilter::new(),
+            point: None,
+            rgb: None,
+            panel: TextPanel::new(ctx)?,
+        })
+    }
+
+    // records a 1x1 copy of the picked pixel, the value shown is from the previous frame
+    pub fn process(
+        &mut self,
+        ctx: &mut Renderer,
+        desktop: &Resource,
+        config: &Config,
+    ) -> Result<()> {
+        let point = config.pick_point.filter(|_| config.enable_eyedropper);
+        if point != self.point {
+            self.point = point;
+            self.rgb = None;
+            self.filter.reset();
+        }
+
+        let Some((x, y)) = point else {
+            return Ok(());
+        };
+
+        let left = config.window_rect.left + x;
+        let top = config.window_rect.top + y;
+        self.readback.copy(
+            ctx,
+            desktop,
+            RECT {
+                left,
+                top,
+                right: left + 1,
+                bottom: top + 1,
+            },
+        )?;
+
+        if let Some(rgb) = self.rgb {
+            self.panel.set_lines(ctx, readout(rgb, config.hdr_mode))?;
+
+            // keep the readout inside the window
+            let (width, height) = config.window_rect.size();
+            let (panel_width, panel_height) = self.panel.size();
+            let panel_x = if x + OFFSET + panel_width as i32 > width {
+                x - OFFSET - panel_width as i32
+            } else {
+                x + OFFSET
+            };
+            let panel_y = (y + OFFSET).min(height - panel_height as i32);
+
+            self.panel.draw(
+                ctx,
+                panel_x.max(0),
+                panel_y.max(0),
+                config.window_rect.usize(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // valid after the frame recorded by process() has finished on the GPU
+    pub fn read(&mut self, config: &Config) -> Result<()> {
+        if self.point.is_none() {
+            return Ok(());
+        }
+
+        if let Some(pixels) = self.readback.read()? {
+            if let Some(&rgb) = pixels.rgb.first() {
+                self.rgb = Some(self.filter.push(
+                    rgb,
+                    config.sample_frames,
+                    config.reject_outliers,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn readout(rgb: [f32; 3], hdr: bool) -> Vec<TextLine> {
+    let [r, g, b] = rgb.map(linear_to_srgb8);
+    let [h, s, l] = color::rgb_to_hsl([r, g, b].map(|c| c as f32 / 255.0));
+    let colorref = r as u32 | (g as u32) << 8 | (b as u32) << 16;
+
+    let mut lines = vec![
+        TextLine::new(format!("#{r:02X}{g:02X}{b:02X}"), Some(colorref)),
+        TextLine::new(format!("RGB {r} {g} {b}"), None),
+        TextLine::new(
+            format!("HSL {h:.0}\u{b0} {:.0}% {:.0}%", 100.0 * s, 100.0 * l),
+            None,
+        ),
+        TextLine::new(
+            format!("Linear {:.3} {:.3} {:.3}", rgb[0], rgb[1], rgb[2]),
+            None,
+        ),
+    ];
+
+    // scRGB has 1.0 at 80 nits
+    if hdr {
+        let [_, y, _] = color::rgb_to_xyz(rgb);
+        lines.push(TextLine::new(format!("{:.0} nits", 80.0 * y), None));
+    }
+
+    lines
+}
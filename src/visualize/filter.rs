@@ -1,85 +1,193 @@
-use anyhow::Result;
-use windows::Win32::{
-    Foundation::RECT,
-    Graphics::{
-        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
-        Direct3D12::D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
-        Dxgi::Common::DXGI_FORMAT_R16G16B16A16_FLOAT,
-    },
-};
-
-use crate::{
-    config::Config,
-    graphics::{
-        core::{pso::PipelineState, wrap::*},
-        initializer::Initializer,
-        renderer::Renderer,
-    },
-};
-
-#[allow(unused)]
-pub struct Filter {
-    pso: PipelineState,
-}
-
-impl Filter {
-    pub fn new(ctx: &mut Initializer) -> Result<Self> {
-        let pso = ctx.create_graphics_pipeline(
-            include_bytes!("../shaders/bin/FilterVs.bin"),
-            include_bytes!("../shaders/bin/FilterPs.bin"),
-            BlendDesc::none(),
-            RasterizerDesc::none(),
-            DepthStencilDesc::none(),
-            &[],
-            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
-            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
-            None,
-            None,
-        )?;
-
-        Ok(Self { pso })
-    }
-
-    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
-        if config.enable_filter {
-            self.draw(ctx, config)?;
-        }
-        Ok(())
-    }
-
-    fn draw(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
-        ctx.set_pipeline_state(&self.pso);
-        ctx.set_viewport(crate::graphics::renderer::ViewportKind::Full);
-        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
-
-        #[repr(C)]
-        struct Params {
-            rect: RECT,
-            mode: u32,
-            mask: [f32; 3],
-        }
-
-        let params = Params {
-            rect: config.window_rect,
-            mode: config.filter_mode,
-            mask: channel_mask(&config.filter_channels),
-        };
-        ctx.set_graphics_constants(&params);
-
-        ctx.draw(3, 1);
-
-        Ok(())
-    }
-}
-
-fn channel_mask(channels: &[bool]) -> [f32; 3] {
-    fn mask(ch: bool) -> f32 {
-        if ch {
-            1.0
-        } else {
-            0.0
-        }
-    }
-
-    [mask(channels[0]), mask(channels[1]), mask(channels[2])]
-}
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+        Direct3D12::{
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE, D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+        },
+        Dxgi::Common::DXGI_FORMAT_R16G16B16A16_FLOAT,
+    },
+};
+
+use crate::{
+    config::{Config, FILTER_MODE_RGB, TRANSPARENCY_MODE_DITHER},
+    graphics::{
+        core::{
+            descriptor::{Descriptor, RtvHeap},
+            pso::PipelineState,
+            wrap::*,
+        },
+        initializer::Initializer,
+        renderer::Renderer,
+        resource::RenderTexture,
+    },
+    gui::utils::Rect as _,
+};
+
+#[allow(unused)]
+pub struct Filter {
+    pso: PipelineState,
+    frame: u32,
+    rtv_heap: RtvHeap,
+    output: RenderTexture,
+}
+
+impl Filter {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/FilterVs.bin"),
+            include_bytes!("../shaders/bin/FilterPs.bin"),
+            BlendDesc::none(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let rtv_heap = RtvHeap::new(ctx, 1)?;
+        let output = RenderTexture::new(
+            ctx,
+            rtv_heap.descriptor(0),
+            1,
+            1,
+            DXGI_FORMAT_R16G16B16A16_FLOAT,
+            [0.0; 4],
+        )?;
+
+        Ok(Self {
+            pso,
+            frame: 0,
+            rtv_heap,
+            output,
+        })
+    }
+
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        let levels = preview_levels(config);
+
+        // previewed levels show through the plain rgb filter while the filter is off
+        if config.enable_filter || levels.is_some() {
+            // animates the zebra stripes
+            self.frame = self.frame.wrapping_add(1);
+
+            // sdr only, scRGB keeps more than 8 bits all the way to the display
+            let dither = config.transparency_mode == TRANSPARENCY_MODE_DITHER && !config.hdr_mode;
+            self.draw(ctx, config, levels, dither, false)?;
+        }
+        Ok(())
+    }
+
+    // renders the filter again into a window sized texture for the scopes to analyze, masked like
+    // the raw capture so our own overlay doesn't feed back. previewed levels are left out, the
+    // suggestion would otherwise chase its own output
+    pub fn render_output(
+        &mut self,
+        ctx: &mut Renderer,
+        config: &Config,
+    ) -> Result<Option<Descriptor>> {
+        if !(config.enable_filter && config.analyze_filtered) {
+            return Ok(None);
+        }
+
+        let (width, height) = config.window_rect.usize();
+        if self.output.size() != (width, height) {
+            self.output.resize(ctx.device(), width, height)?;
+        }
+
+        ctx.resource_barrier(&[self.output.transition_barrier(
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+        )]);
+        ctx.set_render_targets(&[self.output.rtv]);
+
+        self.draw(ctx, config, None, false, true)?;
+
+        ctx.reset_render_targets();
+        ctx.resource_barrier(&[self.output.transition_barrier(
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+        )]);
+
+        Ok(Some(self.output.srv))
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Renderer,
+        config: &Config,
+        levels: Option<[f32; 2]>,
+        dither: bool,
+        masked: bool,
+    ) -> Result<()> {
+        ctx.set_pipeline_state(&self.pso);
+        ctx.set_viewport(crate::graphics::renderer::ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            mode: u32,
+            mask: [f32; 3],
+            frame: u32,
+            zebra_high: f32,
+            zebra_low: f32,
+            levels: [f32; 2],
+            desaturation: f32,
+            solo: u32,
+            dither: u32,
+            masked: u32,
+        }
+
+        let (mode, mask, solo) = if config.enable_filter {
+            (
+                config.filter_mode,
+                channel_mask(&config.filter_channels),
+                config.filter_solo,
+            )
+        } else {
+            (FILTER_MODE_RGB, [1.0; 3], false)
+        };
+
+        let params = Params {
+            rect: config.window_rect,
+            mode,
+            mask,
+            frame: self.frame,
+            zebra_high: config.zebra_high,
+            zebra_low: config.zebra_low,
+            levels: levels.unwrap_or([0.0, 1.0]),
+            desaturation: config.desaturation,
+            solo: solo as u32,
+            dither: dither as u32,
+            masked: masked as u32,
+        };
+        ctx.set_graphics_constants(&params);
+
+        ctx.draw(3, 1);
+
+        Ok(())
+    }
+}
+
+fn preview_levels(config: &Config) -> Option<[f32; 2]> {
+    config
+        .suggested_levels
+        .filter(|_| config.suggest_levels && config.preview_levels)
+}
+
+fn channel_mask(channels: &[bool]) -> [f32; 3] {
+    fn mask(ch: bool) -> f32 {
+        if ch {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    [mask(channels[0]), mask(channels[1]), mask(channels[2])]
+}
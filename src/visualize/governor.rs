@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use crate::config::{
+    Config, TRANSPARENCY_MODE_AUTO, TRANSPARENCY_MODE_BLEND, TRANSPARENCY_MODE_DITHER,
+};
+
+// budget when the frame rate is unlimited
+const DEFAULT_BUDGET: Duration = Duration::from_micros(16_667);
+
+// hysteresis keeps the mode from flickering around the budget
+const SLOW: f32 = 1.1;
+const FAST: f32 = 0.7;
+
+// resolves automatic choices from how long recent frames took
+pub struct Governor {
+    frame_time: Option<f32>,
+    dither: bool,
+}
+
+impl Governor {
+    pub fn new() -> Self {
+        Self {
+            frame_time: None,
+            dither: false,
+        }
+    }
+
+    // time spent processing one frame, gpu included
+    pub fn update(&mut self, config: &Config, elapsed: Duration) {
+        let elapsed = elapsed.as_secs_f32();
+        let frame_time = match self.frame_time {
+            Some(frame_time) => frame_time + 0.1 * (elapsed - frame_time),
+            None => elapsed,
+        };
+        self.frame_time = Some(frame_time);
+
+        let budget = config
+            .frame_interval()
+            .unwrap_or(DEFAULT_BUDGET)
+            .as_secs_f32();
+        if frame_time > SLOW * budget {
+            self.dither = true;
+        } else if frame_time < FAST * budget {
+            self.dither = false;
+        }
+    }
+
+    // blend or dither, never auto
+    pub fn transparency_mode(&self, config: &Config) -> u32 {
+        match config.transparency_mode {
+            TRANSPARENCY_MODE_AUTO if self.dither => TRANSPARENCY_MODE_DITHER,
+            TRANSPARENCY_MODE_AUTO => TRANSPARENCY_MODE_BLEND,
+            mode => mode,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mode: u32) -> Config {
+        let mut config = Config::load("").unwrap();
+        config.target_fps = 50;
+        config.transparency_mode = mode;
+        config
+    }
+
+    #[test]
+    fn auto_dithers_while_frames_are_slow() {
+        let config = config(TRANSPARENCY_MODE_AUTO);
+        let mut governor = Governor::new();
+
+        governor.update(&config, Duration::from_millis(10));
+        assert_eq!(governor.transparency_mode(&config), TRANSPARENCY_MODE_BLEND);
+
+        for _ in 0..50 {
+            governor.update(&config, Duration::from_millis(40));
+        }
+        assert_eq!(
+            governor.transparency_mode(&config),
+            TRANSPARENCY_MODE_DITHER
+        );
+
+        // inside the hysteresis band nothing changes
+        for _ in 0..50 {
+            governor.update(&config, Duration::from_millis(18));
+        }
+        assert_eq!(
+            governor.transparency_mode(&config),
+            TRANSPARENCY_MODE_DITHER
+        );
+
+        for _ in 0..50 {
+            governor.update(&config, Duration::from_millis(5));
+        }
+        assert_eq!(governor.transparency_mode(&config), TRANSPARENCY_MODE_BLEND);
+    }
+
+    #[test]
+    fn manual_modes_are_kept() {
+        let mut governor = Governor::new();
+        governor.update(&config(TRANSPARENCY_MODE_AUTO), Duration::from_millis(100));
+
+        let config = config(TRANSPARENCY_MODE_BLEND);
+        assert_eq!(governor.transparency_mode(&config), TRANSPARENCY_MODE_BLEND);
+    }
+}
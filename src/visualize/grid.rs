@@ -1,214 +1,611 @@
-use core::f32::consts::PI;
-
-use anyhow::Result;
-use windows::{
-    core::s,
-    Win32::Graphics::{
-        Direct3D::D3D_PRIMITIVE_TOPOLOGY_LINELIST,
-        Direct3D12::{D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE, D3D12_RASTERIZER_DESC},
-        Dxgi::Common::{
-            DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32G32B32_FLOAT,
-        },
-    },
-};
-
-use crate::{
-    config::Config,
-    graphics::{
-        core::{pso::PipelineState, wrap::*},
-        initializer::Initializer,
-        renderer::Renderer,
-        resource::VertexBuffer,
-    },
-};
-
-pub struct Grids {
-    pso: PipelineState,
-    grids: [VertexBuffer; 2],
-}
-
-impl Grids {
-    pub fn new(ctx: &mut Initializer) -> Result<Self> {
-        let pso = ctx.create_graphics_pipeline(
-            include_bytes!("../shaders/bin/PrimitiveVs.bin"),
-            include_bytes!("../shaders/bin/PrimitivePs.bin"),
-            BlendDesc::none(),
-            D3D12_RASTERIZER_DESC {
-                AntialiasedLineEnable: true.into(),
-                ..RasterizerDesc::none()
-            },
-            DepthStencilDesc::depth(),
-            &[
-                InputElementDesc::per_vertex(s!("POSITION"), DXGI_FORMAT_R32G32B32_FLOAT),
-                InputElementDesc::per_vertex(s!("COLOR"), DXGI_FORMAT_R32G32B32_FLOAT),
-            ],
-            D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
-            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
-            Some(DXGI_FORMAT_D16_UNORM),
-            None,
-        )?;
-
-        let grids = [
-            VertexBuffer::new(ctx, &rgb_grid())?,
-            VertexBuffer::new(ctx, &hsl_grid(6, 48))?,
-        ];
-
-        Ok(Self { pso, grids })
-    }
-
-    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
-        if config.enable_color_cloud && config.show_grid {
-            self.show(ctx, config)?;
-        }
-
-        Ok(())
-    }
-
-    fn show(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
-        ctx.set_pipeline_state(&self.pso);
-        ctx.set_viewport(crate::graphics::renderer::ViewportKind::Adjust);
-        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
-
-        let vertex_buffer = &self.grids[config.color_cloud_mode as usize];
-        ctx.set_vertex_buffers(&[vertex_buffer.view()]);
-
-        #[repr(C)]
-        struct Params {
-            projection: [f32; 12],
-        }
-
-        let params = Params {
-            projection: config.projection_matrix().as_4x3(),
-        };
-
-        ctx.set_graphics_constants(&params);
-        ctx.draw(vertex_buffer.vertex_count(), 1);
-
-        Ok(())
-    }
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Default)]
-struct Vertex {
-    pub position: [f32; 3],
-    pub color: [f32; 3],
-}
-
-impl Vertex {
-    fn new(position: [f32; 3], color: [f32; 3]) -> Self {
-        Self { position, color }
-    }
-}
-
-fn rgb_grid() -> Vec<Vertex> {
-    fn rgb_position(r: f32, g: f32, b: f32) -> [f32; 3] {
-        [1.25 * (r - 0.5), 1.25 * (g - 0.5), 1.25 * (b - 0.5)]
-    }
-
-    fn rgb_vertex(r: f32, g: f32, b: f32) -> Vertex {
-        Vertex::new(rgb_position(r, g, b), [r, g, b])
-    }
-
-    let v0 = rgb_vertex(0.0, 0.0, 0.0);
-    let r = rgb_vertex(1.0, 0.0, 0.0);
-    let g = rgb_vertex(0.0, 1.0, 0.0);
-    let b = rgb_vertex(0.0, 0.0, 1.0);
-    let rg = rgb_vertex(1.0, 1.0, 0.0);
-    let rb = rgb_vertex(1.0, 0.0, 1.0);
-    let gb = rgb_vertex(0.0, 1.0, 1.0);
-    let v1 = rgb_vertex(1.0, 1.0, 1.0);
-
-    vec![
-        v0, r, v0, g, v0, b, r, rg, r, rb, g, rg, g, gb, b, rb, b, gb, rg, v1, rb, v1, gb, v1,
-    ]
-}
-
-fn hsl_grid(n_hue: u32, n_div: u32) -> Vec<Vertex> {
-    fn hsl_to_position(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
-        let h = 2.0 * PI * hue;
-        let mut s = saturation;
-        let mut l = 2.0 * lightness - 1.0;
-
-        let a = s + l.abs();
-        let b = (s * s + l * l).sqrt();
-        if b > 0.0 {
-            let n = a / b;
-            s *= n;
-            l *= n;
-        }
-
-        let y = l;
-
-        let (mut z, mut x) = h.sin_cos();
-        x *= s;
-        z *= s;
-
-        [x, y, -z]
-    }
-
-    fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
-        let max = lightness + saturation / 2.0;
-        let min = lightness - saturation / 2.0;
-        let del = max - min;
-
-        let hue = 360.0 * hue;
-        if hue < 60.0 {
-            [max, min + del * hue / 60.0, min]
-        } else if hue < 120.0 {
-            [min + del * (120.0 - hue) / 60.0, max, min]
-        } else if hue < 180.0 {
-            [min, max, min + del * (hue - 120.0) / 60.0]
-        } else if hue < 240.0 {
-            [min, min + del * (240.0 - hue) / 60.0, max]
-        } else if hue < 300.0 {
-            [min + del * (hue - 240.0) / 60.0, min, max]
-        } else {
-            [max, min, min + del * (360.0 - hue) / 60.0]
-        }
-    }
-
-    fn hsl_vertex(hue: f32, saturation: f32, lightness: f32) -> Vertex {
-        Vertex::new(
-            hsl_to_position(hue, saturation, lightness),
-            hsl_to_rgb(hue, saturation, lightness),
-        )
-    }
-
-    let n_edge = n_hue * n_div + 2 * n_div;
-    let n_vertices = 2 * n_edge;
-    let mut vertices = Vec::with_capacity(n_vertices as _);
-
-    vertices.push(hsl_vertex(0.0, 0.0, 0.0));
-    vertices.push(hsl_vertex(0.0, 0.0, 1.0));
-
-    for hue in 0..n_hue {
-        for i in 1..=n_div {
-            vertices.push(if i == 1 {
-                hsl_vertex(0.0, 0.0, 0.0)
-            } else {
-                *vertices.last().unwrap()
-            });
-
-            let hue = hue as f32 / n_hue as f32;
-            let lightness = i as f32 / n_div as f32;
-            let saturation = 1.0 - 2.0 * (lightness - 0.5).abs();
-            vertices.push(hsl_vertex(hue, saturation, lightness));
-        }
-    }
-
-    for i in 1..=(2 * n_div) {
-        vertices.push(if i == 1 {
-            hsl_vertex(0.0, 1.0, 0.5)
-        } else {
-            *vertices.last().unwrap()
-        });
-
-        let hue = i as f32 / (2 * n_div) as f32;
-        let saturation = 1.0;
-        let lightness = 0.5;
-        vertices.push(hsl_vertex(hue, saturation, lightness));
-    }
-
-    vertices
-}
+use core::f32::consts::PI;
+
+use anyhow::Result;
+use windows::{
+    core::s,
+    Win32::Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_LINELIST,
+        Direct3D12::D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+        Dxgi::Common::{
+            DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32G32B32_FLOAT,
+        },
+    },
+};
+
+use crate::{
+    config::*,
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        math::{self, Vec4},
+        renderer::{msaa_index, Renderer, MSAA_SAMPLES},
+        resource::VertexBuffer,
+    },
+    gui::utils::Rect as _,
+};
+
+use super::{
+    color,
+    text::{TextLine, TextPanel},
+};
+
+// a* and b* scaled by these reach the edge of the cloud, matches common.hlsli
+const LAB_EXTENT: f32 = 110.0;
+const OKLAB_EXTENT: f32 = 0.33;
+
+// tick marks stick out of the grid this far and their labels are centered further out, in cloud
+// units
+const TICK_LENGTH: f32 = 0.04;
+const LABEL_DISTANCE: f32 = 0.12;
+const TICK_COLOR: [f32; 3] = [0.75, 0.75, 0.75];
+
+// corners of the rgb cube joined by its edges
+const CUBE_EDGES: [([f32; 3], [f32; 3]); 12] = [
+    ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0]),
+    ([0.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ([0.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+    ([1.0, 0.0, 0.0], [1.0, 1.0, 0.0]),
+    ([1.0, 0.0, 0.0], [1.0, 0.0, 1.0]),
+    ([0.0, 1.0, 0.0], [1.0, 1.0, 0.0]),
+    ([0.0, 1.0, 0.0], [0.0, 1.0, 1.0]),
+    ([0.0, 0.0, 1.0], [1.0, 0.0, 1.0]),
+    ([0.0, 0.0, 1.0], [0.0, 1.0, 1.0]),
+    ([1.0, 1.0, 0.0], [1.0, 1.0, 1.0]),
+    ([1.0, 0.0, 1.0], [1.0, 1.0, 1.0]),
+    ([0.0, 1.0, 1.0], [1.0, 1.0, 1.0]),
+];
+
+pub struct Grids {
+    // one per msaa sample count
+    psos: Vec<PipelineState>,
+    // one per COLORCLOUD_MODE_*
+    grids: [VertexBuffer; 6],
+    // the ycbcr grid for PRIMARIES_BT601 and PRIMARIES_BT2020, the bt.709 one is in grids
+    ycbcr_grids: [VertexBuffer; 2],
+    ticks: [Vec<Tick>; 6],
+    // enough for the most labeled grid, drawn as text alone
+    labels: Vec<TextPanel>,
+}
+
+impl Grids {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let psos = MSAA_SAMPLES
+            .iter()
+            .map(|&samples| {
+                ctx.create_multisampled_pipeline(
+                    samples,
+                    include_bytes!("../shaders/bin/PrimitiveVs.bin"),
+                    include_bytes!("../shaders/bin/PrimitivePs.bin"),
+                    BlendDesc::none(),
+                    RasterizerDesc::lines(samples),
+                    DepthStencilDesc::depth(),
+                    &[
+                        InputElementDesc::per_vertex(s!("POSITION"), DXGI_FORMAT_R32G32B32_FLOAT),
+                        InputElementDesc::per_vertex(s!("COLOR"), DXGI_FORMAT_R32G32B32_FLOAT),
+                    ],
+                    D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+                    RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+                    Some(DXGI_FORMAT_D16_UNORM),
+                    None,
+                )
+            })
+            .collect::<Result<_>>()?;
+
+        let ticks = [
+            COLORCLOUD_MODE_RGB,
+            COLORCLOUD_MODE_HSL,
+            COLORCLOUD_MODE_HSV,
+            COLORCLOUD_MODE_YCBCR,
+            COLORCLOUD_MODE_LAB,
+            COLORCLOUD_MODE_OKLAB,
+        ]
+        .map(ticks);
+
+        let with_ticks = |mut vertices: Vec<Vertex>, mode: u32| {
+            vertices.extend(ticks[mode as usize].iter().flat_map(Tick::vertices));
+            vertices
+        };
+        let grids = [
+            VertexBuffer::new(ctx, &with_ticks(rgb_grid(), COLORCLOUD_MODE_RGB))?,
+            VertexBuffer::new(ctx, &with_ticks(hsl_grid(6, 48), COLORCLOUD_MODE_HSL))?,
+            VertexBuffer::new(ctx, &with_ticks(hsv_grid(6, 48), COLORCLOUD_MODE_HSV))?,
+            VertexBuffer::new(
+                ctx,
+                &with_ticks(ycbcr_grid(PRIMARIES_BT709), COLORCLOUD_MODE_YCBCR),
+            )?,
+            VertexBuffer::new(
+                ctx,
+                &with_ticks(
+                    gamut_grid(32, |srgb| lab_position(srgb.map(math::srgb_to_linear))),
+                    COLORCLOUD_MODE_LAB,
+                ),
+            )?,
+            VertexBuffer::new(
+                ctx,
+                &with_ticks(
+                    gamut_grid(32, |srgb| oklab_position(srgb.map(math::srgb_to_linear))),
+                    COLORCLOUD_MODE_OKLAB,
+                ),
+            )?,
+        ];
+        let ycbcr_grids = [
+            VertexBuffer::new(
+                ctx,
+                &with_ticks(ycbcr_grid(PRIMARIES_BT601), COLORCLOUD_MODE_YCBCR),
+            )?,
+            VertexBuffer::new(
+                ctx,
+                &with_ticks(ycbcr_grid(PRIMARIES_BT2020), COLORCLOUD_MODE_YCBCR),
+            )?,
+        ];
+
+        let n_labels = ticks
+            .iter()
+            .map(|ticks| ticks.iter().filter(|tick| !tick.text.is_empty()).count())
+            .max()
+            .unwrap_or(0);
+        let labels = (0..n_labels)
+            .map(|_| TextPanel::with_opacity(ctx, 0.0))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            psos,
+            grids,
+            ycbcr_grids,
+            ticks,
+            labels,
+        })
+    }
+
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_color_cloud && config.show_grid {
+            self.show(ctx, config)?;
+        }
+
+        Ok(())
+    }
+
+    fn show(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        ctx.set_pipeline_state(&self.psos[msaa_index(ctx.samples())]);
+        ctx.set_viewport(config.cloud_viewport());
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+
+        let vertex_buffer = match (config.color_cloud_mode, config.source_primaries) {
+            (COLORCLOUD_MODE_YCBCR, primaries) if primaries != PRIMARIES_BT709 => {
+                &self.ycbcr_grids[primaries as usize - 1]
+            }
+            (mode, _) => &self.grids[mode as usize],
+        };
+        ctx.set_vertex_buffers(&[vertex_buffer.view()]);
+
+        #[repr(C)]
+        struct Params {
+            projection: [f32; 16],
+        }
+
+        let params = Params {
+            projection: config.projection_matrix().as_4x4(),
+        };
+
+        ctx.set_graphics_constants(&params);
+        ctx.draw(vertex_buffer.vertex_count(), 1);
+
+        Ok(())
+    }
+
+    // separate from process() since the text isn't drawn multisampled
+    pub fn process_labels(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if !(config.enable_color_cloud && config.show_grid && config.grid_labels) {
+            return Ok(());
+        }
+
+        let kind = config.cloud_viewport();
+        let target = config.window_rect.usize();
+        let projection = config.projection_matrix();
+
+        let labeled = self.ticks[config.color_cloud_mode as usize]
+            .iter()
+            .filter(|tick| !tick.text.is_empty());
+        for (panel, tick) in self.labels.iter_mut().zip(labeled) {
+            let [x, y, z] = tick.label_position();
+            let clip = projection.transform(&Vec4::new(x, y, z, 1.0));
+            if clip.w() <= 0.0 {
+                continue;
+            }
+            let ndc = [clip.x() / clip.w(), clip.y() / clip.w()];
+            let Some([x, y]) = kind.ndc_to_pixel(ndc, target.0, target.1) else {
+                continue;
+            };
+
+            panel.set_lines(ctx, vec![TextLine::new(tick.text.clone(), None)])?;
+            let (dx, dy) = panel.text_center();
+            panel.draw(ctx, x as i32 - dx, y as i32 - dy, target)?;
+        }
+
+        Ok(())
+    }
+}
+
+// a mark pointing out of the grid at a known value, labeled unless the text is empty
+struct Tick {
+    at: [f32; 3],
+    out: [f32; 3],
+    text: String,
+}
+
+impl Tick {
+    fn new(at: [f32; 3], out: [f32; 3], text: impl Into<String>) -> Self {
+        let length = out.iter().map(|c| c * c).sum::<f32>().sqrt();
+        Self {
+            at,
+            out: out.map(|c| c / length),
+            text: text.into(),
+        }
+    }
+
+    fn along(&self, distance: f32) -> [f32; 3] {
+        [0, 1, 2].map(|i| self.at[i] + distance * self.out[i])
+    }
+
+    fn vertices(&self) -> [Vertex; 2] {
+        [
+            Vertex::new(self.at, TICK_COLOR),
+            Vertex::new(self.along(TICK_LENGTH), TICK_COLOR),
+        ]
+    }
+
+    fn label_position(&self) -> [f32; 3] {
+        self.along(LABEL_DISTANCE)
+    }
+}
+
+// code values along the rgb axes, hue degrees around hsl and hsv, the lightness axis and the
+// direction of the chroma axes for the others
+fn ticks(mode: u32) -> Vec<Tick> {
+    match mode {
+        COLORCLOUD_MODE_RGB => {
+            let mut ticks = vec![Tick::new([-0.625; 3], [-1.0; 3], "0")];
+            for (axis, name) in ["R", "G", "B"].into_iter().enumerate() {
+                for code in [64, 128, 192, 255] {
+                    let mut at = [-0.625; 3];
+                    at[axis] = 1.25 * (code as f32 / 255.0 - 0.5);
+                    // away from the cube, past the far corner for the last one
+                    let mut out = [-1.0; 3];
+                    out[axis] = if code == 255 { 1.0 } else { 0.0 };
+
+                    let text = match code {
+                        128 => "128".to_string(),
+                        255 => format!("{name} 255"),
+                        _ => String::new(),
+                    };
+                    ticks.push(Tick::new(at, out, text));
+                }
+            }
+            ticks
+        }
+        COLORCLOUD_MODE_HSL => hue_ticks(0.0),
+        COLORCLOUD_MODE_HSV => hue_ticks(1.0),
+        COLORCLOUD_MODE_YCBCR => {
+            let mut ticks = lightness_ticks(
+                [0.0, 128.0 / 255.0, 1.0]
+                    .map(|y| ycbcr_position([y; 3], color::luma_weights(PRIMARIES_BT709))[1]),
+                ["Y 0", "128", "Y 255"],
+            );
+            ticks.push(Tick::new([0.625, 0.0, 0.0], [1.0, 0.0, 0.0], "Cr"));
+            ticks.push(Tick::new([0.0, 0.0, 0.625], [0.0, 0.0, 1.0], "Cb"));
+            ticks
+        }
+        COLORCLOUD_MODE_LAB => {
+            let mut ticks = lightness_ticks([-1.0, 0.0, 1.0], ["L* 0", "50", "L* 100"]);
+            let a = 100.0 / LAB_EXTENT;
+            ticks.push(Tick::new([a, 0.0, 0.0], [1.0, 0.0, 0.0], "a* 100"));
+            ticks.push(Tick::new([0.0, 0.0, -a], [0.0, 0.0, -1.0], "b* 100"));
+            ticks
+        }
+        _ => {
+            let mut ticks = lightness_ticks([-1.0, 0.0, 1.0], ["L 0", "0.5", "L 1"]);
+            let a = 0.3 / OKLAB_EXTENT;
+            ticks.push(Tick::new([a, 0.0, 0.0], [1.0, 0.0, 0.0], "a 0.3"));
+            ticks.push(Tick::new([0.0, 0.0, -a], [0.0, 0.0, -1.0], "b 0.3"));
+            ticks
+        }
+    }
+}
+
+// every 30 degrees around the rim at height y, labeled every 60
+fn hue_ticks(y: f32) -> Vec<Tick> {
+    (0..12)
+        .map(|i| {
+            let (z, x) = (PI * i as f32 / 6.0).sin_cos();
+            let text = if i % 2 == 0 {
+                format!("{}\u{b0}", 30 * i)
+            } else {
+                String::new()
+            };
+            Tick::new([x, y, -z], [x, 0.0, -z], text)
+        })
+        .collect()
+}
+
+// along the neutral axis in the middle of the solid, pointing left
+fn lightness_ticks(heights: [f32; 3], texts: [&str; 3]) -> Vec<Tick> {
+    heights
+        .into_iter()
+        .zip(texts)
+        .map(|(y, text)| Tick::new([0.0, y, 0.0], [-1.0, 0.0, 0.0], text))
+        .collect()
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+impl Vertex {
+    fn new(position: [f32; 3], color: [f32; 3]) -> Self {
+        Self { position, color }
+    }
+}
+
+fn rgb_grid() -> Vec<Vertex> {
+    fn rgb_position(r: f32, g: f32, b: f32) -> [f32; 3] {
+        [1.25 * (r - 0.5), 1.25 * (g - 0.5), 1.25 * (b - 0.5)]
+    }
+
+    fn rgb_vertex(r: f32, g: f32, b: f32) -> Vertex {
+        Vertex::new(rgb_position(r, g, b), [r, g, b])
+    }
+
+    let v0 = rgb_vertex(0.0, 0.0, 0.0);
+    let r = rgb_vertex(1.0, 0.0, 0.0);
+    let g = rgb_vertex(0.0, 1.0, 0.0);
+    let b = rgb_vertex(0.0, 0.0, 1.0);
+    let rg = rgb_vertex(1.0, 1.0, 0.0);
+    let rb = rgb_vertex(1.0, 0.0, 1.0);
+    let gb = rgb_vertex(0.0, 1.0, 1.0);
+    let v1 = rgb_vertex(1.0, 1.0, 1.0);
+
+    vec![
+        v0, r, v0, g, v0, b, r, rg, r, rb, g, rg, g, gb, b, rb, b, gb, rg, v1, rb, v1, gb, v1,
+    ]
+}
+
+fn hsl_grid(n_hue: u32, n_div: u32) -> Vec<Vertex> {
+    fn hsl_to_position(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+        let h = 2.0 * PI * hue;
+        let mut s = saturation;
+        let mut l = 2.0 * lightness - 1.0;
+
+        let a = s + l.abs();
+        let b = (s * s + l * l).sqrt();
+        if b > 0.0 {
+            let n = a / b;
+            s *= n;
+            l *= n;
+        }
+
+        let y = l;
+
+        let (mut z, mut x) = h.sin_cos();
+        x *= s;
+        z *= s;
+
+        [x, y, -z]
+    }
+
+    fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+        hue_to_rgb(
+            hue,
+            lightness + saturation / 2.0,
+            lightness - saturation / 2.0,
+        )
+    }
+
+    fn hsl_vertex(hue: f32, saturation: f32, lightness: f32) -> Vertex {
+        Vertex::new(
+            hsl_to_position(hue, saturation, lightness),
+            hsl_to_rgb(hue, saturation, lightness),
+        )
+    }
+
+    let n_edge = n_hue * n_div + 2 * n_div;
+    let n_vertices = 2 * n_edge;
+    let mut vertices = Vec::with_capacity(n_vertices as _);
+
+    vertices.push(hsl_vertex(0.0, 0.0, 0.0));
+    vertices.push(hsl_vertex(0.0, 0.0, 1.0));
+
+    for hue in 0..n_hue {
+        for i in 1..=n_div {
+            vertices.push(if i == 1 {
+                hsl_vertex(0.0, 0.0, 0.0)
+            } else {
+                *vertices.last().unwrap()
+            });
+
+            let hue = hue as f32 / n_hue as f32;
+            let lightness = i as f32 / n_div as f32;
+            let saturation = 1.0 - 2.0 * (lightness - 0.5).abs();
+            vertices.push(hsl_vertex(hue, saturation, lightness));
+        }
+    }
+
+    for i in 1..=(2 * n_div) {
+        vertices.push(if i == 1 {
+            hsl_vertex(0.0, 1.0, 0.5)
+        } else {
+            *vertices.last().unwrap()
+        });
+
+        let hue = i as f32 / (2 * n_div) as f32;
+        let saturation = 1.0;
+        let lightness = 0.5;
+        vertices.push(hsl_vertex(hue, saturation, lightness));
+    }
+
+    vertices
+}
+
+// the fully saturated hue between the largest and smallest channel, hue in [0, 1]
+fn hue_to_rgb(hue: f32, max: f32, min: f32) -> [f32; 3] {
+    let del = max - min;
+
+    let hue = 360.0 * hue;
+    if hue < 60.0 {
+        [max, min + del * hue / 60.0, min]
+    } else if hue < 120.0 {
+        [min + del * (120.0 - hue) / 60.0, max, min]
+    } else if hue < 180.0 {
+        [min, max, min + del * (hue - 120.0) / 60.0]
+    } else if hue < 240.0 {
+        [min, min + del * (240.0 - hue) / 60.0, max]
+    } else if hue < 300.0 {
+        [min + del * (hue - 240.0) / 60.0, min, max]
+    } else {
+        [max, min, min + del * (360.0 - hue) / 60.0]
+    }
+}
+
+// a curve through n segments as a line list, t from 0 to 1
+fn segments(n: u32, vertex: impl Fn(f32) -> Vertex) -> impl Iterator<Item = Vertex> {
+    (0..n).flat_map(move |i| {
+        [
+            vertex(i as f32 / n as f32),
+            vertex((i + 1) as f32 / n as f32),
+        ]
+    })
+}
+
+// the value axis, the side at each of n_hue hues, the spokes and the rim of the top
+fn hsv_grid(n_hue: u32, n_div: u32) -> Vec<Vertex> {
+    fn hsv_vertex(hue: f32, saturation: f32, value: f32) -> Vertex {
+        let (z, x) = (2.0 * PI * hue).sin_cos();
+        Vertex::new(
+            [saturation * x, 2.0 * value - 1.0, -saturation * z],
+            hue_to_rgb(hue, value, value * (1.0 - saturation)),
+        )
+    }
+
+    let mut vertices: Vec<_> = segments(n_div, |v| hsv_vertex(0.0, 0.0, v)).collect();
+
+    for hue in 0..n_hue {
+        let hue = hue as f32 / n_hue as f32;
+        vertices.extend(segments(n_div, |v| hsv_vertex(hue, 1.0, v)));
+        vertices.extend(segments(n_div, |s| hsv_vertex(hue, s, 1.0)));
+    }
+
+    vertices.extend(segments(2 * n_div, |hue| hsv_vertex(hue, 1.0, 1.0)));
+
+    vertices
+}
+
+// the edges of the srgb cube and its neutral axis carried into another space, n_div segments
+// each, evenly spaced in encoded values. position takes srgb encoded values
+fn gamut_grid(n_div: u32, position: impl Fn([f32; 3]) -> [f32; 3]) -> Vec<Vertex> {
+    let edges = CUBE_EDGES
+        .into_iter()
+        .chain([([0.0, 0.0, 0.0], [1.0, 1.0, 1.0])]);
+    // borrowed, each edge's closure uses it
+    let position = &position;
+
+    edges
+        .flat_map(|(from, to)| {
+            segments(n_div, move |t| {
+                let srgb = [0, 1, 2].map(|i| from[i] + t * (to[i] - from[i]));
+                Vertex::new(position(srgb), srgb.map(math::srgb_to_linear))
+            })
+            .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn ycbcr_grid(primaries: u32) -> Vec<Vertex> {
+    let weights = color::luma_weights(primaries);
+    gamut_grid(1, |rgb| ycbcr_position(rgb, weights))
+}
+
+// y'cbcr scaled like the rgb cube, cr along x and cb along z
+fn ycbcr_position(rgb: [f32; 3], weights: [f32; 2]) -> [f32; 3] {
+    let [y, cb, cr] = color::rgb_to_ycbcr(rgb, weights);
+    [1.25 * cr, 1.25 * (y - 0.5), 1.25 * cb]
+}
+
+// lightness up, a* toward +x and b* toward -z, like the hue angle of the hsl bicone
+fn lab_position(rgb: [f32; 3]) -> [f32; 3] {
+    let [l, a, b] = color::rgb_to_lab(rgb);
+    [a / LAB_EXTENT, l / 50.0 - 1.0, -b / LAB_EXTENT]
+}
+
+fn oklab_position(rgb: [f32; 3]) -> [f32; 3] {
+    let [l, a, b] = color::rgb_to_oklab(rgb);
+    [a / OKLAB_EXTENT, 2.0 * l - 1.0, -b / OKLAB_EXTENT]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamuts_fit_the_cloud() {
+        let grids = [
+            hsv_grid(6, 8),
+            ycbcr_grid(PRIMARIES_BT709),
+            ycbcr_grid(PRIMARIES_BT601),
+            ycbcr_grid(PRIMARIES_BT2020),
+            gamut_grid(16, |srgb| lab_position(srgb.map(math::srgb_to_linear))),
+            gamut_grid(16, |srgb| oklab_position(srgb.map(math::srgb_to_linear))),
+        ];
+
+        for vertices in grids {
+            assert_eq!(vertices.len() % 2, 0);
+            for vertex in vertices {
+                assert!(vertex.position.iter().all(|c| c.abs() <= 1.0 + 1e-4));
+            }
+        }
+    }
+
+    #[test]
+    fn lightness_is_up_and_red_is_right() {
+        let ycbcr: fn([f32; 3]) -> [f32; 3] =
+            |rgb| ycbcr_position(rgb, color::luma_weights(PRIMARIES_BT709));
+        for position in [ycbcr, lab_position, oklab_position] {
+            let [_, black, _] = position([0.0; 3]);
+            let [x, white, z] = position([1.0; 3]);
+            assert!((black + white).abs() < 0.4, "{black} {white}");
+            assert!(x.abs() < 1e-3 && z.abs() < 1e-3);
+
+            let [x, _, _] = position([1.0, 0.0, 0.0]);
+            assert!(x > 0.3);
+        }
+    }
+
+    #[test]
+    fn ticks_stick_out_of_the_grid() {
+        for mode in COLORCLOUD_MODE_RGB..=COLORCLOUD_MODE_OKLAB {
+            let ticks = ticks(mode);
+            assert!(ticks.iter().any(|tick| !tick.text.is_empty()));
+
+            for tick in ticks {
+                assert!(
+                    tick.at.iter().all(|c| c.abs() <= 1.0 + 1e-4),
+                    "{}",
+                    tick.text
+                );
+                let label = tick.label_position();
+                let distance = (0..3)
+                    .map(|i| (label[i] - tick.at[i]).powi(2))
+                    .sum::<f32>()
+                    .sqrt();
+                assert!((distance - LABEL_DISTANCE).abs() < 1e-5);
+            }
+        }
+
+        // where the cloud puts code 128 of red
+        let ticks = ticks(COLORCLOUD_MODE_RGB);
+        let tick = ticks.iter().find(|tick| tick.text == "128").unwrap();
+        assert!((tick.at[0] - 1.25 * (128.0 / 255.0 - 0.5)).abs() < 1e-6);
+        assert_eq!(tick.at[1], -0.625);
+    }
+}
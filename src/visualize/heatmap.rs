@@ -0,0 +1,129 @@
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+        Direct3D12::{
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE, D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        },
+        Dxgi::Common::{
+            DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32_UINT,
+        },
+    },
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        renderer::{Renderer, ViewportKind},
+        resource::RwBuffer,
+    },
+    gui::utils::Rect as _,
+};
+
+// rg, rb and gb
+const PLANES: u32 = 3;
+const SIZE: u32 = 256;
+const THREAD: u32 = 16;
+
+// tiles along the bottom right corner, in pixels
+const TILE: i32 = 128;
+const MARGIN: i32 = 8;
+
+// the cloud's counters projected onto the three axis planes, drawn as small flat tiles
+pub struct Heatmaps {
+    project_pso: PipelineState,
+    draw_pso: PipelineState,
+    planes: RwBuffer,
+}
+
+impl Heatmaps {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let project_pso =
+            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/HeatmapCs.bin"), None)?;
+
+        let draw_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/HeatmapVs.bin"),
+            include_bytes!("../shaders/bin/HeatmapPs.bin"),
+            BlendDesc::alpha(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            Some(DXGI_FORMAT_D16_UNORM),
+            None,
+        )?;
+
+        let planes = RwBuffer::new(ctx, PLANES * SIZE * SIZE, DXGI_FORMAT_R32_UINT)?;
+
+        Ok(Self {
+            project_pso,
+            draw_pso,
+            planes,
+        })
+    }
+
+    // compute only, after the counter is complete
+    pub fn project(&self, ctx: &mut Renderer, counter: &RwBuffer) -> Result<()> {
+        ctx.resource_barrier(&[
+            counter.uav_barrier(),
+            self.planes.transition_barrier(
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            ),
+        ]);
+
+        ctx.set_pipeline_state(&self.project_pso);
+        ctx.set_uavs(&[counter.uav, self.planes.uav]);
+        ctx.dispatch(SIZE / THREAD, SIZE / THREAD, PLANES);
+
+        ctx.resource_barrier(&[self.planes.transition_barrier(
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+
+    pub fn draw(&self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        ctx.set_pipeline_state(&self.draw_pso);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+        ctx.set_graphics_srvs(&[self.planes.srv]);
+
+        #[repr(C)]
+        struct Params {
+            plane: u32,
+            inv_log_total: f32,
+        }
+
+        // same log scale as the isosurface and the volume
+        let (width, height) = config.analysis_rect().size();
+        let total = (width * height).max(1) as f32;
+        let inv_log_total = 1.0 / (1.0 + total).log2();
+
+        let right = config.window_rect.width() - MARGIN;
+        let bottom = config.window_rect.height() - MARGIN;
+
+        for plane in 0..PLANES {
+            let left = right - (PLANES - plane) as i32 * (TILE + MARGIN) + MARGIN;
+            ctx.set_viewport(ViewportKind::Rect(RECT {
+                left,
+                top: bottom - TILE,
+                right: left + TILE,
+                bottom,
+            }));
+
+            ctx.set_graphics_constants(&Params {
+                plane,
+                inv_log_total,
+            });
+            ctx.draw(4, 1);
+        }
+
+        Ok(())
+    }
+}
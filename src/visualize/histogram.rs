@@ -1,192 +1,927 @@
-use anyhow::Result;
-use windows::Win32::{
-    Foundation::RECT,
-    Graphics::{
-        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
-        Direct3D12::*,
-        Dxgi::Common::{DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32_UINT},
-    },
-};
-
-use crate::{
-    config::{Config, HISTOGRAM_MODE_HUE, HISTOGRAM_MODE_RGB, HISTOGRAM_MODE_RGBL},
-    graphics::{
-        core::{
-            pso::PipelineState,
-            wrap::{BlendDesc, DepthStencilDesc, RasterizerDesc, RtvFormats},
-        },
-        initializer::Initializer,
-        math,
-        renderer::Renderer,
-        resource::RwBuffer,
-    },
-    gui::utils::Rect as _,
-};
-
-pub struct Histogram {
-    compute_pso: PipelineState,
-    draw_pso: PipelineState,
-    buffers: [RwBuffer; 4],
-}
-
-impl Histogram {
-    pub fn new(ctx: &mut Initializer) -> Result<Self> {
-        let compute_pso =
-            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/HistogramCs.bin"), None)?;
-
-        let draw_pso = ctx.create_graphics_pipeline(
-            include_bytes!("../shaders/bin/HistogramVs.bin"),
-            include_bytes!("../shaders/bin/HistogramPs.bin"),
-            BlendDesc::mul(),
-            RasterizerDesc::none(),
-            DepthStencilDesc::none(),
-            &[],
-            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
-            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
-            None,
-            None,
-        )?;
-
-        const NUM_ELEMS: u32 = 256;
-        let buffers = [
-            RwBuffer::new(ctx, NUM_ELEMS, DXGI_FORMAT_R32_UINT)?,
-            RwBuffer::new(ctx, NUM_ELEMS, DXGI_FORMAT_R32_UINT)?,
-            RwBuffer::new(ctx, NUM_ELEMS, DXGI_FORMAT_R32_UINT)?,
-            RwBuffer::new(ctx, NUM_ELEMS, DXGI_FORMAT_R32_UINT)?,
-        ];
-
-        Ok(Self {
-            compute_pso,
-            draw_pso,
-            buffers,
-        })
-    }
-
-    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
-        if config.enable_histogram {
-            self.clear(ctx)?;
-            self.compute(config, ctx)?;
-            self.draw(config, ctx)?;
-        }
-        Ok(())
-    }
-
-    fn clear(&mut self, ctx: &mut Renderer) -> Result<()> {
-        let barriers: Vec<_> = self
-            .buffers
-            .iter()
-            .map(|buffer| {
-                buffer.transition_barrier(
-                    D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-                    D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-                )
-            })
-            .collect();
-        ctx.resource_barrier(&barriers);
-
-        for buffer in &self.buffers {
-            ctx.clear_uav(buffer.raw_uav, buffer);
-        }
-
-        Ok(())
-    }
-
-    fn compute(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
-        ctx.set_pipeline_state(&self.compute_pso);
-
-        #[repr(C)]
-        struct Params {
-            rect: RECT,
-            mode: u32,
-            ch: u32,
-        }
-        let ch = match config.histogram_mode {
-            HISTOGRAM_MODE_RGB => 3,
-            HISTOGRAM_MODE_RGBL => 4,
-            _ => 1,
-        };
-        let params = Params {
-            rect: config.window_rect,
-            mode: config.histogram_mode,
-            ch,
-        };
-        ctx.set_compute_constants(&params);
-        ctx.set_uavs(&[
-            self.buffers[0].uav,
-            self.buffers[1].uav,
-            self.buffers[2].uav,
-            self.buffers[3].uav,
-        ]);
-
-        let threads = 2 * 8;
-        ctx.dispatch(
-            math::div_round_up(config.window_rect.width() as u32, threads),
-            math::div_round_up(config.window_rect.height() as u32, threads),
-            1,
-        );
-
-        Ok(())
-    }
-
-    fn draw(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
-        ctx.set_pipeline_state(&self.draw_pso);
-        ctx.set_viewport(crate::graphics::renderer::ViewportKind::Full);
-
-        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
-
-        let barriers: Vec<_> = self
-            .buffers
-            .iter()
-            .map(|buf| {
-                buf.transition_barrier(
-                    D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-                    D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-                )
-            })
-            .collect();
-        ctx.resource_barrier(&barriers);
-
-        #[repr(C)]
-        struct Params {
-            colors: [[f32; 4]; 4],
-            mode: u32,
-            scale: f32,
-        }
-
-        let (width, height) = config.window_rect.size();
-        let scale = config.histogram_scale
-            * if config.histogram_mode == HISTOGRAM_MODE_HUE {
-                0.20 / ((width * height) as f32)
-            } else {
-                10.0 / ((width * height) as f32)
-            };
-
-        let params = Params {
-            colors: [
-                [0.0, 1.0, 0.0, 0.8],
-                [1.0, 0.0, 0.0, 0.8],
-                [0.0, 0.0, 1.0, 0.8],
-                [1.0, 1.0, 1.0, 0.8],
-            ],
-            mode: config.histogram_mode as _,
-            scale,
-        };
-
-        ctx.set_graphics_constants(&params);
-        ctx.set_graphics_srvs(&[
-            self.buffers[0].srv,
-            self.buffers[1].srv,
-            self.buffers[2].srv,
-            self.buffers[3].srv,
-        ]);
-
-        let ch = match config.histogram_mode {
-            HISTOGRAM_MODE_RGB => 3,
-            HISTOGRAM_MODE_RGBL => 4,
-            _ => 1,
-        };
-        ctx.draw(2 * 256, ch);
-
-        Ok(())
-    }
-}
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{
+        Direct3D::{D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST, D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP},
+        Direct3D12::*,
+        Dxgi::Common::{
+            DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32_FLOAT, DXGI_FORMAT_R32_SINT,
+            DXGI_FORMAT_R32_UINT,
+        },
+    },
+};
+
+use crate::{
+    config::{
+        Config, HISTOGRAM_BINS, HISTOGRAM_MODE_HUE, HISTOGRAM_MODE_LUMA, HISTOGRAM_MODE_RGB,
+        HISTOGRAM_MODE_RGBL, LAYOUT_HISTOGRAM,
+    },
+    graphics::{
+        core::{
+            command_queue::FRAME_COUNT,
+            pso::PipelineState,
+            resource::Resource,
+            wrap::{BlendDesc, DepthStencilDesc, HeapProps, RasterizerDesc, RtvFormats},
+        },
+        initializer::Initializer,
+        math,
+        renderer::{Renderer, ViewportKind},
+        resource::{IndirectArgs, RwBuffer, RwTexture2D},
+    },
+    gui::utils::Rect as _,
+};
+
+use super::{color, export};
+
+// the buffers and the readback fit this many, matches histogram.hlsl
+pub const MAX_BINS: usize = 1024;
+const DEFAULT_BINS: u32 = 256;
+// a word past the bins of the first channel and of the delta keeps their peak for auto scale
+const PEAK: u32 = 1;
+
+// lab chroma of the last bin, matches histogram.hlsl
+pub const MAX_CHROMA: f32 = 150.0;
+
+// HistogramVs draws the peak marker for this channel index
+const MARKER: u32 = 4;
+
+// frame to frame changes are small next to the counts, so their bars are drawn taller
+const DELTA_GAIN: f32 = 8.0;
+
+// share of pixels clipped at each end by the suggested levels
+const LEVELS_CLIP: f32 = 0.001;
+
+// frames kept by the history, one column each, matches histogram.hlsl
+const HISTORY: u32 = 512;
+
+pub struct HistogramData {
+    pub mode: u32,
+    pub channels: u32,
+    pub hdr: bool,
+    // config.histogram_colors, r, g, b and luma
+    pub colors: [u32; 4],
+    // the same number of bins in each channel
+    pub bins: [Vec<u32>; 4],
+    // whole window, when a roi is compared against it
+    pub full: Option<[Vec<u32>; 4]>,
+}
+
+impl HistogramData {
+    // per channel mean of the binned values in [0, 1]
+    pub fn means(bins: &[Vec<u32>; 4]) -> [f32; 4] {
+        bins.each_ref().map(|bins| Self::mean(bins))
+    }
+
+    pub fn mean(bins: &[u32]) -> f32 {
+        let (sum, count) = bins
+            .iter()
+            .enumerate()
+            .fold((0u64, 0u64), |(sum, count), (i, &n)| {
+                (sum + i as u64 * n as u64, count + n as u64)
+            });
+
+        if count > 0 {
+            sum as f32 / count as f32 / (bins.len() - 1) as f32
+        } else {
+            0.0
+        }
+    }
+
+    // black and white points that clip LEVELS_CLIP of the pixels at each end. sdr only, from
+    // luma when it is binned and from r, g and b together otherwise
+    pub fn levels(&self) -> Option<[f32; 2]> {
+        if self.hdr {
+            return None;
+        }
+
+        let bins = match self.mode {
+            HISTOGRAM_MODE_RGB => (0..self.bins[0].len())
+                .map(|i| self.bins[0][i] + self.bins[1][i] + self.bins[2][i])
+                .collect(),
+            HISTOGRAM_MODE_RGBL => self.bins[3].clone(),
+            HISTOGRAM_MODE_LUMA => self.bins[0].clone(),
+            _ => return None,
+        };
+
+        percentile_levels(&bins, LEVELS_CLIP)
+    }
+
+    // mean of each drawn channel in the roi minus the whole window's, None without the comparison
+    pub fn roi_difference(&self) -> Option<Vec<(&'static str, f32)>> {
+        let full = self.full.as_ref()?;
+        let roi = export::channels_of(self.mode, &self.bins, self.colors);
+        let full = export::channels_of(self.mode, full, self.colors);
+
+        let differences = roi
+            .iter()
+            .zip(&full)
+            .map(|(roi, full)| (roi.name, Self::mean(roi.bins) - Self::mean(full.bins)))
+            .collect();
+        Some(differences)
+    }
+
+    // highest non-empty pq bin of r, g and b in nits, 0 unless hdr
+    pub fn max_cll(&self) -> f32 {
+        let channels = match self.mode {
+            HISTOGRAM_MODE_RGB | HISTOGRAM_MODE_RGBL => 3,
+            _ => return 0.0,
+        };
+
+        if !self.hdr {
+            return 0.0;
+        }
+
+        self.bins[..channels]
+            .iter()
+            .filter_map(|bins| bins.iter().rposition(|&n| n > 0))
+            .max()
+            .map_or(0.0, |peak| {
+                pq_to_nits(peak as f32 / (self.bins[0].len() - 1) as f32)
+            })
+    }
+}
+
+// the lowest and highest bins, in [0, 1], with more than `clip` of the total beyond them
+fn percentile_levels(bins: &[u32], clip: f32) -> Option<[f32; 2]> {
+    let total = bins.iter().map(|&n| n as u64).sum::<u64>();
+    if total == 0 {
+        return None;
+    }
+
+    // position of the bin where the running count passes the clipped share
+    fn past_limit<'a>(mut counts: impl Iterator<Item = &'a u32>, limit: u64) -> Option<usize> {
+        let mut sum = 0;
+        counts.position(|&n| {
+            sum += n as u64;
+            sum > limit
+        })
+    }
+
+    let limit = (clip as f64 * total as f64) as u64;
+    let black = past_limit(bins.iter(), limit)?;
+    let white = bins.len() - 1 - past_limit(bins.iter().rev(), limit)?;
+
+    let scale = 1.0 / (bins.len() - 1) as f32;
+    (white > black).then(|| [black as f32 * scale, white as f32 * scale])
+}
+
+// SMPTE ST 2084, matches PqToNits in common.hlsli
+pub fn pq_to_nits(pq: f32) -> f32 {
+    const M1: f32 = 2610.0 / 16384.0;
+    const M2: f32 = 2523.0 / 4096.0 * 128.0;
+    const C1: f32 = 3424.0 / 4096.0;
+    const C2: f32 = 2413.0 / 4096.0 * 32.0;
+    const C3: f32 = 2392.0 / 4096.0 * 32.0;
+
+    let e = pq.clamp(0.0, 1.0).powf(1.0 / M2);
+    10000.0 * ((e - C1).max(0.0) / (C2 - C3 * e)).powf(1.0 / M1)
+}
+
+pub struct Histogram {
+    compute_pso: PipelineState,
+    cull_pso: PipelineState,
+    draw_pso: PipelineState,
+    delta_pso: PipelineState,
+    delta_draw_pso: PipelineState,
+    buffers: [RwBuffer; 4],
+    full_buffers: [RwBuffer; 4],
+    draw_args: IndirectArgs,
+    // the last frame's bins summed over the channels, and the signed change since
+    previous: RwBuffer,
+    delta: RwBuffer,
+    scroll_pso: PipelineState,
+    history_pso: PipelineState,
+    // scrolled from one into the other, history[current] holds the latest frame
+    history: [RwTexture2D; 2],
+    current: usize,
+    // the columns are stale after a resize or while the history is off
+    history_reset: bool,
+    // one per frame slot, read once the context has waited for the slot or for everything
+    readbacks: Vec<Resource>,
+    copied: [bool; FRAME_COUNT],
+    readback_requested: bool,
+    // the size of the buffers, one of HISTOGRAM_BINS
+    bins: u32,
+}
+
+// the configured count, the default for anything the menu doesn't offer
+fn bin_count(config: &Config) -> u32 {
+    if HISTOGRAM_BINS.contains(&config.histogram_bins) {
+        config.histogram_bins
+    } else {
+        DEFAULT_BINS
+    }
+}
+
+// channel colors, bound once per pass through the constant buffer ring
+#[repr(C)]
+struct Palette {
+    colors: [[f32; 4]; 4],
+}
+
+#[repr(C)]
+struct DrawParams {
+    mode: u32,
+    scale: f32,
+    channel: u32,
+    auto_scale: u32,
+    bins: u32,
+}
+
+impl Histogram {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let compute_pso =
+            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/HistogramCs.bin"), None)?;
+
+        let cull_pso = ctx
+            .create_compute_pipeline(include_bytes!("../shaders/bin/HistogramCullCs.bin"), None)?;
+
+        let draw_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/HistogramVs.bin"),
+            include_bytes!("../shaders/bin/HistogramPs.bin"),
+            BlendDesc::mul(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let delta_pso = ctx
+            .create_compute_pipeline(include_bytes!("../shaders/bin/HistogramDeltaCs.bin"), None)?;
+
+        let delta_draw_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/HistogramDeltaVs.bin"),
+            include_bytes!("../shaders/bin/HistogramPs.bin"),
+            BlendDesc::mul(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let buffers = [
+            RwBuffer::new(ctx, DEFAULT_BINS + PEAK, DXGI_FORMAT_R32_UINT)?,
+            RwBuffer::new(ctx, DEFAULT_BINS + PEAK, DXGI_FORMAT_R32_UINT)?,
+            RwBuffer::new(ctx, DEFAULT_BINS + PEAK, DXGI_FORMAT_R32_UINT)?,
+            RwBuffer::new(ctx, DEFAULT_BINS + PEAK, DXGI_FORMAT_R32_UINT)?,
+        ];
+
+        let full_buffers = [
+            RwBuffer::new(ctx, DEFAULT_BINS + PEAK, DXGI_FORMAT_R32_UINT)?,
+            RwBuffer::new(ctx, DEFAULT_BINS + PEAK, DXGI_FORMAT_R32_UINT)?,
+            RwBuffer::new(ctx, DEFAULT_BINS + PEAK, DXGI_FORMAT_R32_UINT)?,
+            RwBuffer::new(ctx, DEFAULT_BINS + PEAK, DXGI_FORMAT_R32_UINT)?,
+        ];
+
+        let previous = RwBuffer::new(ctx, DEFAULT_BINS, DXGI_FORMAT_R32_UINT)?;
+        let delta = RwBuffer::new(ctx, DEFAULT_BINS + PEAK, DXGI_FORMAT_R32_SINT)?;
+
+        let scroll_pso = ctx.create_compute_pipeline(
+            include_bytes!("../shaders/bin/HistogramScrollCs.bin"),
+            None,
+        )?;
+
+        let history_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/HistogramHistoryVs.bin"),
+            include_bytes!("../shaders/bin/HistogramHistoryPs.bin"),
+            BlendDesc::alpha(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let history = [
+            RwTexture2D::new(ctx, HISTORY, DEFAULT_BINS, DXGI_FORMAT_R32_FLOAT)?,
+            RwTexture2D::new(ctx, HISTORY, DEFAULT_BINS, DXGI_FORMAT_R32_FLOAT)?,
+        ];
+
+        // roi bins followed by the full frame bins
+        let readbacks = (0..FRAME_COUNT)
+            .map(|_| {
+                Resource::new_buffer(
+                    ctx,
+                    &HeapProps::readback(),
+                    None,
+                    std::mem::size_of::<[[u32; MAX_BINS]; 8]>() as _,
+                    D3D12_RESOURCE_FLAG_NONE,
+                    D3D12_RESOURCE_STATE_COPY_DEST,
+                )
+            })
+            .collect::<Result<_>>()?;
+
+        // one draw per channel
+        let draw_args = IndirectArgs::draw(ctx, 4)?;
+
+        Ok(Self {
+            compute_pso,
+            cull_pso,
+            draw_pso,
+            delta_pso,
+            delta_draw_pso,
+            buffers,
+            full_buffers,
+            draw_args,
+            previous,
+            delta,
+            scroll_pso,
+            history_pso,
+            history,
+            current: 0,
+            history_reset: true,
+            readbacks,
+            copied: [false; FRAME_COUNT],
+            readback_requested: false,
+            bins: DEFAULT_BINS,
+        })
+    }
+
+    // recreates the buffers for another bin count, the replaced ones live until the frame is done
+    fn resize(&mut self, ctx: &mut Renderer, bins: u32) -> Result<()> {
+        for buffer in self.buffers.iter_mut().chain(&mut self.full_buffers) {
+            let old = buffer.resource.clone();
+            buffer.resize(ctx.device(), bins + PEAK, DXGI_FORMAT_R32_UINT)?;
+            ctx.retire(old);
+        }
+
+        let old = self.previous.resource.clone();
+        self.previous
+            .resize(ctx.device(), bins, DXGI_FORMAT_R32_UINT)?;
+        ctx.retire(old);
+
+        let old = self.delta.resource.clone();
+        self.delta
+            .resize(ctx.device(), bins + PEAK, DXGI_FORMAT_R32_SINT)?;
+        ctx.retire(old);
+
+        // one row per bin, the old rows don't line up with the new ones
+        for texture in &mut self.history {
+            let old = texture.resource.clone();
+            texture.resize(ctx.device(), HISTORY, bins)?;
+            ctx.retire(old);
+        }
+        self.history_reset = true;
+
+        self.bins = bins;
+        Ok(())
+    }
+
+    // compute only, may be recorded on the async compute queue
+    pub fn accumulate(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        self.history_reset |= !Self::keeps_history(config);
+
+        if config.enable_histogram {
+            let compare = Self::compare(config);
+
+            let bins = bin_count(config);
+            if bins != self.bins {
+                self.resize(ctx, bins)?;
+            }
+
+            self.clear(ctx, &self.buffers)?;
+            self.compute(config, ctx, &self.buffers, config.analysis_rect())?;
+
+            if compare {
+                self.clear(ctx, &self.full_buffers)?;
+                self.compute(config, ctx, &self.full_buffers, config.window_rect)?;
+                Self::transition(
+                    ctx,
+                    &self.full_buffers,
+                    D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                    D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                );
+            }
+
+            // the history takes the place of the bars, so there are no draw arguments to fill
+            if config.histogram_history {
+                Self::transition(
+                    ctx,
+                    &self.buffers,
+                    D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                    D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                );
+            } else {
+                self.cull(ctx)?;
+            }
+
+            // the delta pass also keeps the summed bins the history scrolls in
+            if config.histogram_delta || config.histogram_history {
+                self.compute_delta(ctx)?;
+            }
+
+            if config.histogram_history {
+                self.scroll(ctx)?;
+            }
+        }
+        Ok(())
+    }
+
+    // draws the bins counted by accumulate(), frame is the slot the readback goes to
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config, frame: usize) -> Result<()> {
+        if config.enable_histogram {
+            let compare = Self::compare(config);
+
+            if config.histogram_history {
+                self.draw_history(config, ctx)?;
+            } else {
+                if compare {
+                    self.draw_full(config, ctx)?;
+                }
+                self.draw(config, ctx)?;
+
+                if config.histogram_delta {
+                    self.draw_delta(config, ctx)?;
+                }
+            }
+
+            let requested = std::mem::take(&mut self.readback_requested);
+            self.copied[frame] = config.share_stats || requested || Self::shows_difference(config);
+            if self.copied[frame] {
+                self.copy_to_readback(ctx, &self.buffers, 0, frame)?;
+
+                if compare {
+                    self.copy_to_readback(ctx, &self.full_buffers, 4, frame)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // copies the next frame's bins for read() even without shared stats
+    pub fn request_readback(&mut self) {
+        self.readback_requested = true;
+    }
+
+    // valid once the frame that last used the slot has finished on the GPU, None when that frame
+    // copied nothing
+    pub fn read(&self, config: &Config, frame: usize) -> Result<Option<HistogramData>> {
+        if !config.enable_histogram || !self.copied[frame] {
+            return Ok(None);
+        }
+
+        let bins = self.bins as usize;
+        let data = self.readbacks[frame].read::<u32>(8 * bins)?;
+        let channels =
+            |slot: usize| std::array::from_fn(|i| data[(slot + i) * bins..][..bins].to_vec());
+
+        Ok(Some(HistogramData {
+            mode: config.histogram_mode,
+            channels: Self::channels(config),
+            hdr: config.hdr_mode,
+            colors: config.histogram_colors,
+            bins: channels(0),
+            full: Self::compare(config).then(|| channels(4)),
+        }))
+    }
+
+    fn channels(config: &Config) -> u32 {
+        match config.histogram_mode {
+            HISTOGRAM_MODE_RGB => 3,
+            HISTOGRAM_MODE_RGBL => 4,
+            _ => 1,
+        }
+    }
+
+    fn compare(config: &Config) -> bool {
+        config.enable_roi && config.compare_roi
+    }
+
+    // the legend lists the roi against the whole window, read back a couple of frames late
+    pub fn shows_difference(config: &Config) -> bool {
+        config.enable_histogram && config.show_legend && Self::compare(config)
+    }
+
+    fn keeps_history(config: &Config) -> bool {
+        config.enable_histogram && config.histogram_history
+    }
+
+    fn transition(
+        ctx: &mut Renderer,
+        buffers: &[RwBuffer; 4],
+        before: D3D12_RESOURCE_STATES,
+        after: D3D12_RESOURCE_STATES,
+    ) {
+        let barriers: Vec<_> = buffers
+            .iter()
+            .map(|buffer| buffer.transition_barrier(before, after))
+            .collect();
+        ctx.resource_barrier(&barriers);
+    }
+
+    // slot is the channel index in the readback buffer of frame
+    fn copy_to_readback(
+        &self,
+        ctx: &mut Renderer,
+        buffers: &[RwBuffer; 4],
+        slot: u64,
+        frame: usize,
+    ) -> Result<()> {
+        Self::transition(
+            ctx,
+            buffers,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_COPY_SOURCE,
+        );
+
+        let size = 4 * self.bins as u64;
+        for (i, buffer) in buffers.iter().enumerate() {
+            ctx.copy_buffer_region(
+                &self.readbacks[frame],
+                size * (slot + i as u64),
+                buffer,
+                0,
+                size,
+            );
+        }
+
+        Self::transition(
+            ctx,
+            buffers,
+            D3D12_RESOURCE_STATE_COPY_SOURCE,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        );
+
+        Ok(())
+    }
+
+    fn clear(&self, ctx: &mut Renderer, buffers: &[RwBuffer; 4]) -> Result<()> {
+        Self::transition(
+            ctx,
+            buffers,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        );
+
+        for buffer in buffers {
+            ctx.clear_uav(buffer.raw_uav, buffer);
+        }
+
+        Ok(())
+    }
+
+    fn compute(
+        &self,
+        config: &Config,
+        ctx: &mut Renderer,
+        buffers: &[RwBuffer; 4],
+        rect: RECT,
+    ) -> Result<()> {
+        ctx.set_pipeline_state(&self.compute_pso);
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            origin: [i32; 2],
+            mode: u32,
+            ch: u32,
+            hdr: u32,
+            bins: u32,
+            inv_log_total: f32,
+            reset: u32,
+        }
+        let ch = Self::channels(config);
+
+        // same log scale as the cloud's heatmaps, over the analyzed pixels
+        let (width, height) = config.analysis_rect().size();
+        let inv_log_total = 1.0 / (1.0 + (width * height).max(1) as f32).log2();
+
+        let params = Params {
+            rect,
+            origin: [config.window_rect.left, config.window_rect.top],
+            mode: config.histogram_mode,
+            ch,
+            hdr: config.hdr_mode as u32,
+            bins: self.bins,
+            inv_log_total,
+            reset: self.history_reset as u32,
+        };
+        ctx.set_compute_constants(&params);
+        ctx.set_uavs(&[
+            buffers[0].uav,
+            buffers[1].uav,
+            buffers[2].uav,
+            buffers[3].uav,
+        ]);
+
+        let threads = 2 * 8;
+        ctx.dispatch(
+            math::div_round_up(rect.width() as u32, threads),
+            math::div_round_up(rect.height() as u32, threads),
+            1,
+        );
+
+        Ok(())
+    }
+
+    // uses the constants set by compute()
+    fn cull(&mut self, ctx: &mut Renderer) -> Result<()> {
+        let barriers: Vec<_> = self
+            .buffers
+            .iter()
+            .map(|buf| {
+                buf.transition_barrier(
+                    D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                    D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                )
+            })
+            .collect();
+        ctx.resource_barrier(&barriers);
+        self.draw_args.begin_write(ctx, false);
+
+        ctx.set_pipeline_state(&self.cull_pso);
+        ctx.set_compute_srvs(&[
+            self.buffers[0].srv,
+            self.buffers[1].srv,
+            self.buffers[2].srv,
+            self.buffers[3].srv,
+        ]);
+        ctx.set_uavs(&[self.draw_args.uav()]);
+        ctx.dispatch(1, 1, 1);
+
+        self.draw_args.end_write(ctx);
+
+        Ok(())
+    }
+
+    // uses the constants set by compute()
+    fn compute_delta(&mut self, ctx: &mut Renderer) -> Result<()> {
+        ctx.resource_barrier(&[
+            self.previous.transition_barrier(
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            ),
+            self.delta.transition_barrier(
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            ),
+        ]);
+
+        ctx.set_pipeline_state(&self.delta_pso);
+        ctx.set_compute_srvs(&[
+            self.buffers[0].srv,
+            self.buffers[1].srv,
+            self.buffers[2].srv,
+            self.buffers[3].srv,
+        ]);
+        ctx.set_uavs(&[self.previous.uav, self.delta.uav]);
+        ctx.dispatch(1, 1, 1);
+
+        ctx.resource_barrier(&[
+            self.previous.transition_barrier(
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            ),
+            self.delta.transition_barrier(
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            ),
+        ]);
+
+        Ok(())
+    }
+
+    // uses the constants set by compute(), after compute_delta() has summed this frame's bins
+    fn scroll(&mut self, ctx: &mut Renderer) -> Result<()> {
+        let (src, dst) = (self.current, 1 - self.current);
+
+        ctx.resource_barrier(&[self.history[dst].transition_barrier(
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        )]);
+
+        ctx.set_pipeline_state(&self.scroll_pso);
+        ctx.set_compute_srvs(&[self.previous.srv, self.history[src].srv]);
+        ctx.set_uavs(&[self.history[dst].uav]);
+
+        let threads = 8;
+        ctx.dispatch(
+            math::div_round_up(HISTORY, threads),
+            math::div_round_up(self.bins, threads),
+            1,
+        );
+
+        ctx.resource_barrier(&[self.history[dst].transition_barrier(
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        self.current = dst;
+        self.history_reset = false;
+
+        Ok(())
+    }
+
+    // the bins are green, red, blue and luma, the config's colors are in rgb order
+    fn palette(config: &Config, alpha: f32) -> Palette {
+        let [r, g, b, l] = config.histogram_colors.map(|color| {
+            let [r, g, b] = color::colorref_to_linear(color);
+            [r, g, b, alpha]
+        });
+        Palette {
+            colors: [g, r, b, l],
+        }
+    }
+
+    fn draw_params(&self, config: &Config, rect: RECT) -> DrawParams {
+        let (width, height) = rect.size();
+        let pixels = (width * height).max(1);
+        // hue counts are weighted by saturation, saturation and chroma pile up near gray
+        let scale = config.histogram_scale
+            * match config.histogram_mode {
+                HISTOGRAM_MODE_HUE => 0.20 / (pixels as f32),
+                HISTOGRAM_MODE_RGB | HISTOGRAM_MODE_RGBL | HISTOGRAM_MODE_LUMA => {
+                    10.0 / (pixels as f32)
+                }
+                _ => 4.0 / (pixels as f32),
+            };
+
+        DrawParams {
+            mode: config.histogram_mode as _,
+            scale,
+            channel: 0,
+            auto_scale: config.histogram_auto_scale as _,
+            bins: self.bins,
+        }
+    }
+
+    // the whole window, faint and behind the roi histogram
+    fn draw_full(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
+        ctx.set_pipeline_state(&self.draw_pso);
+        ctx.set_viewport(config.scope_viewport(LAYOUT_HISTOGRAM, ViewportKind::Full));
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+
+        let mut params = self.draw_params(config, config.window_rect);
+        ctx.set_graphics_cbv(&Self::palette(config, 0.3))?;
+
+        ctx.set_graphics_srvs(&[
+            self.full_buffers[0].srv,
+            self.full_buffers[1].srv,
+            self.full_buffers[2].srv,
+            self.full_buffers[3].srv,
+        ]);
+
+        for channel in 0..Self::channels(config) {
+            params.channel = channel;
+            ctx.set_graphics_constants(&params);
+            ctx.draw(2 * self.bins, 1);
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
+        ctx.set_pipeline_state(&self.draw_pso);
+        ctx.set_viewport(config.scope_viewport(LAYOUT_HISTOGRAM, ViewportKind::Full));
+
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+
+        let mut params = self.draw_params(config, config.analysis_rect());
+        ctx.set_graphics_cbv(&Self::palette(config, 0.8))?;
+
+        ctx.set_graphics_srvs(&[
+            self.buffers[0].srv,
+            self.buffers[1].srv,
+            self.buffers[2].srv,
+            self.buffers[3].srv,
+        ]);
+
+        let ch = Self::channels(config);
+        for channel in 0..ch {
+            params.channel = channel;
+            ctx.set_graphics_constants(&params);
+
+            self.draw_args.execute(ctx, channel, 1);
+        }
+
+        // the peak marker only means something for encoded values
+        let values = matches!(
+            config.histogram_mode,
+            HISTOGRAM_MODE_RGB | HISTOGRAM_MODE_RGBL | HISTOGRAM_MODE_LUMA
+        );
+        if config.hdr_mode && values {
+            params.channel = MARKER;
+            ctx.set_graphics_constants(&params);
+            ctx.draw(4, 1);
+        }
+
+        self.draw_args.finish(ctx);
+
+        Ok(())
+    }
+
+    // loaded by the pixel shader, so the texture leaves the compute state for the draw
+    fn draw_history(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
+        let history = &self.history[self.current];
+
+        ctx.resource_barrier(&[history.transition_barrier(
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        ctx.set_pipeline_state(&self.history_pso);
+        ctx.set_viewport(config.scope_viewport(LAYOUT_HISTOGRAM, ViewportKind::Full));
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+        ctx.set_graphics_srvs(&[history.srv]);
+        ctx.draw(4, 1);
+
+        ctx.resource_barrier(&[history.transition_barrier(
+            D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+
+    // gains in green above the middle, losses in red below it
+    fn draw_delta(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
+        ctx.set_pipeline_state(&self.delta_draw_pso);
+        ctx.set_viewport(config.scope_viewport(LAYOUT_HISTOGRAM, ViewportKind::Full));
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+        let mut params = self.draw_params(config, config.analysis_rect());
+        params.scale *= DELTA_GAIN / Self::channels(config) as f32;
+        ctx.set_graphics_constants(&params);
+
+        ctx.set_graphics_srvs(&[self.delta.srv]);
+        ctx.draw(6 * self.bins, 1);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_clip_the_tails() {
+        let mut bins = [0; 256];
+        bins[0] = 1;
+        bins[255] = 1;
+        bins[16..=235].fill(10);
+
+        let [black, white] = percentile_levels(&bins, 0.001).unwrap();
+        assert_eq!(black, 16.0 / 255.0);
+        assert_eq!(white, 235.0 / 255.0);
+
+        // a tail heavier than the clipped share is kept
+        bins[0] = 100;
+        assert_eq!(percentile_levels(&bins, 0.001).unwrap()[0], 0.0);
+    }
+
+    #[test]
+    fn no_levels_for_a_flat_or_empty_frame() {
+        let mut bins = [0; 256];
+        assert_eq!(percentile_levels(&bins, 0.001), None);
+
+        bins[128] = 1000;
+        assert_eq!(percentile_levels(&bins, 0.001), None);
+    }
+
+    #[test]
+    fn roi_difference_follows_the_drawn_channels() {
+        let bins = |g: usize, r: usize| {
+            let mut channels: [Vec<u32>; 4] = std::array::from_fn(|_| vec![0; 3]);
+            channels[0][g] = 1;
+            channels[1][r] = 1;
+            channels
+        };
+        let mut data = HistogramData {
+            mode: HISTOGRAM_MODE_RGB,
+            channels: 3,
+            hdr: false,
+            colors: [0; 4],
+            bins: bins(2, 1),
+            full: None,
+        };
+        assert_eq!(data.roi_difference(), None);
+
+        data.full = Some(bins(1, 1));
+        assert_eq!(
+            data.roi_difference().unwrap(),
+            [("r", 0.0), ("g", 0.5), ("b", 0.0)]
+        );
+    }
+
+    #[test]
+    fn values_are_relative_to_the_bin_count() {
+        for len in [64, 1024] {
+            let mut bins = vec![0; len];
+            bins[len - 1] = 1;
+            assert_eq!(HistogramData::mean(&bins), 1.0);
+
+            bins[0] = 1;
+            assert_eq!(HistogramData::mean(&bins), 0.5);
+            assert_eq!(percentile_levels(&bins, 0.0), Some([0.0, 1.0]));
+        }
+    }
+}
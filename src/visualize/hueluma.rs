@@ -0,0 +1,147 @@
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+        Direct3D12::*,
+        Dxgi::Common::{DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32_UINT},
+    },
+};
+
+use crate::{
+    config::{Config, LAYOUT_HUE_LUMA},
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        math,
+        renderer::{Renderer, ViewportKind},
+        resource::RwBuffer,
+    },
+    gui::utils::Rect as _,
+};
+
+const NUM_BINS: u32 = 256;
+
+// half size of the map in the window, like the vectorscope in its square
+const EXTENT: f32 = 0.9;
+
+// hue on x and luma on y, for spotting hue shifts across the tonal range
+pub struct HueLuma {
+    compute_pso: PipelineState,
+    draw_pso: PipelineState,
+    bins: RwBuffer,
+}
+
+impl HueLuma {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let compute_pso =
+            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/HueLumaCs.bin"), None)?;
+
+        let draw_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/HueLumaVs.bin"),
+            include_bytes!("../shaders/bin/HueLumaPs.bin"),
+            BlendDesc::mul(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let bins = RwBuffer::new(ctx, NUM_BINS * NUM_BINS, DXGI_FORMAT_R32_UINT)?;
+
+        Ok(Self {
+            compute_pso,
+            draw_pso,
+            bins,
+        })
+    }
+
+    // graphics queue compute, runs next to the async compute passes
+    pub fn accumulate(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_hue_luma {
+            self.clear(ctx)?;
+            self.compute(config, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_hue_luma {
+            self.draw(config, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, ctx: &mut Renderer) -> Result<()> {
+        ctx.resource_barrier(&[self.bins.transition_barrier(
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        )]);
+
+        ctx.clear_uav(self.bins.raw_uav, &self.bins);
+
+        Ok(())
+    }
+
+    fn compute(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
+        ctx.set_pipeline_state(&self.compute_pso);
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            origin: [i32; 2],
+            hdr: u32,
+        }
+        let rect = config.analysis_rect();
+        let params = Params {
+            rect,
+            origin: [config.window_rect.left, config.window_rect.top],
+            hdr: config.hdr_mode as u32,
+        };
+        ctx.set_compute_constants(&params);
+        ctx.set_uavs(&[self.bins.uav]);
+
+        let threads = 8;
+        ctx.dispatch(
+            math::div_round_up(rect.width() as u32, threads),
+            math::div_round_up(rect.height() as u32, threads),
+            1,
+        );
+
+        ctx.resource_barrier(&[self.bins.transition_barrier(
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+
+    fn draw(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
+        ctx.set_pipeline_state(&self.draw_pso);
+        ctx.set_viewport(config.scope_viewport(LAYOUT_HUE_LUMA, ViewportKind::Full));
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+
+        #[repr(C)]
+        struct Params {
+            extent: f32,
+            scale: f32,
+        }
+
+        let (width, height) = config.analysis_rect().size();
+        let params = Params {
+            extent: EXTENT,
+            scale: config.hue_luma_scale * 4096.0 / ((width * height).max(1) as f32),
+        };
+
+        ctx.set_graphics_constants(&params);
+        ctx.set_graphics_srvs(&[self.bins.srv]);
+        ctx.draw(4, 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use windows::Win32::Graphics::{
+    Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+    Direct3D12::{
+        D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE, D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+    },
+    Dxgi::Common::{DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32_FLOAT},
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        renderer::Renderer,
+        resource::{IndirectArgs, RwBuffer},
+    },
+    gui::utils::Rect as _,
+};
+
+// density grid cells per axis, each sums 4^3 counter bins
+const GRID: u32 = 64;
+const THREAD: u32 = 4;
+
+// a surface through noise can be large, the rest is dropped
+const MAX_TRIANGLES: u32 = 1 << 18;
+
+// the cloud's counters as a surface of equal density, triangulated on the gpu every analyzed frame
+pub struct Isosurface {
+    density_pso: PipelineState,
+    mesh_pso: PipelineState,
+    args_pso: PipelineState,
+    draw_pso: PipelineState,
+    density: RwBuffer,
+    vertices: RwBuffer,
+    draw_args: IndirectArgs,
+}
+
+impl Isosurface {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let density_pso = ctx.create_compute_pipeline(
+            include_bytes!("../shaders/bin/IsosurfaceDensityCs.bin"),
+            None,
+        )?;
+
+        let mesh_pso = ctx
+            .create_compute_pipeline(include_bytes!("../shaders/bin/IsosurfaceMeshCs.bin"), None)?;
+
+        let args_pso = ctx
+            .create_compute_pipeline(include_bytes!("../shaders/bin/IsosurfaceArgsCs.bin"), None)?;
+
+        let draw_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/IsosurfaceVs.bin"),
+            include_bytes!("../shaders/bin/IsosurfacePs.bin"),
+            BlendDesc::none(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::depth(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            Some(DXGI_FORMAT_D16_UNORM),
+            None,
+        )?;
+
+        let density = RwBuffer::new(ctx, GRID * GRID * GRID, DXGI_FORMAT_R32_FLOAT)?;
+        let vertices = RwBuffer::structured::<[f32; 3]>(ctx, 3 * MAX_TRIANGLES)?;
+        let draw_args = IndirectArgs::draw(ctx, 1)?;
+
+        Ok(Self {
+            density_pso,
+            mesh_pso,
+            args_pso,
+            draw_pso,
+            density,
+            vertices,
+            draw_args,
+        })
+    }
+
+    // compute only, the counter is still being written by the cloud
+    pub fn extract(&self, ctx: &mut Renderer, counter: &RwBuffer, config: &Config) -> Result<()> {
+        ctx.resource_barrier(&[
+            counter.uav_barrier(),
+            self.density.transition_barrier(
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            ),
+            self.vertices.transition_barrier(
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            ),
+        ]);
+        self.draw_args.begin_write(ctx, true);
+
+        #[repr(C)]
+        struct Params {
+            inv_log_total: f32,
+            level: f32,
+            max_triangles: u32,
+        }
+
+        // the level is on a log scale of the analyzed pixel count, so it means the same at any size
+        let (width, height) = config.analysis_rect().size();
+        let total = (width * height).max(1) as f32;
+
+        let params = Params {
+            inv_log_total: 1.0 / (1.0 + total).log2(),
+            level: config.color_cloud_iso_level.max(1e-3),
+            max_triangles: MAX_TRIANGLES,
+        };
+
+        ctx.set_compute_constants(&params);
+        ctx.set_uavs(&[
+            counter.uav,
+            self.density.uav,
+            self.vertices.uav,
+            self.draw_args.uav(),
+        ]);
+
+        ctx.set_pipeline_state(&self.density_pso);
+        ctx.dispatch(GRID / THREAD, GRID / THREAD, GRID / THREAD);
+        ctx.resource_barrier(&[self.density.uav_barrier()]);
+
+        ctx.set_pipeline_state(&self.mesh_pso);
+        ctx.dispatch(GRID / THREAD, GRID / THREAD, GRID / THREAD);
+        ctx.resource_barrier(&[self.draw_args.uav_barrier()]);
+
+        ctx.set_pipeline_state(&self.args_pso);
+        ctx.dispatch(1, 1, 1);
+
+        ctx.resource_barrier(&[
+            self.density.transition_barrier(
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            ),
+            self.vertices.transition_barrier(
+                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            ),
+        ]);
+        self.draw_args.end_write(ctx);
+
+        Ok(())
+    }
+
+    pub fn draw(&self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        ctx.set_pipeline_state(&self.draw_pso);
+        ctx.set_viewport(config.cloud_viewport());
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+        #[repr(C)]
+        struct Params {
+            projection: [f32; 16],
+            rotation: [f32; 12],
+            color_space: u32,
+        }
+
+        let params = Params {
+            projection: config.projection_matrix().as_4x4(),
+            rotation: config.view_rotation().as_4x3(),
+            color_space: config.color_cloud_mode,
+        };
+        ctx.set_graphics_constants(&params);
+        ctx.set_graphics_srvs(&[self.vertices.srv]);
+
+        self.draw_args.execute(ctx, 0, 1);
+        self.draw_args.finish(ctx);
+
+        Ok(())
+    }
+}
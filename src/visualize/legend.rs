@@ -0,0 +1,523 @@
+use anyhow::Result;
+
+use crate::{
+    config::*,
+    graphics::{initializer::Initializer, renderer::Renderer},
+    gui::utils::Rect as _,
+};
+
+use super::text::{TextLine, TextPanel};
+
+// colorref values, 0x00bbggrr
+const RED: u32 = 0x4040ff;
+const GREEN: u32 = 0x40ff40;
+const BLUE: u32 = 0xff4040;
+const MAGENTA: u32 = 0xff00ff;
+const ZEBRA_BLUE: u32 = 0xff8000;
+const DELTA_GREEN: u32 = 0x33ff33;
+const DELTA_RED: u32 = 0x3333ff;
+// the outlines of contrast.hlsl
+const CONTRAST_NORMAL: u32 = 0x1a99ff;
+const CONTRAST_LARGE: u32 = 0x3333ff;
+// the bars of pacing.rs
+const PACING_STEADY: u32 = 0x33ff33;
+const PACING_STUTTER: u32 = 0x4040ff;
+const PACING_MERGED: u32 = 0x999999;
+
+const MARGIN: i32 = 8;
+
+// summary of what the visible scopes draw so screenshots read on their own
+pub struct Legend {
+    panel: TextPanel,
+}
+
+impl Legend {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        Ok(Self {
+            panel: TextPanel::new(ctx)?,
+        })
+    }
+
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if !config.show_legend {
+            return Ok(());
+        }
+
+        self.panel.set_lines(ctx, legend_lines(config))?;
+
+        self.panel
+            .draw(ctx, MARGIN, MARGIN, config.window_rect.usize())
+    }
+}
+
+fn legend_lines(config: &Config) -> Vec<TextLine> {
+    let mut lines = vec![];
+    let line = |text: String| TextLine::new(text, None);
+    let swatch = |text: &str, color| TextLine::new(format!("  {text}"), Some(color));
+    let rgb = |lines: &mut Vec<TextLine>| {
+        lines.push(swatch("red", RED));
+        lines.push(swatch("green", GREEN));
+        lines.push(swatch("blue", BLUE));
+    };
+
+    if config.enable_filter {
+        // a soloed channel is named instead of rgb
+        let solo = ["red", "green", "blue"]
+            .into_iter()
+            .zip(config.filter_channels)
+            .find(|&(_, enabled)| enabled && config.filter_solo)
+            .map(|(name, _)| name);
+        let mode = match config.filter_mode {
+            FILTER_MODE_RGB => solo.unwrap_or("rgb"),
+            FILTER_MODE_HUE => "hue",
+            FILTER_MODE_SAT => "saturation",
+            FILTER_MODE_LUMA => "luma",
+            FILTER_MODE_ZEBRA => "zebra",
+            FILTER_MODE_MONO => "mono",
+            FILTER_MODE_BLUE => "blue only",
+            FILTER_MODE_RANGE => "out of range",
+            _ => "rgb",
+        };
+        let analyzed = if config.analyze_filtered {
+            ", analyzed by scopes"
+        } else {
+            ""
+        };
+        lines.push(line(format!("Filter: {mode}{analyzed}")));
+
+        if config.filter_mode == FILTER_MODE_ZEBRA {
+            lines.push(swatch(
+                &format!("luma above {:.0}%", 100.0 * config.zebra_high),
+                MAGENTA,
+            ));
+            lines.push(swatch(
+                &format!("luma below {:.0}%", 100.0 * config.zebra_low),
+                ZEBRA_BLUE,
+            ));
+        }
+
+        if config.filter_mode == FILTER_MODE_RANGE {
+            lines.push(swatch("a channel above 1.0", MAGENTA));
+            lines.push(swatch("a channel below 0.0", ZEBRA_BLUE));
+        }
+
+        if config.filter_mode == FILTER_MODE_MONO {
+            lines.push(line(format!(
+                "  {:.0}% desaturated",
+                100.0 * config.desaturation
+            )));
+        }
+    }
+
+    if config.enable_histogram {
+        let mode = match config.histogram_mode {
+            HISTOGRAM_MODE_RGBL => "rgb + luma",
+            HISTOGRAM_MODE_LUMA => "luma",
+            HISTOGRAM_MODE_HUE => "hue",
+            HISTOGRAM_MODE_SAT => "hsv saturation",
+            HISTOGRAM_MODE_CHROMA => "lab chroma",
+            _ => "rgb",
+        };
+
+        // the history is a heatmap, the channel colors and the delta aren't drawn
+        if config.histogram_history {
+            lines.push(line(format!(
+                "Histogram: {mode} over time, newest on the right"
+            )));
+        } else {
+            let scale = if config.histogram_auto_scale {
+                "auto".to_string()
+            } else {
+                format!("{:.2}", config.histogram_scale)
+            };
+            lines.push(line(format!("Histogram: {mode}, scale {scale}")));
+
+            let [r, g, b, l] = config.histogram_colors;
+            if matches!(
+                config.histogram_mode,
+                HISTOGRAM_MODE_RGB | HISTOGRAM_MODE_RGBL
+            ) {
+                lines.push(swatch("red", r));
+                lines.push(swatch("green", g));
+                lines.push(swatch("blue", b));
+            }
+            if config.histogram_mode == HISTOGRAM_MODE_RGBL {
+                lines.push(swatch("luma", l));
+            }
+
+            if config.histogram_delta {
+                lines.push(swatch("gained since last frame", DELTA_GREEN));
+                lines.push(swatch("lost since last frame", DELTA_RED));
+            }
+        }
+
+        if config.suggest_levels {
+            let levels = match config.suggested_levels {
+                Some([black, white]) => {
+                    format!("black {:.0}%, white {:.0}%", 100.0 * black, 100.0 * white)
+                }
+                None => "none".to_string(),
+            };
+            let preview = if config.preview_levels {
+                ", previewed"
+            } else {
+                ""
+            };
+            lines.push(line(format!("Levels: {levels}{preview}")));
+        }
+    }
+
+    if config.enable_vectorscope {
+        lines.push(line(format!(
+            "Vectorscope: scale {:.2}",
+            config.vectorscope_scale
+        )));
+    }
+
+    if config.enable_hue_luma {
+        lines.push(line(format!(
+            "Hue vs luma: scale {:.2}",
+            config.hue_luma_scale
+        )));
+    }
+
+    if config.enable_waveform {
+        let layout = match config.waveform_layout {
+            WAVEFORM_LAYOUT_PARADE => "rgb parade",
+            _ => "composite",
+        };
+        lines.push(line(format!(
+            "Waveform: {layout}, scale {:.2}, lines every 25%",
+            config.waveform_scale
+        )));
+        rgb(&mut lines);
+    }
+
+    if config.enable_color_cloud {
+        let mode = match config.color_cloud_mode {
+            COLORCLOUD_MODE_HSL => "hsl",
+            COLORCLOUD_MODE_HSV => "hsv",
+            COLORCLOUD_MODE_YCBCR => "ycbcr",
+            COLORCLOUD_MODE_LAB => "cielab",
+            COLORCLOUD_MODE_OKLAB => "oklab",
+            _ => "rgb",
+        };
+        let dither = config.transparency_mode == TRANSPARENCY_MODE_DITHER;
+        let style = match (config.color_cloud_style, dither, config.color_cloud_oit) {
+            (COLORCLOUD_STYLE_SPLAT, true, _) => ", splats (dithered)",
+            (COLORCLOUD_STYLE_SPLAT, false, true) => ", splats (oit)",
+            (COLORCLOUD_STYLE_SPLAT, false, false) => ", splats",
+            (COLORCLOUD_STYLE_VOLUME, _, _) => ", volume",
+            _ => "",
+        };
+        let view = if config.camera.perspective {
+            ", perspective"
+        } else {
+            ""
+        };
+        lines.push(line(format!("Color cloud: {mode}{style}{view}")));
+
+        if config.color_cloud_heatmaps {
+            lines.push(line(
+                "  rg, rb, gb planes at bottom right, first channel rightward".to_string(),
+            ));
+        }
+    }
+
+    if config.estimate_gamma {
+        lines.push(line(match config.estimated_gamma {
+            Some(gamma) => format!("Signal gamma: {gamma:.2} from a gray ramp"),
+            None => "Signal gamma: no gray ramp across the region".to_string(),
+        }));
+    }
+
+    if config.enable_histogram && config.enable_roi && config.compare_roi {
+        lines.push(line(match &config.roi_difference {
+            Some(differences) => {
+                let differences: Vec<_> = differences
+                    .iter()
+                    .map(|(name, difference)| format!("{name} {difference:+.3}"))
+                    .collect();
+                format!("Region minus full window mean: {}", differences.join(", "))
+            }
+            None => "Region minus full window mean: not read yet".to_string(),
+        }));
+    }
+
+    if config.compare_outputs {
+        let output = config.compare_output;
+        lines.push(line(match config.output_difference {
+            Some([r, g, b, delta_e]) => {
+                format!(
+                    "Output {output} vs primary: dE {delta_e:.2}, r {r:+.3} g {g:+.3} b {b:+.3}"
+                )
+            }
+            None => format!("Output {output} vs primary: nothing captured yet"),
+        }));
+    }
+
+    if config.count_out_of_range {
+        lines.push(line(match config.out_of_range {
+            Some([over, under]) => format!(
+                "Out of range: {:.2}% above 1.0, {:.2}% below 0.0",
+                100.0 * over,
+                100.0 * under
+            ),
+            None => "Out of range: no pixels counted".to_string(),
+        }));
+    }
+
+    if config.enable_text_contrast {
+        lines.push(line(
+            "Text contrast: text-like tiles below WCAG minimums".to_string(),
+        ));
+        lines.push(swatch("below 4.5:1, normal text", CONTRAST_NORMAL));
+        lines.push(swatch("below 3:1, large text", CONTRAST_LARGE));
+    }
+
+    if config.enable_frame_pacing {
+        lines.push(line(
+            "Frame pacing: desktop update intervals, newest at right".to_string(),
+        ));
+        lines.push(swatch("steady", PACING_STEADY));
+        lines.push(swatch("stutter, over 1.5x the median", PACING_STUTTER));
+        lines.push(swatch("averaged over missed updates", PACING_MERGED));
+    }
+
+    if config.enable_frame_difference {
+        lines.push(line(
+            "Frame difference: changes since the previous desktop update".to_string(),
+        ));
+        if config.frame_difference_mode == DIFFERENCE_THRESHOLD {
+            lines.push(swatch(
+                &format!(
+                    "changed by over {:.0}%",
+                    100.0 * config.frame_difference_threshold
+                ),
+                MAGENTA,
+            ));
+        } else {
+            lines.push(line("  dark to bright, small to large changes".to_string()));
+        }
+    }
+
+    let rect = config.analysis_rect();
+    lines.push(line(format!(
+        "Region: {}x{} at {}, {}{}",
+        rect.width(),
+        rect.height(),
+        rect.left,
+        rect.top,
+        if config.hdr_mode { ", HDR (PQ)" } else { "" }
+    )));
+
+    if let Some(name) = config
+        .lut_path
+        .as_ref()
+        .filter(|_| config.enable_lut)
+        .and_then(|path| path.file_name())
+    {
+        lines.push(line(format!("LUT: {}", name.to_string_lossy())));
+    }
+
+    // only when it differs from an srgb desktop
+    if (config.source_primaries, config.source_transfer) != (PRIMARIES_BT709, TRANSFER_SRGB) {
+        let primaries = match config.source_primaries {
+            PRIMARIES_BT601 => "Rec.601",
+            PRIMARIES_BT2020 => "Rec.2020",
+            _ => "Rec.709",
+        };
+        let transfer = match config.source_transfer {
+            TRANSFER_BT709 => "Rec.709",
+            TRANSFER_BT2020 => "Rec.2020",
+            _ => "sRGB",
+        };
+        lines.push(line(format!("Source: {primaries} / {transfer}")));
+    }
+
+    let source = config
+        .source_path
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy());
+    match (config.capture_source, source, config.video_time) {
+        (SOURCE_FILE, Some(name), _) => lines.push(line(format!("Image: {name}"))),
+        (SOURCE_VIDEO, Some(name), Some((position, duration))) => lines.push(line(format!(
+            "Video: {name}, {} / {}{}",
+            clock(position),
+            clock(duration),
+            if config.video_playing { "" } else { ", paused" }
+        ))),
+        (SOURCE_SHARED, _, _) if !config.shared_name.is_empty() => {
+            lines.push(line(format!("Shared: {}", config.shared_name)))
+        }
+        _ => {}
+    }
+
+    if config.freeze {
+        lines.push(line("Frozen frame".to_string()));
+    }
+
+    if config.recording {
+        lines.push(line("Recording".to_string()));
+    }
+
+    lines
+}
+
+// m:ss.s
+fn clock(seconds: f32) -> String {
+    let tenths = (10.0 * seconds.max(0.0)) as u32;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_only_enabled_scopes() {
+        let mut config = Config::load("").unwrap();
+        config.enable_filter = false;
+        config.enable_histogram = true;
+        config.histogram_mode = HISTOGRAM_MODE_LUMA;
+        config.enable_vectorscope = false;
+        config.enable_waveform = false;
+        config.enable_color_cloud = false;
+
+        let lines = legend_lines(&config);
+        let texts: Vec<_> = lines.iter().map(|line| line.text.as_str()).collect();
+
+        assert!(texts[0].starts_with("Histogram: luma"));
+        assert!(texts[1].starts_with("Region: "));
+        assert_eq!(texts.len(), 2);
+    }
+
+    #[test]
+    fn lists_suggested_levels() {
+        let mut config = Config::load("").unwrap();
+        config.enable_histogram = true;
+        config.histogram_mode = HISTOGRAM_MODE_LUMA;
+        config.suggest_levels = true;
+        config.suggested_levels = Some([0.05, 0.95]);
+
+        let lines = legend_lines(&config);
+        assert!(lines
+            .iter()
+            .any(|line| line.text == "Levels: black 5%, white 95%"));
+    }
+
+    #[test]
+    fn lists_estimated_gamma() {
+        let mut config = Config::load("").unwrap();
+        config.estimate_gamma = true;
+        config.estimated_gamma = Some(2.2);
+
+        let lines = legend_lines(&config);
+        assert!(lines
+            .iter()
+            .any(|line| line.text == "Signal gamma: 2.20 from a gray ramp"));
+    }
+
+    #[test]
+    fn lists_the_roi_difference() {
+        let mut config = Config::load("").unwrap();
+        config.enable_histogram = true;
+        config.enable_roi = true;
+        config.compare_roi = true;
+        config.roi_difference = Some(vec![("luma", -0.125)]);
+
+        let lines = legend_lines(&config);
+        assert!(lines
+            .iter()
+            .any(|line| line.text == "Region minus full window mean: luma -0.125"));
+    }
+
+    #[test]
+    fn lists_the_output_difference() {
+        let mut config = Config::load("").unwrap();
+        config.compare_outputs = true;
+        config.compare_output = 1;
+        config.output_difference = Some([0.01, -0.02, 0.0, 1.5]);
+
+        let lines = legend_lines(&config);
+        assert!(lines
+            .iter()
+            .any(|line| line.text == "Output 1 vs primary: dE 1.50, r +0.010 g -0.020 b +0.000"));
+    }
+
+    #[test]
+    fn lists_out_of_range_pixels() {
+        let mut config = Config::load("").unwrap();
+        config.count_out_of_range = true;
+        config.out_of_range = Some([0.125, 0.0]);
+
+        let lines = legend_lines(&config);
+        assert!(lines
+            .iter()
+            .any(|line| line.text == "Out of range: 12.50% above 1.0, 0.00% below 0.0"));
+    }
+
+    #[test]
+    fn lists_partial_desaturation() {
+        let mut config = Config::load("").unwrap();
+        config.enable_filter = true;
+        config.filter_mode = FILTER_MODE_MONO;
+        config.desaturation = 0.5;
+
+        let lines = legend_lines(&config);
+        assert!(lines[0].text.starts_with("Filter: mono"));
+        assert_eq!(lines[1].text, "  50% desaturated");
+    }
+
+    #[test]
+    fn lists_the_solo_channel() {
+        let mut config = Config::load("").unwrap();
+        config.enable_filter = true;
+        config.filter_mode = FILTER_MODE_RGB;
+        config.filter_channels = [false, true, false, true];
+        config.filter_solo = true;
+
+        let lines = legend_lines(&config);
+        assert!(lines[0].text.starts_with("Filter: green"));
+    }
+
+    #[test]
+    fn lists_the_video_position() {
+        let mut config = Config::load("").unwrap();
+        config.capture_source = SOURCE_VIDEO;
+        config.source_path = Some("clips/test.mp4".into());
+        config.video_time = Some((72.35, 605.0));
+        config.video_playing = false;
+
+        let lines = legend_lines(&config);
+        assert_eq!(
+            lines.last().unwrap().text,
+            "Video: test.mp4, 1:12.3 / 10:05.0, paused"
+        );
+    }
+
+    #[test]
+    fn lists_text_contrast_outlines() {
+        let mut config = Config::load("").unwrap();
+        config.enable_text_contrast = true;
+
+        let lines = legend_lines(&config);
+        assert!(lines.iter().any(
+            |line| line.text == "  below 3:1, large text" && line.color == Some(CONTRAST_LARGE)
+        ));
+    }
+
+    #[test]
+    fn lists_the_difference_threshold() {
+        let mut config = Config::load("").unwrap();
+        config.enable_frame_difference = true;
+        config.frame_difference_mode = DIFFERENCE_THRESHOLD;
+        config.frame_difference_threshold = 0.1;
+
+        let lines = legend_lines(&config);
+        assert!(lines
+            .iter()
+            .any(|line| line.text == "  changed by over 10%" && line.color == Some(MAGENTA)));
+    }
+}
@@ -0,0 +1,232 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{bail, Context as _, Result};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::descriptor::Descriptor, initializer::Initializer, renderer::Renderer,
+        resource::Texture3D,
+    },
+};
+
+// d3d12 caps 3d textures at 2048, real luts stay far below that
+const MAX_SIZE: u32 = 256;
+
+// how often the file is checked for changes, so a fixed or edited lut is picked up without
+// selecting it again
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// 3d lut applied to the captured pixels before the filter and every scope
+pub struct Lut {
+    texture: Texture3D,
+    none: Texture3D,
+    path: Option<PathBuf>,
+    loaded: bool,
+    // modification time of the file when it was last read, and when that was checked
+    modified: Option<SystemTime>,
+    polled: Instant,
+}
+
+impl Lut {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let texture = Texture3D::new(ctx, 1)?;
+        let none = Texture3D::new(ctx, 1)?;
+
+        Ok(Self {
+            texture,
+            none,
+            path: None,
+            loaded: false,
+            modified: None,
+            polled: Instant::now(),
+        })
+    }
+
+    // loads the configured file when it is selected or changes on disk, the returned srv goes
+    // into the shared table
+    pub fn prepare(&mut self, ctx: &mut Renderer, config: &Config) -> Result<Descriptor> {
+        let path = config.lut_path.as_ref().filter(|_| config.enable_lut);
+        if path.is_none() {
+            // enabling it again reads the file again
+            self.path = None;
+        }
+
+        if let Some(path) = path {
+            let selected = self.path.as_ref() != Some(path);
+            let now = Instant::now();
+            let poll = now.duration_since(self.polled) >= POLL_INTERVAL;
+
+            if selected || poll {
+                self.polled = now;
+
+                // a broken file is reported once and retried only after it was saved again
+                let modified = modified(path);
+                if selected || modified != self.modified {
+                    self.path = Some(path.clone());
+                    self.modified = modified;
+
+                    self.loaded = match self.load(ctx, path) {
+                        Ok(()) => true,
+                        Err(e) => {
+                            println!("{e:?}");
+                            false
+                        }
+                    };
+                }
+            }
+        }
+
+        Ok(if path.is_some() && self.loaded {
+            self.texture.srv
+        } else {
+            self.none.srv
+        })
+    }
+
+    fn load(&mut self, ctx: &mut Renderer, path: &Path) -> Result<()> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let cube = parse_cube(&text).with_context(|| format!("invalid lut {}", path.display()))?;
+
+        let texels: Vec<[f32; 4]> = cube.rgb.iter().map(|&[r, g, b]| [r, g, b, 1.0]).collect();
+        self.texture.upload(ctx, cube.size, &texels)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+#[derive(Debug)]
+pub struct Cube {
+    pub size: u32,
+    // red changes fastest, then green, then blue
+    pub rgb: Vec<[f32; 3]>,
+}
+
+// parses the adobe/resolve .cube format, only 3d luts over the default 0..1 domain. resolve writes
+// the domain as LUT_3D_INPUT_RANGE
+pub fn parse_cube(text: &str) -> Result<Cube> {
+    let mut size = None;
+    let mut rgb = vec![];
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+
+        match keyword {
+            "TITLE" => {}
+            "LUT_3D_SIZE" => {
+                let n: u32 = words.next().unwrap_or_default().parse()?;
+                if !(2..=MAX_SIZE).contains(&n) {
+                    bail!("unsupported size {n}");
+                }
+                size = Some(n);
+            }
+            "LUT_1D_SIZE" => bail!("1d luts are not supported"),
+            "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                let expected = if keyword == "DOMAIN_MIN" { 0.0 } else { 1.0 };
+                for word in words {
+                    if word.parse::<f32>()? != expected {
+                        bail!("only the 0..1 domain is supported");
+                    }
+                }
+            }
+            "LUT_3D_INPUT_RANGE" => {
+                let range = words
+                    .map(str::parse::<f32>)
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| format!("unexpected line \"{line}\""))?;
+                if range[..] != [0.0, 1.0] {
+                    bail!("only the 0..1 input range is supported");
+                }
+            }
+            "LUT_1D_INPUT_RANGE" => bail!("1d luts are not supported"),
+            _ => {
+                let values = line
+                    .split_whitespace()
+                    .map(str::parse::<f32>)
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| format!("unexpected line \"{line}\""))?;
+                let [r, g, b] = values[..] else {
+                    bail!("expected 3 values in \"{line}\"");
+                };
+                rgb.push([r, g, b]);
+            }
+        }
+    }
+
+    let Some(size) = size else {
+        bail!("missing LUT_3D_SIZE");
+    };
+
+    let expected = (size * size * size) as usize;
+    if rgb.len() != expected {
+        bail!("expected {expected} entries, found {}", rgb.len());
+    }
+
+    Ok(Cube { size, rgb })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTITY_2: &str = "TITLE \"identity\"
+# comment
+LUT_3D_SIZE 2
+DOMAIN_MIN 0.0 0.0 0.0
+DOMAIN_MAX 1.0 1.0 1.0
+
+0 0 0
+1 0 0
+0 1 0
+1 1 0
+0 0 1
+1 0 1
+0 1 1
+1 1 1
+";
+
+    #[test]
+    fn parses_identity_in_red_major_order() {
+        let cube = parse_cube(IDENTITY_2).unwrap();
+
+        assert_eq!(cube.size, 2);
+        assert_eq!(cube.rgb.len(), 8);
+        assert_eq!(cube.rgb[1], [1.0, 0.0, 0.0]);
+        assert_eq!(cube.rgb[2], [0.0, 1.0, 0.0]);
+        assert_eq!(cube.rgb[4], [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn rejects_unsupported_files() {
+        assert!(parse_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+        assert!(parse_cube("0 0 0\n").is_err());
+        assert!(parse_cube(&IDENTITY_2.replace("DOMAIN_MAX 1.0", "DOMAIN_MAX 2.0")).is_err());
+        assert!(parse_cube(&IDENTITY_2.replace("1 1 1\n", "")).is_err());
+        assert!(parse_cube(&IDENTITY_2.replace("1 1 1\n", "1 1\n")).is_err());
+    }
+
+    #[test]
+    fn accepts_the_resolve_input_range() {
+        let resolve = IDENTITY_2.replace("DOMAIN_MIN 0.0 0.0 0.0", "LUT_3D_INPUT_RANGE 0.0 1.0");
+        assert_eq!(parse_cube(&resolve).unwrap().size, 2);
+
+        let wide = IDENTITY_2.replace("DOMAIN_MIN 0.0 0.0 0.0", "LUT_3D_INPUT_RANGE 0.0 2.0");
+        assert!(parse_cube(&wide).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::Result;
+use windows::Win32::Graphics::{
+    Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+    Direct3D12::{
+        D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE, D3D12_RESOURCE_STATE_DEPTH_WRITE,
+        D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE,
+    },
+    Dxgi::Common::{DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R16_UNORM},
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{descriptor::Descriptor, pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        renderer::{msaa_index, Renderer, ViewportKind, MSAA_SAMPLES},
+        resource::Texture2D,
+    },
+    gui::utils::Rect as _,
+};
+
+// line passes drawn between begin() and end() go through the multisampled targets,
+// the resolved layer is then blended over the swap chain buffer
+pub struct MsaaLayer {
+    resolved: Texture2D,
+    composite_pso: PipelineState,
+    // one per msaa sample count
+    depth_psos: Vec<PipelineState>,
+    depth_srv: Descriptor,
+    active: bool,
+}
+
+impl MsaaLayer {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let resolved = Texture2D::new(ctx, 1, 1, DXGI_FORMAT_R16G16B16A16_FLOAT)?;
+
+        let composite_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/MsaaCompositeVs.bin"),
+            include_bytes!("../shaders/bin/MsaaCompositePs.bin"),
+            BlendDesc::premultiplied(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let depth_psos = MSAA_SAMPLES
+            .iter()
+            .map(|&samples| {
+                ctx.create_multisampled_pipeline(
+                    samples,
+                    include_bytes!("../shaders/bin/MsaaCompositeVs.bin"),
+                    include_bytes!("../shaders/bin/MsaaDepthPs.bin"),
+                    BlendDesc::none(),
+                    RasterizerDesc::none(),
+                    DepthStencilDesc::overwrite(),
+                    &[],
+                    D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+                    RtvFormats::from_slice(&[]),
+                    Some(DXGI_FORMAT_D16_UNORM),
+                    None,
+                )
+            })
+            .collect::<Result<_>>()?;
+        let depth_srv = ctx.next_descriptor();
+
+        Ok(Self {
+            resolved,
+            composite_pso,
+            depth_psos,
+            depth_srv,
+            active: false,
+        })
+    }
+
+    // the resolved layer follows the window size, the context waits for the gpu when it changes.
+    // with scene_depth the lines are depth tested against what was drawn before, like without msaa
+    pub fn begin(&mut self, ctx: &mut Renderer, config: &Config, scene_depth: bool) -> Result<()> {
+        if config.msaa_samples <= 1 {
+            return Ok(());
+        }
+
+        let (width, height) = config.window_rect.usize();
+        let desc = self.resolved.desc();
+        if (desc.Width as u32, desc.Height) != (width, height) {
+            self.resolved.resize(ctx.device(), width, height)?;
+        }
+
+        self.active = ctx.begin_msaa();
+        if self.active && scene_depth {
+            self.copy_depth(ctx);
+        }
+
+        Ok(())
+    }
+
+    pub fn end(&mut self, ctx: &mut Renderer) -> Result<()> {
+        if !std::mem::take(&mut self.active) {
+            return Ok(());
+        }
+
+        ctx.end_msaa(&self.resolved);
+
+        ctx.set_pipeline_state(&self.composite_pso);
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+        ctx.set_graphics_srvs(&[self.resolved.srv]);
+        ctx.draw(4, 1);
+
+        Ok(())
+    }
+
+    // the swap chain buffer's depth isn't bound while the multisampled targets are
+    fn copy_depth(&mut self, ctx: &mut Renderer) {
+        let depth = ctx.depth().clone();
+        ctx.device().create_srv(
+            &depth,
+            Some(&SrvDesc::texture2d(DXGI_FORMAT_R16_UNORM)),
+            self.depth_srv.cpu,
+        );
+
+        ctx.resource_barrier(&[depth.transition_barrier(
+            D3D12_RESOURCE_STATE_DEPTH_WRITE,
+            D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        ctx.set_pipeline_state(&self.depth_psos[msaa_index(ctx.samples())]);
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+        ctx.set_graphics_srvs(&[self.depth_srv]);
+        ctx.draw(4, 1);
+
+        ctx.resource_barrier(&[depth.transition_barrier(
+            D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_DEPTH_WRITE,
+        )]);
+    }
+}
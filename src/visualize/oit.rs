@@ -0,0 +1,108 @@
+use anyhow::Result;
+use windows::Win32::Graphics::{
+    Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+    Direct3D12::{
+        D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE, D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+        D3D12_RESOURCE_STATE_RENDER_TARGET,
+    },
+    Dxgi::Common::{DXGI_FORMAT, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R16_FLOAT},
+};
+
+use crate::graphics::{
+    core::{descriptor::RtvHeap, pso::PipelineState, wrap::*},
+    initializer::Initializer,
+    renderer::{Renderer, ViewportKind},
+    resource::RenderTexture,
+};
+
+pub const ACCUM_FORMAT: DXGI_FORMAT = DXGI_FORMAT_R16G16B16A16_FLOAT;
+pub const REVEAL_FORMAT: DXGI_FORMAT = DXGI_FORMAT_R16_FLOAT;
+
+// weighted blended order independent transparency (mcguire and bavoil 2013)
+// translucent passes draw into accum and reveal between begin() and end()
+pub struct WeightedOit {
+    #[allow(unused)]
+    rtv_heap: RtvHeap,
+    accum: RenderTexture,
+    reveal: RenderTexture,
+    composite_pso: PipelineState,
+}
+
+impl WeightedOit {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let rtv_heap = RtvHeap::new(ctx, 2)?;
+
+        let accum = RenderTexture::new(ctx, rtv_heap.descriptor(0), 1, 1, ACCUM_FORMAT, [0.0; 4])?;
+        let reveal =
+            RenderTexture::new(ctx, rtv_heap.descriptor(1), 1, 1, REVEAL_FORMAT, [1.0; 4])?;
+
+        let composite_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/OitCompositeVs.bin"),
+            include_bytes!("../shaders/bin/OitCompositePs.bin"),
+            BlendDesc::mul(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        Ok(Self {
+            rtv_heap,
+            accum,
+            reveal,
+            composite_pso,
+        })
+    }
+
+    // the targets follow the window size, the context waits for the gpu when it changes
+    pub fn begin(&mut self, ctx: &mut Renderer, width: u32, height: u32) -> Result<()> {
+        if self.accum.size() != (width, height) {
+            self.accum.resize(ctx.device(), width, height)?;
+            self.reveal.resize(ctx.device(), width, height)?;
+        }
+
+        ctx.resource_barrier(&[
+            self.accum.transition_barrier(
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+            ),
+            self.reveal.transition_barrier(
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+            ),
+        ]);
+
+        ctx.clear_render_target(self.accum.rtv, &self.accum.clear);
+        ctx.clear_render_target(self.reveal.rtv, &self.reveal.clear);
+        ctx.set_render_targets(&[self.accum.rtv, self.reveal.rtv]);
+
+        Ok(())
+    }
+
+    // resolves the accumulated layers over the swap chain buffer
+    pub fn end(&mut self, ctx: &mut Renderer) -> Result<()> {
+        ctx.reset_render_targets();
+
+        ctx.resource_barrier(&[
+            self.accum.transition_barrier(
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            ),
+            self.reveal.transition_barrier(
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            ),
+        ]);
+
+        ctx.set_pipeline_state(&self.composite_pso);
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+        ctx.set_graphics_srvs(&[self.accum.srv, self.reveal.srv]);
+        ctx.draw(4, 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use windows::Win32::Foundation::RECT;
+
+use crate::graphics::{
+    core::{device::Device, resource::Resource},
+    duplicate::Duplication,
+    renderer::Renderer,
+    resource::{Pixels, TextureReadback},
+};
+
+use super::color;
+
+// full frame readbacks are too slow to run every frame
+const INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct OutputComparison {
+    // linear rgb of the primary and the compared output
+    pub mean: [[f32; 3]; 2],
+    pub delta: [f32; 3],
+    pub delta_e: f32,
+}
+
+// measures the same region on the primary and a second output of the adapter
+pub struct OutputCompare {
+    index: u32,
+    dupl: Option<Duplication>,
+    readbacks: [TextureReadback; 2],
+    means: [Option<[f32; 3]>; 2],
+    last: Option<Instant>,
+    // whether the frame being recorded copies anything for read()
+    copied: bool,
+}
+
+impl OutputCompare {
+    pub fn new(device: &Device, index: u32) -> Self {
+        let dupl = match Duplication::output(device, index) {
+            Ok(dupl) => Some(dupl),
+            Err(e) => {
+                println!("output {index}: {e:?}");
+                None
+            }
+        };
+
+        Self {
+            index,
+            dupl,
+            readbacks: Default::default(),
+            means: [None; 2],
+            last: None,
+            copied: false,
+        }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    // only frames that capture need to be waited for
+    pub fn copied(&self) -> bool {
+        self.copied
+    }
+
+    // the rect is in each output's own coordinates
+    pub fn capture(&mut self, ctx: &mut Renderer, primary: &Resource, rect: RECT) -> Result<()> {
+        self.copied = false;
+        if self.last.is_some_and(|last| last.elapsed() < INTERVAL) {
+            return Ok(());
+        }
+        self.last = Some(Instant::now());
+        self.copied = true;
+
+        self.readbacks[0].copy(ctx, primary, rect)?;
+
+        // a static test pattern produces no new frames, the last mean is kept then
+        if let Some(dupl) = &mut self.dupl {
+            if dupl.acquire(ctx.device(), 0)? {
+                if let Some(resource) = dupl.resource() {
+                    self.readbacks[1].copy(ctx, resource, rect)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // valid after the frame recorded by capture() has finished on the GPU
+    pub fn read(&mut self) -> Result<()> {
+        if !std::mem::take(&mut self.copied) {
+            return Ok(());
+        }
+
+        for (readback, mean) in self.readbacks.iter_mut().zip(&mut self.means) {
+            if let Some(pixels) = readback.read()? {
+                *mean = Some(mean_rgb(&pixels));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn comparison(&self) -> Option<OutputComparison> {
+        let [Some(primary), Some(other)] = self.means else {
+            return None;
+        };
+
+        Some(OutputComparison {
+            mean: [primary, other],
+            delta: std::array::from_fn(|ch| other[ch] - primary[ch]),
+            delta_e: color::delta_e_2000(color::rgb_to_lab(primary), color::rgb_to_lab(other)),
+        })
+    }
+}
+
+fn mean_rgb(pixels: &Pixels) -> [f32; 3] {
+    let sum = pixels.rgb.iter().fold([0f64; 3], |sum, rgb| {
+        std::array::from_fn(|ch| sum[ch] + rgb[ch] as f64)
+    });
+    let count = pixels.rgb.len().max(1) as f64;
+
+    sum.map(|sum| (sum / count) as f32)
+}
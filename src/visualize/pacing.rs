@@ -0,0 +1,328 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use windows::{
+    core::s,
+    Win32::{
+        Foundation::RECT,
+        Graphics::{
+            Direct3D::D3D_PRIMITIVE_TOPOLOGY_LINELIST,
+            Direct3D12::D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+            Dxgi::Common::{DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32G32B32_FLOAT},
+        },
+    },
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        duplicate::Present,
+        initializer::Initializer,
+        math::Matrix,
+        profiler,
+        renderer::{Renderer, ViewportKind},
+        resource::DynamicVertexBuffer,
+    },
+    gui::utils::Rect as _,
+};
+
+use super::text::{TextLine, TextPanel};
+
+// intervals kept for the chart, one per desktop update
+const HISTORY: usize = 240;
+
+// intervals this many times the median count as a stutter
+const STUTTER: f32 = 1.5;
+
+// the chart at the bottom center, in pixels
+const WIDTH: i32 = 480;
+const HEIGHT: i32 = 96;
+const MARGIN: i32 = 8;
+
+const GREEN: [f32; 3] = [0.2, 1.0, 0.2];
+const RED: [f32; 3] = [1.0, 0.25, 0.25];
+const GRAY: [f32; 3] = [0.6, 0.6, 0.6];
+const DIM: [f32; 3] = [0.35, 0.35, 0.35];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Sample {
+    // ms between desktop updates
+    interval: f32,
+    // several updates since the last acquired frame, the interval is their mean
+    merged: bool,
+}
+
+// intervals between the desktop updates of the captured content, independent of how often the
+// overlay draws
+#[derive(Default)]
+struct Timeline {
+    last: Option<i64>,
+    samples: VecDeque<Sample>,
+}
+
+impl Timeline {
+    fn record(&mut self, present: Present, frequency: u64) {
+        if let Some(last) = self.last.filter(|&last| present.time > last) {
+            let ms = 1000.0 * (present.time - last) as f64 / frequency as f64;
+            let count = present.accumulated.max(1);
+
+            self.samples.push_back(Sample {
+                interval: (ms / count as f64) as f32,
+                merged: count > 1,
+            });
+            if self.samples.len() > HISTORY {
+                self.samples.pop_front();
+            }
+        }
+
+        self.last = Some(present.time);
+    }
+
+    // the next update isn't compared with the last, for pauses in capturing
+    fn interrupt(&mut self) {
+        self.last = None;
+    }
+
+    fn median(&self) -> Option<f32> {
+        let mut intervals: Vec<_> = self.samples.iter().map(|s| s.interval).collect();
+        intervals.sort_by(f32::total_cmp);
+        intervals.get(intervals.len() / 2).copied()
+    }
+
+    fn stutters(&self, median: f32) -> usize {
+        self.samples
+            .iter()
+            .filter(|s| s.interval > STUTTER * median)
+            .count()
+    }
+
+    fn lines(&self) -> Vec<TextLine> {
+        let Some(median) = self.median() else {
+            return vec![TextLine::new(
+                "Frame pacing: waiting for desktop updates",
+                None,
+            )];
+        };
+
+        let max = self.samples.iter().map(|s| s.interval).fold(0.0, f32::max);
+        let merged = self.samples.iter().filter(|s| s.merged).count();
+
+        let mut lines = vec![
+            TextLine::new(
+                format!(
+                    "Frame pacing: {median:.1} ms median ({:.0} fps)",
+                    1000.0 / median.max(0.001)
+                ),
+                None,
+            ),
+            TextLine::new(
+                format!(
+                    "  max {max:.1} ms, {} stutters in {}",
+                    self.stutters(median),
+                    self.samples.len()
+                ),
+                None,
+            ),
+        ];
+        if merged > 0 {
+            lines.push(TextLine::new(
+                format!("  {merged} averaged over missed updates"),
+                None,
+            ));
+        }
+
+        lines
+    }
+
+    // a bar per interval with the newest at the right, in ndc of the chart. intervals reach the
+    // top at three times the median
+    fn vertices(&self) -> Vec<Vertex> {
+        let Some(median) = self.median() else {
+            return vec![];
+        };
+
+        let scale = 3.0 * median.max(0.001);
+        let y = |interval: f32| 2.0 * (interval / scale).min(1.0) - 1.0;
+        let line = |from: [f32; 2], to: [f32; 2], color| {
+            [from, to].map(|[x, y]| Vertex {
+                position: [x, y, 0.5],
+                color,
+            })
+        };
+
+        let mut vertices = vec![];
+        vertices.extend(line([-1.0, y(median)], [1.0, y(median)], DIM));
+        vertices.extend(line(
+            [-1.0, y(STUTTER * median)],
+            [1.0, y(STUTTER * median)],
+            DIM,
+        ));
+
+        let newest = self.samples.len() - 1;
+        for (i, sample) in self.samples.iter().enumerate() {
+            let x = 1.0 - 2.0 * (newest - i) as f32 / (HISTORY - 1) as f32;
+            let color = if sample.interval > STUTTER * median {
+                RED
+            } else if sample.merged {
+                GRAY
+            } else {
+                GREEN
+            };
+            vertices.extend(line([x, -1.0], [x, y(sample.interval)], color));
+        }
+
+        vertices
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+// a strip chart of how steadily the captured content delivers frames, for spotting its stutter
+// apart from the overlay's own
+pub struct FramePacing {
+    pso: PipelineState,
+    chart: DynamicVertexBuffer,
+    panel: TextPanel,
+    timeline: Timeline,
+    frequency: u64,
+}
+
+impl FramePacing {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/PrimitiveVs.bin"),
+            include_bytes!("../shaders/bin/PrimitivePs.bin"),
+            BlendDesc::none(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[
+                InputElementDesc::per_vertex(s!("POSITION"), DXGI_FORMAT_R32G32B32_FLOAT),
+                InputElementDesc::per_vertex(s!("COLOR"), DXGI_FORMAT_R32G32B32_FLOAT),
+            ],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let chart = DynamicVertexBuffer::new::<Vertex>(ctx, 2 * HISTORY + 4)?;
+
+        Ok(Self {
+            pso,
+            chart,
+            panel: TextPanel::new(ctx)?,
+            timeline: Timeline::default(),
+            frequency: profiler::frequency(),
+        })
+    }
+
+    // the update of the frame just acquired, None when there was none or the desktop is reused
+    pub fn update(&mut self, config: &Config, present: Option<Present>) {
+        if !config.enable_frame_pacing || config.freeze {
+            self.timeline.interrupt();
+            return;
+        }
+
+        if let Some(present) = present {
+            self.timeline.record(present, self.frequency);
+        }
+    }
+
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if !config.enable_frame_pacing {
+            return Ok(());
+        }
+
+        let (width, height) = config.window_rect.size();
+        let left = ((width - WIDTH) / 2).max(0);
+        let bottom = height - MARGIN;
+        let rect = RECT {
+            left,
+            top: (bottom - HEIGHT).max(0),
+            right: (left + WIDTH).min(width),
+            bottom,
+        };
+
+        let vertices = self.timeline.vertices();
+        if !vertices.is_empty() && rect.width() > 0 && rect.height() > 0 {
+            ctx.set_pipeline_state(&self.pso);
+            ctx.set_viewport(ViewportKind::Rect(rect));
+            ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+
+            self.chart.write(&vertices)?;
+            ctx.set_vertex_buffers(&[self.chart.view()]);
+
+            #[repr(C)]
+            struct Params {
+                projection: [f32; 16],
+            }
+            ctx.set_graphics_constants(&Params {
+                projection: Matrix::identity().as_4x4(),
+            });
+            ctx.draw(self.chart.vertex_count(), 1);
+        }
+
+        self.panel.set_lines(ctx, self.timeline.lines())?;
+        let y = rect.top - self.panel.size().1 as i32 - MARGIN;
+        self.panel
+            .draw(ctx, left, y.max(0), config.window_rect.usize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn present(time: i64, accumulated: u32) -> Present {
+        Present { time, accumulated }
+    }
+
+    #[test]
+    fn records_intervals_between_updates() {
+        let mut timeline = Timeline::default();
+        // 1000 ticks per ms
+        for (i, time) in [0, 16_000, 33_000, 50_000, 100_000].into_iter().enumerate() {
+            timeline.record(present(time + 1, 1), 1_000_000);
+            assert_eq!(timeline.samples.len(), i);
+        }
+
+        let intervals: Vec<_> = timeline.samples.iter().map(|s| s.interval).collect();
+        assert_eq!(intervals, [16.0, 17.0, 17.0, 50.0]);
+        assert_eq!(timeline.median(), Some(17.0));
+        assert_eq!(timeline.stutters(17.0), 1);
+    }
+
+    #[test]
+    fn spreads_missed_updates_and_skips_pauses() {
+        let mut timeline = Timeline::default();
+        timeline.record(present(1000, 1), 1000);
+        timeline.record(present(1040, 4), 1000);
+        assert_eq!(
+            timeline.samples[0],
+            Sample {
+                interval: 10.0,
+                merged: true
+            }
+        );
+
+        timeline.interrupt();
+        timeline.record(present(9000, 1), 1000);
+        assert_eq!(timeline.samples.len(), 1);
+    }
+
+    #[test]
+    fn keeps_a_bounded_history() {
+        let mut timeline = Timeline::default();
+        for i in 0..2 * HISTORY as i64 {
+            timeline.record(present(1 + 16 * i, 1), 1000);
+        }
+        assert_eq!(timeline.samples.len(), HISTORY);
+        assert_eq!(timeline.vertices().len(), 2 * HISTORY + 4);
+    }
+}
@@ -0,0 +1,183 @@
+use anyhow::Result;
+use windows::Win32::Foundation::RECT;
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{command_queue::FRAME_COUNT, resource::Resource},
+        renderer::Renderer,
+        resource::TextureReadback,
+    },
+    gui::utils::Rect as _,
+};
+
+use super::{color, sampling::SampleFilter};
+
+// shorter rows don't have enough steps for a fit
+const MIN_LENGTH: usize = 32;
+
+// how far r, g and b may spread, relative to the brightest channel, for a pixel to count as gray
+const NEUTRAL_TOLERANCE: f32 = 0.05;
+
+// dips along the ramp allowed for noise and dithering, relative to the white end
+const MONOTONIC_TOLERANCE: f32 = 0.01;
+
+// the dark end has to be this close to black, relative to the white end
+const MAX_BLACK: f32 = 0.05;
+
+// gamma of a black to white gray ramp running left to right across the whole row, None when the
+// row isn't one. the signal of each pixel is taken from its position between the ends
+pub fn ramp_gamma(row: &[[f32; 3]]) -> Option<f32> {
+    if row.len() < MIN_LENGTH {
+        return None;
+    }
+
+    let neutral = row.iter().all(|&[r, g, b]| {
+        let max = r.max(g).max(b);
+        max - r.min(g).min(b) <= NEUTRAL_TOLERANCE * max + 1e-3
+    });
+    if !neutral {
+        return None;
+    }
+
+    let luminance: Vec<_> = row.iter().map(|&rgb| color::rgb_to_xyz(rgb)[1]).collect();
+    let (black, white) = (luminance[0], luminance[row.len() - 1]);
+    if white <= 0.0 || black > MAX_BLACK * white {
+        return None;
+    }
+
+    let tolerance = MONOTONIC_TOLERANCE * white;
+    if luminance
+        .windows(2)
+        .any(|pair| pair[1] < pair[0] - tolerance)
+    {
+        return None;
+    }
+
+    let last = (row.len() - 1) as f32;
+    let pairs: Vec<_> = luminance
+        .iter()
+        .enumerate()
+        .map(|(i, y)| (i as f32 / last, (y - black) / (white - black)))
+        .collect();
+
+    color::fit_gamma(&pairs)
+}
+
+// reads back the middle row of the analyzed region and fits a gamma when it holds a gray ramp.
+// that is the gamma the source encoded the ramp with as it reaches the capture, the display's own
+// response never shows up in it. each frame slot has its own readback, read a couple of frames
+// late once the context has waited for the slot again
+pub struct RampGamma {
+    readbacks: [TextureReadback; FRAME_COUNT],
+    filter: SampleFilter<1>,
+    gamma: Option<f32>,
+}
+
+impl RampGamma {
+    pub fn new() -> Self {
+        Self {
+            readbacks: Default::default(),
+            filter: SampleFilter::new(),
+            gamma: None,
+        }
+    }
+
+    // the signal gamma from the frames read so far, None while no ramp is in view
+    pub fn gamma(&self) -> Option<f32> {
+        self.gamma
+    }
+
+    pub fn process(
+        &mut self,
+        ctx: &mut Renderer,
+        desktop: &Resource,
+        config: &Config,
+        frame: usize,
+    ) -> Result<()> {
+        if !config.estimate_gamma {
+            return Ok(());
+        }
+
+        let rect = config.analysis_rect();
+        let y = rect.top + rect.height() / 2;
+        self.readbacks[frame].copy(
+            ctx,
+            desktop,
+            RECT {
+                left: rect.left,
+                top: y,
+                right: rect.right,
+                bottom: y + 1,
+            },
+        )
+    }
+
+    // valid once the frame that last used the slot has finished on the GPU
+    pub fn read(&mut self, config: &Config, frame: usize) -> Result<()> {
+        if !config.estimate_gamma {
+            self.gamma = None;
+            self.filter.reset();
+            return Ok(());
+        }
+
+        let Some(pixels) = self.readbacks[frame].read()? else {
+            return Ok(());
+        };
+
+        self.gamma = match ramp_gamma(&pixels.rgb) {
+            Some(gamma) => {
+                let [gamma] =
+                    self.filter
+                        .push([gamma], config.sample_frames, config.reject_outliers);
+                Some(gamma)
+            }
+            None => {
+                self.filter.reset();
+                None
+            }
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(gamma: f32, len: usize) -> Vec<[f32; 3]> {
+        (0..len)
+            .map(|i| [(i as f32 / (len - 1) as f32).powf(gamma); 3])
+            .collect()
+    }
+
+    #[test]
+    fn fits_the_gamma_of_a_ramp() {
+        for gamma in [1.8, 2.2, 2.4] {
+            let fit = ramp_gamma(&ramp(gamma, 256)).unwrap();
+            assert!((fit - gamma).abs() < 1e-3, "{fit} != {gamma}");
+        }
+    }
+
+    #[test]
+    fn ignores_rows_that_are_not_ramps() {
+        // too short
+        assert_eq!(ramp_gamma(&ramp(2.2, 8)), None);
+
+        // right to left
+        let mut reversed = ramp(2.2, 256);
+        reversed.reverse();
+        assert_eq!(ramp_gamma(&reversed), None);
+
+        // tinted
+        let tinted: Vec<_> = ramp(2.2, 256)
+            .iter()
+            .map(|&[r, g, b]| [r, 0.8 * g, b])
+            .collect();
+        assert_eq!(ramp_gamma(&tinted), None);
+
+        // flat gray
+        assert_eq!(ramp_gamma(&[[0.5; 3]; 64]), None);
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    config::Config,
+    graphics::{
+        context::Context, core::resource::Resource, renderer::Renderer, spout::SharedTexture,
+    },
+};
+
+use super::still::StillImage;
+
+// a sender may start after colormel or restart, its name is tried again this often
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+// feeds the frames of a spout sender or a named shared texture into StillImage
+pub struct SharedReceiver {
+    texture: Option<SharedTexture>,
+    name: Option<String>,
+    // when the name was last tried without success
+    failed: Option<Instant>,
+    // copies of a previous size, in use until the frames drawing them have run
+    retired: Vec<Resource>,
+}
+
+impl SharedReceiver {
+    pub fn new() -> Self {
+        Self {
+            texture: None,
+            name: None,
+            failed: None,
+            retired: vec![],
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.drop_texture();
+        self.name = None;
+        self.failed = None;
+    }
+
+    // copies the sender's new frames into the still image, true while one is received
+    pub fn update(&mut self, ctx: &Context, config: &Config, still: &mut StillImage) -> bool {
+        // errors are printed once per name, not on every retry
+        let renamed = self.name.as_ref() != Some(&config.shared_name);
+        if renamed {
+            self.close();
+            self.name = Some(config.shared_name.clone());
+        }
+
+        let due = self
+            .failed
+            .is_none_or(|failed| failed.elapsed() >= RETRY_INTERVAL);
+        if self.texture.is_none() && due && !config.shared_name.is_empty() {
+            match SharedTexture::open(ctx, &config.shared_name) {
+                Ok(texture) => {
+                    self.texture = Some(texture);
+                    self.failed = None;
+                }
+                Err(e) => {
+                    if renamed {
+                        println!("shared texture: {e:?}");
+                    }
+                    self.failed = Some(Instant::now());
+                }
+            }
+        }
+
+        let Some(texture) = &mut self.texture else {
+            return false;
+        };
+
+        match texture.receive(ctx) {
+            // the last frame stays up until the sender draws another
+            Ok((resource, retired)) => {
+                if let Some(resource) = resource {
+                    still.set_shared_frame(ctx, resource);
+                }
+                self.retired.extend(retired);
+                true
+            }
+            // the sender closed, it is looked for again
+            Err(e) => {
+                println!("shared texture: {e:?}");
+                self.drop_texture();
+                self.failed = Some(Instant::now());
+                false
+            }
+        }
+    }
+
+    pub fn retire(&mut self, ctx: &mut Renderer) {
+        for resource in self.retired.drain(..) {
+            ctx.retire(resource);
+        }
+    }
+
+    fn drop_texture(&mut self) {
+        if let Some(texture) = self.texture.take() {
+            self.retired.extend(texture.into_bridge());
+        }
+    }
+}
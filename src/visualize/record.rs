@@ -0,0 +1,477 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context as _, Result};
+use windows::{
+    core::{Interface, HSTRING, PCWSTR},
+    Win32::{
+        Foundation::RECT,
+        Graphics::{
+            Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+            Direct3D11::*,
+            Direct3D12::{
+                D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE, D3D12_RESOURCE_STATE_COMMON,
+                D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE, D3D12_RESOURCE_STATE_RENDER_TARGET,
+            },
+            Dxgi::{
+                Common::{
+                    DXGI_FORMAT_B8G8R8A8_TYPELESS, DXGI_FORMAT_B8G8R8A8_UNORM,
+                    DXGI_FORMAT_B8G8R8A8_UNORM_SRGB, DXGI_FORMAT_R8G8B8A8_UNORM,
+                    DXGI_FORMAT_R8G8B8A8_UNORM_SRGB, DXGI_SAMPLE_DESC,
+                },
+                IDXGIResource1,
+            },
+        },
+        Media::MediaFoundation::*,
+    },
+};
+
+use crate::{
+    config::{Config, RECORD_CAPTURE},
+    graphics::{
+        context::Context,
+        core::{
+            descriptor::{Descriptor, RtvHeap},
+            device::Device,
+            pso::PipelineState,
+            resource::Resource,
+            wrap::*,
+        },
+        duplicate::d3d11_device,
+        initializer::Initializer,
+        renderer::{Renderer, ViewportKind},
+        spout::SharedFence,
+    },
+    gui::utils::Rect as _,
+};
+
+use super::{export, video::MediaFoundation};
+
+// media foundation times are in 100ns units
+const TICKS_PER_SECOND: i64 = 10_000_000;
+
+// encoded bits per pixel and frame, scopes are mostly flat areas and thin lines
+const BITS_PER_PIXEL: f32 = 0.15;
+
+// converts recorded frames into the encoder's 8-bit srgb, shared by every recording
+pub struct RecordPass {
+    pso: PipelineState,
+    rtv_heap: RtvHeap,
+    srv: Descriptor,
+}
+
+impl RecordPass {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/RecordVs.bin"),
+            include_bytes!("../shaders/bin/RecordPs.bin"),
+            BlendDesc::none(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_B8G8R8A8_UNORM_SRGB),
+            None,
+            None,
+        )?;
+        let rtv_heap = RtvHeap::new(ctx, 1)?;
+        let srv = ctx.next_descriptor();
+
+        Ok(Self { pso, rtv_heap, srv })
+    }
+}
+
+// records the window's output, or the capture behind it, to an h.264 mp4 until finish(). frames
+// stay on the gpu, d3d12 draws them into a texture shared with the encoder's d3d11 device
+pub struct Recorder {
+    path: PathBuf,
+    encoder: Option<Encoder>,
+    // when the first frame was drawn and when the last one was
+    start: Option<Instant>,
+    drawn: Option<Instant>,
+    frames: u32,
+    // after an error nothing more is written, finish() still ends the file
+    stopped: bool,
+}
+
+impl Recorder {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            path: dir
+                .as_ref()
+                .join(format!("recording-{}.mp4", export::timestamp())),
+            encoder: None,
+            start: None,
+            drawn: None,
+            frames: 0,
+            stopped: false,
+        }
+    }
+
+    // draws this frame for write(). the capture is the desktop texture, None while another
+    // source stands in for it, then the output is recorded. the encoder is opened with the
+    // first frame's size, later sizes are fitted into it
+    pub fn capture(
+        &mut self,
+        ctx: &mut Renderer,
+        pass: &RecordPass,
+        desktop: Option<&Resource>,
+        config: &Config,
+    ) -> Result<()> {
+        if self.stopped {
+            return Ok(());
+        }
+
+        let result = self.draw(ctx, pass, desktop, config);
+        self.stopped = result.is_err();
+        result
+    }
+
+    // valid after capture() and the frame's execution, the encoder's device waits for it on
+    // the gpu
+    pub fn write(&mut self, ctx: &Context) -> Result<()> {
+        if self.stopped {
+            return Ok(());
+        }
+
+        let result = self.encode(ctx);
+        self.stopped = result.is_err();
+        result
+    }
+
+    // writes the end of the file, returns its path and the number of frames in it
+    pub fn finish(self, ctx: &Context) -> Result<(PathBuf, u32)> {
+        let Some(mut encoder) = self.encoder else {
+            anyhow::bail!("nothing was recorded");
+        };
+        // d3d12 may still draw into the shared texture
+        encoder.fence.queue_signal(ctx)?;
+        encoder.fence.wait_idle()?;
+        encoder.writer.finish()?;
+
+        Ok((self.path, self.frames))
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Renderer,
+        pass: &RecordPass,
+        desktop: Option<&Resource>,
+        config: &Config,
+    ) -> Result<()> {
+        // desktop textures are promoted from the common state, the output is a render target
+        let (source, rect, output) = match desktop {
+            Some(desktop) if config.record_source == RECORD_CAPTURE => {
+                (desktop.clone(), config.window_rect, false)
+            }
+            _ => {
+                let (width, height) = ctx.render_target().size();
+                (
+                    ctx.render_target().clone(),
+                    RECT::new(0, 0, width as _, height as _),
+                    true,
+                )
+            }
+        };
+        if rect.width() <= 0 || rect.height() <= 0 {
+            return Ok(());
+        }
+
+        let encoder = match &mut self.encoder {
+            Some(encoder) => encoder,
+            encoder => {
+                std::fs::create_dir_all(self.path.parent().unwrap_or(Path::new(".")))?;
+                let fps = if config.target_fps > 0 {
+                    config.target_fps
+                } else {
+                    60
+                };
+                // h.264 frames have even sizes
+                let (width, height) = (rect.width() as u32 & !1, rect.height() as u32 & !1);
+                encoder.insert(Encoder::create(
+                    ctx.device(),
+                    &self.path,
+                    width.max(2),
+                    height.max(2),
+                    fps,
+                )?)
+            }
+        };
+
+        // 8-bit desktops are decoded as srgb like everywhere else
+        let format = match source.desc().Format {
+            DXGI_FORMAT_B8G8R8A8_UNORM => DXGI_FORMAT_B8G8R8A8_UNORM_SRGB,
+            DXGI_FORMAT_R8G8B8A8_UNORM => DXGI_FORMAT_R8G8B8A8_UNORM_SRGB,
+            format => format,
+        };
+        ctx.device()
+            .create_srv(&source, Some(&SrvDesc::texture2d(format)), pass.srv.cpu);
+        let rtv = pass.rtv_heap.descriptor(0);
+        ctx.device().create_rtv(
+            &encoder.target,
+            Some(&RtvDesc::default(DXGI_FORMAT_B8G8R8A8_UNORM_SRGB)),
+            rtv.cpu,
+        );
+
+        let mut barriers = vec![encoder.target.transition_barrier(
+            D3D12_RESOURCE_STATE_COMMON,
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+        )];
+        if output {
+            barriers.push(source.transition_barrier(
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+                D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE,
+            ));
+        }
+        ctx.resource_barrier(&barriers);
+        ctx.set_render_targets(&[rtv]);
+
+        let (width, height) = (encoder.writer.width, encoder.writer.height);
+        ctx.set_pipeline_state(&pass.pso);
+        ctx.set_viewport(ViewportKind::Target { width, height });
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+        ctx.set_graphics_constants(&[
+            rect.left as f32,
+            rect.top as f32,
+            rect.width() as f32,
+            rect.height() as f32,
+            width as f32,
+            height as f32,
+        ]);
+        ctx.set_graphics_srvs(&[pass.srv]);
+        ctx.draw(3, 1);
+
+        ctx.reset_render_targets();
+        ctx.set_viewport(ViewportKind::Full);
+        // the other device takes the texture in the common state
+        let mut barriers = vec![encoder.target.transition_barrier(
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+            D3D12_RESOURCE_STATE_COMMON,
+        )];
+        if output {
+            barriers.push(source.transition_barrier(
+                D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+            ));
+        }
+        ctx.resource_barrier(&barriers);
+
+        self.drawn = Some(Instant::now());
+        Ok(())
+    }
+
+    fn encode(&mut self, ctx: &Context) -> Result<()> {
+        let (Some(encoder), Some(drawn)) = (&mut self.encoder, self.drawn.take()) else {
+            return Ok(());
+        };
+
+        let start = *self.start.get_or_insert(drawn);
+        let time = (drawn.duration_since(start).as_secs_f64() * TICKS_PER_SECOND as f64) as i64;
+        encoder.encode(ctx, time)?;
+        self.frames += 1;
+
+        Ok(())
+    }
+}
+
+// the d3d11 device media foundation encodes on and the texture d3d12 draws the frames into
+struct Encoder {
+    device: ID3D11Device,
+    context: ID3D11DeviceContext4,
+    shared: ID3D11Texture2D,
+    target: Resource,
+    fence: SharedFence,
+    writer: VideoWriter,
+}
+
+impl Encoder {
+    fn create(device: &Device, path: &Path, width: u32, height: u32, fps: u32) -> Result<Self> {
+        unsafe {
+            let device_d3d11 = d3d11_device(device, true)?;
+            // media foundation uses the device from its own threads
+            device_d3d11
+                .cast::<ID3D11Multithread>()?
+                .SetMultithreadProtected(true);
+            let context = device_d3d11.GetImmediateContext()?.cast()?;
+            let fence = SharedFence::new(&device_d3d11, device)?;
+
+            // typeless, d3d12 draws it through an srgb view and the frames are copied out unorm
+            let desc = D3D11_TEXTURE2D_DESC {
+                Width: width,
+                Height: height,
+                MipLevels: 1,
+                ArraySize: 1,
+                Format: DXGI_FORMAT_B8G8R8A8_TYPELESS,
+                SampleDesc: DXGI_SAMPLE_DESC {
+                    Count: 1,
+                    Quality: 0,
+                },
+                Usage: D3D11_USAGE_DEFAULT,
+                BindFlags: (D3D11_BIND_RENDER_TARGET.0 | D3D11_BIND_SHADER_RESOURCE.0) as u32,
+                CPUAccessFlags: 0,
+                MiscFlags: (D3D11_RESOURCE_MISC_SHARED_NTHANDLE.0 | D3D11_RESOURCE_MISC_SHARED.0)
+                    as u32,
+            };
+            let mut shared = None;
+            device_d3d11.CreateTexture2D(&desc, None, Some(&mut shared))?;
+            let shared = shared.unwrap();
+            let target = Resource::from_dxgi_writable(&shared.cast::<IDXGIResource1>()?, device)?;
+
+            let writer = VideoWriter::create(&device_d3d11, path, width, height, fps)?;
+
+            Ok(Self {
+                device: device_d3d11,
+                context,
+                shared,
+                target,
+                fence,
+                writer,
+            })
+        }
+    }
+
+    // the frame drawn last is copied into a texture of its own, the encoder may hold on to a
+    // few of them while the shared one is drawn into again
+    fn encode(&mut self, ctx: &Context, time: i64) -> Result<()> {
+        unsafe {
+            let mut desc = D3D11_TEXTURE2D_DESC::default();
+            self.shared.GetDesc(&mut desc);
+            let desc = D3D11_TEXTURE2D_DESC {
+                Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                MiscFlags: 0,
+                ..desc
+            };
+            let mut frame = None;
+            self.device.CreateTexture2D(&desc, None, Some(&mut frame))?;
+            let frame = frame.unwrap();
+
+            let drawn = self.fence.queue_signal(ctx)?;
+            self.fence.wait(&self.context, drawn)?;
+            self.context.CopyResource(&frame, &self.shared);
+            let copied = self.fence.signal(&self.context)?;
+            self.context.Flush();
+            // the next frame draws into the shared texture after the copy
+            self.fence.queue_wait(ctx, copied)?;
+
+            self.writer.write(time, &frame)
+        }
+    }
+}
+
+struct VideoWriter {
+    writer: IMFSinkWriter,
+    stream: u32,
+    width: u32,
+    height: u32,
+    // the last frame's time, each frame lasts until the next one
+    last: Option<(i64, IMFSample)>,
+    _device_manager: IMFDXGIDeviceManager,
+    // last, so the writer is released before media foundation shuts down
+    _media_foundation: MediaFoundation,
+}
+
+// recordings start after the pipeline has moved to the visualizer thread, the sink writer is
+// created with com initialized multithreaded there and is only ever called from that thread
+unsafe impl Send for VideoWriter {}
+
+impl VideoWriter {
+    // bgra textures are converted to the encoder's yuv on the gpu by the sink writer
+    fn create(
+        device: &ID3D11Device,
+        path: &Path,
+        width: u32,
+        height: u32,
+        fps: u32,
+    ) -> Result<Self> {
+        unsafe {
+            let media_foundation = MediaFoundation::startup()?;
+
+            let mut token = 0;
+            let mut device_manager = None;
+            MFCreateDXGIDeviceManager(&mut token, &mut device_manager)?;
+            let device_manager = device_manager.unwrap();
+            device_manager.ResetDevice(device, token)?;
+
+            let mut attributes = None;
+            MFCreateAttributes(&mut attributes, 2)?;
+            let attributes = attributes.unwrap();
+            attributes.SetUnknown(&MF_SINK_WRITER_D3D_MANAGER, &device_manager)?;
+            attributes.SetUINT32(&MF_READWRITE_ENABLE_HARDWARE_TRANSFORMS, 1)?;
+
+            let url = HSTRING::from(path.as_os_str());
+            let writer = MFCreateSinkWriterFromURL(PCWSTR(url.as_ptr()), None, &attributes)?;
+
+            let set_video = |media_type: &IMFMediaType| -> Result<()> {
+                media_type.SetGUID(&MF_MT_MAJOR_TYPE, &MFMediaType_Video)?;
+                media_type
+                    .SetUINT32(&MF_MT_INTERLACE_MODE, MFVideoInterlace_Progressive.0 as u32)?;
+                media_type.SetUINT64(&MF_MT_FRAME_SIZE, (width as u64) << 32 | height as u64)?;
+                media_type.SetUINT64(&MF_MT_FRAME_RATE, (fps as u64) << 32 | 1)?;
+                media_type.SetUINT64(&MF_MT_PIXEL_ASPECT_RATIO, 1 << 32 | 1)?;
+                Ok(())
+            };
+
+            let output = MFCreateMediaType()?;
+            set_video(&output)?;
+            output.SetGUID(&MF_MT_SUBTYPE, &MFVideoFormat_H264)?;
+            let bitrate = BITS_PER_PIXEL * (width * height * fps) as f32;
+            output.SetUINT32(&MF_MT_AVG_BITRATE, bitrate as u32)?;
+            let stream = writer.AddStream(&output)?;
+
+            // textures are top-down
+            let input = MFCreateMediaType()?;
+            set_video(&input)?;
+            input.SetGUID(&MF_MT_SUBTYPE, &MFVideoFormat_RGB32)?;
+            input.SetUINT32(&MF_MT_DEFAULT_STRIDE, 4 * width)?;
+            writer
+                .SetInputMediaType(stream, &input, None)
+                .context("the encoder doesn't take this size")?;
+
+            writer.BeginWriting()?;
+
+            Ok(Self {
+                writer,
+                stream,
+                width,
+                height,
+                last: None,
+                _device_manager: device_manager,
+                _media_foundation: media_foundation,
+            })
+        }
+    }
+
+    // frames are held back one, until the next one's time gives their duration
+    fn write(&mut self, time: i64, frame: &ID3D11Texture2D) -> Result<()> {
+        unsafe {
+            let buffer = MFCreateDXGISurfaceBuffer(&ID3D11Texture2D::IID, frame, 0, false)?;
+            let length = buffer.cast::<IMF2DBuffer>()?.GetContiguousLength()?;
+            buffer.SetCurrentLength(length)?;
+
+            let sample = MFCreateSample()?;
+            sample.AddBuffer(&buffer)?;
+            sample.SetSampleTime(time)?;
+
+            if let Some((last, sample)) = self.last.replace((time, sample)) {
+                sample.SetSampleDuration(time - last)?;
+                self.writer.WriteSample(self.stream, &sample)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        unsafe {
+            // the last frame has no next one to time it, the sink ends the file with it
+            if let Some((_, sample)) = self.last.take() {
+                self.writer.WriteSample(self.stream, &sample)?;
+            }
+            self.writer.Finalize()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use windows::Win32::Foundation::RECT;
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::resource::Resource,
+        renderer::Renderer,
+        resource::{Pixels, TextureReadback},
+    },
+    gui::utils::Rect as _,
+};
+
+use super::{
+    export,
+    histogram::HistogramData,
+    stats::{FrameStats, PERCENTILE_RANKS},
+};
+
+// writes a screenshot, stats json and histogram csv into one session folder every interval
+pub struct Reporter {
+    dir: PathBuf,
+    last: Option<Instant>,
+    screenshot: TextureReadback,
+}
+
+impl Reporter {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir
+                .as_ref()
+                .join(format!("session-{}", export::timestamp())),
+            last: None,
+            screenshot: TextureReadback::default(),
+        }
+    }
+
+    // true once per interval, the first report is taken right away
+    pub fn due(&mut self, config: &Config) -> bool {
+        let interval = Duration::from_secs(60 * config.report_interval as u64);
+
+        if self.last.is_some_and(|last| last.elapsed() < interval) {
+            return false;
+        }
+
+        self.last = Some(Instant::now());
+        true
+    }
+
+    // records a copy of the rect (desktop coordinates) for the next write()
+    pub fn capture(&mut self, ctx: &mut Renderer, desktop: &Resource, rect: RECT) -> Result<()> {
+        self.screenshot.copy(ctx, desktop, rect)
+    }
+
+    // valid after the frame recorded by capture() has finished on the GPU, returns the stats path
+    pub fn write(
+        &mut self,
+        config: &Config,
+        histogram: Option<&HistogramData>,
+        frame: Option<&FrameStats>,
+    ) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let stamp = export::timestamp();
+
+        if let Some(pixels) = self.screenshot.read()? {
+            let path = self.dir.join(format!("{stamp}-screen.png"));
+            write_screenshot(&path, &pixels)?;
+        }
+
+        if let Some(histogram) = histogram {
+            let path = self.dir.join(format!("{stamp}-histogram.csv"));
+            export::write_csv(&path, histogram)?;
+        }
+
+        let path = self.dir.join(format!("{stamp}-stats.json"));
+        write_stats(&path, &stamp, config, histogram, frame)?;
+
+        Ok(path)
+    }
+}
+
+// scRGB captures are clipped to sdr white
+fn write_screenshot(path: &Path, pixels: &Pixels) -> Result<()> {
+    export::save_png(path, pixels.width, pixels.height, &export::bgra(pixels))
+}
+
+fn write_stats(
+    path: &Path,
+    stamp: &str,
+    config: &Config,
+    histogram: Option<&HistogramData>,
+    frame: Option<&FrameStats>,
+) -> Result<()> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    let rect = |rect: RECT| {
+        format!(
+            "{{ \"x\": {}, \"y\": {}, \"width\": {}, \"height\": {} }}",
+            rect.left,
+            rect.top,
+            rect.width(),
+            rect.height()
+        )
+    };
+
+    writeln!(file, "{{")?;
+    writeln!(file, "  \"time\": \"{stamp}\",")?;
+    writeln!(file, "  \"window\": {},", rect(config.window_rect))?;
+    writeln!(file, "  \"region\": {},", rect(config.analysis_rect()))?;
+    writeln!(file, "  \"hdr\": {},", config.hdr_mode)?;
+
+    if let Some(frame) = frame {
+        let values = |values: &[f32; 4]| {
+            let [r, g, b, l] = values;
+            format!("{{ \"r\": {r}, \"g\": {g}, \"b\": {b}, \"l\": {l} }}")
+        };
+        let percentiles: Vec<_> = PERCENTILE_RANKS
+            .iter()
+            .zip(frame.percentiles)
+            .map(|(rank, value)| format!("\"p{:.0}\": {value}", 100.0 * rank))
+            .collect();
+
+        writeln!(file, "  \"frame-min\": {},", values(&frame.min))?;
+        writeln!(file, "  \"frame-max\": {},", values(&frame.max))?;
+        writeln!(file, "  \"frame-mean\": {},", values(&frame.mean))?;
+        writeln!(
+            file,
+            "  \"luma-percentiles\": {{ {} }},",
+            percentiles.join(", ")
+        )?;
+    }
+
+    if let Some(histogram) = histogram {
+        let means = |bins: &[Vec<u32>; 4]| {
+            export::channels_of(histogram.mode, bins, histogram.colors)
+                .iter()
+                .map(|channel| {
+                    format!(
+                        "\"{}\": {}",
+                        channel.name,
+                        HistogramData::mean(channel.bins)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        writeln!(file, "  \"histogram-mode\": {},", histogram.mode)?;
+        writeln!(file, "  \"histogram-bins\": {},", histogram.bins[0].len())?;
+        if let Some(full) = &histogram.full {
+            writeln!(file, "  \"full-mean\": {{ {} }},", means(full))?;
+        }
+        writeln!(file, "  \"max-cll\": {},", histogram.max_cll())?;
+        writeln!(file, "  \"mean\": {{ {} }}", means(&histogram.bins))?;
+    } else {
+        writeln!(file, "  \"mean\": null")?;
+    }
+
+    writeln!(file, "}}")?;
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use windows::{
+    core::s,
+    Win32::Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_LINELIST,
+        Direct3D12::{D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE, D3D12_RASTERIZER_DESC},
+        Dxgi::Common::{DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32G32B32_FLOAT},
+    },
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        math::Matrix,
+        renderer::{Renderer, ViewportKind},
+        resource::DynamicVertexBuffer,
+    },
+    gui::utils::Rect as _,
+};
+
+pub struct RoiOutline {
+    pso: PipelineState,
+    outline: DynamicVertexBuffer,
+}
+
+impl RoiOutline {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/PrimitiveVs.bin"),
+            include_bytes!("../shaders/bin/PrimitivePs.bin"),
+            BlendDesc::none(),
+            D3D12_RASTERIZER_DESC {
+                AntialiasedLineEnable: true.into(),
+                ..RasterizerDesc::none()
+            },
+            DepthStencilDesc::none(),
+            &[
+                InputElementDesc::per_vertex(s!("POSITION"), DXGI_FORMAT_R32G32B32_FLOAT),
+                InputElementDesc::per_vertex(s!("COLOR"), DXGI_FORMAT_R32G32B32_FLOAT),
+            ],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let outline = DynamicVertexBuffer::new::<Vertex>(ctx, 8)?;
+
+        Ok(Self { pso, outline })
+    }
+
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_roi {
+            self.draw(ctx, config)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        ctx.set_pipeline_state(&self.pso);
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+
+        self.outline.write(&outline(config, [1.0, 0.8, 0.0]))?;
+        ctx.set_vertex_buffers(&[self.outline.view()]);
+
+        #[repr(C)]
+        struct Params {
+            projection: [f32; 16],
+        }
+
+        let params = Params {
+            projection: Matrix::identity().as_4x4(),
+        };
+
+        ctx.set_graphics_constants(&params);
+        ctx.draw(self.outline.vertex_count(), 1);
+
+        Ok(())
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+// edges through the outermost pixel centers of the roi, in ndc of the full viewport
+fn outline(config: &Config, color: [f32; 3]) -> Vec<Vertex> {
+    let (width, height) = config.window_rect.size();
+    let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+
+    let rect = config.analysis_rect();
+    let left = (rect.left - config.window_rect.left) as f32 + 0.5;
+    let top = (rect.top - config.window_rect.top) as f32 + 0.5;
+    let (roi_width, roi_height) = rect.size();
+    let right = left + (roi_width - 1).max(0) as f32;
+    let bottom = top + (roi_height - 1).max(0) as f32;
+
+    let corners = [[left, top], [right, top], [right, bottom], [left, bottom]]
+        .map(|[x, y]| [2.0 * x / width - 1.0, 1.0 - 2.0 * y / height]);
+
+    (0..4)
+        .flat_map(|i| [corners[i], corners[(i + 1) % 4]])
+        .map(|[x, y]| Vertex {
+            position: [x, y, 0.5],
+            color,
+        })
+        .collect()
+}
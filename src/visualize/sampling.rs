@@ -0,0 +1,116 @@
+use std::collections::VecDeque;
+
+// averages readouts over the last frames, dropping samples far from the median
+pub struct SampleFilter<const N: usize> {
+    samples: VecDeque<[f32; N]>,
+}
+
+impl<const N: usize> SampleFilter<N> {
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, sample: [f32; N], frames: u32, reject_outliers: bool) -> [f32; N] {
+        let frames = frames.max(1) as usize;
+        while self.samples.len() >= frames {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+
+        let mut average = [0.0; N];
+        let mut values = Vec::with_capacity(self.samples.len());
+        for (ch, average) in average.iter_mut().enumerate() {
+            values.clear();
+            values.extend(self.samples.iter().map(|sample| sample[ch]));
+
+            *average = if reject_outliers {
+                robust_mean(&values)
+            } else {
+                values.iter().sum::<f32>() / values.len() as f32
+            };
+        }
+
+        average
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+}
+
+fn median(values: &mut [f32]) -> f32 {
+    values.sort_by(f32::total_cmp);
+
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        0.5 * (values[mid - 1] + values[mid])
+    } else {
+        values[mid]
+    }
+}
+
+// mean of the samples within 3 sigma of the median, sigma estimated from the median absolute deviation
+fn robust_mean(values: &[f32]) -> f32 {
+    let center = median(&mut values.to_vec());
+    let mut deviations: Vec<_> = values.iter().map(|v| (v - center).abs()).collect();
+    let limit = 3.0 * 1.4826 * median(&mut deviations);
+
+    let (sum, count) = values
+        .iter()
+        .filter(|v| (*v - center).abs() <= limit)
+        .fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+
+    sum / count as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_frame_passes_through() {
+        let mut filter = SampleFilter::<2>::new();
+
+        assert_eq!(filter.push([1.0, 2.0], 1, true), [1.0, 2.0]);
+        assert_eq!(filter.push([3.0, 4.0], 1, true), [3.0, 4.0]);
+    }
+
+    #[test]
+    fn averages_over_window() {
+        let mut filter = SampleFilter::<1>::new();
+
+        for v in [10.0, 0.0, 1.0, 2.0] {
+            filter.push([v], 3, false);
+        }
+        assert_eq!(filter.push([3.0], 3, false), [2.0]);
+    }
+
+    #[test]
+    fn rejects_outliers() {
+        let mut filter = SampleFilter::<1>::new();
+
+        let mut average = [0.0];
+        for v in [0.50, 0.51, 0.49, 0.50, 0.95, 0.51, 0.49] {
+            average = filter.push([v], 8, true);
+        }
+        assert!((average[0] - 0.5).abs() < 1e-3, "{average:?}");
+
+        filter.reset();
+        for v in [0.50, 0.51, 0.49, 0.50, 0.95, 0.51, 0.49] {
+            average = filter.push([v], 8, false);
+        }
+        assert!(average[0] > 0.55);
+    }
+
+    #[test]
+    fn keeps_constant_signal() {
+        let mut filter = SampleFilter::<1>::new();
+
+        for _ in 0..4 {
+            filter.push([0.25], 4, true);
+        }
+        assert_eq!(filter.push([0.25], 4, true), [0.25]);
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use windows::Win32::{Foundation::RECT, Graphics::Dxgi::Common::DXGI_FORMAT_R16G16B16A16_FLOAT};
+
+use crate::{
+    graphics::{core::resource::Resource, renderer::Renderer, resource::TextureReadback},
+    gui::utils::Rect as _,
+};
+
+use super::export;
+
+// saves the duplicated frame as captured, and the window's output as drawn, each on its own request
+#[derive(Default)]
+pub struct Screenshots {
+    capture: TextureReadback,
+    output: TextureReadback,
+}
+
+impl Screenshots {
+    // the whole output, without the conversion and grading the passes apply
+    pub fn copy_capture(&mut self, ctx: &mut Renderer, desktop: &Resource) -> Result<()> {
+        let (width, height) = desktop.size();
+        self.capture
+            .copy(ctx, desktop, RECT::new(0, 0, width as _, height as _))
+    }
+
+    // everything drawn into the window, after the last pass
+    pub fn copy_output(&mut self, ctx: &mut Renderer) -> Result<()> {
+        let (width, height) = ctx.render_target().size();
+        self.output
+            .copy_render_target(ctx, RECT::new(0, 0, width as _, height as _))
+    }
+
+    // valid after the frame recorded by the copies has finished on the GPU, returns the paths
+    // written. hdr captures are kept as half floats in a .jxr, sdr ones as a .png
+    pub fn write(&mut self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let mut paths = vec![];
+        let stem = |kind: &str| dir.as_ref().join(format!("{kind}-{}", export::timestamp()));
+
+        if let Some(raw) = self.capture.read_raw()? {
+            std::fs::create_dir_all(&dir)?;
+            let path = if raw.format == DXGI_FORMAT_R16G16B16A16_FLOAT {
+                let path = stem("capture").with_extension("jxr");
+                export::save_jxr(&path, raw.width, raw.height, &raw.data)?;
+                path
+            } else {
+                let path = stem("capture").with_extension("png");
+                export::save_png(&path, raw.width, raw.height, &raw.data)?;
+                path
+            };
+            paths.push(path);
+        }
+
+        if let Some(pixels) = self.output.read()? {
+            std::fs::create_dir_all(&dir)?;
+            let path = stem("output").with_extension("png");
+            export::save_png(&path, pixels.width, pixels.height, &export::bgra(&pixels))?;
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+}
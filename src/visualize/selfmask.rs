@@ -0,0 +1,95 @@
+use anyhow::Result;
+use windows::Win32::Graphics::{
+    Direct3D12::{
+        D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE, D3D12_RESOURCE_STATE_COPY_DEST,
+        D3D12_RESOURCE_STATE_COPY_SOURCE, D3D12_RESOURCE_STATE_RENDER_TARGET,
+    },
+    Dxgi::Common::DXGI_FORMAT_R16G16B16A16_FLOAT,
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{descriptor::Descriptor, wrap::SrvDesc},
+        initializer::Initializer,
+        renderer::Renderer,
+        resource::Texture2D,
+    },
+    gui::utils::Rect as _,
+};
+
+pub struct SelfMask {
+    overlay: Texture2D,
+    null_srv: Descriptor,
+    valid: bool,
+}
+
+impl SelfMask {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let overlay = Texture2D::new(ctx, 1, 1, DXGI_FORMAT_R16G16B16A16_FLOAT)?;
+
+        let null_srv = ctx.next_descriptor();
+        ctx.create_null_srv(
+            Some(&SrvDesc::texture2d(DXGI_FORMAT_R16G16B16A16_FLOAT)),
+            null_srv.cpu,
+        );
+
+        Ok(Self {
+            overlay,
+            null_srv,
+            valid: false,
+        })
+    }
+
+    pub fn prepare(&mut self, ctx: &mut Renderer, config: &Config) -> Result<Descriptor> {
+        let (width, height) = config.window_rect.size();
+        let size = (width as u32, height as u32);
+
+        if config.exclude_from_capture {
+            self.valid = false;
+        } else if self.overlay.size() != size {
+            self.overlay.resize(ctx.device(), size.0, size.1)?;
+            self.valid = false;
+        }
+
+        Ok(if self.valid {
+            self.overlay.srv
+        } else {
+            self.null_srv
+        })
+    }
+
+    pub fn capture(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.exclude_from_capture || ctx.render_target().size() != self.overlay.size() {
+            return Ok(());
+        }
+
+        ctx.resource_barrier(&[
+            ctx.render_target().transition_barrier(
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+                D3D12_RESOURCE_STATE_COPY_SOURCE,
+            ),
+            self.overlay.transition_barrier(
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_COPY_DEST,
+            ),
+        ]);
+
+        ctx.copy_resource(&self.overlay, ctx.render_target());
+
+        ctx.resource_barrier(&[
+            ctx.render_target().transition_barrier(
+                D3D12_RESOURCE_STATE_COPY_SOURCE,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+            ),
+            self.overlay.transition_barrier(
+                D3D12_RESOURCE_STATE_COPY_DEST,
+                D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            ),
+        ]);
+
+        self.valid = true;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::resource::Resource,
+        math::linear_to_srgb8,
+        renderer::Renderer,
+        resource::{Pixels, TextureReadback},
+    },
+};
+
+use super::{
+    export,
+    stats::{FrameStats, PERCENTILE_RANKS},
+};
+
+// thumbnails are shrunk to fit this width
+const THUMBNAIL_WIDTH: u32 = 320;
+
+// the first reduced frame of a second stands for it
+struct Sample {
+    second: u64,
+    stats: FrameStats,
+}
+
+struct Bookmark {
+    at: Duration,
+    // local time
+    time: String,
+    // file name next to the timeline
+    thumbnail: Option<String>,
+}
+
+#[derive(Default)]
+struct Timeline {
+    samples: Vec<Sample>,
+    bookmarks: Vec<Bookmark>,
+}
+
+impl Timeline {
+    fn wants(&self, second: u64) -> bool {
+        self.samples.last().is_none_or(|last| last.second < second)
+    }
+
+    fn record(&mut self, second: u64, stats: &FrameStats) {
+        if self.wants(second) {
+            self.samples.push(Sample {
+                second,
+                stats: stats.clone(),
+            });
+        }
+    }
+
+    // the bookmark numbers, from 1, that fall into the second
+    fn bookmarks_in(&self, second: u64) -> Vec<String> {
+        self.bookmarks
+            .iter()
+            .enumerate()
+            .filter(|(_, bookmark)| bookmark.at.as_secs() == second)
+            .map(|(i, _)| (i + 1).to_string())
+            .collect()
+    }
+
+    fn write_csv(&self, out: &mut impl Write) -> Result<()> {
+        let percentiles: Vec<_> = PERCENTILE_RANKS
+            .iter()
+            .map(|rank| format!("p{:.0}", 100.0 * rank))
+            .collect();
+        writeln!(
+            out,
+            "second,mean_r,mean_g,mean_b,mean_l,min_l,max_l,{},bookmarks",
+            percentiles.join(",")
+        )?;
+
+        for Sample { second, stats } in &self.samples {
+            let values: Vec<_> = stats
+                .mean
+                .iter()
+                .chain([&stats.min[3], &stats.max[3]])
+                .chain(&stats.percentiles)
+                .map(|value| format!("{value:.4}"))
+                .collect();
+            writeln!(
+                out,
+                "{second},{},{}",
+                values.join(","),
+                self.bookmarks_in(*second).join(" ")
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_json(&self, out: &mut impl Write, started: &str, hdr: bool) -> Result<()> {
+        writeln!(out, "{{")?;
+        writeln!(out, "  \"started\": \"{started}\",")?;
+        writeln!(out, "  \"hdr\": {hdr},")?;
+
+        writeln!(out, "  \"seconds\": [")?;
+        for (i, Sample { second, stats }) in self.samples.iter().enumerate() {
+            let [r, g, b, l] = stats.mean;
+            let percentiles: Vec<_> = PERCENTILE_RANKS
+                .iter()
+                .zip(stats.percentiles)
+                .map(|(rank, value)| format!("\"p{:.0}\": {value}", 100.0 * rank))
+                .collect();
+            let comma = if i + 1 < self.samples.len() { "," } else { "" };
+            writeln!(
+                out,
+                "    {{ \"second\": {second}, \"mean\": {{ \"r\": {r}, \"g\": {g}, \"b\": {b}, \"l\": {l} }}, \"min-l\": {}, \"max-l\": {}, {} }}{comma}",
+                stats.min[3],
+                stats.max[3],
+                percentiles.join(", ")
+            )?;
+        }
+        writeln!(out, "  ],")?;
+
+        writeln!(out, "  \"bookmarks\": [")?;
+        for (i, bookmark) in self.bookmarks.iter().enumerate() {
+            let thumbnail = bookmark
+                .thumbnail
+                .as_ref()
+                .map_or("null".to_string(), |name| format!("\"{name}\""));
+            let comma = if i + 1 < self.bookmarks.len() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(
+                out,
+                "    {{ \"number\": {}, \"second\": {:.3}, \"time\": \"{}\", \"thumbnail\": {thumbnail} }}{comma}",
+                i + 1,
+                bookmark.at.as_secs_f64(),
+                bookmark.time
+            )?;
+        }
+        writeln!(out, "  ]")?;
+
+        writeln!(out, "}}")?;
+
+        Ok(())
+    }
+}
+
+// records the region's stats once a second and bookmarks on request, then writes timeline.csv,
+// timeline.json and the bookmark thumbnails into one folder for reporting. whatever is unsaved is
+// written when the session ends, also when the app closes
+pub struct Session {
+    dir: PathBuf,
+    start: Instant,
+    started: String,
+    hdr: bool,
+    timeline: Timeline,
+    thumbnail: TextureReadback,
+    // the bookmark whose thumbnail was copied this frame
+    capturing: Option<usize>,
+    unsaved: bool,
+}
+
+impl Session {
+    pub fn new(dir: impl AsRef<Path>, config: &Config) -> Self {
+        let started = export::timestamp();
+        Self {
+            dir: dir.as_ref().join(format!("timeline-{started}")),
+            start: Instant::now(),
+            started,
+            hdr: config.hdr_mode,
+            timeline: Timeline::default(),
+            thumbnail: TextureReadback::default(),
+            capturing: None,
+            unsaved: false,
+        }
+    }
+
+    fn second(&self) -> u64 {
+        self.start.elapsed().as_secs()
+    }
+
+    // the frame stats reduction is needed once a second
+    pub fn wants_stats(&self) -> bool {
+        self.timeline.wants(self.second())
+    }
+
+    // records a copy of the rect (desktop coordinates) for the thumbnail when enabled
+    pub fn bookmark(
+        &mut self,
+        ctx: &mut Renderer,
+        desktop: Option<&Resource>,
+        config: &Config,
+    ) -> Result<()> {
+        let at = self.start.elapsed();
+        println!(
+            "bookmark {} at {:.1} s",
+            self.timeline.bookmarks.len() + 1,
+            at.as_secs_f64()
+        );
+
+        self.timeline.bookmarks.push(Bookmark {
+            at,
+            time: export::timestamp(),
+            thumbnail: None,
+        });
+        self.unsaved = true;
+
+        if let (true, Some(desktop)) = (config.session_thumbnails, desktop) {
+            self.thumbnail.copy(ctx, desktop, config.analysis_rect())?;
+            self.capturing = Some(self.timeline.bookmarks.len() - 1);
+        }
+
+        Ok(())
+    }
+
+    // valid after the frame recorded by bookmark() has finished on the GPU
+    pub fn read(&mut self, frame: Option<&FrameStats>) -> Result<()> {
+        if let Some(frame) = frame.filter(|_| self.wants_stats()) {
+            self.timeline.record(self.second(), frame);
+            self.unsaved = true;
+        }
+
+        let Some(index) = self.capturing.take() else {
+            return Ok(());
+        };
+        if let Some(pixels) = self.thumbnail.read()? {
+            std::fs::create_dir_all(&self.dir)?;
+            let name = format!("bookmark-{}.png", index + 1);
+            let (width, height, bgra) = thumbnail(&pixels);
+            export::save_png(&self.dir.join(&name), width, height, &bgra)?;
+            self.timeline.bookmarks[index].thumbnail = Some(name);
+        }
+
+        Ok(())
+    }
+
+    // writes the timeline so far, returns the json path
+    pub fn write(&mut self) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let mut csv =
+            std::io::BufWriter::new(std::fs::File::create(self.dir.join("timeline.csv"))?);
+        self.timeline.write_csv(&mut csv)?;
+        csv.flush()?;
+
+        let path = self.dir.join("timeline.json");
+        let mut json = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        self.timeline
+            .write_json(&mut json, &self.started, self.hdr)?;
+        json.flush()?;
+
+        self.unsaved = false;
+        Ok(path)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if self.unsaved {
+            match self.write() {
+                Ok(path) => println!("session {}", path.display()),
+                Err(e) => println!("{e:?}"),
+            }
+        }
+    }
+}
+
+// nearest neighbour down to THUMBNAIL_WIDTH, bgra for save_png. scRGB is clipped to sdr white
+fn thumbnail(pixels: &Pixels) -> (u32, u32, Vec<u8>) {
+    let step = pixels.width.div_ceil(THUMBNAIL_WIDTH).max(1);
+    let (width, height) = (pixels.width.div_ceil(step), pixels.height.div_ceil(step));
+
+    let bgra = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x * step, y * step)))
+        .flat_map(|(x, y)| {
+            let [r, g, b] = pixels.rgb[(y * pixels.width + x) as usize];
+            [
+                linear_to_srgb8(b),
+                linear_to_srgb8(g),
+                linear_to_srgb8(r),
+                255,
+            ]
+        })
+        .collect();
+
+    (width, height, bgra)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(mean: f32) -> FrameStats {
+        FrameStats {
+            count: 1,
+            min: [0.0; 4],
+            max: [1.0; 4],
+            mean: [mean; 4],
+            percentiles: [0.0, 0.25, 0.5, 0.75, 1.0],
+            over: 0,
+            under: 0,
+        }
+    }
+
+    #[test]
+    fn keeps_one_sample_a_second() {
+        let mut timeline = Timeline::default();
+        timeline.record(0, &stats(0.1));
+        timeline.record(0, &stats(0.2));
+        assert!(!timeline.wants(0));
+        assert!(timeline.wants(2));
+        timeline.record(2, &stats(0.3));
+
+        let means: Vec<_> = timeline.samples.iter().map(|s| s.stats.mean[3]).collect();
+        assert_eq!(means, [0.1, 0.3]);
+    }
+
+    #[test]
+    fn csv_lists_bookmarks_by_second() {
+        let mut timeline = Timeline::default();
+        timeline.record(0, &stats(0.5));
+        timeline.record(1, &stats(0.25));
+        for ms in [1200, 1900] {
+            timeline.bookmarks.push(Bookmark {
+                at: Duration::from_millis(ms),
+                time: String::new(),
+                thumbnail: None,
+            });
+        }
+
+        let mut csv = vec![];
+        timeline.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "second,mean_r,mean_g,mean_b,mean_l,min_l,max_l,p1,p5,p50,p95,p99,bookmarks"
+        );
+        assert!(lines[1].starts_with("0,0.5000,") && lines[1].ends_with(','));
+        assert!(lines[2].starts_with("1,0.2500,") && lines[2].ends_with(",1 2"));
+    }
+
+    #[test]
+    fn thumbnails_fit_the_width() {
+        let pixels = Pixels {
+            width: 1000,
+            height: 500,
+            rgb: vec![[1.0, 0.0, 0.0]; 1000 * 500],
+        };
+
+        let (width, height, bgra) = thumbnail(&pixels);
+        assert_eq!((width, height), (250, 125));
+        assert_eq!(bgra.len(), 4 * 250 * 125);
+        assert_eq!(bgra[..4], [0, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,205 @@
+use std::sync::atomic::{fence, AtomicU32, Ordering};
+
+use anyhow::{bail, Result};
+use windows::{
+    core::HSTRING,
+    Win32::{
+        Foundation::{
+            CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HANDLE, INVALID_HANDLE_VALUE,
+        },
+        System::Memory::{
+            CreateFileMappingW, MapViewOfFile, UnmapViewOfFile, FILE_MAP_WRITE,
+            MEMORY_MAPPED_VIEW_ADDRESS, PAGE_READWRITE,
+        },
+    },
+};
+
+use crate::{config::Config, gui::utils::Rect as _};
+
+use super::{
+    ddc::{MonitorControl, Setting},
+    histogram::{HistogramData, MAX_BINS},
+    outputs::OutputComparison,
+    sampling::SampleFilter,
+};
+
+// the instance analyzing monitor n publishes to Local\colormel-stats-n. readers open it with
+// OpenFileMappingW and FILE_MAP_READ, a reader that creates it keeps colormel from publishing
+const MAPPING_NAME: &str = "Local\\colormel-stats";
+const MAGIC: u32 = u32::from_le_bytes(*b"CMST");
+const VERSION: u32 = 6;
+
+// the layout readers see, repr(C) little endian with 4 byte u32 and f32 fields and no padding.
+// magic is "CMST" and version changes whenever the layout does. sequence is odd while a frame is
+// written, readers copy the block between two loads of it and retry while it is odd or changed
+// during the read. histogram holds MAX_BINS (1024) bins per channel
+#[repr(C)]
+struct Block {
+    magic: u32,
+    version: u32,
+    sequence: AtomicU32,
+    frame: u32,
+    width: u32,
+    height: u32,
+    histogram_mode: u32,
+    histogram_channels: u32,
+    // valid entries at the start of each histogram channel
+    histogram_bins: u32,
+    mean: [f32; 4],
+    histogram: [[u32; MAX_BINS]; 4],
+    max_cll: f32,
+    // full window stats next to the roi ones, valid when compared != 0
+    compared: u32,
+    full_mean: [f32; 4],
+    mean_delta: [f32; 4],
+    // ddc/ci readings as min, current, max, valid when the has_ flag != 0
+    has_brightness: u32,
+    brightness: [u32; 3],
+    has_contrast: u32,
+    contrast: [u32; 3],
+    // same region on two outputs, linear rgb, valid when outputs_compared != 0
+    outputs_compared: u32,
+    output_mean: [[f32; 3]; 2],
+    output_delta: [f32; 3],
+    output_delta_e: f32,
+}
+
+pub struct SharedStats {
+    mapping: HANDLE,
+    view: MEMORY_MAPPED_VIEW_ADDRESS,
+    frame: u32,
+    mean: SampleFilter<4>,
+    full_mean: SampleFilter<4>,
+    max_cll: SampleFilter<1>,
+}
+
+// the mapping is only touched by the visualizer thread
+unsafe impl Send for SharedStats {}
+
+impl SharedStats {
+    // one writer per block, a second instance on the same monitor is turned away
+    pub fn new(monitor: u32) -> Result<Self> {
+        unsafe {
+            let size = std::mem::size_of::<Block>();
+
+            let mapping = CreateFileMappingW(
+                INVALID_HANDLE_VALUE,
+                None,
+                PAGE_READWRITE,
+                0,
+                size as _,
+                &HSTRING::from(format!("{MAPPING_NAME}-{monitor}")),
+            )?;
+            if GetLastError() == ERROR_ALREADY_EXISTS {
+                _ = CloseHandle(mapping);
+                bail!("stats for monitor {monitor} are already shared by another instance");
+            }
+
+            let view = MapViewOfFile(mapping, FILE_MAP_WRITE, 0, 0, size);
+            if view.Value.is_null() {
+                let e = windows::core::Error::from_win32();
+                _ = CloseHandle(mapping);
+                anyhow::bail!(e)
+            }
+
+            let block = &mut *(view.Value as *mut Block);
+            block.magic = MAGIC;
+            block.version = VERSION;
+
+            Ok(Self {
+                mapping,
+                view,
+                frame: 0,
+                mean: SampleFilter::new(),
+                full_mean: SampleFilter::new(),
+                max_cll: SampleFilter::new(),
+            })
+        }
+    }
+
+    pub fn publish(
+        &mut self,
+        config: &Config,
+        histogram: Option<&HistogramData>,
+        monitor: Option<&MonitorControl>,
+        outputs: Option<&OutputComparison>,
+    ) {
+        self.frame = self.frame.wrapping_add(1);
+
+        let block = unsafe { &mut *(self.view.Value as *mut Block) };
+
+        let sequence = block.sequence.load(Ordering::Relaxed);
+        block
+            .sequence
+            .store(sequence.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        let (width, height) = config.window_rect.size();
+        block.frame = self.frame;
+        block.width = width as _;
+        block.height = height as _;
+
+        if let Some(histogram) = histogram {
+            block.histogram_mode = histogram.mode;
+            block.histogram_channels = histogram.channels;
+            block.histogram_bins = histogram.bins[0].len() as _;
+            for (shared, bins) in block.histogram.iter_mut().zip(&histogram.bins) {
+                shared[..bins.len()].copy_from_slice(bins);
+                shared[bins.len()..].fill(0);
+            }
+
+            let mean = HistogramData::means(&histogram.bins);
+
+            let (frames, reject) = (config.sample_frames, config.reject_outliers);
+            block.mean = self.mean.push(mean, frames, reject);
+            [block.max_cll] = self.max_cll.push([histogram.max_cll()], frames, reject);
+
+            if let Some(full) = &histogram.full {
+                let full_mean = HistogramData::means(full);
+
+                block.compared = 1;
+                block.full_mean = self.full_mean.push(full_mean, frames, reject);
+                block.mean_delta = std::array::from_fn(|ch| block.mean[ch] - block.full_mean[ch]);
+            } else {
+                block.compared = 0;
+                self.full_mean.reset();
+            }
+        } else {
+            block.histogram_channels = 0;
+            block.max_cll = 0.0;
+            block.compared = 0;
+            self.mean.reset();
+            self.full_mean.reset();
+            self.max_cll.reset();
+        }
+
+        let setting = |setting: Option<Setting>| match setting {
+            Some(Setting { min, current, max }) => (1, [min, current, max]),
+            None => (0, [0; 3]),
+        };
+        (block.has_brightness, block.brightness) = setting(monitor.and_then(|m| m.brightness()));
+        (block.has_contrast, block.contrast) = setting(monitor.and_then(|m| m.contrast()));
+
+        if let Some(outputs) = outputs {
+            block.outputs_compared = 1;
+            block.output_mean = outputs.mean;
+            block.output_delta = outputs.delta;
+            block.output_delta_e = outputs.delta_e;
+        } else {
+            block.outputs_compared = 0;
+        }
+
+        block
+            .sequence
+            .store(sequence.wrapping_add(2), Ordering::Release);
+    }
+}
+
+impl Drop for SharedStats {
+    fn drop(&mut self) {
+        unsafe {
+            _ = UnmapViewOfFile(self.view);
+            _ = CloseHandle(self.mapping);
+        }
+    }
+}
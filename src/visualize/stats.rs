@@ -0,0 +1,245 @@
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{Direct3D12::*, Dxgi::Common::DXGI_FORMAT_R32_UINT},
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{
+            command_queue::FRAME_COUNT, pso::PipelineState, resource::Resource, wrap::HeapProps,
+        },
+        initializer::Initializer,
+        math,
+        renderer::Renderer,
+        resource::RwBuffer,
+    },
+    gui::utils::Rect as _,
+};
+
+// word offsets in the stats buffer, matches stats.hlsl
+const MIN: usize = 0;
+const MAX: usize = 4;
+const SUM_LO: usize = 8;
+const SUM_HI: usize = 12;
+const COUNT: usize = 16;
+const PERCENTILES: usize = 17;
+const OVER: usize = 22;
+const UNDER: usize = 23;
+// only these words are read back, the luma histogram after them stays on the gpu
+const HEADER: usize = 32;
+const NUM_BINS: usize = 1024;
+
+const FIXED: f64 = 65536.0;
+
+// the ranks StatsPercentileCs looks up
+pub const PERCENTILE_RANKS: [f32; 5] = [0.01, 0.05, 0.5, 0.95, 0.99];
+
+// frame-wide values of r, g, b and luma as the histogram bins them, srgb or pq encoded in [0, 1]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameStats {
+    pub count: u32,
+    pub min: [f32; 4],
+    pub max: [f32; 4],
+    pub mean: [f32; 4],
+    // luma at PERCENTILE_RANKS
+    pub percentiles: [f32; 5],
+    // pixels with a channel above 1.0 and below 0.0 before encoding, scrgb highlights and
+    // negative wide gamut values
+    pub over: u32,
+    pub under: u32,
+}
+
+impl FrameStats {
+    // None when no pixel was counted
+    fn from_words(words: &[u32]) -> Option<Self> {
+        let count = words[COUNT];
+        if count == 0 {
+            return None;
+        }
+
+        Some(Self {
+            count,
+            min: std::array::from_fn(|ch| f32::from_bits(!words[MIN + ch])),
+            max: std::array::from_fn(|ch| f32::from_bits(words[MAX + ch])),
+            mean: std::array::from_fn(|ch| {
+                let sum = (words[SUM_HI + ch] as u64) << 32 | words[SUM_LO + ch] as u64;
+                (sum as f64 / FIXED / count as f64) as f32
+            }),
+            percentiles: std::array::from_fn(|i| {
+                words[PERCENTILES + i] as f32 / (NUM_BINS - 1) as f32
+            }),
+            over: words[OVER],
+            under: words[UNDER],
+        })
+    }
+
+    // over and under as fractions of the counted pixels
+    pub fn out_of_range(&self) -> [f32; 2] {
+        [self.over, self.under].map(|n| n as f32 / self.count as f32)
+    }
+}
+
+// min, max, mean and luma percentiles of the analyzed region reduced on the gpu, for the
+// readouts and anything that scales with the frame content. each frame slot has its own readback,
+// read once the context has waited for that slot again, so the values arrive a couple of frames
+// late without stalling
+pub struct Stats {
+    reduce_pso: PipelineState,
+    percentile_pso: PipelineState,
+    buffer: RwBuffer,
+    readbacks: Vec<Resource>,
+    copied: [bool; FRAME_COUNT],
+    latest: Option<FrameStats>,
+}
+
+impl Stats {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let reduce_pso =
+            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/StatsCs.bin"), None)?;
+
+        let percentile_pso = ctx.create_compute_pipeline(
+            include_bytes!("../shaders/bin/StatsPercentileCs.bin"),
+            None,
+        )?;
+
+        let buffer = RwBuffer::new(ctx, (HEADER + NUM_BINS) as _, DXGI_FORMAT_R32_UINT)?;
+
+        let readbacks = (0..FRAME_COUNT)
+            .map(|_| {
+                Resource::new_buffer(
+                    ctx,
+                    &HeapProps::readback(),
+                    None,
+                    (4 * HEADER) as _,
+                    D3D12_RESOURCE_FLAG_NONE,
+                    D3D12_RESOURCE_STATE_COPY_DEST,
+                )
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            reduce_pso,
+            percentile_pso,
+            buffer,
+            readbacks,
+            copied: [false; FRAME_COUNT],
+            latest: None,
+        })
+    }
+
+    // graphics queue compute, copies the result into the readback of frame for read()
+    pub fn accumulate(&mut self, ctx: &mut Renderer, config: &Config, frame: usize) -> Result<()> {
+        ctx.resource_barrier(&[self.buffer.transition_barrier(
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        )]);
+        ctx.clear_uav(self.buffer.raw_uav, &self.buffer);
+
+        ctx.set_pipeline_state(&self.reduce_pso);
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            origin: [i32; 2],
+            hdr: u32,
+        }
+        let rect = config.analysis_rect();
+        let params = Params {
+            rect,
+            origin: [config.window_rect.left, config.window_rect.top],
+            hdr: config.hdr_mode as u32,
+        };
+        ctx.set_compute_constants(&params);
+        ctx.set_uavs(&[self.buffer.uav]);
+
+        let threads = 2 * 8;
+        ctx.dispatch(
+            math::div_round_up(rect.width() as u32, threads),
+            math::div_round_up(rect.height() as u32, threads),
+            1,
+        );
+
+        ctx.resource_barrier(&[self.buffer.uav_barrier()]);
+        ctx.set_pipeline_state(&self.percentile_pso);
+        ctx.set_uavs(&[self.buffer.uav]);
+        ctx.dispatch(1, 1, 1);
+
+        ctx.resource_barrier(&[self.buffer.transition_barrier(
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COPY_SOURCE,
+        )]);
+        ctx.copy_buffer_region(
+            &self.readbacks[frame],
+            0,
+            &self.buffer,
+            0,
+            (4 * HEADER) as _,
+        );
+        ctx.resource_barrier(&[self.buffer.transition_barrier(
+            D3D12_RESOURCE_STATE_COPY_SOURCE,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        self.copied[frame] = true;
+        Ok(())
+    }
+
+    // valid once the frame that last used the slot has finished on the GPU, true when it
+    // brought new values
+    pub fn read(&mut self, frame: usize) -> Result<bool> {
+        if !std::mem::take(&mut self.copied[frame]) {
+            return Ok(false);
+        }
+
+        let words = self.readbacks[frame].read::<u32>(HEADER)?;
+        self.latest = FrameStats::from_words(&words);
+        Ok(true)
+    }
+
+    // the last values read, kept until newer ones arrive
+    pub fn latest(&self) -> Option<&FrameStats> {
+        self.latest.as_ref()
+    }
+
+    // forgets everything once nothing needs the reduction
+    pub fn reset(&mut self) {
+        self.copied = [false; FRAME_COUNT];
+        self.latest = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_the_reduced_words() {
+        let mut words = [0; HEADER];
+        words[COUNT] = 4;
+        for ch in 0..4 {
+            words[MIN + ch] = !0.25f32.to_bits();
+            words[MAX + ch] = 0.75f32.to_bits();
+        }
+        // 2.0 in the low word and 65536.0 carried into the high one
+        let sum = (2.0 * FIXED) as u64 + (1 << 32);
+        words[SUM_LO] = sum as u32;
+        words[SUM_HI] = (sum >> 32) as u32;
+        words[PERCENTILES + 2] = (NUM_BINS - 1) as u32;
+        words[OVER] = 1;
+
+        let stats = FrameStats::from_words(&words).unwrap();
+        assert_eq!(stats.min, [0.25; 4]);
+        assert_eq!(stats.max, [0.75; 4]);
+        assert_eq!(stats.mean[0], (2.0 + 65536.0) / 4.0);
+        assert_eq!(stats.mean[1], 0.0);
+        assert_eq!(stats.percentiles[2], 1.0);
+        assert_eq!(stats.out_of_range(), [0.25, 0.0]);
+    }
+
+    #[test]
+    fn nothing_counted_is_none() {
+        assert_eq!(FrameStats::from_words(&[0; HEADER]), None);
+    }
+}
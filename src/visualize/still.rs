@@ -0,0 +1,333 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use windows::{
+    core::{HSTRING, PCWSTR},
+    Win32::{
+        Foundation::GENERIC_READ,
+        Graphics::{
+            Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+            Direct3D12::{
+                D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE, D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+            },
+            Dxgi::Common::{
+                DXGI_FORMAT_B8G8R8A8_UNORM, DXGI_FORMAT_B8G8R8A8_UNORM_SRGB,
+                DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R8G8B8A8_UNORM,
+                DXGI_FORMAT_R8G8B8A8_UNORM_SRGB,
+            },
+            Imaging::{
+                CLSID_WICImagingFactory, GUID_WICPixelFormat64bppRGBAHalf, IWICImagingFactory,
+                IWICPalette, WICBitmapDitherTypeNone, WICBitmapPaletteTypeCustom,
+                WICDecodeMetadataCacheOnDemand,
+            },
+        },
+        System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER},
+    },
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{
+            descriptor::{Descriptor, RtvHeap},
+            device::Device,
+            pso::PipelineState,
+            resource::Resource,
+            wrap::*,
+        },
+        initializer::Initializer,
+        mips::MipGenerator,
+        renderer::{Renderer, ViewportKind},
+        resource::{RenderTexture, Texture2D},
+    },
+    gui::utils::Rect as _,
+};
+
+// the largest texture d3d12 guarantees
+const MAX_SIZE: u32 = 16384;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Content {
+    Image,
+    Video,
+    Shared,
+}
+
+// an image file, a video's frame or a shared texture analyzed in place of the desktop, fitted
+// into the window
+pub struct StillImage {
+    compose_pso: PipelineState,
+    show_pso: PipelineState,
+    #[allow(unused)]
+    rtv_heap: RtvHeap,
+    // mipmapped, photos are often many times the window's size and would alias when shrunk
+    image: Texture2D,
+    mips: MipGenerator,
+    // config.linear_downsample of the mips in image, none until they are generated
+    linear: Option<bool>,
+    // video frames are 8-bit bgra
+    frame: Texture2D,
+    // views the texture of SharedTexture, it is owned there
+    shared_srv: Descriptor,
+    content: Content,
+    output: RenderTexture,
+    // the file tried last, a failed one isn't read again until another is picked
+    path: Option<PathBuf>,
+    size: Option<(u32, u32)>,
+    pending: Option<Vec<u8>>,
+    fresh: bool,
+}
+
+impl StillImage {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let create_pso = |ctx: &mut Initializer, blend| {
+            ctx.create_graphics_pipeline(
+                include_bytes!("../shaders/bin/StillVs.bin"),
+                include_bytes!("../shaders/bin/StillPs.bin"),
+                blend,
+                RasterizerDesc::none(),
+                DepthStencilDesc::none(),
+                &[],
+                D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+                RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+                None,
+                None,
+            )
+        };
+        let compose_pso = create_pso(ctx, BlendDesc::none())?;
+        // the bars keep the background
+        let show_pso = create_pso(ctx, BlendDesc::alpha())?;
+
+        let rtv_heap = RtvHeap::new(ctx, 1)?;
+        let output = RenderTexture::new(
+            ctx,
+            rtv_heap.descriptor(0),
+            1,
+            1,
+            DXGI_FORMAT_R16G16B16A16_FLOAT,
+            [0.0; 4],
+        )?;
+        let image = Texture2D::with_mips(ctx, 1, 1, DXGI_FORMAT_R16G16B16A16_FLOAT)?;
+        let mips = MipGenerator::new(ctx)?;
+        let frame = Texture2D::new(ctx, 1, 1, DXGI_FORMAT_B8G8R8A8_UNORM_SRGB)?;
+        let shared_srv = ctx.next_descriptor();
+
+        Ok(Self {
+            compose_pso,
+            show_pso,
+            rtv_heap,
+            image,
+            mips,
+            linear: None,
+            frame,
+            shared_srv,
+            content: Content::Image,
+            output,
+            path: None,
+            size: None,
+            pending: None,
+            fresh: false,
+        })
+    }
+
+    // reads a newly picked file, true while there is an image to analyze
+    pub fn update(&mut self, config: &Config) -> bool {
+        if config.source_path != self.path {
+            self.path = config.source_path.clone();
+            self.size = None;
+            self.pending = None;
+
+            if let Some(path) = &self.path {
+                match load_image(path) {
+                    Ok((width, height, pixels)) => {
+                        self.size = Some((width, height));
+                        self.pending = Some(pixels);
+                        self.content = Content::Image;
+                        self.fresh = true;
+                    }
+                    Err(e) => println!("{}: {e:?}", path.display()),
+                }
+            }
+        }
+
+        self.size.is_some()
+    }
+
+    // a decoded frame of VideoPlayer, the image file is read again when it is picked next
+    pub fn set_video_frame(&mut self, width: u32, height: u32, pixels: Vec<u8>) {
+        self.path = None;
+        self.size = Some((width, height));
+        self.pending = Some(pixels);
+        self.content = Content::Video;
+        self.fresh = true;
+    }
+
+    // a frame received by SharedTexture, 8-bit formats are decoded as srgb like the desktop
+    pub fn set_shared_frame(&mut self, device: &Device, resource: &Resource) {
+        let format = match resource.desc().Format {
+            DXGI_FORMAT_B8G8R8A8_UNORM => DXGI_FORMAT_B8G8R8A8_UNORM_SRGB,
+            DXGI_FORMAT_R8G8B8A8_UNORM => DXGI_FORMAT_R8G8B8A8_UNORM_SRGB,
+            format => format,
+        };
+        device.create_srv(
+            resource,
+            Some(&SrvDesc::texture2d(format)),
+            self.shared_srv.cpu,
+        );
+
+        self.path = None;
+        self.size = Some(resource.size());
+        self.pending = None;
+        self.content = Content::Shared;
+        self.fresh = true;
+    }
+
+    // true once after a new image, it counts as a desktop update
+    pub fn take_fresh(&mut self) -> bool {
+        std::mem::take(&mut self.fresh)
+    }
+
+    pub fn srv(&self) -> Descriptor {
+        self.srv_of_content()
+    }
+
+    // renders the fitted image into a window sized texture bound like the filter output
+    pub fn compose(&mut self, ctx: &mut Renderer, config: &Config) -> Result<Descriptor> {
+        if let (Some((width, height)), Some(pixels)) = (self.size, self.pending.take()) {
+            self.texture_mut().upload(ctx, width, height, &pixels)?;
+            if self.content == Content::Image {
+                self.linear = None;
+            }
+        }
+
+        // again when the averaging is toggled, without reading the file again
+        if self.content == Content::Image && self.linear != Some(config.linear_downsample) {
+            self.mips
+                .generate(ctx, &self.image, config.linear_downsample);
+            self.linear = Some(config.linear_downsample);
+        }
+
+        let (width, height) = config.window_rect.usize();
+        if self.output.size() != (width, height) {
+            let old = self.output.resource.clone();
+            self.output.resize(ctx.device(), width, height)?;
+            ctx.retire(old);
+        }
+
+        ctx.resource_barrier(&[self.output.transition_barrier(
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+        )]);
+        ctx.set_render_targets(&[self.output.rtv]);
+
+        ctx.set_pipeline_state(&self.compose_pso);
+        self.draw(ctx, config);
+
+        ctx.reset_render_targets();
+        ctx.resource_barrier(&[self.output.transition_barrier(
+            D3D12_RESOURCE_STATE_RENDER_TARGET,
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+        )]);
+
+        Ok(self.output.srv)
+    }
+
+    // the image as the window's content, in place of the desktop behind it
+    pub fn show(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        ctx.set_pipeline_state(&self.show_pso);
+        self.draw(ctx, config);
+
+        Ok(())
+    }
+
+    fn draw(&self, ctx: &mut Renderer, config: &Config) {
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+        let fit = fit(self.size.unwrap_or((1, 1)), config.window_rect.usize());
+        ctx.set_graphics_constants(&fit);
+        ctx.set_graphics_srvs(&[self.srv_of_content()]);
+
+        ctx.draw(3, 1);
+    }
+
+    fn srv_of_content(&self) -> Descriptor {
+        match self.content {
+            Content::Image => self.image.srv,
+            Content::Video => self.frame.srv,
+            Content::Shared => self.shared_srv,
+        }
+    }
+
+    fn texture_mut(&mut self) -> &mut Texture2D {
+        if self.content == Content::Video {
+            &mut self.frame
+        } else {
+            &mut self.image
+        }
+    }
+}
+
+// the largest centered rect with the image's aspect, [x, y, width, height]
+fn fit(image: (u32, u32), window: (u32, u32)) -> [f32; 4] {
+    let (iw, ih) = (image.0.max(1) as f32, image.1.max(1) as f32);
+    let (ww, wh) = (window.0 as f32, window.1 as f32);
+
+    let scale = (ww / iw).min(wh / ih);
+    let (width, height) = (scale * iw, scale * ih);
+
+    [(ww - width) / 2.0, (wh - height) / 2.0, width, height]
+}
+
+// decoded by wic to linear half floats like an hdr desktop, exr needs a codec to be installed
+fn load_image(path: &Path) -> Result<(u32, u32, Vec<u8>)> {
+    unsafe {
+        let factory: IWICImagingFactory =
+            CoCreateInstance(&CLSID_WICImagingFactory, None, CLSCTX_INPROC_SERVER)?;
+
+        let path = HSTRING::from(path.as_os_str());
+        let decoder = factory.CreateDecoderFromFilename(
+            PCWSTR(path.as_ptr()),
+            None,
+            GENERIC_READ,
+            WICDecodeMetadataCacheOnDemand,
+        )?;
+        let frame = decoder.GetFrame(0)?;
+
+        let converter = factory.CreateFormatConverter()?;
+        converter.Initialize(
+            &frame,
+            &GUID_WICPixelFormat64bppRGBAHalf,
+            WICBitmapDitherTypeNone,
+            None::<&IWICPalette>,
+            0.0,
+            WICBitmapPaletteTypeCustom,
+        )?;
+
+        let (mut width, mut height) = (0, 0);
+        converter.GetSize(&mut width, &mut height)?;
+        anyhow::ensure!(
+            width <= MAX_SIZE && height <= MAX_SIZE,
+            "{width}x{height} is over the {MAX_SIZE} pixel texture limit"
+        );
+
+        let stride = 8 * width;
+        let mut pixels = vec![0u8; (stride * height) as usize];
+        converter.CopyPixels(std::ptr::null(), stride, &mut pixels)?;
+
+        Ok((width, height, pixels))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_keeps_the_aspect_centered() {
+        assert_eq!(fit((200, 100), (400, 400)), [0.0, 100.0, 400.0, 200.0]);
+        assert_eq!(fit((100, 200), (400, 400)), [100.0, 0.0, 200.0, 400.0]);
+        assert_eq!(fit((640, 360), (1280, 720)), [0.0, 0.0, 1280.0, 720.0]);
+    }
+}
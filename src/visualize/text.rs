@@ -0,0 +1,265 @@
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::{COLORREF, RECT, SIZE},
+    Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+        Direct3D12::D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+        Dxgi::Common::{DXGI_FORMAT_B8G8R8A8_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT},
+        Gdi::{
+            CreateCompatibleDC, CreateDIBSection, CreateSolidBrush, DeleteDC, DeleteObject,
+            FillRect, GdiFlush, GetStockObject, GetTextExtentPoint32W, SelectObject, SetBkMode,
+            SetTextColor, TextOutW, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DEFAULT_GUI_FONT,
+            DIB_RGB_COLORS, HDC, TRANSPARENT,
+        },
+    },
+};
+
+use crate::graphics::{
+    core::{
+        descriptor::Descriptor,
+        pso::PipelineState,
+        wrap::{BlendDesc, DepthStencilDesc, RasterizerDesc, RtvFormats},
+    },
+    initializer::Initializer,
+    renderer::{Renderer, ViewportKind},
+    resource::Texture2D,
+};
+
+// the panel is cropped to this
+const MAX_WIDTH: u32 = 512;
+const MAX_HEIGHT: u32 = 256;
+const PADDING: i32 = 6;
+const SWATCH: i32 = 8;
+// background of the panel
+const OPACITY: f32 = 0.6;
+
+#[derive(Clone, PartialEq)]
+pub struct TextLine {
+    pub text: String,
+    // 0x00bbggrr like COLORREF, drawn as a swatch before the text
+    pub color: Option<u32>,
+}
+
+impl TextLine {
+    pub fn new(text: impl Into<String>, color: Option<u32>) -> Self {
+        Self {
+            text: text.into(),
+            color,
+        }
+    }
+}
+
+// lines of gdi text rasterized on the cpu and drawn as a quad over the scopes
+pub struct TextPanel {
+    pso: PipelineState,
+    // created on the first upload
+    texture: Option<Texture2D>,
+    srv: Descriptor,
+    lines: Vec<TextLine>,
+    size: (u32, u32),
+    opacity: f32,
+}
+
+impl TextPanel {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        Self::with_opacity(ctx, OPACITY)
+    }
+
+    // 0 draws the text alone
+    pub fn with_opacity(ctx: &mut Initializer, opacity: f32) -> Result<Self> {
+        let pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/TextVs.bin"),
+            include_bytes!("../shaders/bin/TextPs.bin"),
+            BlendDesc::mul(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let srv = ctx.next_descriptor();
+
+        Ok(Self {
+            pso,
+            texture: None,
+            srv,
+            lines: vec![],
+            size: (0, 0),
+            opacity,
+        })
+    }
+
+    // rasterizes and uploads again only when the lines changed
+    pub fn set_lines(&mut self, ctx: &mut Renderer, lines: Vec<TextLine>) -> Result<()> {
+        if lines == self.lines {
+            return Ok(());
+        }
+
+        let (width, height, pixels) = rasterize(&lines)?;
+        if width > 0 && height > 0 {
+            match &mut self.texture {
+                Some(texture) => texture.upload(ctx, width, height, &pixels)?,
+                None => {
+                    self.texture = Some(Texture2D::from_pixels(
+                        ctx,
+                        self.srv,
+                        width,
+                        height,
+                        DXGI_FORMAT_B8G8R8A8_UNORM,
+                        &pixels,
+                    )?)
+                }
+            }
+        }
+        self.size = (width, height);
+        self.lines = lines;
+
+        Ok(())
+    }
+
+    // cropped size of the current lines in pixels
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    // the middle of the text from the top-left corner, the swatch column pads it unevenly
+    pub fn text_center(&self) -> (i32, i32) {
+        let (width, height) = (self.size.0 as i32, self.size.1 as i32);
+        let left = PADDING + SWATCH + PADDING;
+        ((left + width - PADDING) / 2, (height - 2) / 2)
+    }
+
+    // x and y are the top-left corner in window pixels
+    pub fn draw(&self, ctx: &mut Renderer, x: i32, y: i32, target: (u32, u32)) -> Result<()> {
+        if self.size.0 == 0 || self.size.1 == 0 || self.texture.is_none() {
+            return Ok(());
+        }
+
+        ctx.set_pipeline_state(&self.pso);
+        ctx.set_viewport(ViewportKind::Full);
+        ctx.set_scissor(RECT {
+            left: x,
+            top: y,
+            right: x + self.size.0 as i32,
+            bottom: y + self.size.1 as i32,
+        });
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+
+        #[repr(C)]
+        struct Params {
+            position: [i32; 2],
+            size: [u32; 2],
+            target: [u32; 2],
+            opacity: f32,
+        }
+
+        let params = Params {
+            position: [x, y],
+            size: [self.size.0, self.size.1],
+            target: [target.0, target.1],
+            opacity: self.opacity,
+        };
+
+        ctx.set_graphics_constants(&params);
+        ctx.set_graphics_srvs(&[self.srv]);
+        ctx.draw(4, 1);
+
+        Ok(())
+    }
+}
+
+// returns the cropped size and its tightly packed bgra rows
+fn rasterize(lines: &[TextLine]) -> Result<(u32, u32, Vec<u8>)> {
+    unsafe {
+        let hdc = CreateCompatibleDC(None);
+
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as _,
+                biWidth: MAX_WIDTH as _,
+                biHeight: -(MAX_HEIGHT as i32),
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut bits = std::ptr::null_mut();
+        let bitmap = match CreateDIBSection(hdc, &info, DIB_RGB_COLORS, &mut bits, None, 0) {
+            Ok(bitmap) => bitmap,
+            Err(e) => {
+                _ = DeleteDC(hdc);
+                anyhow::bail!(e)
+            }
+        };
+        let old = SelectObject(hdc, bitmap);
+
+        SelectObject(hdc, GetStockObject(DEFAULT_GUI_FONT));
+        SetBkMode(hdc, TRANSPARENT);
+        SetTextColor(hdc, COLORREF(0xe0e0e0));
+
+        let (width, height) = draw_lines(hdc, lines);
+        let width = (width.max(0) as u32).min(MAX_WIDTH);
+        let height = (height.max(0) as u32).min(MAX_HEIGHT);
+
+        _ = GdiFlush();
+
+        let stride = 4 * MAX_WIDTH as usize;
+        let bitmap_bits = std::slice::from_raw_parts(bits as *const u8, stride * height as usize);
+        let pixels = bitmap_bits
+            .chunks_exact(stride)
+            .flat_map(|row| &row[..4 * width as usize])
+            .copied()
+            .collect();
+
+        SelectObject(hdc, old);
+        _ = DeleteObject(bitmap);
+        _ = DeleteDC(hdc);
+
+        Ok((width, height, pixels))
+    }
+}
+
+fn draw_lines(hdc: HDC, lines: &[TextLine]) -> (i32, i32) {
+    let mut y = PADDING;
+    let mut width = 0;
+
+    for line in lines {
+        let text: Vec<u16> = line.text.encode_utf16().collect();
+        let mut extent = SIZE::default();
+
+        unsafe {
+            _ = GetTextExtentPoint32W(hdc, &text, &mut extent);
+
+            let x = PADDING + SWATCH + PADDING;
+            if let Some(color) = line.color {
+                let brush = CreateSolidBrush(COLORREF(color));
+                let top = y + (extent.cy - SWATCH) / 2;
+                FillRect(
+                    hdc,
+                    &RECT {
+                        left: PADDING,
+                        top,
+                        right: PADDING + SWATCH,
+                        bottom: top + SWATCH,
+                    },
+                    brush,
+                );
+                _ = DeleteObject(brush);
+            }
+
+            _ = TextOutW(hdc, x, y, &text);
+
+            width = width.max(x + extent.cx + PADDING);
+        }
+
+        y += extent.cy + 2;
+    }
+
+    (width, y + PADDING)
+}
@@ -0,0 +1,310 @@
+use anyhow::Result;
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::{
+        Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+        Direct3D12::*,
+        Dxgi::Common::{
+            DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32_FLOAT,
+        },
+    },
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{pso::PipelineState, resource::Resource, wrap::*},
+        initializer::Initializer,
+        renderer::{Renderer, ViewportKind},
+        resource::RwBuffer,
+    },
+    gui::utils::Rect as _,
+};
+
+use super::{
+    color,
+    text::{TextLine, TextPanel},
+};
+
+pub const MIN_GRID: u32 = 3;
+pub const MAX_GRID: u32 = 15;
+
+// the map in the top-right corner, its width in pixels
+const TILE: i32 = 192;
+const MARGIN: i32 = 8;
+
+// cells with less of their area counted are mostly covered by our own overlay
+const MIN_COVERAGE: f32 = 0.5;
+
+// grids are odd so one cell sits in the middle for the others to be compared against
+pub fn grid_size(grid: u32) -> u32 {
+    grid.clamp(MIN_GRID, MAX_GRID) | 1
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Uniformity {
+    pub grid: u32,
+    // largest luminance difference from the center, relative to it, and the cell as column, row
+    pub luminance: (f32, [u32; 2]),
+    // largest ciede2000 difference from the center with the center as white
+    pub delta_e: (f32, [u32; 2]),
+}
+
+impl Uniformity {
+    // cells are row major mean linear rgb and the counted share of the cell, None without a
+    // usable center
+    fn from_cells(grid: u32, cells: &[[f32; 4]]) -> Option<Self> {
+        let center = cells[(grid / 2 * grid + grid / 2) as usize];
+        let white = color::rgb_to_xyz([center[0], center[1], center[2]]);
+        if center[3] < MIN_COVERAGE || white[1] <= 0.0 {
+            return None;
+        }
+
+        let lab = |xyz: [f32; 3]| color::xyz_to_lab(xyz.map(|c| c / white[1]));
+        let center_lab = lab(white);
+
+        let mut uniformity = Self {
+            grid,
+            luminance: (0.0, [grid / 2; 2]),
+            delta_e: (0.0, [grid / 2; 2]),
+        };
+
+        for (i, &[r, g, b, coverage]) in cells.iter().enumerate() {
+            if coverage < MIN_COVERAGE {
+                continue;
+            }
+
+            let cell = [i as u32 % grid, i as u32 / grid];
+            let xyz = color::rgb_to_xyz([r, g, b]);
+
+            let deviation = xyz[1] / white[1] - 1.0;
+            if deviation.abs() > uniformity.luminance.0.abs() {
+                uniformity.luminance = (deviation, cell);
+            }
+
+            let delta_e = color::delta_e_2000(lab(xyz), center_lab);
+            if delta_e > uniformity.delta_e.0 {
+                uniformity.delta_e = (delta_e, cell);
+            }
+        }
+
+        Some(uniformity)
+    }
+
+    fn lines(&self) -> Vec<TextLine> {
+        let (luminance, [lx, ly]) = self.luminance;
+        let (delta_e, [ex, ey]) = self.delta_e;
+
+        vec![
+            TextLine::new(format!("Uniformity {0}x{0}", self.grid), None),
+            TextLine::new(
+                format!(
+                    "Luminance {:+.1}% at {},{}",
+                    100.0 * luminance,
+                    lx + 1,
+                    ly + 1
+                ),
+                None,
+            ),
+            TextLine::new(
+                format!("\u{394}E {delta_e:.2} at {},{}", ex + 1, ey + 1),
+                None,
+            ),
+        ]
+    }
+}
+
+// averages the analyzed region over a grid of cells for a panel uniformity check, drawn as a
+// heatmap of the luminance deviation from the center cell with the largest deviations below it
+pub struct UniformityMap {
+    reduce_pso: PipelineState,
+    draw_pso: PipelineState,
+    cells: RwBuffer,
+    readback: Resource,
+    panel: TextPanel,
+    // the grid of the cells copied for read()
+    copied: Option<u32>,
+    uniformity: Option<Uniformity>,
+}
+
+impl UniformityMap {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let reduce_pso =
+            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/UniformityCs.bin"), None)?;
+
+        let draw_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/UniformityVs.bin"),
+            include_bytes!("../shaders/bin/UniformityPs.bin"),
+            BlendDesc::alpha(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            Some(DXGI_FORMAT_D16_UNORM),
+            None,
+        )?;
+
+        let num_floats = 4 * MAX_GRID * MAX_GRID;
+        let cells = RwBuffer::new(ctx, num_floats, DXGI_FORMAT_R32_FLOAT)?;
+
+        let readback = Resource::new_buffer(
+            ctx,
+            &HeapProps::readback(),
+            None,
+            (4 * num_floats) as _,
+            D3D12_RESOURCE_FLAG_NONE,
+            D3D12_RESOURCE_STATE_COPY_DEST,
+        )?;
+
+        Ok(Self {
+            reduce_pso,
+            draw_pso,
+            cells,
+            readback,
+            panel: TextPanel::new(ctx)?,
+            copied: None,
+            uniformity: None,
+        })
+    }
+
+    // graphics queue compute, copies the cells for read()
+    pub fn accumulate(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if !config.enable_uniformity {
+            return Ok(());
+        }
+
+        let grid = grid_size(config.uniformity_grid);
+
+        ctx.resource_barrier(&[self.cells.transition_barrier(
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        )]);
+
+        ctx.set_pipeline_state(&self.reduce_pso);
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            origin: [i32; 2],
+            grid: u32,
+        }
+        ctx.set_compute_constants(&Params {
+            rect: config.analysis_rect(),
+            origin: [config.window_rect.left, config.window_rect.top],
+            grid,
+        });
+        ctx.set_uavs(&[self.cells.uav]);
+        ctx.dispatch(grid, grid, 1);
+
+        let size = (4 * 4 * grid * grid) as u64;
+        ctx.resource_barrier(&[self.cells.transition_barrier(
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COPY_SOURCE,
+        )]);
+        ctx.copy_buffer_region(&self.readback, 0, &self.cells, 0, size);
+        ctx.resource_barrier(&[self.cells.transition_barrier(
+            D3D12_RESOURCE_STATE_COPY_SOURCE,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        self.copied = Some(grid);
+        Ok(())
+    }
+
+    // the map of this frame's cells, the numbers of the last frame's
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if !config.enable_uniformity {
+            return Ok(());
+        }
+
+        let grid = grid_size(config.uniformity_grid);
+        let (width, height) = config.analysis_rect().size();
+        let tile_height = TILE * height.max(1) / width.max(1);
+
+        let right = config.window_rect.width() - MARGIN;
+        let rect = RECT {
+            left: right - TILE,
+            top: MARGIN,
+            right,
+            bottom: MARGIN + tile_height.clamp(1, 2 * TILE),
+        };
+
+        ctx.set_pipeline_state(&self.draw_pso);
+        ctx.set_viewport(ViewportKind::Rect(rect));
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+        ctx.set_graphics_srvs(&[self.cells.srv]);
+        ctx.set_graphics_constants(&grid);
+        ctx.draw(4, 1);
+
+        let lines = match &self.uniformity {
+            Some(uniformity) => uniformity.lines(),
+            None => vec![TextLine::new("Uniformity: no center reading", None)],
+        };
+        self.panel.set_lines(ctx, lines)?;
+
+        let x = right - self.panel.size().0 as i32;
+        self.panel.draw(
+            ctx,
+            x.max(0),
+            rect.bottom + MARGIN,
+            config.window_rect.usize(),
+        )
+    }
+
+    // valid after the frame recorded by accumulate() has finished on the GPU
+    pub fn read(&mut self, config: &Config) -> Result<()> {
+        if !config.enable_uniformity {
+            self.uniformity = None;
+            return Ok(());
+        }
+
+        let Some(grid) = self.copied.take() else {
+            return Ok(());
+        };
+
+        let floats = self.readback.read::<f32>((4 * grid * grid) as usize)?;
+        let cells: Vec<_> = floats
+            .chunks_exact(4)
+            .map(|c| [c[0], c[1], c[2], c[3]])
+            .collect();
+        self.uniformity = Uniformity::from_cells(grid, &cells);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_are_odd_and_bounded() {
+        assert_eq!(grid_size(0), MIN_GRID);
+        assert_eq!(grid_size(8), 9);
+        assert_eq!(grid_size(9), 9);
+        assert_eq!(grid_size(100), MAX_GRID);
+    }
+
+    #[test]
+    fn finds_the_largest_deviations() {
+        let mut cells = vec![[0.5, 0.5, 0.5, 1.0]; 9];
+        // a darker corner and a tinted edge
+        cells[0] = [0.4, 0.4, 0.4, 1.0];
+        cells[5] = [0.5, 0.45, 0.5, 1.0];
+        // covered, ignored
+        cells[8] = [0.0, 0.0, 0.0, 0.0];
+
+        let uniformity = Uniformity::from_cells(3, &cells).unwrap();
+        assert!((uniformity.luminance.0 + 0.2).abs() < 1e-4);
+        assert_eq!(uniformity.luminance.1, [0, 0]);
+        assert_eq!(uniformity.delta_e.1, [2, 1]);
+    }
+
+    #[test]
+    fn needs_the_center() {
+        let mut cells = vec![[0.5, 0.5, 0.5, 1.0]; 9];
+        cells[4][3] = 0.0;
+        assert_eq!(Uniformity::from_cells(3, &cells), None);
+    }
+}
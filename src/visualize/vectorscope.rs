@@ -0,0 +1,367 @@
+use core::f32::consts::PI;
+
+use anyhow::Result;
+use windows::{
+    core::s,
+    Win32::{
+        Foundation::RECT,
+        Graphics::{
+            Direct3D::{D3D_PRIMITIVE_TOPOLOGY_LINELIST, D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP},
+            Direct3D12::*,
+            Dxgi::Common::{
+                DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32G32B32_FLOAT,
+                DXGI_FORMAT_R32_UINT,
+            },
+        },
+    },
+};
+
+use crate::{
+    config::{Config, LAYOUT_VECTORSCOPE, PRIMARIES_BT2020, PRIMARIES_BT601, PRIMARIES_BT709},
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        math::{self, Matrix},
+        renderer::{msaa_index, Renderer, ViewportKind, MSAA_SAMPLES},
+        resource::{RwBuffer, VertexBuffer},
+    },
+    gui::utils::Rect as _,
+};
+
+use super::color;
+
+const NUM_BINS: u32 = 256;
+
+// radius of the scope in its square viewport, same fit as the color cloud
+const EXTENT: f32 = 0.9;
+
+// the i axis, counterclockwise from +cb. skin tones of any complexion fall close to it
+const SKIN_TONE_ANGLE: f32 = 123.0;
+
+pub struct Vectorscope {
+    compute_pso: PipelineState,
+    draw_pso: PipelineState,
+    // one per msaa sample count
+    graticule_psos: Vec<PipelineState>,
+    bins: RwBuffer,
+    // one per PRIMARIES_*, the targets move with the luma weights
+    graticules: [VertexBuffer; 3],
+    skin_tone: VertexBuffer,
+}
+
+impl Vectorscope {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let compute_pso =
+            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/VectorscopeCs.bin"), None)?;
+
+        let draw_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/VectorscopeVs.bin"),
+            include_bytes!("../shaders/bin/VectorscopePs.bin"),
+            BlendDesc::mul(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let graticule_psos = MSAA_SAMPLES
+            .iter()
+            .map(|&samples| {
+                ctx.create_multisampled_pipeline(
+                    samples,
+                    include_bytes!("../shaders/bin/PrimitiveVs.bin"),
+                    include_bytes!("../shaders/bin/PrimitivePs.bin"),
+                    BlendDesc::none(),
+                    RasterizerDesc::lines(samples),
+                    DepthStencilDesc::none(),
+                    &[
+                        InputElementDesc::per_vertex(s!("POSITION"), DXGI_FORMAT_R32G32B32_FLOAT),
+                        InputElementDesc::per_vertex(s!("COLOR"), DXGI_FORMAT_R32G32B32_FLOAT),
+                    ],
+                    D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+                    RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+                    Some(DXGI_FORMAT_D16_UNORM),
+                    None,
+                )
+            })
+            .collect::<Result<_>>()?;
+
+        let bins = RwBuffer::new(ctx, NUM_BINS * NUM_BINS, DXGI_FORMAT_R32_UINT)?;
+        let graticules = [
+            VertexBuffer::new(ctx, &graticule(64, color::luma_weights(PRIMARIES_BT709)))?,
+            VertexBuffer::new(ctx, &graticule(64, color::luma_weights(PRIMARIES_BT601)))?,
+            VertexBuffer::new(ctx, &graticule(64, color::luma_weights(PRIMARIES_BT2020)))?,
+        ];
+        let skin_tone = VertexBuffer::new(ctx, &skin_tone(64))?;
+
+        Ok(Self {
+            compute_pso,
+            draw_pso,
+            graticule_psos,
+            bins,
+            graticules,
+            skin_tone,
+        })
+    }
+
+    // graphics queue compute, runs next to the async compute passes
+    pub fn accumulate(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_vectorscope {
+            self.clear(ctx)?;
+            self.compute(config, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_vectorscope {
+            self.draw(config, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    // drawn separately so the lines can go through the msaa layer
+    pub fn process_graticule(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_vectorscope {
+            self.draw_graticule(ctx, config)?;
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, ctx: &mut Renderer) -> Result<()> {
+        ctx.resource_barrier(&[self.bins.transition_barrier(
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        )]);
+
+        ctx.clear_uav(self.bins.raw_uav, &self.bins);
+
+        Ok(())
+    }
+
+    fn compute(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
+        ctx.set_pipeline_state(&self.compute_pso);
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            origin: [i32; 2],
+            hdr: u32,
+        }
+        let rect = config.analysis_rect();
+        let params = Params {
+            rect,
+            origin: [config.window_rect.left, config.window_rect.top],
+            hdr: config.hdr_mode as u32,
+        };
+        ctx.set_compute_constants(&params);
+        ctx.set_uavs(&[self.bins.uav]);
+
+        let threads = 8;
+        ctx.dispatch(
+            math::div_round_up(rect.width() as u32, threads),
+            math::div_round_up(rect.height() as u32, threads),
+            1,
+        );
+
+        ctx.resource_barrier(&[self.bins.transition_barrier(
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+
+    fn draw(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
+        ctx.set_pipeline_state(&self.draw_pso);
+        ctx.set_viewport(config.scope_viewport(
+            LAYOUT_VECTORSCOPE,
+            ViewportKind::Square {
+                fill: config.vectorscope_fill,
+                margin: config.vectorscope_margin,
+            },
+        ));
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+
+        #[repr(C)]
+        struct Params {
+            extent: f32,
+            scale: f32,
+        }
+
+        let (width, height) = config.analysis_rect().size();
+        let params = Params {
+            extent: EXTENT,
+            scale: config.vectorscope_scale * 4096.0 / ((width * height).max(1) as f32),
+        };
+
+        ctx.set_graphics_constants(&params);
+        ctx.set_graphics_srvs(&[self.bins.srv]);
+        ctx.draw(4, 1);
+
+        Ok(())
+    }
+
+    fn draw_graticule(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        ctx.set_pipeline_state(&self.graticule_psos[msaa_index(ctx.samples())]);
+        ctx.set_viewport(config.scope_viewport(
+            LAYOUT_VECTORSCOPE,
+            ViewportKind::Square {
+                fill: config.vectorscope_fill,
+                margin: config.vectorscope_margin,
+            },
+        ));
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+        let graticule = &self.graticules[config.source_primaries as usize];
+        ctx.set_vertex_buffers(&[graticule.view()]);
+
+        #[repr(C)]
+        struct Params {
+            projection: [f32; 16],
+        }
+
+        // chroma spans [-0.5, 0.5], the scope spans [-extent, extent]
+        let scale = 2.0 * EXTENT;
+        let params = Params {
+            projection: Matrix::scale(scale, scale, 1.0)
+                .mul(&Matrix::translate(0.0, 0.0, 0.5))
+                .as_4x4(),
+        };
+
+        ctx.set_graphics_constants(&params);
+        ctx.draw(graticule.vertex_count(), 1);
+
+        if config.vectorscope_skin_tone {
+            ctx.set_vertex_buffers(&[self.skin_tone.view()]);
+            ctx.draw(self.skin_tone.vertex_count(), 1);
+        }
+
+        Ok(())
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+impl Vertex {
+    fn new(cb: f32, cr: f32, color: [f32; 3]) -> Self {
+        Self {
+            position: [cb, cr, 0.0],
+            color,
+        }
+    }
+}
+
+fn rgb_to_cbcr(rgb: [f32; 3], weights: [f32; 2]) -> (f32, f32) {
+    let [_, cb, cr] = color::rgb_to_ycbcr(rgb, weights);
+    (cb, cr)
+}
+
+fn graticule(n_div: u32, weights: [f32; 2]) -> Vec<Vertex> {
+    const GRAY: [f32; 3] = [0.4, 0.4, 0.4];
+    const TARGETS: [[f32; 3]; 6] = [
+        [1.0, 0.0, 0.0],
+        [1.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 1.0, 0.0],
+    ];
+
+    let mut vertices = Vec::new();
+
+    // outer circle at the maximum chroma
+    circle(&mut vertices, 0.5, n_div, GRAY);
+
+    // crosshair
+    vertices.push(Vertex::new(-0.5, 0.0, GRAY));
+    vertices.push(Vertex::new(0.5, 0.0, GRAY));
+    vertices.push(Vertex::new(0.0, -0.5, GRAY));
+    vertices.push(Vertex::new(0.0, 0.5, GRAY));
+
+    // 75% target boxes and a tick towards the 100% position
+    for target in TARGETS {
+        let (cb, cr) = rgb_to_cbcr(target.map(|c| 0.75 * c), weights);
+        let (cb1, cr1) = rgb_to_cbcr(target, weights);
+
+        let d = 0.02;
+        let corners = [
+            (cb - d, cr - d),
+            (cb + d, cr - d),
+            (cb + d, cr + d),
+            (cb - d, cr + d),
+        ];
+        for i in 0..4 {
+            let (x0, y0) = corners[i];
+            let (x1, y1) = corners[(i + 1) % 4];
+            vertices.push(Vertex::new(x0, y0, target));
+            vertices.push(Vertex::new(x1, y1, target));
+        }
+
+        vertices.push(Vertex::new(
+            cb1 - 0.5 * (cb1 - cb),
+            cr1 - 0.5 * (cr1 - cr),
+            target,
+        ));
+        vertices.push(Vertex::new(cb1, cr1, target));
+    }
+
+    vertices
+}
+
+fn circle(vertices: &mut Vec<Vertex>, radius: f32, n_div: u32, color: [f32; 3]) {
+    for i in 0..n_div {
+        let (s0, c0) = (2.0 * PI * i as f32 / n_div as f32).sin_cos();
+        let (s1, c1) = (2.0 * PI * (i + 1) as f32 / n_div as f32).sin_cos();
+        vertices.push(Vertex::new(radius * c0, radius * s0, color));
+        vertices.push(Vertex::new(radius * c1, radius * s1, color));
+    }
+}
+
+// the i line out to the maximum chroma, with rings at a quarter, half and three quarters of it
+fn skin_tone(n_div: u32) -> Vec<Vertex> {
+    const RING: [f32; 3] = [0.25, 0.25, 0.25];
+    const SKIN: [f32; 3] = [0.9, 0.6, 0.45];
+
+    let mut vertices = Vec::new();
+
+    for saturation in [0.25, 0.5, 0.75] {
+        circle(&mut vertices, 0.5 * saturation, n_div, RING);
+    }
+
+    let (s, c) = SKIN_TONE_ANGLE.to_radians().sin_cos();
+    vertices.push(Vertex::new(0.0, 0.0, SKIN));
+    vertices.push(Vertex::new(0.5 * c, 0.5 * s, SKIN));
+
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skin_tones_fall_near_the_line() {
+        // light and dark complexions in srgb
+        for rgb in [[0.96, 0.8, 0.69], [0.87, 0.67, 0.55], [0.55, 0.38, 0.26]] {
+            let (cb, cr) = rgb_to_cbcr(rgb, color::luma_weights(PRIMARIES_BT709));
+            let angle = cr.atan2(cb).to_degrees();
+            assert!((angle - SKIN_TONE_ANGLE).abs() < 10.0, "{rgb:?} at {angle}");
+        }
+
+        let line = skin_tone(8);
+        let end = line.last().unwrap().position;
+        assert!(((end[0] * end[0] + end[1] * end[1]).sqrt() - 0.5).abs() < 1e-6);
+        assert_eq!(line.len(), 3 * 2 * 8 + 2);
+    }
+}
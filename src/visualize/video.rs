@@ -0,0 +1,376 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, Result};
+use windows::{
+    core::{GUID, HSTRING, PCWSTR, PROPVARIANT},
+    Win32::{
+        Foundation::{HWND, LPARAM, WPARAM},
+        Media::MediaFoundation::*,
+    },
+};
+
+use crate::{
+    config::Config,
+    gui::{hwnd::Hwnd, menu::WM_VIDEO_POSITION},
+};
+
+use super::still::StillImage;
+
+// media foundation times are in 100ns units
+const TICKS_PER_SECOND: i64 = 10_000_000;
+
+// plays a video file into StillImage one decoded frame at a time, looping at the end
+pub struct VideoPlayer {
+    // the viewer's window handle, told the position for the seek slider
+    viewer: isize,
+    reader: Option<VideoReader>,
+    // the file opened last, a failed one isn't opened again until another is picked
+    path: Option<PathBuf>,
+    seek_request: u32,
+    // the next frame, decoded ahead so it can wait for its time
+    next: Option<(i64, Vec<u8>)>,
+    // when playback resumed and the media time it resumed at
+    clock: Option<(Instant, i64)>,
+    position: i64,
+    // shows one frame while paused, after opening or seeking
+    step: bool,
+    shown: bool,
+    posted: Option<i32>,
+}
+
+impl VideoPlayer {
+    pub fn new(hwnd: HWND) -> Self {
+        Self {
+            viewer: hwnd.0 as isize,
+            reader: None,
+            path: None,
+            seek_request: 0,
+            next: None,
+            clock: None,
+            position: 0,
+            step: false,
+            shown: false,
+            posted: None,
+        }
+    }
+
+    pub fn close(&mut self) {
+        *self = Self {
+            seek_request: self.seek_request,
+            ..Self::new(HWND(self.viewer as _))
+        };
+    }
+
+    // decodes what is due and hands it to the still image, true once a frame is shown
+    pub fn update(&mut self, config: &Config, still: &mut StillImage) -> bool {
+        if config.source_path != self.path {
+            self.close();
+            self.path = config.source_path.clone();
+            self.step = true;
+
+            if let Some(path) = &self.path {
+                match VideoReader::open(path) {
+                    Ok(reader) => self.reader = Some(reader),
+                    Err(e) => println!("{}: {e:?}", path.display()),
+                }
+            }
+        }
+
+        if config.video_seek_request != self.seek_request {
+            self.seek_request = config.video_seek_request;
+            if let Some(reader) = &self.reader {
+                self.position = (config.video_seek as f64 * reader.duration as f64) as i64;
+                self.next = None;
+                self.clock = None;
+                self.step = true;
+            }
+        }
+
+        if let Err(e) = self.advance(config.video_playing, still) {
+            println!("video: {e:?}");
+            self.reader = None;
+        }
+        self.post_position();
+
+        self.reader.is_some() && self.shown
+    }
+
+    // (position, duration) in seconds
+    pub fn time(&self) -> Option<(f32, f32)> {
+        let reader = self.reader.as_ref()?;
+        let seconds = |ticks: i64| (ticks as f64 / TICKS_PER_SECOND as f64) as f32;
+
+        Some((seconds(self.position), seconds(reader.duration)))
+    }
+
+    // in thousandths of the duration like the seek slider, only when it moved a step
+    fn post_position(&mut self) {
+        let Some((position, duration)) = self.time().filter(|_| self.shown) else {
+            return;
+        };
+
+        let permille = if duration > 0.0 {
+            (1000.0 * position / duration).round().clamp(0.0, 1000.0) as i32
+        } else {
+            0
+        };
+        if self.posted != Some(permille) {
+            self.posted = Some(permille);
+            HWND(self.viewer as _).post_message(
+                WM_VIDEO_POSITION,
+                WPARAM(permille as usize),
+                LPARAM(0),
+            );
+        }
+    }
+
+    fn advance(&mut self, playing: bool, still: &mut StillImage) -> Result<()> {
+        let Some(reader) = &mut self.reader else {
+            return Ok(());
+        };
+
+        if !playing {
+            self.clock = None;
+            if !self.step {
+                return Ok(());
+            }
+        }
+
+        if self.next.is_none() {
+            if self.step {
+                reader.seek(self.position)?;
+            }
+            self.next = reader.read()?;
+        }
+
+        // the end loops back to the start
+        if self.next.is_none() {
+            reader.seek(0)?;
+            self.next = reader.read()?;
+            self.clock = None;
+        }
+
+        // frames that are already late are dropped to keep up
+        let now = Instant::now();
+        let mut due = None;
+        while let Some(time) = self.next.as_ref().map(|(time, _)| *time) {
+            let (start, base) = *self.clock.get_or_insert((now, time));
+            let elapsed = now.duration_since(start).as_nanos() as i64 / 100;
+            if time - base > elapsed && !self.step {
+                break;
+            }
+
+            due = self.next.take();
+            self.step = false;
+            self.next = reader.read()?;
+
+            if !playing {
+                break;
+            }
+        }
+
+        if let Some((time, pixels)) = due {
+            self.position = time;
+            still.set_video_frame(reader.width, reader.height, pixels);
+            self.shown = true;
+        }
+
+        Ok(())
+    }
+}
+
+// balances MFStartup, also when opening fails after it
+pub struct MediaFoundation;
+
+impl MediaFoundation {
+    pub fn startup() -> Result<Self> {
+        unsafe { MFStartup(MF_VERSION, MFSTARTUP_LITE)? };
+        Ok(Self)
+    }
+}
+
+impl Drop for MediaFoundation {
+    fn drop(&mut self) {
+        unsafe {
+            _ = MFShutdown();
+        }
+    }
+}
+
+struct VideoReader {
+    reader: IMFSourceReader,
+    width: u32,
+    height: u32,
+    // negative for bottom-up rows
+    stride: i32,
+    duration: i64,
+    // last, so the reader is released before media foundation shuts down
+    _media_foundation: MediaFoundation,
+}
+
+// the source reader is created after the pipeline has moved to the visualizer thread, with com
+// initialized multithreaded there, and is only ever called from that thread
+unsafe impl Send for VideoReader {}
+
+impl VideoReader {
+    // the first video stream, converted to bgra by media foundation's video processing
+    fn open(path: &Path) -> Result<Self> {
+        unsafe {
+            let media_foundation = MediaFoundation::startup()?;
+
+            let mut attributes = None;
+            MFCreateAttributes(&mut attributes, 1)?;
+            let attributes = attributes.context("no attributes")?;
+            attributes.SetUINT32(&MF_SOURCE_READER_ENABLE_VIDEO_PROCESSING, 1)?;
+
+            let url = HSTRING::from(path.as_os_str());
+            let reader = MFCreateSourceReaderFromURL(PCWSTR(url.as_ptr()), &attributes)?;
+
+            reader.SetStreamSelection(MF_SOURCE_READER_ALL_STREAMS.0 as u32, false)?;
+            reader.SetStreamSelection(MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32, true)?;
+
+            let media_type = MFCreateMediaType()?;
+            media_type.SetGUID(&MF_MT_MAJOR_TYPE, &MFMediaType_Video)?;
+            media_type.SetGUID(&MF_MT_SUBTYPE, &MFVideoFormat_RGB32)?;
+            reader.SetCurrentMediaType(
+                MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32,
+                None,
+                &media_type,
+            )?;
+
+            let duration = reader
+                .GetPresentationAttribute(MF_SOURCE_READER_MEDIASOURCE.0 as u32, &MF_PD_DURATION)
+                .ok()
+                .and_then(|duration| u64::try_from(&duration).ok())
+                .unwrap_or(0);
+
+            let mut video = Self {
+                reader,
+                width: 0,
+                height: 0,
+                stride: 0,
+                duration: duration as i64,
+                _media_foundation: media_foundation,
+            };
+            video.read_format()?;
+
+            Ok(video)
+        }
+    }
+
+    fn read_format(&mut self) -> Result<()> {
+        unsafe {
+            let media_type = self
+                .reader
+                .GetCurrentMediaType(MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32)?;
+
+            let size = media_type.GetUINT64(&MF_MT_FRAME_SIZE)?;
+            self.width = (size >> 32) as u32;
+            self.height = size as u32;
+            self.stride = media_type
+                .GetUINT32(&MF_MT_DEFAULT_STRIDE)
+                .map_or(4 * self.width as i32, |stride| stride as i32);
+        }
+
+        Ok(())
+    }
+
+    fn seek(&mut self, time: i64) -> Result<()> {
+        unsafe {
+            self.reader
+                .SetCurrentPosition(&GUID::zeroed(), &PROPVARIANT::from(time))?;
+        }
+
+        Ok(())
+    }
+
+    // the next frame's time and tightly packed bgra rows, None at the end of the stream
+    fn read(&mut self) -> Result<Option<(i64, Vec<u8>)>> {
+        unsafe {
+            let mut flags = 0;
+            let mut time = 0;
+            let mut sample = None::<IMFSample>;
+            self.reader.ReadSample(
+                MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32,
+                0,
+                None,
+                Some(&mut flags as *mut _),
+                Some(&mut time as *mut _),
+                Some(&mut sample as *mut _),
+            )?;
+
+            if flags & MF_SOURCE_READERF_ENDOFSTREAM.0 as u32 != 0 {
+                return Ok(None);
+            }
+            if flags & MF_SOURCE_READERF_CURRENTMEDIATYPECHANGED.0 as u32 != 0 {
+                self.read_format()?;
+            }
+
+            // a gap in the stream, the last frame stays up
+            let Some(sample) = sample else {
+                return self.read();
+            };
+
+            let buffer = sample.ConvertToContiguousBuffer()?;
+            let mut data = std::ptr::null_mut();
+            let mut len = 0;
+            buffer.Lock(&mut data, None, Some(&mut len as *mut _))?;
+            let pixels = packed_rows(
+                std::slice::from_raw_parts(data, len as usize),
+                self.width,
+                self.height,
+                self.stride,
+            );
+            buffer.Unlock()?;
+
+            Ok(Some((time, pixels?)))
+        }
+    }
+}
+
+// top-down rows without padding, a negative stride means the buffer starts at the bottom row
+fn packed_rows(data: &[u8], width: u32, height: u32, stride: i32) -> Result<Vec<u8>> {
+    let row_size = 4 * width as usize;
+    let pitch = stride.unsigned_abs() as usize;
+    anyhow::ensure!(
+        pitch >= row_size && data.len() >= pitch * (height as usize - 1) + row_size,
+        "a {width}x{height} frame doesn't fit {} bytes with a {stride} byte stride",
+        data.len()
+    );
+
+    let mut pixels = Vec::with_capacity(row_size * height as usize);
+    for y in 0..height as usize {
+        let row = if stride < 0 {
+            height as usize - 1 - y
+        } else {
+            y
+        };
+        pixels.extend_from_slice(&data[row * pitch..][..row_size]);
+    }
+
+    Ok(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bottom_up_rows_are_flipped() {
+        // 1x2 with 4 bytes of padding per row
+        let data = [[1u8; 4], [0; 4], [2; 4], [0; 4]].concat();
+
+        assert_eq!(
+            packed_rows(&data, 1, 2, 8).unwrap(),
+            [1, 1, 1, 1, 2, 2, 2, 2]
+        );
+        assert_eq!(
+            packed_rows(&data, 1, 2, -8).unwrap(),
+            [2, 2, 2, 2, 1, 1, 1, 1]
+        );
+        assert!(packed_rows(&data[..8], 1, 2, 8).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use windows::Win32::Graphics::{
+    Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+    Direct3D12::D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+    Dxgi::Common::{DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT},
+};
+
+use crate::{
+    config::Config,
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        renderer::Renderer,
+        resource::RwBuffer,
+    },
+    gui::utils::Rect as _,
+};
+
+// the cloud's counters ray marched as a density volume, one quad over the viewport
+pub struct Volume {
+    pso: PipelineState,
+}
+
+impl Volume {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/VolumeVs.bin"),
+            include_bytes!("../shaders/bin/VolumePs.bin"),
+            BlendDesc::premultiplied(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            Some(DXGI_FORMAT_D16_UNORM),
+            None,
+        )?;
+
+        Ok(Self { pso })
+    }
+
+    pub fn draw(&self, ctx: &mut Renderer, counter: &RwBuffer, config: &Config) -> Result<()> {
+        let Some(inv_projection) = config.projection_matrix().inverse() else {
+            return Ok(());
+        };
+
+        ctx.set_pipeline_state(&self.pso);
+        ctx.set_viewport(config.cloud_viewport());
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+
+        #[repr(C)]
+        struct Params {
+            inv_projection: [f32; 16],
+            color_space: u32,
+            inv_log_total: f32,
+            ramp_low: f32,
+            ramp_high: f32,
+        }
+
+        // same log scale as the isosurface level
+        let (width, height) = config.analysis_rect().size();
+        let total = (width * height).max(1) as f32;
+
+        let params = Params {
+            inv_projection: inv_projection.as_4x4(),
+            color_space: config.color_cloud_mode,
+            inv_log_total: 1.0 / (1.0 + total).log2(),
+            ramp_low: config.color_cloud_ramp[0],
+            ramp_high: config.color_cloud_ramp[1],
+        };
+        ctx.set_graphics_constants(&params);
+        ctx.set_graphics_srvs(&[counter.srv]);
+        ctx.draw(4, 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use windows::{
+    core::s,
+    Win32::{
+        Foundation::RECT,
+        Graphics::{
+            Direct3D::{D3D_PRIMITIVE_TOPOLOGY_LINELIST, D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP},
+            Direct3D12::*,
+            Dxgi::Common::{
+                DXGI_FORMAT_D16_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R32G32B32_FLOAT,
+                DXGI_FORMAT_R32_UINT,
+            },
+        },
+    },
+};
+
+use crate::{
+    config::{Config, LAYOUT_WAVEFORM},
+    graphics::{
+        core::{pso::PipelineState, wrap::*},
+        initializer::Initializer,
+        math::{self, Matrix},
+        renderer::{msaa_index, Renderer, ViewportKind, MSAA_SAMPLES},
+        resource::{RwBuffer, VertexBuffer},
+    },
+    gui::utils::Rect as _,
+};
+
+const NUM_COLUMNS: u32 = 256;
+const NUM_LEVELS: u32 = 256;
+
+pub struct Waveform {
+    compute_pso: PipelineState,
+    draw_pso: PipelineState,
+    // one per msaa sample count
+    graticule_psos: Vec<PipelineState>,
+    bins: RwBuffer,
+    graticule: VertexBuffer,
+}
+
+impl Waveform {
+    pub fn new(ctx: &mut Initializer) -> Result<Self> {
+        let compute_pso =
+            ctx.create_compute_pipeline(include_bytes!("../shaders/bin/WaveformCs.bin"), None)?;
+
+        let draw_pso = ctx.create_graphics_pipeline(
+            include_bytes!("../shaders/bin/WaveformVs.bin"),
+            include_bytes!("../shaders/bin/WaveformPs.bin"),
+            BlendDesc::mul(),
+            RasterizerDesc::none(),
+            DepthStencilDesc::none(),
+            &[],
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+            None,
+            None,
+        )?;
+
+        let graticule_psos = MSAA_SAMPLES
+            .iter()
+            .map(|&samples| {
+                ctx.create_multisampled_pipeline(
+                    samples,
+                    include_bytes!("../shaders/bin/PrimitiveVs.bin"),
+                    include_bytes!("../shaders/bin/PrimitivePs.bin"),
+                    BlendDesc::none(),
+                    RasterizerDesc::lines(samples),
+                    DepthStencilDesc::none(),
+                    &[
+                        InputElementDesc::per_vertex(s!("POSITION"), DXGI_FORMAT_R32G32B32_FLOAT),
+                        InputElementDesc::per_vertex(s!("COLOR"), DXGI_FORMAT_R32G32B32_FLOAT),
+                    ],
+                    D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+                    RtvFormats::single(DXGI_FORMAT_R16G16B16A16_FLOAT),
+                    Some(DXGI_FORMAT_D16_UNORM),
+                    None,
+                )
+            })
+            .collect::<Result<_>>()?;
+
+        let bins = RwBuffer::new(ctx, 3 * NUM_LEVELS * NUM_COLUMNS, DXGI_FORMAT_R32_UINT)?;
+        let graticule = VertexBuffer::new(ctx, &graticule())?;
+
+        Ok(Self {
+            compute_pso,
+            draw_pso,
+            graticule_psos,
+            bins,
+            graticule,
+        })
+    }
+
+    // graphics queue compute, runs next to the async compute passes
+    pub fn accumulate(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_waveform {
+            self.clear(ctx)?;
+            self.compute(config, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn process(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_waveform {
+            self.draw(config, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    // drawn separately so the lines can go through the msaa layer
+    pub fn process_graticule(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        if config.enable_waveform {
+            self.draw_graticule(ctx, config)?;
+        }
+
+        Ok(())
+    }
+
+    // one column per pixel for narrow regions
+    fn columns(config: &Config) -> u32 {
+        (config.analysis_rect().width().max(1) as u32).min(NUM_COLUMNS)
+    }
+
+    fn clear(&mut self, ctx: &mut Renderer) -> Result<()> {
+        ctx.resource_barrier(&[self.bins.transition_barrier(
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        )]);
+
+        ctx.clear_uav(self.bins.raw_uav, &self.bins);
+
+        Ok(())
+    }
+
+    fn compute(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
+        ctx.set_pipeline_state(&self.compute_pso);
+
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            origin: [i32; 2],
+            columns: u32,
+            hdr: u32,
+        }
+        let rect = config.analysis_rect();
+        let params = Params {
+            rect,
+            origin: [config.window_rect.left, config.window_rect.top],
+            columns: Self::columns(config),
+            hdr: config.hdr_mode as u32,
+        };
+        ctx.set_compute_constants(&params);
+        ctx.set_uavs(&[self.bins.uav]);
+
+        let threads = 8;
+        ctx.dispatch(
+            math::div_round_up(rect.width() as u32, threads),
+            math::div_round_up(rect.height() as u32, threads),
+            1,
+        );
+
+        ctx.resource_barrier(&[self.bins.transition_barrier(
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_ALL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+
+    fn draw(&mut self, config: &Config, ctx: &mut Renderer) -> Result<()> {
+        ctx.set_pipeline_state(&self.draw_pso);
+        ctx.set_viewport(config.scope_viewport(LAYOUT_WAVEFORM, ViewportKind::Full));
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+
+        #[repr(C)]
+        struct Params {
+            columns: u32,
+            layout: u32,
+            scale: f32,
+        }
+
+        // a bin holding its column's average share of pixels reaches about 1 - exp(-4 * scale)
+        let (width, height) = config.analysis_rect().size();
+        let pixels_per_bin =
+            (width * height).max(1) as f32 / (NUM_LEVELS * Self::columns(config)) as f32;
+        let params = Params {
+            columns: Self::columns(config),
+            layout: config.waveform_layout,
+            scale: 4.0 * config.waveform_scale / pixels_per_bin,
+        };
+
+        ctx.set_graphics_constants(&params);
+        ctx.set_graphics_srvs(&[self.bins.srv]);
+        ctx.draw(4, 1);
+
+        Ok(())
+    }
+
+    fn draw_graticule(&mut self, ctx: &mut Renderer, config: &Config) -> Result<()> {
+        let viewport = config.scope_viewport(LAYOUT_WAVEFORM, ViewportKind::Full);
+        ctx.set_pipeline_state(&self.graticule_psos[msaa_index(ctx.samples())]);
+        ctx.set_viewport(viewport);
+        ctx.set_primitive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+        ctx.set_vertex_buffers(&[self.graticule.view()]);
+
+        #[repr(C)]
+        struct Params {
+            projection: [f32; 16],
+        }
+
+        // half a pixel in, the 0 and 100% lines would otherwise fall on the viewport's edges
+        let (width, height) = config.window_rect.usize();
+        let (_, viewport_height) = viewport.size(width, height);
+        let params = Params {
+            projection: Matrix::scale(1.0, 1.0 - 1.0 / viewport_height.max(1.0), 1.0)
+                .mul(&Matrix::translate(0.0, 0.0, 0.5))
+                .as_4x4(),
+        };
+
+        ctx.set_graphics_constants(&params);
+        ctx.draw(self.graticule.vertex_count(), 1);
+
+        Ok(())
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+// lines at 0, 25, 50, 75 and 100% across the scope
+fn graticule() -> Vec<Vertex> {
+    const GRAY: [f32; 3] = [0.3, 0.3, 0.3];
+
+    (0..=4)
+        .flat_map(|i| {
+            let y = 0.5 * i as f32 - 1.0;
+            [-1.0, 1.0].map(|x| Vertex {
+                position: [x, y, 0.0],
+                color: GRAY,
+            })
+        })
+        .collect()
+}